@@ -24,9 +24,9 @@ impl TabComplete for ArgumentSpec {
                 }
             },
             ArgumentSpec::Any(_, _) => vec![],
-            ArgumentSpec::Command(_) => complete_command(part),
+            ArgumentSpec::Command(_) => complete_command(part, &client),
             ArgumentSpec::Message(_) => complete_player(part, &client),
-            ArgumentSpec::SubCommand => complete_command(part),
+            ArgumentSpec::SubCommand => complete_command(part, &client),
             ArgumentSpec::Enum(_, strings, _) => strings
                 .iter()
                 .filter(|string| string.starts_with(part))
@@ -37,8 +37,25 @@ impl TabComplete for ArgumentSpec {
                 .filter(|string| string.starts_with(part))
                 .map(|c| c.to_string())
                 .collect(),
+            ArgumentSpec::ItemSpec(_) => ITEM_SPECS
+                .iter()
+                .filter(|spec| spec.starts_with(part))
+                .cloned()
+                .collect(),
+            ArgumentSpec::Position(_, _) => vec![],
+        }
+    }
+}
+
+/// The argument that the nth word after the command belongs to
+fn nth_arg(args: &[ArgumentSpec], mut n: usize) -> Option<&ArgumentSpec> {
+    for arg in args {
+        match arg.word_count() {
+            Some(count) if n >= count => n -= count,
+            _ => return Some(arg),
         }
     }
+    None
 }
 
 fn complete_player(part: &str, client: &Client) -> Vec<String> {
@@ -51,11 +68,18 @@ fn complete_player(part: &str, client: &Client) -> Vec<String> {
         .collect()
 }
 
-fn complete_command(part: &str) -> Vec<String> {
+/// Completes the commands the player is allowed to use
+fn complete_command(part: &str, client: &Client) -> Vec<String> {
+    let is_admin = client.is_admin();
     CHAT_SHORTCUTS
         .keys()
         .map(ToString::to_string)
-        .chain(CHAT_COMMANDS.iter().map(ToString::to_string))
+        .chain(
+            CHAT_COMMANDS
+                .iter()
+                .filter(|cmd| is_admin || !cmd.needs_admin())
+                .map(ToString::to_string),
+        )
         .filter(|kwd| kwd.starts_with(part) || format!("/{}", kwd).starts_with(part))
         .map(|c| format!("/{}", c))
         .collect()
@@ -96,16 +120,22 @@ pub fn complete(line: &str, client: &Client) -> Vec<String> {
         let i = iter.count() + if word.is_empty() { 1 } else { 0 };
         if i == 0 {
             // Completing chat command name
-            complete_command(word)
+            complete_command(word, &client)
         } else if let Ok(cmd) = cmd.parse::<ChatCommand>() {
-            if let Some(arg) = cmd.data().args.get(i - 1) {
+            if let Some(arg) = nth_arg(&cmd.data().args, i - 1) {
                 // Complete ith argument
                 arg.complete(word, &client)
             } else {
                 // Complete past the last argument
                 match cmd.data().args.last() {
                     Some(ArgumentSpec::SubCommand) => {
-                        if let Some(index) = nth_word(line, cmd.data().args.len()) {
+                        let words = cmd
+                            .data()
+                            .args
+                            .iter()
+                            .filter_map(ArgumentSpec::word_count)
+                            .sum::<usize>();
+                        if let Some(index) = nth_word(line, words + 1) {
                             complete(&line[index..], &client)
                         } else {
                             vec![]
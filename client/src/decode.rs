@@ -0,0 +1,113 @@
+//! Decoding of server messages off the main thread
+//!
+//! Decompressing and deserializing big messages, like terrain chunks and the
+//! sync packages of crowded areas, takes long enough to cause frame spikes.
+//! Such messages are decoded on the thread pool instead, and handed back in
+//! the order they were received, since later messages of a stream may depend
+//! on earlier ones (e.g. block updates on the chunk they apply to).
+
+use std::{collections::BTreeMap, sync::mpsc};
+use uvth::ThreadPool;
+
+/// Messages smaller than this are decoded right away, unless bigger messages
+/// received before them are still being decoded
+pub const INLINE_DECODE_LEN: usize = 4096;
+
+/// Messages of one stream that are being decoded on the thread pool
+pub struct DecodeQueue<T> {
+    tx: mpsc::Sender<(u64, T)>,
+    rx: mpsc::Receiver<(u64, T)>,
+    /// Decoded messages waiting for the ones received before them
+    decoded: BTreeMap<u64, T>,
+    received: u64,
+    handled: u64,
+}
+
+impl<T: Send + 'static> Default for DecodeQueue<T> {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            tx,
+            rx,
+            decoded: BTreeMap::new(),
+            received: 0,
+            handled: 0,
+        }
+    }
+}
+
+impl<T: Send + 'static> DecodeQueue<T> {
+    /// Decode a message of `size` bytes. Small messages that nothing is
+    /// ahead of are decoded right away and returned, the others are decoded
+    /// on the thread pool and handed out by [`DecodeQueue::pop`].
+    pub fn push(
+        &mut self,
+        thread_pool: &ThreadPool,
+        size: usize,
+        decode: impl FnOnce() -> T + Send + 'static,
+    ) -> Option<T> {
+        let index = self.received;
+        self.received += 1;
+        if size < INLINE_DECODE_LEN && index == self.handled {
+            self.handled += 1;
+            return Some(decode());
+        }
+        let tx = self.tx.clone();
+        thread_pool.execute(move || {
+            let _ = tx.send((index, decode()));
+        });
+        None
+    }
+
+    /// The next decoded message, if all messages received before it have been
+    /// handed out
+    pub fn pop(&mut self) -> Option<T> {
+        self.decoded.extend(self.rx.try_iter());
+        let msg = self.decoded.remove(&self.handled)?;
+        self.handled += 1;
+        Some(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{thread, time::Duration};
+    use uvth::ThreadPoolBuilder;
+
+    fn pop_all(queue: &mut DecodeQueue<u32>, count: usize) -> Vec<u32> {
+        let mut popped = Vec::new();
+        while popped.len() < count {
+            match queue.pop() {
+                Some(msg) => popped.push(msg),
+                None => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+        popped
+    }
+
+    #[test]
+    fn messages_keep_their_order() {
+        let pool = ThreadPoolBuilder::new().num_threads(2).build();
+        let mut queue = DecodeQueue::default();
+
+        // Nothing is ahead of a small message
+        assert_eq!(queue.push(&pool, 10, || 1), Some(1));
+
+        // A small message has to wait for the big one before it
+        assert_eq!(
+            queue.push(&pool, INLINE_DECODE_LEN, || {
+                thread::sleep(Duration::from_millis(20));
+                2
+            }),
+            None
+        );
+        assert_eq!(queue.push(&pool, 10, || 3), None);
+        assert_eq!(queue.push(&pool, INLINE_DECODE_LEN, || 4), None);
+        assert_eq!(pop_all(&mut queue, 3), vec![2, 3, 4]);
+        assert_eq!(queue.pop(), None);
+
+        // Once everything is handed out, small messages skip the pool again
+        assert_eq!(queue.push(&pool, 10, || 5), Some(5));
+    }
+}
@@ -13,6 +13,8 @@ pub enum Error {
     TooManyPlayers,
     NotOnWhitelist,
     AlreadyLoggedIn,
+    /// The account is already logged in and its player is in combat
+    AlreadyLoggedInAndInCombat,
     AuthErr(String),
     AuthClientError(AuthClientError),
     AuthServerNotTrusted,
@@ -3,10 +3,15 @@
 #![feature(label_break_value, option_zip)]
 
 pub mod cmd;
+mod decode;
 pub mod error;
+pub mod net_sim;
+pub mod net_stats;
+mod prediction;
+pub mod terrain_query;
 
 // Reexports
-pub use crate::error::Error;
+pub use crate::{error::Error, terrain_query::TerrainHit};
 pub use authc::AuthClientError;
 pub use specs::{
     join::Join,
@@ -14,41 +19,55 @@ pub use specs::{
     Builder, DispatcherBuilder, Entity as EcsEntity, ReadStorage, WorldExt,
 };
 
+use crate::{
+    decode::DecodeQueue,
+    net_sim::{NetConditions, SimulatedLink},
+    net_stats::{MsgCategory, NetStats, NetworkStats, StreamKind},
+    prediction::{Correction, PredictedInput, Prediction},
+};
 use byteorder::{ByteOrder, LittleEndian};
 use common::{
-    character::{CharacterId, CharacterItem},
+    character::{AccountUnlocks, CharacterId, CharacterItem},
+    combat::CombatEvent,
     comp::{
         self,
         chat::{KillSource, KillType},
         group, ControlAction, ControlEvent, Controller, ControllerInputs, GroupManip,
         InventoryManip, InventoryUpdateEvent,
     },
+    difficulty,
     event::{EventBus, LocalEvent},
+    memory::{MemoryReport, MemoryUsage},
     msg::{
-        validate_chat_msg, ChatMsgValidationError, ClientGeneral, ClientInGame, ClientMsg,
-        ClientRegister, ClientType, DisconnectReason, InviteAnswer, Notification, PingMsg,
-        PlayerInfo, PlayerListUpdate, RegisterError, ServerGeneral, ServerInfo, ServerInit,
-        ServerRegisterAnswer, MAX_BYTES_CHAT_MSG,
+        validate_chat_msg, world_msg::MAX_OVERVIEW_TILES_PER_REQUEST, Capability,
+        ChatMsgValidationError, ClientGeneral, ClientInGame, ClientMsg, ClientRegister, ClientType,
+        DisconnectReason, InviteAnswer, NamedLocation, Notification, OverviewCache, PingMsg,
+        PlayerInfo, PlayerListUpdate, RegionOverview, RegisterError, ServerGeneral, ServerInfo,
+        ServerInit, ServerRegisterAnswer, VoiceFrame, VoiceRelay, WorldOverview,
+        MAX_BYTES_CHAT_MSG, MAX_BYTES_UI_PREFERENCES, MAX_BYTES_VOICE_FRAME,
     },
     outcome::Outcome,
     recipe::RecipeBook,
-    state::State,
+    state::{State, TimeOfDay},
+    states::utils::move_accel,
     sync::{Uid, UidAllocator, WorldSyncExt},
-    terrain::{block::Block, neighbors, TerrainChunk, TerrainChunkSize},
-    vol::RectVolSize,
+    sys::phys::replay_movement,
+    terrain::{block::Block, neighbors, ChunkEncoding, TerrainChunk, TerrainChunkSize},
+    vol::{ReadVol, RectVolSize},
+    weather::WeatherCell,
 };
 use futures_executor::block_on;
 use futures_timer::Delay;
 use futures_util::{select, FutureExt};
 use hashbrown::{HashMap, HashSet};
 use image::DynamicImage;
-use network::{Network, Participant, Pid, ProtocolAddr, Stream};
+use network::{Message, Network, Participant, Pid, ProtocolAddr, Stream, StreamError};
 use num::traits::FloatConst;
 use rayon::prelude::*;
 use std::{
     collections::VecDeque,
     net::SocketAddr,
-    sync::Arc,
+    sync::{mpsc, Arc},
     time::{Duration, Instant},
 };
 use tracing::{debug, error, trace, warn};
@@ -56,6 +75,22 @@ use uvth::{ThreadPool, ThreadPoolBuilder};
 use vek::*;
 
 const PING_ROLLING_AVERAGE_SECS: usize = 10;
+// Number of messages queued for the server after which physics updates are no
+// longer sent, since each one supersedes the previous
+const PHYSICS_DROP_THRESHOLD: u64 = 64;
+// Differences to the server's time of day (in in-game seconds) above which we
+// jump to the server's time instead of gradually catching up
+const TIME_OF_DAY_SNAP_THRESHOLD: f64 = 600.0;
+// Real time over which differences to the server's time of day are smoothed out
+const TIME_OF_DAY_CORRECTION_TIME: f64 = 2.0;
+// Number of received voice frames kept for the frontend, older ones are dropped
+const MAX_PENDING_VOICE_FRAMES: usize = 64;
+// Number of received combat events kept for the frontend, older ones are
+// dropped
+const MAX_PENDING_COMBAT_EVENTS: usize = 256;
+// Number of chunks beyond the loaded terrain whose collision data is kept for
+// physics prediction after their full data is unloaded
+const COLLISION_DISTANCE_MARGIN: u32 = 4;
 
 pub enum Event {
     Chat(comp::ChatMsg),
@@ -66,6 +101,55 @@ pub enum Event {
     Notification(Notification),
     SetViewDistance(u32),
     Outcome(Outcome),
+    /// A sound near the player, sent by the server or made by the player's
+    /// own character
+    Sound(comp::Sound),
+    /// The player's character died
+    Death {
+        killer: Option<Uid>,
+        cause: KillSource,
+    },
+    /// The player's character was revived after dying
+    Respawned,
+    /// The player's character gained experience
+    ExpGain {
+        exp: i64,
+        source: comp::ExpSource,
+    },
+    /// The player's character reached a new level
+    LevelUp(u32),
+    /// The session expired and signing in with the auth server again failed,
+    /// so the player will be disconnected soon
+    SessionRefreshFailed(String),
+    /// UI preferences the server stored for the character, `None` if there
+    /// aren't any yet
+    UiPreferences(Option<Vec<u8>>),
+}
+
+/// What the player signed in to the auth server with, kept to sign in again
+/// once the session expires. The auth server doesn't hand out refresh tokens,
+/// so this is only kept when the frontend opts in with
+/// [`Client::with_session_refresh`]. The password is zeroed when dropped.
+#[derive(Clone)]
+struct AuthCredentials {
+    addr: String,
+    username: String,
+    password: String,
+}
+
+impl Drop for AuthCredentials {
+    fn drop(&mut self) { zero_string(&mut self.password); }
+}
+
+/// Overwrite a string holding a secret, so that it doesn't linger in memory
+/// after it is freed
+fn zero_string(s: &mut String) {
+    // SAFETY: a string of zero bytes is still valid UTF-8
+    for byte in unsafe { s.as_bytes_mut() } {
+        // SAFETY: `byte` is a valid, aligned reference. The volatile write keeps the
+        // compiler from optimizing away a store to memory that is about to be freed.
+        unsafe { std::ptr::write_volatile(byte, 0) };
+    }
 }
 
 pub struct Client {
@@ -95,11 +179,24 @@ pub struct Client {
     /// chunk (i.e. the sea level) in its x coordinate, and the maximum land
     /// height above this height (i.e. the max height) in its y coordinate.
     pub world_map: (Arc<DynamicImage>, Vec2<u16>, Vec2<f32>),
+    /// Biomes and altitudes of the regions of the world, requested from the
+    /// server a few tiles at a time
+    world_overview: OverviewCache,
+    /// Places in the world named by the server's admins
+    named_locations: Vec<NamedLocation>,
     pub player_list: HashMap<Uid, PlayerInfo>,
     pub character_list: CharacterList,
     pub active_character_id: Option<CharacterId>,
+    /// Rules of the server that have to be accepted before playing
+    server_rules: Option<String>,
     recipe_book: RecipeBook,
     available_recipes: HashSet<String>,
+    /// Where the server measures the danger of regions from, if it scales NPC
+    /// levels with it
+    danger_origin: Option<Vec2<f32>>,
+    /// Whether the server told us that the character died and hasn't respawned
+    /// since
+    is_dead: bool,
 
     max_group_size: u32,
     // Client has received an invite (inviter uid, time out instant)
@@ -117,6 +214,31 @@ pub struct Client {
     register_stream: Stream,
     character_screen_stream: Stream,
     in_game_stream: Stream,
+    terrain_stream: Stream,
+    // Only opened if the server supports voice chat
+    voice_stream: Option<Stream>,
+    voice_seq: u32,
+    // Frames of other players that are yet to be taken by the frontend
+    voice_frames: VecDeque<VoiceRelay>,
+    // Damage and kills that are yet to be taken by the frontend
+    combat_events: VecDeque<CombatEvent>,
+    net_stats: NetStats,
+    // Messages held back to simulate bad network conditions
+    net_conditions: NetConditions,
+    delayed_outgoing: SimulatedLink<Message>,
+    delayed_incoming: SimulatedLink<Message>,
+
+    // Big in game and terrain messages are decompressed and deserialized on the
+    // thread pool, and handled in the order they were received once decoded
+    in_game_decoding: DecodeQueue<(usize, Result<ServerGeneral, StreamError>)>,
+    terrain_decoding: DecodeQueue<Result<ServerGeneral, StreamError>>,
+
+    // Signing in with the auth server again happens on the thread pool, the
+    // new token is sent to the server once it arrives
+    session_refresh: bool,
+    auth_credentials: Option<AuthCredentials>,
+    session_token_tx: mpsc::Sender<Result<String, String>>,
+    session_token_rx: mpsc::Receiver<Result<String, String>>,
 
     client_timeout: Duration,
     last_server_ping: f64,
@@ -127,6 +249,10 @@ pub struct Client {
     tick: u64,
     state: State,
     entity: EcsEntity,
+    prediction: Prediction,
+    // Difference between the server's and our time of day that is yet to be
+    // applied, spread over several ticks so that lighting doesn't jump
+    time_of_day_correction: f64,
 
     view_distance: Option<u32>,
     // TODO: move into voxygen
@@ -140,6 +266,8 @@ pub struct Client {
 #[derive(Default)]
 pub struct CharacterList {
     pub characters: Vec<CharacterItem>,
+    /// What the account unlocked, such as extra character slots
+    pub unlocks: AccountUnlocks,
     pub loading: bool,
     pub error: Option<String>,
 }
@@ -162,6 +290,7 @@ impl Client {
         let mut register_stream = block_on(participant.opened())?;
         let character_screen_stream = block_on(participant.opened())?;
         let in_game_stream = block_on(participant.opened())?;
+        let terrain_stream = block_on(participant.opened())?;
 
         register_stream.send(ClientType::Game)?;
         let server_info: ServerInfo = block_on(register_stream.recv())?;
@@ -177,6 +306,16 @@ impl Client {
             );
         }
         debug!("Auth Server: {:?}", server_info.auth_provider);
+        debug!("Server capabilities: {:?}", server_info.capabilities);
+        let voice_stream = if server_info
+            .capabilities
+            .iter()
+            .any(|name| name == Capability::Voice.name())
+        {
+            Some(block_on(participant.opened())?)
+        } else {
+            None
+        };
 
         ping_stream.send(PingMsg::Ping)?;
 
@@ -188,17 +327,22 @@ impl Client {
             lod_alt,
             lod_horizon,
             world_map,
+            world_overview,
             recipe_book,
             max_group_size,
             client_timeout,
+            danger_origin,
         ) = match block_on(register_stream.recv())? {
             ServerInit::GameSync {
                 entity_package,
                 time_of_day,
+                day_cycle_factor,
+                weather,
                 max_group_size,
                 client_timeout,
                 world_map,
                 recipe_book,
+                danger_origin,
             } => {
                 // Initialize `State`
                 let mut state = State::default();
@@ -206,9 +350,16 @@ impl Client {
                 state
                     .ecs_mut()
                     .register::<comp::Last<comp::CharacterState>>();
+                // Physics prediction falls back to collision data for terrain
+                state
+                    .ecs_mut()
+                    .write_resource::<common::terrain::CollisionMap>()
+                    .enable();
 
                 let entity = state.ecs_mut().apply_entity_package(entity_package);
                 *state.ecs_mut().write_resource() = time_of_day;
+                *state.ecs_mut().write_resource() = day_cycle_factor;
+                *state.ecs_mut().write_resource() = weather;
 
                 let map_size_lg = common::terrain::MapSizeLg::new(world_map.dimensions_lg)
                     .map_err(|_| {
@@ -218,6 +369,7 @@ impl Client {
                         ))
                     })?;
                 let map_size = map_size_lg.chunks();
+                let world_overview = OverviewCache::new(world_map.dimensions_lg);
                 let max_height = world_map.max_height;
                 let sea_level = world_map.sea_level;
                 let rgba = world_map.rgba;
@@ -356,9 +508,11 @@ impl Client {
                     lod_alt,
                     lod_horizon,
                     (world_map, map_size, map_bounds),
+                    world_overview,
                     recipe_book,
                     max_group_size,
                     client_timeout,
+                    danger_origin,
                 ))
             },
             ServerInit::TooManyPlayers => Err(Error::TooManyPlayers),
@@ -371,6 +525,8 @@ impl Client {
         // We reduce the thread count by 1 to keep rendering smooth
         thread_pool.set_num_threads((num_cpus::get() - 1).max(1));
 
+        let (session_token_tx, session_token_rx) = mpsc::channel();
+
         debug!("Initial sync done");
 
         Ok(Self {
@@ -382,12 +538,17 @@ impl Client {
             lod_base,
             lod_alt,
             lod_horizon,
+            world_overview,
+            named_locations: Vec::new(),
             player_list: HashMap::new(),
             character_list: CharacterList::default(),
             active_character_id: None,
+            server_rules: None,
             recipe_book,
             available_recipes: HashSet::default(),
+            danger_origin,
 
+            is_dead: false,
             max_group_size,
             group_invite: None,
             group_leader: None,
@@ -401,6 +562,23 @@ impl Client {
             register_stream,
             character_screen_stream,
             in_game_stream,
+            terrain_stream,
+            voice_stream,
+            voice_seq: 0,
+            voice_frames: VecDeque::new(),
+            combat_events: VecDeque::new(),
+            net_stats: NetStats::default(),
+            net_conditions: NetConditions::default(),
+            delayed_outgoing: SimulatedLink::default(),
+            delayed_incoming: SimulatedLink::default(),
+
+            in_game_decoding: DecodeQueue::default(),
+            terrain_decoding: DecodeQueue::default(),
+
+            session_refresh: false,
+            auth_credentials: None,
+            session_token_tx,
+            session_token_rx,
 
             client_timeout,
 
@@ -412,6 +590,8 @@ impl Client {
             tick: 0,
             state,
             entity,
+            prediction: Prediction::default(),
+            time_of_day_correction: 0.0,
             view_distance,
             loaded_distance: 0.0,
 
@@ -424,11 +604,20 @@ impl Client {
         self
     }
 
+    /// Keep the auth server password in memory while connected, to sign in
+    /// again in the background when the session expires. Without it the
+    /// password is discarded right after registering, and the player is
+    /// disconnected once the session runs out.
+    pub fn with_session_refresh(mut self, session_refresh: bool) -> Self {
+        self.session_refresh = session_refresh;
+        self
+    }
+
     /// Request a state transition to `ClientState::Registered`.
     pub fn register(
         &mut self,
         username: String,
-        password: String,
+        mut password: String,
         mut auth_trusted: impl FnMut(&str) -> bool,
     ) -> Result<(), Error> {
         // Authentication
@@ -441,12 +630,27 @@ impl Client {
                 } else {
                     Err(Error::AuthServerNotTrusted)
                 }
-        ).unwrap_or(Ok(username))?;
+        ).unwrap_or_else(|| Ok(username.clone()))?;
+        match self.server_info.auth_provider.clone() {
+            Some(addr) if self.session_refresh => {
+                self.auth_credentials = Some(AuthCredentials {
+                    addr,
+                    username,
+                    password,
+                });
+            },
+            _ => zero_string(&mut password),
+        }
 
-        self.send_msg_err(ClientRegister { token_or_username })?;
+        self.send_msg_err(ClientRegister {
+            token_or_username,
+            chunk_encoding: ChunkEncoding::LATEST,
+            capabilities: Capability::supported(),
+        })?;
 
         match block_on(self.register_stream.recv::<ServerRegisterAnswer>())? {
             Err(RegisterError::AlreadyLoggedIn) => Err(Error::AlreadyLoggedIn),
+            Err(RegisterError::InCombat) => Err(Error::AlreadyLoggedInAndInCombat),
             Err(RegisterError::AuthError(err)) => Err(Error::AuthErr(err)),
             Err(RegisterError::InvalidCharacter) => Err(Error::InvalidCharacter),
             Err(RegisterError::NotOnWhitelist) => Err(Error::NotOnWhitelist),
@@ -475,16 +679,27 @@ impl Client {
                 )
             );
         }
-        match msg {
-            ClientMsg::Type(msg) => self.register_stream.send(msg),
-            ClientMsg::Register(msg) => self.register_stream.send(msg),
+        let category = match &msg {
+            ClientMsg::General(msg) => MsgCategory::of_client_msg(msg),
+            _ => MsgCategory::Other,
+        };
+        let (kind, msg) = match msg {
+            ClientMsg::Type(msg) => (
+                StreamKind::Register,
+                Message::serialize(&msg, &self.register_stream),
+            ),
+            ClientMsg::Register(msg) => (
+                StreamKind::Register,
+                Message::serialize(&msg, &self.register_stream),
+            ),
             ClientMsg::General(msg) => {
-                let stream = match msg {
+                let kind = match msg {
                     ClientGeneral::RequestCharacterList
                     | ClientGeneral::CreateCharacter { .. }
                     | ClientGeneral::DeleteCharacter(_)
                     | ClientGeneral::Character(_)
-                    | ClientGeneral::Spectate => &mut self.character_screen_stream,
+                    | ClientGeneral::Spectate
+                    | ClientGeneral::AcceptRules => StreamKind::CharacterScreen,
                     //Only in game
                     ClientGeneral::ControllerInputs(_)
                     | ClientGeneral::ControlEvent(_)
@@ -492,20 +707,68 @@ impl Client {
                     | ClientGeneral::SetViewDistance(_)
                     | ClientGeneral::BreakBlock(_)
                     | ClientGeneral::PlaceBlock(_, _)
+                    | ClientGeneral::EditSign { .. }
                     | ClientGeneral::ExitInGame
                     | ClientGeneral::PlayerPhysics { .. }
                     | ClientGeneral::TerrainChunkRequest { .. }
                     | ClientGeneral::UnlockSkill(_)
                     | ClientGeneral::RefundSkill(_)
-                    | ClientGeneral::UnlockSkillGroup(_) => &mut self.in_game_stream,
+                    | ClientGeneral::UnlockSkillGroup(_)
+                    | ClientGeneral::SetAbilitySlot { .. }
+                    | ClientGeneral::RequestUiPreferences
+                    | ClientGeneral::SetUiPreferences(_) => StreamKind::InGame,
                     //Always possible
-                    ClientGeneral::ChatMsg(_)
+                    ClientGeneral::RequestWorldMap(_)
+                    | ClientGeneral::ChatMsg(_)
+                    | ClientGeneral::RefreshSession(_)
                     | ClientGeneral::Disconnect
-                    | ClientGeneral::Terminate => &mut self.general_stream,
+                    | ClientGeneral::Terminate => StreamKind::General,
                 };
-                stream.send(msg)
+                (kind, Message::serialize(&msg, self.stream(kind)))
             },
-            ClientMsg::Ping(msg) => self.ping_stream.send(msg),
+            ClientMsg::Ping(msg) => (
+                StreamKind::Ping,
+                Message::serialize(&msg, &self.ping_stream),
+            ),
+        };
+        self.send_raw(kind, category, msg)
+    }
+
+    fn stream(&mut self, kind: StreamKind) -> &mut Stream {
+        match kind {
+            StreamKind::Register => &mut self.register_stream,
+            StreamKind::General => &mut self.general_stream,
+            StreamKind::Ping => &mut self.ping_stream,
+            StreamKind::CharacterScreen => &mut self.character_screen_stream,
+            StreamKind::InGame => &mut self.in_game_stream,
+            StreamKind::Terrain => &mut self.terrain_stream,
+            StreamKind::Voice => self
+                .voice_stream
+                .as_mut()
+                .expect("voice frames are only sent if the server opened the voice stream"),
+        }
+    }
+
+    /// Send a serialized message, or hold it back if bad network conditions
+    /// are simulated
+    fn send_raw(
+        &mut self,
+        kind: StreamKind,
+        category: MsgCategory,
+        msg: Message,
+    ) -> Result<(), network::StreamError> {
+        self.net_stats.record_sent(kind, category, msg.size());
+        if self.net_conditions.is_perfect() && self.delayed_outgoing.is_empty() {
+            self.stream(kind).send_raw(&msg)
+        } else {
+            self.delayed_outgoing.push(
+                Instant::now(),
+                kind,
+                msg,
+                &self.net_conditions,
+                &mut rand::thread_rng(),
+            );
+            Ok(())
         }
     }
 
@@ -532,6 +795,17 @@ impl Client {
         self.active_character_id = Some(character_id);
     }
 
+    /// Rules of the server that have yet to be accepted
+    pub fn server_rules(&self) -> Option<&str> { self.server_rules.as_deref() }
+
+    pub fn named_locations(&self) -> &[NamedLocation] { &self.named_locations }
+
+    pub fn accept_rules(&mut self) {
+        if self.server_rules.take().is_some() {
+            self.send_msg(ClientGeneral::AcceptRules);
+        }
+    }
+
     /// Load the current players character list
     pub fn load_character_list(&mut self) {
         self.character_list.loading = true;
@@ -577,6 +851,49 @@ impl Client {
         )));
     }
 
+    pub fn sort_inventory(&mut self) {
+        self.send_msg(ClientGeneral::ControlEvent(ControlEvent::InventoryManip(
+            InventoryManip::Sort,
+        )));
+    }
+
+    pub fn merge_stacks(&mut self, from: usize, to: usize) {
+        self.send_msg(ClientGeneral::ControlEvent(ControlEvent::InventoryManip(
+            InventoryManip::MergeStacks(from, to),
+        )));
+    }
+
+    pub fn split_stack(&mut self, slot: usize, amount: u32) {
+        self.send_msg(ClientGeneral::ControlEvent(ControlEvent::InventoryManip(
+            InventoryManip::SplitStack(slot, amount),
+        )));
+    }
+
+    /// Brew the ingredients in the given inventory slots into potions
+    pub fn brew(&mut self, slots: Vec<usize>) {
+        self.send_msg(ClientGeneral::ControlEvent(ControlEvent::InventoryManip(
+            InventoryManip::Brew(slots),
+        )));
+    }
+
+    /// Dye the item in a slot with the dye in the given inventory slot
+    pub fn dye_item(&mut self, dye_slot: usize, target: comp::slot::Slot) {
+        self.send_msg(ClientGeneral::ControlEvent(ControlEvent::InventoryManip(
+            InventoryManip::Dye(dye_slot, target),
+        )));
+    }
+
+    /// Change what others see in an armor slot
+    pub fn set_appearance(
+        &mut self,
+        armor_slot: comp::slot::ArmorSlot,
+        choice: comp::slot::AppearanceChoice,
+    ) {
+        self.send_msg(ClientGeneral::ControlEvent(ControlEvent::InventoryManip(
+            InventoryManip::SetAppearance(armor_slot, choice),
+        )));
+    }
+
     pub fn drop_slot(&mut self, slot: comp::slot::Slot) {
         self.send_msg(ClientGeneral::ControlEvent(ControlEvent::InventoryManip(
             InventoryManip::Drop(slot),
@@ -633,6 +950,19 @@ impl Client {
 
     pub fn max_group_size(&self) -> u32 { self.max_group_size }
 
+    /// Danger rating of the region at `pos`, or `None` if the server doesn't
+    /// scale NPC levels with the distance from spawn
+    pub fn region_danger(&self, pos: Vec2<f32>) -> Option<u32> {
+        self.danger_origin
+            .map(|origin| difficulty::region_danger(origin, pos))
+    }
+
+    /// Biome and altitude of the region at `pos`, once the server sent them
+    pub fn region_overview(&self, pos: Vec2<f32>) -> Option<RegionOverview> {
+        let key = self.state.terrain().pos_key(pos.map(|e| e as i32).into());
+        self.world_overview.get(WorldOverview::region_of(key))
+    }
+
     pub fn group_invite(&self) -> Option<(Uid, std::time::Instant, std::time::Duration)> {
         self.group_invite
     }
@@ -710,21 +1040,97 @@ impl Client {
     pub fn unmount(&mut self) { self.send_msg(ClientGeneral::ControlEvent(ControlEvent::Unmount)); }
 
     pub fn respawn(&mut self) {
-        if self
-            .state
-            .ecs()
-            .read_storage::<comp::Stats>()
-            .get(self.entity)
-            .map_or(false, |s| s.is_dead)
-        {
+        if self.is_dead {
             self.send_msg(ClientGeneral::ControlEvent(ControlEvent::Respawn));
         }
     }
 
+    /// Whether the character died and is waiting to respawn
+    pub fn is_dead(&self) -> bool { self.is_dead }
+
+    /// Rest at the campfire the player is at, so that they respawn there
+    pub fn set_waypoint(&mut self) {
+        self.send_msg(ClientGeneral::ControlEvent(ControlEvent::SetWaypoint));
+    }
+
     /// Checks whether a player can swap their weapon+ability `Loadout` settings
     /// and sends the `ControlAction` event that signals to do the swap.
     pub fn swap_loadout(&mut self) { self.control_action(ControlAction::SwapLoadout) }
 
+    /// Use the ability in the given slot of the ability hotbar
+    pub fn use_ability_slot(&mut self, slot: usize) {
+        self.control_action(ControlAction::UseAbilitySlot(slot))
+    }
+
+    /// Put an ability of the active item in a slot of the ability hotbar, or
+    /// give the slot back to the skill at its position with `None`
+    pub fn set_ability_slot(&mut self, slot: usize, ability: Option<comp::AbilityId>) {
+        self.send_msg(ClientGeneral::SetAbilitySlot { slot, ability });
+    }
+
+    /// Ask the server for the UI preferences of the current character, which
+    /// arrive as [`Event::UiPreferences`]. Does nothing if the server doesn't
+    /// store them.
+    pub fn request_ui_preferences(&mut self) {
+        if self.server_supports(Capability::UiPreferences) {
+            self.send_msg(ClientGeneral::RequestUiPreferences);
+        }
+    }
+
+    /// Store UI preferences of the current character on the server, replacing
+    /// the ones stored before. Their format is up to the frontend.
+    pub fn set_ui_preferences(&mut self, data: Vec<u8>) {
+        if !self.server_supports(Capability::UiPreferences) {
+            return;
+        }
+        if data.len() > MAX_BYTES_UI_PREFERENCES {
+            tracing::warn!(
+                "Attempted to store UI preferences that are too large (Over {} bytes)",
+                MAX_BYTES_UI_PREFERENCES
+            );
+            return;
+        }
+        self.send_msg(ClientGeneral::SetUiPreferences(data));
+    }
+
+    /// Whether the server supports voice chat
+    pub fn voice_chat_available(&self) -> bool { self.voice_stream.is_some() }
+
+    /// Send a chunk of opus-encoded audio to the players near us. Does nothing
+    /// if the server doesn't support voice chat or we aren't in game.
+    pub fn send_voice_frame(&mut self, data: Vec<u8>) {
+        if self.voice_stream.is_none() || self.in_game.is_none() {
+            return;
+        }
+        if data.len() > MAX_BYTES_VOICE_FRAME {
+            warn!(
+                "Attempted to send a voice frame that's too large (Over {} bytes)",
+                MAX_BYTES_VOICE_FRAME
+            );
+            return;
+        }
+        let frame = VoiceFrame {
+            seq: self.voice_seq,
+            data,
+        };
+        self.voice_seq = self.voice_seq.wrapping_add(1);
+        let msg = Message::serialize(&frame, self.stream(StreamKind::Voice));
+        if let Err(e) = self.send_raw(StreamKind::Voice, MsgCategory::Voice, msg) {
+            debug!(?e, "Failed to send voice frame");
+        }
+    }
+
+    /// Take the voice frames of other players received since the last call,
+    /// in the order they arrived. Frames can be lost or arrive out of order,
+    /// so playback should go by their sequence numbers.
+    pub fn recv_voice_frames(&mut self) -> Vec<VoiceRelay> { self.voice_frames.drain(..).collect() }
+
+    /// Damage and kills near the player received since the last call, in the
+    /// order they happened
+    pub fn recv_combat_events(&mut self) -> Vec<CombatEvent> {
+        self.combat_events.drain(..).collect()
+    }
+
     pub fn toggle_wield(&mut self) {
         let is_wielding = self
             .state
@@ -836,6 +1242,40 @@ impl Client {
         self.state.terrain().get_key_arc(chunk_pos).cloned()
     }
 
+    /// Height of the ground below `pos`, including sprites like chests that
+    /// can be stood on. Only the `max_depth` blocks below `pos` are searched;
+    /// returns None if there's no ground there or it isn't loaded.
+    pub fn ground_height_at(&self, pos: Vec3<f32>, max_depth: f32) -> Option<f32> {
+        terrain_query::ground_height_at(&self.state.terrain(), pos, max_depth)
+    }
+
+    /// Casts a ray from `from` to `to` through the loaded terrain and returns
+    /// the first block that entities collide with, if any
+    pub fn raycast_terrain(&self, from: Vec3<f32>, to: Vec3<f32>) -> Option<TerrainHit> {
+        terrain_query::raycast_terrain(&self.state.terrain(), from, to)
+    }
+
+    /// Casts a ray from `from` to `to` through the loaded terrain and returns
+    /// the first block for which `is_hit` is true. Returns None if no block
+    /// was hit before the end of the ray or the ray left the loaded terrain.
+    pub fn raycast_terrain_until(
+        &self,
+        from: Vec3<f32>,
+        to: Vec3<f32>,
+        is_hit: impl FnMut(&Block) -> bool,
+    ) -> Option<TerrainHit> {
+        terrain_query::raycast_terrain_until(&self.state.terrain(), from, to, is_hit)
+    }
+
+    /// The weather at the position of the player.
+    pub fn current_weather(&self) -> WeatherCell {
+        self.state
+            .read_storage::<comp::Pos>()
+            .get(self.entity)
+            .map(|pos| self.state.weather().get(pos.0.xy()))
+            .unwrap_or_default()
+    }
+
     pub fn inventories(&self) -> ReadStorage<comp::Inventory> { self.state.read_storage() }
 
     pub fn loadouts(&self) -> ReadStorage<comp::Loadout> { self.state.read_storage() }
@@ -865,6 +1305,21 @@ impl Client {
         self.send_msg(ClientGeneral::BreakBlock(pos));
     }
 
+    /// The text written on the sign at the position, if any
+    pub fn sign_text(&self, pos: Vec3<i32>) -> Option<String> {
+        let terrain = self.state.terrain();
+        terrain
+            .get_key(terrain.pos_key(pos))
+            .and_then(|chunk| chunk.meta().sign(pos))
+            .map(String::from)
+    }
+
+    /// Ask the server to change the text of the sign at the position, an
+    /// empty text clears it
+    pub fn edit_sign(&mut self, pos: Vec3<i32>, text: String) {
+        self.send_msg(ClientGeneral::EditSign { pos, text });
+    }
+
     pub fn collect_block(&mut self, pos: Vec3<i32>) {
         self.send_msg(ClientGeneral::ControlEvent(ControlEvent::InventoryManip(
             InventoryManip::Collect(pos),
@@ -963,7 +1418,26 @@ impl Client {
         }
 
         // 4) Tick the client's LocalState
+        if self.time_of_day_correction != 0.0 {
+            let step = self.time_of_day_correction
+                * (dt.as_secs_f64() / TIME_OF_DAY_CORRECTION_TIME).min(1.0);
+            self.state.ecs_mut().write_resource::<TimeOfDay>().0 += step;
+            self.time_of_day_correction -= step;
+        }
         self.state.tick(dt, add_foreign_systems, true);
+        // The sounds of our own character are played as soon as they are
+        // predicted, the server doesn't send them back. Everything else is
+        // played when the server sends it, so that every client hears the
+        // same sounds.
+        let own_uid = self.uid();
+        frontend_events.extend(
+            self.state
+                .ecs()
+                .read_resource::<EventBus<comp::Sound>>()
+                .recv_all()
+                .filter(|sound| sound.source.is_some() && sound.source == own_uid)
+                .map(Event::Sound),
+        );
 
         // 5) Terrain
         let pos = self
@@ -992,6 +1466,11 @@ impl Client {
             for key in chunks_to_remove {
                 self.state.remove_chunk(key);
             }
+            // Only the collision data of chunks just beyond that is kept around
+            self.state
+                .ecs()
+                .write_resource::<common::terrain::CollisionMap>()
+                .retain_near(chunk_pos, view_distance + 2 + COLLISION_DISTANCE_MARGIN);
 
             // Request chunks from the server.
             self.loaded_distance = ((view_distance * TerrainChunkSize::RECT_SIZE.x) as f32).powi(2);
@@ -1059,21 +1538,55 @@ impl Client {
                 .retain(|_, created| now.duration_since(*created) < Duration::from_secs(3));
         }
 
+        // Fill in the world overview, one batch of tiles at a time and the ones
+        // closest to the player first
+        if self.registered
+            && self.world_overview.pending() == 0
+            && !self.world_overview.is_complete()
+        {
+            let near = pos.map_or(Vec2::zero(), |pos| {
+                WorldOverview::region_of(self.state.terrain().pos_key(pos.0.map(|e| e as i32)))
+            });
+            let tiles = self
+                .world_overview
+                .next_request(near, MAX_OVERVIEW_TILES_PER_REQUEST);
+            self.send_msg_err(ClientGeneral::RequestWorldMap(tiles))?;
+        }
+
         // Send a ping to the server once every second
         if self.state.get_time() - self.last_server_ping > 1. {
             self.send_msg_err(PingMsg::Ping)?;
+            self.net_stats.record_ping();
             self.last_server_ping = self.state.get_time();
         }
 
         // 6) Update the server about the player's physics attributes.
-        if self.in_game.is_some() {
-            if let (Some(pos), Some(vel), Some(ori)) = (
+        if self.in_game.is_some() && self.in_game_stream.queued_messages() <= PHYSICS_DROP_THRESHOLD
+        {
+            if let (Some(pos), Some(vel), Some(ori), Some(controller), Some(body), Some(physics)) = (
                 self.state.read_storage().get(self.entity).cloned(),
                 self.state.read_storage().get(self.entity).cloned(),
                 self.state.read_storage().get(self.entity).cloned(),
+                self.state
+                    .read_storage::<Controller>()
+                    .get(self.entity)
+                    .map(|c| c.inputs.clone()),
+                self.state
+                    .read_storage::<comp::Body>()
+                    .get(self.entity)
+                    .copied(),
+                self.state
+                    .read_storage::<comp::PhysicsState>()
+                    .get(self.entity)
+                    .cloned(),
             ) {
-                self.in_game_stream
-                    .send(ClientGeneral::PlayerPhysics { pos, vel, ori })?;
+                let seq = self.prediction.record(PredictedInput {
+                    inputs: controller,
+                    accel: move_accel(&body, &physics),
+                    dt: self.state.get_delta_time(),
+                    pos,
+                });
+                self.send_msg_err(ClientGeneral::PlayerPhysics { pos, vel, ori, seq })?;
             }
         }
 
@@ -1142,6 +1655,17 @@ impl Client {
                     );
                 }
             },
+            ServerGeneral::PlayerListUpdate(PlayerListUpdate::Idle(uid, idle)) => {
+                if let Some(player_info) = self.player_list.get_mut(&uid) {
+                    player_info.is_idle = idle;
+                } else {
+                    warn!(
+                        "Received msg to update idle status of uid {}, but they were not in the \
+                         list.",
+                        uid
+                    );
+                }
+            },
             ServerGeneral::PlayerListUpdate(PlayerListUpdate::SelectedCharacter(
                 uid,
                 char_info,
@@ -1215,12 +1739,23 @@ impl Client {
             ServerGeneral::SetPlayerEntity(uid) => {
                 if let Some(entity) = self.state.ecs().entity_from_uid(uid.0) {
                     self.entity = entity;
+                    self.prediction.reset();
                 } else {
                     return Err(Error::Other("Failed to find entity from uid.".to_owned()));
                 }
             },
             ServerGeneral::TimeOfDay(time_of_day) => {
-                *self.state.ecs_mut().write_resource() = time_of_day;
+                let diff = time_of_day.0 - self.state.get_time_of_day();
+                if diff.abs() > TIME_OF_DAY_SNAP_THRESHOLD {
+                    // Large jumps, e.g. from `/time`, are applied immediately
+                    *self.state.ecs_mut().write_resource() = time_of_day;
+                    self.time_of_day_correction = 0.0;
+                } else {
+                    self.time_of_day_correction = diff;
+                }
+            },
+            ServerGeneral::WeatherUpdate(weather) => {
+                *self.state.ecs_mut().write_resource() = weather;
             },
             ServerGeneral::EntitySync(entity_sync_package) => {
                 self.state
@@ -1245,6 +1780,13 @@ impl Client {
             ServerGeneral::Notification(n) => {
                 frontend_events.push(Event::Notification(n));
             },
+            ServerGeneral::WorldMapData(tile) => self.world_overview.insert(tile),
+            ServerGeneral::NamedLocations(locations) => self.named_locations = locations,
+            ServerGeneral::SessionExpiring => self.refresh_session(frontend_events),
+            ServerGeneral::SessionRefreshed(Ok(())) => debug!("Session refreshed"),
+            ServerGeneral::SessionRefreshed(Err(err)) => {
+                frontend_events.push(Event::SessionRefreshFailed(format!("{:?}", err)));
+            },
             _ => unreachable!("Not a general msg"),
         }
         Ok(())
@@ -1356,6 +1898,7 @@ impl Client {
             // Cleanup for when the client goes back to the `in_game = None`
             ServerGeneral::ExitInGameSuccess => {
                 self.in_game = None;
+                self.is_dead = false;
                 self.clean_state();
             },
             ServerGeneral::InventoryUpdate(mut inventory, event) => {
@@ -1374,12 +1917,17 @@ impl Client {
             },
             ServerGeneral::TerrainChunkUpdate { key, chunk } => {
                 if let Ok(chunk) = chunk {
-                    self.state.insert_chunk(key, *chunk);
+                    match chunk.unpack() {
+                        Some(chunk) => self.state.insert_chunk(key, chunk),
+                        None => warn!(?key, "Received a malformed terrain chunk"),
+                    }
                 }
                 self.pending_chunks.remove(&key);
             },
-            ServerGeneral::TerrainBlockUpdates(mut blocks) => {
-                blocks.drain().for_each(|(pos, block)| {
+            ServerGeneral::TerrainBlockUpdates(blocks) => {
+                // Changes to chunks that aren't loaded are dropped when applied, those
+                // chunks are up to date once they are received in full
+                blocks.into_iter().for_each(|(pos, block)| {
                     self.state.set_block(pos, block);
                 });
             },
@@ -1390,6 +1938,15 @@ impl Client {
             ServerGeneral::Outcomes(outcomes) => {
                 frontend_events.extend(outcomes.into_iter().map(Event::Outcome))
             },
+            ServerGeneral::CombatEvent(event) => {
+                if self.combat_events.len() >= MAX_PENDING_COMBAT_EVENTS {
+                    self.combat_events.pop_front();
+                }
+                self.combat_events.push_back(event);
+            },
+            ServerGeneral::SoundEvents(sounds) => {
+                frontend_events.extend(sounds.into_iter().map(Event::Sound))
+            },
             ServerGeneral::Knockback(impulse) => {
                 self.state
                     .ecs()
@@ -1399,6 +1956,72 @@ impl Client {
                         impulse,
                     });
             },
+            ServerGeneral::YouDied { killer, cause } => {
+                self.is_dead = true;
+                frontend_events.push(Event::Death { killer, cause });
+            },
+            ServerGeneral::Respawned => {
+                self.is_dead = false;
+                frontend_events.push(Event::Respawned);
+            },
+            ServerGeneral::PhysicsAck { seq, pos, vel, ori } => {
+                let terrain = self.state.terrain();
+                let mut physics_state = self
+                    .state
+                    .read_storage::<comp::PhysicsState>()
+                    .get(self.entity)
+                    .cloned()
+                    .unwrap_or_default();
+                let correction =
+                    self.prediction
+                        .reconcile(seq, pos, vel, |pos, vel, input: &PredictedInput| {
+                            replay_movement(
+                                &*terrain,
+                                pos,
+                                vel,
+                                &mut physics_state,
+                                input.inputs.move_dir,
+                                input.accel,
+                                input.dt,
+                            )
+                        });
+                drop(terrain);
+                match correction {
+                    Correction::None => {},
+                    Correction::Replay(pos, vel) => {
+                        self.state.write_component(self.entity, pos);
+                        self.state.write_component(self.entity, vel);
+                    },
+                    Correction::Snap => {
+                        self.state.write_component(self.entity, pos);
+                        self.state.write_component(self.entity, vel);
+                        self.state.write_component(self.entity, ori);
+                    },
+                }
+            },
+            ServerGeneral::UiPreferences(data) => {
+                frontend_events.push(Event::UiPreferences(data));
+            },
+            ServerGeneral::SkillSetUpdate(skill_set) => {
+                if let Some(stats) = self
+                    .state
+                    .ecs()
+                    .write_storage::<comp::Stats>()
+                    .get_mut(self.entity)
+                {
+                    stats.skill_set = skill_set;
+                }
+            },
+            ServerGeneral::AlchemyJournalUpdate(journal) => {
+                self.state.write_component(self.entity, journal);
+            },
+            ServerGeneral::AbilityHotbarUpdate(hotbar) => {
+                self.state.write_component(self.entity, hotbar);
+            },
+            ServerGeneral::ExpGain { exp, source } => {
+                frontend_events.push(Event::ExpGain { exp, source });
+            },
+            ServerGeneral::LevelUp(level) => frontend_events.push(Event::LevelUp(level)),
             _ => unreachable!("Not a in_game message"),
         }
         Ok(())
@@ -1410,6 +2033,7 @@ impl Client {
                 self.character_list.characters = character_list;
                 self.character_list.loading = false;
             },
+            ServerGeneral::AccountUnlocks(unlocks) => self.character_list.unlocks = unlocks,
             ServerGeneral::CharacterActionError(error) => {
                 warn!("CharacterActionError: {:?}.", error);
                 self.character_list.error = Some(error);
@@ -1420,6 +2044,7 @@ impl Client {
                 self.clean_state();
                 self.character_list.error = Some(error);
             },
+            ServerGeneral::ServerRules(rules) => self.server_rules = Some(rules),
             ServerGeneral::CharacterSuccess => {
                 debug!("client is now in ingame state on server");
                 if let Some(vd) = self.view_distance {
@@ -1437,6 +2062,7 @@ impl Client {
                 self.send_msg_err(PingMsg::Pong)?;
             },
             PingMsg::Pong => {
+                self.net_stats.record_pong();
                 self.last_server_pong = self.state.get_time();
                 self.last_ping_delta = self.state.get_time() - self.last_server_ping;
 
@@ -1458,26 +2084,170 @@ impl Client {
         cnt: &mut u64,
     ) -> Result<(), Error> {
         loop {
-            let (m1, m2, m3, m4) = select!(
-                msg = self.general_stream.recv().fuse() => (Some(msg), None, None, None),
-                msg = self.ping_stream.recv().fuse() => (None, Some(msg), None, None),
-                msg = self.character_screen_stream.recv().fuse() => (None, None, Some(msg), None),
-                msg = self.in_game_stream.recv().fuse() => (None, None, None, Some(msg)),
+            let (kind, msg) = select!(
+                msg = self.general_stream.recv_raw().fuse() => (StreamKind::General, msg),
+                msg = self.ping_stream.recv_raw().fuse() => (StreamKind::Ping, msg),
+                msg = self.character_screen_stream.recv_raw().fuse() => {
+                    (StreamKind::CharacterScreen, msg)
+                },
+                msg = self.in_game_stream.recv_raw().fuse() => (StreamKind::InGame, msg),
+                msg = self.terrain_stream.recv_raw().fuse() => (StreamKind::Terrain, msg),
+                msg = recv_optional(&mut self.voice_stream).fuse() => (StreamKind::Voice, msg),
             );
             *cnt += 1;
-            if let Some(msg) = m1 {
-                self.handle_server_msg(frontend_events, msg?)?;
-            }
-            if let Some(msg) = m2 {
-                self.handle_ping_msg(msg?)?;
-            }
-            if let Some(msg) = m3 {
-                self.handle_server_character_screen_msg(msg?)?;
+            let msg = msg?;
+            if self.net_conditions.is_perfect() && self.delayed_incoming.is_empty() {
+                self.handle_raw_msg(frontend_events, kind, msg)?;
+            } else {
+                self.delayed_incoming.push(
+                    Instant::now(),
+                    kind,
+                    msg,
+                    &self.net_conditions,
+                    &mut rand::thread_rng(),
+                );
             }
-            if let Some(msg) = m4 {
-                self.handle_server_in_game_msg(frontend_events, msg?)?;
+        }
+    }
+
+    fn handle_raw_msg(
+        &mut self,
+        frontend_events: &mut Vec<Event>,
+        kind: StreamKind,
+        msg: Message,
+    ) -> Result<(), Error> {
+        let size = msg.size();
+        match kind {
+            StreamKind::General => {
+                let msg = msg.deserialize()?;
+                let category = MsgCategory::of_server_msg(&msg);
+                self.net_stats.record_received(kind, category, size);
+                self.handle_server_msg(frontend_events, msg)
+            },
+            StreamKind::Ping => {
+                self.net_stats
+                    .record_received(kind, MsgCategory::Other, size);
+                self.handle_ping_msg(msg.deserialize()?)
+            },
+            StreamKind::CharacterScreen => {
+                self.net_stats
+                    .record_received(kind, MsgCategory::Other, size);
+                self.handle_server_character_screen_msg(msg.deserialize()?)
+            },
+            StreamKind::InGame => {
+                // The category is only known once decoded
+                match self
+                    .in_game_decoding
+                    .push(&self.thread_pool, size, move || (size, msg.deserialize()))
+                {
+                    Some((size, msg)) => {
+                        self.handle_decoded_in_game_msg(frontend_events, size, msg)
+                    },
+                    None => Ok(()),
+                }
+            },
+            StreamKind::Terrain => {
+                // Only terrain is sent on this stream
+                self.net_stats
+                    .record_received(kind, MsgCategory::Terrain, size);
+                match self
+                    .terrain_decoding
+                    .push(&self.thread_pool, size, move || msg.deserialize())
+                {
+                    Some(msg) => self.handle_server_in_game_msg(frontend_events, msg?),
+                    None => Ok(()),
+                }
+            },
+            StreamKind::Voice => {
+                self.net_stats
+                    .record_received(kind, MsgCategory::Voice, size);
+                if self.voice_frames.len() >= MAX_PENDING_VOICE_FRAMES {
+                    self.voice_frames.pop_front();
+                }
+                self.voice_frames.push_back(msg.deserialize()?);
+                Ok(())
+            },
+            // Registering is done before the client is handed out, so nothing
+            // is received on this stream afterwards
+            StreamKind::Register => Ok(()),
+        }
+    }
+
+    /// Send and handle the messages held back by simulated network
+    /// conditions that are due
+    fn handle_delayed_msgs(&mut self, frontend_events: &mut Vec<Event>) -> Result<(), Error> {
+        let now = Instant::now();
+        while let Some((kind, msg)) = self.delayed_outgoing.pop_due(now) {
+            self.stream(kind).send_raw(&msg)?;
+        }
+        while let Some((kind, msg)) = self.delayed_incoming.pop_due(now) {
+            self.handle_raw_msg(frontend_events, kind, msg)?;
+        }
+        self.net_stats.tick(now);
+        Ok(())
+    }
+
+    fn handle_decoded_in_game_msg(
+        &mut self,
+        frontend_events: &mut Vec<Event>,
+        size: usize,
+        msg: Result<ServerGeneral, StreamError>,
+    ) -> Result<(), Error> {
+        let msg = msg?;
+        let category = MsgCategory::of_server_msg(&msg);
+        self.net_stats
+            .record_received(StreamKind::InGame, category, size);
+        self.handle_server_in_game_msg(frontend_events, msg)
+    }
+
+    /// Handle the messages that have finished decoding on the thread pool, in
+    /// the order they were received on their stream
+    fn handle_decoded_msgs(&mut self, frontend_events: &mut Vec<Event>) -> Result<(), Error> {
+        while let Some((size, msg)) = self.in_game_decoding.pop() {
+            self.handle_decoded_in_game_msg(frontend_events, size, msg)?;
+        }
+        while let Some(msg) = self.terrain_decoding.pop() {
+            self.handle_server_in_game_msg(frontend_events, msg?)?;
+        }
+        Ok(())
+    }
+
+    /// Sign in with the auth server again on the thread pool, to send the
+    /// server a new token before the session expires
+    fn refresh_session(&mut self, frontend_events: &mut Vec<Event>) {
+        let credentials = match &self.auth_credentials {
+            Some(credentials) => credentials.clone(),
+            None => {
+                warn!("Server asked for a new token, but the credentials weren't kept");
+                frontend_events.push(Event::SessionRefreshFailed(
+                    "session refresh is disabled".to_string(),
+                ));
+                return;
+            },
+        };
+        debug!("Session is expiring, signing in again");
+        let session_token_tx = self.session_token_tx.clone();
+        self.thread_pool.execute(move || {
+            let token = authc::AuthClient::new(&credentials.addr)
+                .sign_in(&credentials.username, &credentials.password)
+                .map(|token| token.serialize())
+                .map_err(|err| format!("{:?}", err));
+            let _ = session_token_tx.send(token);
+        });
+    }
+
+    /// Send the server the tokens of finished sign ins
+    fn handle_session_tokens(&mut self, frontend_events: &mut Vec<Event>) -> Result<(), Error> {
+        while let Ok(token) = self.session_token_rx.try_recv() {
+            match token {
+                Ok(token) => self.send_msg_err(ClientGeneral::RefreshSession(token))?,
+                Err(err) => {
+                    warn!(?err, "Failed to sign in again to refresh the session");
+                    frontend_events.push(Event::SessionRefreshFailed(err));
+                },
             }
         }
+        Ok(())
     }
 
     /// Handle new server messages.
@@ -1513,6 +2283,10 @@ impl Client {
             )
         })?;
 
+        self.handle_delayed_msgs(&mut frontend_events)?;
+        self.handle_decoded_msgs(&mut frontend_events)?;
+        self.handle_session_tokens(&mut frontend_events)?;
+
         if handles_msg == 0
             && self.state.get_time() - self.last_server_pong > self.client_timeout.as_secs() as f64
         {
@@ -1522,6 +2296,25 @@ impl Client {
         Ok(frontend_events)
     }
 
+    /// Bandwidth used by each stream during the last seconds
+    pub fn net_stats(&self) -> &NetStats { &self.net_stats }
+
+    /// Ping, packet loss and bandwidth of the connection to the server
+    pub fn network_stats(&self) -> NetworkStats {
+        NetworkStats {
+            ping_ms: self.get_ping_ms_rolling_avg(),
+            packet_loss: self.net_stats.packet_loss(),
+            last: self.net_stats.last(),
+        }
+    }
+
+    pub fn net_conditions(&self) -> NetConditions { self.net_conditions }
+
+    /// Simulate bad network conditions on the connection to the server
+    pub fn set_net_conditions(&mut self, conditions: NetConditions) {
+        self.net_conditions = conditions;
+    }
+
     pub fn entity(&self) -> EcsEntity { self.entity }
 
     pub fn uid(&self) -> Option<Uid> { self.state.read_component_copied(self.entity) }
@@ -1530,10 +2323,44 @@ impl Client {
 
     pub fn registered(&self) -> bool { self.registered }
 
+    /// Whether the server supports an optional protocol feature
+    pub fn server_supports(&self, capability: Capability) -> bool {
+        self.server_info
+            .capabilities
+            .iter()
+            .any(|name| name == capability.name())
+    }
+
     pub fn get_tick(&self) -> u64 { self.tick }
 
     pub fn get_ping_ms(&self) -> f64 { self.last_ping_delta * 1000.0 }
 
+    /// Approximate memory used by the subsystems of the client. Meshes are
+    /// left to the frontend, which owns them.
+    pub fn memory_report(&self) -> MemoryReport {
+        let network = [
+            &self.general_stream,
+            &self.ping_stream,
+            &self.register_stream,
+            &self.character_screen_stream,
+            &self.in_game_stream,
+            &self.terrain_stream,
+        ]
+        .iter()
+        .copied()
+        .chain(self.voice_stream.as_ref())
+        .map(|s| s.queued_bytes())
+        .sum::<u64>();
+
+        MemoryReport {
+            terrain: self.state.terrain().memory_usage(),
+            ecs: common::memory::ecs_usage(self.state.ecs()),
+            network: network as usize,
+            assets: common::assets::cache_size(),
+            ..MemoryReport::default()
+        }
+    }
+
     pub fn get_ping_ms_rolling_avg(&self) -> f64 {
         let mut total_weight = 0.;
         let pings = self.ping_deltas.len() as f64;
@@ -1619,11 +2446,22 @@ impl Client {
 
     /// Format a message for the client (voxygen chat box or chat-cli)
     pub fn format_message(&self, msg: &comp::ChatMsg, character_name: bool) -> String {
+        self.format_message_with(msg, character_name, &self.player_list)
+    }
+
+    /// Format a message with the names of the given player list, e.g. one in
+    /// which the names were replaced
+    pub fn format_message_with(
+        &self,
+        msg: &comp::ChatMsg,
+        character_name: bool,
+        player_list: &HashMap<Uid, PlayerInfo>,
+    ) -> String {
         let comp::ChatMsg {
             chat_type, message, ..
         } = &msg;
         let alias_of_uid = |uid| {
-            self.player_list
+            player_list
                 .get(uid)
                 .map_or("<?>".to_string(), |player_info| {
                     if player_info.is_admin {
@@ -1636,7 +2474,10 @@ impl Client {
                     }
                 })
         };
-        let name_of_uid = |uid| {
+        let name_of_uid = |uid: &Uid| {
+            if let Some(character) = player_list.get(uid).and_then(|p| p.character.as_ref()) {
+                return Some(character.name.clone());
+            }
             let ecs = self.state.ecs();
             (
                 &ecs.read_storage::<comp::Stats>(),
@@ -1715,6 +2556,11 @@ impl Client {
                             alias_of_uid(attacker_uid),
                             alias_of_uid(victim)
                         ),
+                        KillSource::Player(attacker_uid, KillType::Buff) => format!(
+                            "[{}] succumbed to wounds inflicted by [{}]",
+                            alias_of_uid(victim),
+                            alias_of_uid(attacker_uid)
+                        ),
                         KillSource::NonPlayer(attacker_name, KillType::Melee) => {
                             format!("{} killed [{}]", attacker_name, alias_of_uid(victim))
                         },
@@ -1729,6 +2575,11 @@ impl Client {
                             attacker_name,
                             alias_of_uid(victim)
                         ),
+                        KillSource::NonPlayer(attacker_name, KillType::Buff) => format!(
+                            "[{}] succumbed to wounds inflicted by {}",
+                            alias_of_uid(victim),
+                            attacker_name
+                        ),
                         KillSource::Environment(environment) => {
                             format!("[{}] died in {}", alias_of_uid(victim), environment)
                         },
@@ -1754,6 +2605,9 @@ impl Client {
                         KillSource::Player(attacker_uid, KillType::Energy) => message
                             .replace("{attacker}", &alias_of_uid(attacker_uid))
                             .replace("{victim}", &alias_of_uid(victim)),
+                        KillSource::Player(attacker_uid, KillType::Buff) => message
+                            .replace("{attacker}", &alias_of_uid(attacker_uid))
+                            .replace("{victim}", &alias_of_uid(victim)),
                         KillSource::NonPlayer(attacker_name, KillType::Melee) => message
                             .replace("{attacker}", attacker_name)
                             .replace("{victim}", &alias_of_uid(victim)),
@@ -1766,6 +2620,9 @@ impl Client {
                         KillSource::NonPlayer(attacker_name, KillType::Energy) => message
                             .replace("{attacker}", attacker_name)
                             .replace("{victim}", &alias_of_uid(victim)),
+                        KillSource::NonPlayer(attacker_name, KillType::Buff) => message
+                            .replace("{attacker}", attacker_name)
+                            .replace("{victim}", &alias_of_uid(victim)),
                         KillSource::Environment(environment) => message
                             .replace("{name}", &alias_of_uid(victim))
                             .replace("{environment}", environment),
@@ -1785,6 +2642,7 @@ impl Client {
                 }
             },
             comp::ChatType::Say(uid) => message_format(uid, message, None),
+            comp::ChatType::Shout(uid) => message_format(uid, message, None),
             comp::ChatType::Group(uid, s) => message_format(uid, message, Some(s)),
             comp::ChatType::Faction(uid, s) => message_format(uid, message, Some(s)),
             comp::ChatType::Region(uid) => message_format(uid, message, None),
@@ -1797,9 +2655,22 @@ impl Client {
     }
 }
 
+/// Receives from a stream that might not have been opened, never returning if
+/// it wasn't
+async fn recv_optional(stream: &mut Option<Stream>) -> Result<Message, StreamError> {
+    match stream {
+        Some(stream) => stream.recv_raw().await,
+        None => futures_util::future::pending().await,
+    }
+}
+
 impl Drop for Client {
     fn drop(&mut self) {
         trace!("Dropping client");
+        let delayed = self.delayed_outgoing.drain().collect::<Vec<_>>();
+        for (kind, msg) in delayed {
+            let _ = self.stream(kind).send_raw(&msg);
+        }
         if self.registered {
             if let Err(e) = self.send_msg_err(ClientGeneral::Disconnect) {
                 warn!(
@@ -0,0 +1,138 @@
+//! Simulated bad network conditions, for testing how the game copes with them
+//! without needing a bad network
+//!
+//! Messages sent to and received from the server are held back for the
+//! configured latency plus a random amount of jitter. Streams are reliable and
+//! ordered, so a lost message isn't gone but arrives late once it was sent
+//! again, and holds back the messages behind it on the same stream.
+
+use crate::net_stats::StreamKind;
+use rand::Rng;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Lowest time after which a lost message is sent again
+const MIN_RETRANSMIT_DELAY: Duration = Duration::from_millis(200);
+/// Loss is capped so that messages are eventually delivered
+const MAX_LOSS: f32 = 0.9;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct NetConditions {
+    /// Added to the time each message takes in either direction
+    pub latency: Duration,
+    /// Largest random delay added on top of the latency
+    pub jitter: Duration,
+    /// Chance of each message being lost on its first try
+    pub loss: f32,
+}
+
+impl NetConditions {
+    pub fn is_perfect(&self) -> bool {
+        self.latency == Duration::default()
+            && self.jitter == Duration::default()
+            && self.loss <= 0.0
+    }
+
+    fn delay(&self, rng: &mut impl Rng) -> Duration {
+        let mut delay = self.latency + self.jitter.mul_f32(rng.gen());
+        while rng.gen::<f32>() < self.loss.min(MAX_LOSS) {
+            delay += MIN_RETRANSMIT_DELAY.max(self.latency * 2);
+        }
+        delay
+    }
+}
+
+/// Messages in one direction that are held back until they are due
+pub struct SimulatedLink<T> {
+    /// Held back messages, ordered by the time they are due
+    queue: VecDeque<(Instant, StreamKind, T)>,
+    /// Time the last message of each stream is due, which later messages of
+    /// the stream can't overtake
+    last_due: [Option<Instant>; StreamKind::COUNT],
+}
+
+impl<T> Default for SimulatedLink<T> {
+    fn default() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            last_due: [None; StreamKind::COUNT],
+        }
+    }
+}
+
+impl<T> SimulatedLink<T> {
+    pub fn is_empty(&self) -> bool { self.queue.is_empty() }
+
+    pub fn push(
+        &mut self,
+        now: Instant,
+        kind: StreamKind,
+        msg: T,
+        conditions: &NetConditions,
+        rng: &mut impl Rng,
+    ) {
+        let mut due = now + conditions.delay(rng);
+        let last_due = &mut self.last_due[kind.index()];
+        if let Some(last_due) = *last_due {
+            due = due.max(last_due);
+        }
+        *last_due = Some(due);
+
+        let index = self
+            .queue
+            .iter()
+            .rposition(|(other_due, _, _)| *other_due <= due)
+            .map_or(0, |i| i + 1);
+        self.queue.insert(index, (due, kind, msg));
+    }
+
+    /// The next message that is due by `now`
+    pub fn pop_due(&mut self, now: Instant) -> Option<(StreamKind, T)> {
+        if self.queue.front().map_or(false, |(due, _, _)| *due <= now) {
+            self.queue.pop_front().map(|(_, kind, msg)| (kind, msg))
+        } else {
+            None
+        }
+    }
+
+    /// All held back messages in the order they are due, for when they can't
+    /// wait any longer
+    pub fn drain(&mut self) -> impl Iterator<Item = (StreamKind, T)> + '_ {
+        self.last_due = [None; StreamKind::COUNT];
+        self.queue.drain(..).map(|(_, kind, msg)| (kind, msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_stay_ordered() {
+        let conditions = NetConditions {
+            latency: Duration::from_millis(50),
+            jitter: Duration::from_millis(100),
+            loss: 0.2,
+        };
+        let mut rng = rand::thread_rng();
+        let mut link = SimulatedLink::default();
+        let start = Instant::now();
+        for i in 0..100 {
+            let kind = if i % 2 == 0 {
+                StreamKind::InGame
+            } else {
+                StreamKind::Terrain
+            };
+            link.push(start, kind, i, &conditions, &mut rng);
+        }
+
+        let mut last = [None; StreamKind::COUNT];
+        while let Some((kind, i)) = link.pop_due(start + Duration::from_secs(3600)) {
+            assert!(last[kind.index()].map_or(true, |last| last < i));
+            last[kind.index()] = Some(i);
+        }
+        assert!(link.is_empty());
+    }
+}
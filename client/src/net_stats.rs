@@ -0,0 +1,251 @@
+//! Bandwidth used by each stream of the connection to the server, for
+//! displaying it in debug tools
+
+use common::msg::{ClientGeneral, ServerGeneral};
+use std::{collections::VecDeque, time::Instant};
+
+/// Number of one second samples that are kept
+pub const HISTORY_SECS: usize = 30;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StreamKind {
+    Register,
+    General,
+    Ping,
+    CharacterScreen,
+    InGame,
+    Terrain,
+    Voice,
+}
+
+impl StreamKind {
+    pub const ALL: [StreamKind; Self::COUNT] = [
+        StreamKind::Register,
+        StreamKind::General,
+        StreamKind::Ping,
+        StreamKind::CharacterScreen,
+        StreamKind::InGame,
+        StreamKind::Terrain,
+        StreamKind::Voice,
+    ];
+    pub const COUNT: usize = 7;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StreamKind::Register => "Register",
+            StreamKind::General => "General",
+            StreamKind::Ping => "Ping",
+            StreamKind::CharacterScreen => "Character screen",
+            StreamKind::InGame => "In game",
+            StreamKind::Terrain => "Terrain",
+            StreamKind::Voice => "Voice",
+        }
+    }
+
+    pub fn index(self) -> usize { self as usize }
+}
+
+/// What messages are about, independent of the stream they are sent on
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MsgCategory {
+    Terrain,
+    EntitySync,
+    Chat,
+    Voice,
+    Other,
+}
+
+impl MsgCategory {
+    pub const ALL: [MsgCategory; Self::COUNT] = [
+        MsgCategory::Terrain,
+        MsgCategory::EntitySync,
+        MsgCategory::Chat,
+        MsgCategory::Voice,
+        MsgCategory::Other,
+    ];
+    pub const COUNT: usize = 5;
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MsgCategory::Terrain => "Terrain",
+            MsgCategory::EntitySync => "Entity sync",
+            MsgCategory::Chat => "Chat",
+            MsgCategory::Voice => "Voice",
+            MsgCategory::Other => "Other",
+        }
+    }
+
+    pub fn index(self) -> usize { self as usize }
+
+    pub(crate) fn of_client_msg(msg: &ClientGeneral) -> Self {
+        match msg {
+            ClientGeneral::TerrainChunkRequest { .. }
+            | ClientGeneral::RequestWorldMap(_)
+            | ClientGeneral::BreakBlock(_)
+            | ClientGeneral::PlaceBlock(..)
+            | ClientGeneral::EditSign { .. } => MsgCategory::Terrain,
+            ClientGeneral::ControllerInputs(_) | ClientGeneral::PlayerPhysics { .. } => {
+                MsgCategory::EntitySync
+            },
+            ClientGeneral::ChatMsg(_) => MsgCategory::Chat,
+            _ => MsgCategory::Other,
+        }
+    }
+
+    pub(crate) fn of_server_msg(msg: &ServerGeneral) -> Self {
+        match msg {
+            ServerGeneral::TerrainChunkUpdate { .. }
+            | ServerGeneral::TerrainBlockUpdates(_)
+            | ServerGeneral::WorldMapData(_) => MsgCategory::Terrain,
+            ServerGeneral::EntitySync(_)
+            | ServerGeneral::CompSync(_)
+            | ServerGeneral::CreateEntity(_)
+            | ServerGeneral::DeleteEntity(_)
+            | ServerGeneral::PhysicsAck { .. } => MsgCategory::EntitySync,
+            ServerGeneral::ChatMsg(_) => MsgCategory::Chat,
+            _ => MsgCategory::Other,
+        }
+    }
+}
+
+/// Bytes sent and received on each stream and in each category of messages
+/// during one second
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Sample {
+    sent: [usize; StreamKind::COUNT],
+    received: [usize; StreamKind::COUNT],
+    category_sent: [usize; MsgCategory::COUNT],
+    category_received: [usize; MsgCategory::COUNT],
+    pings: usize,
+    pongs: usize,
+}
+
+impl Sample {
+    pub fn sent(&self, kind: StreamKind) -> usize { self.sent[kind.index()] }
+
+    pub fn received(&self, kind: StreamKind) -> usize { self.received[kind.index()] }
+
+    pub fn category_sent(&self, category: MsgCategory) -> usize {
+        self.category_sent[category.index()]
+    }
+
+    pub fn category_received(&self, category: MsgCategory) -> usize {
+        self.category_received[category.index()]
+    }
+
+    pub fn total_sent(&self) -> usize { self.sent.iter().sum() }
+
+    pub fn total_received(&self) -> usize { self.received.iter().sum() }
+}
+
+pub struct NetStats {
+    current: Sample,
+    current_start: Instant,
+    /// Finished samples, oldest first
+    history: VecDeque<Sample>,
+}
+
+impl Default for NetStats {
+    fn default() -> Self {
+        Self {
+            current: Sample::default(),
+            current_start: Instant::now(),
+            history: VecDeque::with_capacity(HISTORY_SECS),
+        }
+    }
+}
+
+impl NetStats {
+    pub(crate) fn record_sent(&mut self, kind: StreamKind, category: MsgCategory, bytes: usize) {
+        self.current.sent[kind.index()] += bytes;
+        self.current.category_sent[category.index()] += bytes;
+    }
+
+    pub(crate) fn record_received(
+        &mut self,
+        kind: StreamKind,
+        category: MsgCategory,
+        bytes: usize,
+    ) {
+        self.current.received[kind.index()] += bytes;
+        self.current.category_received[category.index()] += bytes;
+    }
+
+    pub(crate) fn record_ping(&mut self) { self.current.pings += 1; }
+
+    pub(crate) fn record_pong(&mut self) { self.current.pongs += 1; }
+
+    /// Finish the current sample once it covers a second
+    pub(crate) fn tick(&mut self, now: Instant) {
+        if now.duration_since(self.current_start).as_secs() >= 1 {
+            if self.history.len() >= HISTORY_SECS {
+                self.history.pop_front();
+            }
+            self.history.push_back(std::mem::take(&mut self.current));
+            self.current_start = now;
+        }
+    }
+
+    /// Bytes sent and received during each of the last seconds, oldest first
+    pub fn history(&self) -> impl Iterator<Item = &Sample> + ExactSizeIterator {
+        self.history.iter()
+    }
+
+    /// The last full second
+    pub fn last(&self) -> Sample { self.history.back().copied().unwrap_or_default() }
+
+    /// Share of the pings of the last seconds that weren't answered, from 0 to
+    /// 1. The connection is reliable, so this counts pongs that are late
+    /// because packets had to be sent again, but also ones that are late for
+    /// other reasons.
+    pub fn packet_loss(&self) -> f32 {
+        // The pong to a ping sent during the last second might still be on its way
+        let samples = self.history.len().saturating_sub(1);
+        let (pings, pongs) = self
+            .history
+            .iter()
+            .take(samples)
+            .fold((0, 0), |(pings, pongs), sample| {
+                (pings + sample.pings, pongs + sample.pongs)
+            });
+        if pings == 0 {
+            0.0
+        } else {
+            (1.0 - pongs as f32 / pings as f32).max(0.0)
+        }
+    }
+}
+
+/// Summary of the connection to the server, for debug overlays
+#[derive(Copy, Clone, Debug)]
+pub struct NetworkStats {
+    /// Rolling average of the round trip time
+    pub ping_ms: f64,
+    /// Estimated share of lost packets, from 0 to 1
+    pub packet_loss: f32,
+    /// Bytes sent and received during the last full second
+    pub last: Sample,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn packet_loss_from_unanswered_pings() {
+        let mut stats = NetStats::default();
+        let mut now = Instant::now();
+        assert_eq!(stats.packet_loss(), 0.0);
+        for i in 0..5 {
+            stats.record_ping();
+            if i % 2 == 0 {
+                stats.record_pong();
+            }
+            now += Duration::from_secs(1);
+            stats.tick(now);
+        }
+        // the last ping isn't counted yet, of the others 2 of 4 were answered
+        assert_eq!(stats.packet_loss(), 0.5);
+    }
+}
@@ -0,0 +1,214 @@
+//! Reconciliation of the locally predicted movement of the player with the
+//! server
+//!
+//! The client simulates its own entity right away instead of waiting for the
+//! server. The inputs of every physics update sent to the server are kept
+//! around, tagged with a sequence number, and the server acknowledges them with
+//! the state it settled on. If that differs from what was predicted, the
+//! inputs the server hasn't acknowledged yet are replayed on top of the
+//! server's state, or the entity snaps to the server's state when they
+//! diverged too far.
+
+use common::comp::{ControllerInputs, Pos, Vel};
+use std::collections::VecDeque;
+use vek::*;
+
+/// Errors below this distance are ignored, so that the player doesn't jitter
+/// from float imprecision
+const MIN_CORRECTION: f32 = 0.01;
+/// Errors above this distance aren't worth replaying, the player is snapped to
+/// the server's state instead
+const SNAP_DISTANCE: f32 = 4.0;
+/// Number of unacknowledged updates kept around. Older ones are forgotten if
+/// the server stops acknowledging them.
+const MAX_UNACKNOWLEDGED: usize = 256;
+
+/// The inputs the player steered with during one update, and where they ended
+/// up according to the prediction
+#[derive(Clone, Debug)]
+pub struct PredictedInput {
+    pub inputs: ControllerInputs,
+    /// Acceleration of the player's movement, which depends on their body and
+    /// on whether they were on the ground
+    pub accel: f32,
+    pub dt: f32,
+    pub pos: Pos,
+}
+
+/// How the predicted state of the player has to be corrected
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Correction {
+    /// The prediction matches the server
+    None,
+    /// The state the player ends up in after replaying the unacknowledged
+    /// inputs from the server's state
+    Replay(Pos, Vel),
+    /// The prediction is too far off, take the server's state as is
+    Snap,
+}
+
+#[derive(Default)]
+pub struct Prediction {
+    next_seq: u64,
+    /// The unacknowledged inputs, oldest first
+    unacknowledged: VecDeque<(u64, PredictedInput)>,
+}
+
+impl Prediction {
+    /// Remember the inputs of an update sent to the server, returning the
+    /// sequence number to tag it with
+    pub fn record(&mut self, input: PredictedInput) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        if self.unacknowledged.len() >= MAX_UNACKNOWLEDGED {
+            self.unacknowledged.pop_front();
+        }
+        self.unacknowledged.push_back((seq, input));
+        seq
+    }
+
+    /// Compare the server's state after the update `seq` with what was
+    /// predicted for it. If they differ, the later inputs are replayed one by
+    /// one with `step`, starting from the server's state.
+    pub fn reconcile(
+        &mut self,
+        seq: u64,
+        server_pos: Pos,
+        server_vel: Vel,
+        mut step: impl FnMut(&mut Pos, &mut Vel, &PredictedInput),
+    ) -> Correction {
+        while self.unacknowledged.front().map_or(false, |(s, _)| *s < seq) {
+            self.unacknowledged.pop_front();
+        }
+        let predicted = match self.unacknowledged.pop_front() {
+            Some((s, predicted)) if s == seq => predicted.pos,
+            // Acknowledgements that are older than the oldest update kept around
+            // can't be compared
+            Some(entry) => {
+                self.unacknowledged.push_front(entry);
+                return Correction::None;
+            },
+            None => return Correction::None,
+        };
+
+        let error = server_pos.0 - predicted.0;
+        if error.magnitude_squared() > SNAP_DISTANCE.powi(2) {
+            self.unacknowledged.clear();
+            Correction::Snap
+        } else if error.magnitude_squared() > MIN_CORRECTION.powi(2) {
+            // Later predictions started from the wrong state too, so they are
+            // redone from the server's
+            let (mut pos, mut vel) = (server_pos, server_vel);
+            for (_, input) in self.unacknowledged.iter_mut() {
+                step(&mut pos, &mut vel, input);
+                input.pos = pos;
+            }
+            Correction::Replay(pos, vel)
+        } else {
+            Correction::None
+        }
+    }
+
+    /// Forget all unacknowledged updates, when the player entity changes
+    pub fn reset(&mut self) { self.unacknowledged.clear(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steering sets the velocity directly, to keep the numbers simple
+    fn step(pos: &mut Pos, vel: &mut Vel, input: &PredictedInput) {
+        vel.0 = Vec3::from(input.inputs.move_dir * input.accel);
+        pos.0 += vel.0 * input.dt;
+    }
+
+    /// Predicts `n` updates moving along x from `pos`, recording each of them
+    fn walk(prediction: &mut Prediction, pos: &mut Pos, n: usize) -> Vec<u64> {
+        (0..n)
+            .map(|_| {
+                let mut input = PredictedInput {
+                    inputs: ControllerInputs {
+                        move_dir: Vec2::unit_x(),
+                        ..Default::default()
+                    },
+                    accel: 10.0,
+                    dt: 0.1,
+                    pos: *pos,
+                };
+                step(pos, &mut Vel::default(), &input);
+                input.pos = *pos;
+                prediction.record(input)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matching_predictions_are_kept() {
+        let mut prediction = Prediction::default();
+        let mut pos = Pos(Vec3::zero());
+        let seqs = walk(&mut prediction, &mut pos, 3);
+        let correction = prediction.reconcile(
+            seqs[0],
+            Pos(Vec3::unit_x()),
+            Vel(Vec3::unit_x() * 10.0),
+            step,
+        );
+        assert_eq!(correction, Correction::None);
+    }
+
+    #[test]
+    fn unacknowledged_inputs_are_replayed_from_the_server_state() {
+        let mut prediction = Prediction::default();
+        let mut pos = Pos(Vec3::zero());
+        let seqs = walk(&mut prediction, &mut pos, 4);
+        // The server had the player bumped to the side during the first update
+        let correction = prediction.reconcile(
+            seqs[0],
+            Pos(Vec3::new(1.0, 0.5, 0.0)),
+            Vel(Vec3::unit_x() * 10.0),
+            step,
+        );
+        assert_eq!(
+            correction,
+            Correction::Replay(Pos(Vec3::new(4.0, 0.5, 0.0)), Vel(Vec3::unit_x() * 10.0))
+        );
+
+        // The replayed predictions are what later acknowledgements are compared
+        // against
+        let correction = prediction.reconcile(
+            seqs[1],
+            Pos(Vec3::new(2.0, 0.5, 0.0)),
+            Vel(Vec3::unit_x() * 10.0),
+            step,
+        );
+        assert_eq!(correction, Correction::None);
+    }
+
+    #[test]
+    fn large_errors_snap_to_the_server_state() {
+        let mut prediction = Prediction::default();
+        let mut pos = Pos(Vec3::zero());
+        let seqs = walk(&mut prediction, &mut pos, 3);
+        let correction = prediction.reconcile(
+            seqs[0],
+            Pos(Vec3::new(1.0, 10.0, 0.0)),
+            Vel::default(),
+            step,
+        );
+        assert_eq!(correction, Correction::Snap);
+        // Nothing is left to compare later acknowledgements with
+        let correction = prediction.reconcile(seqs[1], Pos(Vec3::zero()), Vel::default(), step);
+        assert_eq!(correction, Correction::None);
+    }
+
+    #[test]
+    fn stale_acknowledgements_are_ignored() {
+        let mut prediction = Prediction::default();
+        let mut pos = Pos(Vec3::zero());
+        let seqs = walk(&mut prediction, &mut pos, 3);
+        prediction.reconcile(seqs[1], Pos(Vec3::unit_x() * 2.0), Vel::default(), step);
+        let correction = prediction.reconcile(seqs[0], Pos(Vec3::zero()), Vel::default(), step);
+        assert_eq!(correction, Correction::None);
+    }
+}
@@ -0,0 +1,151 @@
+//! Collision queries against the loaded terrain
+//!
+//! Frontend features like camera collision, footstep sounds or particles on
+//! the ground use these instead of walking the voxels themselves. They are
+//! available on the [`Client`](../struct.Client.html), and as functions of the
+//! terrain for systems that only have access to the ECS.
+
+use common::{
+    ray::Ray,
+    terrain::{Block, TerrainGrid},
+};
+use vek::*;
+
+/// The first block hit by a ray cast through the loaded terrain
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TerrainHit {
+    /// Distance from the start of the ray to the point where it hit
+    pub distance: f32,
+    /// Point where the ray entered the block
+    pub pos: Vec3<f32>,
+    pub block_pos: Vec3<i32>,
+    pub block: Block,
+}
+
+/// Height of the ground below `pos`, including sprites like chests that can be
+/// stood on. Only the `max_depth` blocks below `pos` are searched; returns None
+/// if there's no ground there or it isn't loaded.
+pub fn ground_height_at(terrain: &TerrainGrid, pos: Vec3<f32>, max_depth: f32) -> Option<f32> {
+    raycast_terrain(terrain, pos, pos - Vec3::unit_z() * max_depth)
+        .map(|hit| hit.block_pos.z as f32 + hit.block.solid_height())
+}
+
+/// Casts a ray from `from` to `to` through the loaded terrain and returns the
+/// first block that entities collide with, if any
+pub fn raycast_terrain(
+    terrain: &TerrainGrid,
+    from: Vec3<f32>,
+    to: Vec3<f32>,
+) -> Option<TerrainHit> {
+    raycast_terrain_until(terrain, from, to, Block::is_solid)
+}
+
+/// Casts a ray from `from` to `to` through the loaded terrain and returns the
+/// first block for which `is_hit` is true. Returns None if no block was hit
+/// before the end of the ray or the ray left the loaded terrain.
+pub fn raycast_terrain_until(
+    terrain: &TerrainGrid,
+    from: Vec3<f32>,
+    to: Vec3<f32>,
+    is_hit: impl FnMut(&Block) -> bool,
+) -> Option<TerrainHit> {
+    let dir = (to - from).try_normalized()?;
+    // A ray crosses at most one block boundary per block it travels along each
+    // axis
+    let max_iter = (to - from).map(f32::abs).sum() as usize + 3;
+    let mut block_pos = None;
+    let (distance, block) = Ray::<_, _, fn(&Block, Vec3<i32>)>::new(terrain, from, to, is_hit)
+        .max_iter(max_iter)
+        .for_each(|_, pos| block_pos = Some(pos))
+        .cast();
+    let block = *block.ok()??;
+    Some(TerrainHit {
+        distance,
+        pos: from + dir * distance,
+        block_pos: block_pos?,
+        block,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::terrain::{BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta};
+    use std::sync::Arc;
+
+    /// A single loaded chunk of solid rock below z = 0 and air above it
+    fn terrain() -> TerrainGrid {
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let air = Block::air(SpriteKind::Empty);
+        let mut terrain = TerrainGrid::new().unwrap();
+        terrain.insert(
+            Vec2::zero(),
+            Arc::new(TerrainChunk::new(0, rock, air, TerrainChunkMeta::void())),
+        );
+        terrain
+    }
+
+    #[test]
+    fn rays_stop_where_they_enter_the_ground() {
+        let terrain = terrain();
+        let hit = raycast_terrain(
+            &terrain,
+            Vec3::new(4.5, 4.5, 10.5),
+            Vec3::new(4.5, 4.5, -10.5),
+        )
+        .expect("Expected to hit the ground");
+        assert_eq!(hit.block_pos, Vec3::new(4, 4, -1));
+        assert!((hit.distance - 10.5).abs() < 0.01);
+        assert!(hit.pos.distance(Vec3::new(4.5, 4.5, 0.0)) < 0.01);
+        assert_eq!(
+            ground_height_at(&terrain, Vec3::new(4.5, 4.5, 10.5), 20.0),
+            Some(0.0)
+        );
+
+        // The ground is out of reach
+        assert_eq!(
+            ground_height_at(&terrain, Vec3::new(4.5, 4.5, 10.5), 5.0),
+            None
+        );
+
+        // Any kind of block can be looked for
+        let hit = raycast_terrain_until(
+            &terrain,
+            Vec3::new(4.5, 4.5, -2.5),
+            Vec3::new(4.5, 4.5, 2.5),
+            |block| !block.is_solid(),
+        )
+        .expect("Expected to hit the air");
+        assert_eq!(hit.block_pos, Vec3::new(4, 4, 0));
+        assert!(hit.pos.distance(Vec3::new(4.5, 4.5, 0.0)) < 0.01);
+    }
+
+    #[test]
+    fn unloaded_chunks_are_never_hit() {
+        let terrain = terrain();
+
+        // The chunk next to the loaded one isn't there
+        assert_eq!(
+            raycast_terrain(
+                &terrain,
+                Vec3::new(36.5, 4.5, 10.5),
+                Vec3::new(36.5, 4.5, -10.5),
+            ),
+            None
+        );
+        assert_eq!(
+            ground_height_at(&terrain, Vec3::new(36.5, 4.5, 10.5), 20.0),
+            None
+        );
+
+        // Rays leaving the loaded terrain stop there
+        assert_eq!(
+            raycast_terrain(
+                &terrain,
+                Vec3::new(28.5, 4.5, 0.5),
+                Vec3::new(40.5, 4.5, -0.5),
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use vek::*;
+use veloren_common::{
+    comp::{ControllerInputs, Ori, Pos, Vel},
+    msg::{ClientGeneral, EcsCompPacket, ServerGeneral},
+    sync::{CompSyncPackage, Uid},
+    util::Dir,
+};
+
+/// Number of entities synced in a single physics update
+const ENTITIES: u64 = 200;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // Setup: The physics components of entities scattered around a player, as
+    // sent every tick by the server.
+    let mut package = CompSyncPackage::<EcsCompPacket>::new();
+    for i in 0..ENTITIES {
+        let f = i as f32;
+        package.comp_modified(Uid(i), Pos(Vec3::new(f * 3.1, f * 1.7, 120.0 + f % 13.0)));
+        package.comp_modified(Uid(i), Vel(Vec3::new(f.sin(), f.cos(), 0.0) * 5.0));
+        package.comp_modified(Uid(i), Ori(Dir::new(Vec3::new(f.cos(), f.sin(), 0.0))));
+    }
+    let comp_sync = ServerGeneral::CompSync(package);
+    let inputs = ClientGeneral::ControllerInputs(ControllerInputs {
+        move_dir: Vec2::new(0.6, 0.8),
+        look_dir: Dir::new(Vec3::new(0.0, 1.0, -0.2).normalized()),
+        ..ControllerInputs::default()
+    });
+
+    c.bench_function("comp sync: serialize", |b| {
+        b.iter(|| black_box(bincode::serialize(black_box(&comp_sync)).unwrap()))
+    });
+
+    c.bench_function("comp sync: serialize into reused buffer", |b| {
+        let mut buf = Vec::new();
+        b.iter(|| {
+            buf.clear();
+            bincode::serialize_into(&mut buf, black_box(&comp_sync)).unwrap();
+            black_box(&buf);
+        })
+    });
+
+    c.bench_function("comp sync: deserialize", |b| {
+        let bytes = bincode::serialize(&comp_sync).unwrap();
+        b.iter(|| black_box(bincode::deserialize::<ServerGeneral>(black_box(&bytes)).unwrap()))
+    });
+
+    c.bench_function("controller inputs: serialize", |b| {
+        b.iter(|| black_box(bincode::serialize(black_box(&inputs)).unwrap()))
+    });
+
+    c.bench_function("controller inputs: serialize into reused buffer", |b| {
+        let mut buf = Vec::new();
+        b.iter(|| {
+            buf.clear();
+            bincode::serialize_into(&mut buf, black_box(&inputs)).unwrap();
+            black_box(&buf);
+        })
+    });
+
+    c.bench_function("controller inputs: deserialize", |b| {
+        let bytes = bincode::serialize(&inputs).unwrap();
+        b.iter(|| black_box(bincode::deserialize::<ClientGeneral>(black_box(&bytes)).unwrap()))
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,104 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use vek::*;
+use veloren_common::{
+    terrain::{
+        block::{Block, BlockKind},
+        ChunkEncoding, PackedChunk, SpriteKind, TerrainChunk, TerrainChunkMeta,
+    },
+    vol::*,
+};
+
+const MIN_Z: i32 = 140;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // Setup: Create a chunk resembling generated terrain, with rolling hills of
+    // rock topped by a few blocks of dirt and grass, and the odd sprite.
+    let mut chunk = TerrainChunk::new(
+        MIN_Z,
+        Block::new(BlockKind::Rock, Rgb::zero()),
+        Block::air(SpriteKind::Empty),
+        TerrainChunkMeta::void(),
+    );
+    for x in 0..TerrainChunk::RECT_SIZE.x as i32 {
+        for y in 0..TerrainChunk::RECT_SIZE.y as i32 {
+            let height = MIN_Z + 40 + ((x as f32 * 0.3).sin() * 8.0 + y as f32 * 0.5) as i32;
+            for z in MIN_Z..height {
+                let block = if z < height - 3 {
+                    Block::new(BlockKind::Rock, Rgb::new(100, 100, 100))
+                } else if z < height - 1 {
+                    Block::new(BlockKind::Earth, Rgb::new(120, 80, 40))
+                } else {
+                    Block::new(BlockKind::Grass, Rgb::new(60, 150 + (x + y) as u8, 40))
+                };
+                chunk.set(Vec3::new(x, y, z), block).unwrap();
+            }
+            if (x * 7 + y * 13) % 11 == 0 {
+                chunk
+                    .set(Vec3::new(x, y, height), Block::air(SpriteKind::LongGrass))
+                    .unwrap();
+            }
+        }
+    }
+    let packed = PackedChunk::pack(&chunk);
+    let paletted = PackedChunk::pack_with(&chunk, ChunkEncoding::Paletted);
+
+    println!(
+        "Serialized size: {} bytes as a chunk, {} bytes paletted, {} bytes run length encoded",
+        bincode::serialize(&chunk).unwrap().len(),
+        bincode::serialize(&paletted).unwrap().len(),
+        bincode::serialize(&packed).unwrap().len(),
+    );
+
+    c.bench_function("chunk: encode", |b| {
+        b.iter(|| black_box(bincode::serialize(black_box(&chunk)).unwrap()))
+    });
+
+    c.bench_function("chunk: decode", |b| {
+        let bytes = bincode::serialize(&chunk).unwrap();
+        b.iter(|| black_box(bincode::deserialize::<TerrainChunk>(black_box(&bytes)).unwrap()))
+    });
+
+    c.bench_function("packed chunk: encode", |b| {
+        b.iter(|| black_box(bincode::serialize(&PackedChunk::pack(black_box(&chunk))).unwrap()))
+    });
+
+    c.bench_function("paletted chunk: encode", |b| {
+        b.iter(|| {
+            black_box(
+                bincode::serialize(&PackedChunk::pack_with(
+                    black_box(&chunk),
+                    ChunkEncoding::Paletted,
+                ))
+                .unwrap(),
+            )
+        })
+    });
+
+    c.bench_function("paletted chunk: decode", |b| {
+        let bytes = bincode::serialize(&paletted).unwrap();
+        b.iter(|| {
+            black_box(
+                bincode::deserialize::<PackedChunk>(black_box(&bytes))
+                    .unwrap()
+                    .unpack()
+                    .unwrap(),
+            )
+        })
+    });
+
+    c.bench_function("packed chunk: decode", |b| {
+        let bytes = bincode::serialize(&packed).unwrap();
+        b.iter(|| {
+            black_box(
+                bincode::deserialize::<PackedChunk>(black_box(&bytes))
+                    .unwrap()
+                    .unpack()
+                    .unwrap(),
+            )
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,221 @@
+//! Brewing potions out of gathered plants
+//!
+//! Every ingredient has a few properties that players don't know of until they
+//! brew with it. Brewing two or more different ingredients in an empty vial
+//! makes a potion for each property shared by at least two of them, and
+//! reveals to the brewer that those ingredients have that property.
+//! Ingredients with nothing in common only make a brew that fizzles out.
+
+use crate::{
+    assets::{self, Asset},
+    comp::{item::ItemDef, AlchemyJournal, Inventory, Item},
+};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{fs::File, io::BufReader, sync::Arc};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Property {
+    /// Heals right away
+    Restoring,
+    /// Restores energy
+    Invigorating,
+    /// Heals and restores energy over time
+    Regenerating,
+    /// Strengthens melee attacks
+    Fortifying,
+    /// Poisons whoever drinks it
+    Toxic,
+}
+
+/// Why ingredients couldn't be brewed
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BrewError {
+    /// Brewing takes at least two different ingredients
+    TooFewIngredients,
+    /// The item with the given name can't be brewed
+    NotAnIngredient(String),
+    /// There is no empty vial to brew in
+    MissingVial,
+}
+
+/// The outcome of brewing a set of ingredients
+pub struct Brew<'a> {
+    /// One potion for each property shared by the ingredients, none if the
+    /// brew fizzled out
+    pub potions: Vec<Arc<ItemDef>>,
+    /// Properties of the ingredients that were revealed by brewing them, by
+    /// item definition id
+    pub revealed: Vec<(&'a str, Property)>,
+}
+
+pub struct Alchemy {
+    /// Brewing uses up one of these
+    pub vial: Arc<ItemDef>,
+    ingredients: HashMap<String, Vec<Property>>,
+    potions: HashMap<Property, Arc<ItemDef>>,
+}
+
+impl Alchemy {
+    /// Whether the item with the given definition id can be brewed
+    pub fn is_ingredient(&self, item_definition_id: &str) -> bool {
+        self.ingredients.contains_key(item_definition_id)
+    }
+
+    /// Brew the ingredients with the given item definition ids
+    pub fn brew<'a>(&self, ingredients: &[&'a str]) -> Result<Brew<'a>, BrewError> {
+        let mut distinct = ingredients.to_vec();
+        distinct.sort_unstable();
+        distinct.dedup();
+        if distinct.len() < 2 {
+            return Err(BrewError::TooFewIngredients);
+        }
+
+        let properties = distinct
+            .iter()
+            .map(|id| {
+                self.ingredients
+                    .get(*id)
+                    .map(|properties| properties.as_slice())
+                    .ok_or_else(|| BrewError::NotAnIngredient((*id).to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let shared = shared_properties(&properties);
+
+        Ok(Brew {
+            potions: shared
+                .iter()
+                .filter_map(|property| self.potions.get(property).cloned())
+                .collect(),
+            revealed: distinct
+                .iter()
+                .zip(properties)
+                .flat_map(|(id, properties)| {
+                    properties
+                        .iter()
+                        .filter(|property| shared.contains(property))
+                        .map(move |property| (*id, *property))
+                })
+                .collect(),
+        })
+    }
+
+    /// Brew the ingredients in the given inventory slots, using up one of each
+    /// and an empty vial from the inventory. The revealed properties are
+    /// written down in `journal`. Returns the potions, which still have to be
+    /// put somewhere, and how many properties weren't known before.
+    pub fn brew_from(
+        &self,
+        inv: &mut Inventory,
+        slots: &[usize],
+        journal: &mut AlchemyJournal,
+    ) -> Result<(Vec<Item>, usize), BrewError> {
+        // Only one of each kind of ingredient is used
+        let mut ingredients = Vec::<(usize, &str)>::new();
+        for slot in slots {
+            if let Some(item) = inv.get(*slot) {
+                if ingredients
+                    .iter()
+                    .all(|(_, id)| *id != item.item_definition_id())
+                {
+                    ingredients.push((*slot, item.item_definition_id()));
+                }
+            }
+        }
+        let ids = ingredients.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+        let brew = self.brew(&ids)?;
+        let vial = inv
+            .slots()
+            .iter()
+            .position(|slot| {
+                slot.as_ref()
+                    .map_or(false, |item| item.is_same_item_def(&self.vial))
+            })
+            .ok_or(BrewError::MissingVial)?;
+
+        let learned = brew
+            .revealed
+            .iter()
+            .filter(|(id, property)| journal.learn(id, *property))
+            .count();
+        let potions = brew.potions.into_iter().map(Item::new).collect();
+        let slots = ingredients
+            .iter()
+            .map(|(slot, _)| *slot)
+            .collect::<Vec<_>>();
+        inv.take(vial);
+        slots.into_iter().for_each(|slot| {
+            inv.take(slot);
+        });
+        Ok((potions, learned))
+    }
+}
+
+/// Properties that at least two of the ingredients have, in order
+fn shared_properties(ingredients: &[&[Property]]) -> Vec<Property> {
+    let mut counts = HashMap::<Property, usize>::new();
+    for properties in ingredients {
+        let mut properties = properties.to_vec();
+        properties.sort_unstable();
+        properties.dedup();
+        properties
+            .into_iter()
+            .for_each(|property| *counts.entry(property).or_default() += 1);
+    }
+    let mut shared = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= 2)
+        .map(|(property, _)| property)
+        .collect::<Vec<_>>();
+    shared.sort_unstable();
+    shared
+}
+
+/// The alchemy asset, with items referred to by their asset specifier
+#[derive(Deserialize)]
+struct RawAlchemy {
+    vial: String,
+    ingredients: HashMap<String, Vec<Property>>,
+    potions: HashMap<Property, String>,
+}
+
+impl Asset for Alchemy {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+
+    fn parse(buf_reader: BufReader<File>, _specifier: &str) -> Result<Self, assets::Error> {
+        let raw = ron::de::from_reader::<BufReader<File>, RawAlchemy>(buf_reader)
+            .map_err(assets::Error::parse_error)?;
+        Ok(Alchemy {
+            vial: ItemDef::load(&raw.vial)?,
+            ingredients: raw.ingredients,
+            potions: raw
+                .potions
+                .into_iter()
+                .map::<Result<(Property, Arc<ItemDef>), assets::Error>, _>(|(property, potion)| {
+                    Ok((property, ItemDef::load(&potion)?))
+                })
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+pub fn default_alchemy() -> Arc<Alchemy> { Alchemy::load_expect("common.alchemy") }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_shared_properties_are_brewed() {
+        use Property::*;
+        assert_eq!(
+            shared_properties(&[&[Restoring, Toxic], &[Toxic, Fortifying], &[
+                Fortifying,
+                Invigorating,
+                Invigorating
+            ],]),
+            vec![Fortifying, Toxic],
+        );
+        assert!(shared_properties(&[&[Restoring, Restoring], &[Toxic]]).is_empty());
+    }
+}
@@ -59,13 +59,30 @@ lazy_static! {
     /// The HashMap where all loaded assets are stored in.
     static ref ASSETS: RwLock<HashMap<String, Arc<dyn Any + 'static + Sync + Send>>> =
         RwLock::new(HashMap::new());
+    /// Size of the files the cached assets were parsed from, as an estimate
+    /// of the memory they take
+    static ref ASSET_SIZES: RwLock<HashMap<String, usize>> = RwLock::new(HashMap::new());
+}
+
+/// Estimated memory taken by all loaded assets, going by the size of the
+/// files they were parsed from
+pub fn cache_size() -> usize { ASSET_SIZES.read().unwrap().values().sum() }
+
+fn record_size(specifier: &str, file: &BufReader<File>) {
+    let size = file.get_ref().metadata().map_or(0, |m| m.len() as usize);
+    ASSET_SIZES
+        .write()
+        .unwrap()
+        .insert(specifier.to_owned(), size);
 }
 
 fn reload<A: Asset>(specifier: &str) -> Result<(), Error>
 where
     A::Output: Send + Sync + 'static,
 {
-    let asset = Arc::new(A::parse(load_file(specifier, A::ENDINGS)?, specifier)?);
+    let file = load_file(specifier, A::ENDINGS)?;
+    record_size(specifier, &file);
+    let asset = Arc::new(A::parse(file, specifier)?);
     let mut assets_write = ASSETS.write().unwrap();
     match assets_write.get_mut(specifier) {
         Some(a) => *a = asset,
@@ -113,10 +130,9 @@ pub trait Asset: Sized {
             Some(asset) => Ok(Arc::clone(asset).downcast()?),
             None => {
                 drop(assets_read); // Drop the asset hashmap to permit recursive loading
-                let asset = Arc::new(f(Self::parse(
-                    load_file(specifier, Self::ENDINGS)?,
-                    specifier,
-                )?));
+                let file = load_file(specifier, Self::ENDINGS)?;
+                record_size(specifier, &file);
+                let asset = Arc::new(f(Self::parse(file, specifier)?));
                 let clone = Arc::clone(&asset);
                 ASSETS.write().unwrap().insert(specifier.to_owned(), clone);
                 Ok(asset)
@@ -3,7 +3,8 @@
 use crate::comp;
 use serde::{Deserialize, Serialize};
 
-/// The limit on how many characters that a player can have
+/// The limit on how many characters that a player can have, unless their
+/// account unlocked more character slots
 pub const MAX_CHARACTERS_PER_PLAYER: usize = 8;
 pub type CharacterId = i64;
 
@@ -23,3 +24,19 @@ pub struct CharacterItem {
     pub level: usize,
     pub loadout: comp::Loadout,
 }
+
+/// Things unlocked by an account, rather than by one of its characters
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccountUnlocks {
+    /// Character slots on top of `MAX_CHARACTERS_PER_PLAYER`
+    pub extra_character_slots: usize,
+    /// Identifiers of the cosmetics all characters of the account can use
+    pub cosmetics: Vec<String>,
+}
+
+impl AccountUnlocks {
+    /// How many characters the account can have
+    pub fn character_slots(&self) -> usize {
+        MAX_CHARACTERS_PER_PLAYER + self.extra_character_slots
+    }
+}
@@ -61,3 +61,51 @@ impl Clock {
         };
     }
 }
+
+/// Splits the time passed between frames into steps of a fixed length, so that
+/// a simulation advances the same way regardless of how often it is updated.
+pub struct FixedTimestep {
+    step: Duration,
+    accumulated: Duration,
+    max_steps: u32,
+}
+
+impl FixedTimestep {
+    /// `max_steps`: Most steps taken at once. Time beyond that is dropped, so
+    /// that a simulation that can't keep up doesn't fall further and further
+    /// behind.
+    pub fn new(tps: u32, max_steps: u32) -> Self {
+        Self {
+            step: Duration::from_secs(1) / tps.max(1),
+            accumulated: Duration::default(),
+            max_steps,
+        }
+    }
+
+    pub fn step(&self) -> Duration { self.step }
+
+    /// Add the time passed since the last call, returning how many steps have
+    /// to be taken
+    pub fn advance(&mut self, dt: Duration) -> u32 {
+        self.accumulated += dt;
+        let steps = (self.accumulated.as_nanos() / self.step.as_nanos()) as u32;
+        self.accumulated -= self.step * steps;
+        steps.min(self.max_steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_timestep_carries_over_and_drops_time() {
+        let mut timestep = FixedTimestep::new(10, 3);
+        assert_eq!(timestep.advance(Duration::from_millis(150)), 1);
+        assert_eq!(timestep.advance(Duration::from_millis(60)), 1);
+        assert_eq!(timestep.advance(Duration::from_millis(20)), 0);
+        // Only the steps beyond the limit are dropped, not the remainder
+        assert_eq!(timestep.advance(Duration::from_millis(1000)), 3);
+        assert_eq!(timestep.advance(Duration::from_millis(90)), 1);
+    }
+}
@@ -5,8 +5,10 @@ use std::{
     fmt::{self, Display},
     path::Path,
     str::FromStr,
+    time::Duration,
 };
 use tracing::warn;
+use vek::*;
 
 /// Struct representing a command that a user can run from server chat.
 pub struct ChatCommandData {
@@ -33,7 +35,7 @@ impl ChatCommandData {
 }
 
 // Please keep this sorted alphabetically :-)
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum ChatCommand {
     Adminify,
     Alias,
@@ -51,6 +53,7 @@ pub enum ChatCommand {
     Group,
     Health,
     Help,
+    Home,
     JoinFaction,
     Jump,
     Kick,
@@ -58,6 +61,7 @@ pub enum ChatCommand {
     KillNpcs,
     Lantern,
     Light,
+    Location,
     MakeBlock,
     MakeSprite,
     Motd,
@@ -65,14 +69,19 @@ pub enum ChatCommand {
     Players,
     Region,
     RemoveLights,
+    RunScript,
     Say,
     SetLevel,
     SetMotd,
+    Shout,
+    Site,
     Spawn,
     Sudo,
+    Suspects,
     Tell,
     Time,
     Tp,
+    TpPos,
     Unban,
     Version,
     Waypoint,
@@ -98,6 +107,7 @@ pub static CHAT_COMMANDS: &[ChatCommand] = &[
     ChatCommand::Group,
     ChatCommand::Health,
     ChatCommand::Help,
+    ChatCommand::Home,
     ChatCommand::JoinFaction,
     ChatCommand::Jump,
     ChatCommand::Kick,
@@ -105,6 +115,7 @@ pub static CHAT_COMMANDS: &[ChatCommand] = &[
     ChatCommand::KillNpcs,
     ChatCommand::Lantern,
     ChatCommand::Light,
+    ChatCommand::Location,
     ChatCommand::MakeBlock,
     ChatCommand::MakeSprite,
     ChatCommand::Motd,
@@ -112,14 +123,19 @@ pub static CHAT_COMMANDS: &[ChatCommand] = &[
     ChatCommand::Players,
     ChatCommand::Region,
     ChatCommand::RemoveLights,
+    ChatCommand::RunScript,
     ChatCommand::Say,
     ChatCommand::SetLevel,
     ChatCommand::SetMotd,
+    ChatCommand::Shout,
+    ChatCommand::Site,
     ChatCommand::Spawn,
     ChatCommand::Sudo,
+    ChatCommand::Suspects,
     ChatCommand::Tell,
     ChatCommand::Time,
     ChatCommand::Tp,
+    ChatCommand::TpPos,
     ChatCommand::Unban,
     ChatCommand::Version,
     ChatCommand::Waypoint,
@@ -170,7 +186,7 @@ lazy_static! {
         .collect();
 
     /// List of item specifiers. Useful for tab completing
-    static ref ITEM_SPECS: Vec<String> = {
+    pub static ref ITEM_SPECS: Vec<String> = {
         let path = assets::ASSETS_PATH.join("common").join("items");
         let mut items = vec![];
         fn list_items (path: &Path, base: &Path, mut items: &mut Vec<String>) -> std::io::Result<()>{
@@ -221,7 +237,7 @@ impl ChatCommand {
             ),
             ChatCommand::Dummy => cmd(vec![], "Spawns a training dummy", Admin),
             ChatCommand::Explosion => cmd(
-                vec![Float("radius", 5.0, Required)],
+                vec![Float("radius", 5.0, Optional)],
                 "Explodes the ground around you",
                 Admin,
             ),
@@ -236,19 +252,12 @@ impl ChatCommand {
                 Admin,
             ),
             ChatCommand::GiveItem => cmd(
-                vec![
-                    Enum("item", ITEM_SPECS.clone(), Required),
-                    Integer("num", 1, Optional),
-                ],
+                vec![ItemSpec(Required), Integer("num", 1, Optional)],
                 "Give yourself some items",
                 Admin,
             ),
             ChatCommand::Goto => cmd(
-                vec![
-                    Float("x", 0.0, Required),
-                    Float("y", 0.0, Required),
-                    Float("z", 0.0, Required),
-                ],
+                vec![Position("position", Required)],
                 "Teleport to a position",
                 Admin,
             ),
@@ -267,17 +276,18 @@ impl ChatCommand {
                 "Display information about commands",
                 NoAdmin,
             ),
+            ChatCommand::Home => cmd(
+                vec![],
+                "Return to your waypoint, or to the spawn point if you have none",
+                NoAdmin,
+            ),
             ChatCommand::JoinFaction => ChatCommandData::new(
                 vec![Any("faction", Optional)],
                 "Join/leave the specified faction",
                 NoAdmin,
             ),
             ChatCommand::Jump => cmd(
-                vec![
-                    Float("x", 0.0, Required),
-                    Float("y", 0.0, Required),
-                    Float("z", 0.0, Required),
-                ],
+                vec![Position("offset", Required)],
                 "Offset your current position",
                 Admin,
             ),
@@ -311,6 +321,18 @@ impl ChatCommand {
                 "Spawn entity with light",
                 Admin,
             ),
+            ChatCommand::Location => cmd(
+                vec![
+                    Enum(
+                        "action",
+                        vec!["add".to_string(), "remove".to_string()],
+                        Required,
+                    ),
+                    Any("name", Required),
+                ],
+                "Name your current position, or remove a named location",
+                Admin,
+            ),
             ChatCommand::MakeBlock => cmd(
                 vec![Enum("block", BLOCK_KINDS.clone(), Required)],
                 "Make a block at your location",
@@ -342,6 +364,11 @@ impl ChatCommand {
                 "Send messages to everyone in your region of the world",
                 NoAdmin,
             ),
+            ChatCommand::RunScript => cmd(
+                vec![Any("script", Required), Message(Optional)],
+                "Run the commands of a script in the scripts directory of the server",
+                Admin,
+            ),
             ChatCommand::Say => cmd(
                 vec![Message(Optional)],
                 "Send messages to everyone within shouting distance",
@@ -355,6 +382,16 @@ impl ChatCommand {
             ChatCommand::SetMotd => {
                 cmd(vec![Message(Optional)], "Set the server description", Admin)
             },
+            ChatCommand::Shout => cmd(
+                vec![Message(Required)],
+                "Shout a message to everyone in a wide area, at the cost of energy",
+                NoAdmin,
+            ),
+            ChatCommand::Site => cmd(
+                vec![Message(Required)],
+                "Teleport to the town, dungeon or other site with the given name",
+                Admin,
+            ),
             ChatCommand::Spawn => cmd(
                 vec![
                     Enum("alignment", ALIGNMENTS.clone(), Required),
@@ -370,6 +407,9 @@ impl ChatCommand {
                 "Run command as if you were another player",
                 Admin,
             ),
+            ChatCommand::Suspects => {
+                cmd(vec![], "Lists online players suspected of cheating", Admin)
+            },
             ChatCommand::Tell => cmd(
                 vec![PlayerName(Required), Message(Optional)],
                 "Send a message to another player",
@@ -382,7 +422,12 @@ impl ChatCommand {
             ),
             ChatCommand::Tp => cmd(
                 vec![PlayerName(Optional)],
-                "Teleport to another player",
+                "Teleport to another player or a named location",
+                Admin,
+            ),
+            ChatCommand::TpPos => cmd(
+                vec![Position("position", Required)],
+                "Teleport to a position",
                 Admin,
             ),
             ChatCommand::Unban => cmd(
@@ -427,12 +472,14 @@ impl ChatCommand {
             ChatCommand::Health => "health",
             ChatCommand::JoinFaction => "join_faction",
             ChatCommand::Help => "help",
+            ChatCommand::Home => "home",
             ChatCommand::Jump => "jump",
             ChatCommand::Kick => "kick",
             ChatCommand::Kill => "kill",
             ChatCommand::KillNpcs => "kill_npcs",
             ChatCommand::Lantern => "lantern",
             ChatCommand::Light => "light",
+            ChatCommand::Location => "location",
             ChatCommand::MakeBlock => "make_block",
             ChatCommand::MakeSprite => "make_sprite",
             ChatCommand::Motd => "motd",
@@ -440,14 +487,19 @@ impl ChatCommand {
             ChatCommand::Players => "players",
             ChatCommand::Region => "region",
             ChatCommand::RemoveLights => "remove_lights",
+            ChatCommand::RunScript => "runscript",
             ChatCommand::Say => "say",
             ChatCommand::SetLevel => "set_level",
             ChatCommand::SetMotd => "set_motd",
+            ChatCommand::Shout => "shout",
+            ChatCommand::Site => "site",
             ChatCommand::Spawn => "spawn",
             ChatCommand::Sudo => "sudo",
+            ChatCommand::Suspects => "suspects",
             ChatCommand::Tell => "tell",
             ChatCommand::Time => "time",
             ChatCommand::Tp => "tp",
+            ChatCommand::TpPos => "tppos",
             ChatCommand::Unban => "unban",
             ChatCommand::Version => "version",
             ChatCommand::Waypoint => "waypoint",
@@ -466,6 +518,39 @@ impl ChatCommand {
         format!("{}: {}", usage, data.description)
     }
 
+    /// How long a player has to wait before using the command again
+    pub fn cooldown(&self) -> Option<Duration> {
+        match self {
+            ChatCommand::Home => Some(Duration::from_secs(300)),
+            ChatCommand::Tp | ChatCommand::TpPos => Some(Duration::from_secs(5)),
+            _ => None,
+        }
+    }
+
+    /// Parse the arguments given to the command, checking that all required
+    /// ones are there and that they have the right type. Arguments past the
+    /// ones the command takes are ignored.
+    pub fn parse_args(&self, args: &str) -> Result<Vec<Argument>, String> {
+        let mut parsed = Vec::new();
+        let mut rest = args.trim_start();
+        for spec in self.data().args.iter() {
+            if rest.is_empty() {
+                if spec.is_required() {
+                    return Err(format!("Missing argument {}", spec.usage_string()));
+                }
+                break;
+            }
+            let (word, remainder) = match spec.word_count() {
+                // These take the rest of the line
+                None => (rest, ""),
+                Some(count) => split_words(rest, count),
+            };
+            parsed.push(spec.parse(word)?);
+            rest = remainder.trim_start();
+        }
+        Ok(parsed)
+    }
+
     /// A boolean that is used to check whether the command requires
     /// administrator permissions or not.
     pub fn needs_admin(&self) -> bool { IsAdminOnly::Admin == self.data().needs_admin }
@@ -485,6 +570,8 @@ impl ChatCommand {
                 ArgumentSpec::SubCommand => "{} {/.*/}",
                 ArgumentSpec::Enum(_, _, _) => "{}",
                 ArgumentSpec::Boolean(_, _, _) => "{}",
+                ArgumentSpec::ItemSpec(_) => "{}",
+                ArgumentSpec::Position(_, _) => "{} {} {}",
             })
             .collect::<Vec<_>>()
             .join(" ")
@@ -571,9 +658,97 @@ pub enum ArgumentSpec {
     /// * suggested tab-completion
     /// * whether it's optional
     Boolean(&'static str, String, Requirement),
+    /// The argument is the specifier of an item asset
+    ItemSpec(Requirement),
+    /// The argument is a position given as three floats. The associated
+    /// values are
+    /// * label
+    /// * whether it's optional
+    Position(&'static str, Requirement),
+}
+
+/// The value of a chat command argument, parsed according to its
+/// `ArgumentSpec`
+#[derive(Clone, PartialEq, Debug)]
+pub enum Argument {
+    PlayerName(String),
+    Float(f32),
+    Integer(i32),
+    Boolean(bool),
+    Command(ChatCommand),
+    ItemSpec(String),
+    Position(Vec3<f32>),
+    /// Any other text, including messages and sub commands
+    Text(String),
 }
 
 impl ArgumentSpec {
+    pub fn is_required(&self) -> bool {
+        match self {
+            ArgumentSpec::PlayerName(req)
+            | ArgumentSpec::Float(_, _, req)
+            | ArgumentSpec::Integer(_, _, req)
+            | ArgumentSpec::Any(_, req)
+            | ArgumentSpec::Command(req)
+            | ArgumentSpec::Message(req)
+            | ArgumentSpec::Enum(_, _, req)
+            | ArgumentSpec::Boolean(_, _, req)
+            | ArgumentSpec::ItemSpec(req)
+            | ArgumentSpec::Position(_, req) => &Requirement::Required == req,
+            ArgumentSpec::SubCommand => true,
+        }
+    }
+
+    /// The number of words the argument is made of, or `None` if it takes the
+    /// rest of the line
+    pub fn word_count(&self) -> Option<usize> {
+        match self {
+            ArgumentSpec::Message(_) | ArgumentSpec::SubCommand => None,
+            ArgumentSpec::Position(_, _) => Some(3),
+            _ => Some(1),
+        }
+    }
+
+    /// Parse the value of an argument of this kind
+    pub fn parse(&self, word: &str) -> Result<Argument, String> {
+        let invalid = || format!("'{}' is not a valid {}", word, self.usage_string());
+        match self {
+            ArgumentSpec::PlayerName(_) => Ok(Argument::PlayerName(word.to_owned())),
+            ArgumentSpec::Float(_, _, _) => {
+                word.parse().map(Argument::Float).map_err(|_| invalid())
+            },
+            ArgumentSpec::Integer(_, _, _) => {
+                word.parse().map(Argument::Integer).map_err(|_| invalid())
+            },
+            ArgumentSpec::Boolean(_, _, _) => {
+                word.parse().map(Argument::Boolean).map_err(|_| invalid())
+            },
+            ArgumentSpec::Command(_) => word.parse().map(Argument::Command).map_err(|_| invalid()),
+            ArgumentSpec::ItemSpec(_) => {
+                if ITEM_SPECS.iter().any(|spec| spec == word) {
+                    Ok(Argument::ItemSpec(word.to_owned()))
+                } else {
+                    Err(invalid())
+                }
+            },
+            ArgumentSpec::Position(_, _) => {
+                let coords = word
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<Vec<f32>, _>>()
+                    .map_err(|_| invalid())?;
+                match coords[..] {
+                    [x, y, z] => Ok(Argument::Position(Vec3::new(x, y, z))),
+                    _ => Err(invalid()),
+                }
+            },
+            ArgumentSpec::Any(_, _)
+            | ArgumentSpec::Message(_)
+            | ArgumentSpec::SubCommand
+            | ArgumentSpec::Enum(_, _, _) => Ok(Argument::Text(word.to_owned())),
+        }
+    }
+
     pub fn usage_string(&self) -> String {
         match self {
             ArgumentSpec::PlayerName(req) => {
@@ -633,6 +808,66 @@ impl ArgumentSpec {
                     format!("[{}]", label)
                 }
             },
+            ArgumentSpec::ItemSpec(req) => {
+                if &Requirement::Required == req {
+                    "<item>".to_string()
+                } else {
+                    "[item]".to_string()
+                }
+            },
+            ArgumentSpec::Position(label, req) => {
+                if &Requirement::Required == req {
+                    format!("<{}: x y z>", label)
+                } else {
+                    format!("[{}: x y z]", label)
+                }
+            },
         }
     }
 }
+
+/// Split off the first `count` words of `s`, keeping the whitespace between
+/// them
+fn split_words(s: &str, count: usize) -> (&str, &str) {
+    let mut end = 0;
+    for _ in 0..count {
+        let rest = &s[end..];
+        let word = rest.trim_start();
+        let len = word.find(char::is_whitespace).unwrap_or_else(|| word.len());
+        end += rest.len() - word.len() + len;
+    }
+    s.split_at(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_command_args() {
+        assert_eq!(
+            ChatCommand::TpPos.parse_args(" 1 2.5  -3"),
+            Ok(vec![Argument::Position(Vec3::new(1.0, 2.5, -3.0))])
+        );
+        assert!(ChatCommand::TpPos.parse_args("1 two 3").is_err());
+        assert!(ChatCommand::TpPos.parse_args("1 2").is_err());
+        assert_eq!(
+            ChatCommand::Jump.parse_args("0\t0 5 ignored"),
+            Ok(vec![Argument::Position(Vec3::new(0.0, 0.0, 5.0))])
+        );
+
+        // Messages take the rest of the line
+        assert_eq!(
+            ChatCommand::Tell.parse_args("someone hello there"),
+            Ok(vec![
+                Argument::PlayerName("someone".to_owned()),
+                Argument::Text("hello there".to_owned())
+            ])
+        );
+        assert_eq!(ChatCommand::Help.parse_args(""), Ok(vec![]));
+        assert_eq!(
+            ChatCommand::Help.parse_args("/tp"),
+            Ok(vec![Argument::Command(ChatCommand::Tp)])
+        );
+    }
+}
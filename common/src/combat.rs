@@ -0,0 +1,130 @@
+use crate::{
+    comp::{DamageSource, HealthChange, HealthSource, HitModifiers},
+    sync::Uid,
+};
+use serde::{Deserialize, Serialize};
+use vek::*;
+
+/// Feedback about a fight. The server resolves damage and deaths and sends
+/// these to the clients nearby, which show them as damage numbers, in the
+/// combat log and in the kill feed.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CombatEvent {
+    /// The health of an entity changed as the result of an attack, a heal or
+    /// the environment
+    Damage {
+        pos: Vec3<f32>,
+        target: Uid,
+        change: HealthChange,
+        source: DamageSource,
+        hit: HitModifiers,
+    },
+    /// An entity with stats died, `killer` is whoever dealt the final blow
+    Kill {
+        pos: Vec3<f32>,
+        target: Uid,
+        killer: Option<Uid>,
+    },
+}
+
+/// How a player was involved in a combat event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Involvement {
+    /// The player caused the health change or death of someone else
+    Dealt,
+    /// The health of the player changed, or the player died
+    Taken,
+    /// The event happened nearby without involving the player
+    Other,
+}
+
+impl Involvement {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Involvement::Dealt => "dealt",
+            Involvement::Taken => "taken",
+            Involvement::Other => "other",
+        }
+    }
+}
+
+impl CombatEvent {
+    pub fn pos(&self) -> Vec3<f32> {
+        match self {
+            CombatEvent::Damage { pos, .. } | CombatEvent::Kill { pos, .. } => *pos,
+        }
+    }
+
+    pub fn target(&self) -> Uid {
+        match self {
+            CombatEvent::Damage { target, .. } | CombatEvent::Kill { target, .. } => *target,
+        }
+    }
+
+    /// The entity that dealt the damage, healed the target or made the kill
+    pub fn attacker(&self) -> Option<Uid> {
+        match self {
+            CombatEvent::Damage { change, .. } => match change.cause {
+                HealthSource::Healing { by } => by,
+                cause => cause.attacker(),
+            },
+            CombatEvent::Kill { killer, .. } => *killer,
+        }
+    }
+
+    pub fn involvement(&self, player: Option<Uid>) -> Involvement {
+        if player.is_some() && player == Some(self.target()) {
+            Involvement::Taken
+        } else if player.is_some() && player == self.attacker() {
+            Involvement::Dealt
+        } else {
+            Involvement::Other
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comp::Hazard;
+
+    fn damage(target: Uid, cause: HealthSource) -> CombatEvent {
+        CombatEvent::Damage {
+            pos: Vec3::zero(),
+            target,
+            change: HealthChange { amount: -10, cause },
+            source: DamageSource::Melee,
+            hit: HitModifiers::default(),
+        }
+    }
+
+    #[test]
+    fn involvement_of_the_player() {
+        let hit = damage(Uid(2), HealthSource::Attack {
+            by: Uid(1),
+            ability: None,
+        });
+        assert_eq!(hit.attacker(), Some(Uid(1)));
+        assert_eq!(hit.involvement(Some(Uid(1))), Involvement::Dealt);
+        assert_eq!(hit.involvement(Some(Uid(2))), Involvement::Taken);
+        assert_eq!(hit.involvement(Some(Uid(3))), Involvement::Other);
+        assert_eq!(hit.involvement(None), Involvement::Other);
+
+        // Healers count as the attacker of a heal
+        let heal = damage(Uid(2), HealthSource::Healing { by: Some(Uid(1)) });
+        assert_eq!(heal.involvement(Some(Uid(1))), Involvement::Dealt);
+
+        // Nobody dealt environmental damage
+        let fall = damage(Uid(2), HealthSource::Hazard(Hazard::Falling));
+        assert_eq!(fall.attacker(), None);
+        assert_eq!(fall.involvement(None), Involvement::Other);
+
+        let kill = CombatEvent::Kill {
+            pos: Vec3::zero(),
+            target: Uid(2),
+            killer: Some(Uid(1)),
+        };
+        assert_eq!(kill.involvement(Some(Uid(1))), Involvement::Dealt);
+        assert_eq!(kill.involvement(Some(Uid(2))), Involvement::Taken);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     comp::{
-        item::{armor::Protection, Item, ItemKind},
+        item::{armor::Protection, tool::AbilityMap, Item, ItemKind, ToolKind},
+        slot::ArmorSlot,
         Body, CharacterState, EnergySource, Gravity, LightEmitter, Projectile, StateUpdate,
     },
     states::{
@@ -10,10 +11,11 @@ use crate::{
     sys::character_behavior::JoinData,
 };
 use arraygen::Arraygen;
+use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
-use std::time::Duration;
+use std::{borrow::Cow, time::Duration};
 use vek::Vec3;
 
 #[derive(Copy, Clone, Hash, Eq, PartialEq, Debug, Serialize, Deserialize)]
@@ -40,7 +42,7 @@ impl From<&CharacterState> for CharacterAbilityType {
             CharacterState::BasicRanged(_) => Self::BasicRanged,
             CharacterState::Boost(_) => Self::Boost,
             CharacterState::DashMelee(data) => Self::DashMelee(data.stage_section),
-            CharacterState::BasicBlock => Self::BasicBlock,
+            CharacterState::BasicBlock(_) => Self::BasicBlock,
             CharacterState::LeapMelee(data) => Self::LeapMelee(data.stage_section),
             CharacterState::ComboMelee(data) => Self::ComboMelee(data.stage_section, data.stage),
             CharacterState::SpinMelee(data) => Self::SpinMelee(data.stage_section),
@@ -111,7 +113,15 @@ pub enum CharacterAbility {
         infinite_charge: bool,
         is_interruptible: bool,
     },
-    BasicBlock,
+    BasicBlock {
+        max_angle: f32,
+        damage_reduction: f32,
+        stability: f32,
+    },
+    Fishing {
+        cast_duration: Duration,
+        reel_window: Duration,
+    },
     Roll,
     ComboMelee {
         stage_data: Vec<combo_melee::Stage>,
@@ -209,6 +219,28 @@ pub enum CharacterAbility {
 }
 
 impl CharacterAbility {
+    /// Name of the kind of the ability, which identifies it on an item
+    /// together with the number of abilities of the same kind before it
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            CharacterAbility::BasicMelee { .. } => "BasicMelee",
+            CharacterAbility::BasicRanged { .. } => "BasicRanged",
+            CharacterAbility::RepeaterRanged { .. } => "RepeaterRanged",
+            CharacterAbility::Boost { .. } => "Boost",
+            CharacterAbility::DashMelee { .. } => "DashMelee",
+            CharacterAbility::BasicBlock { .. } => "BasicBlock",
+            CharacterAbility::Fishing { .. } => "Fishing",
+            CharacterAbility::Roll => "Roll",
+            CharacterAbility::ComboMelee { .. } => "ComboMelee",
+            CharacterAbility::LeapMelee { .. } => "LeapMelee",
+            CharacterAbility::SpinMelee { .. } => "SpinMelee",
+            CharacterAbility::ChargedMelee { .. } => "ChargedMelee",
+            CharacterAbility::ChargedRanged { .. } => "ChargedRanged",
+            CharacterAbility::Shockwave { .. } => "Shockwave",
+            CharacterAbility::BasicBeam { .. } => "BasicBeam",
+        }
+    }
+
     /// Attempts to fulfill requirements, mutating `update` (taking energy) if
     /// applicable.
     pub fn requirements_paid(&self, data: &JoinData, update: &mut StateUpdate) -> bool {
@@ -258,33 +290,142 @@ impl CharacterAbility {
                 .energy
                 .try_change_by(-(*energy_cost as i32), EnergySource::Ability)
                 .is_ok(),
+            CharacterAbility::Fishing { .. } => {
+                data.physics.on_ground && data.physics.in_fluid.is_none()
+            },
             _ => true,
         }
     }
+
+    /// Multiplies the damage and healing of the ability by `power`, used to
+    /// scale the abilities of a tool to its power
+    pub fn adjusted_by_power(mut self, power: f32) -> Self {
+        use CharacterAbility::*;
+        let scale_u32 = |x: &mut u32| *x = (*x as f32 * power) as u32;
+        match &mut self {
+            BasicMelee {
+                base_healthchange, ..
+            } => *base_healthchange = (*base_healthchange as f32 * power) as i32,
+            BasicRanged { projectile, .. } | RepeaterRanged { projectile, .. } => {
+                *projectile = projectile.clone().adjusted_by_power(power)
+            },
+            DashMelee {
+                base_damage,
+                max_damage,
+                ..
+            } => {
+                scale_u32(base_damage);
+                scale_u32(max_damage);
+            },
+            ComboMelee { stage_data, .. } => {
+                for stage in stage_data {
+                    scale_u32(&mut stage.base_damage);
+                    scale_u32(&mut stage.max_damage);
+                    scale_u32(&mut stage.damage_increase);
+                }
+            },
+            LeapMelee { base_damage, .. } | SpinMelee { base_damage, .. } => scale_u32(base_damage),
+            ChargedMelee {
+                initial_damage,
+                max_damage,
+                ..
+            }
+            | ChargedRanged {
+                initial_damage,
+                max_damage,
+                ..
+            } => {
+                scale_u32(initial_damage);
+                scale_u32(max_damage);
+            },
+            Shockwave { damage, .. } => scale_u32(damage),
+            BasicBeam {
+                base_hps, base_dps, ..
+            } => {
+                scale_u32(base_hps);
+                scale_u32(base_dps);
+            },
+            Boost { .. } | BasicBlock { .. } | Fishing { .. } | Roll => {},
+        }
+        self
+    }
 }
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct ItemConfig {
     pub item: Item,
-    pub ability1: Option<CharacterAbility>,
-    pub ability2: Option<CharacterAbility>,
-    pub ability3: Option<CharacterAbility>,
+    /// The primary and secondary abilities, followed by the skills that can be
+    /// put on the ability hotbar
+    pub abilities: Vec<CharacterAbility>,
     pub block_ability: Option<CharacterAbility>,
     pub dodge_ability: Option<CharacterAbility>,
 }
 
+impl ItemConfig {
+    /// Index of the first ability in `abilities` that isn't bound to a mouse
+    /// button
+    pub const FIRST_SKILL: usize = 2;
+
+    pub fn primary_ability(&self) -> Option<&CharacterAbility> { self.abilities.get(0) }
+
+    /// Updates the item to the currently loaded version of its definition, and
+    /// the abilities to the currently loaded ability map, unless they were
+    /// customised, e.g. for NPCs. `previous_abilities` is the ability map the
+    /// abilities were created from. Returns whether anything changed.
+    pub fn refresh_definition(&mut self, previous_abilities: &AbilityMap) -> bool {
+        let default_abilities = match self.item.kind() {
+            ItemKind::Tool(tool) => self.abilities == tool.get_abilities_from(previous_abilities),
+            _ => false,
+        };
+        let mut changed = self.item.refresh_definition();
+        if let ItemKind::Tool(tool) = self.item.kind() {
+            let abilities = tool.get_abilities();
+            if default_abilities && abilities != self.abilities {
+                self.abilities = abilities;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    pub fn secondary_ability(&self) -> Option<&CharacterAbility> { self.abilities.get(1) }
+
+    pub fn ability(&self, index: usize) -> Option<&CharacterAbility> { self.abilities.get(index) }
+
+    /// The id of the ability with the given index
+    pub fn ability_id(&self, index: usize) -> Option<AbilityId> {
+        let kind = self.abilities.get(index)?.kind_name();
+        let nth = self.abilities[..index]
+            .iter()
+            .filter(|ability| ability.kind_name() == kind)
+            .count();
+        Some(AbilityId {
+            kind: kind.to_owned(),
+            nth,
+        })
+    }
+
+    /// The ability with the given id, if the item has it
+    pub fn ability_by_id(&self, id: &AbilityId) -> Option<&CharacterAbility> {
+        self.abilities
+            .iter()
+            .filter(|ability| ability.kind_name() == id.kind)
+            .nth(id.nth)
+    }
+
+    /// The ids of the abilities that can be put on the ability hotbar
+    pub fn skill_ids(&self) -> impl Iterator<Item = AbilityId> + '_ {
+        (Self::FIRST_SKILL..self.abilities.len()).filter_map(move |index| self.ability_id(index))
+    }
+}
+
 impl From<Item> for ItemConfig {
     fn from(item: Item) -> Self {
         if let ItemKind::Tool(tool) = &item.kind() {
-            let mut abilities = tool.get_abilities();
-            let mut ability_drain = abilities.drain(..);
-
             return ItemConfig {
+                abilities: tool.get_abilities(),
                 item,
-                ability1: ability_drain.next(),
-                ability2: ability_drain.next(),
-                ability3: ability_drain.next(),
-                block_ability: Some(CharacterAbility::BasicBlock),
+                block_ability: Some(tool.block_ability()),
                 dodge_ability: Some(CharacterAbility::Roll),
             };
         }
@@ -293,6 +434,115 @@ impl From<Item> for ItemConfig {
     }
 }
 
+/// Number of slots on the ability hotbar
+pub const ABILITY_SLOTS: usize = 5;
+
+/// Refers to an ability of an item by its kind, and by the number of abilities
+/// of the same kind before it on the item. Unlike an index, it keeps referring
+/// to the same ability when the abilities of an item are reordered, and to the
+/// matching ability when another item is wielded.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct AbilityId {
+    /// See [`CharacterAbility::kind_name`]
+    pub kind: String,
+    pub nth: usize,
+}
+
+/// Which ability of the active item each slot of a character's ability hotbar
+/// uses. Slots without an ability use the skill of the active item at the same
+/// position.
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub struct AbilityHotbar {
+    pub slots: [Option<AbilityId>; ABILITY_SLOTS],
+}
+
+impl AbilityHotbar {
+    /// The ability of `item` in the given slot, if any
+    pub fn ability<'a>(&self, slot: usize, item: &'a ItemConfig) -> Option<&'a CharacterAbility> {
+        match self.slots.get(slot)? {
+            Some(id) => item.ability_by_id(id),
+            None => item.ability(ItemConfig::FIRST_SKILL + slot),
+        }
+    }
+
+    /// Put an ability in a slot, or give the slot back to the skill of the
+    /// active item with `None`. Returns `false` if there's no such slot.
+    pub fn assign(&mut self, slot: usize, ability: Option<AbilityId>) -> bool {
+        match self.slots.get_mut(slot) {
+            Some(s) => {
+                *s = ability;
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+impl Component for AbilityHotbar {
+    type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
+}
+
+/// How the active item and the second item of a loadout are wielded together
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pairing {
+    /// Two one-handed weapons, striking in turn
+    DualWield,
+    /// A weapon with a shield in the offhand to block with
+    Shield,
+}
+
+impl Pairing {
+    /// How `main` can be wielded together with `offhand`, or `None` if they
+    /// can't be
+    pub fn of(main: &ToolKind, offhand: &ToolKind) -> Option<Self> {
+        match (main, offhand) {
+            (ToolKind::Dagger(_), ToolKind::Dagger(_)) => Some(Pairing::DualWield),
+            (ToolKind::Sword(_), ToolKind::Shield(_))
+            | (ToolKind::Axe(_), ToolKind::Shield(_))
+            | (ToolKind::Dagger(_), ToolKind::Shield(_)) => Some(Pairing::Shield),
+            _ => None,
+        }
+    }
+}
+
+fn tool_kind(item: &Option<ItemConfig>) -> Option<&ToolKind> {
+    match item.as_ref().map(|i| i.item.kind()) {
+        Some(ItemKind::Tool(tool)) => Some(&tool.kind),
+        _ => None,
+    }
+}
+
+/// A stage of a combo made of the given melee attack
+fn combo_stage(stage: u32, ability: &CharacterAbility) -> Option<combo_melee::Stage> {
+    match ability {
+        CharacterAbility::BasicMelee {
+            buildup_duration,
+            recover_duration,
+            base_healthchange,
+            knockback,
+            range,
+            max_angle,
+            energy_cost: _,
+        } => {
+            let damage = (-*base_healthchange).max(0) as u32;
+            Some(combo_melee::Stage {
+                stage,
+                base_damage: damage,
+                max_damage: damage * 3 / 2,
+                damage_increase: damage / 10,
+                knockback: *knockback,
+                range: *range,
+                angle: *max_angle,
+                base_buildup_duration: *buildup_duration,
+                base_swing_duration: Duration::from_millis(100),
+                base_recover_duration: *recover_duration,
+                forward_movement: 0.0,
+            })
+        },
+        _ => None,
+    }
+}
+
 #[derive(Arraygen, Clone, PartialEq, Default, Debug, Serialize, Deserialize)]
 #[gen_array(pub fn get_armor: &Option<Item>)]
 pub struct Loadout {
@@ -324,9 +574,102 @@ pub struct Loadout {
     pub head: Option<Item>,
     #[in_array(get_armor)]
     pub tabard: Option<Item>,
+
+    /// What others see in armor slots instead of the equipped armor
+    #[serde(default)]
+    pub appearance: HashMap<ArmorSlot, Appearance>,
+}
+
+/// What is shown in an armor slot instead of the item equipped in it
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Appearance {
+    /// Nothing is shown, e.g. to hide a helmet
+    Hidden,
+    /// The model of another piece of armor the player owned when picking it
+    Item(Item),
 }
 
 impl Loadout {
+    /// How the active item and the second item are wielded together, if they
+    /// can be. Otherwise the second item is only kept to swap to.
+    pub fn pairing(&self) -> Option<Pairing> {
+        match (tool_kind(&self.active_item), tool_kind(&self.second_item)) {
+            (Some(main), Some(offhand)) => Pairing::of(main, offhand),
+            _ => None,
+        }
+    }
+
+    /// The armor equipped in a slot
+    pub fn armor(&self, slot: ArmorSlot) -> Option<&Item> {
+        match slot {
+            ArmorSlot::Head => self.head.as_ref(),
+            ArmorSlot::Neck => self.neck.as_ref(),
+            ArmorSlot::Shoulders => self.shoulder.as_ref(),
+            ArmorSlot::Chest => self.chest.as_ref(),
+            ArmorSlot::Hands => self.hand.as_ref(),
+            ArmorSlot::Ring => self.ring.as_ref(),
+            ArmorSlot::Back => self.back.as_ref(),
+            ArmorSlot::Belt => self.belt.as_ref(),
+            ArmorSlot::Legs => self.pants.as_ref(),
+            ArmorSlot::Feet => self.foot.as_ref(),
+            ArmorSlot::Tabard => self.tabard.as_ref(),
+        }
+    }
+
+    /// The armor to show in a slot, taking the appearance picked for it into
+    /// account. Empty slots always look empty.
+    pub fn shown_armor(&self, slot: ArmorSlot) -> Option<&Item> {
+        let equipped = self.armor(slot)?;
+        match self.appearance.get(&slot) {
+            None => Some(equipped),
+            Some(Appearance::Hidden) => None,
+            Some(Appearance::Item(item)) => Some(item),
+        }
+    }
+
+    /// The second item, if it's held in the offhand
+    pub fn offhand(&self) -> Option<&ItemConfig> { self.pairing().and(self.second_item.as_ref()) }
+
+    /// The ability of the primary input. Dual wielded weapons take turns, as
+    /// the stages of a combo.
+    pub fn primary_ability(&self) -> Option<Cow<'_, CharacterAbility>> {
+        let main = self.active_item.as_ref()?.primary_ability()?;
+        if self.pairing() == Some(Pairing::DualWield) {
+            let combo = self
+                .offhand()
+                .and_then(|offhand| offhand.primary_ability())
+                .and_then(|offhand| {
+                    Some(CharacterAbility::ComboMelee {
+                        stage_data: vec![combo_stage(1, main)?, combo_stage(2, offhand)?],
+                        initial_energy_gain: 0,
+                        max_energy_gain: 50,
+                        energy_increase: 10,
+                        speed_increase: 0.05,
+                        max_speed_increase: 1.5,
+                        is_interruptible: true,
+                    })
+                });
+            if let Some(combo) = combo {
+                return Some(Cow::Owned(combo));
+            }
+        }
+        Some(Cow::Borrowed(main))
+    }
+
+    /// The ability of the secondary input, which belongs to the offhand item
+    /// when there is one
+    pub fn secondary_ability(&self) -> Option<&CharacterAbility> {
+        match self.offhand() {
+            Some(offhand) => offhand.secondary_ability(),
+            None => self.active_item.as_ref()?.secondary_ability(),
+        }
+    }
+
+    /// Movement speed multiplier from the weapon being wielded
+    pub fn wield_speed_modifier(&self) -> f32 {
+        tool_kind(&self.active_item).map_or(1.0, |kind| kind.hands().speed_modifier())
+    }
+
     pub fn get_damage_reduction(&self) -> f32 {
         let protection = self
             .get_armor()
@@ -436,7 +779,27 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
                 stage_section: StageSection::Buildup,
                 exhausted: false,
             }),
-            CharacterAbility::BasicBlock => CharacterState::BasicBlock,
+            CharacterAbility::BasicBlock {
+                max_angle,
+                damage_reduction,
+                stability,
+            } => CharacterState::BasicBlock(basic_block::Data {
+                max_angle: *max_angle,
+                damage_reduction: *damage_reduction,
+                stability: *stability,
+            }),
+            CharacterAbility::Fishing {
+                cast_duration,
+                reel_window,
+            } => CharacterState::Fishing(fishing::Data {
+                static_data: fishing::StaticData {
+                    cast_duration: *cast_duration,
+                    reel_window: *reel_window,
+                },
+                stage: fishing::Stage::Casting,
+                timer: Duration::default(),
+                released: false,
+            }),
             CharacterAbility::Roll => CharacterState::Roll(roll::Data {
                 remaining_duration: Duration::from_millis(500),
                 was_wielded: false, // false by default. utils might set it to true
@@ -692,3 +1055,91 @@ impl From<(&CharacterAbility, AbilityKey)> for CharacterState {
 impl Component for Loadout {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LoadoutBuilder;
+
+    #[test]
+    fn hotbar_slots_follow_their_ability() {
+        let mut bow =
+            LoadoutBuilder::default_item_config_from_str("common.items.weapons.bow.starter_bow");
+        let mut hotbar = AbilityHotbar::default();
+        // Unassigned slots use the skills of the item in order
+        assert_eq!(
+            hotbar.ability(0, &bow),
+            bow.ability(ItemConfig::FIRST_SKILL)
+        );
+
+        let last = bow.abilities.len() - 1;
+        let id = bow.ability_id(last).unwrap();
+        assert!(hotbar.assign(0, Some(id.clone())));
+        assert!(!hotbar.assign(ABILITY_SLOTS, Some(id)));
+        let assigned = bow.abilities[last].clone();
+        assert_eq!(hotbar.ability(0, &bow), Some(&assigned));
+
+        // The slot keeps its ability when the abilities of the item are reordered
+        bow.abilities.swap(0, last);
+        assert_eq!(hotbar.ability(0, &bow), Some(&assigned));
+
+        // Items without the ability leave the slot empty
+        let farming = LoadoutBuilder::default_item_config_from_str("common.items.weapons.tool.hoe");
+        assert!(farming
+            .abilities
+            .iter()
+            .all(|ability| ability.kind_name() != assigned.kind_name()));
+        assert_eq!(hotbar.ability(0, &farming), None);
+
+        assert!(hotbar.assign(0, None));
+        assert_eq!(hotbar, AbilityHotbar::default());
+    }
+
+    #[test]
+    fn abilities_of_the_same_kind_get_their_own_ids() {
+        let mut item = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.dagger.starter_dagger",
+        );
+        item.abilities = vec![CharacterAbility::Roll, CharacterAbility::Roll];
+        let second = item.ability_id(1).unwrap();
+        assert_eq!(second, AbilityId {
+            kind: "Roll".to_owned(),
+            nth: 1,
+        });
+        assert_eq!(item.ability_by_id(&second), item.ability(1));
+        assert_eq!(
+            item.ability_by_id(&AbilityId {
+                kind: "Roll".to_owned(),
+                nth: 2,
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn dual_wielded_daggers_strike_in_turn() {
+        let dagger = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.dagger.starter_dagger",
+        );
+        let sword = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.sword.zweihander_sword_0",
+        );
+
+        let loadout = LoadoutBuilder::new()
+            .active_item(Some(dagger.clone()))
+            .second_item(Some(dagger.clone()))
+            .build();
+        assert_eq!(loadout.pairing(), Some(Pairing::DualWield));
+        assert!(matches!(
+            loadout.primary_ability().as_deref(),
+            Some(CharacterAbility::ComboMelee { stage_data, .. }) if stage_data.len() == 2
+        ));
+
+        let loadout = LoadoutBuilder::new()
+            .active_item(Some(sword))
+            .second_item(Some(dagger))
+            .build();
+        assert_eq!(loadout.pairing(), None);
+        assert!(loadout.offhand().is_none());
+    }
+}
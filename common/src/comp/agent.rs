@@ -2,6 +2,7 @@ use crate::{
     comp::{humanoid, quadruped_low, quadruped_medium, quadruped_small, Body},
     path::Chaser,
     sync::Uid,
+    time::DayPeriod,
 };
 use specs::{Component, Entity as EcsEntity};
 use specs_idvs::IdvStorage;
@@ -141,6 +142,10 @@ pub struct Agent {
     // TODO move speech patterns into a Behavior component
     pub can_speak: bool,
     pub psyche: Psyche,
+    /// Time at which the agent last said something
+    pub last_bark: Option<f64>,
+    /// Daily routine of villagers, overriding the patrol origin
+    pub schedule: Option<Schedule>,
 }
 
 impl Agent {
@@ -149,6 +154,26 @@ impl Agent {
         self
     }
 
+    pub fn with_schedule(mut self, schedule: Option<Schedule>) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Whether the agent can say something at `time` without repeating itself
+    /// too often. If so, the time is recorded.
+    pub fn try_bark(&mut self, time: f64) -> bool {
+        if self.can_speak
+            && self
+                .last_bark
+                .map_or(true, |last| time - last > BARK_COOLDOWN)
+        {
+            self.last_bark = Some(time);
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn new(origin: Vec3<f32>, can_speak: bool, body: &Body) -> Self {
         let patrol_origin = Some(origin);
         Agent {
@@ -164,6 +189,73 @@ impl Component for Agent {
     type Storage = IdvStorage<Self>;
 }
 
+/// What a scheduled agent is doing at some time of day
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Routine {
+    /// Wander around the place of work
+    Work,
+    /// Stay around the house with the lantern lit
+    Rest,
+    /// Sit still at home in the dark
+    Sleep,
+}
+
+impl From<DayPeriod> for Routine {
+    fn from(day_period: DayPeriod) -> Self {
+        match day_period {
+            DayPeriod::Morning | DayPeriod::Noon => Routine::Work,
+            DayPeriod::Evening => Routine::Rest,
+            DayPeriod::Night => Routine::Sleep,
+        }
+    }
+}
+
+/// The places a villager spends its day at
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Schedule {
+    pub home: Vec3<f32>,
+    pub work: Vec3<f32>,
+}
+
+impl Schedule {
+    pub fn new(home: Vec3<f32>, work: Vec3<f32>) -> Self { Self { home, work } }
+
+    pub fn routine(&self, time_of_day: f64) -> Routine { DayPeriod::from(time_of_day).into() }
+
+    /// Where the agent should be while following the given routine
+    pub fn place(&self, routine: Routine) -> Vec3<f32> {
+        match routine {
+            Routine::Work => self.work,
+            Routine::Rest | Routine::Sleep => self.home,
+        }
+    }
+}
+
+/// Minimum time between two lines said by the same agent, in seconds
+const BARK_COOLDOWN: f64 = 20.0;
+
+/// Situations in which an agent says something. Each has its own set of
+/// localized lines, one of which is picked at random.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Bark {
+    /// A player came close to an idle villager
+    Greeting,
+    /// An enemy is about to fight
+    CombatTaunt,
+    /// A villager got hit
+    UnderAttack,
+}
+
+impl Bark {
+    pub fn i18n_key(self) -> &'static str {
+        match self {
+            Bark::Greeting => "npc.speech.villager_greeting",
+            Bark::CombatTaunt => "npc.speech.enemy_taunt",
+            Bark::UnderAttack => "npc.speech.villager_under_attack",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Activity {
     Idle(Vec2<f32>),
@@ -178,12 +270,18 @@ pub enum Activity {
         been_close: bool,
         powerup: f32,
     },
+    /// Walk back to the patrol origin after wandering or chasing too far away
+    ReturnHome {
+        chaser: Chaser,
+    },
 }
 
 impl Activity {
     pub fn is_follow(&self) -> bool { matches!(self, Activity::Follow { .. }) }
 
     pub fn is_attack(&self) -> bool { matches!(self, Activity::Attack { .. }) }
+
+    pub fn is_return_home(&self) -> bool { matches!(self, Activity::ReturnHome { .. }) }
 }
 
 impl Default for Activity {
@@ -0,0 +1,37 @@
+use crate::alchemy::Property;
+use serde::{Deserialize, Serialize};
+use specs::{Component, FlaggedStorage};
+use specs_idvs::IdvStorage;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The properties of alchemy ingredients a character found out about by
+/// brewing them
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlchemyJournal {
+    /// Known properties by item definition id of the ingredient
+    known: BTreeMap<String, BTreeSet<Property>>,
+}
+
+impl AlchemyJournal {
+    /// Record that an ingredient has a property, returning whether it wasn't
+    /// known before
+    pub fn learn(&mut self, item_definition_id: &str, property: Property) -> bool {
+        self.known
+            .entry(item_definition_id.to_string())
+            .or_default()
+            .insert(property)
+    }
+
+    /// Properties of the ingredient with the given item definition id found
+    /// out about so far
+    pub fn known(&self, item_definition_id: &str) -> impl Iterator<Item = Property> + '_ {
+        self.known
+            .get(item_definition_id)
+            .into_iter()
+            .flat_map(|properties| properties.iter().copied())
+    }
+}
+
+impl Component for AlchemyJournal {
+    type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
+}
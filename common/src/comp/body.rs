@@ -135,6 +135,11 @@ impl<
 impl Body {
     pub fn is_humanoid(&self) -> bool { matches!(self, Body::Humanoid(_)) }
 
+    /// Bodies only found on bosses, which get a poise bar and boss music
+    pub fn is_boss(&self) -> bool {
+        matches!(self, Body::BipedLarge(_) | Body::Golem(_) | Body::Dragon(_))
+    }
+
     // Note: this might need to be refined to something more complex for realistic
     // behavior with less cylindrical bodies (e.g. wolfs)
     pub fn radius(&self) -> f32 {
@@ -530,6 +535,54 @@ impl Body {
         }
     }
 
+    /// How well the body keeps its footing when hit, from 0 (flinches at every
+    /// hit) to 1 (never flinches)
+    pub fn base_stability(&self) -> f32 {
+        match self {
+            Body::QuadrupedLow(quadruped_low) => match quadruped_low.species {
+                quadruped_low::Species::Tortoise => 0.6,
+                quadruped_low::Species::Rocksnapper => 0.6,
+                _ => 0.2,
+            },
+            Body::QuadrupedMedium(quadruped_medium) => match quadruped_medium.species {
+                quadruped_medium::Species::Tarasque => 0.6,
+                quadruped_medium::Species::Catoblepas => 0.5,
+                _ => 0.2,
+            },
+            Body::BipedLarge(_) => 0.5,
+            Body::Golem(_) => 0.8,
+            Body::Dragon(_) => 0.9,
+            Body::Object(_) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Poise damage the body takes before it is staggered, see
+    /// [`Poise`](super::Poise)
+    pub fn base_poise(&self) -> u32 {
+        match self {
+            Body::Humanoid(_) => 100,
+            Body::QuadrupedSmall(_) => 40,
+            Body::QuadrupedMedium(quadruped_medium) => match quadruped_medium.species {
+                quadruped_medium::Species::Tarasque => 300,
+                quadruped_medium::Species::Catoblepas => 250,
+                _ => 100,
+            },
+            Body::QuadrupedLow(quadruped_low) => match quadruped_low.species {
+                quadruped_low::Species::Tortoise => 250,
+                quadruped_low::Species::Rocksnapper => 300,
+                _ => 100,
+            },
+            Body::BirdMedium(_) => 50,
+            Body::BirdSmall(_) | Body::FishSmall(_) | Body::FishMedium(_) => 30,
+            Body::Theropod(_) => 80,
+            Body::BipedLarge(_) => 400,
+            Body::Golem(_) => 600,
+            Body::Dragon(_) => 2000,
+            Body::Object(_) => 0,
+        }
+    }
+
     pub fn default_light_offset(&self) -> Vec3<f32> {
         // TODO: Make this a manifest
         match self {
@@ -0,0 +1,184 @@
+use crate::sync::Uid;
+use serde::{Deserialize, Serialize};
+use specs::{Component, FlaggedStorage};
+use specs_idvs::IdvStorage;
+
+/// Number of times a buff of the same kind can stack
+pub const MAX_BUFF_STACKS: u32 = 5;
+/// Lowest fraction of the movement speed that slows can leave an entity with
+const MIN_SPEED_MODIFIER: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BuffKind {
+    /// Loses health over time
+    Poison,
+    /// Regains health and energy over time
+    Regeneration,
+    /// Moves slower
+    Slow,
+    /// Loses health over time, put out by water
+    Burning,
+    /// Deals more melee damage
+    Strength,
+}
+
+impl BuffKind {
+    /// Whether the buff is harmful to the entity it is applied to
+    pub fn is_debuff(self) -> bool {
+        match self {
+            BuffKind::Poison | BuffKind::Slow | BuffKind::Burning => true,
+            BuffKind::Regeneration | BuffKind::Strength => false,
+        }
+    }
+}
+
+/// A buff as applied by abilities and consumables, before it is placed on an
+/// entity
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BuffData {
+    pub kind: BuffKind,
+    /// Strength of a single stack: health per second for poison, burning and
+    /// regeneration, the fraction of movement speed lost for slow and the
+    /// fraction of damage gained for strength
+    pub strength: f32,
+    /// Duration in seconds
+    pub duration: f32,
+}
+
+/// A timed effect on an entity
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Buff {
+    pub data: BuffData,
+    pub stacks: u32,
+    /// Seconds until the buff wears off
+    pub time_left: f32,
+    /// Entity that applied the buff last
+    pub source: Option<Uid>,
+    /// Seconds since the periodic effects were last applied
+    #[serde(skip)]
+    pub tick_timer: f32,
+}
+
+impl Buff {
+    /// Change in health per second caused by all stacks of this buff
+    pub fn health_rate(&self) -> f32 {
+        let per_stack = match self.data.kind {
+            BuffKind::Poison | BuffKind::Burning => -self.data.strength,
+            BuffKind::Regeneration => self.data.strength,
+            BuffKind::Slow | BuffKind::Strength => 0.0,
+        };
+        per_stack * self.stacks as f32
+    }
+
+    /// Change in energy per second caused by all stacks of this buff
+    pub fn energy_rate(&self) -> f32 {
+        match self.data.kind {
+            BuffKind::Regeneration => self.data.strength * self.stacks as f32,
+            _ => 0.0,
+        }
+    }
+}
+
+/// All buffs currently applied to an entity, at most one per kind
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Buffs {
+    buffs: Vec<Buff>,
+}
+
+impl Buffs {
+    /// Applies a buff, adding a stack and refreshing the duration if one of
+    /// the same kind is already present
+    pub fn add(&mut self, data: BuffData, source: Option<Uid>) {
+        if let Some(buff) = self.buffs.iter_mut().find(|b| b.data.kind == data.kind) {
+            buff.stacks = (buff.stacks + 1).min(MAX_BUFF_STACKS);
+            buff.time_left = buff.time_left.max(data.duration);
+            buff.data.strength = buff.data.strength.max(data.strength);
+            buff.data.duration = buff.data.duration.max(data.duration);
+            buff.source = source.or(buff.source);
+        } else {
+            self.buffs.push(Buff {
+                data,
+                stacks: 1,
+                time_left: data.duration,
+                source,
+                tick_timer: 0.0,
+            });
+        }
+    }
+
+    pub fn remove(&mut self, kind: BuffKind) { self.buffs.retain(|b| b.data.kind != kind); }
+
+    /// Removes buffs that ran out, returning whether there were any
+    pub fn remove_expired(&mut self) -> bool {
+        let len = self.buffs.len();
+        self.buffs.retain(|b| b.time_left > 0.0);
+        self.buffs.len() != len
+    }
+
+    pub fn clear(&mut self) { self.buffs.clear(); }
+
+    pub fn is_empty(&self) -> bool { self.buffs.is_empty() }
+
+    pub fn get(&self, kind: BuffKind) -> Option<&Buff> {
+        self.buffs.iter().find(|b| b.data.kind == kind)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Buff> { self.buffs.iter() }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Buff> { self.buffs.iter_mut() }
+
+    /// Factor applied to the movement speed
+    pub fn speed_modifier(&self) -> f32 {
+        self.get(BuffKind::Slow).map_or(1.0, |b| {
+            (1.0 - b.data.strength * b.stacks as f32).max(MIN_SPEED_MODIFIER)
+        })
+    }
+
+    /// Factor applied to the melee damage dealt
+    pub fn damage_modifier(&self) -> f32 {
+        self.get(BuffKind::Strength)
+            .map_or(1.0, |b| 1.0 + b.data.strength * b.stacks as f32)
+    }
+}
+
+impl Component for Buffs {
+    type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buff(kind: BuffKind, strength: f32, duration: f32) -> BuffData {
+        BuffData {
+            kind,
+            strength,
+            duration,
+        }
+    }
+
+    #[test]
+    fn buffs_of_the_same_kind_stack() {
+        let mut buffs = Buffs::default();
+        for _ in 0..MAX_BUFF_STACKS + 2 {
+            buffs.add(buff(BuffKind::Poison, 10.0, 5.0), None);
+        }
+        buffs.add(buff(BuffKind::Poison, 5.0, 8.0), None);
+
+        let poison = buffs.get(BuffKind::Poison).unwrap();
+        assert_eq!(buffs.iter().count(), 1);
+        assert_eq!(poison.stacks, MAX_BUFF_STACKS);
+        assert!((poison.time_left - 8.0).abs() < f32::EPSILON);
+        assert!((poison.health_rate() + 10.0 * MAX_BUFF_STACKS as f32).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn slows_never_stop_movement() {
+        let mut buffs = Buffs::default();
+        assert!((buffs.speed_modifier() - 1.0).abs() < f32::EPSILON);
+        for _ in 0..MAX_BUFF_STACKS {
+            buffs.add(buff(BuffKind::Slow, 0.5, 5.0), None);
+        }
+        assert!((buffs.speed_modifier() - MIN_SPEED_MODIFIER).abs() < f32::EPSILON);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    comp::{Energy, Ori, Pos, Vel},
+    comp::{Energy, Ori, Pos, Sound, Vel},
     event::{LocalEvent, ServerEvent},
     states::*,
     sys::character_behavior::JoinData,
@@ -19,6 +19,7 @@ pub struct StateUpdate {
     pub swap_loadout: bool,
     pub local_events: VecDeque<LocalEvent>,
     pub server_events: VecDeque<ServerEvent>,
+    pub sounds: VecDeque<Sound>,
 }
 
 impl From<&JoinData<'_>> for StateUpdate {
@@ -32,6 +33,7 @@ impl From<&JoinData<'_>> for StateUpdate {
             character: data.character.clone(),
             local_events: VecDeque::new(),
             server_events: VecDeque::new(),
+            sounds: VecDeque::new(),
         }
     }
 }
@@ -45,7 +47,7 @@ pub enum CharacterState {
     Glide,
     GlideWield,
     /// A basic blocking state
-    BasicBlock,
+    BasicBlock(basic_block::Data),
     /// Player is busy equipping or unequipping weapons
     Equipping(equipping::Data),
     /// Player is holding a weapon and can perform other actions
@@ -78,6 +80,12 @@ pub enum CharacterState {
     /// A continuous attack that affects all creatures in a cone originating
     /// from the source
     BasicBeam(basic_beam::Data),
+    /// Briefly recoiling from a light hit
+    Flinch(flinch::Data),
+    /// Reeling from a heavy hit or a broken guard, unable to act
+    Stagger(stagger::Data),
+    /// Waiting for a fish to bite after casting a line
+    Fishing(fishing::Data),
 }
 
 impl CharacterState {
@@ -88,7 +96,7 @@ impl CharacterState {
             | CharacterState::BasicRanged(_)
             | CharacterState::DashMelee(_)
             | CharacterState::ComboMelee(_)
-            | CharacterState::BasicBlock
+            | CharacterState::BasicBlock(_)
             | CharacterState::LeapMelee(_)
             | CharacterState::SpinMelee(_)
             | CharacterState::ChargedMelee(_)
@@ -96,6 +104,7 @@ impl CharacterState {
             | CharacterState::RepeaterRanged(_)
             | CharacterState::Shockwave(_)
             | CharacterState::BasicBeam(_)
+            | CharacterState::Fishing(_)
         )
     }
 
@@ -121,7 +130,7 @@ impl CharacterState {
             | CharacterState::BasicRanged(_)
             | CharacterState::DashMelee(_)
             | CharacterState::ComboMelee(_)
-            | CharacterState::BasicBlock
+            | CharacterState::BasicBlock(_)
             | CharacterState::LeapMelee(_)
             | CharacterState::ChargedMelee(_)
             | CharacterState::ChargedRanged(_)
@@ -131,10 +140,33 @@ impl CharacterState {
         )
     }
 
-    pub fn is_block(&self) -> bool { matches!(self, CharacterState::BasicBlock) }
+    pub fn is_block(&self) -> bool { matches!(self, CharacterState::BasicBlock(_)) }
+
+    /// How attacks are blocked, if blocking
+    pub fn block(&self) -> Option<&basic_block::Data> {
+        match self {
+            CharacterState::BasicBlock(data) => Some(data),
+            _ => None,
+        }
+    }
 
     pub fn is_dodge(&self) -> bool { matches!(self, CharacterState::Roll(_)) }
 
+    /// Whether the character is recoiling from a hit
+    pub fn is_hit_reaction(&self) -> bool {
+        matches!(self, CharacterState::Flinch(_) | CharacterState::Stagger(_))
+    }
+
+    /// Whether the character holds its weapon, or will again once it has
+    /// recovered from a hit
+    pub fn returns_to_wield(&self) -> bool {
+        match self {
+            CharacterState::Flinch(data) => data.was_wielded,
+            CharacterState::Stagger(data) => data.was_wielded,
+            _ => self.is_wield(),
+        }
+    }
+
     /// Compares for shallow equality (does not check internal struct equality)
     pub fn same_variant(&self, other: &Self) -> bool {
         // Check if state is the same without looking at the inner data
@@ -159,6 +191,8 @@ pub struct Attacking {
     pub applied: bool,
     pub hit_count: u32,
     pub knockback: f32,
+    /// Poise damage dealt by a hit, before it is blocked or absorbed by armor
+    pub poise_damage: u32,
 }
 
 impl Component for Attacking {
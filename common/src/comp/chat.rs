@@ -51,6 +51,7 @@ pub enum KillType {
     Projectile,
     Explosion,
     Energy,
+    Buff,
     // Projectile(String), TODO: add projectile name when available
 }
 
@@ -88,6 +89,8 @@ pub enum ChatType<G> {
     Tell(Uid, Uid),
     /// Chat with nearby players
     Say(Uid),
+    /// Shouted message, heard further than `Say`
+    Shout(Uid),
     /// Group chat
     Group(Uid, G),
     /// Factional chat
@@ -139,6 +142,7 @@ impl<G> GenericChatMsg<G> {
     pub const NPC_DISTANCE: f32 = 100.0;
     pub const REGION_DISTANCE: f32 = 1000.0;
     pub const SAY_DISTANCE: f32 = 100.0;
+    pub const SHOUT_DISTANCE: f32 = 400.0;
 
     pub fn npc(uid: Uid, message: String) -> Self {
         let chat_type = ChatType::Npc(uid, rand::random());
@@ -157,6 +161,7 @@ impl<G> GenericChatMsg<G> {
             ChatType::Kill(a, b) => ChatType::Kill(a, b),
             ChatType::Tell(a, b) => ChatType::Tell(a, b),
             ChatType::Say(a) => ChatType::Say(a),
+            ChatType::Shout(a) => ChatType::Shout(a),
             ChatType::Group(a, g) => ChatType::Group(a, f(g)),
             ChatType::Faction(a, b) => ChatType::Faction(a, b),
             ChatType::Region(a) => ChatType::Region(a),
@@ -193,6 +198,7 @@ impl<G> GenericChatMsg<G> {
             ChatType::Kill(_, _) => SpeechBubbleType::None,
             ChatType::Tell(_u, _) => SpeechBubbleType::Tell,
             ChatType::Say(_u) => SpeechBubbleType::Say,
+            ChatType::Shout(_u) => SpeechBubbleType::Say,
             ChatType::Group(_u, _s) => SpeechBubbleType::Group,
             ChatType::Faction(_u, _s) => SpeechBubbleType::Faction,
             ChatType::Region(_u) => SpeechBubbleType::Region,
@@ -214,6 +220,7 @@ impl<G> GenericChatMsg<G> {
             ChatType::Kill(_, _) => None,
             ChatType::Tell(u, _t) => Some(*u),
             ChatType::Say(u) => Some(*u),
+            ChatType::Shout(u) => Some(*u),
             ChatType::Group(u, _s) => Some(*u),
             ChatType::Faction(u, _s) => Some(*u),
             ChatType::Region(u) => Some(*u),
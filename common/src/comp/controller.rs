@@ -1,4 +1,8 @@
-use crate::{comp::inventory::slot::Slot, sync::Uid, util::Dir};
+use crate::{
+    comp::inventory::slot::{AppearanceChoice, ArmorSlot, Slot},
+    sync::Uid,
+    util::Dir,
+};
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
@@ -16,6 +20,20 @@ pub enum InventoryManip {
     Swap(Slot, Slot),
     Drop(Slot),
     CraftRecipe(String),
+    /// Merge stacks and order the items in the inventory
+    Sort,
+    /// Move items from the stack in the first inventory slot onto the one in
+    /// the second
+    MergeStacks(usize, usize),
+    /// Move the given amount of items off the stack in an inventory slot into
+    /// a free one
+    SplitStack(usize, u32),
+    /// Brew the ingredients in the given inventory slots
+    Brew(Vec<usize>),
+    /// Dye the item in a slot with the dye in the given inventory slot
+    Dye(usize, Slot),
+    /// Change what others see in an armor slot
+    SetAppearance(ArmorSlot, AppearanceChoice),
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -38,6 +56,8 @@ pub enum ControlEvent {
     InventoryManip(InventoryManip),
     GroupManip(GroupManip),
     Respawn,
+    /// Rest at the campfire the player is at, making it their waypoint
+    SetWaypoint,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
@@ -50,6 +70,8 @@ pub enum ControlAction {
     Dance,
     Sneak,
     Stand,
+    /// Use the ability in a slot of the ability hotbar
+    UseAbilitySlot(usize),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -1,8 +1,9 @@
-use crate::comp::Loadout;
+use crate::{
+    comp::{Body, CharacterState, Loadout},
+    states::{basic_block, flinch, stagger},
+};
 use serde::{Deserialize, Serialize};
 
-pub const BLOCK_EFFICIENCY: f32 = 0.9;
-
 pub struct Damage {
     pub healthchange: f32,
     pub source: DamageSource,
@@ -17,10 +18,31 @@ pub enum DamageSource {
     Falling,
     Shockwave,
     Energy,
+    Buff,
+}
+
+/// What happened to a hit on its way to the target
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HitModifiers {
+    /// Health that blocking and armor prevented from being lost, negative
+    /// when a critical hit increased the damage
+    pub mitigated: i32,
+    pub crit: bool,
+    pub blocked: bool,
 }
 
 impl Damage {
-    pub fn modify_damage(&mut self, block: bool, loadout: &Loadout) {
+    /// Applies critical hits, blocking and armor to the damage, returning how
+    /// it was modified
+    pub fn modify_damage(
+        &mut self,
+        block: Option<&basic_block::Data>,
+        loadout: &Loadout,
+    ) -> HitModifiers {
+        let base = self.healthchange;
+        // Hits too strong to be blocked go straight through the guard
+        let block = block.filter(|block| !block.breaks_guard(-base));
+        let mut crit = false;
         match self.source {
             DamageSource::Melee => {
                 // Critical hit
@@ -29,8 +51,8 @@ impl Damage {
                     critdamage = self.healthchange * 0.3;
                 }
                 // Block
-                if block {
-                    self.healthchange *= 1.0 - BLOCK_EFFICIENCY
+                if let Some(block) = block {
+                    self.healthchange *= 1.0 - block.damage_reduction
                 }
                 // Armor
                 let damage_reduction = loadout.get_damage_reduction();
@@ -39,16 +61,18 @@ impl Damage {
                 // Critical damage applies after armor for melee
                 if (damage_reduction - 1.0).abs() > f32::EPSILON {
                     self.healthchange += critdamage;
+                    crit = critdamage != 0.0;
                 }
             },
             DamageSource::Projectile => {
                 // Critical hit
                 if rand::random() {
                     self.healthchange *= 1.2;
+                    crit = true;
                 }
                 // Block
-                if block {
-                    self.healthchange *= 1.0 - BLOCK_EFFICIENCY
+                if let Some(block) = block {
+                    self.healthchange *= 1.0 - block.damage_reduction
                 }
                 // Armor
                 let damage_reduction = loadout.get_damage_reduction();
@@ -56,8 +80,8 @@ impl Damage {
             },
             DamageSource::Explosion => {
                 // Block
-                if block {
-                    self.healthchange *= 1.0 - BLOCK_EFFICIENCY
+                if let Some(block) = block {
+                    self.healthchange *= 1.0 - block.damage_reduction
                 }
                 // Armor
                 let damage_reduction = loadout.get_damage_reduction();
@@ -75,5 +99,135 @@ impl Damage {
             },
             _ => {},
         }
+        let blocked = block.is_some()
+            && matches!(
+                self.source,
+                DamageSource::Melee | DamageSource::Projectile | DamageSource::Explosion
+            );
+        HitModifiers {
+            mitigated: (base.abs() - self.healthchange.abs()) as i32,
+            crit,
+            blocked,
+        }
+    }
+}
+
+/// How a character recoils from a hit that got through its defences
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HitReaction {
+    /// Briefly slowed down and unable to act
+    Flinch,
+    /// Knocked off balance once poise breaks, unable to move or act for a
+    /// moment
+    Stagger,
+}
+
+impl HitReaction {
+    /// Fraction of maximum health a hit has to deal, after stability, to cause
+    /// a flinch
+    const FLINCH_THRESHOLD: f32 = 0.05;
+
+    /// Stability of a character, combining its body with the armor it wears
+    pub fn stability(body: &Body, loadout: Option<&Loadout>) -> f32 {
+        let armor = loadout.map_or(0.0, |loadout| loadout.get_damage_reduction());
+        (body.base_stability() + armor * 0.5).min(1.0)
+    }
+
+    /// Whether a hit dealing `damage` to a character with `max_health` makes
+    /// it flinch, with its stability absorbing part of the impact. Staggers
+    /// come from breaking [`Poise`](super::Poise) instead.
+    pub fn from_damage(damage: u32, max_health: u32, stability: f32) -> Option<Self> {
+        if max_health == 0 {
+            return None;
+        }
+        let impact = damage as f32 / max_health as f32 * (1.0 - stability.max(0.0).min(1.0));
+        if impact >= Self::FLINCH_THRESHOLD {
+            Some(HitReaction::Flinch)
+        } else {
+            None
+        }
+    }
+
+    /// The state a character in `current` is put into by this reaction, if
+    /// any. Flinches don't interrupt attacks, blocks or dodges, while
+    /// staggers only spare dodges.
+    pub fn interrupt(self, current: &CharacterState) -> Option<CharacterState> {
+        match (self, current) {
+            (_, CharacterState::Roll(_)) | (_, CharacterState::Stagger(_)) => None,
+            (HitReaction::Stagger, current) => Some(CharacterState::Stagger(stagger::Data {
+                time_left: stagger::STAGGER_DURATION,
+                was_wielded: current.returns_to_wield(),
+            })),
+            (HitReaction::Flinch, current)
+                if current.is_attack() || current.is_block() || current.is_hit_reaction() =>
+            {
+                None
+            },
+            (HitReaction::Flinch, current) => Some(CharacterState::Flinch(flinch::Data {
+                time_left: flinch::FLINCH_DURATION,
+                was_wielded: current.returns_to_wield(),
+            })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_reaction_scales_with_damage_and_stability() {
+        assert_eq!(HitReaction::from_damage(10, 1000, 0.0), None);
+        assert_eq!(
+            HitReaction::from_damage(100, 1000, 0.0),
+            Some(HitReaction::Flinch)
+        );
+        assert_eq!(HitReaction::from_damage(80, 1000, 0.5), None);
+        assert_eq!(HitReaction::from_damage(1000, 1000, 1.0), None);
+    }
+
+    #[test]
+    fn flinch_does_not_interrupt_attacks() {
+        let blocking = CharacterState::BasicBlock(basic_block::Data {
+            max_angle: 90.0,
+            damage_reduction: 0.5,
+            stability: 100.0,
+        });
+        assert_eq!(HitReaction::Flinch.interrupt(&blocking), None);
+        assert!(matches!(
+            HitReaction::Stagger.interrupt(&blocking),
+            Some(CharacterState::Stagger(_))
+        ));
+        assert!(matches!(
+            HitReaction::Flinch.interrupt(&CharacterState::Wielding),
+            Some(CharacterState::Flinch(flinch::Data {
+                was_wielded: true,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn stagger_returns_to_the_previous_stance() {
+        assert!(matches!(
+            HitReaction::Stagger.interrupt(&CharacterState::Idle),
+            Some(CharacterState::Stagger(stagger::Data {
+                was_wielded: false,
+                ..
+            }))
+        ));
+        // A flinch that is cut short by a stagger still knows the stance
+        // from before it
+        let flinch = CharacterState::Flinch(flinch::Data {
+            time_left: flinch::FLINCH_DURATION,
+            was_wielded: true,
+        });
+        assert!(matches!(
+            HitReaction::Stagger.interrupt(&flinch),
+            Some(CharacterState::Stagger(stagger::Data {
+                was_wielded: true,
+                ..
+            }))
+        ));
     }
 }
@@ -18,6 +18,8 @@ pub enum EnergySource {
     HitEnemy,
     Regen,
     Revive,
+    Buff,
+    Item,
     Unknown,
 }
 
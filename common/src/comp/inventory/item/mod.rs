@@ -2,12 +2,14 @@ pub mod armor;
 pub mod tool;
 
 // Reexports
-pub use tool::{Hands, Tool, ToolCategory, ToolKind};
+pub use tool::{BlockStats, Hands, Tool, ToolCategory, ToolKind};
 
 use crate::{
     assets::{self, Asset, Error},
+    comp::{object, projectile, Projectile},
     effect::Effect,
     lottery::Lottery,
+    sync::Uid,
     terrain::{Block, SpriteKind},
 };
 use crossbeam::atomic::AtomicCell;
@@ -20,9 +22,13 @@ use std::{
     io::BufReader,
     num::{NonZeroU32, NonZeroU64},
     sync::Arc,
+    time::Duration,
 };
 use vek::Rgb;
 
+/// Largest amount of a stackable item that fits into a single inventory slot
+pub const MAX_STACK_SIZE: u32 = 99;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Throwable {
     Bomb,
@@ -39,6 +45,33 @@ pub enum Reagent {
     Yellow,
 }
 
+/// The projectile an item turns into when it is thrown
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ThrownProjectile {
+    pub body: object::Body,
+    /// Speed at which the item leaves the hand of the thrower
+    pub speed: f32,
+    /// How strongly the projectile is pulled down while in flight
+    pub gravity: f32,
+    pub hit_solid: Vec<projectile::Effect>,
+    pub hit_entity: Vec<projectile::Effect>,
+    /// Seconds until the projectile despawns
+    pub lifetime: f32,
+}
+
+impl ThrownProjectile {
+    pub fn projectile(&self, owner: Option<Uid>) -> Projectile {
+        Projectile {
+            hit_solid: self.hit_solid.clone(),
+            hit_entity: self.hit_entity.clone(),
+            time_left: Duration::from_secs_f32(self.lifetime.max(0.0)),
+            drag: 0.0,
+            owner,
+            ignore_group: true,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Utility {
     Collar,
@@ -88,12 +121,22 @@ pub enum ItemKind {
     Throwable {
         kind: Throwable,
     },
+    /// Thrown as a projectile when used, using up one of the item
+    Thrown {
+        kind: String,
+        projectile: ThrownProjectile,
+    },
     Utility {
         kind: Utility,
     },
     Ingredient {
         kind: String,
     },
+    /// Applied to a piece of armor to change its color
+    Dye {
+        kind: String,
+        color: Rgb<u8>,
+    },
 }
 
 pub type ItemId = AtomicCell<Option<NonZeroU64>>;
@@ -126,6 +169,9 @@ pub struct Item {
     /// amount is hidden because it needs to maintain the invariant that only
     /// stackable items can have > 1 amounts.
     amount: NonZeroU32,
+    /// Color the item was dyed in, replacing the colors of its model
+    #[serde(default)]
+    dye: Option<Rgb<u8>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -146,7 +192,9 @@ impl ItemDef {
     pub fn is_stackable(&self) -> bool {
         matches!(self.kind, ItemKind::Consumable { .. }
             | ItemKind::Ingredient { .. }
+            | ItemKind::Dye { .. }
             | ItemKind::Throwable { .. }
+            | ItemKind::Thrown { .. }
             | ItemKind::Utility { .. })
     }
 }
@@ -185,6 +233,7 @@ impl Item {
             item_id: Arc::new(AtomicCell::new(None)),
             item_def: inner_item,
             amount: NonZeroU32::new(1).unwrap(),
+            dye: None,
         }
     }
 
@@ -216,7 +265,25 @@ impl Item {
     }
 
     /// Duplicates an item, creating an exact copy but with a new item ID
-    pub fn duplicate(&self) -> Self { Item::new(Arc::clone(&self.item_def)) }
+    pub fn duplicate(&self) -> Self {
+        Item {
+            dye: self.dye,
+            ..Item::new(Arc::clone(&self.item_def))
+        }
+    }
+
+    /// Points the item at the currently loaded version of its definition, which
+    /// differs from the one it was created with if the definition was
+    /// reloaded since. Returns whether the definition changed.
+    pub fn refresh_definition(&mut self) -> bool {
+        match ItemDef::load(&self.item_def.item_definition_id) {
+            Ok(item_def) if !Arc::ptr_eq(&item_def, &self.item_def) => {
+                self.item_def = item_def;
+                true
+            },
+            _ => false,
+        }
+    }
 
     /// FIXME: HACK: In order to set the entity ID asynchronously, we currently
     /// start it at None, and then atomically set it when it's saved for the
@@ -286,6 +353,15 @@ impl Item {
 
     pub fn is_stackable(&self) -> bool { self.item_def.is_stackable() }
 
+    /// Largest amount of this item that fits into a single inventory slot
+    pub fn max_amount(&self) -> u32 {
+        if self.is_stackable() {
+            MAX_STACK_SIZE
+        } else {
+            1
+        }
+    }
+
     pub fn name(&self) -> &str { &self.item_def.name }
 
     pub fn description(&self) -> &str { &self.item_def.description }
@@ -296,6 +372,23 @@ impl Item {
 
     pub fn quality(&self) -> Quality { self.item_def.quality }
 
+    pub fn dye(&self) -> Option<Rgb<u8>> { self.dye }
+
+    pub fn set_dye(&mut self, dye: Option<Rgb<u8>>) { self.dye = dye; }
+
+    /// Whether the item can be dyed, which are the pieces of armor that are
+    /// shown on characters
+    pub fn is_dyeable(&self) -> bool {
+        use armor::ArmorKind::*;
+        match self.kind() {
+            ItemKind::Armor(armor) => matches!(
+                armor.kind,
+                Shoulder(_) | Chest(_) | Belt(_) | Hand(_) | Pants(_) | Foot(_) | Back(_)
+            ),
+            _ => false,
+        }
+    }
+
     pub fn try_reclaim_from_block(block: Block) -> Option<Self> {
         let chosen;
         let mut rng = rand::thread_rng();
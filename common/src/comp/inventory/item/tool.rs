@@ -2,12 +2,15 @@
 // version in voxygen\src\meta.rs in order to reset save files to being empty
 
 use crate::{
-    comp::{body::object, projectile, Body, CharacterAbility, Gravity, LightEmitter, Projectile},
-    states::combo_melee,
-    Explosion,
+    assets::{self, Asset},
+    comp::CharacterAbility,
 };
+use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::{fs::File, io::BufReader, time::Duration};
+
+/// Asset specifier of the ability map used by tools
+pub const ABILITY_MAP_SPECIFIER: &str = "common.abilities.weapon_abilities";
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ToolKind {
@@ -45,11 +48,30 @@ impl ToolKind {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Hands {
     OneHand,
     TwoHand,
 }
 
+impl Hands {
+    /// Two-handed weapons hit harder
+    pub fn power_modifier(self) -> f32 {
+        match self {
+            Hands::OneHand => 1.0,
+            Hands::TwoHand => 1.15,
+        }
+    }
+
+    /// Wielding a two-handed weapon slows its wielder down
+    pub fn speed_modifier(self) -> f32 {
+        match self {
+            Hands::OneHand => 1.0,
+            Hands::TwoHand => 0.9,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ToolCategory {
     Sword,
@@ -89,6 +111,36 @@ impl From<&ToolKind> for ToolCategory {
 pub struct Stats {
     equip_time_millis: u32,
     power: f32,
+    /// How well the tool blocks attacks, for tools made to block with
+    #[serde(default)]
+    block: Option<BlockStats>,
+    /// Poise damage dealt per point of damage by attacks with the tool
+    #[serde(default = "default_poise_power")]
+    poise_power: f32,
+}
+
+fn default_poise_power() -> f32 { 1.0 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockStats {
+    /// Angle in degrees of the cone in front of the wielder that attacks are
+    /// blocked from
+    pub angle: f32,
+    /// Fraction of the damage of blocked attacks that is absorbed
+    pub damage_reduction: f32,
+    /// Largest hit that can be blocked, bigger ones break the guard
+    pub stability: f32,
+}
+
+impl Default for BlockStats {
+    /// Blocking with a tool that isn't made for it
+    fn default() -> Self {
+        Self {
+            angle: 90.0,
+            damage_reduction: 0.5,
+            stability: 50.0,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -105,6 +157,8 @@ impl Tool {
             stats: Stats {
                 equip_time_millis: 0,
                 power: 1.00,
+                block: None,
+                poise_power: default_poise_power(),
             },
         }
     }
@@ -112,495 +166,60 @@ impl Tool {
     // Keep power between 0.5 and 2.00
     pub fn base_power(&self) -> f32 { self.stats.power }
 
+    /// Power of the tool once the number of hands it takes is accounted for
+    pub fn power(&self) -> f32 { self.base_power() * self.kind.hands().power_modifier() }
+
+    /// Poise damage dealt per point of damage by the tool's abilities, as
+    /// configured in the tool's stats and in the ability map
+    pub fn poise_power(&self) -> f32 {
+        self.poise_power_from(&AbilityMap::load_expect(ABILITY_MAP_SPECIFIER))
+    }
+
+    /// Poise damage dealt per point of damage by the tool's abilities, with
+    /// the tuning of its abilities taken from the given ability map
+    pub fn poise_power_from(&self, ability_map: &AbilityMap) -> f32 {
+        let ability_power = ability_map
+            .get(&self.kind)
+            .map_or_else(default_poise_power, |set| set.poise_power);
+        self.stats.poise_power * ability_power
+    }
+
+    pub fn block_stats(&self) -> BlockStats { self.stats.block.unwrap_or_default() }
+
+    pub fn block_ability(&self) -> CharacterAbility {
+        let block = self.block_stats();
+        CharacterAbility::BasicBlock {
+            max_angle: block.angle,
+            damage_reduction: block.damage_reduction,
+            stability: block.stability,
+        }
+    }
+
     pub fn equip_time(&self) -> Duration {
         Duration::from_millis(self.stats.equip_time_millis as u64)
     }
 
+    /// The abilities of the tool, as configured in the ability map
     pub fn get_abilities(&self) -> Vec<CharacterAbility> {
-        use CharacterAbility::*;
-        use ToolKind::*;
-
-        match &self.kind {
-            Sword(_) => vec![
-                ComboMelee {
-                    stage_data: vec![
-                        combo_melee::Stage {
-                            stage: 1,
-                            base_damage: (100.0 * self.base_power()) as u32,
-                            max_damage: (120.0 * self.base_power()) as u32,
-                            damage_increase: (10.0 * self.base_power()) as u32,
-                            knockback: 10.0,
-                            range: 4.0,
-                            angle: 30.0,
-                            base_buildup_duration: Duration::from_millis(350),
-                            base_swing_duration: Duration::from_millis(100),
-                            base_recover_duration: Duration::from_millis(400),
-                            forward_movement: 0.5,
-                        },
-                        combo_melee::Stage {
-                            stage: 2,
-                            base_damage: (80.0 * self.base_power()) as u32,
-                            max_damage: (110.0 * self.base_power()) as u32,
-                            damage_increase: (15.0 * self.base_power()) as u32,
-                            knockback: 12.0,
-                            range: 3.5,
-                            angle: 180.0,
-                            base_buildup_duration: Duration::from_millis(400),
-                            base_swing_duration: Duration::from_millis(600),
-                            base_recover_duration: Duration::from_millis(400),
-                            forward_movement: 0.0,
-                        },
-                        combo_melee::Stage {
-                            stage: 3,
-                            base_damage: (130.0 * self.base_power()) as u32,
-                            max_damage: (170.0 * self.base_power()) as u32,
-                            damage_increase: (20.0 * self.base_power()) as u32,
-                            knockback: 14.0,
-                            range: 6.0,
-                            angle: 10.0,
-                            base_buildup_duration: Duration::from_millis(500),
-                            base_swing_duration: Duration::from_millis(200),
-                            base_recover_duration: Duration::from_millis(300),
-                            forward_movement: 1.2,
-                        },
-                    ],
-                    initial_energy_gain: 0,
-                    max_energy_gain: 100,
-                    energy_increase: 20,
-                    speed_increase: 0.05,
-                    max_speed_increase: 1.8,
-                    is_interruptible: true,
-                },
-                DashMelee {
-                    energy_cost: 200,
-                    base_damage: (120.0 * self.base_power()) as u32,
-                    max_damage: (260.0 * self.base_power()) as u32,
-                    base_knockback: 10.0,
-                    max_knockback: 20.0,
-                    range: 5.0,
-                    angle: 45.0,
-                    energy_drain: 500,
-                    forward_speed: 4.0,
-                    buildup_duration: Duration::from_millis(250),
-                    charge_duration: Duration::from_millis(400),
-                    swing_duration: Duration::from_millis(100),
-                    recover_duration: Duration::from_millis(500),
-                    infinite_charge: true,
-                    is_interruptible: true,
-                },
-                SpinMelee {
-                    buildup_duration: Duration::from_millis(750),
-                    swing_duration: Duration::from_millis(500),
-                    recover_duration: Duration::from_millis(500),
-                    base_damage: (140.0 * self.base_power()) as u32,
-                    knockback: 10.0,
-                    range: 3.5,
-                    energy_cost: 200,
-                    is_infinite: false,
-                    is_helicopter: false,
-                    is_interruptible: true,
-                    forward_speed: 1.0,
-                    num_spins: 3,
-                },
-            ],
-            Axe(_) => vec![
-                BasicMelee {
-                    energy_cost: 0,
-                    buildup_duration: Duration::from_millis(700),
-                    recover_duration: Duration::from_millis(300),
-                    base_healthchange: (-120.0 * self.base_power()) as i32,
-                    knockback: 0.0,
-                    range: 3.5,
-                    max_angle: 20.0,
-                },
-                SpinMelee {
-                    buildup_duration: Duration::from_millis(100),
-                    swing_duration: Duration::from_millis(250),
-                    recover_duration: Duration::from_millis(100),
-                    base_damage: (60.0 * self.base_power()) as u32,
-                    knockback: 0.0,
-                    range: 3.5,
-                    energy_cost: 100,
-                    is_infinite: true,
-                    is_helicopter: true,
-                    is_interruptible: false,
-                    forward_speed: 0.0,
-                    num_spins: 1,
-                },
-                LeapMelee {
-                    energy_cost: 450,
-                    buildup_duration: Duration::from_millis(200),
-                    movement_duration: Duration::from_millis(200),
-                    swing_duration: Duration::from_millis(200),
-                    recover_duration: Duration::from_millis(200),
-                    base_damage: (240.0 * self.base_power()) as u32,
-                    knockback: 12.0,
-                    range: 4.5,
-                    max_angle: 30.0,
-                    forward_leap_strength: 28.0,
-                    vertical_leap_strength: 8.0,
-                },
-            ],
-            Hammer(_) => vec![
-                BasicMelee {
-                    energy_cost: 0,
-                    buildup_duration: Duration::from_millis(700),
-                    recover_duration: Duration::from_millis(300),
-                    base_healthchange: (-120.0 * self.base_power()) as i32,
-                    knockback: 0.0,
-                    range: 3.5,
-                    max_angle: 20.0,
-                },
-                ChargedMelee {
-                    energy_cost: 1,
-                    energy_drain: 300,
-                    initial_damage: (10.0 * self.base_power()) as u32,
-                    max_damage: (170.0 * self.base_power()) as u32,
-                    initial_knockback: 10.0,
-                    max_knockback: 60.0,
-                    range: 3.5,
-                    max_angle: 30.0,
-                    charge_duration: Duration::from_millis(1200),
-                    swing_duration: Duration::from_millis(400),
-                    recover_duration: Duration::from_millis(100),
-                },
-                LeapMelee {
-                    energy_cost: 700,
-                    buildup_duration: Duration::from_millis(100),
-                    movement_duration: Duration::from_millis(800),
-                    swing_duration: Duration::from_millis(150),
-                    recover_duration: Duration::from_millis(200),
-                    base_damage: (240.0 * self.base_power()) as u32,
-                    knockback: 25.0,
-                    range: 4.5,
-                    max_angle: 360.0,
-                    forward_leap_strength: 28.0,
-                    vertical_leap_strength: 8.0,
-                },
-            ],
-            Farming(_) => vec![BasicMelee {
-                energy_cost: 1,
-                buildup_duration: Duration::from_millis(700),
-                recover_duration: Duration::from_millis(150),
-                base_healthchange: (-50.0 * self.base_power()) as i32,
-                knockback: 0.0,
-                range: 3.5,
-                max_angle: 20.0,
-            }],
-            Bow(_) => vec![
-                BasicRanged {
-                    energy_cost: 0,
-                    holdable: true,
-                    prepare_duration: Duration::from_millis(100),
-                    recover_duration: Duration::from_millis(400),
-                    projectile: Projectile {
-                        hit_solid: vec![projectile::Effect::Stick],
-                        hit_entity: vec![
-                            projectile::Effect::Damage((-40.0 * self.base_power()) as i32),
-                            projectile::Effect::Knockback(10.0),
-                            projectile::Effect::RewardEnergy(50),
-                            projectile::Effect::Vanish,
-                        ],
-                        time_left: Duration::from_secs(15),
-                        owner: None,
-                        ignore_group: true,
-                    },
-                    projectile_body: Body::Object(object::Body::Arrow),
-                    projectile_light: None,
-                    projectile_gravity: Some(Gravity(0.2)),
-                    projectile_speed: 100.0,
-                },
-                ChargedRanged {
-                    energy_cost: 0,
-                    energy_drain: 300,
-                    initial_damage: (40.0 * self.base_power()) as u32,
-                    max_damage: (200.0 * self.base_power()) as u32,
-                    initial_knockback: 10.0,
-                    max_knockback: 20.0,
-                    prepare_duration: Duration::from_millis(100),
-                    charge_duration: Duration::from_millis(1500),
-                    recover_duration: Duration::from_millis(500),
-                    projectile_body: Body::Object(object::Body::MultiArrow),
-                    projectile_light: None,
-                    projectile_gravity: Some(Gravity(0.2)),
-                    initial_projectile_speed: 100.0,
-                    max_projectile_speed: 500.0,
-                },
-                RepeaterRanged {
-                    energy_cost: 450,
-                    movement_duration: Duration::from_millis(300),
-                    buildup_duration: Duration::from_millis(200),
-                    shoot_duration: Duration::from_millis(200),
-                    recover_duration: Duration::from_millis(800),
-                    leap: Some(10.0),
-                    projectile: Projectile {
-                        hit_solid: vec![projectile::Effect::Stick],
-                        hit_entity: vec![
-                            projectile::Effect::Damage((-40.0 * self.base_power()) as i32),
-                            projectile::Effect::Knockback(10.0),
-                            projectile::Effect::RewardEnergy(50),
-                            projectile::Effect::Vanish,
-                        ],
-                        time_left: Duration::from_secs(15),
-                        owner: None,
-                        ignore_group: true,
-                    },
-                    projectile_body: Body::Object(object::Body::Arrow),
-                    projectile_light: None,
-                    projectile_gravity: Some(Gravity(0.2)),
-                    projectile_speed: 100.0,
-                    reps_remaining: 5,
-                },
-            ],
-            Dagger(_) => vec![BasicMelee {
-                energy_cost: 0,
-                buildup_duration: Duration::from_millis(100),
-                recover_duration: Duration::from_millis(400),
-                base_healthchange: (-50.0 * self.base_power()) as i32,
-                knockback: 0.0,
-                range: 3.5,
-                max_angle: 20.0,
-            }],
-            Sceptre(_) => vec![
-                BasicBeam {
-                    buildup_duration: Duration::from_millis(250),
-                    recover_duration: Duration::from_millis(250),
-                    beam_duration: Duration::from_secs(1),
-                    base_hps: (60.0 * self.base_power()) as u32,
-                    base_dps: (60.0 * self.base_power()) as u32,
-                    tick_rate: 2.0,
-                    range: 25.0,
-                    max_angle: 1.0,
-                    lifesteal_eff: 0.20,
-                    energy_regen: 50,
-                    energy_cost: 100,
-                    energy_drain: 0,
-                },
-                BasicRanged {
-                    energy_cost: 800,
-                    holdable: true,
-                    prepare_duration: Duration::from_millis(800),
-                    recover_duration: Duration::from_millis(50),
-                    projectile: Projectile {
-                        hit_solid: vec![
-                            projectile::Effect::Explode(Explosion {
-                                radius: 3.0 + 2.5 * self.base_power(),
-                                max_damage: (50.0 * self.base_power()) as u32,
-                                min_damage: (20.0 * self.base_power()) as u32,
-                                max_heal: (140.0 * self.base_power()) as u32,
-                                min_heal: (50.0 * self.base_power()) as u32,
-                                terrain_destruction_power: 0.0,
-                                energy_regen: 0,
-                            }),
-                            projectile::Effect::Vanish,
-                        ],
-                        hit_entity: vec![
-                            projectile::Effect::Explode(Explosion {
-                                radius: 3.0 + 2.5 * self.base_power(),
-                                max_damage: (50.0 * self.base_power()) as u32,
-                                min_damage: (20.0 * self.base_power()) as u32,
-                                max_heal: (140.0 * self.base_power()) as u32,
-                                min_heal: (50.0 * self.base_power()) as u32,
-                                terrain_destruction_power: 0.0,
-                                energy_regen: 0,
-                            }),
-                            projectile::Effect::Vanish,
-                        ],
-                        time_left: Duration::from_secs(20),
-                        owner: None,
-                        ignore_group: true,
-                    },
-                    projectile_body: Body::Object(object::Body::BoltNature),
-                    projectile_light: Some(LightEmitter {
-                        col: (0.0, 1.0, 0.0).into(),
-                        ..Default::default()
-                    }),
-                    projectile_gravity: Some(Gravity(0.5)),
-                    projectile_speed: 40.0,
-                },
-            ],
-            Staff(_) => vec![
-                BasicRanged {
-                    energy_cost: 0,
-                    holdable: false,
-                    prepare_duration: Duration::from_millis(500),
-                    recover_duration: Duration::from_millis(350),
-                    projectile: Projectile {
-                        hit_solid: vec![
-                            projectile::Effect::Explode(Explosion {
-                                radius: 5.0,
-                                max_damage: (100.0 * self.base_power()) as u32,
-                                min_damage: 0,
-                                max_heal: 0,
-                                min_heal: 0,
-                                terrain_destruction_power: 0.0,
-                                energy_regen: 50,
-                            }),
-                            projectile::Effect::Vanish,
-                        ],
-                        hit_entity: vec![
-                            projectile::Effect::Explode(Explosion {
-                                radius: 5.0,
-                                max_damage: (100.0 * self.base_power()) as u32,
-                                min_damage: 0,
-                                max_heal: 0,
-                                min_heal: 0,
-                                terrain_destruction_power: 0.0,
-                                energy_regen: 50,
-                            }),
-                            projectile::Effect::Vanish,
-                        ],
-                        time_left: Duration::from_secs(20),
-                        owner: None,
-                        ignore_group: true,
-                    },
-                    projectile_body: Body::Object(object::Body::BoltFire),
-                    projectile_light: Some(LightEmitter {
-                        col: (1.0, 0.75, 0.11).into(),
-                        ..Default::default()
-                    }),
-                    projectile_gravity: Some(Gravity(0.3)),
-                    projectile_speed: 60.0,
-                },
-                BasicBeam {
-                    buildup_duration: Duration::from_millis(250),
-                    recover_duration: Duration::from_millis(250),
-                    beam_duration: Duration::from_millis(500),
-                    base_hps: 0,
-                    base_dps: (150.0 * self.base_power()) as u32,
-                    tick_rate: 3.0,
-                    range: 15.0,
-                    max_angle: 22.5,
-                    lifesteal_eff: 0.0,
-                    energy_regen: 0,
-                    energy_cost: 0,
-                    energy_drain: 350,
-                },
-                Shockwave {
-                    energy_cost: 600,
-                    buildup_duration: Duration::from_millis(700),
-                    swing_duration: Duration::from_millis(100),
-                    recover_duration: Duration::from_millis(300),
-                    damage: (200.0 * self.base_power()) as u32,
-                    knockback: 25.0,
-                    shockwave_angle: 360.0,
-                    shockwave_vertical_angle: 90.0,
-                    shockwave_speed: 20.0,
-                    shockwave_duration: Duration::from_millis(500),
-                    requires_ground: false,
-                    move_efficiency: 0.1,
-                },
-            ],
-            Shield(_) => vec![
-                BasicMelee {
-                    energy_cost: 0,
-                    buildup_duration: Duration::from_millis(100),
-                    recover_duration: Duration::from_millis(400),
-                    base_healthchange: (-40.0 * self.base_power()) as i32,
-                    knockback: 0.0,
-                    range: 3.0,
-                    max_angle: 120.0,
-                },
-                BasicBlock,
-            ],
-            NpcWeapon(kind) => {
-                if kind == "StoneGolemsFist" {
-                    vec![
-                        BasicMelee {
-                            energy_cost: 0,
-                            buildup_duration: Duration::from_millis(500),
-                            recover_duration: Duration::from_millis(250),
-                            knockback: 25.0,
-                            base_healthchange: -200,
-                            range: 5.0,
-                            max_angle: 120.0,
-                        },
-                        Shockwave {
-                            energy_cost: 0,
-                            buildup_duration: Duration::from_millis(500),
-                            swing_duration: Duration::from_millis(200),
-                            recover_duration: Duration::from_millis(800),
-                            damage: 500,
-                            knockback: -40.0,
-                            shockwave_angle: 90.0,
-                            shockwave_vertical_angle: 15.0,
-                            shockwave_speed: 20.0,
-                            shockwave_duration: Duration::from_millis(2000),
-                            requires_ground: true,
-                            move_efficiency: 0.05,
-                        },
-                    ]
-                } else if kind == "BeastClaws" {
-                    vec![BasicMelee {
-                        energy_cost: 0,
-                        buildup_duration: Duration::from_millis(500),
-                        recover_duration: Duration::from_millis(250),
-                        knockback: 25.0,
-                        base_healthchange: -200,
-                        range: 5.0,
-                        max_angle: 120.0,
-                    }]
-                } else {
-                    vec![BasicMelee {
-                        energy_cost: 0,
-                        buildup_duration: Duration::from_millis(100),
-                        recover_duration: Duration::from_millis(300),
-                        base_healthchange: -10,
-                        knockback: 0.0,
-                        range: 1.0,
-                        max_angle: 30.0,
-                    }]
-                }
-            },
-            Debug(kind) => {
-                if kind == "Boost" {
-                    vec![
-                        CharacterAbility::Boost {
-                            duration: Duration::from_millis(50),
-                            only_up: false,
-                        },
-                        CharacterAbility::Boost {
-                            duration: Duration::from_millis(50),
-                            only_up: true,
-                        },
-                        BasicRanged {
-                            energy_cost: 0,
-                            holdable: false,
-                            prepare_duration: Duration::from_millis(0),
-                            recover_duration: Duration::from_millis(10),
-                            projectile: Projectile {
-                                hit_solid: vec![projectile::Effect::Stick],
-                                hit_entity: vec![
-                                    projectile::Effect::Stick,
-                                    projectile::Effect::Possess,
-                                ],
-                                time_left: Duration::from_secs(10),
-                                owner: None,
-                                ignore_group: false,
-                            },
-                            projectile_body: Body::Object(object::Body::ArrowSnake),
-                            projectile_light: Some(LightEmitter {
-                                col: (0.0, 1.0, 0.33).into(),
-                                ..Default::default()
-                            }),
-                            projectile_gravity: None,
-                            projectile_speed: 100.0,
-                        },
-                    ]
-                } else {
-                    vec![]
-                }
-            },
-            Empty => vec![BasicMelee {
-                energy_cost: 0,
-                buildup_duration: Duration::from_millis(0),
-                recover_duration: Duration::from_millis(1000),
-                base_healthchange: -20,
-                knockback: 0.0,
-                range: 3.5,
-                max_angle: 15.0,
-            }],
+        self.get_abilities_from(&AbilityMap::load_expect(ABILITY_MAP_SPECIFIER))
+    }
+
+    /// The abilities of the tool as configured in the given ability map, with
+    /// their damage and healing adjusted to the power of the tool
+    pub fn get_abilities_from(&self, ability_map: &AbilityMap) -> Vec<CharacterAbility> {
+        let mut abilities = match ability_map.get(&self.kind) {
+            Some(set) if set.scale_with_power => set
+                .abilities
+                .iter()
+                .map(|ability| ability.clone().adjusted_by_power(self.power()))
+                .collect(),
+            Some(set) => set.abilities.clone(),
+            None => Vec::new(),
+        };
+        if let ToolKind::Shield(_) = self.kind {
+            abilities.push(self.block_ability());
         }
+        abilities
     }
 
     /// Determines whether two tools are superficially equivalent to one another
@@ -610,3 +229,119 @@ impl Tool {
         ToolCategory::from(&self.kind) == ToolCategory::from(&other.kind)
     }
 }
+
+fn default_scale_with_power() -> bool { true }
+
+/// The abilities of a kind of tool, with damage and healing given for a tool
+/// of power 1.0
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AbilitySet {
+    pub abilities: Vec<CharacterAbility>,
+    /// Whether damage and healing are multiplied by the power of the tool
+    #[serde(default = "default_scale_with_power")]
+    pub scale_with_power: bool,
+    /// Poise damage dealt per point of damage by the abilities, before the
+    /// poise power of the tool is applied
+    #[serde(default = "default_poise_power")]
+    pub poise_power: f32,
+}
+
+/// The abilities of every kind of tool, loaded from the asset at
+/// [`ABILITY_MAP_SPECIFIER`] so that they can be balanced without recompiling.
+/// Tools without an entry for their kind use the one of their category.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AbilityMap {
+    categories: HashMap<ToolCategory, AbilitySet>,
+    #[serde(default)]
+    kinds: HashMap<ToolKind, AbilitySet>,
+}
+
+impl AbilityMap {
+    pub fn get(&self, kind: &ToolKind) -> Option<&AbilitySet> {
+        self.kinds
+            .get(kind)
+            .or_else(|| self.categories.get(&ToolCategory::from(kind)))
+    }
+}
+
+impl Asset for AbilityMap {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+
+    fn parse(buf_reader: BufReader<File>, _specifier: &str) -> Result<Self, assets::Error> {
+        ron::de::from_reader(buf_reader).map_err(assets::Error::parse_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ability_map_covers_all_categories() {
+        use ToolCategory::*;
+        let ability_map = AbilityMap::load_expect(ABILITY_MAP_SPECIFIER);
+        for category in &[
+            Sword, Axe, Hammer, Bow, Dagger, Staff, Sceptre, Shield, NpcWeapon, Debug, Farming,
+            Empty,
+        ] {
+            assert!(
+                ability_map.categories.contains_key(category),
+                "No abilities for {:?}",
+                category
+            );
+        }
+    }
+
+    #[test]
+    fn abilities_scale_with_power() {
+        let ability_map = AbilityMap::load_expect(ABILITY_MAP_SPECIFIER);
+        let mut tool = Tool::empty();
+        tool.kind = ToolKind::Dagger("Test".to_string());
+        tool.stats.power = 2.0;
+        let base = &ability_map.get(&tool.kind).unwrap().abilities;
+        match (&base[0], &tool.get_abilities_from(&ability_map)[0]) {
+            (
+                CharacterAbility::BasicMelee {
+                    base_healthchange: base,
+                    ..
+                },
+                CharacterAbility::BasicMelee {
+                    base_healthchange: scaled,
+                    ..
+                },
+            ) => assert_eq!(*scaled, *base * 2),
+            _ => panic!("Daggers should start with a basic melee attack"),
+        }
+    }
+
+    #[test]
+    fn poise_power_combines_tool_and_abilities() {
+        let ability_map = AbilityMap::load_expect(ABILITY_MAP_SPECIFIER);
+        let mut tool = Tool::empty();
+        tool.kind = ToolKind::Bow("Test".to_string());
+        tool.stats.poise_power = 2.0;
+        let ability_power = ability_map.get(&tool.kind).unwrap().poise_power;
+        assert!((tool.poise_power_from(&ability_map) - 2.0 * ability_power).abs() < 0.001);
+    }
+
+    #[test]
+    fn sceptre_explosions_grow_with_power() {
+        let ability_map = AbilityMap::load_expect(ABILITY_MAP_SPECIFIER);
+        let mut tool = Tool::empty();
+        tool.kind = ToolKind::Sceptre("Test".to_string());
+        for base_power in &[0.5, 1.0, 2.0] {
+            tool.stats.power = *base_power;
+            let radius = match &tool.get_abilities_from(&ability_map)[1] {
+                CharacterAbility::BasicRanged { projectile, .. } => {
+                    match &projectile.hit_solid[0] {
+                        crate::comp::projectile::Effect::Explode(explosion) => explosion.radius,
+                        _ => panic!("Sceptre projectiles should explode"),
+                    }
+                },
+                _ => panic!("The second sceptre ability should be ranged"),
+            };
+            // The radius used to be computed in code as `3.0 + 2.5 * power`
+            assert!((radius - (3.0 + 2.5 * tool.power())).abs() < 0.001);
+        }
+    }
+}
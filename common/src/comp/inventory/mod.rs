@@ -1,7 +1,11 @@
 pub mod item;
 pub mod slot;
 
-use crate::{comp::inventory::item::ItemDef, recipe::Recipe};
+use crate::{
+    alchemy::BrewError,
+    comp::inventory::item::{ItemDef, ItemKind},
+    recipe::{CraftError, Recipe},
+};
 use core::ops::Not;
 use item::Item;
 use serde::{Deserialize, Serialize};
@@ -36,6 +40,10 @@ impl Inventory {
 
     pub fn slots(&self) -> &[Option<Item>] { &self.slots }
 
+    pub fn items_mut(&mut self) -> impl Iterator<Item = &mut Item> {
+        self.slots.iter_mut().filter_map(Option::as_mut)
+    }
+
     pub fn len(&self) -> usize { self.slots.len() }
 
     /// Total number of occupied slots in the inventory.
@@ -53,7 +61,7 @@ impl Inventory {
                 .slots
                 .iter_mut()
                 .filter_map(Option::as_mut)
-                .find(|s| *s == &item)
+                .find(|s| *s == &item && s.amount() + item.amount() <= s.max_amount())
             {
                 return slot_item
                     .increase_amount(item.amount())
@@ -142,7 +150,8 @@ impl Inventory {
         if item.is_stackable() {
             match self.slots.get_mut(cell) {
                 Some(Some(slot_item)) => {
-                    Ok(if slot_item == &item {
+                    let fits = slot_item.amount() + item.amount() <= slot_item.max_amount();
+                    Ok(if slot_item == &item && fits {
                         slot_item
                             .increase_amount(item.amount())
                             .err()
@@ -210,6 +219,75 @@ impl Inventory {
         }
     }
 
+    /// Returns a copy of the inventory with stacks of the same item merged and
+    /// the items ordered by kind, quality and name, without gaps between them
+    pub fn sorted(&self) -> Self {
+        let mut items: Vec<Item> = Vec::new();
+        for item in self.slots.iter().flatten() {
+            let mut remaining = Some(item.clone());
+            for stack in items.iter_mut() {
+                remaining = match remaining {
+                    Some(item) if stack == &item => merge_stack(stack, item),
+                    remaining => remaining,
+                };
+            }
+            items.extend(remaining);
+        }
+        items.sort_by(|a, b| {
+            kind_order(a.kind())
+                .cmp(&kind_order(b.kind()))
+                .then((b.quality() as u8).cmp(&(a.quality() as u8)))
+                .then_with(|| a.name().cmp(b.name()))
+        });
+
+        let mut slots = items.into_iter().map(Some).collect::<Vec<_>>();
+        slots.resize(self.slots.len(), None);
+        let mut inventory = Self { slots, amount: 0 };
+        inventory.recount_items();
+        inventory
+    }
+
+    /// Moves as many items as fit from the stack in slot `from` onto the stack
+    /// of the same item in slot `to`. Returns whether anything was moved.
+    pub fn stack_merge(&mut self, from: usize, to: usize) -> bool {
+        if from == to || from.max(to) >= self.slots.len() {
+            return false;
+        }
+        match (&self.slots[from], &self.slots[to]) {
+            (Some(source), Some(target))
+                if source == target && target.amount() < target.max_amount() => {},
+            _ => return false,
+        }
+        let source = self.slots[from].take();
+        if let (Some(source), Some(target)) = (source, self.slots[to].as_mut()) {
+            let remaining = merge_stack(target, source);
+            self.slots[from] = remaining;
+        }
+        self.recount_items();
+        true
+    }
+
+    /// Moves `amount` items off the stack in `slot` into the first empty slot.
+    /// Returns whether the stack was split, which requires a free slot and
+    /// leaving at least one item behind.
+    pub fn split_stack(&mut self, slot: usize, amount: u32) -> bool {
+        let empty = match self.slots.iter().position(Option::is_none) {
+            Some(empty) => empty,
+            None => return false,
+        };
+        let item = match self.slots.get_mut(slot) {
+            Some(Some(item)) if amount > 0 && amount < item.amount() => item,
+            _ => return false,
+        };
+        let mut split = item.duplicate();
+        if item.decrease_amount(amount).is_err() || split.set_amount(amount).is_err() {
+            return false;
+        }
+        self.slots[empty] = Some(split);
+        self.recount_items();
+        true
+    }
+
     /// Determine how many of a particular item there is in the inventory.
     pub fn item_count(&self, item_def: &ItemDef) -> u64 {
         self.slots()
@@ -257,6 +335,36 @@ impl Inventory {
     }
 }
 
+/// Moves as many items as fit from `source` onto the `target` stack, returning
+/// what is left of `source`
+fn merge_stack(target: &mut Item, mut source: Item) -> Option<Item> {
+    let moved = source
+        .amount()
+        .min(target.max_amount().saturating_sub(target.amount()));
+    if moved == 0 || target.increase_amount(moved).is_err() {
+        Some(source)
+    } else if moved == source.amount() {
+        None
+    } else {
+        source.decrease_amount(moved).ok()?;
+        Some(source)
+    }
+}
+
+/// Position of an item kind when sorting an inventory
+fn kind_order(kind: &ItemKind) -> u8 {
+    match kind {
+        ItemKind::Tool(_) => 0,
+        ItemKind::Armor(_) => 1,
+        ItemKind::Glider(_) => 2,
+        ItemKind::Lantern(_) => 3,
+        ItemKind::Consumable { .. } => 4,
+        ItemKind::Throwable { .. } | ItemKind::Thrown { .. } => 5,
+        ItemKind::Utility { .. } => 6,
+        ItemKind::Ingredient { .. } | ItemKind::Dye { .. } => 7,
+    }
+}
+
 impl Default for Inventory {
     fn default() -> Inventory {
         let mut inventory = Inventory {
@@ -287,6 +395,20 @@ pub enum InventoryUpdateEvent {
     Possession,
     Debug,
     Craft,
+    CraftFailed(CraftError),
+    /// Food that was cooking is done, and may have burnt
+    Cooked {
+        item: Item,
+        burnt: bool,
+    },
+    /// Ingredients were brewed into the named potions, none if the brew
+    /// fizzled out, revealing `learned` properties that weren't known before
+    Brewed {
+        potions: Vec<String>,
+        learned: usize,
+    },
+    BrewFailed(BrewError),
+    EquipFailed(slot::EquipError),
 }
 
 impl Default for InventoryUpdateEvent {
@@ -1,6 +1,10 @@
 use crate::{
     comp,
-    comp::{item, item::armor, ItemConfig},
+    comp::{
+        item,
+        item::{armor, Hands, ToolKind},
+        Appearance, ItemConfig, Pairing,
+    },
 };
 use comp::{Inventory, Loadout};
 use serde::{Deserialize, Serialize};
@@ -21,7 +25,7 @@ pub enum EquipSlot {
     Glider,
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Hash, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub enum ArmorSlot {
     Head,
     Neck,
@@ -36,6 +40,14 @@ pub enum ArmorSlot {
     Tabard,
 }
 
+/// Reasons for an item to be refused by an equipment slot
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum EquipError {
+    /// The weapon in the main hand needs both hands and can't be wielded
+    /// together with the item
+    HandsFull,
+}
+
 //const ALL_ARMOR_SLOTS: [ArmorSlot; 11] = [
 //    Head, Neck, Shoulders, Chest, Hands, Ring, Back, Belt, Legs, Feet, Tabard,
 //];
@@ -65,7 +77,7 @@ impl EquipSlot {
 }
 
 impl ArmorSlot {
-    fn can_hold(self, armor: &item::armor::ArmorKind) -> bool {
+    pub fn can_hold(self, armor: &item::armor::ArmorKind) -> bool {
         use item::armor::ArmorKind;
         match (self, armor) {
             (Self::Head, ArmorKind::Head(_)) => true,
@@ -84,6 +96,42 @@ impl ArmorSlot {
     }
 }
 
+fn tool_kind(item: &item::Item) -> Option<&ToolKind> {
+    match item.kind() {
+        item::ItemKind::Tool(tool) => Some(&tool.kind),
+        _ => None,
+    }
+}
+
+/// Whether `offhand` can be held in the offhand while `main` is in the main
+/// hand. A two-handed main weapon leaves no hand free for one-handed items,
+/// unless they pair up with it. Two-handed items in the offhand are only
+/// carried, so they never get in the way.
+fn fits_offhand(main: Option<&item::Item>, offhand: &item::Item) -> bool {
+    match (main.and_then(tool_kind), tool_kind(offhand)) {
+        (Some(main), Some(offhand)) => {
+            main.hands() == Hands::OneHand
+                || offhand.hands() == Hands::TwoHand
+                || Pairing::of(main, offhand).is_some()
+        },
+        _ => true,
+    }
+}
+
+fn mainhand(loadout: &Loadout) -> Option<&item::Item> {
+    loadout.active_item.as_ref().map(|i| &i.item)
+}
+
+/// Move the offhand item to the inventory if it can't be held together with
+/// the main hand weapon
+fn clear_offhand(inventory: &mut Inventory, loadout: &mut Loadout) {
+    if let Some(offhand) = loadout.second_item.as_ref() {
+        if !fits_offhand(mainhand(loadout), &offhand.item) {
+            unequip(EquipSlot::Offhand, inventory, loadout);
+        }
+    }
+}
+
 /// Replace an equipment slot with an item. Return the item that was in the
 /// slot, if any. Doesn't update the inventory.
 fn loadout_replace(
@@ -155,13 +203,21 @@ pub fn loadout_remove(equip_slot: EquipSlot, loadout: &mut Loadout) -> Option<it
     loadout_replace(equip_slot, None, loadout)
 }
 
-/// Swap item in an inventory slot with one in a loadout slot.
+/// Swap item in an inventory slot with one in a loadout slot. A two-handed
+/// weapon going to the main hand clears the offhand of items it can't be
+/// wielded with.
 fn swap_inventory_loadout(
     inventory_slot: usize,
     equip_slot: EquipSlot,
     inventory: &mut Inventory,
     loadout: &mut Loadout,
-) {
+) -> Result<(), EquipError> {
+    if let (EquipSlot::Offhand, Some(item)) = (equip_slot, inventory.get(inventory_slot)) {
+        if !fits_offhand(mainhand(loadout), item) {
+            return Err(EquipError::HandsFull);
+        }
+    }
+
     // Check if loadout slot can hold item
     if inventory
         .get(inventory_slot)
@@ -181,7 +237,11 @@ fn swap_inventory_loadout(
         if let Some(item) = from_inv {
             loadout_insert(equip_slot, item, loadout).unwrap_none(); // Can never fail
         }
+        if let EquipSlot::Mainhand = equip_slot {
+            clear_offhand(inventory, loadout);
+        }
     }
+    Ok(())
 }
 
 /// Swap items in loadout. Does nothing if items are not compatible with their
@@ -214,12 +274,14 @@ fn swap_loadout(slot_a: EquipSlot, slot_b: EquipSlot, loadout: &mut Loadout) {
 // minimizing network use)
 
 /// Swap items from two slots, regardless of if either is inventory or loadout.
+/// Fails without changing anything if an item can't be held in its new slot
+/// alongside the rest of the loadout.
 pub fn swap(
     slot_a: Slot,
     slot_b: Slot,
     inventory: Option<&mut Inventory>,
     loadout: Option<&mut Loadout>,
-) {
+) -> Result<(), EquipError> {
     match (slot_a, slot_b) {
         (Slot::Inventory(slot_a), Slot::Inventory(slot_b)) => {
             inventory.map(|i| i.swap_slots(slot_a, slot_b));
@@ -227,19 +289,34 @@ pub fn swap(
         (Slot::Inventory(inv_slot), Slot::Equip(equip_slot))
         | (Slot::Equip(equip_slot), Slot::Inventory(inv_slot)) => {
             if let Some((inventory, loadout)) = loadout.and_then(|l| inventory.map(|i| (i, l))) {
-                swap_inventory_loadout(inv_slot, equip_slot, inventory, loadout);
+                swap_inventory_loadout(inv_slot, equip_slot, inventory, loadout)?;
             }
         },
 
         (Slot::Equip(slot_a), Slot::Equip(slot_b)) => {
-            loadout.map(|l| swap_loadout(slot_a, slot_b, l));
+            if let Some(loadout) = loadout {
+                // Swapping hands puts the main hand weapon in the offhand
+                if let (EquipSlot::Mainhand, EquipSlot::Offhand)
+                | (EquipSlot::Offhand, EquipSlot::Mainhand) = (slot_a, slot_b)
+                {
+                    let offhand = loadout.second_item.as_ref().map(|i| &i.item);
+                    if let Some(main) = mainhand(loadout) {
+                        if !fits_offhand(offhand, main) {
+                            return Err(EquipError::HandsFull);
+                        }
+                    }
+                }
+                swap_loadout(slot_a, slot_b, loadout);
+            }
         },
     }
+    Ok(())
 }
 
 /// Equip an item from a slot in inventory. The currently equipped item will go
 /// into inventory. If the item is going to mainhand, put mainhand in
-/// offhand and place offhand into inventory.
+/// offhand and place offhand into inventory. The offhand is emptied if the new
+/// mainhand weapon needs both hands.
 ///
 /// ```
 /// use veloren_common::{
@@ -292,7 +369,8 @@ pub fn equip(slot: usize, inventory: &mut Inventory, loadout: &mut Loadout) {
             swap_loadout(EquipSlot::Mainhand, EquipSlot::Offhand, loadout);
         }
 
-        swap_inventory_loadout(slot, equip_slot, inventory, loadout);
+        // Nothing can be refused by the main hand or armor slots
+        let _ = swap_inventory_loadout(slot, equip_slot, inventory, loadout);
     }
 }
 
@@ -329,6 +407,82 @@ pub fn unequip(slot: EquipSlot, inventory: &mut Inventory, loadout: &mut Loadout
         .unwrap_none(); // Never fails
 }
 
+/// Dye the item in the target slot with the dye in an inventory slot, using
+/// up one of the dye. Returns whether the item could be dyed.
+pub fn dye(
+    dye_slot: usize,
+    target: Slot,
+    inventory: &mut Inventory,
+    loadout: &mut Loadout,
+) -> bool {
+    let color = match inventory.get(dye_slot).map(item::Item::kind) {
+        Some(item::ItemKind::Dye { color, .. }) => *color,
+        _ => return false,
+    };
+
+    let dyed = match target {
+        Slot::Inventory(slot) if inventory.get(slot).map_or(false, item::Item::is_dyeable) => {
+            inventory.remove(slot).map_or(false, |mut item| {
+                item.set_dye(Some(color));
+                inventory.insert(slot, item).is_ok()
+            })
+        },
+        Slot::Equip(slot @ EquipSlot::Armor(_)) => match loadout_remove(slot, loadout) {
+            Some(mut item) => {
+                let dyeable = item.is_dyeable();
+                if dyeable {
+                    item.set_dye(Some(color));
+                }
+                loadout_insert(slot, item, loadout).unwrap_none();
+                dyeable
+            },
+            None => false,
+        },
+        _ => false,
+    };
+
+    if dyed {
+        inventory.take(dye_slot);
+    }
+    dyed
+}
+
+/// What to show in an armor slot, as picked in the wardrobe
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum AppearanceChoice {
+    /// The armor equipped in the slot
+    Equipped,
+    /// Nothing at all
+    Hidden,
+    /// The armor in an inventory slot, which has to fit the armor slot
+    Item(usize),
+}
+
+/// Change what is shown in an armor slot. Returns whether the choice was
+/// valid.
+pub fn set_appearance(
+    slot: ArmorSlot,
+    choice: AppearanceChoice,
+    inventory: &Inventory,
+    loadout: &mut Loadout,
+) -> bool {
+    let appearance = match choice {
+        AppearanceChoice::Equipped => {
+            loadout.appearance.remove(&slot);
+            return true;
+        },
+        AppearanceChoice::Hidden => Appearance::Hidden,
+        AppearanceChoice::Item(i) => match inventory.get(i) {
+            Some(item) if EquipSlot::Armor(slot).can_hold(item.kind()) => {
+                Appearance::Item(item.duplicate())
+            },
+            _ => return false,
+        },
+    };
+    loadout.appearance.insert(slot, appearance);
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -394,6 +548,52 @@ mod tests {
         assert_eq!(inv.slots.len(), 1);
     }
 
+    #[test]
+    fn test_two_handed_weapons_clear_offhand() {
+        let sword = Item::new_from_asset_expect("common.items.weapons.sword.zweihander_sword_0");
+        let dagger = LoadoutBuilder::default_item_config_from_str(
+            "common.items.weapons.dagger.starter_dagger",
+        );
+
+        let mut inv = Inventory {
+            slots: vec![Some(sword.duplicate()), None],
+            amount: 1,
+        };
+
+        let mut loadout = LoadoutBuilder::new()
+            .defaults()
+            .active_item(Some(dagger.clone()))
+            .second_item(Some(dagger.clone()))
+            .build();
+
+        // The sword can't be wielded alongside a dagger
+        swap(
+            Slot::Inventory(0),
+            Slot::Equip(EquipSlot::Mainhand),
+            Some(&mut inv),
+            Some(&mut loadout),
+        )
+        .unwrap();
+        assert_eq!(Some(sword), loadout.active_item.map(|i| i.item));
+        assert_eq!(None, loadout.second_item);
+        assert_eq!(inv.slots, vec![
+            Some(dagger.item.duplicate()),
+            Some(dagger.item.duplicate())
+        ]);
+
+        // Nor can a dagger be put back in the offhand
+        assert_eq!(
+            Err(EquipError::HandsFull),
+            swap(
+                Slot::Inventory(0),
+                Slot::Equip(EquipSlot::Offhand),
+                Some(&mut inv),
+                Some(&mut loadout),
+            )
+        );
+        assert_eq!(None, loadout.second_item);
+    }
+
     #[test]
     fn test_loadout_replace() {
         let boots: Option<comp::Item> = Some(Item::new_from_asset_expect(
@@ -443,4 +643,84 @@ mod tests {
         // We should now have nothing equiped
         assert_eq!(None, loadout.active_item);
     }
+
+    #[test]
+    fn test_dye_equipped_armor() {
+        let mut inv = Inventory {
+            slots: vec![
+                Some(Item::new_from_asset_expect("common.items.dye.red")),
+                None,
+            ],
+            amount: 1,
+        };
+
+        let mut loadout = LoadoutBuilder::new()
+            .foot(Some(Item::new_from_asset_expect(
+                "common.items.testing.test_boots",
+            )))
+            .build();
+
+        // There is nothing to dye in an empty slot
+        assert!(!dye(0, Slot::Inventory(1), &mut inv, &mut loadout));
+        assert!(inv.slots[0].is_some());
+
+        let feet = Slot::Equip(EquipSlot::Armor(ArmorSlot::Feet));
+        assert!(dye(0, feet, &mut inv, &mut loadout));
+        assert!(loadout.foot.as_ref().and_then(Item::dye).is_some());
+        // The dye was used up
+        assert_eq!(inv.slots[0], None);
+        assert!(!dye(0, feet, &mut inv, &mut loadout));
+    }
+
+    #[test]
+    fn test_set_appearance() {
+        let boots = Item::new_from_asset_expect("common.items.armor.foot.leather_0");
+        let inv = Inventory {
+            slots: vec![
+                Some(boots.clone()),
+                Some(Item::new_from_asset_expect(
+                    "common.items.armor.chest.leather_0",
+                )),
+            ],
+            amount: 2,
+        };
+
+        let mut loadout = LoadoutBuilder::new()
+            .foot(Some(Item::new_from_asset_expect(
+                "common.items.testing.test_boots",
+            )))
+            .build();
+        let feet = ArmorSlot::Feet;
+
+        // Chest armor can't be shown on the feet
+        assert!(!set_appearance(
+            feet,
+            AppearanceChoice::Item(1),
+            &inv,
+            &mut loadout
+        ));
+        assert!(set_appearance(
+            feet,
+            AppearanceChoice::Item(0),
+            &inv,
+            &mut loadout
+        ));
+        assert_eq!(loadout.shown_armor(feet), Some(&boots));
+
+        assert!(set_appearance(
+            feet,
+            AppearanceChoice::Hidden,
+            &inv,
+            &mut loadout
+        ));
+        assert_eq!(loadout.shown_armor(feet), None);
+
+        assert!(set_appearance(
+            feet,
+            AppearanceChoice::Equipped,
+            &inv,
+            &mut loadout
+        ));
+        assert_eq!(loadout.shown_armor(feet), loadout.foot.as_ref());
+    }
 }
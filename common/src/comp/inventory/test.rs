@@ -69,3 +69,44 @@ fn push_all_unique_empty() {
         "Pushing unique items into an empty inventory that didn't contain them didn't work!",
     );
 }
+
+/// Splitting a stack and merging it back should give the original stack.
+#[test]
+fn split_and_merge_stack() {
+    let mut apples = Item::new_from_asset_expect("common.items.food.apple");
+    apples.set_amount(10).unwrap();
+    let mut inv = Inventory {
+        slots: vec![Some(apples), None],
+        amount: 1,
+    };
+    assert!(!inv.split_stack(0, 10));
+    assert!(inv.split_stack(0, 4));
+    assert_eq!(inv.get(0).unwrap().amount(), 6);
+    assert_eq!(inv.get(1).unwrap().amount(), 4);
+    assert!(!inv.split_stack(0, 1), "There is no free slot left");
+
+    assert!(inv.stack_merge(1, 0));
+    assert_eq!(inv.get(0).unwrap().amount(), 10);
+    assert!(inv.get(1).is_none());
+    assert_eq!(inv.amount(), 1);
+}
+
+/// Sorting should merge stacks and move items to the front.
+#[test]
+fn sorted_merges_stacks() {
+    let apple = Item::new_from_asset_expect("common.items.food.apple");
+    let inv = Inventory {
+        slots: vec![
+            None,
+            Some(apple.duplicate()),
+            Some(TEST_ITEMS[0].clone()),
+            Some(apple),
+        ],
+        amount: 3,
+    };
+    let sorted = inv.sorted();
+    assert_eq!(sorted.get(0), Some(&TEST_ITEMS[0]));
+    assert_eq!(sorted.get(1).map(Item::amount), Some(2));
+    assert!(sorted.get(2).is_none());
+    assert_eq!(sorted.amount(), 2);
+}
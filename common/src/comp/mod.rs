@@ -1,8 +1,10 @@
 mod ability;
 mod admin;
 pub mod agent;
+mod alchemy;
 pub mod beam;
 pub mod body;
+mod buff;
 mod character_state;
 pub mod chat;
 mod controller;
@@ -16,21 +18,28 @@ mod location;
 mod misc;
 mod phys;
 mod player;
+mod poise;
 pub mod projectile;
 pub mod shockwave;
 pub mod skills;
+mod sound;
 mod stats;
 pub mod visual;
 
 // Reexports
-pub use ability::{CharacterAbility, CharacterAbilityType, ItemConfig, Loadout};
+pub use ability::{
+    AbilityHotbar, AbilityId, Appearance, CharacterAbility, CharacterAbilityType, ItemConfig,
+    Loadout, Pairing, ABILITY_SLOTS,
+};
 pub use admin::Admin;
 pub use agent::{Agent, Alignment};
+pub use alchemy::AlchemyJournal;
 pub use beam::{Beam, BeamSegment};
 pub use body::{
     biped_large, bird_medium, bird_small, dragon, fish_medium, fish_small, golem, humanoid, object,
     quadruped_low, quadruped_medium, quadruped_small, theropod, AllBodies, Body, BodyData,
 };
+pub use buff::{Buff, BuffData, BuffKind, Buffs, MAX_BUFF_STACKS};
 pub use character_state::{Attacking, CharacterState, StateUpdate};
 pub use chat::{
     ChatMode, ChatMsg, ChatType, Faction, SpeechBubble, SpeechBubbleType, UnresolvedChatMsg,
@@ -39,7 +48,7 @@ pub use controller::{
     Climb, ControlAction, ControlEvent, Controller, ControllerInputs, GroupManip, Input,
     InventoryManip, MountState, Mounting,
 };
-pub use damage::{Damage, DamageSource};
+pub use damage::{Damage, DamageSource, HitModifiers, HitReaction};
 pub use energy::{Energy, EnergySource};
 pub use group::Group;
 pub use inputs::CanBuild;
@@ -51,10 +60,14 @@ pub use inventory::{
 pub use last::Last;
 pub use location::{Waypoint, WaypointArea};
 pub use misc::Object;
-pub use phys::{Collider, ForceUpdate, Gravity, Mass, Ori, PhysicsState, Pos, Scale, Sticky, Vel};
+pub use phys::{
+    BlockHit, Collider, ForceUpdate, Gravity, Mass, Ori, PhysicsState, Pos, Scale, Sticky, Vel,
+};
 pub use player::{Player, MAX_MOUNT_RANGE_SQR};
+pub use poise::Poise;
 pub use projectile::Projectile;
 pub use shockwave::{Shockwave, ShockwaveHitEntities};
-pub use skills::{Skill, SkillGroup, SkillGroupType, SkillSet};
-pub use stats::{Exp, HealthChange, HealthSource, Level, Stats};
-pub use visual::{LightAnimation, LightEmitter};
+pub use skills::{Skill, SkillError, SkillGroup, SkillGroupType, SkillSet};
+pub use sound::{Sound, SoundCategory, SoundKind};
+pub use stats::{Exp, ExpSource, Hazard, HealthChange, HealthSource, Level, Stats};
+pub use visual::{Cosmetic, LightAnimation, LightEmitter};
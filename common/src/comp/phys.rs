@@ -89,6 +89,15 @@ impl Component for Sticky {
     type Storage = FlaggedStorage<Self, NullStorage<Self>>;
 }
 
+/// A block that a point collider ran into
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockHit {
+    /// Position of the block that was hit
+    pub pos: Vec3<i32>,
+    /// Normal of the face of the block that was hit
+    pub normal: Vec3<i32>,
+}
+
 // PhysicsState
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PhysicsState {
@@ -97,6 +106,8 @@ pub struct PhysicsState {
     pub on_wall: Option<Vec3<f32>>,
     pub touch_entities: Vec<Uid>,
     pub in_fluid: Option<f32>, // Depth
+    /// The block hit during the last tick, only tracked for point colliders
+    pub hit_block: Option<BlockHit>,
 }
 
 impl PhysicsState {
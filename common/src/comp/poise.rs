@@ -0,0 +1,112 @@
+use crate::comp::{item::ItemKind, Loadout};
+use serde::{Deserialize, Serialize};
+use specs::{Component, FlaggedStorage};
+use specs_idvs::IdvStorage;
+
+/// How much punishment a character takes before it is staggered. Heavy hits
+/// wear poise down, and once it breaks the character staggers and its poise
+/// is restored. Poise recovers on its own when the character isn't hit for a
+/// while.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Poise {
+    current: u32,
+    maximum: u32,
+    /// Seconds since poise was last damaged
+    pub since_damage: f32,
+}
+
+impl Poise {
+    /// Seconds without poise damage before poise starts to recover
+    pub const REGEN_DELAY: f32 = 3.0;
+    /// Fraction of maximum poise recovered per second
+    pub const REGEN_RATE: f32 = 0.25;
+
+    /// Poise damage of a hit that changed health by `healthchange` after
+    /// blocks and armor, dealt by an attacker with `loadout`. Like melee
+    /// attacks, the damage is scaled by the poise power of the attacker's
+    /// active tool. Heals don't affect poise.
+    pub fn damage_from_hit(healthchange: f32, loadout: Option<&Loadout>) -> u32 {
+        let poise_power = loadout
+            .and_then(|loadout| loadout.active_item.as_ref())
+            .and_then(|slot| match slot.item.kind() {
+                ItemKind::Tool(tool) => Some(tool.poise_power()),
+                _ => None,
+            })
+            .unwrap_or(1.0);
+        (-healthchange * poise_power).max(0.0).round() as u32
+    }
+
+    pub fn new(amount: u32) -> Self {
+        Self {
+            current: amount,
+            maximum: amount,
+            since_damage: Self::REGEN_DELAY,
+        }
+    }
+
+    pub fn current(&self) -> u32 { self.current }
+
+    pub fn maximum(&self) -> u32 { self.maximum }
+
+    pub fn set_maximum(&mut self, amount: u32) {
+        self.maximum = amount;
+        self.current = self.current.min(self.maximum);
+    }
+
+    /// Wears poise down, returning whether it broke. Broken poise is restored
+    /// right away, so the next stagger takes the same punishment again.
+    /// Characters without any poise are never staggered.
+    pub fn damage(&mut self, amount: u32) -> bool {
+        if amount == 0 || self.maximum == 0 {
+            return false;
+        }
+        self.since_damage = 0.0;
+        if amount >= self.current {
+            self.current = self.maximum;
+            true
+        } else {
+            self.current -= amount;
+            false
+        }
+    }
+
+    /// Recovers poise over `dt` seconds, once enough time has passed since it
+    /// was last damaged
+    pub fn regen(&mut self, dt: f32) {
+        self.since_damage += dt;
+        if self.since_damage >= Self::REGEN_DELAY && self.current < self.maximum {
+            let regen = (self.maximum as f32 * Self::REGEN_RATE * dt).ceil() as u32;
+            self.current = (self.current + regen).min(self.maximum);
+        }
+    }
+}
+
+impl Component for Poise {
+    type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poise_breaks_and_recovers() {
+        let mut poise = Poise::new(100);
+        assert!(!poise.damage(60));
+        assert_eq!(poise.current(), 40);
+        // Recovery waits until the character hasn't been hit for a while
+        poise.regen(1.0);
+        assert_eq!(poise.current(), 40);
+        poise.regen(Poise::REGEN_DELAY);
+        assert!(poise.current() > 40);
+        assert!(poise.damage(100));
+        assert_eq!(poise.current(), poise.maximum());
+        assert!(!Poise::new(0).damage(100));
+    }
+
+    #[test]
+    fn hits_without_a_tool_deal_their_damage_as_poise_damage() {
+        assert_eq!(Poise::damage_from_hit(-40.4, None), 40);
+        assert_eq!(Poise::damage_from_hit(25.0, None), 0);
+    }
+}
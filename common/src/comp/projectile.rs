@@ -1,4 +1,4 @@
-use crate::{sync::Uid, Explosion};
+use crate::{comp::BuffData, sync::Uid, Explosion};
 use serde::{Deserialize, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
@@ -13,6 +13,30 @@ pub enum Effect {
     Vanish,
     Stick,
     Possess,
+    Buff(BuffData),
+}
+
+impl Effect {
+    /// Multiplies the damage and healing of the effect by `power`, and grows
+    /// explosions by their `radius_per_power`
+    pub fn adjusted_by_power(self, power: f32) -> Self {
+        match self {
+            Effect::Damage(damage) => Effect::Damage((damage as f32 * power) as i32),
+            Effect::Explode(explosion) => Effect::Explode(Explosion {
+                radius: explosion.radius + explosion.radius_per_power * (power - 1.0),
+                max_damage: (explosion.max_damage as f32 * power) as u32,
+                min_damage: (explosion.min_damage as f32 * power) as u32,
+                max_heal: (explosion.max_heal as f32 * power) as u32,
+                min_heal: (explosion.min_heal as f32 * power) as u32,
+                ..explosion
+            }),
+            Effect::Buff(buff) => Effect::Buff(BuffData {
+                strength: buff.strength * power,
+                ..buff
+            }),
+            effect => effect,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -22,12 +46,33 @@ pub struct Projectile {
     pub hit_entity: Vec<Effect>,
     /// Time left until the projectile will despawn
     pub time_left: Duration,
+    /// Air resistance while in flight, applied in the same way as the air
+    /// friction of other entities
+    pub drag: f32,
     pub owner: Option<Uid>,
     /// Whether projectile collides with entities in the same group as its
     /// owner
     pub ignore_group: bool,
 }
 
+impl Projectile {
+    /// Multiplies the damage and healing of the projectile's effects by
+    /// `power`
+    pub fn adjusted_by_power(self, power: f32) -> Self {
+        let adjust = |effects: Vec<Effect>| {
+            effects
+                .into_iter()
+                .map(|effect| effect.adjusted_by_power(power))
+                .collect()
+        };
+        Self {
+            hit_solid: adjust(self.hit_solid),
+            hit_entity: adjust(self.hit_entity),
+            ..self
+        }
+    }
+}
+
 impl Component for Projectile {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
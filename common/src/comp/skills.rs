@@ -31,6 +31,31 @@ lazy_static! {
 
         defs
     };
+
+    // Skills that have to be unlocked before another skill of the same group can be
+    pub static ref SKILL_PREREQUISITES: HashMap<Skill, HashSet<Skill>> = {
+        let mut prerequisites = HashMap::new();
+        prerequisites.insert(Skill::TestT1Skill5, [Skill::TestT1Skill4]
+                                         .iter().cloned().collect::<HashSet<Skill>>());
+
+        prerequisites.insert(Skill::TestSwordSkill3, [Skill::TestSwordSkill2]
+                                         .iter().cloned().collect::<HashSet<Skill>>());
+
+        prerequisites.insert(Skill::TestAxeSkill3, [Skill::TestAxeSkill2]
+                                         .iter().cloned().collect::<HashSet<Skill>>());
+
+        prerequisites
+    };
+
+    // Skill groups that players can unlock themselves, with the skill group whose skill points
+    // pay for them and how many points they cost. The general skill group is granted on the
+    // first level up instead.
+    pub static ref SKILL_GROUP_COSTS: HashMap<SkillGroupType, (SkillGroupType, u8)> = {
+        let mut costs = HashMap::new();
+        costs.insert(SkillGroupType::Swords, (SkillGroupType::T1, 1));
+        costs.insert(SkillGroupType::Axes, (SkillGroupType::T1, 1));
+        costs
+    };
 }
 
 /// Represents a skill that a player can unlock, that either grants them some
@@ -79,6 +104,24 @@ impl SkillGroup {
     }
 }
 
+/// Why a skill or skill group can't be unlocked or refunded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkillError {
+    AlreadyUnlocked,
+    NotUnlocked,
+    /// The skill isn't part of any skill group
+    UnknownSkill,
+    /// The player doesn't have the skill group of the skill
+    MissingSkillGroup,
+    NoSkillPoints,
+    /// Another skill has to be unlocked first
+    MissingPrerequisite(Skill),
+    /// Players can't unlock the skill group themselves
+    NotPurchasable,
+    /// An unlocked skill depends on the skill
+    RequiredBy(Skill),
+}
+
 /// Contains all of a player's skill groups and skills. Provides methods for
 /// manipulating assigned skills and skill groups including unlocking skills,
 /// refunding skills etc.
@@ -122,17 +165,82 @@ impl SkillSet {
     /// assert_eq!(skillset.skill_groups.len(), 1);
     /// ```
     pub fn unlock_skill_group(&mut self, skill_group_type: SkillGroupType) {
-        if !self
+        match self.check_unlock_skill_group(skill_group_type) {
+            Ok(()) => self.skill_groups.push(SkillGroup::new(skill_group_type)),
+            Err(_) => warn!("Tried to unlock already known skill group"),
+        }
+    }
+
+    /// Checks whether the skill group can be unlocked
+    pub fn check_unlock_skill_group(
+        &self,
+        skill_group_type: SkillGroupType,
+    ) -> Result<(), SkillError> {
+        if self
             .skill_groups
             .iter()
             .any(|x| x.skill_group_type == skill_group_type)
         {
-            self.skill_groups.push(SkillGroup::new(skill_group_type));
+            Err(SkillError::AlreadyUnlocked)
         } else {
-            warn!("Tried to unlock already known skill group");
+            Ok(())
         }
     }
 
+    /// Unlocks a skill group in exchange for skill points of another skill
+    /// group, as requested by a player.
+    ///
+    /// ```
+    /// use veloren_common::comp::skills::{SkillGroupType, SkillSet};
+    ///
+    /// let mut skillset = SkillSet::new();
+    /// skillset.unlock_skill_group(SkillGroupType::T1);
+    /// skillset.add_skill_points(SkillGroupType::T1, 1);
+    ///
+    /// skillset.purchase_skill_group(SkillGroupType::Axes);
+    ///
+    /// assert_eq!(skillset.skill_groups.len(), 2);
+    /// assert_eq!(skillset.skill_groups[0].available_sp, 0);
+    /// ```
+    pub fn purchase_skill_group(&mut self, skill_group_type: SkillGroupType) {
+        match self.check_purchase_skill_group(skill_group_type) {
+            Ok((paid_from, cost)) => {
+                if let Some(skill_group) = self.skill_group_mut(paid_from) {
+                    skill_group.available_sp -= cost;
+                    self.skill_groups.push(SkillGroup::new(skill_group_type));
+                }
+            },
+            Err(e) => warn!(
+                ?e,
+                ?skill_group_type,
+                "Tried to purchase skill group that can't be purchased"
+            ),
+        }
+    }
+
+    /// Checks whether a player can unlock the skill group: it must be one
+    /// that players unlock themselves, and the player needs the skill group
+    /// paying for it with enough skill points. Returns the paying skill group
+    /// and the cost.
+    pub fn check_purchase_skill_group(
+        &self,
+        skill_group_type: SkillGroupType,
+    ) -> Result<(SkillGroupType, u8), SkillError> {
+        self.check_unlock_skill_group(skill_group_type)?;
+        let (paid_from, cost) = *SKILL_GROUP_COSTS
+            .get(&skill_group_type)
+            .ok_or(SkillError::NotPurchasable)?;
+        let paying_group = self
+            .skill_groups
+            .iter()
+            .find(|x| x.skill_group_type == paid_from)
+            .ok_or(SkillError::MissingSkillGroup)?;
+        if paying_group.available_sp < cost {
+            return Err(SkillError::NoSkillPoints);
+        }
+        Ok((paid_from, cost))
+    }
+
     /// Unlocks a skill for a player, assuming they have the relevant skill
     /// group unlocked and available SP in that skill group.
     ///
@@ -148,31 +256,41 @@ impl SkillSet {
     /// assert_eq!(skillset.skills.len(), 1);
     /// ```
     pub fn unlock_skill(&mut self, skill: Skill) {
-        if !self.skills.contains(&skill) {
-            if let Some(skill_group_type) = SkillSet::get_skill_group_type_for_skill(&skill) {
-                if let Some(mut skill_group) = self
-                    .skill_groups
-                    .iter_mut()
-                    .find(|x| x.skill_group_type == skill_group_type)
-                {
-                    if skill_group.available_sp > 0 {
-                        skill_group.available_sp -= 1;
-                        self.skills.insert(skill);
-                    } else {
-                        warn!("Tried to unlock skill for skill group with no available SP");
-                    }
-                } else {
-                    warn!("Tried to unlock skill for a skill group that player does not have");
+        match self.check_unlock_skill(skill) {
+            Ok(skill_group_type) => {
+                if let Some(skill_group) = self.skill_group_mut(skill_group_type) {
+                    skill_group.available_sp -= 1;
+                    self.skills.insert(skill);
                 }
-            } else {
-                warn!(
-                    ?skill,
-                    "Tried to unlock skill that does not exist in any skill group!"
-                );
-            }
-        } else {
-            warn!("Tried to unlock already unlocked skill");
+            },
+            Err(e) => warn!(?e, ?skill, "Tried to unlock skill that can't be unlocked"),
+        }
+    }
+
+    /// Checks whether the skill can be unlocked: the player needs the skill
+    /// group with a skill point available, and the skill's prerequisites.
+    /// Returns the skill group the skill point is taken from.
+    pub fn check_unlock_skill(&self, skill: Skill) -> Result<SkillGroupType, SkillError> {
+        if self.skills.contains(&skill) {
+            return Err(SkillError::AlreadyUnlocked);
+        }
+        let skill_group_type =
+            SkillSet::get_skill_group_type_for_skill(&skill).ok_or(SkillError::UnknownSkill)?;
+        let skill_group = self
+            .skill_groups
+            .iter()
+            .find(|x| x.skill_group_type == skill_group_type)
+            .ok_or(SkillError::MissingSkillGroup)?;
+        if skill_group.available_sp == 0 {
+            return Err(SkillError::NoSkillPoints);
+        }
+        if let Some(missing) = SKILL_PREREQUISITES
+            .get(&skill)
+            .and_then(|prerequisites| prerequisites.iter().find(|s| !self.skills.contains(s)))
+        {
+            return Err(SkillError::MissingPrerequisite(*missing));
         }
+        Ok(skill_group_type)
     }
 
     /// Removes a skill from a player and refunds 1 skill point in the relevant
@@ -191,27 +309,47 @@ impl SkillSet {
     /// assert_eq!(skillset.skills.len(), 0);
     /// ```
     pub fn refund_skill(&mut self, skill: Skill) {
-        if self.skills.contains(&skill) {
-            if let Some(skill_group_type) = SkillSet::get_skill_group_type_for_skill(&skill) {
-                if let Some(mut skill_group) = self
-                    .skill_groups
-                    .iter_mut()
-                    .find(|x| x.skill_group_type == skill_group_type)
-                {
+        match self.check_refund_skill(skill) {
+            Ok(skill_group_type) => {
+                if let Some(skill_group) = self.skill_group_mut(skill_group_type) {
                     skill_group.available_sp += 1;
                     self.skills.remove(&skill);
-                } else {
-                    warn!("Tried to refund skill for a skill group that player does not have");
                 }
-            } else {
-                warn!(
-                    ?skill,
-                    "Tried to refund skill that does not exist in any skill group"
-                )
-            }
-        } else {
-            warn!("Tried to refund skill that has not been unlocked");
+            },
+            Err(e) => warn!(?e, ?skill, "Tried to refund skill that can't be refunded"),
+        }
+    }
+
+    /// Checks whether the skill can be refunded, which requires that no other
+    /// unlocked skill depends on it. Returns the skill group the skill point
+    /// is refunded to.
+    pub fn check_refund_skill(&self, skill: Skill) -> Result<SkillGroupType, SkillError> {
+        if !self.skills.contains(&skill) {
+            return Err(SkillError::NotUnlocked);
+        }
+        let skill_group_type =
+            SkillSet::get_skill_group_type_for_skill(&skill).ok_or(SkillError::UnknownSkill)?;
+        if !self
+            .skill_groups
+            .iter()
+            .any(|x| x.skill_group_type == skill_group_type)
+        {
+            return Err(SkillError::MissingSkillGroup);
+        }
+        if let Some(dependent) = self.skills.iter().find(|s| {
+            SKILL_PREREQUISITES
+                .get(s)
+                .map_or(false, |prerequisites| prerequisites.contains(&skill))
+        }) {
+            return Err(SkillError::RequiredBy(*dependent));
         }
+        Ok(skill_group_type)
+    }
+
+    fn skill_group_mut(&mut self, skill_group_type: SkillGroupType) -> Option<&mut SkillGroup> {
+        self.skill_groups
+            .iter_mut()
+            .find(|x| x.skill_group_type == skill_group_type)
     }
 
     /// Returns the skill group type for a skill from the static skill group
@@ -318,6 +456,60 @@ mod tests {
         assert_eq!(skillset.skills.get(&Skill::TestAxeSkill1), None);
     }
 
+    #[test]
+    fn test_skill_prerequisites() {
+        let mut skillset = SkillSet::new();
+        skillset.unlock_skill_group(SkillGroupType::Axes);
+        skillset.add_skill_points(SkillGroupType::Axes, 2);
+
+        assert_eq!(
+            skillset.check_unlock_skill(Skill::TestAxeSkill3),
+            Err(SkillError::MissingPrerequisite(Skill::TestAxeSkill2))
+        );
+
+        skillset.unlock_skill(Skill::TestAxeSkill2);
+        skillset.unlock_skill(Skill::TestAxeSkill3);
+        assert_eq!(skillset.skills.len(), 2);
+
+        // A prerequisite can't be refunded while a skill depending on it is unlocked
+        assert_eq!(
+            skillset.check_refund_skill(Skill::TestAxeSkill2),
+            Err(SkillError::RequiredBy(Skill::TestAxeSkill3))
+        );
+    }
+
+    #[test]
+    fn test_purchase_skill_group() {
+        let mut skillset = SkillSet::new();
+        assert_eq!(
+            skillset.check_purchase_skill_group(SkillGroupType::Axes),
+            Err(SkillError::MissingSkillGroup)
+        );
+
+        skillset.unlock_skill_group(SkillGroupType::T1);
+        assert_eq!(
+            skillset.check_purchase_skill_group(SkillGroupType::Axes),
+            Err(SkillError::NoSkillPoints)
+        );
+
+        skillset.add_skill_points(SkillGroupType::T1, 1);
+        skillset.purchase_skill_group(SkillGroupType::Axes);
+        assert_eq!(skillset.skill_groups.len(), 2);
+        assert_eq!(skillset.skill_groups[0].available_sp, 0);
+
+        // Skill groups can only be bought once, and the general one not at all
+        skillset.add_skill_points(SkillGroupType::T1, 1);
+        assert_eq!(
+            skillset.check_purchase_skill_group(SkillGroupType::Axes),
+            Err(SkillError::AlreadyUnlocked)
+        );
+        skillset.skill_groups.remove(0);
+        assert_eq!(
+            skillset.check_purchase_skill_group(SkillGroupType::T1),
+            Err(SkillError::NotPurchasable)
+        );
+    }
+
     #[test]
     fn test_add_skill_points() {
         let mut skillset = SkillSet::new();
@@ -0,0 +1,95 @@
+use crate::{
+    comp::{item::ToolCategory, CharacterAbilityType},
+    sync::Uid,
+};
+use serde::{Deserialize, Serialize};
+use vek::*;
+
+/// A sound made somewhere in the world. Gameplay systems emit them on the
+/// `EventBus<Sound>` and the server sends them to the clients within hearing
+/// range, so that every client plays the same sounds at the same places.
+/// The sounds of a player's own character are the exception: its client
+/// predicts them and plays them right away instead of waiting for the server.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sound {
+    pub pos: Vec3<f32>,
+    pub kind: SoundKind,
+    /// The character whose actions made the sound, if any
+    pub source: Option<Uid>,
+}
+
+impl Sound {
+    pub fn new(pos: Vec3<f32>, kind: SoundKind) -> Self {
+        Self {
+            pos,
+            kind,
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: Uid) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Whether the server sends the sound to the client of `listener` at
+    /// `pos`. Clients already played the sounds of their own character.
+    pub fn is_sent_to(&self, listener: Uid, pos: Vec3<f32>) -> bool {
+        self.source != Some(listener) && pos.distance_squared(self.pos) < self.range().powi(2)
+    }
+
+    /// Distance in blocks within which the sound is sent to clients
+    pub fn range(&self) -> f32 {
+        match self.kind.category() {
+            SoundCategory::Movement => 48.0,
+            SoundCategory::Combat => 96.0,
+            SoundCategory::Environment => 256.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SoundKind {
+    /// An entity on the ground taking a step
+    Footstep,
+    /// A melee attack striking out
+    Swing {
+        ability: CharacterAbilityType,
+        tool: ToolCategory,
+    },
+    /// The loudness grows with the power of the explosion
+    Explosion { power: f32 },
+}
+
+impl SoundKind {
+    pub fn category(&self) -> SoundCategory {
+        match self {
+            SoundKind::Footstep => SoundCategory::Movement,
+            SoundKind::Swing { .. } => SoundCategory::Combat,
+            SoundKind::Explosion { .. } => SoundCategory::Environment,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SoundCategory {
+    Movement,
+    Combat,
+    Environment,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn own_sounds_are_not_sent_back() {
+        let sound = Sound::new(Vec3::zero(), SoundKind::Footstep).with_source(Uid(1));
+        assert!(!sound.is_sent_to(Uid(1), Vec3::zero()));
+        assert!(sound.is_sent_to(Uid(2), Vec3::zero()));
+        assert!(!sound.is_sent_to(Uid(2), Vec3::unit_x() * sound.range()));
+
+        let explosion = Sound::new(Vec3::zero(), SoundKind::Explosion { power: 1.0 });
+        assert!(explosion.is_sent_to(Uid(1), Vec3::zero()));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     comp,
-    comp::{body::humanoid::Species, skills::SkillSet, Body},
+    comp::{body::humanoid::Species, skills::SkillSet, Body, CharacterAbilityType},
     sync::Uid,
 };
 use serde::{Deserialize, Serialize};
@@ -16,20 +16,56 @@ pub struct HealthChange {
 
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum HealthSource {
-    Attack { by: Uid }, // TODO: Implement weapon
-    Projectile { owner: Option<Uid> },
-    Explosion { owner: Option<Uid> },
-    Energy { owner: Option<Uid> },
+    Attack {
+        by: Uid,
+        /// The ability the attack was made with, if known
+        ability: Option<CharacterAbilityType>,
+    },
+    Projectile {
+        owner: Option<Uid>,
+    },
+    Explosion {
+        owner: Option<Uid>,
+    },
+    Energy {
+        owner: Option<Uid>,
+    },
+    Buff {
+        owner: Option<Uid>,
+    },
+    Hazard(Hazard),
     Suicide,
     World,
     Revive,
     Command,
     LevelUp,
     Item,
-    Healing { by: Option<Uid> },
+    Healing {
+        by: Option<Uid>,
+    },
     Unknown,
 }
 
+/// Dangers of the environment that can hurt entities
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hazard {
+    Falling,
+}
+
+impl HealthSource {
+    /// The entity whose attack caused the change, if any
+    pub fn attacker(&self) -> Option<Uid> {
+        match self {
+            HealthSource::Attack { by, .. } => Some(*by),
+            HealthSource::Projectile { owner }
+            | HealthSource::Explosion { owner }
+            | HealthSource::Energy { owner }
+            | HealthSource::Buff { owner } => *owner,
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Health {
     current: u32,
@@ -37,6 +73,18 @@ pub struct Health {
     pub last_change: (f64, HealthChange),
 }
 
+/// Why experience was gained
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExpSource {
+    /// Killing something, alone or as part of a group
+    Kill,
+    Quest,
+    /// Consuming an item
+    Item,
+    /// Given by an admin
+    Command,
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Exp {
     current: u32,
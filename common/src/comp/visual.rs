@@ -46,3 +46,16 @@ impl Default for LightAnimation {
 impl Component for LightAnimation {
     type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
 }
+
+/// Marks purely decorative entities, like flocks of birds, that the client
+/// spawns and simulates on its own. They never have a `Uid` and are never
+/// synced, so game logic should ignore them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cosmetic {
+    /// Members of the same flock move together
+    pub flock: u32,
+}
+
+impl Component for Cosmetic {
+    type Storage = IdvStorage<Self>;
+}
@@ -0,0 +1,61 @@
+use crate::terrain::BiomeKind;
+use vek::*;
+
+/// Distance in blocks from the world spawn over which the danger of a region
+/// goes up by one
+pub const DANGER_DISTANCE: f32 = 1500.0;
+/// Highest danger rating a region can have
+pub const MAX_DANGER: u32 = 5;
+/// Levels added to naturally spawned NPCs for every danger rating of their
+/// region
+pub const LEVELS_PER_DANGER: u32 = 4;
+/// Levels an NPC needs to gain an additional roll on its loot table
+pub const LEVELS_PER_LOOT_ROLL: u32 = 10;
+/// Most items a single NPC can drop from its loot table
+pub const MAX_LOOT_ROLLS: u32 = 3;
+
+/// Danger rating of the region at `pos`, from 0 around the world spawn at
+/// `origin` up to `MAX_DANGER` far away from it
+pub fn region_danger(origin: Vec2<f32>, pos: Vec2<f32>) -> u32 {
+    ((pos.distance(origin) / DANGER_DISTANCE) as u32).min(MAX_DANGER)
+}
+
+/// Danger added to a region by its biome, on top of its distance from the
+/// world spawn
+pub fn biome_danger(biome: BiomeKind) -> u32 {
+    match biome {
+        BiomeKind::Mountain | BiomeKind::Snowlands | BiomeKind::Desert | BiomeKind::Swamp => 1,
+        BiomeKind::Void | BiomeKind::Grassland | BiomeKind::Ocean | BiomeKind::Forest => 0,
+    }
+}
+
+/// Level of an NPC spawned in a region of the given danger rating
+pub fn scaled_level(level: u32, danger: u32) -> u32 { level + danger * LEVELS_PER_DANGER }
+
+/// Number of items an NPC of the given level drops from its loot table
+pub fn loot_rolls(level: u32) -> u32 { (1 + level / LEVELS_PER_LOOT_ROLL).min(MAX_LOOT_ROLLS) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn danger_grows_with_distance_up_to_the_max() {
+        let origin = Vec2::new(1000.0, 1000.0);
+        assert_eq!(region_danger(origin, origin), 0);
+        assert_eq!(
+            region_danger(origin, origin + Vec2::new(DANGER_DISTANCE * 2.5, 0.0)),
+            2
+        );
+        assert_eq!(
+            region_danger(origin, origin + Vec2::new(0.0, DANGER_DISTANCE * 100.0)),
+            MAX_DANGER
+        );
+        assert_eq!(biome_danger(BiomeKind::Swamp), 1);
+        assert_eq!(biome_danger(BiomeKind::Grassland), 0);
+        assert_eq!(scaled_level(3, 2), 3 + 2 * LEVELS_PER_DANGER);
+        assert_eq!(loot_rolls(1), 1);
+        assert_eq!(loot_rolls(LEVELS_PER_LOOT_ROLL), 2);
+        assert_eq!(loot_rolls(1000), MAX_LOOT_ROLLS);
+    }
+}
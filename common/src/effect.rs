@@ -2,10 +2,12 @@ use crate::comp;
 use serde::{Deserialize, Serialize};
 
 /// An effect that may be applied to an entity
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Effect {
     Health(comp::HealthChange),
     Xp(i64),
+    Energy(i32),
+    Buff(comp::BuffData),
 }
 
 impl Effect {
@@ -13,6 +15,8 @@ impl Effect {
         match self {
             Effect::Health(c) => format!("{:+} health", c.amount),
             Effect::Xp(n) => format!("{:+} exp", n),
+            Effect::Energy(n) => format!("{:+} energy", n),
+            Effect::Buff(b) => format!("{:?} for {} seconds", b.kind, b.duration),
         }
     }
 }
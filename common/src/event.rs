@@ -37,14 +37,29 @@ pub enum ServerEvent {
     Damage {
         uid: Uid,
         change: comp::HealthChange,
+        source: comp::DamageSource,
+        hit: comp::HitModifiers,
+    },
+    Buff {
+        uid: Uid,
+        buff: comp::BuffData,
+        source: Option<Uid>,
     },
     Destroy {
         entity: EcsEntity,
         cause: comp::HealthSource,
     },
+    /// Delete an entity that was left lying around for too long
+    Expire(EcsEntity),
     InventoryManip(EcsEntity, comp::InventoryManip),
     GroupManip(EcsEntity, comp::GroupManip),
     Respawn(EcsEntity),
+    SetWaypoint(EcsEntity),
+    /// Change the text of a sign, already validated. Empty text clears it.
+    EditSign {
+        pos: Vec3<i32>,
+        text: String,
+    },
     Shoot {
         entity: EcsEntity,
         dir: Dir,
@@ -63,6 +78,31 @@ pub enum ServerEvent {
         entity: EcsEntity,
         impulse: Vec3<f32>,
     },
+    /// A block was hit harder than it can withstand
+    GuardBreak {
+        entity: EcsEntity,
+    },
+    /// A hit wore down the poise of an entity
+    PoiseDamage {
+        entity: EcsEntity,
+        amount: u32,
+    },
+    /// A fishing line was cast in the given direction
+    CastLine {
+        entity: EcsEntity,
+        dir: Dir,
+    },
+    /// A fish was reeled in from the line ending at `bobber`
+    Catch {
+        entity: EcsEntity,
+        bobber: Vec3<f32>,
+    },
+    /// Food cooked for an entity is done
+    Cooked {
+        entity: EcsEntity,
+        recipe: String,
+        burnt: bool,
+    },
     BeamSegment {
         properties: comp::beam::Properties,
         pos: Pos,
@@ -77,6 +117,12 @@ pub enum ServerEvent {
     Mount(EcsEntity, EcsEntity),
     Unmount(EcsEntity),
     Possess(Uid, Uid),
+    /// Grants experience to an entity, e.g. for a kill or completing a quest
+    ExpGain {
+        entity: EcsEntity,
+        exp: i64,
+        source: comp::ExpSource,
+    },
     LevelUp(EcsEntity, u32),
     /// Inserts default components for a character when loading into the game
     InitCharacterData {
@@ -90,6 +136,14 @@ pub enum ServerEvent {
     ExitIngame {
         entity: EcsEntity,
     },
+    /// Sends the client the UI preferences stored for its character
+    LoadUiPreferences(EcsEntity),
+    StoreUiPreferences {
+        entity: EcsEntity,
+        data: Vec<u8>,
+    },
+    /// Saves the character data of a player immediately
+    PersistCharacter(EcsEntity),
     CreateNpc {
         pos: comp::Pos,
         stats: comp::Stats,
@@ -99,6 +153,8 @@ pub enum ServerEvent {
         alignment: comp::Alignment,
         scale: comp::Scale,
         drop_item: Option<Item>,
+        /// Created by the spawner, deleted again when no player is near
+        spawned: bool,
     },
     CreateWaypoint(Vec3<f32>),
     ClientDisconnect(EcsEntity),
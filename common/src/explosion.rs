@@ -3,10 +3,18 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Explosion {
     pub radius: f32,
+    /// How much the radius grows for every point of power of the weapon that
+    /// caused the explosion beyond 1.0, see
+    /// [`Effect::adjusted_by_power`](crate::comp::projectile::Effect::adjusted_by_power)
+    #[serde(default)]
+    pub radius_per_power: f32,
     pub max_damage: u32,
     pub min_damage: u32,
     pub max_heal: u32,
     pub min_heal: u32,
     pub terrain_destruction_power: f32,
     pub energy_regen: u32,
+    /// Speed at which entities at the centre are flung away, falling off
+    /// towards the edge of the radius like the damage does
+    pub knockback: f32,
 }
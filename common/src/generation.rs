@@ -1,5 +1,5 @@
 use crate::{
-    comp::{self, humanoid, Alignment, Body, Item},
+    comp::{self, agent::Schedule, humanoid, Alignment, Body, Item},
     npc::{self, NPC_NAMES},
 };
 use vek::*;
@@ -21,6 +21,7 @@ pub struct EntityInfo {
     pub scale: f32,
     pub level: Option<u32>,
     pub loot_drop: Option<Item>,
+    pub schedule: Option<Schedule>,
 }
 
 impl EntityInfo {
@@ -38,6 +39,7 @@ impl EntityInfo {
             scale: 1.0,
             level: None,
             loot_drop: None,
+            schedule: None,
         }
     }
 
@@ -103,6 +105,11 @@ impl EntityInfo {
         self
     }
 
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
     pub fn with_automatic_name(mut self) -> Self {
         self.name = match &self.body {
             Body::Humanoid(body) => Some(get_npc_name(&NPC_NAMES.humanoid, body.species)),
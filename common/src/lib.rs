@@ -17,12 +17,15 @@
     option_zip
 )]
 
+pub mod alchemy;
 pub mod assets;
 pub mod astar;
 pub mod character;
 pub mod clock;
 pub mod cmd;
+pub mod combat;
 pub mod comp;
+pub mod difficulty;
 pub mod effect;
 pub mod event;
 pub mod explosion;
@@ -30,6 +33,7 @@ pub mod figure;
 pub mod generation;
 pub mod loadout_builder;
 pub mod lottery;
+pub mod memory;
 pub mod metrics;
 pub mod msg;
 pub mod npc;
@@ -50,6 +54,7 @@ pub mod typed;
 pub mod util;
 pub mod vol;
 pub mod volumes;
+pub mod weather;
 
 pub use explosion::Explosion;
 pub use loadout_builder::LoadoutBuilder;
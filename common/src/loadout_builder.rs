@@ -3,6 +3,7 @@ use crate::comp::{
     item::{Item, ItemKind},
     Alignment, Body, CharacterAbility, ItemConfig, Loadout,
 };
+use hashbrown::HashMap;
 use rand::Rng;
 use std::time::Duration;
 
@@ -42,6 +43,7 @@ impl LoadoutBuilder {
             glider: None,
             head: None,
             tabard: None,
+            appearance: HashMap::new(),
         })
     }
 
@@ -145,14 +147,11 @@ impl LoadoutBuilder {
         };
 
         let active_item = if let Some(ItemKind::Tool(tool)) = main_tool.as_ref().map(|i| i.kind()) {
-            let mut abilities = tool.get_abilities();
-            let mut ability_drain = abilities.drain(..);
+            let abilities = tool.get_abilities();
 
             main_tool.map(|item| ItemConfig {
                 item,
-                ability1: ability_drain.next(),
-                ability2: ability_drain.next(),
-                ability3: ability_drain.next(),
+                abilities,
                 block_ability: None,
                 dodge_ability: Some(CharacterAbility::Roll),
             })
@@ -160,7 +159,7 @@ impl LoadoutBuilder {
             Some(ItemConfig {
                 // We need the empty item so npcs can attack
                 item: Item::new_from_asset_expect("common.items.weapons.empty.empty"),
-                ability1: Some(CharacterAbility::BasicMelee {
+                abilities: vec![CharacterAbility::BasicMelee {
                     energy_cost: 0,
                     buildup_duration: Duration::from_millis(0),
                     recover_duration: Duration::from_millis(400),
@@ -168,9 +167,7 @@ impl LoadoutBuilder {
                     knockback: 0.0,
                     range: 3.5,
                     max_angle: 15.0,
-                }),
-                ability2: None,
-                ability3: None,
+                }],
                 block_ability: None,
                 dodge_ability: None,
             })
@@ -212,6 +209,7 @@ impl LoadoutBuilder {
                             glider: None,
                             head: None,
                             tabard: None,
+                            appearance: HashMap::new(),
                         }
                     } else {
                         Loadout {
@@ -254,6 +252,7 @@ impl LoadoutBuilder {
                             glider: None,
                             head: None,
                             tabard: None,
+                            appearance: HashMap::new(),
                         }
                     }
                 },
@@ -290,6 +289,7 @@ impl LoadoutBuilder {
                     glider: None,
                     head: None,
                     tabard: None,
+                    appearance: HashMap::new(),
                 },
                 _ => LoadoutBuilder::animal(body).build(),
             },
@@ -310,6 +310,7 @@ impl LoadoutBuilder {
                     glider: None,
                     head: None,
                     tabard: None,
+                    appearance: HashMap::new(),
                 },
                 _ => LoadoutBuilder::animal(body).build(),
             },
@@ -329,6 +330,7 @@ impl LoadoutBuilder {
                 glider: None,
                 head: None,
                 tabard: None,
+                appearance: HashMap::new(),
             },
             _ => LoadoutBuilder::animal(body).build(),
         };
@@ -341,7 +343,7 @@ impl LoadoutBuilder {
         Self(Loadout {
             active_item: Some(ItemConfig {
                 item: Item::new_from_asset_expect("common.items.weapons.empty.empty"),
-                ability1: Some(CharacterAbility::BasicMelee {
+                abilities: vec![CharacterAbility::BasicMelee {
                     energy_cost: 10,
                     buildup_duration: Duration::from_millis(600),
                     recover_duration: Duration::from_millis(100),
@@ -349,9 +351,7 @@ impl LoadoutBuilder {
                     knockback: 0.0,
                     range: body.base_range(),
                     max_angle: 20.0,
-                }),
-                ability2: None,
-                ability3: None,
+                }],
                 block_ability: None,
                 dodge_ability: None,
             }),
@@ -369,6 +369,7 @@ impl LoadoutBuilder {
             glider: None,
             head: None,
             tabard: None,
+            appearance: HashMap::new(),
         })
     }
 
@@ -0,0 +1,96 @@
+//! Approximate memory usage of the major subsystems, so that leaks and bloat
+//! can be attributed to one of them. The numbers count the data owned by a
+//! structure and ignore allocator overhead and hash table bookkeeping, so
+//! they are estimates rather than exact figures.
+
+use crate::comp;
+use specs::{Component, Join, World, WorldExt};
+use std::mem::size_of;
+
+/// Estimates the memory a structure takes, including its heap allocations
+pub trait MemoryUsage {
+    fn memory_usage(&self) -> usize;
+}
+
+impl<T: MemoryUsage> MemoryUsage for std::sync::Arc<T> {
+    fn memory_usage(&self) -> usize { (**self).memory_usage() }
+}
+
+/// Memory used by each subsystem in bytes. Subsystems that don't exist on
+/// one side, like meshes on the server, stay at zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryReport {
+    pub terrain: usize,
+    pub meshes: usize,
+    pub ecs: usize,
+    pub network: usize,
+    pub assets: usize,
+}
+
+impl MemoryReport {
+    /// Subsystems with their name, as used for metric labels
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, usize)> {
+        vec![
+            ("terrain", self.terrain),
+            ("meshes", self.meshes),
+            ("ecs", self.ecs),
+            ("network", self.network),
+            ("assets", self.assets),
+        ]
+        .into_iter()
+    }
+
+    pub fn total(&self) -> usize { self.iter().map(|(_, bytes)| bytes).sum() }
+}
+
+fn storage_usage<C: Component>(world: &World) -> usize {
+    world.read_storage::<C>().join().count() * size_of::<C>()
+}
+
+/// Memory used by the components of the ECS. Only the inline size of each
+/// component is counted, heap data inside components like inventories is not.
+pub fn ecs_usage(world: &World) -> usize {
+    storage_usage::<comp::Pos>(world)
+        + storage_usage::<comp::Vel>(world)
+        + storage_usage::<comp::Ori>(world)
+        + storage_usage::<comp::Body>(world)
+        + storage_usage::<comp::Stats>(world)
+        + storage_usage::<comp::Energy>(world)
+        + storage_usage::<comp::Loadout>(world)
+        + storage_usage::<comp::Inventory>(world)
+        + storage_usage::<comp::CharacterState>(world)
+        + storage_usage::<comp::Controller>(world)
+        + storage_usage::<comp::PhysicsState>(world)
+        + storage_usage::<comp::Collider>(world)
+        + storage_usage::<comp::Buffs>(world)
+        + storage_usage::<comp::Agent>(world)
+        + storage_usage::<comp::Projectile>(world)
+}
+
+/// Formats a number of bytes with a binary unit, like `12.5 MiB`
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_are_formatted_with_binary_units() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     character::CharacterId,
     comp,
     comp::{Skill, SkillGroupType},
-    terrain::block::Block,
+    terrain::{block::Block, ChunkEncoding},
 };
 use serde::{Deserialize, Serialize};
 use vek::*;
@@ -42,6 +42,11 @@ pub enum ClientType {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ClientRegister {
     pub token_or_username: String,
+    /// Latest encoding of terrain chunks the client understands
+    pub chunk_encoding: ChunkEncoding,
+    /// Names of the optional protocol features the client supports, see
+    /// [`Capability`](super::Capability)
+    pub capabilities: Vec<String>,
 }
 
 /// Messages sent from the client to the server
@@ -57,6 +62,8 @@ pub enum ClientGeneral {
     DeleteCharacter(CharacterId),
     Character(CharacterId),
     Spectate,
+    /// Accept the rules sent by the server
+    AcceptRules,
     //Only in game
     ControllerInputs(comp::ControllerInputs),
     ControlEvent(comp::ControlEvent),
@@ -64,11 +71,21 @@ pub enum ClientGeneral {
     SetViewDistance(u32),
     BreakBlock(Vec3<i32>),
     PlaceBlock(Vec3<i32>, Block),
+    /// Write text on the sign at a position, see [`validate_sign_text`]
+    ///
+    /// [`validate_sign_text`]: crate::terrain::sign::validate_sign_text
+    EditSign {
+        pos: Vec3<i32>,
+        text: String,
+    },
     ExitInGame,
+    /// Physics of the player as predicted by the client
     PlayerPhysics {
         pos: comp::Pos,
         vel: comp::Vel,
         ori: comp::Ori,
+        /// Increases with every update, to be acknowledged by the server
+        seq: u64,
     },
     TerrainChunkRequest {
         key: Vec2<i32>,
@@ -76,8 +93,23 @@ pub enum ClientGeneral {
     UnlockSkill(Skill),
     RefundSkill(Skill),
     UnlockSkillGroup(SkillGroupType),
+    /// Put an ability of the active item in a slot of the ability hotbar, or
+    /// give the slot back to the skill at its position
+    SetAbilitySlot {
+        slot: usize,
+        ability: Option<comp::AbilityId>,
+    },
+    /// Ask for the UI preferences stored for the character
+    RequestUiPreferences,
+    /// Store UI preferences for the character, which the server doesn't look
+    /// into. At most `MAX_BYTES_UI_PREFERENCES` long
+    SetUiPreferences(Vec<u8>),
+    /// Ask for tiles of the world overview, by tile position
+    RequestWorldMap(Vec<Vec2<u16>>),
     //Always possible
     ChatMsg(String),
+    /// A new auth token to extend the session of the player with
+    RefreshSession(String),
     Disconnect,
     Terminate,
 }
@@ -100,7 +132,9 @@ impl ClientMsg {
                         | ClientGeneral::DeleteCharacter(_) => {
                             c_type != ClientType::ChatOnly && in_game.is_none()
                         },
-                        ClientGeneral::Character(_) | ClientGeneral::Spectate => {
+                        ClientGeneral::Character(_)
+                        | ClientGeneral::Spectate
+                        | ClientGeneral::AcceptRules => {
                             c_type == ClientType::Game && in_game.is_none()
                         },
                         //Only in game
@@ -110,16 +144,22 @@ impl ClientMsg {
                         | ClientGeneral::SetViewDistance(_)
                         | ClientGeneral::BreakBlock(_)
                         | ClientGeneral::PlaceBlock(_, _)
+                        | ClientGeneral::EditSign { .. }
                         | ClientGeneral::ExitInGame
                         | ClientGeneral::PlayerPhysics { .. }
                         | ClientGeneral::TerrainChunkRequest { .. }
                         | ClientGeneral::UnlockSkill(_)
                         | ClientGeneral::RefundSkill(_)
-                        | ClientGeneral::UnlockSkillGroup(_) => {
+                        | ClientGeneral::UnlockSkillGroup(_)
+                        | ClientGeneral::SetAbilitySlot { .. }
+                        | ClientGeneral::RequestUiPreferences
+                        | ClientGeneral::SetUiPreferences(_) => {
                             c_type == ClientType::Game && in_game.is_some()
                         },
+                        ClientGeneral::RequestWorldMap(_) => c_type != ClientType::ChatOnly,
                         //Always possible
                         ClientGeneral::ChatMsg(_)
+                        | ClientGeneral::RefreshSession(_)
                         | ClientGeneral::Disconnect
                         | ClientGeneral::Terminate => true,
                     }
@@ -129,6 +169,41 @@ impl ClientMsg {
     }
 }
 
+impl ClientGeneral {
+    /// Name of the variant, e.g. for counting messages by kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClientGeneral::RequestCharacterList => "RequestCharacterList",
+            ClientGeneral::CreateCharacter { .. } => "CreateCharacter",
+            ClientGeneral::DeleteCharacter(_) => "DeleteCharacter",
+            ClientGeneral::Character(_) => "Character",
+            ClientGeneral::Spectate => "Spectate",
+            ClientGeneral::AcceptRules => "AcceptRules",
+            ClientGeneral::ControllerInputs(_) => "ControllerInputs",
+            ClientGeneral::ControlEvent(_) => "ControlEvent",
+            ClientGeneral::ControlAction(_) => "ControlAction",
+            ClientGeneral::SetViewDistance(_) => "SetViewDistance",
+            ClientGeneral::BreakBlock(_) => "BreakBlock",
+            ClientGeneral::PlaceBlock(..) => "PlaceBlock",
+            ClientGeneral::EditSign { .. } => "EditSign",
+            ClientGeneral::ExitInGame => "ExitInGame",
+            ClientGeneral::PlayerPhysics { .. } => "PlayerPhysics",
+            ClientGeneral::TerrainChunkRequest { .. } => "TerrainChunkRequest",
+            ClientGeneral::UnlockSkill(_) => "UnlockSkill",
+            ClientGeneral::RefundSkill(_) => "RefundSkill",
+            ClientGeneral::UnlockSkillGroup(_) => "UnlockSkillGroup",
+            ClientGeneral::SetAbilitySlot { .. } => "SetAbilitySlot",
+            ClientGeneral::RequestUiPreferences => "RequestUiPreferences",
+            ClientGeneral::SetUiPreferences(_) => "SetUiPreferences",
+            ClientGeneral::RequestWorldMap(_) => "RequestWorldMap",
+            ClientGeneral::ChatMsg(_) => "ChatMsg",
+            ClientGeneral::RefreshSession(_) => "RefreshSession",
+            ClientGeneral::Disconnect => "Disconnect",
+            ClientGeneral::Terminate => "Terminate",
+        }
+    }
+}
+
 /*
 end of 2nd level Enums
 */
@@ -14,6 +14,7 @@ sum_type! {
         CanBuild(comp::CanBuild),
         Stats(comp::Stats),
         Energy(comp::Energy),
+        Poise(comp::Poise),
         LightEmitter(comp::LightEmitter),
         Item(comp::Item),
         Scale(comp::Scale),
@@ -31,6 +32,7 @@ sum_type! {
         Ori(comp::Ori),
         Shockwave(comp::Shockwave),
         BeamSegment(comp::BeamSegment),
+        Buffs(comp::Buffs),
     }
 }
 // Automatically derive From<T> for EcsCompPhantom
@@ -43,6 +45,7 @@ sum_type! {
         CanBuild(PhantomData<comp::CanBuild>),
         Stats(PhantomData<comp::Stats>),
         Energy(PhantomData<comp::Energy>),
+        Poise(PhantomData<comp::Poise>),
         LightEmitter(PhantomData<comp::LightEmitter>),
         Item(PhantomData<comp::Item>),
         Scale(PhantomData<comp::Scale>),
@@ -60,6 +63,7 @@ sum_type! {
         Ori(PhantomData<comp::Ori>),
         Shockwave(PhantomData<comp::Shockwave>),
         BeamSegment(PhantomData<comp::BeamSegment>),
+        Buffs(PhantomData<comp::Buffs>),
     }
 }
 impl sync::CompPacket for EcsCompPacket {
@@ -72,6 +76,7 @@ impl sync::CompPacket for EcsCompPacket {
             EcsCompPacket::CanBuild(comp) => sync::handle_insert(comp, entity, world),
             EcsCompPacket::Stats(comp) => sync::handle_insert(comp, entity, world),
             EcsCompPacket::Energy(comp) => sync::handle_insert(comp, entity, world),
+            EcsCompPacket::Poise(comp) => sync::handle_insert(comp, entity, world),
             EcsCompPacket::LightEmitter(comp) => sync::handle_insert(comp, entity, world),
             EcsCompPacket::Item(comp) => sync::handle_insert(comp, entity, world),
             EcsCompPacket::Scale(comp) => sync::handle_insert(comp, entity, world),
@@ -89,6 +94,7 @@ impl sync::CompPacket for EcsCompPacket {
             EcsCompPacket::Ori(comp) => sync::handle_insert(comp, entity, world),
             EcsCompPacket::Shockwave(comp) => sync::handle_insert(comp, entity, world),
             EcsCompPacket::BeamSegment(comp) => sync::handle_insert(comp, entity, world),
+            EcsCompPacket::Buffs(comp) => sync::handle_insert(comp, entity, world),
         }
     }
 
@@ -99,6 +105,7 @@ impl sync::CompPacket for EcsCompPacket {
             EcsCompPacket::CanBuild(comp) => sync::handle_modify(comp, entity, world),
             EcsCompPacket::Stats(comp) => sync::handle_modify(comp, entity, world),
             EcsCompPacket::Energy(comp) => sync::handle_modify(comp, entity, world),
+            EcsCompPacket::Poise(comp) => sync::handle_modify(comp, entity, world),
             EcsCompPacket::LightEmitter(comp) => sync::handle_modify(comp, entity, world),
             EcsCompPacket::Item(comp) => sync::handle_modify(comp, entity, world),
             EcsCompPacket::Scale(comp) => sync::handle_modify(comp, entity, world),
@@ -116,6 +123,7 @@ impl sync::CompPacket for EcsCompPacket {
             EcsCompPacket::Ori(comp) => sync::handle_modify(comp, entity, world),
             EcsCompPacket::Shockwave(comp) => sync::handle_modify(comp, entity, world),
             EcsCompPacket::BeamSegment(comp) => sync::handle_modify(comp, entity, world),
+            EcsCompPacket::Buffs(comp) => sync::handle_modify(comp, entity, world),
         }
     }
 
@@ -126,6 +134,7 @@ impl sync::CompPacket for EcsCompPacket {
             EcsCompPhantom::CanBuild(_) => sync::handle_remove::<comp::CanBuild>(entity, world),
             EcsCompPhantom::Stats(_) => sync::handle_remove::<comp::Stats>(entity, world),
             EcsCompPhantom::Energy(_) => sync::handle_remove::<comp::Energy>(entity, world),
+            EcsCompPhantom::Poise(_) => sync::handle_remove::<comp::Poise>(entity, world),
             EcsCompPhantom::LightEmitter(_) => {
                 sync::handle_remove::<comp::LightEmitter>(entity, world)
             },
@@ -147,6 +156,7 @@ impl sync::CompPacket for EcsCompPacket {
             EcsCompPhantom::Ori(_) => sync::handle_remove::<comp::Ori>(entity, world),
             EcsCompPhantom::Shockwave(_) => sync::handle_remove::<comp::Shockwave>(entity, world),
             EcsCompPhantom::BeamSegment(_) => sync::handle_remove::<comp::Ori>(entity, world),
+            EcsCompPhantom::Buffs(_) => sync::handle_remove::<comp::Buffs>(entity, world),
         }
     }
 }
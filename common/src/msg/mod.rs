@@ -1,6 +1,7 @@
 pub mod client;
 pub mod ecs_packet;
 pub mod server;
+pub mod voice;
 pub mod world_msg;
 
 // Reexports
@@ -8,13 +9,65 @@ pub use self::{
     client::{ClientGeneral, ClientMsg, ClientRegister, ClientType},
     ecs_packet::EcsCompPacket,
     server::{
-        CharacterInfo, DisconnectReason, InviteAnswer, Notification, PlayerInfo, PlayerListUpdate,
-        RegisterError, ServerGeneral, ServerInfo, ServerInit, ServerMsg, ServerRegisterAnswer,
+        CharacterInfo, DisconnectReason, InviteAnswer, NamedLocation, Notification, PlayerInfo,
+        PlayerListUpdate, RegisterError, ServerGeneral, ServerInfo, ServerInit, ServerMsg,
+        ServerRegisterAnswer,
     },
-    world_msg::WorldMapMsg,
+    voice::{VoiceFrame, VoiceRelay, MAX_BYTES_VOICE_FRAME},
+    world_msg::{OverviewCache, OverviewTile, RegionOverview, WorldMapMsg, WorldOverview},
 };
+use hashbrown::HashSet;
 use serde::{Deserialize, Serialize};
 
+/// Optional protocol features. Clients and servers tell each other which ones
+/// they support by name and ignore names they don't know, so that features can
+/// be added without breaking peers that lack them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Changed blocks are sent on their own instead of resending their chunks
+    TerrainDeltas,
+    /// Shouted chat messages, which fall back to local chat
+    ShoutChat,
+    /// UI preferences of characters are stored on the server
+    UiPreferences,
+    /// Voice chat with nearby players on a stream of its own
+    Voice,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 4] = [
+        Capability::TerrainDeltas,
+        Capability::ShoutChat,
+        Capability::UiPreferences,
+        Capability::Voice,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Capability::TerrainDeltas => "terrain_deltas",
+            Capability::ShoutChat => "shout_chat",
+            Capability::UiPreferences => "ui_preferences",
+            Capability::Voice => "voice",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| c.name() == name)
+    }
+
+    /// Names of all capabilities of this build, to send to the other side
+    pub fn supported() -> Vec<String> { Self::ALL.iter().map(|c| c.name().to_string()).collect() }
+
+    /// The capabilities among the names sent by the other side that this build
+    /// knows about
+    pub fn parse_all(names: &[String]) -> HashSet<Capability> {
+        names
+            .iter()
+            .filter_map(|name| Self::from_name(name))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ClientInGame {
     Spectator,
@@ -28,6 +81,8 @@ pub enum PingMsg {
 }
 
 pub const MAX_BYTES_CHAT_MSG: usize = 256;
+/// Largest UI preferences the server stores for a character
+pub const MAX_BYTES_UI_PREFERENCES: usize = 16 * 1024;
 
 pub enum ChatMsgValidationError {
     TooLong,
@@ -41,3 +96,76 @@ pub fn validate_chat_msg(msg: &str) -> Result<(), ChatMsgValidationError> {
         Err(ChatMsgValidationError::TooLong)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_capabilities_are_ignored() {
+        let names = vec!["terrain_deltas".to_string(), "from_the_future".to_string()];
+        let capabilities = Capability::parse_all(&names);
+        assert_eq!(capabilities.len(), 1);
+        assert!(capabilities.contains(&Capability::TerrainDeltas));
+        assert_eq!(
+            Capability::parse_all(&Capability::supported()).len(),
+            Capability::ALL.len()
+        );
+    }
+
+    /// `ClientRegister` and `ServerInfo` before they carried capabilities
+    #[derive(Serialize, Deserialize)]
+    struct OldClientRegister {
+        token_or_username: String,
+        chunk_encoding: crate::terrain::ChunkEncoding,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct OldServerInfo {
+        name: String,
+        description: String,
+        git_hash: String,
+        git_date: String,
+        auth_provider: Option<String>,
+    }
+
+    #[test]
+    fn old_register_layout_is_rejected() {
+        let register = ClientRegister {
+            token_or_username: "player".to_string(),
+            chunk_encoding: crate::terrain::ChunkEncoding::RunLength,
+            capabilities: Capability::supported(),
+        };
+        let bytes = bincode::serialize(&register).unwrap();
+        assert_eq!(
+            bincode::deserialize::<ClientRegister>(&bytes).unwrap(),
+            register
+        );
+
+        // Older clients can't send capabilities, which is why adding them
+        // bumped the major network version to keep those clients out
+        let old = bincode::serialize(&OldClientRegister {
+            token_or_username: "player".to_string(),
+            chunk_encoding: crate::terrain::ChunkEncoding::RunLength,
+        })
+        .unwrap();
+        assert!(bincode::deserialize::<ClientRegister>(&old).is_err());
+    }
+
+    #[test]
+    fn old_clients_can_read_server_info() {
+        let info = ServerInfo {
+            name: "Server".to_string(),
+            description: "Welcome".to_string(),
+            git_hash: "abc".to_string(),
+            git_date: "today".to_string(),
+            auth_provider: None,
+            capabilities: Capability::supported(),
+        };
+        let bytes = bincode::serialize(&info).unwrap();
+        let old = bincode::deserialize::<OldServerInfo>(&bytes).unwrap();
+        assert_eq!(old.name, info.name);
+        assert_eq!(old.auth_provider, info.auth_provider);
+        assert!(bincode::deserialize::<ServerInfo>(&bincode::serialize(&old).unwrap()).is_err());
+    }
+}
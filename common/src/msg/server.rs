@@ -1,12 +1,14 @@
-use super::{ClientType, EcsCompPacket, PingMsg};
+use super::{world_msg::OverviewTile, ClientType, EcsCompPacket, PingMsg};
 use crate::{
-    character::CharacterItem,
+    character::{AccountUnlocks, CharacterItem},
+    combat::CombatEvent,
     comp,
     outcome::Outcome,
     recipe::RecipeBook,
     state, sync,
     sync::Uid,
-    terrain::{Block, TerrainChunk},
+    terrain::{Block, PackedChunk},
+    weather,
 };
 use authc::AuthClientError;
 use hashbrown::HashMap;
@@ -40,6 +42,9 @@ pub struct ServerInfo {
     pub git_hash: String,
     pub git_date: String,
     pub auth_provider: Option<String>,
+    /// Names of the optional protocol features the server supports, see
+    /// [`Capability`](super::Capability)
+    pub capabilities: Vec<String>,
 }
 
 /// Reponse To ClientType
@@ -50,10 +55,15 @@ pub enum ServerInit {
     GameSync {
         entity_package: sync::EntityPackage<EcsCompPacket>,
         time_of_day: state::TimeOfDay,
+        day_cycle_factor: state::DayCycleFactor,
+        weather: weather::Weather,
         max_group_size: u32,
         client_timeout: Duration,
         world_map: crate::msg::world_msg::WorldMapMsg,
         recipe_book: RecipeBook,
+        /// Position that the danger of regions is measured from, if the
+        /// server scales NPC levels with it
+        danger_origin: Option<Vec2<f32>>,
     },
 }
 
@@ -67,9 +77,13 @@ pub enum ServerGeneral {
     CharacterDataLoadError(String),
     /// A list of characters belonging to the a authenticated player was sent
     CharacterListUpdate(Vec<CharacterItem>),
+    /// What the account of the player unlocked, such as character slots
+    AccountUnlocks(AccountUnlocks),
     /// An error occurred while creating or deleting a character
     CharacterActionError(String),
     CharacterSuccess,
+    /// Rules of the server the player has to accept before playing
+    ServerRules(String),
     //Ingame related
     GroupUpdate(comp::group::ChangeNotification<sync::Uid>),
     /// Indicate to the client that they are invited to join a group
@@ -93,14 +107,54 @@ pub enum ServerGeneral {
     /// from an ingame state
     ExitInGameSuccess,
     InventoryUpdate(comp::Inventory, comp::InventoryUpdateEvent),
+    /// The UI preferences stored for the character, if it has any
+    UiPreferences(Option<Vec<u8>>),
+    /// The skill set of the character after the server handled a skill
+    /// request, whether or not it was accepted
+    SkillSetUpdate(comp::SkillSet),
+    /// The alchemy journal of the character, only sent to its owner when it
+    /// changes
+    AlchemyJournalUpdate(comp::AlchemyJournal),
+    /// The ability hotbar of the character, only sent to its owner when it
+    /// changes
+    AbilityHotbarUpdate(comp::AbilityHotbar),
+    /// The character gained (or lost) experience
+    ExpGain {
+        exp: i64,
+        source: comp::ExpSource,
+    },
+    /// The character reached a new level
+    LevelUp(u32),
     TerrainChunkUpdate {
         key: Vec2<i32>,
-        chunk: Result<Box<TerrainChunk>, ()>,
+        chunk: Result<PackedChunk, ()>,
     },
-    TerrainBlockUpdates(HashMap<Vec3<i32>, Block>),
+    /// Blocks changed since the last tick, limited to chunks within the view
+    /// distance of the client
+    TerrainBlockUpdates(Vec<(Vec3<i32>, Block)>),
     SetViewDistance(u32),
     Outcomes(Vec<Outcome>),
+    /// Damage and kills near the client, for damage numbers and the kill feed
+    CombatEvent(CombatEvent),
+    /// Sounds emitted by gameplay systems within hearing range of the client
+    SoundEvents(Vec<comp::Sound>),
     Knockback(Vec3<f32>),
+    /// The player's character died, sent once per death so that the client
+    /// doesn't have to infer it from stats
+    YouDied {
+        killer: Option<sync::Uid>,
+        cause: comp::chat::KillSource,
+    },
+    /// The player's character was revived after dying
+    Respawned,
+    /// The state the server settled on for the player after handling the
+    /// physics update `seq`, to reconcile the client's prediction with
+    PhysicsAck {
+        seq: u64,
+        pos: comp::Pos,
+        vel: comp::Vel,
+        ori: comp::Ori,
+    },
     // Always possible
     PlayerListUpdate(PlayerListUpdate),
     /// A message to go into the client chat box. The client is responsible for
@@ -108,6 +162,7 @@ pub enum ServerGeneral {
     ChatMsg(comp::ChatMsg),
     SetPlayerEntity(Uid),
     TimeOfDay(state::TimeOfDay),
+    WeatherUpdate(weather::Weather),
     EntitySync(sync::EntitySyncPackage),
     CompSync(sync::CompSyncPackage<EcsCompPacket>),
     CreateEntity(sync::EntityPackage<EcsCompPacket>),
@@ -115,6 +170,15 @@ pub enum ServerGeneral {
     Disconnect(DisconnectReason),
     /// Send a popup notification such as "Waypoint Saved"
     Notification(Notification),
+    /// A requested tile of the world overview
+    WorldMapData(OverviewTile),
+    /// All locations named by admins, sent on joining and whenever they change
+    NamedLocations(Vec<NamedLocation>),
+    /// The auth token the client registered with is about to expire, and the
+    /// client is disconnected unless it sends a new one
+    SessionExpiring,
+    /// Answer to a new auth token sent by the client
+    SessionRefreshed(Result<(), RegisterError>),
 }
 
 /*
@@ -129,6 +193,7 @@ pub enum PlayerListUpdate {
     SelectedCharacter(Uid, CharacterInfo),
     LevelChange(Uid, u32),
     Admin(Uid, bool),
+    Idle(Uid, bool),
     Remove(Uid),
     Alias(Uid, String),
 }
@@ -137,10 +202,20 @@ pub enum PlayerListUpdate {
 pub struct PlayerInfo {
     pub is_admin: bool,
     pub is_online: bool,
+    /// The player hasn't given any input for a while
+    pub is_idle: bool,
     pub player_alias: String,
     pub character: Option<CharacterInfo>,
 }
 
+/// A place in the world that admins gave a name, shown on the map and usable
+/// as a teleport target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedLocation {
+    pub name: String,
+    pub pos: Vec3<f32>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CharacterInfo {
     pub name: String,
@@ -157,6 +232,8 @@ pub enum InviteAnswer {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Notification {
     WaypointSaved,
+    /// The player came across a campfire they can set their waypoint at
+    WaypointDiscovered,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -172,6 +249,9 @@ pub enum DisconnectReason {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum RegisterError {
     AlreadyLoggedIn,
+    /// The account is already logged in, and can't be taken over while its
+    /// player is in combat
+    InCombat,
     AuthError(String),
     Banned(String),
     InvalidCharacter,
@@ -196,10 +276,11 @@ impl ServerMsg {
                         //Character Screen related
                         ServerGeneral::CharacterDataLoadError(_)
                         | ServerGeneral::CharacterListUpdate(_)
+                        | ServerGeneral::AccountUnlocks(_)
                         | ServerGeneral::CharacterActionError(_) => {
                             c_type != ClientType::ChatOnly && in_game.is_none()
                         },
-                        ServerGeneral::CharacterSuccess => {
+                        ServerGeneral::CharacterSuccess | ServerGeneral::ServerRules(_) => {
                             c_type == ClientType::Game && in_game.is_none()
                         },
                         //Ingame related
@@ -209,24 +290,41 @@ impl ServerMsg {
                         | ServerGeneral::InviteComplete { .. }
                         | ServerGeneral::ExitInGameSuccess
                         | ServerGeneral::InventoryUpdate(_, _)
+                        | ServerGeneral::UiPreferences(_)
+                        | ServerGeneral::SkillSetUpdate(_)
+                        | ServerGeneral::AlchemyJournalUpdate(_)
+                        | ServerGeneral::AbilityHotbarUpdate(_)
+                        | ServerGeneral::ExpGain { .. }
+                        | ServerGeneral::LevelUp(_)
                         | ServerGeneral::TerrainChunkUpdate { .. }
                         | ServerGeneral::TerrainBlockUpdates(_)
                         | ServerGeneral::SetViewDistance(_)
                         | ServerGeneral::Outcomes(_)
-                        | ServerGeneral::Knockback(_) => {
+                        | ServerGeneral::CombatEvent(_)
+                        | ServerGeneral::SoundEvents(_)
+                        | ServerGeneral::Knockback(_)
+                        | ServerGeneral::YouDied { .. }
+                        | ServerGeneral::Respawned
+                        | ServerGeneral::PhysicsAck { .. } => {
                             c_type == ClientType::Game && in_game.is_some()
                         },
+                        ServerGeneral::WorldMapData(_) | ServerGeneral::NamedLocations(_) => {
+                            c_type != ClientType::ChatOnly
+                        },
                         // Always possible
                         ServerGeneral::PlayerListUpdate(_)
                         | ServerGeneral::ChatMsg(_)
                         | ServerGeneral::SetPlayerEntity(_)
                         | ServerGeneral::TimeOfDay(_)
+                        | ServerGeneral::WeatherUpdate(_)
                         | ServerGeneral::EntitySync(_)
                         | ServerGeneral::CompSync(_)
                         | ServerGeneral::CreateEntity(_)
                         | ServerGeneral::DeleteEntity(_)
                         | ServerGeneral::Disconnect(_)
-                        | ServerGeneral::Notification(_) => true,
+                        | ServerGeneral::Notification(_)
+                        | ServerGeneral::SessionExpiring
+                        | ServerGeneral::SessionRefreshed(_) => true,
                     }
             },
             ServerMsg::Ping(_) => true,
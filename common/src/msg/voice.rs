@@ -0,0 +1,35 @@
+//! Voice chat messages
+//!
+//! Clients that support [`Capability::Voice`] exchange opus-encoded audio on
+//! a stream of its own that trades reliability for latency: frames can be
+//! lost or arrive out of order, so they carry a sequence number for the
+//! receiving side to put them back in order and notice gaps. The server
+//! relays each frame to the players near the speaker.
+//!
+//! [`Capability::Voice`]: super::Capability::Voice
+
+use crate::sync::Uid;
+use serde::{Deserialize, Serialize};
+use vek::*;
+
+/// Largest opus packet a client may send in one frame. 20ms of audio at the
+/// highest bitrate opus supports fit comfortably.
+pub const MAX_BYTES_VOICE_FRAME: usize = 1275;
+
+/// A chunk of opus-encoded audio, sent from the client to the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoiceFrame {
+    /// Increases by one with every frame the speaker sends
+    pub seq: u32,
+    pub data: Vec<u8>,
+}
+
+/// A frame relayed by the server, tagged with who said it and where
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoiceRelay {
+    pub speaker: Uid,
+    /// Position of the speaker when the server relayed the frame, for
+    /// positional playback
+    pub pos: Vec3<f32>,
+    pub frame: VoiceFrame,
+}
@@ -1,6 +1,20 @@
+use crate::terrain::BiomeKind;
+use hashbrown::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use vek::*;
 
+/// Log base 2 of the side length, in chunks, of the regions summarized by the
+/// world overview
+pub const OVERVIEW_REGION_LG: u32 = 4;
+/// Log base 2 of the side length, in regions, of the tiles the world overview
+/// is sent in
+pub const OVERVIEW_TILE_LG: u32 = 4;
+/// Most tiles of the world overview a client may request at once
+pub const MAX_OVERVIEW_TILES_PER_REQUEST: usize = 16;
+
+const OVERVIEW_TILE_SIZE: u32 = 1 << OVERVIEW_TILE_LG;
+const OVERVIEW_TILE_LEN: usize = (OVERVIEW_TILE_SIZE * OVERVIEW_TILE_SIZE) as usize;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 /// World map information.  Note that currently, we always send the whole thing
 /// in one go, but the structure aims to try to provide information as locally
@@ -121,3 +135,200 @@ pub struct WorldMapMsg {
     /// (256 possible angles).
     pub horizons: [(Vec<u8>, Vec<u8>); 2],
 }
+
+/// Whether `region` lies within a world of `size` regions
+fn within(size: Vec2<u32>, region: Vec2<i32>) -> bool {
+    region
+        .map2(size, |e, size| e >= 0 && (e as u32) < size)
+        .reduce_and()
+}
+
+/// Summary of a region of the world, so that maps can be drawn without loading
+/// its terrain
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RegionOverview {
+    /// Most common biome among the chunks of the region
+    pub biome: BiomeKind,
+    /// Average altitude of the chunks of the region
+    pub alt: i16,
+}
+
+impl RegionOverview {
+    /// Placeholder for regions beyond the edge of the world
+    pub const VOID: Self = RegionOverview {
+        biome: BiomeKind::Void,
+        alt: 0,
+    };
+}
+
+/// Square piece of the world overview, as it is sent to clients
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OverviewTile {
+    /// Position of the tile, in tiles
+    pub pos: Vec2<u16>,
+    /// Regions of the tile in the usual chunk order, with the ones beyond the
+    /// edge of the world left void
+    pub regions: Vec<RegionOverview>,
+}
+
+/// Overview of the regions of the whole world, generated along with it
+#[derive(Clone, Debug)]
+pub struct WorldOverview {
+    /// Size of the world in regions
+    size: Vec2<u32>,
+    regions: Vec<RegionOverview>,
+}
+
+impl WorldOverview {
+    /// `regions`: Summary of each region, in the usual chunk order
+    pub fn new(size: Vec2<u32>, regions: Vec<RegionOverview>) -> Self {
+        assert_eq!(regions.len(), (size.x * size.y) as usize);
+        Self { size, regions }
+    }
+
+    /// Number of regions of a world with the given log base 2 of its size in
+    /// chunks. Partial regions at the edge count as whole ones.
+    pub fn regions_in(dimensions_lg: Vec2<u32>) -> Vec2<u32> {
+        dimensions_lg.map(|lg| ((1u32 << lg) + (1 << OVERVIEW_REGION_LG) - 1) >> OVERVIEW_REGION_LG)
+    }
+
+    /// Number of tiles the overview of a world with the given size in regions
+    /// is split into
+    pub fn tiles_in(size: Vec2<u32>) -> Vec2<u16> {
+        size.map(|e| ((e + OVERVIEW_TILE_SIZE - 1) / OVERVIEW_TILE_SIZE) as u16)
+    }
+
+    pub fn size(&self) -> Vec2<u32> { self.size }
+
+    /// Region containing the chunk `key`
+    pub fn region_of(key: Vec2<i32>) -> Vec2<i32> { key.map(|e| e >> OVERVIEW_REGION_LG) }
+
+    pub fn get(&self, region: Vec2<i32>) -> Option<RegionOverview> {
+        if !within(self.size, region) {
+            return None;
+        }
+        self.regions
+            .get(region.y as usize * self.size.x as usize + region.x as usize)
+            .copied()
+    }
+
+    /// Cut the tile at `pos` out of the overview
+    pub fn tile(&self, pos: Vec2<u16>) -> Option<OverviewTile> {
+        let tiles = Self::tiles_in(self.size);
+        if pos.x >= tiles.x || pos.y >= tiles.y {
+            return None;
+        }
+        let origin = pos.map(i32::from) * OVERVIEW_TILE_SIZE as i32;
+        let regions = (0..OVERVIEW_TILE_LEN)
+            .map(|i| {
+                let offset = Vec2::new(
+                    i % OVERVIEW_TILE_SIZE as usize,
+                    i / OVERVIEW_TILE_SIZE as usize,
+                );
+                self.get(origin + offset.map(|e| e as i32))
+                    .unwrap_or(RegionOverview::VOID)
+            })
+            .collect();
+        Some(OverviewTile { pos, regions })
+    }
+}
+
+/// The tiles of the world overview that a client received so far
+#[derive(Clone, Debug)]
+pub struct OverviewCache {
+    /// Size of the world in regions
+    size: Vec2<u32>,
+    tiles: HashMap<Vec2<u16>, Vec<RegionOverview>>,
+    /// Tiles that were requested, but haven't arrived yet
+    requested: HashSet<Vec2<u16>>,
+}
+
+impl OverviewCache {
+    /// `dimensions_lg`: Log base 2 of the size of the world in chunks
+    pub fn new(dimensions_lg: Vec2<u32>) -> Self {
+        Self {
+            size: WorldOverview::regions_in(dimensions_lg),
+            tiles: HashMap::new(),
+            requested: HashSet::new(),
+        }
+    }
+
+    /// Store a tile received from the server, ignoring malformed ones
+    pub fn insert(&mut self, tile: OverviewTile) {
+        let tiles = WorldOverview::tiles_in(self.size);
+        self.requested.remove(&tile.pos);
+        if tile.pos.x < tiles.x && tile.pos.y < tiles.y && tile.regions.len() == OVERVIEW_TILE_LEN {
+            self.tiles.insert(tile.pos, tile.regions);
+        }
+    }
+
+    /// Summary of the region, if its tile was received already
+    pub fn get(&self, region: Vec2<i32>) -> Option<RegionOverview> {
+        if !within(self.size, region) {
+            return None;
+        }
+        let tile = region.map(|e| (e as u32 / OVERVIEW_TILE_SIZE) as u16);
+        let offset = region.map(|e| e as u32 % OVERVIEW_TILE_SIZE);
+        self.tiles
+            .get(&tile)
+            .and_then(|regions| regions.get((offset.y * OVERVIEW_TILE_SIZE + offset.x) as usize))
+            .copied()
+    }
+
+    /// Whether every tile of the world was received
+    pub fn is_complete(&self) -> bool {
+        let tiles = WorldOverview::tiles_in(self.size);
+        self.tiles.len() >= tiles.x as usize * tiles.y as usize
+    }
+
+    /// Pick up to `max` tiles that were neither received nor requested yet,
+    /// the ones closest to the region `near` first, and mark them as requested
+    pub fn next_request(&mut self, near: Vec2<i32>, max: usize) -> Vec<Vec2<u16>> {
+        let tiles = WorldOverview::tiles_in(self.size);
+        let near = near.map(|e| e.max(0) / OVERVIEW_TILE_SIZE as i32);
+        let mut missing = (0..tiles.y)
+            .flat_map(|y| (0..tiles.x).map(move |x| Vec2::new(x, y)))
+            .filter(|pos| !self.tiles.contains_key(pos) && !self.requested.contains(pos))
+            .collect::<Vec<_>>();
+        missing.sort_by_key(|pos| (pos.map(i32::from) - near).map(|e| e.abs()).sum());
+        missing.truncate(max);
+        self.requested.extend(missing.iter().copied());
+        missing
+    }
+
+    /// Number of requested tiles that haven't arrived yet
+    pub fn pending(&self) -> usize { self.requested.len() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overview_tiles_fill_the_cache() {
+        let size = WorldOverview::regions_in(Vec2::new(9, 5));
+        let regions = (0..size.x * size.y)
+            .map(|i| RegionOverview {
+                biome: BiomeKind::Forest,
+                alt: i as i16,
+            })
+            .collect();
+        let overview = WorldOverview::new(size, regions);
+        let mut cache = OverviewCache::new(Vec2::new(9, 5));
+        assert_eq!(size, Vec2::new(32, 2));
+
+        let requested = cache.next_request(Vec2::new(19, 0), 1);
+        assert_eq!(requested, vec![Vec2::new(1, 0)]);
+        cache.insert(overview.tile(requested[0]).unwrap());
+        assert_eq!(cache.get(Vec2::new(17, 1)), overview.get(Vec2::new(17, 1)));
+        assert_eq!(cache.get(Vec2::new(3, 1)), None);
+        assert_eq!(cache.get(Vec2::new(17, 2)), None);
+        assert!(!cache.is_complete());
+
+        let requested = cache.next_request(Vec2::zero(), 4);
+        assert_eq!(requested, vec![Vec2::new(0, 0)]);
+        cache.insert(overview.tile(requested[0]).unwrap());
+        assert!(cache.is_complete());
+        assert_eq!(cache.pending(), 0);
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     comp::{self, AllBodies, Body},
 };
 use lazy_static::lazy_static;
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
 use serde::Deserialize;
 use std::{str::FromStr, sync::Arc};
 
@@ -87,17 +87,27 @@ pub fn get_npc_name(npc_type: NpcKind) -> &'static str {
 }
 
 /// Randomly generates a body associated with this NPC kind.
-pub fn kind_to_body(kind: NpcKind) -> Body {
+pub fn kind_to_body(kind: NpcKind) -> Body { kind_to_body_with(kind, &mut rand::thread_rng()) }
+
+/// Generates a body associated with this NPC kind from `rng`, so that a
+/// seeded `rng` always generates the same bodies.
+pub fn kind_to_body_with(kind: NpcKind, rng: &mut impl Rng) -> Body {
+    macro_rules! random_body {
+        ($body:ident) => {{
+            let species = *comp::$body::ALL_SPECIES.choose(rng).unwrap();
+            comp::$body::Body::random_with(rng, &species).into()
+        }};
+    }
     match kind {
-        NpcKind::Humanoid => comp::humanoid::Body::random().into(),
-        NpcKind::Pig => comp::quadruped_small::Body::random().into(),
-        NpcKind::Wolf => comp::quadruped_medium::Body::random().into(),
-        NpcKind::Duck => comp::bird_medium::Body::random().into(),
-        NpcKind::Ogre => comp::biped_large::Body::random().into(),
-        NpcKind::Archaeos => comp::theropod::Body::random().into(),
-        NpcKind::StoneGolem => comp::golem::Body::random().into(),
-        NpcKind::Reddragon => comp::dragon::Body::random().into(),
-        NpcKind::Crocodile => comp::quadruped_low::Body::random().into(),
+        NpcKind::Humanoid => random_body!(humanoid),
+        NpcKind::Pig => random_body!(quadruped_small),
+        NpcKind::Wolf => random_body!(quadruped_medium),
+        NpcKind::Duck => random_body!(bird_medium),
+        NpcKind::Ogre => random_body!(biped_large),
+        NpcKind::Archaeos => random_body!(theropod),
+        NpcKind::StoneGolem => random_body!(golem),
+        NpcKind::Reddragon => random_body!(dragon),
+        NpcKind::Crocodile => random_body!(quadruped_low),
     }
 }
 
@@ -69,6 +69,56 @@ pub struct TraversalConfig {
     pub min_tgt_dist: f32,
 }
 
+/// The number of search iterations a single chaser may perform per call
+const MAX_ITERS_PER_SEARCH: usize = 100;
+/// The number of search iterations all chasers together may perform per tick
+const MAX_ITERS_PER_TICK: usize = 2_000;
+/// The furthest an agent is willing to drop down from a ledge, in blocks
+const MAX_DROP: i32 = 4;
+
+/// A limit on the amount of pathfinding work all agents together may do in a
+/// single tick, so that many simultaneous searches can't stall the server.
+/// Searches that don't fit into the budget any more are deferred to a later
+/// tick.
+#[derive(Copy, Clone, Debug)]
+pub struct SearchBudget {
+    per_tick: usize,
+    remaining: usize,
+}
+
+impl Default for SearchBudget {
+    fn default() -> Self { Self::new(MAX_ITERS_PER_TICK) }
+}
+
+impl SearchBudget {
+    pub fn new(per_tick: usize) -> Self {
+        Self {
+            per_tick,
+            remaining: per_tick,
+        }
+    }
+
+    /// Restore the full budget at the start of a tick
+    pub fn refill(&mut self) { self.remaining = self.per_tick; }
+
+    pub fn is_exhausted(&self) -> bool { self.remaining == 0 }
+
+    /// Take up to `iters` iterations from the budget, returning how many were
+    /// granted.
+    pub fn take(&mut self, iters: usize) -> usize {
+        let granted = iters.min(self.remaining);
+        self.remaining -= granted;
+        granted
+    }
+}
+
+const CARDINALS: [Vec2<i32>; 4] = [
+    Vec2::new(0, 1),
+    Vec2::new(1, 0),
+    Vec2::new(0, -1),
+    Vec2::new(-1, 0),
+];
+
 const DIAGONALS: [Vec2<i32>; 8] = [
     Vec2::new(1, 0),
     Vec2::new(1, 1),
@@ -324,6 +374,7 @@ impl Chaser {
         vel: Vec3<f32>,
         tgt: Vec3<f32>,
         traversal_cfg: TraversalConfig,
+        budget: &mut SearchBudget,
     ) -> Option<(Vec3<f32>, f32)>
     where
         V: BaseVol<Vox = Block> + ReadVol,
@@ -378,16 +429,17 @@ impl Chaser {
             // Only search for a path if the target has moved from their last position. We
             // don't want to be thrashing the pathfinding code for targets that
             // we're unable to access!
-            if self
+            if (self
                 .last_search_tgt
                 .map(|last_tgt| last_tgt.distance(tgt) > pos_to_tgt * 0.15 + 5.0)
                 .unwrap_or(true)
                 || self.astar.is_some()
-                || self.route.is_none()
+                || self.route.is_none())
+                && !budget.is_exhausted()
             {
                 self.last_search_tgt = Some(tgt);
 
-                let (path, complete) = find_path(&mut self.astar, vol, pos, tgt);
+                let (path, complete) = find_path(&mut self.astar, vol, pos, tgt, budget);
 
                 self.route = path.map(|path| {
                     let start_index = path
@@ -453,6 +505,7 @@ fn find_path<V>(
     vol: &V,
     startf: Vec3<f32>,
     endf: Vec3<f32>,
+    budget: &mut SearchBudget,
 ) -> (Option<Path<Vec3<i32>>>, bool)
 where
     V: BaseVol<Vox = Block> + ReadVol,
@@ -479,6 +532,24 @@ where
     };
 
     let heuristic = |pos: &Vec3<i32>| (pos.distance_squared(end) as f32).sqrt();
+    // Dropping down from a ledge further than a regular step requires the whole
+    // column we fall through to be clear
+    let is_clear = |pos: Vec3<i32>| vol.get(pos).map(|b| !b.is_solid()).unwrap_or(true);
+    let drops = move |pos: Vec3<i32>| {
+        CARDINALS
+            .iter()
+            .filter(move |_| is_walkable(&pos))
+            .filter_map(move |dir| {
+                let edge = pos + Vec3::from(*dir);
+                if !(0..3).all(|z| is_clear(edge + Vec3::unit_z() * z)) {
+                    return None;
+                }
+                (3..=MAX_DROP)
+                    .map(|drop| edge - Vec3::unit_z() * drop)
+                    .take_while(|below| is_clear(*below + Vec3::unit_z() * 2))
+                    .find(|below| is_walkable(below))
+            })
+    };
     let neighbors = |pos: &Vec3<i32>| {
         let pos = *pos;
         const DIRS: [Vec3<i32>; 21] = [
@@ -545,6 +616,7 @@ where
                                 .unwrap_or(true)))
             })
             .map(move |(pos, dir)| pos + dir)
+            .chain(drops(pos))
         // .chain(
         //     DIAGONALS
         //         .iter()
@@ -565,6 +637,8 @@ where
         // straight line toward our target. This means we get smoother movement.
         1.0 + crow_line.distance_to_point(b.xy().map(|e| e as f32)) * 0.025
             + (b.z - a.z - 1).max(0) as f32 * 10.0
+            // Long drops hurt, so only take them when it saves a detour
+            + (a.z - b.z - 2).max(0) as f32 * 2.0
     };
     let satisfied = |pos: &Vec3<i32>| pos == &end;
 
@@ -573,7 +647,8 @@ where
         Some(astar) => astar,
     };
 
-    let path_result = new_astar.poll(100, heuristic, neighbors, transition, satisfied);
+    let iters = budget.take(MAX_ITERS_PER_SEARCH);
+    let path_result = new_astar.poll(iters, heuristic, neighbors, transition, satisfied);
 
     *astar = Some(new_astar);
 
@@ -593,3 +668,50 @@ where
         PathResult::Pending => (None, false),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta};
+
+    fn chase(chaser: &mut Chaser, ground: &TerrainChunk, budget: &mut SearchBudget) {
+        let traversal_cfg = TraversalConfig {
+            node_tolerance: 0.7,
+            slow_factor: 1.0,
+            on_ground: true,
+            min_tgt_dist: 1.0,
+        };
+        chaser.chase(
+            ground,
+            Vec3::new(2.5, 2.5, 0.0),
+            Vec3::zero(),
+            Vec3::new(20.5, 20.5, 0.0),
+            traversal_cfg,
+            budget,
+        );
+    }
+
+    #[test]
+    fn searches_are_deferred_once_the_budget_is_spent() {
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let air = Block::air(SpriteKind::Empty);
+        let ground = TerrainChunk::new(0, rock, air, TerrainChunkMeta::void());
+        let mut budget = SearchBudget::new(MAX_ITERS_PER_SEARCH);
+
+        let mut first = Chaser::default();
+        chase(&mut first, &ground, &mut budget);
+        assert!(first.last_search_tgt.is_some());
+        assert!(budget.is_exhausted());
+
+        // Nothing is left for other chasers in the same tick
+        let mut second = Chaser::default();
+        chase(&mut second, &ground, &mut budget);
+        assert!(second.last_search_tgt.is_none());
+        assert!(second.route.is_none() && second.astar.is_none());
+
+        // They search once the budget is refilled on the next tick
+        budget.refill();
+        chase(&mut second, &ground, &mut budget);
+        assert!(second.last_search_tgt.is_some());
+    }
+}
@@ -6,10 +6,58 @@ use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 use std::{fs::File, io::BufReader, sync::Arc};
 
+/// Why a recipe couldn't be crafted
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CraftError {
+    /// There is no recipe with the requested name
+    UnknownRecipe,
+    /// Some ingredients are missing, listed by item name and missing amount
+    MissingIngredients(Vec<(String, u32)>),
+    /// The recipe has to be cooked over a lit fire, but there is none nearby
+    NotAtFire,
+    /// Something else is already being cooked
+    AlreadyCooking,
+}
+
+/// How a recipe is cooked over a fire
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Cooking {
+    /// Seconds it takes for the food to be done
+    pub duration: f32,
+    /// What the food turns into when it burns
+    pub burnt: Arc<ItemDef>,
+    /// Chance that the food burns when the cook leaves the fire for the whole
+    /// time it is cooking
+    pub burn_chance: f32,
+}
+
+impl Cooking {
+    /// Chance that the food burns after the cook was away from the fire for
+    /// `away` seconds while it was cooking
+    pub fn burn_probability(&self, away: f32) -> f32 {
+        if self.duration > 0.0 {
+            self.burn_chance * (away / self.duration).min(1.0).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Recipe {
     pub output: (Arc<ItemDef>, u32),
     pub inputs: Vec<(Arc<ItemDef>, u32)>,
+    /// Recipes that have to be cooked only give their output once the food is
+    /// done
+    pub cooking: Option<Cooking>,
+}
+
+/// What crafting a recipe resulted in
+pub enum Crafted<'a> {
+    /// The output is in the inventory, apart from the items that didn't fit
+    Done(Option<(Item, u32)>),
+    /// The ingredients were taken, and the food has to cook before it is done
+    Cooking(&'a Cooking),
 }
 
 #[allow(clippy::type_complexity)]
@@ -19,6 +67,13 @@ impl Recipe {
         &self,
         inv: &mut Inventory,
     ) -> Result<Option<(Item, u32)>, Vec<(&ItemDef, u32)>> {
+        self.take_inputs(inv)?;
+        Ok(self.give(&self.output.0, inv))
+    }
+
+    /// Take the ingredients from the inventory, returning a list of missing
+    /// items on failure
+    pub fn take_inputs(&self, inv: &mut Inventory) -> Result<(), Vec<(&ItemDef, u32)>> {
         // Get ingredient cells from inventory,
         inv.contains_ingredients(self)?
             .into_iter()
@@ -28,15 +83,19 @@ impl Recipe {
                     inv.take(i).expect("Expected item to exist in inventory");
                 })
             });
+        Ok(())
+    }
 
+    /// Put as many of the given item as the recipe outputs into the
+    /// inventory, returning the ones that didn't fit
+    pub fn give(&self, item_def: &Arc<ItemDef>, inv: &mut Inventory) -> Option<(Item, u32)> {
         for i in 0..self.output.1 {
-            let crafted_item = Item::new(Arc::clone(&self.output.0));
+            let crafted_item = Item::new(Arc::clone(item_def));
             if let Some(item) = inv.push(crafted_item) {
-                return Ok(Some((item, self.output.1 - i)));
+                return Some((item, self.output.1 - i));
             }
         }
-
-        Ok(None)
+        None
     }
 
     pub fn inputs(&self) -> impl ExactSizeIterator<Item = (&Arc<ItemDef>, u32)> {
@@ -54,6 +113,33 @@ pub struct RecipeBook {
 impl RecipeBook {
     pub fn get(&self, recipe: &str) -> Option<&Recipe> { self.recipes.get(recipe) }
 
+    /// Crafts the named recipe with the ingredients in the inventory. Recipes
+    /// that have to be cooked need `at_fire`, and only take the ingredients.
+    pub fn craft(
+        &self,
+        recipe: &str,
+        inv: &mut Inventory,
+        at_fire: bool,
+    ) -> Result<Crafted, CraftError> {
+        let recipe = self.get(recipe).ok_or(CraftError::UnknownRecipe)?;
+        let missing = |missing: Vec<(&ItemDef, u32)>| {
+            CraftError::MissingIngredients(
+                missing
+                    .into_iter()
+                    .map(|(item_def, amount)| (item_def.name.clone(), amount))
+                    .collect(),
+            )
+        };
+        match &recipe.cooking {
+            Some(_) if !at_fire => Err(CraftError::NotAtFire),
+            Some(cooking) => recipe
+                .take_inputs(inv)
+                .map(|()| Crafted::Cooking(cooking))
+                .map_err(missing),
+            None => recipe.perform(inv).map(Crafted::Done).map_err(missing),
+        }
+    }
+
     pub fn iter(&self) -> impl ExactSizeIterator<Item = (&String, &Recipe)> { self.recipes.iter() }
 
     pub fn get_available(&self, inv: &Inventory) -> Vec<(String, Recipe)> {
@@ -65,35 +151,57 @@ impl RecipeBook {
     }
 }
 
+/// A recipe as written in the recipe book: the output and inputs by item name
+/// and amount, and optionally how it is cooked
+#[derive(Deserialize)]
+struct RawRecipe(
+    (String, u32),
+    Vec<(String, u32)>,
+    #[serde(default)] Option<RawCooking>,
+);
+
+#[derive(Deserialize)]
+struct RawCooking {
+    duration: f32,
+    burnt: String,
+    burn_chance: f32,
+}
+
 impl Asset for RecipeBook {
     const ENDINGS: &'static [&'static str] = &["ron"];
 
     fn parse(buf_reader: BufReader<File>, _specifier: &str) -> Result<Self, assets::Error> {
-        ron::de::from_reader::<
-            BufReader<File>,
-            HashMap<String, ((String, u32), Vec<(String, u32)>)>,
-        >(buf_reader)
-        .map_err(assets::Error::parse_error)
-        .and_then(|recipes| {
-            Ok(RecipeBook {
-                recipes: recipes
-                    .into_iter()
-                    .map::<Result<(String, Recipe), assets::Error>, _>(
-                        |(name, ((output, amount), inputs))| {
-                            Ok((name, Recipe {
-                                output: (ItemDef::load(&output)?, amount),
-                                inputs: inputs
-                                    .into_iter()
-                                    .map::<Result<(Arc<ItemDef>, u32), assets::Error>, _>(
-                                        |(name, amount)| Ok((ItemDef::load(&name)?, amount)),
-                                    )
-                                    .collect::<Result<_, _>>()?,
-                            }))
-                        },
-                    )
-                    .collect::<Result<_, _>>()?,
+        ron::de::from_reader::<BufReader<File>, HashMap<String, RawRecipe>>(buf_reader)
+            .map_err(assets::Error::parse_error)
+            .and_then(|recipes| {
+                Ok(RecipeBook {
+                    recipes: recipes
+                        .into_iter()
+                        .map::<Result<(String, Recipe), assets::Error>, _>(
+                            |(name, RawRecipe((output, amount), inputs, cooking))| {
+                                Ok((name, Recipe {
+                                    output: (ItemDef::load(&output)?, amount),
+                                    inputs: inputs
+                                        .into_iter()
+                                        .map::<Result<(Arc<ItemDef>, u32), assets::Error>, _>(
+                                            |(name, amount)| Ok((ItemDef::load(&name)?, amount)),
+                                        )
+                                        .collect::<Result<_, _>>()?,
+                                    cooking: cooking
+                                        .map(|cooking| {
+                                            Ok::<_, assets::Error>(Cooking {
+                                                duration: cooking.duration,
+                                                burnt: ItemDef::load(&cooking.burnt)?,
+                                                burn_chance: cooking.burn_chance,
+                                            })
+                                        })
+                                        .transpose()?,
+                                }))
+                            },
+                        )
+                        .collect::<Result<_, _>>()?,
+                })
             })
-        })
     }
 }
 
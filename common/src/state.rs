@@ -5,9 +5,10 @@ use crate::{
     region::RegionMap,
     sync::WorldSyncExt,
     sys,
-    terrain::{Block, TerrainChunk, TerrainGrid},
+    terrain::{Block, CollisionMap, TerrainChunk, TerrainGrid},
     time::DayPeriod,
     vol::{ReadVol, WriteVol},
+    weather::Weather,
 };
 use hashbrown::{HashMap, HashSet};
 use rayon::{ThreadPool, ThreadPoolBuilder};
@@ -20,14 +21,23 @@ use specs::{
 use std::{sync::Arc, time::Duration};
 use vek::*;
 
-/// How much faster should an in-game day be compared to a real day?
-// TODO: Don't hard-code this.
-const DAY_CYCLE_FACTOR: f64 = 24.0 * 2.0;
+/// How much faster should an in-game day be compared to a real day, unless
+/// the server says otherwise?
+pub const DEFAULT_DAY_CYCLE_FACTOR: f64 = 24.0 * 2.0;
 
 /// A resource that stores the time of day.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, Default)]
 pub struct TimeOfDay(pub f64);
 
+/// A resource that stores how much faster an in-game day passes compared to a
+/// real day.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct DayCycleFactor(pub f64);
+
+impl Default for DayCycleFactor {
+    fn default() -> Self { Self(DEFAULT_DAY_CYCLE_FACTOR) }
+}
+
 /// A resource that stores the tick (i.e: physics) time.
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Time(pub f64);
@@ -113,6 +123,7 @@ impl State {
         ecs.register::<comp::Player>();
         ecs.register::<comp::Stats>();
         ecs.register::<comp::Energy>();
+        ecs.register::<comp::Poise>();
         ecs.register::<comp::CanBuild>();
         ecs.register::<comp::LightEmitter>();
         ecs.register::<comp::Item>();
@@ -129,6 +140,7 @@ impl State {
         ecs.register::<comp::Shockwave>();
         ecs.register::<comp::ShockwaveHitEntities>();
         ecs.register::<comp::BeamSegment>();
+        ecs.register::<comp::Buffs>();
 
         // Register components send from clients -> server
         ecs.register::<comp::Controller>();
@@ -142,9 +154,14 @@ impl State {
         ecs.register::<comp::Ori>();
         ecs.register::<comp::Inventory>();
 
+        // Register components sent from the server to their owner only
+        ecs.register::<comp::AlchemyJournal>();
+        ecs.register::<comp::AbilityHotbar>();
+
         // Register client-local components
         // TODO: only register on the client
         ecs.register::<comp::LightAnimation>();
+        ecs.register::<comp::Cosmetic>();
 
         // Register server-local components
         // TODO: only register on the server
@@ -169,6 +186,8 @@ impl State {
 
         // Register synced resources used by the ECS.
         ecs.insert(TimeOfDay(0.0));
+        ecs.insert(DayCycleFactor::default());
+        ecs.insert(Weather::default());
 
         // Register unsynced resources used by the ECS.
         ecs.insert(Time(0.0));
@@ -176,12 +195,15 @@ impl State {
         ecs.insert(TerrainGrid::new().unwrap());
         ecs.insert(BlockChange::default());
         ecs.insert(TerrainChanges::default());
+        ecs.insert(CollisionMap::default());
         ecs.insert(EventBus::<LocalEvent>::default());
+        ecs.insert(EventBus::<comp::Sound>::default());
         // TODO: only register on the server
         ecs.insert(EventBus::<ServerEvent>::default());
         ecs.insert(comp::group::GroupManager::default());
         ecs.insert(RegionMap::new());
         ecs.insert(SysMetrics::default());
+        ecs.insert(sys::agent::AgentSchedule::default());
 
         ecs
     }
@@ -246,6 +268,9 @@ impl State {
     /// Note that this does not correspond to the time of day.
     pub fn get_time(&self) -> f64 { self.ecs.read_resource::<Time>().0 }
 
+    /// Get a reference to the weather of the world.
+    pub fn weather(&self) -> Fetch<Weather> { self.ecs.read_resource() }
+
     /// Get the current delta time.
     pub fn get_delta_time(&self) -> f32 { self.ecs.read_resource::<DeltaTime>().0 }
 
@@ -282,10 +307,13 @@ impl State {
         self.terrain_mut().drain().for_each(|(key, _)| {
             removed_chunks.insert(key);
         });
+        self.ecs.write_resource::<CollisionMap>().clear();
     }
 
     /// Insert the provided chunk into this state's terrain.
     pub fn insert_chunk(&mut self, key: Vec2<i32>, chunk: TerrainChunk) {
+        // The full data replaces any collision data kept while it was unloaded
+        self.ecs.write_resource::<CollisionMap>().remove(key);
         if self
             .ecs
             .write_resource::<TerrainGrid>()
@@ -307,12 +335,12 @@ impl State {
     /// Remove the chunk with the given key from this state's terrain, if it
     /// exists.
     pub fn remove_chunk(&mut self, key: Vec2<i32>) {
-        if self
-            .ecs
-            .write_resource::<TerrainGrid>()
-            .remove(key)
-            .is_some()
-        {
+        let removed = self.ecs.write_resource::<TerrainGrid>().remove(key);
+        if let Some(chunk) = removed {
+            // Physics prediction can still collide with it
+            self.ecs
+                .write_resource::<CollisionMap>()
+                .insert(key, &chunk);
             self.ecs
                 .write_resource::<TerrainChanges>()
                 .removed_chunks
@@ -337,7 +365,11 @@ impl State {
         // Apply block modifications
         // Only include in `TerrainChanges` if successful
         modified_blocks.retain(|pos, block| terrain.set(*pos, *block).is_ok());
-        self.ecs.write_resource::<TerrainChanges>().modified_blocks = modified_blocks;
+        // Changes made by several ticks may be waiting to be synced
+        self.ecs
+            .write_resource::<TerrainChanges>()
+            .modified_blocks
+            .extend(modified_blocks);
     }
 
     /// Execute a single tick, simulating the game state by the given duration.
@@ -348,8 +380,10 @@ impl State {
         update_terrain_and_regions: bool,
     ) {
         // Change the time accordingly.
-        self.ecs.write_resource::<TimeOfDay>().0 += dt.as_secs_f64() * DAY_CYCLE_FACTOR;
+        let day_cycle_factor = self.ecs.read_resource::<DayCycleFactor>().0;
+        self.ecs.write_resource::<TimeOfDay>().0 += dt.as_secs_f64() * day_cycle_factor;
         self.ecs.write_resource::<Time>().0 += dt.as_secs_f64();
+        self.ecs.write_resource::<Weather>().tick(dt.as_secs_f64());
 
         // Update delta time.
         // Beyond a delta time of MAX_DELTA_TIME, start lagging to avoid skipping
@@ -1,15 +1,39 @@
 use super::utils::*;
 use crate::{
-    comp::StateUpdate,
+    comp::{Ori, StateUpdate},
     sys::character_behavior::{CharacterBehavior, JoinData},
 };
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use vek::Vec3;
 
 // const BLOCK_ACCEL: f32 = 30.0;
 // const BLOCK_SPEED: f32 = 75.0;
 
-#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Eq, Hash)]
-pub struct Data;
+/// How long the guard stays broken after a block is overwhelmed
+pub const GUARD_BREAK_DURATION: Duration = Duration::from_millis(1200);
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Data {
+    /// Angle in degrees of the cone in front of the blocker that attacks are
+    /// blocked from
+    pub max_angle: f32,
+    /// Fraction of the damage of blocked attacks that is absorbed
+    pub damage_reduction: f32,
+    /// Largest hit that can be blocked, bigger ones break the guard
+    pub stability: f32,
+}
+
+impl Data {
+    /// Whether an attack coming from `source` is caught by a blocker at `pos`
+    /// facing `ori`
+    pub fn covers(&self, pos: Vec3<f32>, ori: &Ori, source: Vec3<f32>) -> bool {
+        ori.0.angle_between(source - pos) < self.max_angle.to_radians() / 2.0
+    }
+
+    /// Whether a hit of the given damage is too strong to be blocked
+    pub fn breaks_guard(&self, damage: f32) -> bool { damage > self.stability }
+}
 
 impl CharacterBehavior for Data {
     fn behavior(&self, data: &JoinData) -> StateUpdate {
@@ -60,6 +60,7 @@ impl CharacterBehavior for Data {
                 applied: false,
                 hit_count: 0,
                 knockback: self.knockback,
+                poise_damage: poise_damage(data, damage),
             });
 
             update.character = CharacterState::BasicMelee(Data {
@@ -71,6 +72,7 @@ impl CharacterBehavior for Data {
                 max_angle: self.max_angle,
                 exhausted: true,
             });
+            handle_swing_sound(data, &mut update);
         } else if self.recover_duration != Duration::default() {
             // Recovery
             update.character = CharacterState::BasicMelee(Data {
@@ -131,6 +131,7 @@ impl CharacterBehavior for Data {
                         applied: false,
                         hit_count: 0,
                         knockback,
+                        poise_damage: poise_damage(data, damage as u32),
                     });
 
                     // Starts swinging
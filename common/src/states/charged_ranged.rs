@@ -144,6 +144,7 @@ impl CharacterBehavior for Data {
                     projectile::Effect::Vanish,
                 ],
                 time_left: Duration::from_secs(15),
+                drag: 0.004,
                 owner: None,
                 ignore_group: true,
             };
@@ -125,19 +125,22 @@ impl CharacterBehavior for Data {
                     });
 
                     // Hit attempt
+                    let damage = self.static_data.stage_data[stage_index].max_damage.min(
+                        self.static_data.stage_data[stage_index].base_damage
+                            + self.combo / self.static_data.num_stages
+                                * self.static_data.stage_data[stage_index].damage_increase,
+                    );
                     data.updater.insert(data.entity, Attacking {
-                        base_damage: self.static_data.stage_data[stage_index].max_damage.min(
-                            self.static_data.stage_data[stage_index].base_damage
-                                + self.combo / self.static_data.num_stages
-                                    * self.static_data.stage_data[stage_index].damage_increase,
-                        ),
+                        base_damage: damage,
                         base_heal: 0,
                         range: self.static_data.stage_data[stage_index].range,
                         max_angle: self.static_data.stage_data[stage_index].angle.to_radians(),
                         applied: false,
                         hit_count: 0,
                         knockback: self.static_data.stage_data[stage_index].knockback,
+                        poise_damage: poise_damage(data, damage),
                     });
+                    handle_swing_sound(data, &mut update);
                 }
             },
             StageSection::Swing => {
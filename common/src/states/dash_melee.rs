@@ -128,6 +128,7 @@ impl CharacterBehavior for Data {
                             applied: false,
                             hit_count: 0,
                             knockback,
+                            poise_damage: poise_damage(data, damage as u32),
                         });
                     }
 
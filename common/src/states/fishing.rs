@@ -0,0 +1,131 @@
+//! Casting a line into water and reeling in whatever bites
+//!
+//! The line is cast after a short wind-up. The server then checks that it
+//! landed in water and decides when a fish bites. Pressing the attack button
+//! again within a short window after the bite reels the catch in, while
+//! pressing it any earlier scares the fish away.
+
+use crate::{
+    comp::{CharacterState, StateUpdate},
+    event::ServerEvent,
+    sys::character_behavior::{CharacterBehavior, JoinData},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use vek::*;
+
+/// How long to wait for the server to confirm where the line landed before
+/// giving up
+const LINE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StaticData {
+    /// How long it takes to cast the line
+    pub cast_duration: Duration,
+    /// Time after a bite during which the catch can be reeled in
+    pub reel_window: Duration,
+}
+
+/// Where the line landed and when a fish will bite, as decided by the server
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Line {
+    pub bobber: Vec3<f32>,
+    /// Time spent waiting after which a fish bites
+    pub bite_at: Duration,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Stage {
+    /// Winding up to cast the line
+    Casting,
+    /// Waiting for a bite, once the server knows where the line landed
+    Waiting(Option<Line>),
+    /// A fish is on the hook
+    Bite(Line),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Data {
+    pub static_data: StaticData,
+    pub stage: Stage,
+    /// Time spent in the current stage
+    pub timer: Duration,
+    /// Whether the attack button was let go of since the line was cast, so
+    /// that holding it down doesn't reel the line in right away
+    pub released: bool,
+}
+
+impl CharacterBehavior for Data {
+    fn behavior(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+
+        // Walking away or falling pulls the line out of the water
+        if !data.physics.on_ground || data.inputs.move_dir.magnitude_squared() > 0.0 {
+            update.character = CharacterState::Wielding;
+            return update;
+        }
+
+        let reeling = self.released && data.inputs.primary.is_pressed();
+        let timer = self.timer + Duration::from_secs_f32(data.dt.0);
+        let mut next = Data {
+            released: self.released || !data.inputs.primary.is_pressed(),
+            timer,
+            ..*self
+        };
+
+        match self.stage {
+            Stage::Casting => {
+                if self.timer >= self.static_data.cast_duration {
+                    update.server_events.push_front(ServerEvent::CastLine {
+                        entity: data.entity,
+                        dir: data.inputs.look_dir,
+                    });
+                    next.stage = Stage::Waiting(None);
+                    next.timer = Duration::default();
+                    next.released = !data.inputs.primary.is_pressed();
+                }
+            },
+            Stage::Waiting(line) => {
+                if reeling {
+                    // Too early, the fish are scared away
+                    update.character = CharacterState::Wielding;
+                    return update;
+                }
+                match line {
+                    Some(line) if self.timer >= line.bite_at => {
+                        next.stage = Stage::Bite(line);
+                        next.timer = Duration::default();
+                    },
+                    None if self.timer >= LINE_TIMEOUT => {
+                        update.character = CharacterState::Wielding;
+                        return update;
+                    },
+                    _ => {},
+                }
+            },
+            Stage::Bite(line) => {
+                if reeling {
+                    update.server_events.push_front(ServerEvent::Catch {
+                        entity: data.entity,
+                        bobber: line.bobber,
+                    });
+                    update.character = CharacterState::Wielding;
+                    return update;
+                } else if self.timer >= self.static_data.reel_window {
+                    // The fish got away
+                    update.character = CharacterState::Wielding;
+                    return update;
+                }
+            },
+        }
+
+        update.character = CharacterState::Fishing(next);
+        update
+    }
+
+    fn unwield(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+        update.character = CharacterState::Idle;
+        update
+    }
+}
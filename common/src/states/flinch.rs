@@ -0,0 +1,45 @@
+use super::utils::*;
+use crate::{
+    comp::{CharacterState, StateUpdate},
+    sys::character_behavior::{CharacterBehavior, JoinData},
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long a light hit keeps a character from acting
+pub const FLINCH_DURATION: Duration = Duration::from_millis(250);
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize, Eq, Hash)]
+pub struct Data {
+    /// Time left before the character can act again
+    pub time_left: Duration,
+    /// Whether to go back to wielding afterwards
+    pub was_wielded: bool,
+}
+
+impl CharacterBehavior for Data {
+    fn behavior(&self, data: &JoinData) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+
+        // Unlike a stagger, a flinch still lets the character stumble around
+        handle_move(data, &mut update, 0.5);
+
+        if self.time_left == Duration::default() {
+            update.character = if self.was_wielded {
+                CharacterState::Wielding
+            } else {
+                CharacterState::Idle
+            };
+        } else {
+            update.character = CharacterState::Flinch(Data {
+                time_left: self
+                    .time_left
+                    .checked_sub(Duration::from_secs_f32(data.dt.0))
+                    .unwrap_or_default(),
+                ..*self
+            });
+        }
+
+        update
+    }
+}
@@ -157,6 +157,7 @@ impl CharacterBehavior for Data {
                         applied: false,
                         hit_count: 0,
                         knockback: self.static_data.knockback,
+                        poise_damage: poise_damage(data, self.static_data.base_damage),
                     });
 
                     update.character = CharacterState::LeapMelee(Data {
@@ -10,6 +10,8 @@ pub mod combo_melee;
 pub mod dance;
 pub mod dash_melee;
 pub mod equipping;
+pub mod fishing;
+pub mod flinch;
 pub mod glide;
 pub mod glide_wield;
 pub mod idle;
@@ -20,5 +22,6 @@ pub mod shockwave;
 pub mod sit;
 pub mod sneak;
 pub mod spin_melee;
+pub mod stagger;
 pub mod utils;
 pub mod wielding;
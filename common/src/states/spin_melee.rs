@@ -113,6 +113,7 @@ impl CharacterBehavior for Data {
                         applied: false,
                         hit_count: 0,
                         knockback: self.static_data.knockback,
+                        poise_damage: poise_damage(data, self.static_data.base_damage),
                     });
                 } else if self.timer < self.static_data.swing_duration {
                     if !self.static_data.is_helicopter {
@@ -1,7 +1,8 @@
 use crate::{
     comp::{
-        item::{Hands, ItemKind, Tool},
-        Body, CharacterState, StateUpdate,
+        item::{ItemKind, Tool, ToolCategory},
+        AbilityHotbar, Body, CharacterAbility, CharacterAbilityType, CharacterState, PhysicsState,
+        Sound, SoundKind, StateUpdate,
     },
     event::LocalEvent,
     states::*,
@@ -12,6 +13,8 @@ use serde::{Deserialize, Serialize};
 use vek::*;
 
 pub const MOVEMENT_THRESHOLD_VEL: f32 = 3.0;
+/// Distance in blocks covered by a single footstep
+const FOOTSTEP_LENGTH: f32 = 2.5;
 const BASE_HUMANOID_AIR_ACCEL: f32 = 8.0;
 const BASE_HUMANOID_WATER_ACCEL: f32 = 150.0;
 const BASE_HUMANOID_WATER_SPEED: f32 = 180.0;
@@ -68,6 +71,10 @@ impl Body {
 
 /// Handles updating `Components` to move player based on state of `JoinData`
 pub fn handle_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32) {
+    let mut efficiency = efficiency * data.buffs.map_or(1.0, |b| b.speed_modifier());
+    if data.character.is_wield() {
+        efficiency *= data.loadout.wield_speed_modifier();
+    }
     if let Some(depth) = data.physics.in_fluid {
         swim_move(data, update, efficiency, depth);
     } else {
@@ -75,19 +82,58 @@ pub fn handle_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32) {
     }
 }
 
+/// Acceleration of a character moving on the ground or in the air
+pub fn move_accel(body: &Body, physics: &PhysicsState) -> f32 {
+    if physics.on_ground {
+        body.base_accel()
+    } else {
+        BASE_HUMANOID_AIR_ACCEL
+    }
+}
+
 /// Updates components to move player as if theyre on ground or in air
 #[allow(clippy::assign_op_pattern)] // TODO: Pending review in #587
 fn basic_move(data: &JoinData, update: &mut StateUpdate, efficiency: f32) {
-    let accel = if data.physics.on_ground {
-        data.body.base_accel()
-    } else {
-        BASE_HUMANOID_AIR_ACCEL
-    };
+    let accel = move_accel(data.body, data.physics);
 
     update.vel.0 =
         update.vel.0 + Vec2::broadcast(data.dt.0) * data.inputs.move_dir * accel * efficiency;
 
     handle_orientation(data, update, data.body.base_ori_rate());
+    handle_footstep(data, update);
+}
+
+/// Emits a footstep sound whenever a walking entity crosses a multiple of
+/// `FOOTSTEP_LENGTH` along its direction of movement. Deriving steps from the
+/// position keeps this stateless, so the server and clients agree on them.
+fn handle_footstep(data: &JoinData, update: &mut StateUpdate) {
+    let vel = data.vel.0.xy();
+    let speed = vel.magnitude();
+    if !data.physics.on_ground || speed < MOVEMENT_THRESHOLD_VEL {
+        return;
+    }
+    let travelled = data.pos.0.xy().dot(vel / speed);
+    let before = travelled - speed * data.dt.0;
+    if (travelled / FOOTSTEP_LENGTH).floor() != (before / FOOTSTEP_LENGTH).floor() {
+        update
+            .sounds
+            .push_back(Sound::new(data.pos.0, SoundKind::Footstep).with_source(*data.uid));
+    }
+}
+
+/// Emits the sound of a melee swing with the active weapon
+pub fn handle_swing_sound(data: &JoinData, update: &mut StateUpdate) {
+    let tool = match data.loadout.active_item.as_ref().map(|i| i.item.kind()) {
+        Some(ItemKind::Tool(tool)) => ToolCategory::from(&tool.kind),
+        _ => ToolCategory::Empty,
+    };
+    update.sounds.push_back(
+        Sound::new(data.pos.0, SoundKind::Swing {
+            ability: CharacterAbilityType::from(&update.character),
+            tool,
+        })
+        .with_source(*data.uid),
+    );
 }
 
 /// Similar to basic_move function, but with forced forward movement
@@ -205,9 +251,11 @@ pub fn handle_climb(data: &JoinData, update: &mut StateUpdate) {
     }
 }
 
-/// Checks that player can Swap Weapons and updates `Loadout` if so
+/// Checks that player can Swap Weapons and updates `Loadout` if so. Items
+/// wielded together aren't swapped, since the offhand item would end up in the
+/// main hand.
 pub fn attempt_swap_loadout(data: &JoinData, update: &mut StateUpdate) {
-    if data.loadout.second_item.is_some() {
+    if data.loadout.second_item.is_some() && data.loadout.pairing().is_none() {
         update.swap_loadout = true;
     }
 }
@@ -243,80 +291,62 @@ pub fn handle_jump(data: &JoinData, update: &mut StateUpdate) {
     }
 }
 
-/// Will attempt to go into `loadout.active_item.ability1`
+/// Will attempt to go into the primary ability of the loadout, which combines
+/// the active item with the offhand item when dual wielding
 pub fn handle_ability1_input(data: &JoinData, update: &mut StateUpdate) {
     if data.inputs.primary.is_pressed() {
         if let Some(ability) = data
             .loadout
-            .active_item
-            .as_ref()
-            .and_then(|i| i.ability1.as_ref())
+            .primary_ability()
             .filter(|ability| ability.requirements_paid(data, update))
         {
-            update.character = (ability, AbilityKey::Mouse1).into();
+            update.character = (&*ability, AbilityKey::Mouse1).into();
         }
     }
 }
 
-/// Will attempt to go into `loadout.active_item.ability2`
+/// Will attempt to go into the secondary ability of the loadout, which is the
+/// one of the offhand item if there is one
 pub fn handle_ability2_input(data: &JoinData, update: &mut StateUpdate) {
     if data.inputs.secondary.is_pressed() {
-        let active_tool_kind = match data.loadout.active_item.as_ref().map(|i| i.item.kind()) {
-            Some(ItemKind::Tool(Tool { kind, .. })) => Some(kind),
-            _ => None,
-        };
-
-        let second_tool_kind = match data.loadout.second_item.as_ref().map(|i| i.item.kind()) {
-            Some(ItemKind::Tool(Tool { kind, .. })) => Some(kind),
-            _ => None,
-        };
-
-        match (
-            active_tool_kind.map(|tk| tk.hands()),
-            second_tool_kind.map(|tk| tk.hands()),
-        ) {
-            (Some(Hands::TwoHand), _) => {
-                if let Some(ability) = data
-                    .loadout
-                    .active_item
-                    .as_ref()
-                    .and_then(|i| i.ability2.as_ref())
-                    .filter(|ability| ability.requirements_paid(data, update))
-                {
-                    update.character = (ability, AbilityKey::Mouse2).into();
-                }
-            },
-            (_, Some(Hands::OneHand)) => {
-                if let Some(ability) = data
-                    .loadout
-                    .second_item
-                    .as_ref()
-                    .and_then(|i| i.ability2.as_ref())
-                    .filter(|ability| ability.requirements_paid(data, update))
-                {
-                    update.character = (ability, AbilityKey::Mouse2).into();
-                }
-            },
-            (_, _) => {},
-        };
+        if let Some(ability) = data
+            .loadout
+            .secondary_ability()
+            .filter(|ability| ability.requirements_paid(data, update))
+        {
+            update.character = (ability, AbilityKey::Mouse2).into();
+        }
     }
 }
 
-/// Will attempt to go into `loadout.active_item.ability3`
+/// Will attempt to go into the ability in the first slot of the ability hotbar
 pub fn handle_ability3_input(data: &JoinData, update: &mut StateUpdate) {
     if data.inputs.ability3.is_pressed() {
-        if let Some(ability) = data
-            .loadout
-            .active_item
-            .as_ref()
-            .and_then(|i| i.ability3.as_ref())
-            .filter(|ability| ability.requirements_paid(data, update))
+        if let Some(ability) =
+            hotbar_ability(data, 0).filter(|ability| ability.requirements_paid(data, update))
         {
             update.character = (ability, AbilityKey::Skill1).into();
         }
     }
 }
 
+/// Will attempt to go into the ability in the given slot of the ability hotbar
+pub fn handle_ability_slot(data: &JoinData, update: &mut StateUpdate, slot: usize) {
+    if let Some(ability) =
+        hotbar_ability(data, slot).filter(|ability| ability.requirements_paid(data, update))
+    {
+        update.character = (ability, AbilityKey::Slot(slot)).into();
+    }
+}
+
+fn hotbar_ability<'a>(data: &JoinData<'a>, slot: usize) -> Option<&'a CharacterAbility> {
+    let item = data.loadout.active_item.as_ref()?;
+    match data.hotbar {
+        Some(hotbar) => hotbar.ability(slot, item),
+        None => AbilityHotbar::default().ability(slot, item),
+    }
+}
+
 /// Checks that player can perform a dodge, then
 /// attempts to go into `loadout.active_item.dodge_ability`
 pub fn handle_dodge_input(data: &JoinData, update: &mut StateUpdate) {
@@ -348,6 +378,12 @@ pub fn unwrap_tool_data<'a>(data: &'a JoinData) -> Option<&'a Tool> {
     }
 }
 
+/// Poise damage of a hit dealing `damage` with the active tool
+pub fn poise_damage(data: &JoinData, damage: u32) -> u32 {
+    let poise_power = unwrap_tool_data(data).map_or(1.0, |tool| tool.poise_power());
+    (damage as f32 * poise_power).round() as u32
+}
+
 pub fn handle_interrupt(data: &JoinData, update: &mut StateUpdate) {
     handle_ability1_input(data, update);
     handle_ability2_input(data, update);
@@ -361,6 +397,8 @@ pub fn ability_key_is_pressed(data: &JoinData, ability_key: AbilityKey) -> bool
         AbilityKey::Mouse2 => data.inputs.secondary.is_pressed(),
         AbilityKey::Skill1 => data.inputs.ability3.is_pressed(),
         AbilityKey::Dodge => data.inputs.roll.is_pressed(),
+        // Slots are used through one-off actions, so they can't be held
+        AbilityKey::Slot(_) => false,
     }
 }
 
@@ -384,4 +422,6 @@ pub enum AbilityKey {
     Mouse2,
     Skill1,
     Dodge,
+    /// A slot of the ability hotbar, other than through `Skill1`
+    Slot(usize),
 }
@@ -56,4 +56,10 @@ impl CharacterBehavior for Data {
         attempt_swap_loadout(data, &mut update);
         update
     }
+
+    fn use_ability_slot(&self, data: &JoinData, slot: usize) -> StateUpdate {
+        let mut update = StateUpdate::from(data);
+        handle_ability_slot(data, &mut update, slot);
+        update
+    }
 }
@@ -1,10 +1,12 @@
 use super::{track::UpdateTracker, uid::Uid};
+use hashbrown::HashSet;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use specs::{Component, Entity, Join, ReadStorage, World, WorldExt};
 use std::{
     convert::{TryFrom, TryInto},
     fmt::Debug,
     marker::PhantomData,
+    mem,
 };
 use tracing::error;
 
@@ -84,7 +86,7 @@ impl<P: CompPacket> StatePackage<P> {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct EntitySyncPackage {
     pub created_entities: Vec<u64>,
     pub deleted_entities: Vec<u64>,
@@ -107,6 +109,18 @@ impl EntitySyncPackage {
             deleted_entities,
         }
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.created_entities.is_empty() && self.deleted_entities.is_empty()
+    }
+
+    /// Append the creations and deletions of another package to this one
+    pub fn merge(&mut self, other: &Self) {
+        self.created_entities
+            .extend_from_slice(&other.created_entities);
+        self.deleted_entities
+            .extend_from_slice(&other.deleted_entities);
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -147,6 +161,36 @@ impl<P: CompPacket> CompSyncPackage<P> {
             .push((uid.into(), CompUpdateKind::Removed(PhantomData::<C>.into())));
     }
 
+    pub fn is_empty(&self) -> bool { self.comp_updates.is_empty() }
+
+    /// Remove all updates, keeping the allocated memory for reuse
+    pub fn clear(&mut self) { self.comp_updates.clear(); }
+
+    /// Append the updates of another package to this one, keeping their order
+    pub fn merge(&mut self, other: &Self) {
+        self.comp_updates.extend(other.comp_updates.iter().cloned());
+    }
+
+    /// Drop modifications that are superseded by a later insertion or
+    /// modification of the same component on the same entity, so that only the
+    /// latest state of each component is sent.
+    pub fn coalesce(&mut self) {
+        let mut seen = HashSet::new();
+        let mut updates = mem::take(&mut self.comp_updates);
+        // Walk backwards so the latest update for each component is kept
+        updates.reverse();
+        updates.retain(|(uid, update)| match update {
+            CompUpdateKind::Inserted(packet) => {
+                seen.insert((*uid, mem::discriminant(packet)));
+                true
+            },
+            CompUpdateKind::Modified(packet) => seen.insert((*uid, mem::discriminant(packet))),
+            CompUpdateKind::Removed(_) => true,
+        });
+        updates.reverse();
+        self.comp_updates = updates;
+    }
+
     pub fn with_component<'a, C: Component + Clone + Send + Sync>(
         mut self,
         uids: &ReadStorage<'a, Uid>,
@@ -165,3 +209,47 @@ impl<P: CompPacket> CompSyncPackage<P> {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        comp::{Pos, Vel},
+        msg::EcsCompPacket,
+    };
+    use vek::Vec3;
+
+    #[test]
+    fn coalesce_keeps_latest_modification() {
+        let mut package = CompSyncPackage::<EcsCompPacket>::new();
+        package.comp_modified(Uid(1), Pos(Vec3::zero()));
+        package.comp_modified(Uid(1), Vel(Vec3::zero()));
+        package.comp_modified(Uid(2), Pos(Vec3::zero()));
+        package.comp_modified(Uid(1), Pos(Vec3::one()));
+        package.coalesce();
+
+        assert_eq!(package.comp_updates.len(), 3);
+        assert!(package.comp_updates.iter().any(|(uid, update)| matches!(
+            update,
+            CompUpdateKind::Modified(EcsCompPacket::Pos(Pos(p))) if *uid == 1 && *p == Vec3::one()
+        )));
+        assert!(!package.comp_updates.iter().any(|(uid, update)| matches!(
+            update,
+            CompUpdateKind::Modified(EcsCompPacket::Pos(Pos(p))) if *uid == 1 && *p == Vec3::zero()
+        )));
+    }
+
+    #[test]
+    fn coalesce_keeps_insertions() {
+        let mut package = CompSyncPackage::<EcsCompPacket>::new();
+        package.comp_inserted(Uid(1), Pos(Vec3::zero()));
+        package.comp_modified(Uid(1), Pos(Vec3::one()));
+        package.coalesce();
+
+        assert_eq!(package.comp_updates.len(), 2);
+        assert!(matches!(
+            package.comp_updates[0].1,
+            CompUpdateKind::Inserted(_)
+        ));
+    }
+}
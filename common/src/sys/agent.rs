@@ -1,17 +1,17 @@
 use crate::{
     comp::{
         self,
-        agent::Activity,
+        agent::{Activity, Bark, Routine},
         group,
         group::Invite,
         item::{tool::ToolKind, ItemKind},
         Agent, Alignment, Body, ControlAction, ControlEvent, Controller, Energy, GroupManip,
-        LightEmitter, Loadout, MountState, Ori, PhysicsState, Pos, Scale, Stats, UnresolvedChatMsg,
-        Vel,
+        LightEmitter, Loadout, MountState, Ori, PhysicsState, Player, Pos, Scale, Stats,
+        UnresolvedChatMsg, Vel,
     },
-    event::{EventBus, ServerEvent},
+    event::{Emitter, EventBus, ServerEvent},
     metrics::SysMetrics,
-    path::{Chaser, TraversalConfig},
+    path::{Chaser, SearchBudget, TraversalConfig},
     span,
     state::{DeltaTime, Time, TimeOfDay},
     sync::{Uid, UidAllocator},
@@ -23,10 +23,61 @@ use crate::{
 use rand::{thread_rng, Rng};
 use specs::{
     saveload::{Marker, MarkerAllocator},
-    Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage,
+    Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage,
 };
 use vek::*;
 
+/// Idle agents further than this from their patrol origin walk back
+const MAX_PATROL_DIST: f32 = 64.0;
+
+/// Villagers with a schedule stay this close to where their routine takes them
+const SCHEDULE_PATROL_DIST: f32 = 16.0;
+
+/// Spreads the decisions of agents over several ticks. Every agent thinks once
+/// every `interval` ticks and keeps its previous inputs in between, with agents
+/// taking turns so that the work is spread evenly over the ticks.
+pub struct AgentSchedule {
+    interval: u32,
+    tick: u64,
+}
+
+impl AgentSchedule {
+    pub fn every(interval: u32) -> Self {
+        Self {
+            interval: interval.max(1),
+            tick: 0,
+        }
+    }
+
+    pub fn interval(&self) -> u32 { self.interval }
+
+    fn advance(&mut self) { self.tick = self.tick.wrapping_add(1); }
+
+    fn thinks(&self, entity: Entity) -> bool {
+        (self.tick + entity.id() as u64) % self.interval as u64 == 0
+    }
+}
+
+impl Default for AgentSchedule {
+    fn default() -> Self { Self::every(1) }
+}
+
+/// Say a line for the given situation, unless the agent spoke too recently
+fn bark(
+    agent: &mut Agent,
+    bark: Bark,
+    uid: Uid,
+    time: f64,
+    event_emitter: &mut Emitter<ServerEvent>,
+) {
+    if agent.try_bark(time) {
+        event_emitter.emit(ServerEvent::Chat(UnresolvedChatMsg::npc(
+            uid,
+            bark.i18n_key().to_string(),
+        )));
+    }
+}
+
 /// This system will allow NPCs to modify their controller
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -37,6 +88,8 @@ impl<'a> System<'a> for Sys {
             Read<'a, Time>,
             Read<'a, DeltaTime>,
             Read<'a, group::GroupManager>,
+            Write<'a, AgentSchedule>,
+            Write<'a, SearchBudget>,
         ),
         ReadExpect<'a, SysMetrics>,
         Write<'a, EventBus<ServerEvent>>,
@@ -60,13 +113,14 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Invite>,
         Read<'a, TimeOfDay>,
         ReadStorage<'a, LightEmitter>,
+        ReadStorage<'a, Player>,
     );
 
     #[allow(clippy::or_fun_call)] // TODO: Pending review in #587
     fn run(
         &mut self,
         (
-            (uid_allocator, time, dt, group_manager),
+            (uid_allocator, time, dt, group_manager, mut schedule, mut path_budget),
             sys_metrics,
             event_bus,
             entities,
@@ -89,10 +143,16 @@ impl<'a> System<'a> for Sys {
             invites,
             time_of_day,
             light_emitter,
+            players,
         ): Self::SystemData,
     ) {
         let start_time = std::time::Instant::now();
         span!(_guard, "run", "agent::Sys::run");
+        schedule.advance();
+        // All agents share one pathfinding budget per tick
+        path_budget.refill();
+        // Time that passes between two decisions of the same agent
+        let think_dt = dt.0 * schedule.interval() as f32;
         for (
             entity,
             energy,
@@ -149,8 +209,37 @@ impl<'a> System<'a> for Sys {
                 continue;
             }
 
+            // Keep the inputs of the last decision until it's this agent's turn
+            if !schedule.thinks(entity) {
+                continue;
+            }
+
             controller.reset();
             let mut event_emitter = event_bus.emitter();
+
+            // Villagers go wherever their routine takes them at this time of day
+            let routine = match agent.schedule {
+                Some(schedule) if !matches!(alignment, Some(Alignment::Owned(_))) => {
+                    let routine = schedule.routine(time_of_day.0);
+                    agent.patrol_origin = Some(schedule.place(routine));
+                    Some(routine)
+                },
+                _ => None,
+            };
+            let patrol_dist = if routine.is_some() {
+                SCHEDULE_PATROL_DIST
+            } else {
+                MAX_PATROL_DIST
+            };
+
+            // Pets follow their owner around instead of staying at home
+            let home_dist_sqrd = agent
+                .patrol_origin
+                .filter(|_| !matches!(alignment, Some(Alignment::Owned(_))))
+                .map(|patrol_origin| pos.0.xy().distance_squared(patrol_origin.xy()));
+            let asleep = routine == Some(Routine::Sleep)
+                && home_dist_sqrd.map_or(false, |d| d < patrol_dist.powi(2));
+
             // Light lanterns at night
             // TODO Add a method to turn on NPC lanterns underground
             let lantern_equipped = loadout.lantern.as_ref().map_or(false, |item| {
@@ -161,7 +250,17 @@ impl<'a> System<'a> for Sys {
             // Only emit event for agents that have a lantern equipped
             if lantern_equipped {
                 let mut rng = thread_rng();
-                if day_period.is_dark() && !lantern_turned_on {
+                if let Some(routine) = routine {
+                    // Villagers light their lantern for the evening and the way home, and put
+                    // it out once they're in bed
+                    let wants_light =
+                        routine == Routine::Rest || (routine == Routine::Sleep && !asleep);
+                    if wants_light && !lantern_turned_on {
+                        controller.events.push(ControlEvent::EnableLantern)
+                    } else if !wants_light && lantern_turned_on {
+                        controller.events.push(ControlEvent::DisableLantern)
+                    }
+                } else if day_period.is_dark() && !lantern_turned_on {
                     // Agents with turned off lanterns turn them on randomly once it's nighttime and
                     // keep them on
                     // Only emit event for agents that sill need to
@@ -191,6 +290,10 @@ impl<'a> System<'a> for Sys {
             const SIGHT_DIST: f32 = 80.0;
             const MIN_ATTACK_DIST: f32 = 2.0;
             const MAX_FLEE_DIST: f32 = 20.0;
+            // Agents give up chasing a target this far from their patrol origin
+            const MAX_LEASH_DIST: f32 = 160.0;
+            // Idle villagers greet players within this distance
+            const GREET_DIST: f32 = 8.0;
 
             let scale = scales.get(entity).map(|s| s.0).unwrap_or(1.0);
 
@@ -203,10 +306,19 @@ impl<'a> System<'a> for Sys {
 
             let mut do_idle = false;
             let mut choose_target = false;
+            let mut go_home = false;
+            let mut greet = false;
 
             'activity: {
                 match &mut agent.activity {
                     Activity::Idle(bearing) => {
+                        // Sleep sitting down, only waking up to defend against enemies
+                        if asleep {
+                            controller.actions.push(ControlAction::Sit);
+                            choose_target = thread_rng().gen::<f32>() < 0.1;
+                            break 'activity;
+                        }
+
                         *bearing += Vec2::new(
                             thread_rng().gen::<f32>() - 0.5,
                             thread_rng().gen::<f32>() - 0.5,
@@ -256,6 +368,46 @@ impl<'a> System<'a> for Sys {
                         if thread_rng().gen::<f32>() < 0.1 {
                             choose_target = true;
                         }
+
+                        // Occasionally look around for someone to greet
+                        if agent.can_speak && thread_rng().gen::<f32>() < 0.02 {
+                            greet = true;
+                        }
+
+                        if home_dist_sqrd.map_or(false, |d| d > patrol_dist.powi(2)) {
+                            go_home = true;
+                        }
+                    },
+                    Activity::ReturnHome { chaser } => {
+                        if let Some(patrol_origin) = agent.patrol_origin {
+                            if home_dist_sqrd.map_or(false, |d| d > (patrol_dist * 0.5).powi(2)) {
+                                if let Some((bearing, speed)) = chaser.chase(
+                                    &*terrain,
+                                    pos.0,
+                                    vel.0,
+                                    patrol_origin,
+                                    TraversalConfig {
+                                        node_tolerance,
+                                        slow_factor,
+                                        on_ground: physics_state.on_ground,
+                                        min_tgt_dist: 1.25,
+                                    },
+                                    &mut path_budget,
+                                ) {
+                                    inputs.move_dir =
+                                        bearing.xy().try_normalized().unwrap_or(Vec2::zero())
+                                            * speed
+                                            * 0.65;
+                                    inputs.jump.set_state(bearing.z > 1.5);
+                                    inputs.swimup.set_state(bearing.z > 0.5);
+                                    inputs.swimdown.set_state(bearing.z < 0.5);
+                                }
+                            } else {
+                                do_idle = true;
+                            }
+                        } else {
+                            do_idle = true;
+                        }
                     },
                     Activity::Follow { target, chaser } => {
                         if let (Some(tgt_pos), _tgt_stats) =
@@ -275,6 +427,7 @@ impl<'a> System<'a> for Sys {
                                         on_ground: physics_state.on_ground,
                                         min_tgt_dist: AVG_FOLLOW_DIST,
                                     },
+                                    &mut path_budget,
                                 ) {
                                     inputs.move_dir =
                                         bearing.xy().try_normalized().unwrap_or(Vec2::zero())
@@ -350,6 +503,12 @@ impl<'a> System<'a> for Sys {
                                 }
                             }
 
+                            // Don't get lured too far away from home
+                            if home_dist_sqrd.map_or(false, |d| d > MAX_LEASH_DIST.powi(2)) {
+                                go_home = true;
+                                break 'activity;
+                            }
+
                             let dist_sqrd = pos.0.distance_squared(tgt_pos.0);
 
                             let damage = stats
@@ -379,6 +538,7 @@ impl<'a> System<'a> for Sys {
                                             on_ground: physics_state.on_ground,
                                             min_tgt_dist: 1.25,
                                         },
+                                        &mut path_budget,
                                     ) {
                                         inputs.move_dir =
                                             bearing.xy().try_normalized().unwrap_or(Vec2::zero())
@@ -424,10 +584,10 @@ impl<'a> System<'a> for Sys {
                                             *powerup = 0.0;
                                         } else if *powerup > 4.0 && energy.current() > 10 {
                                             inputs.secondary.set_state(true);
-                                            *powerup += dt.0;
+                                            *powerup += think_dt;
                                         } else {
                                             inputs.primary.set_state(true);
-                                            *powerup += dt.0;
+                                            *powerup += think_dt;
                                         }
                                     },
                                     Tactic::RangedPowerup => inputs.roll.set_state(true),
@@ -451,14 +611,14 @@ impl<'a> System<'a> for Sys {
                                             *powerup = 0.0;
                                         } else {
                                             inputs.primary.set_state(true);
-                                            *powerup += dt.0;
+                                            *powerup += think_dt;
                                         }
                                     } else if let Tactic::Sword = tactic {
                                         if *powerup > 4.0 {
                                             inputs.secondary.set_state(true);
                                             *powerup = 0.0;
                                         } else {
-                                            *powerup += dt.0;
+                                            *powerup += think_dt;
                                         }
                                     } else if let Tactic::Staff = tactic {
                                         inputs.primary.set_state(true);
@@ -467,14 +627,14 @@ impl<'a> System<'a> for Sys {
                                             inputs.ability3.set_state(true);
                                             *powerup = 0.0;
                                         } else {
-                                            *powerup += dt.0;
+                                            *powerup += think_dt;
                                         }
                                     } else if let Tactic::StoneGolemBoss = tactic {
                                         if *powerup > 5.0 {
                                             inputs.secondary.set_state(true);
                                             *powerup = 0.0;
                                         } else {
-                                            *powerup += dt.0;
+                                            *powerup += think_dt;
                                         }
                                     }
                                 }
@@ -495,6 +655,7 @@ impl<'a> System<'a> for Sys {
                                         on_ground: physics_state.on_ground,
                                         min_tgt_dist: 1.25,
                                     },
+                                    &mut path_budget,
                                 ) {
                                     if can_see_tgt {
                                         match tactic {
@@ -552,9 +713,24 @@ impl<'a> System<'a> for Sys {
                 agent.activity = Activity::Idle(Vec2::zero());
             }
 
+            if go_home {
+                agent.activity = Activity::ReturnHome {
+                    chaser: Chaser::default(),
+                };
+            }
+
+            if greet
+                && alignment == Some(Alignment::Npc)
+                && (&positions, &players)
+                    .join()
+                    .any(|(p_pos, _)| p_pos.0.distance_squared(pos.0) < GREET_DIST.powi(2))
+            {
+                bark(agent, Bark::Greeting, *uid, time.0, &mut event_emitter);
+            }
+
             // Choose a new target to attack: only go out of our way to attack targets we
             // are hostile toward!
-            if choose_target {
+            if choose_target && !go_home {
                 // Search for new targets (this looks expensive, but it's only run occasionally)
                 // TODO: Replace this with a better system that doesn't consider *all* entities
                 let closest_entity = (&entities, &positions, &stats, alignments.maybe())
@@ -581,6 +757,9 @@ impl<'a> System<'a> for Sys {
                     .map(|(e, _, _, _)| e);
 
                 if let Some(target) = closest_entity {
+                    if alignment == Some(Alignment::Enemy) {
+                        bark(agent, Bark::CombatTaunt, *uid, time.0, &mut event_emitter);
+                    }
                     agent.activity = Activity::Attack {
                         target,
                         chaser: Chaser::default(),
@@ -598,26 +777,21 @@ impl<'a> System<'a> for Sys {
             if let Some(my_stats) = stats.get(entity) {
                 // Only if the attack was recent
                 if my_stats.health.last_change.0 < 3.0 {
-                    if let comp::HealthSource::Attack { by }
-                    | comp::HealthSource::Projectile { owner: Some(by) }
-                    | comp::HealthSource::Energy { owner: Some(by) }
-                    | comp::HealthSource::Explosion { owner: Some(by) } =
-                        my_stats.health.last_change.1.cause
-                    {
-                        if !agent.activity.is_attack() {
+                    if let Some(by) = my_stats.health.last_change.1.cause.attacker() {
+                        // Agents that were lured away too far don't turn around again
+                        if !agent.activity.is_attack() && !agent.activity.is_return_home() {
                             if let Some(attacker) = uid_allocator.retrieve_entity_internal(by.id())
                             {
                                 if stats.get(attacker).map_or(false, |a| !a.is_dead) {
                                     match agent.activity {
                                         Activity::Attack { target, .. } if target == attacker => {},
                                         _ => {
-                                            if agent.can_speak {
-                                                let msg =
-                                                    "npc.speech.villager_under_attack".to_string();
-                                                event_emitter.emit(ServerEvent::Chat(
-                                                    UnresolvedChatMsg::npc(*uid, msg),
-                                                ));
-                                            }
+                                            let line = if alignment == Some(Alignment::Enemy) {
+                                                Bark::CombatTaunt
+                                            } else {
+                                                Bark::UnderAttack
+                                            };
+                                            bark(agent, line, *uid, time.0, &mut event_emitter);
 
                                             agent.activity = Activity::Attack {
                                                 target: attacker,
@@ -654,9 +828,7 @@ impl<'a> System<'a> for Sys {
                     if owner_stats.health.last_change.0 < 5.0
                         && owner_stats.health.last_change.1.amount < 0
                     {
-                        if let comp::HealthSource::Attack { by } =
-                            owner_stats.health.last_change.1.cause
-                        {
+                        if let Some(by) = owner_stats.health.last_change.1.cause.attacker() {
                             if !agent.activity.is_attack() {
                                 let attacker = uid_allocator.retrieve_entity_internal(by.id())?;
 
@@ -1,7 +1,7 @@
 use crate::{
     comp::{
         group, Beam, BeamSegment, Body, CharacterState, Damage, DamageSource, Energy, EnergySource,
-        HealthChange, HealthSource, Last, Loadout, Ori, Pos, Scale, Stats,
+        HealthChange, HealthSource, HitModifiers, Last, Loadout, Ori, Poise, Pos, Scale, Stats,
     },
     event::{EventBus, ServerEvent},
     state::{DeltaTime, Time},
@@ -11,8 +11,6 @@ use specs::{saveload::MarkerAllocator, Entities, Join, Read, ReadStorage, System
 use std::time::Duration;
 use vek::*;
 
-pub const BLOCK_ANGLE: f32 = 180.0;
-
 /// This system is responsible for handling beams that heal or do damage
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -191,13 +189,15 @@ impl<'a> System<'a> for Sys {
                         source,
                     };
 
-                    let block = character_b.map(|c_b| c_b.is_block()).unwrap_or(false)
+                    let block = character_b
+                        .and_then(|c_b| c_b.block())
                         // TODO: investigate whether this calculation is proper for beams
-                        && ori_b.0.angle_between(pos.0 - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0;
+                        .filter(|block| block.covers(pos_b.0, ori_b, pos.0));
 
-                    if let Some(loadout) = loadouts.get(b) {
-                        damage.modify_damage(block, loadout);
-                    }
+                    let hit = loadouts
+                        .get(b)
+                        .map(|loadout| damage.modify_damage(block, loadout))
+                        .unwrap_or_default();
 
                     if is_damage {
                         server_emitter.emit(ServerEvent::Damage {
@@ -208,6 +208,15 @@ impl<'a> System<'a> for Sys {
                                     owner: beam_segment.owner,
                                 },
                             },
+                            source: damage.source,
+                            hit,
+                        });
+                        server_emitter.emit(ServerEvent::PoiseDamage {
+                            entity: b,
+                            amount: Poise::damage_from_hit(
+                                damage.healthchange,
+                                beam_owner.and_then(|o| loadouts.get(o)),
+                            ),
                         });
                         if beam_segment.lifesteal_eff > 0.0 {
                             server_emitter.emit(ServerEvent::Damage {
@@ -219,6 +228,8 @@ impl<'a> System<'a> for Sys {
                                         by: beam_segment.owner,
                                     },
                                 },
+                                source: DamageSource::Healing,
+                                hit: HitModifiers::default(),
                             });
                         }
                         if let Some(energy_mut) = beam_owner.and_then(|o| energies.get_mut(o)) {
@@ -245,6 +256,8 @@ impl<'a> System<'a> for Sys {
                                             by: beam_segment.owner,
                                         },
                                     },
+                                    source: damage.source,
+                                    hit,
                                 });
                             }
                         }
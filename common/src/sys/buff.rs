@@ -0,0 +1,109 @@
+use crate::{
+    comp::{
+        BuffKind, Buffs, DamageSource, Energy, EnergySource, HealthChange, HealthSource,
+        HitModifiers, PhysicsState, Stats,
+    },
+    event::{EventBus, ServerEvent},
+    span,
+    state::DeltaTime,
+    sync::Uid,
+};
+use specs::{Entities, Join, Read, ReadStorage, System, WriteStorage};
+
+/// Seconds between two applications of the periodic effects of a buff
+const BUFF_TICK_INTERVAL: f32 = 1.0;
+
+/// This system ticks down buffs, applies their periodic health and energy
+/// changes and removes them once they run out
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, DeltaTime>,
+        Read<'a, EventBus<ServerEvent>>,
+        ReadStorage<'a, Uid>,
+        ReadStorage<'a, Stats>,
+        ReadStorage<'a, PhysicsState>,
+        WriteStorage<'a, Energy>,
+        WriteStorage<'a, Buffs>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, dt, server_bus, uids, stats, physics_states, mut energies, mut buffs): Self::SystemData,
+    ) {
+        span!(_guard, "run", "buff::Sys::run");
+        let mut server_emitter = server_bus.emitter();
+        let mut changed = Vec::new();
+
+        // Clients tick the durations themselves, so only sync buffs when they are
+        // added or removed
+        buffs.set_event_emission(false);
+        for (entity, uid, buffs, stats, physics) in
+            (&entities, &uids, &mut buffs, &stats, physics_states.maybe()).join()
+        {
+            if stats.is_dead {
+                if !buffs.is_empty() {
+                    changed.push(entity);
+                }
+                continue;
+            }
+
+            let in_fluid = physics.map_or(false, |p| p.in_fluid.is_some());
+            for buff in buffs.iter_mut() {
+                // Water puts out fires
+                if buff.data.kind == BuffKind::Burning && in_fluid {
+                    buff.time_left = 0.0;
+                    continue;
+                }
+                buff.time_left -= dt.0;
+                buff.tick_timer += dt.0;
+                if buff.tick_timer < BUFF_TICK_INTERVAL {
+                    continue;
+                }
+                buff.tick_timer -= BUFF_TICK_INTERVAL;
+
+                let health_change = (buff.health_rate() * BUFF_TICK_INTERVAL) as i32;
+                if health_change != 0 {
+                    let cause = if health_change < 0 {
+                        HealthSource::Buff { owner: buff.source }
+                    } else {
+                        HealthSource::Healing { by: buff.source }
+                    };
+                    server_emitter.emit(ServerEvent::Damage {
+                        uid: *uid,
+                        change: HealthChange {
+                            amount: health_change,
+                            cause,
+                        },
+                        source: DamageSource::Buff,
+                        hit: HitModifiers::default(),
+                    });
+                }
+                let energy_change = (buff.energy_rate() * BUFF_TICK_INTERVAL) as i32;
+                if energy_change != 0 {
+                    if let Some(energy) = energies.get_mut(entity) {
+                        energy.change_by(energy_change, EnergySource::Buff);
+                    }
+                }
+            }
+
+            if buffs.iter().any(|b| b.time_left <= 0.0) {
+                changed.push(entity);
+            }
+        }
+        buffs.set_event_emission(true);
+
+        for entity in changed {
+            let is_dead = stats.get(entity).map_or(false, |s| s.is_dead);
+            if let Some(buffs) = buffs.get_mut(entity) {
+                if is_dead {
+                    buffs.clear();
+                } else {
+                    buffs.remove_expired();
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
     comp::{
-        Attacking, Beam, Body, CharacterState, ControlAction, Controller, ControllerInputs, Energy,
-        Loadout, Mounting, Ori, PhysicsState, Pos, StateUpdate, Stats, Vel,
+        AbilityHotbar, Attacking, Beam, Body, Buffs, CharacterState, ControlAction, Controller,
+        ControllerInputs, Energy, Loadout, Mounting, Ori, PhysicsState, Pos, Sound, StateUpdate,
+        Stats, Vel,
     },
     event::{EventBus, LocalEvent, ServerEvent},
     metrics::SysMetrics,
@@ -32,6 +33,9 @@ pub trait CharacterBehavior {
     fn dance(&self, data: &JoinData) -> StateUpdate { StateUpdate::from(data) }
     fn sneak(&self, data: &JoinData) -> StateUpdate { StateUpdate::from(data) }
     fn stand(&self, data: &JoinData) -> StateUpdate { StateUpdate::from(data) }
+    fn use_ability_slot(&self, data: &JoinData, _slot: usize) -> StateUpdate {
+        StateUpdate::from(data)
+    }
     fn handle_event(&self, data: &JoinData, event: ControlAction) -> StateUpdate {
         match event {
             ControlAction::SwapLoadout => self.swap_loadout(data),
@@ -42,6 +46,7 @@ pub trait CharacterBehavior {
             ControlAction::Dance => self.dance(data),
             ControlAction::Sneak => self.sneak(data),
             ControlAction::Stand => self.stand(data),
+            ControlAction::UseAbilitySlot(slot) => self.use_ability_slot(data, slot),
         }
     }
     // fn init(data: &JoinData) -> CharacterState;
@@ -64,6 +69,8 @@ pub struct JoinData<'a> {
     pub body: &'a Body,
     pub physics: &'a PhysicsState,
     pub attacking: Option<&'a Attacking>,
+    pub buffs: Option<&'a Buffs>,
+    pub hotbar: Option<&'a AbilityHotbar>,
     pub updater: &'a LazyUpdate,
 }
 
@@ -90,6 +97,8 @@ pub type JoinTuple<'a> = (
     &'a PhysicsState,
     Option<&'a Attacking>,
     Option<&'a Beam>,
+    Option<&'a Buffs>,
+    Option<&'a AbilityHotbar>,
 );
 
 fn incorporate_update(tuple: &mut JoinTuple, state_update: StateUpdate) {
@@ -127,6 +136,8 @@ impl<'a> JoinData<'a> {
             body: j.10,
             physics: j.11,
             attacking: j.12,
+            buffs: j.14,
+            hotbar: j.15,
             updater,
             dt,
         }
@@ -145,6 +156,7 @@ impl<'a> System<'a> for Sys {
         Read<'a, UidAllocator>,
         Read<'a, EventBus<ServerEvent>>,
         Read<'a, EventBus<LocalEvent>>,
+        Read<'a, EventBus<Sound>>,
         Read<'a, DeltaTime>,
         Read<'a, LazyUpdate>,
         ReadExpect<'a, SysMetrics>,
@@ -160,6 +172,8 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, PhysicsState>,
         ReadStorage<'a, Attacking>,
         ReadStorage<'a, Beam>,
+        ReadStorage<'a, Buffs>,
+        ReadStorage<'a, AbilityHotbar>,
         ReadStorage<'a, Uid>,
         ReadStorage<'a, Mounting>,
     );
@@ -172,6 +186,7 @@ impl<'a> System<'a> for Sys {
             _uid_allocator,
             server_bus,
             local_bus,
+            sound_bus,
             dt,
             updater,
             sys_metrics,
@@ -187,6 +202,8 @@ impl<'a> System<'a> for Sys {
             physics_states,
             attacking_storage,
             beam_storage,
+            buff_storage,
+            hotbar_storage,
             uids,
             mountings,
         ): Self::SystemData,
@@ -195,6 +212,7 @@ impl<'a> System<'a> for Sys {
         span!(_guard, "run", "character_behavior::Sys::run");
         let mut server_emitter = server_bus.emitter();
         let mut local_emitter = local_bus.emitter();
+        let mut sound_emitter = sound_bus.emitter();
 
         for mut tuple in (
             &entities,
@@ -211,6 +229,8 @@ impl<'a> System<'a> for Sys {
             &physics_states,
             attacking_storage.maybe(),
             beam_storage.maybe(),
+            buff_storage.maybe(),
+            hotbar_storage.maybe(),
         )
             .join()
         {
@@ -248,9 +268,7 @@ impl<'a> System<'a> for Sys {
                     CharacterState::Sneak => {
                         states::sneak::Data::handle_event(&states::sneak::Data, &j, action)
                     },
-                    CharacterState::BasicBlock => {
-                        states::basic_block::Data.handle_event(&j, action)
-                    },
+                    CharacterState::BasicBlock(data) => data.handle_event(&j, action),
                     CharacterState::Roll(data) => data.handle_event(&j, action),
                     CharacterState::Wielding => states::wielding::Data.handle_event(&j, action),
                     CharacterState::Equipping(data) => data.handle_event(&j, action),
@@ -266,9 +284,13 @@ impl<'a> System<'a> for Sys {
                     CharacterState::RepeaterRanged(data) => data.handle_event(&j, action),
                     CharacterState::Shockwave(data) => data.handle_event(&j, action),
                     CharacterState::BasicBeam(data) => data.handle_event(&j, action),
+                    CharacterState::Flinch(data) => data.handle_event(&j, action),
+                    CharacterState::Stagger(data) => data.handle_event(&j, action),
+                    CharacterState::Fishing(data) => data.handle_event(&j, action),
                 };
                 local_emitter.append(&mut state_update.local_events);
                 server_emitter.append(&mut state_update.server_events);
+                sound_emitter.append(&mut state_update.sounds);
                 incorporate_update(&mut tuple, state_update);
             }
 
@@ -282,7 +304,7 @@ impl<'a> System<'a> for Sys {
                 CharacterState::Sit => states::sit::Data::behavior(&states::sit::Data, &j),
                 CharacterState::Dance => states::dance::Data::behavior(&states::dance::Data, &j),
                 CharacterState::Sneak => states::sneak::Data::behavior(&states::sneak::Data, &j),
-                CharacterState::BasicBlock => states::basic_block::Data.behavior(&j),
+                CharacterState::BasicBlock(data) => data.behavior(&j),
                 CharacterState::Roll(data) => data.behavior(&j),
                 CharacterState::Wielding => states::wielding::Data.behavior(&j),
                 CharacterState::Equipping(data) => data.behavior(&j),
@@ -298,10 +320,14 @@ impl<'a> System<'a> for Sys {
                 CharacterState::RepeaterRanged(data) => data.behavior(&j),
                 CharacterState::Shockwave(data) => data.behavior(&j),
                 CharacterState::BasicBeam(data) => data.behavior(&j),
+                CharacterState::Flinch(data) => data.behavior(&j),
+                CharacterState::Stagger(data) => data.behavior(&j),
+                CharacterState::Fishing(data) => data.behavior(&j),
             };
 
             local_emitter.append(&mut state_update.local_events);
             server_emitter.append(&mut state_update.server_events);
+            sound_emitter.append(&mut state_update.sounds);
             incorporate_update(&mut tuple, state_update);
         }
         sys_metrics.character_behavior_ns.store(
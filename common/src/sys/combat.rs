@@ -1,20 +1,18 @@
 use crate::{
     comp::{
-        group, Attacking, Body, CharacterState, Damage, DamageSource, HealthChange, HealthSource,
-        Loadout, Ori, Pos, Scale, Stats,
+        group, Attacking, Body, Buffs, CharacterAbilityType, CharacterState, Damage, DamageSource,
+        HealthChange, HealthSource, Loadout, Ori, Pos, Scale, Stats,
     },
     event::{EventBus, LocalEvent, ServerEvent},
     metrics::SysMetrics,
     span,
     sync::Uid,
+    terrain::{self, TerrainGrid},
     util::Dir,
 };
 use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
 use vek::*;
 
-pub const BLOCK_EFFICIENCY: f32 = 0.9;
-pub const BLOCK_ANGLE: f32 = 180.0;
-
 /// This system is responsible for handling accepted inputs like moving or
 /// attacking
 pub struct Sys;
@@ -25,6 +23,7 @@ impl<'a> System<'a> for Sys {
         Read<'a, EventBus<ServerEvent>>,
         Read<'a, EventBus<LocalEvent>>,
         ReadExpect<'a, SysMetrics>,
+        ReadExpect<'a, TerrainGrid>,
         ReadStorage<'a, Uid>,
         ReadStorage<'a, Pos>,
         ReadStorage<'a, Ori>,
@@ -34,6 +33,7 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Loadout>,
         ReadStorage<'a, group::Group>,
         ReadStorage<'a, CharacterState>,
+        ReadStorage<'a, Buffs>,
         WriteStorage<'a, Attacking>,
     );
 
@@ -44,6 +44,7 @@ impl<'a> System<'a> for Sys {
             server_bus,
             local_bus,
             sys_metrics,
+            terrain,
             uids,
             positions,
             orientations,
@@ -53,6 +54,7 @@ impl<'a> System<'a> for Sys {
             loadouts,
             groups,
             character_states,
+            buffs,
             mut attacking_storage,
         ): Self::SystemData,
     ) {
@@ -61,12 +63,13 @@ impl<'a> System<'a> for Sys {
         let mut server_emitter = server_bus.emitter();
         let mut _local_emitter = local_bus.emitter();
         // Attacks
-        for (entity, uid, pos, ori, scale_maybe, attack) in (
+        for (entity, uid, pos, ori, scale_maybe, body, attack) in (
             &entities,
             &uids,
             &positions,
             &orientations,
             scales.maybe(),
+            bodies.maybe(),
             &mut attacking_storage,
         )
             .join()
@@ -105,6 +108,12 @@ impl<'a> System<'a> for Sys {
                     // Spherical wedge shaped attack field
                     && pos.0.distance_squared(pos_b.0) < (rad_b + scale * attack.range).powi(2)
                     && ori2.angle_between(pos_b2 - pos2) < attack.max_angle + (rad_b / pos2.distance(pos_b2)).atan()
+                    // Can't hit through walls
+                    && terrain::line_of_sight(
+                        &*terrain,
+                        pos.0 + Vec3::unit_z() * body.map_or(1.0, |b| b.height() * 0.5) * scale,
+                        pos_b.0 + Vec3::unit_z() * body_b.height() * 0.5 * scale_b,
+                    )
                 {
                     // See if entities are in the same group
                     let same_group = groups
@@ -123,25 +132,36 @@ impl<'a> System<'a> for Sys {
                     let (source, healthchange) = if is_heal {
                         (DamageSource::Healing, attack.base_heal as f32)
                     } else {
-                        (DamageSource::Melee, -(attack.base_damage as f32))
+                        let strength = buffs.get(entity).map_or(1.0, |b| b.damage_modifier());
+                        (DamageSource::Melee, -(attack.base_damage as f32) * strength)
                     };
                     let mut damage = Damage {
                         healthchange,
                         source,
                     };
 
-                    let block = character_b.map(|c_b| c_b.is_block()).unwrap_or(false)
-                        && ori_b.0.angle_between(pos.0 - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0;
-
-                    if let Some(loadout) = loadouts.get(b) {
-                        damage.modify_damage(block, loadout);
+                    let block = character_b
+                        .and_then(|c_b| c_b.block())
+                        .filter(|block| block.covers(pos_b.0, ori_b, pos.0));
+                    if block.map_or(false, |block| block.breaks_guard(-damage.healthchange)) {
+                        server_emitter.emit(ServerEvent::GuardBreak { entity: b });
                     }
 
+                    let hit = loadouts
+                        .get(b)
+                        .map(|loadout| damage.modify_damage(block, loadout))
+                        .unwrap_or_default();
+
                     if damage.healthchange != 0.0 {
                         let cause = if is_heal {
                             HealthSource::Healing { by: Some(*uid) }
                         } else {
-                            HealthSource::Attack { by: *uid }
+                            HealthSource::Attack {
+                                by: *uid,
+                                ability: character_states
+                                    .get(entity)
+                                    .map(CharacterAbilityType::from),
+                            }
                         };
                         server_emitter.emit(ServerEvent::Damage {
                             uid: *uid_b,
@@ -149,9 +169,19 @@ impl<'a> System<'a> for Sys {
                                 amount: damage.healthchange as i32,
                                 cause,
                             },
+                            source: damage.source,
+                            hit,
                         });
                         attack.hit_count += 1;
                     }
+                    if is_damage && attack.poise_damage > 0 && damage.healthchange != 0.0 {
+                        // Blocks and armor absorb poise damage like they absorb damage
+                        let absorbed = damage.healthchange / healthchange;
+                        server_emitter.emit(ServerEvent::PoiseDamage {
+                            entity: b,
+                            amount: (attack.poise_damage as f32 * absorbed).round() as u32,
+                        });
+                    }
                     if attack.knockback != 0.0 && damage.healthchange != 0.0 {
                         let kb_dir = Dir::new((pos_b.0 - pos.0).try_normalized().unwrap_or(*ori.0));
                         server_emitter.emit(ServerEvent::Knockback {
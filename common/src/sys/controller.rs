@@ -109,6 +109,9 @@ impl<'a> System<'a> for Sys {
                         server_emitter.emit(ServerEvent::GroupManip(entity, manip))
                     },
                     ControlEvent::Respawn => server_emitter.emit(ServerEvent::Respawn(entity)),
+                    ControlEvent::SetWaypoint => {
+                        server_emitter.emit(ServerEvent::SetWaypoint(entity))
+                    },
                 }
             }
         }
@@ -1,11 +1,13 @@
 pub mod agent;
 mod beam;
+mod buff;
 pub mod character_behavior;
 pub mod combat;
 pub mod controller;
 mod mount;
 pub mod phys;
 mod projectile;
+mod projectile_phys;
 mod shockwave;
 mod stats;
 
@@ -17,10 +19,12 @@ pub const CHARACTER_BEHAVIOR_SYS: &str = "character_behavior_sys";
 pub const COMBAT_SYS: &str = "combat_sys";
 pub const AGENT_SYS: &str = "agent_sys";
 pub const BEAM_SYS: &str = "beam_sys";
+pub const BUFF_SYS: &str = "buff_sys";
 pub const CONTROLLER_SYS: &str = "controller_sys";
 pub const MOUNT_SYS: &str = "mount_sys";
 pub const PHYS_SYS: &str = "phys_sys";
 pub const PROJECTILE_SYS: &str = "projectile_sys";
+pub const PROJECTILE_PHYS_SYS: &str = "projectile_phys_sys";
 pub const SHOCKWAVE_SYS: &str = "shockwave_sys";
 pub const STATS_SYS: &str = "stats_sys";
 
@@ -32,8 +36,10 @@ pub fn add_local_systems(dispatch_builder: &mut DispatcherBuilder) {
         CONTROLLER_SYS,
     ]);
     dispatch_builder.add(stats::Sys, STATS_SYS, &[]);
+    dispatch_builder.add(buff::Sys, BUFF_SYS, &[STATS_SYS]);
     dispatch_builder.add(phys::Sys, PHYS_SYS, &[CONTROLLER_SYS, MOUNT_SYS, STATS_SYS]);
-    dispatch_builder.add(projectile::Sys, PROJECTILE_SYS, &[PHYS_SYS]);
+    dispatch_builder.add(projectile_phys::Sys, PROJECTILE_PHYS_SYS, &[PHYS_SYS]);
+    dispatch_builder.add(projectile::Sys, PROJECTILE_SYS, &[PROJECTILE_PHYS_SYS]);
     dispatch_builder.add(shockwave::Sys, SHOCKWAVE_SYS, &[PHYS_SYS]);
     dispatch_builder.add(beam::Sys, BEAM_SYS, &[PHYS_SYS]);
     dispatch_builder.add(combat::Sys, COMBAT_SYS, &[PROJECTILE_SYS]);
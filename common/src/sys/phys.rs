@@ -1,14 +1,14 @@
 use crate::{
     comp::{
-        BeamSegment, Collider, Gravity, Mass, Mounting, Ori, PhysicsState, Pos, Projectile, Scale,
-        Shockwave, Sticky, Vel,
+        BeamSegment, BlockHit, Collider, Gravity, Mass, Mounting, Ori, PhysicsState, Pos,
+        Projectile, Scale, Shockwave, Sticky, Vel,
     },
     event::{EventBus, ServerEvent},
     metrics::SysMetrics,
     span,
     state::DeltaTime,
     sync::Uid,
-    terrain::{Block, TerrainGrid},
+    terrain::{Block, CollisionMap, TerrainGrid},
     vol::ReadVol,
 };
 use rayon::iter::ParallelIterator;
@@ -17,23 +17,23 @@ use std::ops::Range;
 use vek::*;
 
 pub const GRAVITY: f32 = 9.81 * 5.0;
-const BOUYANCY: f32 = 1.0;
+pub(super) const BOUYANCY: f32 = 1.0;
 // Friction values used for linear damping. They are unitless quantities. The
 // value of these quantities must be between zero and one. They represent the
 // amount an object will slow down within 1/60th of a second. Eg. if the
 // friction is 0.01, and the speed is 1.0, then after 1/60th of a second the
 // speed will be 0.99. after 1 second the speed will be 0.54, which is 0.99 ^
 // 60.
-const FRIC_GROUND: f32 = 0.15;
+pub(super) const FRIC_GROUND: f32 = 0.15;
 const FRIC_AIR: f32 = 0.0125;
-const FRIC_FLUID: f32 = 0.2;
+pub(super) const FRIC_FLUID: f32 = 0.2;
 
 // Integrates forces, calculates the new velocity based off of the old velocity
 // dt = delta time
 // lv = linear velocity
 // damp = linear damping
 // Friction is a type of damping.
-fn integrate_forces(dt: f32, mut lv: Vec3<f32>, grav: f32, damp: f32) -> Vec3<f32> {
+pub(super) fn integrate_forces(dt: f32, mut lv: Vec3<f32>, grav: f32, damp: f32) -> Vec3<f32> {
     // this is not linear damping, because it is proportional to the original
     // velocity this "linear" damping in in fact, quite exponential. and thus
     // must be interpolated accordingly
@@ -43,6 +43,104 @@ fn integrate_forces(dt: f32, mut lv: Vec3<f32>, grav: f32, damp: f32) -> Vec3<f3
     lv * linear_damp
 }
 
+/// Moves a character steering in `move_dir` for one update, in a simplified
+/// way: steering, gravity and friction are applied like the character and
+/// physics systems do, but the character collides with terrain as a point.
+/// Used by clients to replay their inputs after the server corrected them.
+pub fn replay_movement<V: ReadVol<Vox = Block>>(
+    terrain: &V,
+    pos: &mut Pos,
+    vel: &mut Vel,
+    physics_state: &mut PhysicsState,
+    move_dir: Vec2<f32>,
+    accel: f32,
+    dt: f32,
+) {
+    vel.0 += Vec3::from(move_dir * accel * dt);
+    let friction = if physics_state.on_ground {
+        FRIC_GROUND
+    } else {
+        FRIC_AIR
+    };
+    vel.0 = integrate_forces(dt, vel.0, GRAVITY, friction);
+    physics_state.on_ground = false;
+    let pos_delta = vel.0 * dt;
+    move_point(terrain, pos, vel, physics_state, pos_delta);
+}
+
+/// Moves a point collider by `pos_delta`, stopping in front of the first
+/// solid block in its way. The block and the face of it that was hit are
+/// recorded in the physics state.
+pub fn move_point<V: ReadVol<Vox = Block>>(
+    terrain: &V,
+    pos: &mut Pos,
+    vel: &mut Vel,
+    physics_state: &mut PhysicsState,
+    pos_delta: Vec3<f32>,
+) {
+    // Keep track of the last two blocks the ray passed through: the last one is
+    // the block that was hit, and the face that was hit is the one between them
+    let mut visited: [Option<Vec3<i32>>; 2] = [None, None];
+    let (dist, block) = terrain
+        .ray(pos.0, pos.0 + pos_delta)
+        .until(|block| block.is_filled())
+        .for_each(|_, ipos| visited = [visited[1], Some(ipos)])
+        .ignore_error()
+        .cast();
+
+    pos.0 += pos_delta.try_normalized().unwrap_or(Vec3::zero()) * dist;
+    physics_state.hit_block = None;
+
+    // Can't fail since we do ignore_error above
+    if block.unwrap().is_some() {
+        let block_pos = visited[1].unwrap_or_else(|| pos.0.map(|e| e.floor() as i32));
+        let normal = match visited {
+            [Some(prev), Some(hit)] if (prev - hit).map(|e| e.abs()).sum() == 1 => prev - hit,
+            // The ray started inside the block or clipped a corner, so guess the face from
+            // where we ended up within the block
+            _ => {
+                let block_rpos = (pos.0 - (block_pos.map(|e| e as f32) + 0.5))
+                    .try_normalized()
+                    .unwrap_or(Vec3::zero());
+                let axis = block_rpos.map(|e| e.abs());
+                if axis.z > axis.x.max(axis.y) {
+                    Vec3::unit_z() * block_rpos.z.signum() as i32
+                } else if axis.x > axis.y {
+                    Vec3::unit_x() * block_rpos.x.signum() as i32
+                } else {
+                    Vec3::unit_y() * block_rpos.y.signum() as i32
+                }
+            },
+        };
+        physics_state.hit_block = Some(BlockHit {
+            pos: block_pos,
+            normal,
+        });
+
+        // See whether we're on the top/bottom of a block, or the side
+        if normal.z != 0 {
+            if normal.z > 0 {
+                physics_state.on_ground = true;
+            } else {
+                physics_state.on_ceiling = true;
+            }
+            vel.0.z = 0.0;
+        } else {
+            if normal.x != 0 {
+                vel.0.x = 0.0;
+            } else {
+                vel.0.y = 0.0;
+            }
+            physics_state.on_wall = Some(-normal.map(|e| e as f32));
+        }
+    }
+
+    physics_state.in_fluid = terrain
+        .get(pos.0.map(|e| e.floor() as i32))
+        .ok()
+        .and_then(|vox| vox.is_liquid().then_some(1.0));
+}
+
 /// This system applies forces and calculates new positions and velocities.
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -51,6 +149,7 @@ impl<'a> System<'a> for Sys {
         Entities<'a>,
         ReadStorage<'a, Uid>,
         ReadExpect<'a, TerrainGrid>,
+        ReadExpect<'a, CollisionMap>,
         Read<'a, DeltaTime>,
         ReadExpect<'a, SysMetrics>,
         Read<'a, EventBus<ServerEvent>>,
@@ -77,6 +176,7 @@ impl<'a> System<'a> for Sys {
             entities,
             uids,
             terrain,
+            collision,
             dt,
             sys_metrics,
             event_bus,
@@ -268,11 +368,24 @@ impl<'a> System<'a> for Sys {
             &mut orientations,
             &mut physics_states,
             !&mountings,
+            // Projectiles are moved by their own system
+            !&projectiles,
         )
         .par_join()
         .fold(Vec::new, |
             mut land_on_grounds,
-            (entity, _scale, sticky, collider, mut pos, mut vel, _ori, mut physics_state, _),
+            (
+                entity,
+                _scale,
+                sticky,
+                collider,
+                mut pos,
+                mut vel,
+                _ori,
+                mut physics_state,
+                _,
+                _,
+            ),
         | {
             if sticky.is_some() && physics_state.on_surface().is_some() {
                 vel.0 = Vec3::zero();
@@ -298,7 +411,8 @@ impl<'a> System<'a> for Sys {
                 });
             let in_loaded_chunk = terrain
                 .get_key(terrain.pos_key(pos.0.map(|e| e.floor() as i32)))
-                .is_some();
+                .is_some()
+                || collision.is_solid(pos.0.map(|e| e.floor() as i32)).is_some();
             let downward_force = if !in_loaded_chunk {
                 0.0 // No gravity in unloaded chunks
             } else if physics_state
@@ -354,6 +468,7 @@ impl<'a> System<'a> for Sys {
                     fn collision_iter<'a>(
                         pos: Vec3<f32>,
                         terrain: &'a TerrainGrid,
+                        collision: &'a CollisionMap,
                         hit: &'a impl Fn(&Block) -> bool,
                         height: &'a impl Fn(&Block) -> f32,
                         near_iter: impl Iterator<Item = (i32, i32, i32)> + 'a,
@@ -363,7 +478,9 @@ impl<'a> System<'a> for Sys {
                         near_iter.filter_map(move |(i, j, k)| {
                             let block_pos = pos.map(|e| e.floor() as i32) + Vec3::new(i, j, k);
 
-                            if let Some(block) = terrain.get(block_pos).ok().copied().filter(hit) {
+                            // Fall back to the collision data where the full terrain isn't loaded
+                            let block = collision.block_at(terrain, block_pos);
+                            if let Some(block) = block.filter(hit) {
                                 let player_aabb = Aabb {
                                     min: pos + Vec3::new(-radius, -radius, z_range.start),
                                     max: pos + Vec3::new(radius, radius, z_range.end),
@@ -389,12 +506,18 @@ impl<'a> System<'a> for Sys {
                     fn collision_with<'a>(
                         pos: Vec3<f32>,
                         terrain: &'a TerrainGrid,
+                        collision: &'a CollisionMap,
                         hit: impl Fn(&Block) -> bool,
                         near_iter: impl Iterator<Item = (i32, i32, i32)> + 'a,
                         radius: f32,
                         z_range: Range<f32>,
                     ) -> bool {
-                        collision_iter(pos, terrain, &|block| block.is_solid() && hit(block), &Block::solid_height, near_iter, radius, z_range).count()
+                        // Skip looking up blocks that are known to be empty
+                        let base = pos.map(|e| e.floor() as i32);
+                        let near_iter = near_iter.filter(move |(i, j, k)| {
+                            collision.is_solid(base + Vec3::new(*i, *j, *k)) != Some(false)
+                        });
+                        collision_iter(pos, terrain, collision, &|block| block.is_solid() && hit(block), &Block::solid_height, near_iter, radius, z_range).count()
                             > 0
                     };
 
@@ -417,7 +540,7 @@ impl<'a> System<'a> for Sys {
                         const MAX_ATTEMPTS: usize = 16;
 
                         // While the player is colliding with the terrain...
-                        while collision_with(pos.0, &terrain, block_true, near_iter.clone(), radius, z_range.clone())
+                        while collision_with(pos.0, &terrain, &collision, block_true, near_iter.clone(), radius, z_range.clone())
                             && attempts < MAX_ATTEMPTS
                         {
                             // Calculate the player's AABB
@@ -491,7 +614,7 @@ impl<'a> System<'a> for Sys {
 
                             // When the resolution direction is non-vertical, we must be colliding
                             // with a wall If the space above is free...
-                            if !collision_with(Vec3::new(pos.0.x, pos.0.y, (pos.0.z + 0.1).ceil()), &terrain, block_true, near_iter.clone(), radius, z_range.clone())
+                            if !collision_with(Vec3::new(pos.0.x, pos.0.y, (pos.0.z + 0.1).ceil()), &terrain, &collision, block_true, near_iter.clone(), radius, z_range.clone())
                                 // ...and we're being pushed out horizontally...
                                 && resolve_dir.z == 0.0
                                 // ...and the vertical resolution direction is sufficiently great...
@@ -505,6 +628,7 @@ impl<'a> System<'a> for Sys {
                                 && collision_with(
                                     pos.0 + resolve_dir - Vec3::unit_z() * 1.05,
                                     &terrain,
+                                    &collision,
                                     block_true,
                                     near_iter.clone(),
                                     radius,
@@ -547,6 +671,7 @@ impl<'a> System<'a> for Sys {
                     } else if collision_with(
                         pos.0 - Vec3::unit_z() * 1.05,
                         &terrain,
+                        &collision,
                         block_true,
                         near_iter.clone(),
                         radius,
@@ -557,6 +682,7 @@ impl<'a> System<'a> for Sys {
                         && !collision_with(
                             pos.0 - Vec3::unit_z() * 0.05,
                             &terrain,
+                            &collision,
                             |block| block.solid_height() >= (pos.0.z - 0.05).rem_euclid(1.0),
                             near_iter.clone(),
                             radius,
@@ -588,6 +714,7 @@ impl<'a> System<'a> for Sys {
                             if collision_with(
                                 pos.0 + *dir * 0.01,
                                 &terrain,
+                                &collision,
                                 block_true,
                                 near_iter.clone(),
                                 radius,
@@ -608,6 +735,7 @@ impl<'a> System<'a> for Sys {
                     physics_state.in_fluid = collision_iter(
                         pos.0,
                         &terrain,
+                        &collision,
                         &|block| block.is_liquid(),
                         // The liquid part of a liquid block always extends 1 block high.
                         &|_block| 1.0,
@@ -619,44 +747,7 @@ impl<'a> System<'a> for Sys {
                     .map(|block_aabb| block_aabb.max.z - pos.0.z);
                 },
                 Collider::Point => {
-                    let (dist, block) = terrain.ray(pos.0, pos.0 + pos_delta)
-                        .until(|block| block.is_filled())
-                        .ignore_error().cast();
-
-                    pos.0 += pos_delta.try_normalized().unwrap_or(Vec3::zero()) * dist;
-
-                    // Can't fail since we do ignore_error above
-                    if block.unwrap().is_some() {
-                        let block_center = pos.0.map(|e| e.floor()) + 0.5;
-                        let block_rpos = (pos.0 - block_center)
-                            .try_normalized()
-                            .unwrap_or(Vec3::zero());
-
-                        // See whether we're on the top/bottom of a block, or the side
-                        if block_rpos.z.abs()
-                            > block_rpos.xy().map(|e| e.abs()).reduce_partial_max()
-                        {
-                            if block_rpos.z > 0.0 {
-                                physics_state.on_ground = true;
-                            } else {
-                                physics_state.on_ceiling = true;
-                            }
-                            vel.0.z = 0.0;
-                        } else {
-                            physics_state.on_wall =
-                                Some(if block_rpos.x.abs() > block_rpos.y.abs() {
-                                    vel.0.x = 0.0;
-                                    Vec3::unit_x() * -block_rpos.x.signum()
-                                } else {
-                                    vel.0.y = 0.0;
-                                    Vec3::unit_y() * -block_rpos.y.signum()
-                                });
-                        }
-                    }
-
-                    physics_state.in_fluid = terrain.get(pos.0.map(|e| e.floor() as i32))
-                        .ok()
-                        .and_then(|vox| vox.is_liquid().then_some(1.0));
+                    move_point(&*terrain, &mut pos, &mut vel, &mut physics_state, pos_delta);
                 },
             }
 
@@ -1,21 +1,147 @@
 use crate::{
     comp::{
         projectile, Damage, DamageSource, Energy, EnergySource, Group, HealthChange, HealthSource,
-        Loadout, Ori, PhysicsState, Pos, Projectile, Vel,
+        HitModifiers, Loadout, Ori, PhysicsState, Poise, Pos, Projectile, Vel,
     },
-    event::{EventBus, LocalEvent, ServerEvent},
+    event::{Emitter, EventBus, LocalEvent, ServerEvent},
     metrics::SysMetrics,
     span,
     state::DeltaTime,
-    sync::UidAllocator,
+    sync::{Uid, UidAllocator},
     util::Dir,
 };
 use specs::{
-    saveload::MarkerAllocator, Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage,
+    saveload::MarkerAllocator, Entities, Entity, Join, Read, ReadExpect, ReadStorage, System,
+    WriteStorage,
 };
 use std::time::Duration;
 use vek::*;
 
+/// What the effects of projectiles need to be applied
+struct HitContext<'a, 'b> {
+    uid_allocator: &'b UidAllocator,
+    loadouts: &'b ReadStorage<'a, Loadout>,
+    energies: &'b mut WriteStorage<'a, Energy>,
+    local_emitter: Emitter<'b, LocalEvent>,
+    server_emitter: Emitter<'b, ServerEvent>,
+}
+
+/// Applies an effect of a projectile that hit `target`, or a solid block if
+/// there is no target. All projectiles share these effects, arrows and
+/// fireballs only differ in which of them they carry. Effects that need a
+/// target do nothing when a block was hit.
+fn apply_effect(
+    effect: projectile::Effect,
+    ctx: &mut HitContext,
+    entity: Entity,
+    owner: Option<Uid>,
+    pos: Vec3<f32>,
+    dir: Dir,
+    target: Option<Uid>,
+) {
+    match effect {
+        projectile::Effect::Damage(healthchange) => {
+            let (other, owner_uid) = match (target, owner) {
+                (Some(other), Some(owner_uid)) if other != owner_uid => (other, owner_uid),
+                _ => return,
+            };
+            let mut damage = Damage {
+                healthchange: healthchange as f32,
+                source: DamageSource::Projectile,
+            };
+
+            let other_entity = ctx.uid_allocator.retrieve_entity_internal(other.into());
+            let hit = other_entity
+                .and_then(|e| ctx.loadouts.get(e))
+                .map(|loadout| damage.modify_damage(None, loadout))
+                .unwrap_or_default();
+
+            if damage.healthchange < 0.0 {
+                ctx.server_emitter.emit(ServerEvent::Damage {
+                    uid: other,
+                    change: HealthChange {
+                        amount: damage.healthchange as i32,
+                        cause: HealthSource::Projectile {
+                            owner: Some(owner_uid),
+                        },
+                    },
+                    source: damage.source,
+                    hit,
+                });
+                if let Some(other_entity) = other_entity {
+                    let owner_loadout = ctx
+                        .uid_allocator
+                        .retrieve_entity_internal(owner_uid.into())
+                        .and_then(|owner| ctx.loadouts.get(owner));
+                    ctx.server_emitter.emit(ServerEvent::PoiseDamage {
+                        entity: other_entity,
+                        amount: Poise::damage_from_hit(damage.healthchange, owner_loadout),
+                    });
+                }
+            } else if damage.healthchange > 0.0 {
+                ctx.server_emitter.emit(ServerEvent::Damage {
+                    uid: other,
+                    change: HealthChange {
+                        amount: damage.healthchange as i32,
+                        cause: HealthSource::Healing {
+                            by: Some(owner_uid),
+                        },
+                    },
+                    source: DamageSource::Healing,
+                    hit: HitModifiers::default(),
+                });
+            }
+        },
+        projectile::Effect::Knockback(knockback) => {
+            if let Some(entity) =
+                target.and_then(|other| ctx.uid_allocator.retrieve_entity_internal(other.into()))
+            {
+                ctx.local_emitter.emit(LocalEvent::ApplyImpulse {
+                    entity,
+                    impulse: knockback * *Dir::slerp(dir, Dir::new(Vec3::unit_z()), 0.5),
+                });
+            }
+        },
+        projectile::Effect::RewardEnergy(energy) => {
+            if let Some(energy_mut) = owner
+                .filter(|_| target.is_some())
+                .and_then(|o| ctx.uid_allocator.retrieve_entity_internal(o.into()))
+                .and_then(|o| ctx.energies.get_mut(o))
+            {
+                energy_mut.change_by(energy as i32, EnergySource::HitEnemy);
+            }
+        },
+        projectile::Effect::Explode(e) => ctx.server_emitter.emit(ServerEvent::Explosion {
+            pos,
+            explosion: e,
+            owner,
+            friendly_damage: false,
+            reagent: None,
+        }),
+        projectile::Effect::Vanish => ctx.server_emitter.emit(ServerEvent::Destroy {
+            entity,
+            cause: HealthSource::World,
+        }),
+        projectile::Effect::Buff(buff) => {
+            if let Some(other) = target.filter(|other| Some(*other) != owner) {
+                ctx.server_emitter.emit(ServerEvent::Buff {
+                    uid: other,
+                    buff,
+                    source: owner,
+                });
+            }
+        },
+        projectile::Effect::Possess => {
+            if let (Some(other), Some(owner)) = (target, owner) {
+                if other != owner {
+                    ctx.server_emitter.emit(ServerEvent::Possess(owner, other));
+                }
+            }
+        },
+        projectile::Effect::Stick => {},
+    }
+}
+
 /// This system is responsible for handling projectile effect triggers
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -58,8 +184,13 @@ impl<'a> System<'a> for Sys {
     ) {
         let start_time = std::time::Instant::now();
         span!(_guard, "run", "projectile::Sys::run");
-        let mut local_emitter = local_bus.emitter();
-        let mut server_emitter = server_bus.emitter();
+        let mut hit_context = HitContext {
+            uid_allocator: &uid_allocator,
+            loadouts: &loadouts,
+            energies: &mut energies,
+            local_emitter: local_bus.emitter(),
+            server_emitter: server_bus.emitter(),
+        };
 
         // Attacks
         for (entity, pos, physics, ori, projectile) in (
@@ -96,107 +227,34 @@ impl<'a> System<'a> for Sys {
                 }
 
                 for effect in projectile.hit_entity.drain(..) {
-                    match effect {
-                        projectile::Effect::Damage(healthchange) => {
-                            let owner_uid = projectile.owner.unwrap();
-                            let mut damage = Damage {
-                                healthchange: healthchange as f32,
-                                source: DamageSource::Projectile,
-                            };
-
-                            let other_entity = uid_allocator.retrieve_entity_internal(other.into());
-                            if let Some(loadout) = other_entity.and_then(|e| loadouts.get(e)) {
-                                damage.modify_damage(false, loadout);
-                            }
-
-                            if other != owner_uid {
-                                if damage.healthchange < 0.0 {
-                                    server_emitter.emit(ServerEvent::Damage {
-                                        uid: other,
-                                        change: HealthChange {
-                                            amount: damage.healthchange as i32,
-                                            cause: HealthSource::Projectile {
-                                                owner: Some(owner_uid),
-                                            },
-                                        },
-                                    });
-                                } else if damage.healthchange > 0.0 {
-                                    server_emitter.emit(ServerEvent::Damage {
-                                        uid: other,
-                                        change: HealthChange {
-                                            amount: damage.healthchange as i32,
-                                            cause: HealthSource::Healing {
-                                                by: Some(owner_uid),
-                                            },
-                                        },
-                                    });
-                                }
-                            }
-                        },
-                        projectile::Effect::Knockback(knockback) => {
-                            if let Some(entity) =
-                                uid_allocator.retrieve_entity_internal(other.into())
-                            {
-                                local_emitter.emit(LocalEvent::ApplyImpulse {
-                                    entity,
-                                    impulse: knockback
-                                        * *Dir::slerp(ori.0, Dir::new(Vec3::unit_z()), 0.5),
-                                });
-                            }
-                        },
-                        projectile::Effect::RewardEnergy(energy) => {
-                            if let Some(energy_mut) = projectile
-                                .owner
-                                .and_then(|o| uid_allocator.retrieve_entity_internal(o.into()))
-                                .and_then(|o| energies.get_mut(o))
-                            {
-                                energy_mut.change_by(energy as i32, EnergySource::HitEnemy);
-                            }
-                        },
-                        projectile::Effect::Explode(e) => {
-                            server_emitter.emit(ServerEvent::Explosion {
-                                pos: pos.0,
-                                explosion: e,
-                                owner: projectile.owner,
-                                friendly_damage: false,
-                                reagent: None,
-                            })
-                        },
-                        projectile::Effect::Vanish => server_emitter.emit(ServerEvent::Destroy {
-                            entity,
-                            cause: HealthSource::World,
-                        }),
-                        projectile::Effect::Possess => {
-                            if other != projectile.owner.unwrap() {
-                                if let Some(owner) = projectile.owner {
-                                    server_emitter.emit(ServerEvent::Possess(owner, other));
-                                }
-                            }
-                        },
-                        _ => {},
-                    }
+                    apply_effect(
+                        effect,
+                        &mut hit_context,
+                        entity,
+                        projectile.owner,
+                        pos.0,
+                        ori.0,
+                        Some(other),
+                    );
                 }
             }
 
             // Hit something solid
             if physics.on_wall.is_some() || physics.on_ground || physics.on_ceiling {
+                // Detonate in front of the face that was hit rather than inside the block
+                let hit_pos = physics
+                    .hit_block
+                    .map_or(pos.0, |hit| pos.0 + hit.normal.map(|e| e as f32) * 0.5);
                 for effect in projectile.hit_solid.drain(..) {
-                    match effect {
-                        projectile::Effect::Explode(e) => {
-                            server_emitter.emit(ServerEvent::Explosion {
-                                pos: pos.0,
-                                explosion: e,
-                                owner: projectile.owner,
-                                friendly_damage: false,
-                                reagent: None,
-                            })
-                        },
-                        projectile::Effect::Vanish => server_emitter.emit(ServerEvent::Destroy {
-                            entity,
-                            cause: HealthSource::World,
-                        }),
-                        _ => {},
-                    }
+                    apply_effect(
+                        effect,
+                        &mut hit_context,
+                        entity,
+                        projectile.owner,
+                        hit_pos,
+                        ori.0,
+                        None,
+                    );
                 }
             } else if let Some(dir) = velocities
                 .get(entity)
@@ -206,7 +264,7 @@ impl<'a> System<'a> for Sys {
             }
 
             if projectile.time_left == Duration::default() {
-                server_emitter.emit(ServerEvent::Destroy {
+                hit_context.server_emitter.emit(ServerEvent::Destroy {
                     entity,
                     cause: HealthSource::World,
                 });
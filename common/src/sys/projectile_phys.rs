@@ -0,0 +1,179 @@
+use super::phys::{integrate_forces, move_point, BOUYANCY, FRIC_FLUID, FRIC_GROUND, GRAVITY};
+use crate::{
+    comp::{Gravity, PhysicsState, Pos, Projectile, Sticky, Vel},
+    metrics::SysMetrics,
+    span,
+    state::DeltaTime,
+    terrain::TerrainGrid,
+    weather::Weather,
+};
+use specs::{Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
+use vek::*;
+
+/// Fraction of the wind speed by which projectiles are accelerated each second
+const PROJECTILE_WIND_DRIFT: f32 = 0.5;
+
+/// This system moves projectiles along their arcs. They are pulled down by
+/// gravity, slowed down by their own drag and carried by the wind, and stop
+/// at the face of the first block they fly into. Touching entities is still
+/// detected by the physics system, and the effects of hits are applied by the
+/// projectile system.
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, TerrainGrid>,
+        ReadExpect<'a, Weather>,
+        Read<'a, DeltaTime>,
+        ReadExpect<'a, SysMetrics>,
+        ReadStorage<'a, Projectile>,
+        ReadStorage<'a, Sticky>,
+        ReadStorage<'a, Gravity>,
+        WriteStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, PhysicsState>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            terrain,
+            weather,
+            dt,
+            sys_metrics,
+            projectiles,
+            stickies,
+            gravities,
+            mut positions,
+            mut velocities,
+            mut physics_states,
+        ): Self::SystemData,
+    ) {
+        let start_time = std::time::Instant::now();
+        span!(_guard, "run", "projectile_phys::Sys::run");
+
+        for (projectile, sticky, gravity, pos, vel, physics_state) in (
+            &projectiles,
+            stickies.maybe(),
+            gravities.maybe(),
+            &mut positions,
+            &mut velocities,
+            &mut physics_states,
+        )
+            .join()
+        {
+            if sticky.is_some() && physics_state.on_surface().is_some() {
+                vel.0 = Vec3::zero();
+                continue;
+            }
+
+            // Don't move in unloaded chunks
+            if terrain
+                .get_key(terrain.pos_key(pos.0.map(|e| e.floor() as i32)))
+                .is_none()
+            {
+                continue;
+            }
+
+            let old_vel = *vel;
+            let friction = projectile
+                .drag
+                .max(if physics_state.on_ground {
+                    FRIC_GROUND
+                } else {
+                    0.0
+                })
+                .max(if physics_state.in_fluid.is_some() {
+                    FRIC_FLUID
+                } else {
+                    0.0
+                });
+            let downward_force = if physics_state.in_fluid.is_some() {
+                (1.0 - BOUYANCY) * GRAVITY
+            } else {
+                GRAVITY
+            } * gravity.map_or(0.0, |g| g.0);
+            vel.0 = integrate_forces(dt.0, vel.0, downward_force, friction);
+
+            // Projectiles in flight drift with the wind
+            if physics_state.on_surface().is_none() {
+                vel.0 += Vec3::from(weather.wind_at(pos.0.xy())) * PROJECTILE_WIND_DRIFT * dt.0;
+            }
+
+            // Same approximation as for other entities, so that most framerates
+            // behave in a similar manner
+            let dt_lerp = 0.2;
+            let pos_delta = (vel.0 * dt_lerp + old_vel.0 * (1.0 - dt_lerp)) * dt.0;
+            move_point(&*terrain, pos, vel, physics_state, pos_delta);
+        }
+
+        // Projectiles are counted as part of the physics
+        sys_metrics.phys_ns.fetch_add(
+            start_time.elapsed().as_nanos() as i64,
+            std::sync::atomic::Ordering::Relaxed,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta};
+
+    /// Solid rock below z = 0 and air above it
+    fn ground() -> TerrainChunk {
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let air = Block::air(SpriteKind::Empty);
+        TerrainChunk::new(0, rock, air, TerrainChunkMeta::void())
+    }
+
+    fn shoot(terrain: &TerrainChunk, from: Vec3<f32>, delta: Vec3<f32>) -> (Pos, PhysicsState) {
+        let mut pos = Pos(from);
+        let mut vel = Vel(delta);
+        let mut physics_state = PhysicsState::default();
+        move_point(terrain, &mut pos, &mut vel, &mut physics_state, delta);
+        (pos, physics_state)
+    }
+
+    #[test]
+    fn projectiles_stop_at_the_top_of_the_ground() {
+        let (pos, physics_state) = shoot(
+            &ground(),
+            Vec3::new(4.5, 4.5, 3.5),
+            Vec3::new(0.0, 0.0, -10.0),
+        );
+        let hit = physics_state.hit_block.expect("Expected to hit the ground");
+        assert_eq!(hit.pos, Vec3::new(4, 4, -1));
+        assert_eq!(hit.normal, Vec3::unit_z());
+        assert!(physics_state.on_ground);
+        assert!((pos.0.z - 0.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn the_hit_block_is_the_one_the_ray_stopped_in() {
+        // Coming in at an angle, the projectile ends up exactly on the face
+        // between two blocks. The block that was hit is the solid one below it,
+        // not the air block the rounded position is in.
+        let (pos, physics_state) = shoot(
+            &ground(),
+            Vec3::new(4.25, 4.5, 2.5),
+            Vec3::new(3.0, 0.0, -5.0),
+        );
+        let hit = physics_state.hit_block.expect("Expected to hit the ground");
+        assert_eq!(hit.normal, Vec3::unit_z());
+        assert_eq!(hit.pos.z, -1);
+        assert_eq!(hit.pos.xy(), pos.0.xy().map(|e| e.floor() as i32));
+    }
+
+    #[test]
+    fn projectiles_in_the_air_hit_nothing() {
+        let (pos, physics_state) = shoot(
+            &ground(),
+            Vec3::new(4.5, 4.5, 10.5),
+            Vec3::new(2.0, 0.0, -1.0),
+        );
+        assert_eq!(physics_state.hit_block, None);
+        assert!(!physics_state.on_ground);
+        assert!(pos.0.x > 6.49 && pos.0.z < 9.51);
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
     comp::{
-        group, Body, CharacterState, Damage, DamageSource, HealthChange, HealthSource, Last,
-        Loadout, Ori, PhysicsState, Pos, Scale, Shockwave, ShockwaveHitEntities, Stats,
+        group, Body, CharacterAbilityType, CharacterState, Damage, DamageSource, HealthChange,
+        HealthSource, Last, Loadout, Ori, PhysicsState, Poise, Pos, Scale, Shockwave,
+        ShockwaveHitEntities, Stats,
     },
     event::{EventBus, LocalEvent, ServerEvent},
     state::{DeltaTime, Time},
@@ -11,8 +12,6 @@ use crate::{
 use specs::{saveload::MarkerAllocator, Entities, Join, Read, ReadStorage, System, WriteStorage};
 use vek::*;
 
-pub const BLOCK_ANGLE: f32 = 180.0;
-
 /// This system is responsible for handling accepted inputs like moving or
 /// attacking
 pub struct Sys;
@@ -198,17 +197,20 @@ impl<'a> System<'a> for Sys {
                         source: DamageSource::Shockwave,
                     };
 
-                    let block = character_b.map(|c_b| c_b.is_block()).unwrap_or(false)
-                        && ori_b.0.angle_between(pos.0 - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0;
+                    let block = character_b
+                        .and_then(|c_b| c_b.block())
+                        .filter(|block| block.covers(pos_b.0, ori_b, pos.0));
 
-                    if let Some(loadout) = loadouts.get(b) {
-                        damage.modify_damage(block, loadout);
-                    }
+                    let hit = loadouts
+                        .get(b)
+                        .map(|loadout| damage.modify_damage(block, loadout))
+                        .unwrap_or_default();
 
                     if damage.healthchange != 0.0 {
                         let cause = if damage.healthchange < 0.0 {
                             HealthSource::Attack {
                                 by: shockwave.owner.unwrap_or(*uid),
+                                ability: Some(CharacterAbilityType::Shockwave),
                             }
                         } else {
                             HealthSource::Healing {
@@ -221,6 +223,16 @@ impl<'a> System<'a> for Sys {
                                 amount: damage.healthchange as i32,
                                 cause,
                             },
+                            source: damage.source,
+                            hit,
+                        });
+                        let owner_loadout = shockwave
+                            .owner
+                            .and_then(|uid| uid_allocator.retrieve_entity_internal(uid.into()))
+                            .and_then(|owner| loadouts.get(owner));
+                        server_emitter.emit(ServerEvent::PoiseDamage {
+                            entity: b,
+                            amount: Poise::damage_from_hit(damage.healthchange, owner_loadout),
                         });
                         shockwave_hit_list.hit_entities.push(*uid_b);
                     }
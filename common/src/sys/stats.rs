@@ -1,5 +1,5 @@
 use crate::{
-    comp::{CharacterState, Energy, EnergySource, HealthSource, Stats},
+    comp::{CharacterState, Energy, EnergySource, HealthSource, Poise, Stats},
     event::{EventBus, ServerEvent},
     metrics::SysMetrics,
     span,
@@ -9,7 +9,7 @@ use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage}
 
 const ENERGY_REGEN_ACCEL: f32 = 10.0;
 
-/// This system kills players, levels them up, and regenerates energy.
+/// This system kills players, levels them up, and regenerates energy and poise.
 pub struct Sys;
 impl<'a> System<'a> for Sys {
     #[allow(clippy::type_complexity)]
@@ -21,11 +21,21 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, CharacterState>,
         WriteStorage<'a, Stats>,
         WriteStorage<'a, Energy>,
+        WriteStorage<'a, Poise>,
     );
 
     fn run(
         &mut self,
-        (entities, dt, server_event_bus, sys_metrics, character_states, mut stats, mut energies): Self::SystemData,
+        (
+            entities,
+            dt,
+            server_event_bus,
+            sys_metrics,
+            character_states,
+            mut stats,
+            mut energies,
+            mut poises,
+        ): Self::SystemData,
     ) {
         let start_time = std::time::Instant::now();
         span!(_guard, "run", "stats::Sys::run");
@@ -87,6 +97,7 @@ impl<'a> System<'a> for Sys {
                 | CharacterState::GlideWield { .. }
                 | CharacterState::Wielding { .. }
                 | CharacterState::Equipping { .. }
+                | CharacterState::Fishing { .. }
                 | CharacterState::Boost { .. } => {
                     let res = {
                         let energy = energy.get_unchecked();
@@ -105,7 +116,7 @@ impl<'a> System<'a> for Sys {
                             (energy.regen_rate + ENERGY_REGEN_ACCEL * dt.0).min(100.0);
                     }
                 },
-                // Ability use does not regen and sets the rate back to zero.
+                // Ability use and reeling from hits do not regen and set the rate back to zero.
                 CharacterState::BasicMelee { .. }
                 | CharacterState::DashMelee { .. }
                 | CharacterState::LeapMelee { .. }
@@ -116,14 +127,16 @@ impl<'a> System<'a> for Sys {
                 | CharacterState::ChargedRanged { .. }
                 | CharacterState::RepeaterRanged { .. }
                 | CharacterState::Shockwave { .. }
-                | CharacterState::BasicBeam { .. } => {
+                | CharacterState::BasicBeam { .. }
+                | CharacterState::Flinch { .. }
+                | CharacterState::Stagger { .. } => {
                     if energy.get_unchecked().regen_rate != 0.0 {
                         energy.get_mut_unchecked().regen_rate = 0.0
                     }
                 },
                 // recover small amount of passive energy from blocking, and bonus energy from
                 // blocking attacks?
-                CharacterState::BasicBlock => {
+                CharacterState::BasicBlock { .. } => {
                     let res = {
                         let energy = energy.get_unchecked();
                         energy.current() < energy.maximum()
@@ -140,6 +153,18 @@ impl<'a> System<'a> for Sys {
                 CharacterState::Roll { .. } | CharacterState::Climb { .. } => {},
             }
         }
+
+        // Recover poise, leaving untouched poise alone to avoid unnecessary syncing
+        for mut poise in (&mut poises.restrict_mut()).join() {
+            let recovering = {
+                let poise = poise.get_unchecked();
+                poise.since_damage < Poise::REGEN_DELAY || poise.current() < poise.maximum()
+            };
+
+            if recovering {
+                poise.get_mut_unchecked().regen(dt.0);
+            }
+        }
         sys_metrics.stats_ns.store(
             start_time.elapsed().as_nanos() as i64,
             std::sync::atomic::Ordering::Relaxed,
@@ -1,4 +1,5 @@
 use crate::{
+    memory::MemoryUsage,
     vol::{
         BaseVol, IntoPosIterator, IntoVolIterator, ReadVol, RectRasterableVol, RectVolSize,
         VolSize, WriteVol,
@@ -57,6 +58,8 @@ impl<V, S: RectVolSize, M: Clone> Chonk<V, S, M> {
 
     pub fn meta(&self) -> &M { &self.meta }
 
+    pub fn meta_mut(&mut self) -> &mut M { &mut self.meta }
+
     pub fn get_min_z(&self) -> i32 { self.z_offset }
 
     pub fn get_max_z(&self) -> i32 {
@@ -65,6 +68,18 @@ impl<V, S: RectVolSize, M: Clone> Chonk<V, S, M> {
 
     pub fn sub_chunks_len(&self) -> usize { self.sub_chunks.len() }
 
+    /// The voxel that fills everything below the sub chunks
+    pub fn below(&self) -> &V { &self.below }
+
+    /// The voxel that fills everything above the sub chunks
+    pub fn above(&self) -> &V { &self.above }
+
+    /// Add a sub chunk filled with `vox` on top of the existing ones.
+    pub fn push_filled_sub_chunk(&mut self, vox: V) {
+        self.sub_chunks
+            .push(SubChunk::filled(vox, self.meta.clone()));
+    }
+
     pub fn sub_chunk_groups(&self) -> usize {
         self.sub_chunks.iter().map(SubChunk::num_groups).sum()
     }
@@ -134,6 +149,17 @@ impl<V, S: RectVolSize, M: Clone> Chonk<V, S, M> {
     }
 }
 
+impl<V, S: RectVolSize, M: Clone> MemoryUsage for Chonk<V, S, M> {
+    fn memory_usage(&self) -> usize {
+        core::mem::size_of::<Self>()
+            + self
+                .sub_chunks
+                .iter()
+                .map(MemoryUsage::memory_usage)
+                .sum::<usize>()
+    }
+}
+
 impl<V, S: RectVolSize, M: Clone> BaseVol for Chonk<V, S, M> {
     type Error = ChonkError;
     type Vox = V;
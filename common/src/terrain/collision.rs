@@ -0,0 +1,206 @@
+use super::{Block, BlockKind, TerrainChunk, TerrainChunkSize, TerrainGrid};
+use crate::vol::{ReadVol, RectVolSize};
+use hashbrown::HashMap;
+use vek::*;
+
+/// Number of blocks in a single horizontal layer of a chunk
+const LAYER_SIZE: usize = (TerrainChunkSize::RECT_SIZE.x * TerrainChunkSize::RECT_SIZE.y) as usize;
+
+/// Solidity of every block of a terrain chunk, packed into one bit per block.
+///
+/// This is all that's needed to collide against terrain, at a fraction of the
+/// memory of the full block data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkCollision {
+    min_z: i32,
+    max_z: i32,
+    /// Whether blocks below `min_z` are solid
+    below: bool,
+    /// Whether blocks at or above `max_z` are solid
+    above: bool,
+    bits: Vec<u64>,
+}
+
+impl ChunkCollision {
+    pub fn from_chunk(chunk: &TerrainChunk) -> Self {
+        let min_z = chunk.get_min_z();
+        let max_z = chunk.get_max_z();
+        let is_solid = |pos| chunk.get(pos).map_or(false, Block::is_solid);
+
+        let len = LAYER_SIZE * (max_z - min_z).max(0) as usize;
+        let mut this = Self {
+            min_z,
+            max_z,
+            below: is_solid(Vec3::new(0, 0, min_z - 1)),
+            above: is_solid(Vec3::new(0, 0, max_z)),
+            bits: vec![0; (len + 63) / 64],
+        };
+        for z in min_z..max_z {
+            for y in 0..TerrainChunkSize::RECT_SIZE.y as i32 {
+                for x in 0..TerrainChunkSize::RECT_SIZE.x as i32 {
+                    let pos = Vec3::new(x, y, z);
+                    this.set_solid(pos, is_solid(pos));
+                }
+            }
+        }
+        this
+    }
+
+    fn idx(&self, pos: Vec3<i32>) -> Option<usize> {
+        if pos.z < self.min_z || pos.z >= self.max_z {
+            None
+        } else {
+            let size = TerrainChunkSize::RECT_SIZE.map(|e| e as i32);
+            let xy = pos.xy().map2(size, |e, sz| e.rem_euclid(sz));
+            Some(
+                (pos.z - self.min_z) as usize * LAYER_SIZE
+                    + (xy.y * size.x) as usize
+                    + xy.x as usize,
+            )
+        }
+    }
+
+    /// Whether the block at the given position, relative to the chunk, is
+    /// solid
+    pub fn is_solid(&self, pos: Vec3<i32>) -> bool {
+        match self.idx(pos) {
+            Some(idx) => self.bits[idx / 64] & (1 << (idx % 64)) != 0,
+            None => {
+                if pos.z < self.min_z {
+                    self.below
+                } else {
+                    self.above
+                }
+            },
+        }
+    }
+
+    fn set_solid(&mut self, pos: Vec3<i32>, solid: bool) {
+        if let Some(idx) = self.idx(pos) {
+            if solid {
+                self.bits[idx / 64] |= 1 << (idx % 64);
+            } else {
+                self.bits[idx / 64] &= !(1 << (idx % 64));
+            }
+        }
+    }
+}
+
+/// Collision data of terrain chunks whose full block data was unloaded, so
+/// that physics prediction can still collide with them. Chunks that are loaded
+/// are looked up in the full terrain instead and aren't kept here.
+#[derive(Default)]
+pub struct CollisionMap {
+    enabled: bool,
+    chunks: HashMap<Vec2<i32>, ChunkCollision>,
+}
+
+impl CollisionMap {
+    /// Start keeping the collision data of terrain chunks as they get
+    /// unloaded. Disabled by default since the server doesn't predict physics.
+    pub fn enable(&mut self) { self.enabled = true; }
+
+    /// Keep the collision data of a chunk whose full data is being unloaded
+    pub fn insert(&mut self, key: Vec2<i32>, chunk: &TerrainChunk) {
+        if self.enabled {
+            self.chunks.insert(key, ChunkCollision::from_chunk(chunk));
+        }
+    }
+
+    pub fn remove(&mut self, key: Vec2<i32>) { self.chunks.remove(&key); }
+
+    pub fn clear(&mut self) { self.chunks.clear(); }
+
+    /// Forget the chunks that are more than `max_dist` chunks away from
+    /// `center`
+    pub fn retain_near(&mut self, center: Vec2<i32>, max_dist: u32) {
+        self.chunks.retain(|key, _| {
+            (*key - center).map(|e| e.abs() as u32).magnitude_squared() <= max_dist.pow(2)
+        });
+    }
+
+    /// Whether the block at the given world position is solid, or `None` if
+    /// the chunk it belongs to isn't known.
+    pub fn is_solid(&self, wpos: Vec3<i32>) -> Option<bool> {
+        self.chunks
+            .get(&Self::pos_key(wpos))
+            .map(|chunk| chunk.is_solid(wpos))
+    }
+
+    /// The block to collide with at the given world position. It comes from
+    /// the full terrain where its chunk is loaded, and from the collision data
+    /// elsewhere, where partial blocks are treated as full ones.
+    pub fn block_at(&self, terrain: &TerrainGrid, wpos: Vec3<i32>) -> Option<Block> {
+        terrain.get(wpos).ok().copied().or_else(|| {
+            self.is_solid(wpos)
+                .filter(|solid| *solid)
+                .map(|_| Block::new(BlockKind::Rock, Rgb::zero()))
+        })
+    }
+
+    fn pos_key(wpos: Vec3<i32>) -> Vec2<i32> {
+        wpos.xy()
+            .map2(TerrainChunkSize::RECT_SIZE, |e, sz| e.div_euclid(sz as i32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        terrain::{SpriteKind, TerrainChunkMeta},
+        vol::WriteVol,
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn collision_matches_chunk_solidity() {
+        let air = Block::air(SpriteKind::Empty);
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let mut chunk = TerrainChunk::new(0, rock, air, TerrainChunkMeta::void());
+        chunk.set(Vec3::new(3, 4, 5), rock).unwrap();
+
+        let mut map = CollisionMap::default();
+        map.insert(Vec2::zero(), &chunk);
+        assert_eq!(map.is_solid(Vec3::new(3, 4, 5)), None);
+
+        map.enable();
+        map.insert(Vec2::new(1, 0), &chunk);
+        let offset = Vec3::new(32, 0, 0);
+        assert_eq!(map.is_solid(offset + Vec3::new(3, 4, 5)), Some(true));
+        assert_eq!(map.is_solid(offset + Vec3::new(3, 4, 6)), Some(false));
+        assert_eq!(map.is_solid(offset + Vec3::new(3, 4, -10)), Some(true));
+        assert_eq!(map.is_solid(offset + Vec3::new(3, 4, 100)), Some(false));
+
+        map.retain_near(Vec2::new(5, 0), 3);
+        assert_eq!(map.is_solid(offset + Vec3::new(3, 4, 5)), None);
+    }
+
+    #[test]
+    fn unloaded_chunks_fall_back_to_collision_data() {
+        let air = Block::air(SpriteKind::Empty);
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let mut chunk = TerrainChunk::new(0, rock, air, TerrainChunkMeta::void());
+        chunk.set(Vec3::new(3, 4, 5), rock).unwrap();
+        let mut terrain = TerrainGrid::new().unwrap();
+        let mut map = CollisionMap::default();
+        map.enable();
+        let key = Vec2::new(2, 3);
+        let wpos = Vec3::new(2 * 32 + 3, 3 * 32 + 4, 5);
+
+        // Loaded chunks are only stored once, in the full terrain
+        terrain.insert(key, Arc::new(chunk));
+        assert_eq!(map.is_solid(wpos), None);
+        assert_eq!(map.block_at(&terrain, wpos), Some(rock));
+        assert_eq!(map.block_at(&terrain, wpos + Vec3::unit_z()), Some(air));
+
+        // Unloading the full data keeps the collision data around
+        let chunk = terrain.remove(key).unwrap();
+        map.insert(key, &chunk);
+        drop(chunk);
+        assert!(terrain.get(wpos).is_err());
+        assert!(map.block_at(&terrain, wpos).map_or(false, |b| b.is_solid()));
+        assert_eq!(map.block_at(&terrain, wpos + Vec3::unit_z()), None);
+        assert!(map.block_at(&terrain, wpos - Vec3::unit_z() * 10).is_some());
+    }
+}
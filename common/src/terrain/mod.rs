@@ -1,7 +1,10 @@
 pub mod biome;
 pub mod block;
 pub mod chonk;
+pub mod collision;
 pub mod map;
+pub mod packed;
+pub mod sign;
 pub mod sprite;
 pub mod structure;
 
@@ -9,14 +12,20 @@ pub mod structure;
 pub use self::{
     biome::BiomeKind,
     block::{Block, BlockKind},
+    collision::CollisionMap,
     map::MapSizeLg,
+    packed::{ChunkEncoding, PackedChunk},
     sprite::SpriteKind,
     structure::Structure,
 };
+use hashbrown::HashMap;
 use roots::find_roots_cubic;
 use serde::{Deserialize, Serialize};
 
-use crate::{vol::RectVolSize, volumes::vol_grid_2d::VolGrid2d};
+use crate::{
+    vol::{ReadVol, RectVolSize},
+    volumes::vol_grid_2d::VolGrid2d,
+};
 use vek::*;
 
 // TerrainChunkSize
@@ -50,21 +59,40 @@ impl RectVolSize for TerrainChunkSize {
 pub struct TerrainChunkMeta {
     name: Option<String>,
     biome: BiomeKind,
+    /// Text written on the signs in the chunk, by world position
+    signs: HashMap<Vec3<i32>, String>,
 }
 
 impl TerrainChunkMeta {
-    pub fn new(name: Option<String>, biome: BiomeKind) -> Self { Self { name, biome } }
+    pub fn new(name: Option<String>, biome: BiomeKind) -> Self {
+        Self {
+            name,
+            biome,
+            signs: HashMap::new(),
+        }
+    }
 
     pub fn void() -> Self {
         Self {
             name: None,
             biome: BiomeKind::Void,
+            signs: HashMap::new(),
         }
     }
 
     pub fn name(&self) -> &str { self.name.as_deref().unwrap_or("Wilderness") }
 
     pub fn biome(&self) -> BiomeKind { self.biome }
+
+    pub fn sign(&self, pos: Vec3<i32>) -> Option<&str> { self.signs.get(&pos).map(String::as_str) }
+
+    /// Set the text of the sign at a position, or remove it with `None`
+    pub fn set_sign(&mut self, pos: Vec3<i32>, text: Option<String>) {
+        match text {
+            Some(text) => self.signs.insert(pos, text),
+            None => self.signs.remove(&pos),
+        };
+    }
 }
 
 // Terrain type aliases
@@ -74,6 +102,35 @@ pub type TerrainGrid = VolGrid2d<TerrainChunk>;
 
 // Terrain helper functions used across multiple crates.
 
+/// Solid blocks within this distance of either end of a line of sight are
+/// ignored, to tolerate lag and partial blocks at the feet of entities.
+pub const LINE_OF_SIGHT_TOLERANCE: f32 = 1.0;
+
+/// Whether nothing solid is in the way between two points, ignoring blocks
+/// close to either end (see [`LINE_OF_SIGHT_TOLERANCE`]).
+pub fn line_of_sight<V: ReadVol<Vox = Block>>(vol: &V, from: Vec3<f32>, to: Vec3<f32>) -> bool {
+    let dist = from.distance(to);
+    if dist <= LINE_OF_SIGHT_TOLERANCE * 2.0 {
+        return true;
+    }
+
+    let mut blocked = false;
+    let _ = vol
+        .ray(from, to)
+        .until(|_| false)
+        .for_each(|block: &Block, pos| {
+            let center = pos.map(|e| e as f32 + 0.5);
+            blocked |= block.is_solid()
+                && center.distance(from) > LINE_OF_SIGHT_TOLERANCE
+                && center.distance(to) > LINE_OF_SIGHT_TOLERANCE;
+        })
+        // A ray crosses at most three block boundaries per block of distance
+        .max_iter(dist as usize * 3 + 3)
+        .ignore_error()
+        .cast();
+    !blocked
+}
+
 /// Computes the position Vec2 of a SimChunk from an index, where the index was
 /// generated by uniform_noise.
 ///
@@ -208,3 +265,26 @@ pub fn quadratic_nearest_point(
         });
     min_root
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{vol::WriteVol, volumes::dyna::Dyna};
+
+    #[test]
+    fn walls_block_line_of_sight() {
+        let mut vol: Dyna<Block, ()> =
+            Dyna::filled(Vec3::new(16, 16, 16), Block::air(SpriteKind::Empty), ());
+        let (from, to) = (Vec3::new(2.5, 8.5, 8.5), Vec3::new(13.5, 8.5, 8.5));
+        assert!(line_of_sight(&vol, from, to));
+
+        for y in 0..16 {
+            for z in 0..16 {
+                let _ = vol.set(Vec3::new(8, y, z), Block::new(BlockKind::Rock, Rgb::zero()));
+            }
+        }
+        assert!(!line_of_sight(&vol, from, to));
+        // Blocks right next to either end are tolerated
+        assert!(line_of_sight(&vol, Vec3::new(7.5, 8.5, 8.5), to));
+    }
+}
@@ -0,0 +1,290 @@
+use super::{chonk::SubChunkSize, Block, TerrainChunk, TerrainChunkMeta, TerrainChunkSize};
+use crate::vol::{ReadVol, VolSize, WriteVol};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use vek::*;
+
+const SUB_CHUNK_SIZE: Vec3<u32> = SubChunkSize::<TerrainChunkSize>::SIZE;
+const SUB_CHUNK_VOLUME: usize = (SUB_CHUNK_SIZE.x * SUB_CHUNK_SIZE.y * SUB_CHUNK_SIZE.z) as usize;
+
+/// Encodings of [`PackedChunk`]s, in the order they were introduced. Clients
+/// tell the server the latest one they understand when registering.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ChunkEncoding {
+    /// Sub chunks are paletted, with bit packed palette indices
+    Paletted,
+    /// Like `Paletted`, but sub chunks made of long runs of the same block
+    /// store those runs instead
+    RunLength,
+}
+
+impl ChunkEncoding {
+    pub const LATEST: Self = ChunkEncoding::RunLength;
+}
+
+impl Default for ChunkEncoding {
+    fn default() -> Self { ChunkEncoding::Paletted }
+}
+
+/// Compact representation of a terrain chunk, used to transfer chunks to
+/// clients.
+///
+/// Every sub chunk stores a palette of the distinct blocks it contains and the
+/// index into that palette of each of its blocks, packed into as few bits as
+/// the size of the palette allows, or as runs of the same index when that is
+/// smaller. Sub chunks made of a single block only store that block.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PackedChunk {
+    z_offset: i32,
+    below: Block,
+    above: Block,
+    meta: TerrainChunkMeta,
+    sub_chunks: Vec<PackedSubChunk>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum PackedSubChunk {
+    Homogeneous(Block),
+    Paletted {
+        /// Distinct blocks, most common first
+        palette: Vec<Block>,
+        bits: u8,
+        /// Palette indices in x, y, z order; indices never straddle two words
+        indices: Vec<u64>,
+    },
+    RunLength {
+        /// Distinct blocks, most common first
+        palette: Vec<Block>,
+        /// Palette index and length of each run, in x, y, z order
+        runs: Vec<(u16, u16)>,
+    },
+}
+
+/// Position of the `i`th block of a sub chunk, relative to the sub chunk
+fn sub_chunk_pos(i: usize) -> Vec3<i32> {
+    let (w, d) = (SUB_CHUNK_SIZE.x as usize, SUB_CHUNK_SIZE.y as usize);
+    Vec3::new(i % w, (i / w) % d, i / (w * d)).map(|e| e as i32)
+}
+
+/// Number of bits needed to index a palette of the given length
+fn bits_for(palette_len: usize) -> u8 {
+    (64 - (palette_len as u64).saturating_sub(1).leading_zeros()).max(1) as u8
+}
+
+impl PackedSubChunk {
+    fn pack(chunk: &TerrainChunk, min_z: i32, encoding: ChunkEncoding) -> Self {
+        let blocks = (0..SUB_CHUNK_VOLUME)
+            .map(|i| {
+                let pos = sub_chunk_pos(i) + Vec3::unit_z() * min_z;
+                chunk
+                    .get(pos)
+                    .map(|b| *b)
+                    .unwrap_or_else(|_| *chunk.above())
+            })
+            .collect::<Vec<_>>();
+
+        let mut counts = HashMap::<Block, usize>::new();
+        blocks
+            .iter()
+            .for_each(|b| *counts.entry(*b).or_default() += 1);
+        if counts.len() == 1 {
+            return PackedSubChunk::Homogeneous(blocks[0]);
+        }
+
+        let mut palette = counts.into_iter().collect::<Vec<_>>();
+        palette.sort_by(|(_, a), (_, b)| b.cmp(a));
+        let palette = palette.into_iter().map(|(b, _)| b).collect::<Vec<_>>();
+        let lookup = palette
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (*b, i as u64))
+            .collect::<HashMap<_, _>>();
+
+        let bits = bits_for(palette.len());
+        let per_word = 64 / bits as usize;
+        let mut indices = vec![0u64; (SUB_CHUNK_VOLUME + per_word - 1) / per_word];
+        for (i, block) in blocks.iter().enumerate() {
+            indices[i / per_word] |= lookup[block] << ((i % per_word) * bits as usize);
+        }
+
+        if encoding >= ChunkEncoding::RunLength {
+            let mut runs = Vec::<(u16, u16)>::new();
+            for block in blocks.iter() {
+                let idx = lookup[block] as u16;
+                match runs.last_mut() {
+                    Some((last, len)) if *last == idx && *len < u16::MAX => *len += 1,
+                    _ => runs.push((idx, 1)),
+                }
+            }
+            // Both integers of a run take 2 bytes each when serialized
+            if runs.len() * 4 < indices.len() * 8 {
+                return PackedSubChunk::RunLength { palette, runs };
+            }
+        }
+
+        PackedSubChunk::Paletted {
+            palette,
+            bits,
+            indices,
+        }
+    }
+
+    /// Append this sub chunk on top of `chunk`, failing if the data is
+    /// malformed
+    fn unpack_into(&self, chunk: &mut TerrainChunk) -> Option<()> {
+        match self {
+            PackedSubChunk::Homogeneous(block) => chunk.push_filled_sub_chunk(*block),
+            PackedSubChunk::Paletted {
+                palette,
+                bits,
+                indices,
+            } => {
+                let fill = *palette.first()?;
+                if !(1..=64).contains(bits) {
+                    return None;
+                }
+                let per_word = 64 / *bits as usize;
+                let mask = u64::MAX >> (64 - *bits as u32);
+                if indices.len() * per_word < SUB_CHUNK_VOLUME {
+                    return None;
+                }
+
+                let min_z = chunk.get_max_z();
+                chunk.push_filled_sub_chunk(fill);
+                for i in 0..SUB_CHUNK_VOLUME {
+                    let idx = (indices[i / per_word] >> ((i % per_word) * *bits as usize)) & mask;
+                    if idx != 0 {
+                        let pos = sub_chunk_pos(i) + Vec3::unit_z() * min_z;
+                        chunk.set(pos, *palette.get(idx as usize)?).ok()?;
+                    }
+                }
+            },
+            PackedSubChunk::RunLength { palette, runs } => {
+                let fill = *palette.first()?;
+                if runs.iter().map(|(_, len)| *len as usize).sum::<usize>() != SUB_CHUNK_VOLUME {
+                    return None;
+                }
+
+                let min_z = chunk.get_max_z();
+                chunk.push_filled_sub_chunk(fill);
+                let mut i = 0;
+                for (idx, len) in runs {
+                    if *idx != 0 {
+                        let block = *palette.get(*idx as usize)?;
+                        for j in i..i + *len as usize {
+                            chunk
+                                .set(sub_chunk_pos(j) + Vec3::unit_z() * min_z, block)
+                                .ok()?;
+                        }
+                    }
+                    i += *len as usize;
+                }
+            },
+        }
+        Some(())
+    }
+}
+
+impl PackedChunk {
+    pub fn pack(chunk: &TerrainChunk) -> Self { Self::pack_with(chunk, ChunkEncoding::LATEST) }
+
+    /// Pack a chunk so that it can be read by clients supporting `encoding`
+    pub fn pack_with(chunk: &TerrainChunk, encoding: ChunkEncoding) -> Self {
+        let z_offset = chunk.get_min_z();
+        Self {
+            z_offset,
+            below: *chunk.below(),
+            above: *chunk.above(),
+            meta: chunk.meta().clone(),
+            sub_chunks: (0..chunk.sub_chunks_len())
+                .map(|i| {
+                    let min_z = z_offset + (i as u32 * SUB_CHUNK_SIZE.z) as i32;
+                    PackedSubChunk::pack(chunk, min_z, encoding)
+                })
+                .collect(),
+        }
+    }
+
+    /// Rebuild the chunk, or `None` if the packed data is malformed.
+    pub fn unpack(&self) -> Option<TerrainChunk> {
+        let mut chunk = TerrainChunk::new(self.z_offset, self.below, self.above, self.meta.clone());
+        for sub_chunk in &self.sub_chunks {
+            sub_chunk.unpack_into(&mut chunk)?;
+        }
+        Some(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        terrain::{BlockKind, SpriteKind},
+        vol::RectVolSize,
+    };
+
+    #[test]
+    fn packed_chunks_round_trip() {
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let air = Block::air(SpriteKind::Empty);
+        let mut chunk = TerrainChunk::new(-20, rock, air, TerrainChunkMeta::void());
+        for x in 0..TerrainChunkSize::RECT_SIZE.x as i32 {
+            for z in -20..(x % 7) * 5 {
+                let color = Rgb::new(x as u8, z as u8, 0);
+                chunk
+                    .set(Vec3::new(x, x / 2, z), Block::new(BlockKind::Grass, color))
+                    .unwrap();
+            }
+        }
+        chunk.set(Vec3::new(1, 2, 60), rock).unwrap();
+
+        let packed = PackedChunk::pack_with(&chunk, ChunkEncoding::Paletted);
+        assert!(matches!(
+            packed.sub_chunks[0],
+            PackedSubChunk::Paletted { .. }
+        ));
+        assert_same_blocks(&packed.unpack().unwrap(), &chunk);
+    }
+
+    #[test]
+    fn layered_chunks_are_run_length_encoded() {
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let air = Block::air(SpriteKind::Empty);
+        let mut chunk = TerrainChunk::new(0, rock, air, TerrainChunkMeta::void());
+        for x in 0..TerrainChunkSize::RECT_SIZE.x as i32 {
+            for y in 0..TerrainChunkSize::RECT_SIZE.y as i32 {
+                for z in 0..4 {
+                    let kind = if z < 3 {
+                        BlockKind::Earth
+                    } else {
+                        BlockKind::Grass
+                    };
+                    chunk
+                        .set(Vec3::new(x, y, z), Block::new(kind, Rgb::zero()))
+                        .unwrap();
+                }
+            }
+        }
+        chunk.set(Vec3::new(5, 6, 1), rock).unwrap();
+
+        let packed = PackedChunk::pack(&chunk);
+        assert!(matches!(
+            packed.sub_chunks[0],
+            PackedSubChunk::RunLength { .. }
+        ));
+        assert_same_blocks(&packed.unpack().unwrap(), &chunk);
+    }
+
+    fn assert_same_blocks(unpacked: &TerrainChunk, chunk: &TerrainChunk) {
+        assert_eq!(unpacked.get_min_z(), chunk.get_min_z());
+        assert_eq!(unpacked.get_max_z(), chunk.get_max_z());
+        for z in chunk.get_min_z() - 2..chunk.get_max_z() + 2 {
+            for y in 0..TerrainChunkSize::RECT_SIZE.y as i32 {
+                for x in 0..TerrainChunkSize::RECT_SIZE.x as i32 {
+                    let pos = Vec3::new(x, y, z);
+                    assert_eq!(unpacked.get(pos).ok(), chunk.get(pos).ok());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Text that players write on signs placed in the world. The text is kept in
+//! the metadata of the chunk the sign is in, so it travels with the chunk.
+
+use super::{Block, SpriteKind};
+
+/// Most characters a sign can hold
+pub const MAX_SIGN_CHARS: usize = 120;
+/// Most lines of text a sign can hold
+pub const MAX_SIGN_LINES: usize = 4;
+/// Distance in blocks within which players can edit a sign
+pub const MAX_SIGN_EDIT_RANGE: f32 = 6.0;
+
+#[derive(Debug, PartialEq)]
+pub enum SignTextError {
+    TooLong,
+    TooManyLines,
+    InvalidCharacter,
+}
+
+/// Whether text can be written on the block
+pub fn is_sign(block: &Block) -> bool { block.get_sprite() == Some(SpriteKind::HangingSign) }
+
+/// Checks that text fits on a sign and only contains printable characters
+pub fn validate_sign_text(text: &str) -> Result<(), SignTextError> {
+    if text.chars().count() > MAX_SIGN_CHARS {
+        Err(SignTextError::TooLong)
+    } else if text.lines().count() > MAX_SIGN_LINES {
+        Err(SignTextError::TooManyLines)
+    } else if text.chars().any(|c| c.is_control() && c != '\n') {
+        Err(SignTextError::InvalidCharacter)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_text_is_validated() {
+        assert_eq!(validate_sign_text("Welcome to\nthe village"), Ok(()));
+        assert_eq!(
+            validate_sign_text(&"a".repeat(MAX_SIGN_CHARS + 1)),
+            Err(SignTextError::TooLong)
+        );
+        assert_eq!(
+            validate_sign_text("1\n2\n3\n4\n5"),
+            Err(SignTextError::TooManyLines)
+        );
+        assert_eq!(
+            validate_sign_text("bell\u{7}"),
+            Err(SignTextError::InvalidCharacter)
+        );
+    }
+}
@@ -1,5 +1,6 @@
-use crate::vol::{
-    BaseVol, IntoPosIterator, IntoVolIterator, RasterableVol, ReadVol, VolSize, WriteVol,
+use crate::{
+    memory::MemoryUsage,
+    vol::{BaseVol, IntoPosIterator, IntoVolIterator, RasterableVol, ReadVol, VolSize, WriteVol},
 };
 use core::{hash::Hash, iter::Iterator, marker::PhantomData, mem};
 use hashbrown::HashMap;
@@ -322,6 +323,12 @@ impl<V: Clone + PartialEq, S: VolSize, M> WriteVol for Chunk<V, S, M> {
     }
 }
 
+impl<V, S: VolSize, M> MemoryUsage for Chunk<V, S, M> {
+    fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.indices.capacity() + self.vox.capacity() * mem::size_of::<V>()
+    }
+}
+
 pub struct ChunkPosIter<V, S: VolSize, M> {
     // Store as `u8`s so as to reduce memory footprint.
     lb: Vec3<i32>,
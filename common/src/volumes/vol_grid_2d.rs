@@ -1,4 +1,5 @@
 use crate::{
+    memory::MemoryUsage,
     vol::{BaseVol, ReadVol, RectRasterableVol, SampleVol, WriteVol},
     volumes::dyna::DynaError,
 };
@@ -152,6 +153,16 @@ impl<V: RectRasterableVol> VolGrid2d<V> {
     pub fn cached<'a>(&'a self) -> CachedVolGrid2d<'a, V> { CachedVolGrid2d::new(self) }
 }
 
+impl<V: RectRasterableVol + MemoryUsage> MemoryUsage for VolGrid2d<V> {
+    /// Chunks shared with other grids are counted in full
+    fn memory_usage(&self) -> usize {
+        self.chunks
+            .values()
+            .map(|chunk| chunk.memory_usage() + std::mem::size_of::<(Vec2<i32>, Arc<V>)>())
+            .sum()
+    }
+}
+
 pub struct CachedVolGrid2d<'a, V: RectRasterableVol> {
     vol_grid_2d: &'a VolGrid2d<V>,
     // This can't be invalidated by mutations of the chunks hashmap since we hold an immutable
@@ -0,0 +1,234 @@
+//! Region based weather, simulated identically on the server and on clients.
+//!
+//! The weather of every cell is a smooth function of the world seed, the cell
+//! position and the weather time, so keeping clients in sync only requires
+//! occasionally sending them the (tiny) `Weather` resource.
+
+use crate::{terrain::TerrainChunkSize, vol::RectVolSize};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::PI;
+use vek::*;
+
+/// Width of a weather cell in chunks
+pub const CELL_SIZE: u32 = 16;
+/// How often the cells are resimulated, in seconds
+const UPDATE_INTERVAL: f64 = 1.0;
+/// Time over which a weather front builds up and fades, in seconds
+const FRONT_PERIOD: f64 = 20.0 * 60.0;
+/// Width of a weather front in cells
+const FRONT_SCALE: f32 = 5.0;
+/// Time over which the prevailing wind turns around, in seconds
+const WIND_PERIOD: f64 = 2.0 * 60.0 * 60.0;
+/// Speed at which weather fronts drift along with the prevailing wind, in
+/// cells per second
+const FRONT_DRIFT_SPEED: f32 = 0.002;
+/// Wind speed in calm weather, in blocks per second
+const BASE_WIND_SPEED: f32 = 4.0;
+/// Additional wind speed caused by gusts and storms, in blocks per second
+const MAX_EXTRA_WIND_SPEED: f32 = 26.0;
+/// Wind speed above which heavy rain counts as a storm
+const STORM_WIND_SPEED: f32 = 18.0;
+
+/// The weather at a single point of the world.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeatherCell {
+    /// Cloud cover, between 0 and 1
+    pub cloud: f32,
+    /// Rain intensity, between 0 and 1
+    pub rain: f32,
+    /// Wind velocity, in blocks per second
+    pub wind: Vec2<f32>,
+}
+
+impl WeatherCell {
+    pub fn is_raining(&self) -> bool { self.rain > 0.1 }
+
+    pub fn is_storm(&self) -> bool {
+        self.rain > 0.6 && self.wind.magnitude_squared() > STORM_WIND_SPEED.powi(2)
+    }
+
+    fn lerp(a: Self, b: Self, t: f32) -> Self {
+        Self {
+            cloud: Lerp::lerp(a.cloud, b.cloud, t),
+            rain: Lerp::lerp(a.rain, b.rain, t),
+            wind: Lerp::lerp(a.wind, b.wind, t),
+        }
+    }
+}
+
+/// A resource that stores the weather of the world.
+///
+/// Only the parameters of the simulation are serialized, the cells are
+/// recomputed from them locally.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Weather {
+    seed: u32,
+    /// Size of the world, in cells
+    size: Vec2<u32>,
+    /// Time the weather has been simulated for, in seconds
+    time: f64,
+    #[serde(skip)]
+    cells: Vec<WeatherCell>,
+    #[serde(skip)]
+    last_update: f64,
+}
+
+impl Weather {
+    /// Create the weather for a world of `map_size` chunks.
+    pub fn new(map_size: Vec2<u32>, seed: u32) -> Self {
+        let mut weather = Self {
+            seed,
+            size: map_size.map(|e| (e + CELL_SIZE - 1) / CELL_SIZE),
+            ..Self::default()
+        };
+        weather.update_cells();
+        weather
+    }
+
+    /// Advance the weather by `dt` seconds.
+    pub fn tick(&mut self, dt: f64) {
+        self.time += dt;
+        if self.cells.is_empty() || self.time - self.last_update >= UPDATE_INTERVAL {
+            self.update_cells();
+        }
+    }
+
+    pub fn time(&self) -> f64 { self.time }
+
+    /// Change the weather time, e.g. to skip ahead to a different weather.
+    pub fn set_time(&mut self, time: f64) {
+        self.time = time;
+        self.update_cells();
+    }
+
+    /// The weather at the given world position, interpolated between the
+    /// surrounding cells. Outside of the world the weather is always clear.
+    pub fn get(&self, wpos: Vec2<f32>) -> WeatherCell {
+        let cell_blocks = (CELL_SIZE * TerrainChunkSize::RECT_SIZE.x) as f32;
+        // Position relative to the cell centres
+        let cpos = wpos / cell_blocks - 0.5;
+        let base = cpos.map(|e| e.floor() as i32);
+        let frac = cpos - base.map(|e| e as f32);
+
+        let cell = |offs: Vec2<i32>| self.cell(base + offs).unwrap_or_default();
+        WeatherCell::lerp(
+            WeatherCell::lerp(cell(Vec2::new(0, 0)), cell(Vec2::new(1, 0)), frac.x),
+            WeatherCell::lerp(cell(Vec2::new(0, 1)), cell(Vec2::new(1, 1)), frac.x),
+            frac.y,
+        )
+    }
+
+    pub fn rain_at(&self, wpos: Vec2<f32>) -> f32 { self.get(wpos).rain }
+
+    pub fn wind_at(&self, wpos: Vec2<f32>) -> Vec2<f32> { self.get(wpos).wind }
+
+    pub fn is_raining_at(&self, wpos: Vec2<f32>) -> bool { self.get(wpos).is_raining() }
+
+    fn cell(&self, cell: Vec2<i32>) -> Option<WeatherCell> {
+        if cell.x < 0 || cell.y < 0 || cell.x as u32 >= self.size.x || cell.y as u32 >= self.size.y
+        {
+            return None;
+        }
+        self.cells
+            .get(cell.y as usize * self.size.x as usize + cell.x as usize)
+            .copied()
+    }
+
+    fn update_cells(&mut self) {
+        let size = self.size;
+        self.cells = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| Vec2::new(x, y)))
+            .map(|cell| self.simulate_cell(cell.map(|e| e as f32)))
+            .collect();
+        self.last_update = self.time;
+    }
+
+    fn simulate_cell(&self, cell: Vec2<f32>) -> WeatherCell {
+        let wind_angle = value_noise(
+            self.seed,
+            Vec3::new(0.0, 0.0, (self.time / WIND_PERIOD) as f32),
+        ) * 4.0
+            * PI;
+        let wind_dir = Vec2::new(wind_angle.cos(), wind_angle.sin());
+
+        // Weather fronts drift along with the prevailing wind
+        let drifted = cell - wind_dir * FRONT_DRIFT_SPEED * self.time as f32;
+        let front_time = (self.time / FRONT_PERIOD) as f32;
+        let humidity = value_noise(
+            self.seed,
+            Vec3::new(drifted.x / FRONT_SCALE, drifted.y / FRONT_SCALE, front_time),
+        );
+        let gust = value_noise(
+            self.seed.wrapping_add(1),
+            Vec3::new(cell.x, cell.y, front_time * 8.0),
+        );
+
+        let cloud = ((humidity - 0.3) / 0.4).max(0.0).min(1.0);
+        let rain = ((humidity - 0.6) / 0.25).max(0.0).min(1.0);
+        let wind_speed = BASE_WIND_SPEED + MAX_EXTRA_WIND_SPEED * (gust * 0.4 + rain * 0.6);
+
+        WeatherCell {
+            cloud,
+            rain,
+            wind: wind_dir * wind_speed,
+        }
+    }
+}
+
+fn hash(seed: u32, pos: Vec3<i32>) -> f32 {
+    let mut h = seed
+        ^ (pos.x as u32).wrapping_mul(0x8DA6_B343)
+        ^ (pos.y as u32).wrapping_mul(0xD816_3841)
+        ^ (pos.z as u32).wrapping_mul(0xCB1A_B31F);
+    h = (h ^ (h >> 16)).wrapping_mul(0x7FEB_352D);
+    h = (h ^ (h >> 15)).wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+    h as f32 / u32::MAX as f32
+}
+
+/// Smoothly interpolated value noise in the range [0, 1]
+fn value_noise(seed: u32, pos: Vec3<f32>) -> f32 {
+    let base = pos.map(|e| e.floor() as i32);
+    // Smoothstep the fractional part to avoid visible creases
+    let t = (pos - base.map(|e| e as f32)).map(|e| e * e * (3.0 - 2.0 * e));
+
+    let corner = |x, y, z| hash(seed, base + Vec3::new(x, y, z));
+    let lerp_x = |y, z| Lerp::lerp(corner(0, y, z), corner(1, y, z), t.x);
+    let lerp_y = |z| Lerp::lerp(lerp_x(0, z), lerp_x(1, z), t.y);
+    Lerp::lerp(lerp_y(0), lerp_y(1), t.z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weather_is_deterministic() {
+        let mut a = Weather::new(Vec2::new(64, 64), 1337);
+        let mut b = Weather::new(Vec2::new(64, 64), 1337);
+        a.tick(500.0);
+        b.set_time(500.0);
+
+        let wpos = Vec2::new(700.0, 900.0);
+        assert_eq!(a.get(wpos), b.get(wpos));
+    }
+
+    #[test]
+    fn outside_of_world_is_clear() {
+        let weather = Weather::new(Vec2::new(64, 64), 1337);
+        assert_eq!(
+            weather.get(Vec2::new(-100_000.0, 50.0)),
+            WeatherCell::default()
+        );
+    }
+
+    #[test]
+    fn values_are_in_range() {
+        let weather = Weather::new(Vec2::new(64, 64), 42);
+        for cell in &weather.cells {
+            assert!((0.0..=1.0).contains(&cell.cloud));
+            assert!((0.0..=1.0).contains(&cell.rain));
+            assert!(cell.wind.magnitude() <= BASE_WIND_SPEED + MAX_EXTRA_WIND_SPEED + 0.01);
+        }
+    }
+}
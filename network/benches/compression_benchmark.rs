@@ -0,0 +1,76 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures::executor::block_on;
+use veloren_network::{Compression, Network, Participant, Pid, Promises, ProtocolAddr, Stream};
+
+/// Message sizes: below the compression threshold, a typical game message and
+/// a terrain chunk
+const SIZES: [usize; 3] = [32, 1024, 64 * 1024];
+
+/// Keeps both networks alive as long as the streams are used
+struct Connection {
+    _networks: (Network, Network),
+    _participants: (Participant, Participant),
+    sender: Stream,
+    receiver: Stream,
+}
+
+fn connect(port: u16, promises: Promises, compression: Compression) -> Connection {
+    let (n_a, f_a) = Network::new(Pid::fake(0));
+    std::thread::spawn(f_a);
+    let (n_b, f_b) = Network::new(Pid::fake(1));
+    std::thread::spawn(f_b);
+    let addr = ProtocolAddr::Tcp(([127, 0, 0, 1], port).into());
+    block_on(async {
+        n_a.listen(addr.clone()).await.unwrap();
+        let p_b = n_b.connect(addr).await.unwrap();
+        let p_a = n_a.connected().await.unwrap();
+        let mut sender = p_a.open(10, promises).await.unwrap();
+        sender.set_compression(compression);
+        let receiver = p_b.opened().await.unwrap();
+        Connection {
+            _networks: (n_a, n_b),
+            _participants: (p_a, p_b),
+            sender,
+            receiver,
+        }
+    })
+}
+
+/// Runs of similar bytes with some noise, comparable to terrain data
+fn message(size: usize) -> Vec<u8> {
+    (0..size)
+        .map(|i| ((i / 64) % 7) as u8 ^ if i % 13 == 0 { (i % 251) as u8 } else { 0 })
+        .collect()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let reliable = Promises::ORDERED | Promises::CONSISTENCY;
+    let cases = [
+        ("uncompressed", reliable, Compression::Lz4),
+        ("lz4", reliable | Promises::COMPRESSED, Compression::Lz4),
+        (
+            "deflate",
+            reliable | Promises::COMPRESSED,
+            Compression::Deflate,
+        ),
+    ];
+
+    let mut group = c.benchmark_group("send and recv");
+    for (i, (name, promises, compression)) in cases.iter().enumerate() {
+        let mut connection = connect(2700 + i as u16, *promises, *compression);
+        for &size in &SIZES {
+            let msg = message(size);
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::new(*name, size), &msg, |b, msg| {
+                b.iter(|| {
+                    connection.sender.send(msg).unwrap();
+                    block_on(connection.receiver.recv::<Vec<u8>>()).unwrap()
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
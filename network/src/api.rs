@@ -2,11 +2,14 @@
 //!
 //!
 //! (cd network/examples/async_recv && RUST_BACKTRACE=1 cargo run)
+#[cfg(feature = "compression")]
+use crate::types::Compression;
 use crate::{
+    encryption::StreamCipher,
     message::{partial_eq_bincode, IncomingMessage, Message, OutgoingMessage},
     participant::{A2bStreamOpen, S2bShutdownBparticipant},
-    scheduler::Scheduler,
-    types::{Mid, Pid, Prio, Promises, Sid},
+    scheduler::{A2sConnect, ConnectRequester, Scheduler},
+    types::{Mid, Pid, Prio, Promises, Sid, REALTIME_MESSAGE_LIFETIME},
 };
 use async_std::{io, sync::Mutex, task};
 use futures::{
@@ -23,9 +26,10 @@ use std::{
     collections::HashMap,
     net::SocketAddr,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
+    time::Instant,
 };
 use tracing::*;
 use tracing_futures::Instrument;
@@ -37,6 +41,10 @@ type A2sDisconnect = Arc<Mutex<Option<mpsc::UnboundedSender<(Pid, S2bShutdownBpa
 pub enum ProtocolAddr {
     Tcp(SocketAddr),
     Udp(SocketAddr),
+    /// In-process connection to a [`Network`] of this process, with simulated
+    /// network conditions, see [`sim`]
+    ///
+    /// [`sim`]: crate::sim
     Mpsc(u64),
 }
 
@@ -52,6 +60,7 @@ pub struct Participant {
     remote_pid: Pid,
     a2b_stream_open_s: Mutex<mpsc::UnboundedSender<A2bStreamOpen>>,
     b2a_stream_opened_r: Mutex<mpsc::UnboundedReceiver<Stream>>,
+    a2s_connect_s: Mutex<mpsc::UnboundedSender<A2sConnect>>,
     a2s_disconnect_s: A2sDisconnect,
 }
 
@@ -75,7 +84,12 @@ pub struct Stream {
     mid: Mid,
     prio: Prio,
     promises: Promises,
+    #[cfg(feature = "compression")]
+    compression: Compression,
+    cipher: Option<Arc<StreamCipher>>,
     send_closed: Arc<AtomicBool>,
+    queued: Arc<AtomicU64>,
+    queued_bytes: Arc<AtomicU64>,
     a2b_msg_s: crossbeam_channel::Sender<(Prio, Sid, OutgoingMessage)>,
     b2a_msg_recv_r: Option<mpsc::UnboundedReceiver<IncomingMessage>>,
     a2b_close_stream_s: Option<mpsc::UnboundedSender<Sid>>,
@@ -109,10 +123,25 @@ pub enum ParticipantError {
 pub enum StreamError {
     StreamClosed,
     #[cfg(feature = "compression")]
-    Compression(DecodeError),
+    Compression(CompressionError),
     Deserialize(bincode::Error),
 }
 
+/// Why a message on a [`Stream`] with [`Promises::COMPRESSED`] couldn't be
+/// decompressed
+#[cfg(feature = "compression")]
+#[derive(Debug, PartialEq)]
+pub enum CompressionError {
+    /// The message is missing the header naming its [`Compression`]
+    MissingHeader,
+    /// The header names a [`Compression`] this version doesn't know
+    UnknownCompression(u8),
+    Lz4(DecodeError),
+    Deflate,
+    /// The message would decompress to more than a message may be
+    TooLarge,
+}
+
 /// Use the `Network` to create connections to other [`Participants`]
 ///
 /// The `Network` is the single source that handles all connections in your
@@ -153,8 +182,7 @@ pub struct Network {
     participant_disconnect_sender: Mutex<HashMap<Pid, A2sDisconnect>>,
     listen_sender:
         Mutex<mpsc::UnboundedSender<(ProtocolAddr, oneshot::Sender<async_std::io::Result<()>>)>>,
-    connect_sender:
-        Mutex<mpsc::UnboundedSender<(ProtocolAddr, oneshot::Sender<io::Result<Participant>>)>>,
+    connect_sender: Mutex<mpsc::UnboundedSender<A2sConnect>>,
     connected_receiver: Mutex<mpsc::UnboundedReceiver<Participant>>,
     shutdown_sender: Option<oneshot::Sender<()>>,
 }
@@ -335,33 +363,26 @@ impl Network {
     ///     let p1 = network
     ///         .connect(ProtocolAddr::Tcp("127.0.0.1:2010".parse().unwrap()))
     ///         .await?;
-    ///     # //this doesn't work yet, so skip the test
-    ///     # //TODO fixme!
-    ///     # return Ok(());
-    ///     let p2 = network
-    ///         .connect(ProtocolAddr::Udp("127.0.0.1:2011".parse().unwrap()))
+    ///     p1.add_channel(ProtocolAddr::Udp("127.0.0.1:2011".parse().unwrap()))
     ///         .await?;
-    ///     assert_eq!(&p1, &p2);
     ///     # Ok(())
     /// })
     /// # }
     /// ```
     /// Usually the `Network` guarantees that a operation on a [`Participant`]
     /// succeeds, e.g. by automatic retrying unless it fails completely e.g. by
-    /// disconnecting from the remote. If 2 [`ProtocolAddres`] you `connect` to
-    /// belongs to the same [`Participant`], you get the same [`Participant`] as
-    /// a result. This is useful e.g. by connecting to the same
-    /// [`Participant`] via multiple Protocols.
+    /// disconnecting from the remote. Connecting to a [`ProtocolAddr`] that
+    /// belongs to a [`Participant`] you are already connected to fails, use
+    /// [`Participant::add_channel`] to connect to it via multiple Protocols.
     ///
     /// [`Streams`]: crate::api::Stream
-    /// [`ProtocolAddres`]: crate::api::ProtocolAddr
     pub async fn connect(&self, address: ProtocolAddr) -> Result<Participant, NetworkError> {
         let (pid_sender, pid_receiver) = oneshot::channel::<io::Result<Participant>>();
         debug!(?address, "Connect to address");
         self.connect_sender
             .lock()
             .await
-            .send((address, pid_sender))
+            .send((address, ConnectRequester::Network(pid_sender)))
             .await?;
         let participant = match pid_receiver.await? {
             Ok(p) => p,
@@ -428,6 +449,7 @@ impl Participant {
         remote_pid: Pid,
         a2b_stream_open_s: mpsc::UnboundedSender<A2bStreamOpen>,
         b2a_stream_opened_r: mpsc::UnboundedReceiver<Stream>,
+        a2s_connect_s: mpsc::UnboundedSender<A2sConnect>,
         a2s_disconnect_s: mpsc::UnboundedSender<(Pid, S2bShutdownBparticipant)>,
     ) -> Self {
         Self {
@@ -435,6 +457,7 @@ impl Participant {
             remote_pid,
             a2b_stream_open_s: Mutex::new(a2b_stream_open_s),
             b2a_stream_opened_r: Mutex::new(b2a_stream_opened_r),
+            a2s_connect_s: Mutex::new(a2s_connect_s),
             a2s_disconnect_s: Arc::new(Mutex::new(Some(a2s_disconnect_s))),
         }
     }
@@ -553,6 +576,70 @@ impl Participant {
         }
     }
 
+    /// Connects to another [`ProtocolAddr`] of the remote side and adds the
+    /// new channel to this `Participant`.
+    ///
+    /// Every [`Stream`] sends its messages over the channel that suits its
+    /// [`Promises`] best: [`Streams`] without [`ORDERED`] or
+    /// [`GUARANTEED_DELIVERY`] prefer the lower latency of UDP, all others
+    /// only ever use reliable protocols like TCP. When a channel fails, the
+    /// `Participant` stays connected and its [`Streams`] move to the
+    /// remaining channels. Frames that were sent on the failed channel are
+    /// lost, so [`Streams`] with [`ORDERED`] or [`GUARANTEED_DELIVERY`] that
+    /// were using it are closed instead, and return
+    /// [`StreamError::StreamClosed`] from then on. The `Participant` closes
+    /// once its last reliable channel is gone.
+    ///
+    /// A [`NetworkError`] is returned if the address can't be reached or
+    /// belongs to another `Participant`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use futures::executor::block_on;
+    /// use veloren_network::{Network, Pid, Promises, ProtocolAddr};
+    ///
+    /// # fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    /// // Create a Network, connect on port 2210 TCP and add port 2211 UDP
+    /// let (network, f) = Network::new(Pid::new());
+    /// std::thread::spawn(f);
+    /// # let (remote, fr) = Network::new(Pid::new());
+    /// # std::thread::spawn(fr);
+    /// block_on(async {
+    ///     # remote.listen(ProtocolAddr::Tcp("0.0.0.0:2210".parse().unwrap())).await?;
+    ///     # remote.listen(ProtocolAddr::Udp("0.0.0.0:2211".parse().unwrap())).await?;
+    ///     let p1 = network
+    ///         .connect(ProtocolAddr::Tcp("127.0.0.1:2210".parse().unwrap()))
+    ///         .await?;
+    ///     p1.add_channel(ProtocolAddr::Udp("127.0.0.1:2211".parse().unwrap()))
+    ///         .await?;
+    ///     // sent via UDP
+    ///     let _inputs = p1.open(16, Promises::empty()).await?;
+    ///     // sent via TCP
+    ///     let _terrain = p1
+    ///         .open(32, Promises::ORDERED | Promises::CONSISTENCY)
+    ///         .await?;
+    ///     # Ok(())
+    /// })
+    /// # }
+    /// ```
+    ///
+    /// [`Streams`]: crate::api::Stream
+    /// [`ORDERED`]: Promises::ORDERED
+    /// [`GUARANTEED_DELIVERY`]: Promises::GUARANTEED_DELIVERY
+    pub async fn add_channel(&self, address: ProtocolAddr) -> Result<(), NetworkError> {
+        let (s2a_result_s, s2a_result_r) = oneshot::channel::<io::Result<()>>();
+        debug!(?address, ?self.remote_pid, "Add channel to participant");
+        self.a2s_connect_s
+            .lock()
+            .await
+            .send((
+                address,
+                ConnectRequester::Participant(self.remote_pid, s2a_result_s),
+            ))
+            .await?;
+        s2a_result_r.await?.map_err(NetworkError::ConnectFailed)
+    }
+
     /// disconnecting a `Participant` in a async way.
     /// Use this rather than `Participant::Drop` if you want to close multiple
     /// `Participants`.
@@ -654,6 +741,7 @@ impl Stream {
         sid: Sid,
         prio: Prio,
         promises: Promises,
+        cipher: Option<Arc<StreamCipher>>,
         send_closed: Arc<AtomicBool>,
         a2b_msg_s: crossbeam_channel::Sender<(Prio, Sid, OutgoingMessage)>,
         b2a_msg_recv_r: mpsc::UnboundedReceiver<IncomingMessage>,
@@ -665,7 +753,12 @@ impl Stream {
             mid: 0,
             prio,
             promises,
+            #[cfg(feature = "compression")]
+            compression: Compression::default(),
+            cipher,
             send_closed,
+            queued: Arc::new(AtomicU64::new(0)),
+            queued_bytes: Arc::new(AtomicU64::new(0)),
             a2b_msg_s,
             b2a_msg_recv_r: Some(b2a_msg_recv_r),
             a2b_close_stream_s: Some(a2b_close_stream_s),
@@ -783,16 +876,41 @@ impl Stream {
         }
         #[cfg(debug_assertions)]
         message.verify(&self);
+        // Count the message before handing it over, so that it can't be
+        // subtracted again before it was added
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        self.queued_bytes
+            .fetch_add(message.buffer.data.len() as u64, Ordering::Relaxed);
         self.a2b_msg_s.send((self.prio, self.sid, OutgoingMessage {
             buffer: Arc::clone(&message.buffer),
             cursor: 0,
             mid: self.mid,
             sid: self.sid,
+            uncompressed_len: message.uncompressed_len,
+            cipher: self.cipher.clone(),
+            queued: Arc::clone(&self.queued),
+            queued_bytes: Arc::clone(&self.queued_bytes),
+            expires: if self.promises.contains(Promises::REALTIME) {
+                Some(Instant::now() + REALTIME_MESSAGE_LIFETIME)
+            } else {
+                None
+            },
         }))?;
         self.mid += 1;
         Ok(())
     }
 
+    /// Returns the number of messages sent on this `Stream` that haven't been
+    /// fully handed to a channel yet. A growing number means the remote side
+    /// or the connection can't keep up, which applications can use to drop
+    /// or throttle messages.
+    pub fn queued_messages(&self) -> u64 { self.queued.load(Ordering::Relaxed) }
+
+    /// Returns the size in bytes of the messages counted by
+    /// [`queued_messages`](Stream::queued_messages), which is the memory they
+    /// keep buffered
+    pub fn queued_bytes(&self) -> u64 { self.queued_bytes.load(Ordering::Relaxed) }
+
     /// use `recv` to wait on a Message send from the remote side by their
     /// `Stream`. The Message needs to implement [`DeserializeOwned`] and
     /// thus, the resulting type must already be known by the receiving side.
@@ -871,6 +989,7 @@ impl Stream {
             Some(b2a_msg_recv_r) => {
                 match b2a_msg_recv_r.next().await {
                     Some(msg) => Ok(Message {
+                        uncompressed_len: msg.buffer.data.len() as u64,
                         buffer: Arc::new(msg.buffer),
                         #[cfg(feature = "compression")]
                         compressed: self.promises.contains(Promises::COMPRESSED),
@@ -929,6 +1048,7 @@ impl Stream {
                 },
                 Ok(Some(msg)) => Ok(Some(
                     Message {
+                        uncompressed_len: msg.buffer.data.len() as u64,
                         buffer: Arc::new(msg.buffer),
                         #[cfg(feature = "compression")]
                         compressed: self.promises().contains(Promises::COMPRESSED),
@@ -941,6 +1061,17 @@ impl Stream {
     }
 
     pub fn promises(&self) -> Promises { self.promises }
+
+    /// Algorithm messages are compressed with, if this `Stream` has
+    /// [`Promises::COMPRESSED`]
+    #[cfg(feature = "compression")]
+    pub fn compression(&self) -> Compression { self.compression }
+
+    /// Changes the algorithm messages sent from now on are compressed with.
+    /// The remote side doesn't need to be told, and keeps its own choice for
+    /// the messages it sends.
+    #[cfg(feature = "compression")]
+    pub fn set_compression(&mut self, compression: Compression) { self.compression = compression; }
 }
 
 impl core::cmp::PartialEq for Participant {
@@ -1111,6 +1242,19 @@ impl core::fmt::Display for StreamError {
     }
 }
 
+#[cfg(feature = "compression")]
+impl core::fmt::Display for CompressionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CompressionError::MissingHeader => write!(f, "missing compression header"),
+            CompressionError::UnknownCompression(id) => write!(f, "unknown compression: {}", id),
+            CompressionError::Lz4(err) => write!(f, "lz4: {}", err),
+            CompressionError::Deflate => write!(f, "invalid deflate data"),
+            CompressionError::TooLarge => write!(f, "decompressed message too large"),
+        }
+    }
+}
+
 impl core::fmt::Display for ParticipantError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
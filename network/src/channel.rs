@@ -1,11 +1,12 @@
 #[cfg(feature = "metrics")]
 use crate::metrics::NetworkMetrics;
 use crate::{
+    encryption::{KeyExchange, SessionKey},
     participant::C2pFrame,
     protocols::Protocols,
     types::{
-        Cid, Frame, Pid, Sid, STREAM_ID_OFFSET1, STREAM_ID_OFFSET2, VELOREN_MAGIC_NUMBER,
-        VELOREN_NETWORK_VERSION,
+        is_compatible_version, Cid, Frame, Pid, Sid, STREAM_ID_OFFSET1, STREAM_ID_OFFSET2,
+        VELOREN_MAGIC_NUMBER, VELOREN_NETWORK_VERSION,
     },
 };
 use futures::{
@@ -70,6 +71,12 @@ impl Channel {
                     udp.write_to_wire(self.cid, c2w_frame_r),
                 );
             },
+            Protocols::Mpsc(mpsc) => {
+                join!(
+                    mpsc.read_from_wire(self.cid, &mut w2c_cid_frame_s, read_stop_receiver),
+                    mpsc.write_to_wire(self.cid, c2w_frame_r),
+                );
+            },
         }
 
         trace!("Shut down channel");
@@ -81,6 +88,7 @@ pub(crate) struct Handshake {
     cid: Cid,
     local_pid: Pid,
     secret: u128,
+    key_exchange: KeyExchange,
     init_handshake: bool,
     #[cfg(feature = "metrics")]
     metrics: Arc<NetworkMetrics>,
@@ -108,13 +116,17 @@ impl Handshake {
             cid,
             local_pid,
             secret,
+            key_exchange: KeyExchange::new(),
             #[cfg(feature = "metrics")]
             metrics,
             init_handshake,
         }
     }
 
-    pub async fn setup(self, protocol: &Protocols) -> Result<(Pid, Sid, u128, Vec<C2pFrame>), ()> {
+    pub async fn setup(
+        self,
+        protocol: &Protocols,
+    ) -> Result<(Pid, Sid, u128, SessionKey, Vec<C2pFrame>), ()> {
         let (c2w_frame_s, c2w_frame_r) = mpsc::unbounded::<Frame>();
         let (mut w2c_cid_frame_s, mut w2c_cid_frame_r) = mpsc::unbounded::<C2pFrame>();
 
@@ -138,6 +150,14 @@ impl Handshake {
                 })
                 .2
             },
+            Protocols::Mpsc(mpsc) => {
+                (join! {
+                    mpsc.read_from_wire(self.cid, &mut w2c_cid_frame_s, read_stop_receiver),
+                    mpsc.write_to_wire(self.cid, c2w_frame_r),
+                    handler_future,
+                })
+                .2
+            },
         };
 
         match res {
@@ -154,7 +174,7 @@ impl Handshake {
                          bparticipant as leftover_frames"
                     );
                 }
-                Ok((res.0, res.1, res.2, leftover_frames))
+                Ok((res.0, res.1, res.2, res.3, leftover_frames))
             },
             Err(()) => Err(()),
         }
@@ -165,7 +185,7 @@ impl Handshake {
         w2c_cid_frame_r: &mut mpsc::UnboundedReceiver<C2pFrame>,
         mut c2w_frame_s: mpsc::UnboundedSender<Frame>,
         read_stop_sender: oneshot::Sender<()>,
-    ) -> Result<(Pid, Sid, u128), ()> {
+    ) -> Result<(Pid, Sid, u128, SessionKey), ()> {
         const ERR_S: &str = "Got A Raw Message, these are usually Debug Messages indicating that \
                              something went wrong on network layer and connection will be closed";
         #[cfg(feature = "metrics")]
@@ -197,8 +217,8 @@ impl Handshake {
                     self.send_raw_and_shutdown(&mut c2w_frame_s, Self::WRONG_NUMBER.to_vec())
                         .await;
                     Err(())
-                } else if version != VELOREN_NETWORK_VERSION {
-                    error!(?version, "Connection with wrong network version");
+                } else if !is_compatible_version(version) {
+                    error!(?version, "Connection with incompatible network version");
                     #[cfg(debug_assertions)]
                     self.send_raw_and_shutdown(
                         &mut c2w_frame_s,
@@ -214,6 +234,12 @@ impl Handshake {
                     .await;
                     Err(())
                 } else {
+                    if version != VELOREN_NETWORK_VERSION {
+                        info!(
+                            ?version,
+                            "Connection with different but compatible network version"
+                        );
+                    }
                     debug!("Handshake completed");
                     if self.init_handshake {
                         self.send_init(&mut c2w_frame_s).await;
@@ -256,7 +282,11 @@ impl Handshake {
 
         let frame = w2c_cid_frame_r.next().await.map(|(_cid, frame)| frame);
         let r = match frame {
-            Some(Ok(Frame::Init { pid, secret })) => {
+            Some(Ok(Frame::Init {
+                pid,
+                secret,
+                public_key,
+            })) => {
                 debug!(?pid, "Participant send their ID");
                 #[cfg(feature = "metrics")]
                 self.metrics
@@ -269,8 +299,9 @@ impl Handshake {
                     self.send_init(&mut c2w_frame_s).await;
                     STREAM_ID_OFFSET2
                 };
+                let session_key = self.key_exchange.finish(public_key, self.init_handshake);
                 info!(?pid, "This Handshake is now configured!");
-                Ok((pid, stream_id_offset, secret))
+                Ok((pid, stream_id_offset, secret, session_key))
             },
             Some(Ok(frame)) => {
                 #[cfg(feature = "metrics")]
@@ -329,6 +360,7 @@ impl Handshake {
             .send(Frame::Init {
                 pid: self.local_pid,
                 secret: self.secret,
+                public_key: self.key_exchange.public_key(),
             })
             .await
             .unwrap();
@@ -0,0 +1,212 @@
+//! Encryption of [`Streams`] with the [`ENCRYPTED`] promise. This is
+//! experimental and not used by any stream yet.
+//!
+//! Both sides of a channel exchange an x25519 public key in their
+//! `Frame::Init`, which gives the [`Participant`] a shared [`SessionKey`].
+//! The public keys are generated for every handshake and not signed, so
+//! nothing proves that they come from the remote side. This protects against
+//! passive eavesdroppers only: a man in the middle can run a key exchange with
+//! each side and read and alter everything in between.
+//! Every encrypted [`Stream`] derives its own key per direction from it, so a
+//! nonce only has to be unique within one direction of one stream. That is
+//! the case for the `(mid, start)` pair of a `Frame::Data`, as message ids
+//! count up per stream and `start` differs for every frame of a message. The
+//! payload of each `Frame::Data` is sealed with XChaCha20-Poly1305, so frames
+//! that were altered, replayed on another stream or moved within a stream
+//! fail to decrypt.
+//!
+//! [`Streams`]: crate::api::Stream
+//! [`Stream`]: crate::api::Stream
+//! [`ENCRYPTED`]: crate::types::Promises::ENCRYPTED
+//! [`Participant`]: crate::api::Participant
+use crate::types::{Mid, Sid};
+use chacha20poly1305::{
+    aead::{Aead, NewAead},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+pub(crate) const PUBLIC_KEY_LEN: usize = 32;
+
+/// Our half of the key exchange, generated freshly for every handshake
+pub(crate) struct KeyExchange {
+    secret: StaticSecret,
+}
+
+/// Key shared by both sides of a [`Participant`], used to derive the keys of
+/// its encrypted [`Streams`]
+///
+/// [`Participant`]: crate::api::Participant
+/// [`Streams`]: crate::api::Stream
+#[derive(Clone)]
+pub(crate) struct SessionKey {
+    hkdf: Hkdf<Sha256>,
+    /// Whether we initiated the handshake, which decides the key we send with
+    initiator: bool,
+}
+
+/// Seals and opens the `Frame::Data` payloads of a single encrypted stream
+pub(crate) struct StreamCipher {
+    send: XChaCha20Poly1305,
+    recv: XChaCha20Poly1305,
+}
+
+impl KeyExchange {
+    pub fn new() -> Self {
+        Self {
+            secret: StaticSecret::new(rand::rngs::OsRng),
+        }
+    }
+
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_LEN] { *PublicKey::from(&self.secret).as_bytes() }
+
+    /// Combines our secret with the public key the remote side sent us
+    pub fn finish(&self, remote_public_key: [u8; PUBLIC_KEY_LEN], initiator: bool) -> SessionKey {
+        let shared = self
+            .secret
+            .diffie_hellman(&PublicKey::from(remote_public_key));
+        // Both sides need the same salt, so order the public keys by role
+        let local_public_key = self.public_key();
+        let mut salt = [0u8; 2 * PUBLIC_KEY_LEN];
+        let (initiator_key, responder_key) = if initiator {
+            (local_public_key, remote_public_key)
+        } else {
+            (remote_public_key, local_public_key)
+        };
+        salt[..PUBLIC_KEY_LEN].copy_from_slice(&initiator_key);
+        salt[PUBLIC_KEY_LEN..].copy_from_slice(&responder_key);
+        SessionKey {
+            hkdf: Hkdf::new(Some(&salt[..]), shared.as_bytes()),
+            initiator,
+        }
+    }
+}
+
+impl SessionKey {
+    pub fn stream_cipher(&self, sid: Sid) -> StreamCipher {
+        let initiator_key = self.derive(sid, b"initiator");
+        let responder_key = self.derive(sid, b"responder");
+        let (send, recv) = if self.initiator {
+            (initiator_key, responder_key)
+        } else {
+            (responder_key, initiator_key)
+        };
+        StreamCipher { send, recv }
+    }
+
+    fn derive(&self, sid: Sid, direction: &[u8]) -> XChaCha20Poly1305 {
+        let mut info = b"veloren stream ".to_vec();
+        info.extend_from_slice(&sid.to_le_bytes());
+        info.extend_from_slice(direction);
+        let mut key = [0u8; 32];
+        //this only fails for keys longer than 255 * 32 bytes
+        self.hkdf.expand(&info, &mut key).unwrap();
+        XChaCha20Poly1305::new(Key::from_slice(&key))
+    }
+}
+
+impl StreamCipher {
+    pub fn encrypt(&self, mid: Mid, start: u64, data: &[u8]) -> Vec<u8> {
+        //this only fails for payloads of more than 256 GiB
+        self.send.encrypt(&nonce(mid, start), data).unwrap()
+    }
+
+    /// Fails if the frame was not sealed for this position of this stream, or
+    /// was altered on the way
+    pub fn decrypt(&self, mid: Mid, start: u64, data: &[u8]) -> Result<Vec<u8>, ()> {
+        self.recv.decrypt(&nonce(mid, start), data).map_err(|_| ())
+    }
+}
+
+impl std::fmt::Debug for KeyExchange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "KeyExchange") }
+}
+
+impl std::fmt::Debug for SessionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SessionKey {{ initiator: {} }}", self.initiator)
+    }
+}
+
+impl std::fmt::Debug for StreamCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "StreamCipher") }
+}
+
+fn nonce(mid: Mid, start: u64) -> XNonce {
+    let mut nonce = XNonce::default();
+    nonce[..8].copy_from_slice(&mid.to_le_bytes());
+    nonce[8..16].copy_from_slice(&start.to_le_bytes());
+    nonce
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{encryption::*, types::Sid};
+
+    fn ciphers(sid: Sid) -> (StreamCipher, StreamCipher) {
+        let a = KeyExchange::new();
+        let b = KeyExchange::new();
+        let a_key = a.finish(b.public_key(), true);
+        let b_key = b.finish(a.public_key(), false);
+        (a_key.stream_cipher(sid), b_key.stream_cipher(sid))
+    }
+
+    #[test]
+    fn encrypted_roundtrip() {
+        let (a, b) = ciphers(Sid::new(3));
+        let data = b"Hello World".to_vec();
+        let sealed = a.encrypt(1, 0, &data);
+        // sealing adds a 16 byte authentication tag
+        assert_eq!(sealed.len(), data.len() + 16);
+        assert_ne!(&sealed[..data.len()], &data[..]);
+        assert_eq!(b.decrypt(1, 0, &sealed), Ok(data.clone()));
+        // the other direction uses its own key
+        let sealed = b.encrypt(1, 0, &data);
+        assert_eq!(a.decrypt(1, 0, &sealed), Ok(data));
+    }
+
+    #[test]
+    fn tampered_frames_are_rejected() {
+        let (a, b) = ciphers(Sid::new(3));
+        let sealed = a.encrypt(7, 1400, b"Hello World");
+        let mut flipped = sealed.clone();
+        flipped[2] ^= 0x01;
+        assert_eq!(b.decrypt(7, 1400, &flipped), Err(()));
+        let mut truncated = sealed.clone();
+        truncated.pop();
+        assert_eq!(b.decrypt(7, 1400, &truncated), Err(()));
+        // moved to another message or another position in the message
+        assert_eq!(b.decrypt(8, 1400, &sealed), Err(()));
+        assert_eq!(b.decrypt(7, 0, &sealed), Err(()));
+        // reflected back to the sender
+        assert_eq!(a.decrypt(7, 1400, &sealed), Err(()));
+        assert!(b.decrypt(7, 1400, &sealed).is_ok());
+    }
+
+    #[test]
+    fn streams_dont_share_keys() {
+        let a = KeyExchange::new();
+        let b = KeyExchange::new();
+        let a_key = a.finish(b.public_key(), true);
+        let b_key = b.finish(a.public_key(), false);
+        let sealed = a_key
+            .stream_cipher(Sid::new(1))
+            .encrypt(0, 0, b"Hello World");
+        assert_eq!(
+            b_key.stream_cipher(Sid::new(2)).decrypt(0, 0, &sealed),
+            Err(())
+        );
+        assert!(b_key
+            .stream_cipher(Sid::new(1))
+            .decrypt(0, 0, &sealed)
+            .is_ok());
+        // an eavesdropper with its own key pair can't read it
+        let eve = KeyExchange::new().finish(a.public_key(), false);
+        assert_eq!(
+            eve.stream_cipher(Sid::new(1)).decrypt(0, 0, &sealed),
+            Err(())
+        );
+    }
+}
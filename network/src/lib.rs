@@ -101,17 +101,23 @@
 
 mod api;
 mod channel;
+mod encryption;
 mod message;
 #[cfg(feature = "metrics")] mod metrics;
 mod participant;
 mod prios;
 mod protocols;
 mod scheduler;
+pub mod sim;
 #[macro_use]
 mod types;
 
+#[cfg(feature = "compression")]
+pub use api::CompressionError;
 pub use api::{
     Network, NetworkError, Participant, ParticipantError, ProtocolAddr, Stream, StreamError,
 };
 pub use message::Message;
-pub use types::{Pid, Promises};
+#[cfg(feature = "compression")]
+pub use types::Compression;
+pub use types::{Pid, Promises, REALTIME_MESSAGE_LIFETIME};
@@ -1,12 +1,20 @@
 use serde::{de::DeserializeOwned, Serialize};
 //use std::collections::VecDeque;
 #[cfg(feature = "compression")]
-use crate::types::Promises;
+use crate::{
+    api::CompressionError,
+    types::{Compression, Promises},
+};
 use crate::{
     api::{Stream, StreamError},
+    encryption::StreamCipher,
     types::{Frame, Mid, Sid},
 };
-use std::{io, sync::Arc};
+use std::{
+    io,
+    sync::{atomic::AtomicU64, Arc},
+    time::Instant,
+};
 #[cfg(all(feature = "compression", debug_assertions))]
 use tracing::warn;
 
@@ -19,10 +27,35 @@ use tracing::warn;
 /// [`send_raw`]: crate::api::Stream::send_raw
 pub struct Message {
     pub(crate) buffer: Arc<MessageBuffer>,
+    /// Size before compression, only known for messages serialized locally.
+    /// Received messages use the size of their buffer
+    pub(crate) uncompressed_len: u64,
     #[cfg(feature = "compression")]
     pub(crate) compressed: bool,
 }
 
+/// First byte of every message on a compressed [`Stream`], naming how the
+/// rest of it is compressed
+///
+/// [`Stream`]: crate::api::Stream
+#[cfg(feature = "compression")]
+mod compression_header {
+    pub const UNCOMPRESSED: u8 = 0;
+    pub const LZ4: u8 = 1;
+    pub const DEFLATE: u8 = 2;
+}
+
+/// Messages smaller than this are sent uncompressed, as compressing them
+/// barely saves anything but costs time on both sides
+#[cfg(feature = "compression")]
+pub(crate) const MIN_COMPRESSION_LEN: usize = 64;
+#[cfg(feature = "compression")]
+const DEFLATE_LEVEL: u8 = 6;
+/// Received messages that would decompress to more than this are rejected, so
+/// that a small message can't make us allocate unbounded memory
+#[cfg(feature = "compression")]
+pub(crate) const MAX_DECOMPRESSED_LEN: usize = 64 * 1024 * 1024;
+
 //Todo: Evaluate switching to VecDeque for quickly adding and removing data
 // from front, back.
 // - It would prob require custom bincode code but thats possible.
@@ -36,6 +69,18 @@ pub(crate) struct OutgoingMessage {
     pub cursor: u64,
     pub mid: Mid,
     pub sid: Sid,
+    pub uncompressed_len: u64,
+    /// Seals every `Frame::Data` of messages on encrypted [`Stream`]s
+    pub cipher: Option<Arc<StreamCipher>>,
+    /// Number of messages of the sending [`Stream`] that weren't fully sent
+    /// yet, decreased once this message is
+    pub queued: Arc<AtomicU64>,
+    /// Bytes of messages of the sending [`Stream`] that weren't fully sent
+    /// yet, decreased by the size of this message once it is
+    pub queued_bytes: Arc<AtomicU64>,
+    /// Time after which the message is dropped if it wasn't started yet, see
+    /// [`Promises::REALTIME`](crate::types::Promises::REALTIME)
+    pub expires: Option<Instant>,
 }
 
 #[derive(Debug)]
@@ -65,15 +110,13 @@ impl Message {
     pub fn serialize<M: Serialize + ?Sized>(message: &M, stream: &Stream) -> Self {
         //this will never fail: https://docs.rs/bincode/0.8.0/bincode/fn.serialize.html
         let serialized_data = bincode::serialize(message).unwrap();
+        let uncompressed_len = serialized_data.len() as u64;
 
         #[cfg(feature = "compression")]
         let compressed = stream.promises().contains(Promises::COMPRESSED);
         #[cfg(feature = "compression")]
         let data = if compressed {
-            let mut compressed_data = Vec::with_capacity(serialized_data.len() / 4 + 10);
-            let mut table = lz_fear::raw::U32Table::default();
-            lz_fear::raw::compress2(&serialized_data, 0, &mut table, &mut compressed_data).unwrap();
-            compressed_data
+            compress(serialized_data, stream.compression())
         } else {
             serialized_data
         };
@@ -84,11 +127,15 @@ impl Message {
 
         Self {
             buffer: Arc::new(MessageBuffer { data }),
+            uncompressed_len,
             #[cfg(feature = "compression")]
             compressed,
         }
     }
 
+    /// Size of the serialized, and possibly compressed, message in bytes
+    pub fn size(&self) -> usize { self.buffer.data.len() }
+
     /// deserialize this `Message`. This consumes the struct, as deserialization
     /// is only expected once. Use this when deserialize a [`recv_raw`]
     /// `Message`. If you are resending this message, deserialization might need
@@ -131,18 +178,7 @@ impl Message {
 
         #[cfg(feature = "compression")]
         let uncompressed_data = if self.compressed {
-            {
-                let mut uncompressed_data = Vec::with_capacity(self.buffer.data.len() * 2);
-                if let Err(e) = lz_fear::raw::decompress_raw(
-                    &self.buffer.data,
-                    &[0; 0],
-                    &mut uncompressed_data,
-                    usize::MAX,
-                ) {
-                    return Err(StreamError::Compression(e));
-                }
-                uncompressed_data
-            }
+            decompress(&self.buffer.data).map_err(StreamError::Compression)?
         } else {
             match Arc::try_unwrap(self.buffer) {
                 Ok(d) => d.data,
@@ -170,6 +206,143 @@ impl Message {
     }
 }
 
+/// Compresses a message for a [`Stream`] with [`Promises::COMPRESSED`].
+/// Messages that are too small or don't get any smaller are sent as they are.
+///
+/// [`Stream`]: crate::api::Stream
+#[cfg(feature = "compression")]
+pub(crate) fn compress(data: Vec<u8>, compression: Compression) -> Vec<u8> {
+    if data.len() >= MIN_COMPRESSION_LEN {
+        let mut compressed = Vec::with_capacity(data.len() / 4 + 10);
+        match compression {
+            Compression::Lz4 => {
+                compressed.push(compression_header::LZ4);
+                let mut table = lz_fear::raw::U32Table::default();
+                lz_fear::raw::compress2(&data, 0, &mut table, &mut compressed).unwrap();
+            },
+            Compression::Deflate => {
+                compressed.push(compression_header::DEFLATE);
+                compressed.extend(miniz_oxide::deflate::compress_to_vec(&data, DEFLATE_LEVEL));
+            },
+        }
+        if compressed.len() < data.len() {
+            return compressed;
+        }
+    }
+    let mut uncompressed = Vec::with_capacity(data.len() + 1);
+    uncompressed.push(compression_header::UNCOMPRESSED);
+    uncompressed.extend_from_slice(&data);
+    uncompressed
+}
+
+#[cfg(feature = "compression")]
+pub(crate) fn decompress(data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+    decompress_with_limit(data, MAX_DECOMPRESSED_LEN)
+}
+
+/// Decompresses a message, failing with [`CompressionError::TooLarge`] before
+/// the output grows beyond `limit`
+#[cfg(feature = "compression")]
+fn decompress_with_limit(data: &[u8], limit: usize) -> Result<Vec<u8>, CompressionError> {
+    match data.split_first() {
+        Some((&compression_header::UNCOMPRESSED, data)) => Ok(data.to_vec()),
+        Some((&compression_header::LZ4, data)) => {
+            if lz4_decompressed_len(data) > limit {
+                return Err(CompressionError::TooLarge);
+            }
+            let mut uncompressed = Vec::with_capacity((data.len() * 2).min(limit));
+            lz_fear::raw::decompress_raw(data, &[0; 0], &mut uncompressed, usize::MAX)
+                .map_err(CompressionError::Lz4)?;
+            Ok(uncompressed)
+        },
+        Some((&compression_header::DEFLATE, data)) => inflate(data, limit),
+        Some((&id, _)) => Err(CompressionError::UnknownCompression(id)),
+        None => Err(CompressionError::MissingHeader),
+    }
+}
+
+/// Size of a raw LZ4 block once decompressed, read from the lengths of its
+/// sequences without decompressing it. For a block that is cut off, this is
+/// the size decompressing it gets to before failing.
+#[cfg(feature = "compression")]
+fn lz4_decompressed_len(data: &[u8]) -> usize {
+    // Lengths that don't fit in 4 bits continue in the following bytes, until
+    // one of them is below 255
+    fn read_len(data: &[u8], pos: &mut usize, mut len: usize) -> Option<usize> {
+        if len == 15 {
+            loop {
+                let byte = *data.get(*pos)?;
+                *pos += 1;
+                len = len.checked_add(byte as usize)?;
+                if byte != 255 {
+                    break;
+                }
+            }
+        }
+        Some(len)
+    }
+
+    let mut total = 0usize;
+    let mut pos = 0;
+    while pos < data.len() {
+        let token = data[pos];
+        pos += 1;
+        let literals = match read_len(data, &mut pos, (token >> 4) as usize) {
+            Some(literals) => literals,
+            None => break,
+        };
+        pos = pos.saturating_add(literals);
+        total = total.saturating_add(literals);
+        // The last sequence only has literals
+        if pos >= data.len() {
+            break;
+        }
+        // Skip the offset of the match
+        pos += 2;
+        match read_len(data, &mut pos, (token & 0xF) as usize) {
+            Some(match_len) => total = total.saturating_add(match_len).saturating_add(4),
+            None => break,
+        }
+    }
+    total
+}
+
+/// Inflates deflate data into a buffer that grows as needed, up to `limit`
+#[cfg(feature = "compression")]
+fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>, CompressionError> {
+    use miniz_oxide::inflate::{
+        core::{decompress, inflate_flags, DecompressorOxide},
+        TINFLStatus,
+    };
+    let flags = inflate_flags::TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF;
+    let mut uncompressed = vec![0; (data.len() * 2).min(limit)];
+    let mut decompressor = Box::<DecompressorOxide>::default();
+    let (mut in_pos, mut out_pos) = (0, 0);
+    loop {
+        let (status, in_consumed, out_consumed) = {
+            let mut cursor = io::Cursor::new(uncompressed.as_mut_slice());
+            cursor.set_position(out_pos as u64);
+            decompress(&mut decompressor, &data[in_pos..], &mut cursor, flags)
+        };
+        in_pos += in_consumed;
+        out_pos += out_consumed;
+        match status {
+            TINFLStatus::Done => {
+                uncompressed.truncate(out_pos);
+                return Ok(uncompressed);
+            },
+            TINFLStatus::HasMoreOutput if uncompressed.len() >= limit => {
+                return Err(CompressionError::TooLarge);
+            },
+            TINFLStatus::HasMoreOutput => {
+                let len = (uncompressed.len() * 2).max(1).min(limit);
+                uncompressed.resize(len, 0);
+            },
+            _ => return Err(CompressionError::Deflate),
+        }
+    }
+}
+
 impl OutgoingMessage {
     pub(crate) const FRAME_DATA_SIZE: u64 = 1400;
 
@@ -191,10 +364,14 @@ impl OutgoingMessage {
                     length: self.buffer.data.len() as u64,
                 })));
             }
+            let data = &self.buffer.data[self.cursor as usize..][..to_send as usize];
             frames.extend(std::iter::once((msg_sid, Frame::Data {
                 mid: self.mid,
                 start: self.cursor,
-                data: self.buffer.data[self.cursor as usize..][..to_send as usize].to_vec(),
+                data: match &self.cipher {
+                    Some(cipher) => cipher.encrypt(self.mid, self.cursor, data),
+                    None => data.to_vec(),
+                },
             })));
         };
         self.cursor += to_send;
@@ -281,6 +458,7 @@ mod tests {
             Sid::new(0),
             0u8,
             promises,
+            None,
             Arc::new(AtomicBool::new(true)),
             a2b_msg_s,
             b2a_msg_recv_r,
@@ -302,9 +480,10 @@ mod tests {
     #[cfg(feature = "compression")]
     #[test]
     fn serialize_compress_small() {
+        // too small to be worth compressing, so only the header is added
         let msg = Message::serialize("abc", &stub_stream(true));
         assert_eq!(msg.buffer.data.len(), 12);
-        assert_eq!(msg.buffer.data[0], 176);
+        assert_eq!(msg.buffer.data[0], compression_header::UNCOMPRESSED);
         assert_eq!(msg.buffer.data[1], 3);
         assert_eq!(msg.buffer.data[2..8], [0, 0, 0, 0, 0, 0]);
         assert_eq!(msg.buffer.data[9], b'a');
@@ -327,14 +506,15 @@ mod tests {
             "assets/data/plants/flowers/greenrose.ron",
         );
         let msg = Message::serialize(&msg, &stub_stream(true));
-        assert_eq!(msg.buffer.data.len(), 79);
-        assert_eq!(msg.buffer.data[0], 34);
-        assert_eq!(msg.buffer.data[1], 5);
-        assert_eq!(msg.buffer.data[2], 0);
-        assert_eq!(msg.buffer.data[3], 1);
-        assert_eq!(msg.buffer.data[20], 20);
-        assert_eq!(msg.buffer.data[40], 115);
-        assert_eq!(msg.buffer.data[60], 111);
+        assert_eq!(msg.buffer.data.len(), 80);
+        assert_eq!(msg.buffer.data[0], compression_header::LZ4);
+        assert_eq!(msg.buffer.data[1], 34);
+        assert_eq!(msg.buffer.data[2], 5);
+        assert_eq!(msg.buffer.data[3], 0);
+        assert_eq!(msg.buffer.data[4], 1);
+        assert_eq!(msg.buffer.data[21], 20);
+        assert_eq!(msg.buffer.data[41], 115);
+        assert_eq!(msg.buffer.data[61], 111);
     }
 
     #[cfg(feature = "compression")]
@@ -357,6 +537,70 @@ mod tests {
             }
         }
         let msg = Message::serialize(&msg, &stub_stream(true));
-        assert_eq!(msg.buffer.data.len(), 1296);
+        assert_eq!(msg.buffer.data.len(), 1297);
+        assert_eq!(msg.uncompressed_len, 10008);
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compression_roundtrip() {
+        let data: Vec<u8> = (0..4000u32).map(|i| (i / 50) as u8).collect();
+        for &compression in &[Compression::Lz4, Compression::Deflate] {
+            let compressed = compress(data.clone(), compression);
+            assert!(compressed.len() < data.len() / 4);
+            assert_eq!(decompress(&compressed), Ok(data.clone()));
+        }
+        // deflate is the one to pick when size matters most
+        assert!(
+            compress(data.clone(), Compression::Deflate).len()
+                < compress(data, Compression::Lz4).len()
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn incompressible_stays_uncompressed() {
+        use rand::{Rng, SeedableRng};
+        let mut rnd = rand::rngs::StdRng::from_seed([7; 32]);
+        let data: Vec<u8> = (0..1000).map(|_| rnd.gen()).collect();
+        let compressed = compress(data.clone(), Compression::Deflate);
+        assert_eq!(compressed.len(), data.len() + 1);
+        assert_eq!(compressed[0], compression_header::UNCOMPRESSED);
+        assert_eq!(decompress(&compressed), Ok(data));
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn decompress_invalid() {
+        assert_eq!(decompress(&[]), Err(CompressionError::MissingHeader));
+        assert_eq!(
+            decompress(&[42, 1, 2, 3]),
+            Err(CompressionError::UnknownCompression(42))
+        );
+        assert_eq!(
+            decompress(&[compression_header::DEFLATE, 0xFF, 0xFF]),
+            Err(CompressionError::Deflate)
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn decompress_too_large() {
+        // Compresses extremely well, like a message crafted to exhaust memory
+        let data = vec![0u8; 100_000];
+        for &compression in &[Compression::Lz4, Compression::Deflate] {
+            let compressed = compress(data.clone(), compression);
+            assert!(compressed.len() < 1000);
+            assert_eq!(
+                decompress_with_limit(&compressed, data.len() - 1),
+                Err(CompressionError::TooLarge)
+            );
+            assert_eq!(
+                decompress_with_limit(&compressed, data.len()),
+                Ok(data.clone())
+            );
+        }
+        let lz4 = compress(data.clone(), Compression::Lz4);
+        assert_eq!(lz4_decompressed_len(&lz4[1..]), data.len());
     }
 }
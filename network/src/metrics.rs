@@ -38,6 +38,9 @@ pub struct NetworkMetrics {
     pub message_out_total: IntCounterVec,
     // send(prio) Messages throughput, seperated by STREAM AND PARTICIPANT,
     pub message_out_throughput: IntCounterVec,
+    // send(prio) Messages throughput before compression, seperated by STREAM AND PARTICIPANT,
+    // the compression ratio of a stream is message_out_throughput divided by this
+    pub message_out_uncompressed_throughput: IntCounterVec,
     // flushed(prio) stream count, seperated by PARTICIPANT,
     pub streams_flushed: IntCounterVec,
     // TODO: queued Messages, seperated by STREAM (add PART, CHANNEL),
@@ -180,6 +183,13 @@ impl NetworkMetrics {
             ),
             &["participant", "stream"],
         )?;
+        let message_out_uncompressed_throughput = IntCounterVec::new(
+            Opts::new(
+                "message_out_uncompressed_throughput",
+                "Throughput of messages send by streams on the network, before compression",
+            ),
+            &["participant", "stream"],
+        )?;
         let streams_flushed = IntCounterVec::new(
             Opts::new(
                 "stream_flushed",
@@ -228,6 +238,7 @@ impl NetworkMetrics {
             wire_in_throughput,
             message_out_total,
             message_out_throughput,
+            message_out_uncompressed_throughput,
             streams_flushed,
             queued_count,
             queued_bytes,
@@ -254,6 +265,7 @@ impl NetworkMetrics {
         registry.register(Box::new(self.wire_in_throughput.clone()))?;
         registry.register(Box::new(self.message_out_total.clone()))?;
         registry.register(Box::new(self.message_out_throughput.clone()))?;
+        registry.register(Box::new(self.message_out_uncompressed_throughput.clone()))?;
         registry.register(Box::new(self.queued_count.clone()))?;
         registry.register(Box::new(self.queued_bytes.clone()))?;
         registry.register(Box::new(self.participants_ping.clone()))?;
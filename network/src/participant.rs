@@ -3,6 +3,7 @@ use crate::metrics::{MultiCidFrameCache, NetworkMetrics};
 use crate::{
     api::{ParticipantError, Stream},
     channel::Channel,
+    encryption::{SessionKey, StreamCipher},
     message::{IncomingMessage, MessageBuffer, OutgoingMessage},
     prios::PrioManager,
     protocols::Protocols,
@@ -37,6 +38,7 @@ pub(crate) type B2sPrioStatistic = (Pid, u64, u64);
 struct ChannelInfo {
     cid: Cid,
     cid_string: String, //optimisationmetrics
+    reliable: bool,
     b2w_frame_s: mpsc::UnboundedSender<Frame>,
     b2r_read_shutdown: oneshot::Sender<()>,
 }
@@ -45,10 +47,21 @@ struct ChannelInfo {
 struct StreamInfo {
     prio: Prio,
     promises: Promises,
+    /// Only set for streams with [`Promises::ENCRYPTED`]
+    cipher: Option<Arc<StreamCipher>>,
     send_closed: Arc<AtomicBool>,
     b2a_msg_recv_s: Mutex<mpsc::UnboundedSender<IncomingMessage>>,
 }
 
+/// The channel a stream sends its frames on. All frames of a stream use the
+/// same channel, so that they arrive in order.
+#[derive(Debug)]
+struct StreamRoute {
+    promises: Promises,
+    /// Chosen when the first frame is sent, and again when the channel fails
+    cid: Option<Cid>,
+}
+
 #[derive(Debug)]
 struct ControlChannels {
     a2b_stream_open_r: mpsc::UnboundedReceiver<A2bStreamOpen>,
@@ -71,8 +84,13 @@ pub struct BParticipant {
     remote_pid: Pid,
     remote_pid_string: String, //optimisation
     offset_sid: Sid,
+    /// Negotiated by the channel that created this participant. Later channels
+    /// negotiate their own key, which is ignored, so that all streams keep
+    /// using the same one
+    session_key: SessionKey,
     channels: Arc<RwLock<HashMap<Cid, Mutex<ChannelInfo>>>>,
     streams: RwLock<HashMap<Sid, StreamInfo>>,
+    stream_routes: RwLock<HashMap<Sid, StreamRoute>>,
     running_mgr: AtomicUsize,
     run_channels: Option<ControlChannels>,
     #[cfg(feature = "metrics")]
@@ -86,6 +104,7 @@ impl BParticipant {
     pub(crate) fn new(
         remote_pid: Pid,
         offset_sid: Sid,
+        session_key: SessionKey,
         #[cfg(feature = "metrics")] metrics: Arc<NetworkMetrics>,
     ) -> (
         Self,
@@ -120,8 +139,10 @@ impl BParticipant {
                 remote_pid,
                 remote_pid_string: remote_pid.to_string(),
                 offset_sid,
+                session_key,
                 channels: Arc::new(RwLock::new(HashMap::new())),
                 streams: RwLock::new(HashMap::new()),
+                stream_routes: RwLock::new(HashMap::new()),
                 running_mgr: AtomicUsize::new(0),
                 run_channels,
                 #[cfg(feature = "metrics")]
@@ -201,8 +222,9 @@ impl BParticipant {
             let mut frames = VecDeque::new();
             prios.fill_frames(FRAMES_PER_TICK, &mut frames).await;
             let len = frames.len();
-            for (_, frame) in frames {
+            for (sid, frame) in frames {
                 self.send_frame(
+                    Some(sid),
                     frame,
                     #[cfg(feature = "metrics")]
                     &mut send_cache,
@@ -238,17 +260,61 @@ impl BParticipant {
         self.running_mgr.fetch_sub(1, Ordering::Relaxed);
     }
 
+    /// Picks the channel for the frames of a stream, or of the participant
+    /// itself if `sid` is None. Streams keep their channel until it fails.
+    /// Frames that have to arrive are never sent on unreliable channels, so
+    /// there might be no channel for them.
+    async fn route(&self, sid: Option<Sid>) -> Option<Cid> {
+        let channels = self.channels.read().await;
+        let mut routes = self.stream_routes.write().await;
+        let mut route = sid.and_then(|sid| routes.get_mut(&sid));
+        if let Some(cid) = route.as_ref().and_then(|route| route.cid) {
+            if channels.contains_key(&cid) {
+                return Some(cid);
+            }
+        }
+        // frames like `Shutdown` must not get lost
+        let promises = route
+            .as_ref()
+            .map_or(Promises::ORDERED | Promises::GUARANTEED_DELIVERY, |route| {
+                route.promises
+            });
+        let mut best: Option<(Cid, u8)> = None;
+        for (&cid, ci) in channels.iter() {
+            let score = match channel_score(ci.lock().await.reliable, promises) {
+                Some(score) => score,
+                None => continue,
+            };
+            // prefer the oldest channel among equally suited ones
+            if best.map_or(true, |(best_cid, best_score)| {
+                score > best_score || (score == best_score && cid < best_cid)
+            }) {
+                best = Some((cid, score));
+            }
+        }
+        let cid = best.map(|(cid, _)| cid);
+        if let (Some(route), Some(cid)) = (route.as_mut(), cid) {
+            if let Some(old_cid) = route.cid {
+                info!(?sid, ?old_cid, ?cid, "Moving stream to another channel");
+            }
+            route.cid = Some(cid);
+        }
+        cid
+    }
+
     //returns false if sending isn't possible. In that case we have to render the
     // Participant `closed`
     #[must_use = "You need to check if the send was successful and report to client!"]
     async fn send_frame(
         &self,
+        sid: Option<Sid>,
         frame: Frame,
         #[cfg(feature = "metrics")] frames_out_total_cache: &mut MultiCidFrameCache,
     ) -> bool {
         let mut drop_cid = None;
-        // TODO: find out ideal channel here
-        let res = if let Some(ci) = self.channels.read().await.values().next() {
+        let cid = self.route(sid).await;
+        let channels = self.channels.read().await;
+        let res = if let Some(ci) = cid.and_then(|cid| channels.get(&cid)) {
             let mut ci = ci.lock().await;
             //we are increasing metrics without checking the result to please
             // borrow_checker. otherwise we would need to close `frame` what we
@@ -283,26 +349,88 @@ impl BParticipant {
             }
             false
         };
+        drop(channels);
         if let Some(cid) = drop_cid {
-            if let Some(ci) = self.channels.write().await.remove(&cid) {
+            let mut channels = self.channels.write().await;
+            if let Some(ci) = channels.remove(&cid) {
                 let ci = ci.into_inner();
                 trace!(?cid, "stopping read protocol");
                 if let Err(e) = ci.b2r_read_shutdown.send(()) {
                     trace!(?cid, ?e, "seems like was already shut down");
                 }
             }
-            //TODO FIXME tags: takeover channel multiple
-            info!(
-                "FIXME: the frame is actually drop. which is fine for now as the participant will \
-                 be closed, but not if we do channel-takeover"
-            );
-            //TEMP FIX: as we dont have channel takeover yet drop the whole bParticipant
-            self.close_write_api(Some(ParticipantError::ProtocolFailedUnrecoverable))
-                .await;
+            let mut reliable_remaining = false;
+            for ci in channels.values() {
+                reliable_remaining |= ci.lock().await.reliable;
+            }
+            drop(channels);
+            // Without a reliable channel, nothing that has to arrive can be sent
+            // anymore, not even the frames of the participant itself
+            if !reliable_remaining {
+                self.close_write_api(Some(ParticipantError::ProtocolFailedUnrecoverable))
+                    .await;
+            } else {
+                info!(?cid, "Channel failed, its frame is lost");
+                self.close_streams_of_failed_channel(cid).await;
+            }
         };
         res
     }
 
+    /// Closes the streams with reliable [`Promises`] that were sending on the
+    /// failed channel `cid`. Frames handed to that channel may have been lost,
+    /// and as the remote doesn't acknowledge frames there is no telling which
+    /// ones, so these streams can't keep their promises anymore. All other
+    /// streams move to the remaining channels.
+    async fn close_streams_of_failed_channel(&self, cid: Cid) {
+        let failed = {
+            let mut routes = self.stream_routes.write().await;
+            let failed = routes
+                .iter()
+                .filter(|(_, route)| route.cid == Some(cid) && needs_reliable(route.promises))
+                .map(|(sid, _)| *sid)
+                .collect::<Vec<_>>();
+            for sid in &failed {
+                routes.remove(sid);
+            }
+            failed
+        };
+        if failed.is_empty() {
+            return;
+        }
+        info!(
+            ?cid,
+            ?failed,
+            "Closing the reliable streams of the failed channel"
+        );
+
+        let mut streams = self.streams.write().await;
+        for sid in &failed {
+            if let Some(si) = streams.remove(sid) {
+                si.send_closed.store(true, Ordering::Relaxed);
+                si.b2a_msg_recv_s.lock().await.close_channel();
+                #[cfg(feature = "metrics")]
+                self.metrics
+                    .streams_closed_total
+                    .with_label_values(&[&self.remote_pid_string])
+                    .inc();
+            }
+        }
+        drop(streams);
+
+        // Let the remote close its end of the streams as well
+        if let Some(cid) = self.route(None).await {
+            if let Some(ci) = self.channels.read().await.get(&cid) {
+                let mut ci = ci.lock().await;
+                for &sid in &failed {
+                    if let Err(e) = ci.b2w_frame_s.send(Frame::CloseStream { sid }).await {
+                        debug!(?e, ?sid, "Couldn't tell the remote to close the stream");
+                    }
+                }
+            }
+        }
+    }
+
     async fn handle_frames_mgr(
         &self,
         mut w2b_frames_r: mpsc::UnboundedReceiver<C2pFrame>,
@@ -387,9 +515,34 @@ impl BParticipant {
                 },
                 Frame::Data {
                     mid,
-                    start: _,
+                    start,
                     mut data,
                 } => {
+                    let cipher = match messages.get(&mid) {
+                        Some(imsg) => self
+                            .streams
+                            .read()
+                            .await
+                            .get(&imsg.sid)
+                            .and_then(|si| si.cipher.clone()),
+                        None => None,
+                    };
+                    if let Some(cipher) = cipher {
+                        match cipher.decrypt(mid, start, &data) {
+                            Ok(plain) => data = plain,
+                            Err(()) => {
+                                error!(
+                                    ?mid,
+                                    "Received a tampered frame on an encrypted stream, closing \
+                                     the participant"
+                                );
+                                messages.remove(&mid);
+                                self.close_api(Some(ParticipantError::ProtocolFailedUnrecoverable))
+                                    .await;
+                                continue;
+                            },
+                        }
+                    }
                     let finished = if let Some(imsg) = messages.get_mut(&mid) {
                         imsg.buffer.data.append(&mut data);
                         imsg.buffer.data.len() as u64 == imsg.length
@@ -470,6 +623,7 @@ impl BParticipant {
                     let channels = Arc::clone(&self.channels);
                     async move {
                         let (channel, b2w_frame_s, b2r_read_shutdown) = Channel::new(cid);
+                        let reliable = protocol.is_reliable();
                         let mut lock = channels.write().await;
                         #[cfg(feature = "metrics")]
                         let mut channel_no = lock.len();
@@ -480,6 +634,7 @@ impl BParticipant {
                             Mutex::new(ChannelInfo {
                                 cid,
                                 cid_string: cid.to_string(),
+                                reliable,
                                 b2w_frame_s,
                                 b2r_read_shutdown,
                             }),
@@ -516,11 +671,17 @@ impl BParticipant {
                             .inc();
                         info!(?cid, "Channel got closed");
                         //maybe channel got already dropped, we don't know.
-                        channels.write().await.remove(&cid);
+                        let mut lock = channels.write().await;
+                        lock.remove(&cid);
+                        let remaining = lock.len();
+                        drop(lock);
                         trace!(?cid, "Channel cleanup completed");
-                        //TEMP FIX: as we dont have channel takeover yet drop the whole
-                        // bParticipant
-                        self.close_write_api(None).await;
+                        if remaining == 0 {
+                            self.close_write_api(None).await;
+                        } else {
+                            info!(?cid, ?remaining, "Channel failed, other channels remain");
+                            self.close_streams_of_failed_channel(cid).await;
+                        }
                     }
                 },
             )
@@ -562,6 +723,7 @@ impl BParticipant {
                 .await;
             if self
                 .send_frame(
+                    control_route(sid, promises),
                     Frame::OpenStream {
                         sid,
                         prio,
@@ -631,6 +793,7 @@ impl BParticipant {
             debug!("Sending shutdown frame after flushed all prios");
             if !self
                 .send_frame(
+                    None,
                     Frame::Shutdown,
                     #[cfg(feature = "metrics")]
                     &mut send_cache,
@@ -802,13 +965,21 @@ impl BParticipant {
             self.streams.write().await.remove(&sid);
 
             if !from_remote {
+                let promises = self
+                    .stream_routes
+                    .read()
+                    .await
+                    .get(&sid)
+                    .map_or(Promises::empty(), |route| route.promises);
                 self.send_frame(
+                    control_route(sid, promises),
                     Frame::CloseStream { sid },
                     #[cfg(feature = "metrics")]
                     frames_out_total_cache,
                 )
                 .await;
             }
+            self.stream_routes.write().await.remove(&sid);
         }
         .instrument(tracing::info_span!("close", ?sid, ?from_remote))
         .await;
@@ -824,12 +995,22 @@ impl BParticipant {
     ) -> Stream {
         let (b2a_msg_recv_s, b2a_msg_recv_r) = mpsc::unbounded::<IncomingMessage>();
         let send_closed = Arc::new(AtomicBool::new(false));
+        let cipher = if promises.contains(Promises::ENCRYPTED) {
+            Some(Arc::new(self.session_key.stream_cipher(sid)))
+        } else {
+            None
+        };
         self.streams.write().await.insert(sid, StreamInfo {
             prio,
             promises,
+            cipher: cipher.clone(),
             send_closed: Arc::clone(&send_closed),
             b2a_msg_recv_s: Mutex::new(b2a_msg_recv_s),
         });
+        self.stream_routes.write().await.insert(sid, StreamRoute {
+            promises,
+            cid: None,
+        });
         #[cfg(feature = "metrics")]
         self.metrics
             .streams_opened_total
@@ -840,6 +1021,7 @@ impl BParticipant {
             sid,
             prio,
             promises,
+            cipher,
             send_closed,
             a2p_msg_s,
             b2a_msg_recv_r,
@@ -873,3 +1055,51 @@ impl BParticipant {
         }
     }
 }
+
+/// Whether every frame of a stream with these promises has to arrive. Realtime
+/// streams drop late frames anyway.
+fn needs_reliable(promises: Promises) -> bool {
+    !promises.contains(Promises::REALTIME)
+        && promises.intersects(Promises::ORDERED | Promises::GUARANTEED_DELIVERY)
+}
+
+/// How well a channel suits the frames of a stream, higher is better. Streams
+/// that cope with lost or reordered frames prefer the lower latency of
+/// unreliable channels, all others can't use them at all.
+fn channel_score(reliable: bool, promises: Promises) -> Option<u8> {
+    match (reliable, needs_reliable(promises)) {
+        (false, false) => Some(2),
+        (true, _) => Some(1),
+        (false, true) => None,
+    }
+}
+
+/// Where to send the frames that open and close a stream. They must not get
+/// lost, so streams on unreliable channels send them as frames of the
+/// participant. All other streams send them on their own channel, to keep
+/// them in order with their messages.
+fn control_route(sid: Sid, promises: Promises) -> Option<Sid> {
+    if needs_reliable(promises) {
+        Some(sid)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{participant::channel_score, types::Promises};
+
+    #[test]
+    fn streams_prefer_suitable_channels() {
+        let inputs = Promises::empty();
+        let terrain = Promises::ORDERED | Promises::CONSISTENCY;
+        assert!(channel_score(false, inputs) > channel_score(true, inputs));
+        assert!(channel_score(true, terrain).is_some());
+        assert_eq!(channel_score(false, terrain), None);
+        assert!(channel_score(true, Promises::GUARANTEED_DELIVERY).is_some());
+        assert_eq!(channel_score(false, Promises::GUARANTEED_DELIVERY), None);
+        let voice = Promises::REALTIME | Promises::ORDERED;
+        assert!(channel_score(false, voice) > channel_score(true, voice));
+    }
+}
@@ -13,8 +13,12 @@ use crate::{
 };
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use futures::channel::oneshot;
-use std::collections::{HashMap, HashSet, VecDeque};
 #[cfg(feature = "metrics")] use std::sync::Arc;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::atomic::Ordering,
+    time::Instant,
+};
 use tracing::trace;
 
 const PRIO_MAX: usize = 64;
@@ -160,6 +164,10 @@ impl PrioManager {
                     .message_out_throughput
                     .with_label_values(&[&self.pid, &sid_string])
                     .inc_by(msg.buffer.data.len() as i64);
+                self.metrics
+                    .message_out_uncompressed_throughput
+                    .with_label_values(&[&self.pid, &sid_string])
+                    .inc_by(msg.uncompressed_len as i64);
             }
 
             //trace!(?prio, ?sid_string, "tick");
@@ -226,6 +234,7 @@ impl PrioManager {
             v.reserve_exact(no_of_frames)
         }
         self.tick().await;
+        let now = Instant::now();
         for _ in 0..no_of_frames {
             match self.calc_next_prio() {
                 Some(prio) => {
@@ -236,12 +245,21 @@ impl PrioManager {
                     // => messages with same prio get a fair chance :)
                     //TODO: evaluate not popping every time
                     let (sid, mut msg) = self.messages[prio as usize].pop_front().unwrap();
-                    if msg.fill_next(sid, frames) {
+                    // realtime messages that are too late aren't worth sending anymore
+                    let expired =
+                        msg.cursor == 0 && msg.expires.map_or(false, |expires| expires <= now);
+                    if expired {
+                        trace!(?sid, "dropping expired realtime message");
+                    }
+                    if expired || msg.fill_next(sid, frames) {
                         //trace!(?m.mid, "finish message");
                         //check if prio is empty
                         if self.messages[prio as usize].is_empty() {
                             self.queued.remove(&prio);
                         }
+                        msg.queued.fetch_sub(1, Ordering::Relaxed);
+                        msg.queued_bytes
+                            .fetch_sub(msg.buffer.data.len() as u64, Ordering::Relaxed);
                         //decrease pid_sid counter by 1 again
                         let cnt = self.sid_owned.get_mut(&sid).expect(
                             "The pid_sid_owned counter works wrong, more pid,sid removed than \
@@ -291,7 +309,11 @@ mod tests {
     };
     use crossbeam_channel::Sender;
     use futures::{channel::oneshot, executor::block_on};
-    use std::{collections::VecDeque, sync::Arc};
+    use std::{
+        collections::VecDeque,
+        sync::{atomic::AtomicU64, Arc},
+        time::Duration,
+    };
 
     const SIZE: u64 = OutgoingMessage::FRAME_DATA_SIZE;
     const USIZE: usize = OutgoingMessage::FRAME_DATA_SIZE as usize;
@@ -318,6 +340,11 @@ mod tests {
             cursor: 0,
             mid: 1,
             sid,
+            uncompressed_len: 0,
+            cipher: None,
+            queued: Arc::default(),
+            queued_bytes: Arc::default(),
+            expires: None,
         })
     }
 
@@ -331,6 +358,11 @@ mod tests {
             cursor: 0,
             mid: 1,
             sid,
+            uncompressed_len: 0,
+            cipher: None,
+            queued: Arc::default(),
+            queued_bytes: Arc::default(),
+            expires: None,
         })
     }
 
@@ -374,6 +406,40 @@ mod tests {
         assert!(frames.is_empty());
     }
 
+    #[test]
+    fn queued_count_decreases_when_sent() {
+        let (mut mgr, msg_tx, _flush_tx) = mock_new();
+        let queued = Arc::new(AtomicU64::new(2));
+        for _ in 0..2 {
+            let (prio, sid, mut msg) = mock_out(16, 1337);
+            msg.queued = Arc::clone(&queued);
+            msg_tx.send((prio, sid, msg)).unwrap();
+        }
+        let mut frames = VecDeque::new();
+
+        block_on(mgr.fill_frames(1, &mut frames));
+        assert_eq!(queued.load(Ordering::Relaxed), 1);
+        block_on(mgr.fill_frames(1, &mut frames));
+        assert_eq!(queued.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn expired_realtime_messages_are_dropped() {
+        let (mut mgr, msg_tx, _flush_tx) = mock_new();
+        let (prio, sid, mut msg) = mock_out(16, 1);
+        msg.expires = Some(Instant::now());
+        msg_tx.send((prio, sid, msg)).unwrap();
+        let (prio, sid, mut msg) = mock_out(16, 2);
+        msg.expires = Some(Instant::now() + Duration::from_secs(60));
+        msg_tx.send((prio, sid, msg)).unwrap();
+        let mut frames = VecDeque::new();
+        block_on(mgr.fill_frames(100, &mut frames));
+
+        assert_header(&mut frames, 2, 3);
+        assert_data(&mut frames, 0, vec![48, 49, 50]);
+        assert!(frames.is_empty());
+    }
+
     #[test]
     fn single_p16_p20() {
         let (mut mgr, msg_tx, _flush_tx) = mock_new();
@@ -586,6 +652,11 @@ mod tests {
                 cursor: 0,
                 mid: 1,
                 sid,
+                uncompressed_len: 0,
+                cipher: None,
+                queued: Arc::default(),
+                queued_bytes: Arc::default(),
+                expires: None,
             }))
             .unwrap();
 
@@ -615,6 +686,11 @@ mod tests {
                 cursor: 0,
                 mid: 1,
                 sid,
+                uncompressed_len: 0,
+                cipher: None,
+                queued: Arc::default(),
+                queued_bytes: Arc::default(),
+                expires: None,
             }))
             .unwrap();
         msg_tx.send(mock_out(16, 8)).unwrap();
@@ -647,6 +723,11 @@ mod tests {
                 cursor: 0,
                 mid: 1,
                 sid,
+                uncompressed_len: 0,
+                cipher: None,
+                queued: Arc::default(),
+                queued_bytes: Arc::default(),
+                expires: None,
             }))
             .unwrap();
         msg_tx.send(mock_out(20, 8)).unwrap();
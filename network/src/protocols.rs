@@ -2,6 +2,7 @@
 use crate::metrics::{CidFrameCache, NetworkMetrics};
 use crate::{
     participant::C2pFrame,
+    sim::{LinkConditions, SimulatedLink},
     types::{Cid, Frame},
 };
 use async_std::{
@@ -11,13 +12,19 @@ use async_std::{
 
 use futures::{
     channel::{mpsc, oneshot},
-    future::{Fuse, FutureExt},
+    future::{self, Fuse, FutureExt},
     lock::Mutex,
     select,
     sink::SinkExt,
     stream::StreamExt,
 };
-use std::{convert::TryFrom, net::SocketAddr, sync::Arc};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    convert::TryFrom,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::Instant,
+};
 use tracing::*;
 
 // Reserving bytes 0, 10, 13 as i have enough space and want to make it easy to
@@ -38,7 +45,18 @@ const FRAME_RAW: u8 = 8;
 pub(crate) enum Protocols {
     Tcp(TcpProtocol),
     Udp(UdpProtocol),
-    //Mpsc(MpscChannel),
+    Mpsc(MpscProtocol),
+}
+
+impl Protocols {
+    /// Whether the protocol itself delivers every frame, in order
+    pub(crate) fn is_reliable(&self) -> bool {
+        match self {
+            Protocols::Tcp(_) => true,
+            Protocols::Udp(_) => false,
+            Protocols::Mpsc(mpsc) => mpsc.conditions.read().unwrap().reliable,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -57,6 +75,24 @@ pub(crate) struct UdpProtocol {
     data_in: Mutex<mpsc::UnboundedReceiver<Vec<u8>>>,
 }
 
+/// Frame channels of one end of an in-process connection
+#[derive(Debug)]
+pub(crate) struct MpscEndpoint {
+    endpoint_s: mpsc::UnboundedSender<Frame>,
+    endpoint_r: mpsc::UnboundedReceiver<Frame>,
+}
+
+/// One end of an in-process connection, passing frames to the other end
+/// under the simulated [`LinkConditions`] of its address
+#[derive(Debug)]
+pub(crate) struct MpscProtocol {
+    endpoint_s: mpsc::UnboundedSender<Frame>,
+    endpoint_r: Mutex<mpsc::UnboundedReceiver<Frame>>,
+    conditions: Arc<RwLock<LinkConditions>>,
+    #[cfg(feature = "metrics")]
+    metrics: Arc<NetworkMetrics>,
+}
+
 //TODO: PERFORMACE: Use BufWriter and BufReader from std::io!
 impl TcpProtocol {
     pub(crate) fn new(
@@ -98,7 +134,7 @@ impl TcpProtocol {
                 Ok(Frame::gen_handshake(bytes))
             },
             FRAME_INIT => {
-                let mut bytes = [0u8; 32];
+                let mut bytes = [0u8; 64];
                 handle(r.read_exact(&mut bytes).await)?;
                 Ok(Frame::gen_init(bytes))
             },
@@ -224,10 +260,15 @@ impl TcpProtocol {
                 w.write_all(&version[1].to_le_bytes()).await?;
                 w.write_all(&version[2].to_le_bytes()).await?;
             },
-            Frame::Init { pid, secret } => {
+            Frame::Init {
+                pid,
+                secret,
+                public_key,
+            } => {
                 w.write_all(&FRAME_INIT.to_be_bytes()).await?;
                 w.write_all(&pid.to_le_bytes()).await?;
                 w.write_all(&secret.to_le_bytes()).await?;
+                w.write_all(&public_key).await?;
             },
             Frame::Shutdown => {
                 w.write_all(&FRAME_SHUTDOWN.to_be_bytes()).await?;
@@ -356,7 +397,7 @@ impl UdpProtocol {
                 FRAME_HANDSHAKE => {
                     Frame::gen_handshake(*<&[u8; 19]>::try_from(&bytes[1..20]).unwrap())
                 },
-                FRAME_INIT => Frame::gen_init(*<&[u8; 32]>::try_from(&bytes[1..33]).unwrap()),
+                FRAME_INIT => Frame::gen_init(*<&[u8; 64]>::try_from(&bytes[1..65]).unwrap()),
                 FRAME_SHUTDOWN => Frame::Shutdown,
                 FRAME_OPEN_STREAM => {
                     Frame::gen_open_stream(*<&[u8; 10]>::try_from(&bytes[1..11]).unwrap())
@@ -419,11 +460,16 @@ impl UdpProtocol {
                     buffer[16..20].copy_from_slice(&version[2].to_le_bytes());
                     20
                 },
-                Frame::Init { pid, secret } => {
+                Frame::Init {
+                    pid,
+                    secret,
+                    public_key,
+                } => {
                     buffer[0] = FRAME_INIT.to_be_bytes()[0];
                     buffer[1..17].copy_from_slice(&pid.to_le_bytes());
                     buffer[17..33].copy_from_slice(&secret.to_le_bytes());
-                    33
+                    buffer[33..65].copy_from_slice(&public_key);
+                    65
                 },
                 Frame::Shutdown => {
                     buffer[0] = FRAME_SHUTDOWN.to_be_bytes()[0];
@@ -494,6 +540,135 @@ impl UdpProtocol {
     }
 }
 
+impl MpscEndpoint {
+    /// Both ends of a new connection
+    pub(crate) fn pair() -> (Self, Self) {
+        let (a_s, a_r) = mpsc::unbounded::<Frame>();
+        let (b_s, b_r) = mpsc::unbounded::<Frame>();
+        (
+            Self {
+                endpoint_s: b_s,
+                endpoint_r: a_r,
+            },
+            Self {
+                endpoint_s: a_s,
+                endpoint_r: b_r,
+            },
+        )
+    }
+}
+
+impl MpscProtocol {
+    pub(crate) fn new(
+        endpoint: MpscEndpoint,
+        conditions: Arc<RwLock<LinkConditions>>,
+        #[cfg(feature = "metrics")] metrics: Arc<NetworkMetrics>,
+    ) -> Self {
+        Self {
+            endpoint_s: endpoint.endpoint_s,
+            endpoint_r: Mutex::new(endpoint.endpoint_r),
+            conditions,
+            #[cfg(feature = "metrics")]
+            metrics,
+        }
+    }
+
+    pub async fn read_from_wire(
+        &self,
+        cid: Cid,
+        w2c_cid_frame_s: &mut mpsc::UnboundedSender<C2pFrame>,
+        end_r: oneshot::Receiver<()>,
+    ) {
+        trace!("Starting up mpsc read()");
+        #[cfg(feature = "metrics")]
+        let mut metrics_cache = CidFrameCache::new(self.metrics.frames_wire_in_total.clone(), cid);
+        let mut endpoint_r = self.endpoint_r.lock().await;
+        let mut end_r = end_r.fuse();
+        while let Some(frame) = select! {
+            r = endpoint_r.next().fuse() => match r {
+                Some(r) => Some(r),
+                None => {
+                    info!("Mpsc read ended, the other end was dropped");
+                    w2c_cid_frame_s
+                        .send((cid, Err(())))
+                        .await
+                        .expect("Channel or Participant seems no longer to exist");
+                    None
+                }
+            },
+            _ = end_r => None,
+        } {
+            if self.conditions.read().unwrap().cut {
+                info!("Mpsc link was cut, going to close this channel");
+                w2c_cid_frame_s
+                    .send((cid, Err(())))
+                    .await
+                    .expect("Channel or Participant seems no longer to exist");
+                break;
+            }
+            #[cfg(feature = "metrics")]
+            metrics_cache.with_label_values(&frame).inc();
+            w2c_cid_frame_s
+                .send((cid, Ok(frame)))
+                .await
+                .expect("Channel or Participant seems no longer to exist");
+        }
+        trace!("Shutting down mpsc read()");
+    }
+
+    /// Frames are held back until the simulated conditions let them arrive.
+    /// Frames still held back when the channel closes are delivered before
+    /// this returns.
+    pub async fn write_to_wire(&self, cid: Cid, mut c2w_frame_r: mpsc::UnboundedReceiver<Frame>) {
+        trace!("Starting up mpsc write()");
+        #[cfg(feature = "metrics")]
+        let mut metrics_cache = CidFrameCache::new(self.metrics.frames_wire_out_total.clone(), cid);
+        #[cfg(not(feature = "metrics"))]
+        let _cid = cid;
+        let mut link = SimulatedLink::default();
+        let mut rng = StdRng::from_entropy();
+        let mut open = true;
+        while open || !link.is_empty() {
+            let now = Instant::now();
+            while let Some(frame) = link.pop_due(now) {
+                #[cfg(feature = "metrics")]
+                metrics_cache.with_label_values(&frame).inc();
+                if self.endpoint_s.unbounded_send(frame).is_err() {
+                    info!("Other end of mpsc was dropped, going to close this channel");
+                    c2w_frame_r.close();
+                    return;
+                }
+            }
+            let wait = match link.next_due() {
+                Some(due) => async_std::task::sleep(due.saturating_duration_since(now)).boxed(),
+                None => future::pending().boxed(),
+            };
+            if !open {
+                wait.await;
+                continue;
+            }
+            select! {
+                frame = c2w_frame_r.next().fuse() => match frame {
+                    Some(frame) => {
+                        let conditions = *self.conditions.read().unwrap();
+                        if conditions.cut {
+                            info!("Mpsc link was cut, going to close this channel");
+                            c2w_frame_r.close();
+                            return;
+                        }
+                        let droppable =
+                            matches!(frame, Frame::DataHeader { .. } | Frame::Data { .. });
+                        link.push(Instant::now(), frame, droppable, &conditions, &mut rng);
+                    },
+                    None => open = false,
+                },
+                _ = wait.fuse() => {},
+            }
+        }
+        trace!("Shutting down mpsc write()");
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,7 +4,8 @@ use crate::{
     api::{Participant, ProtocolAddr},
     channel::Handshake,
     participant::{B2sPrioStatistic, BParticipant, S2bCreateChannel, S2bShutdownBparticipant},
-    protocols::{Protocols, TcpProtocol, UdpProtocol},
+    protocols::{MpscEndpoint, MpscProtocol, Protocols, TcpProtocol, UdpProtocol},
+    sim,
     types::Pid,
 };
 use async_std::{io, net, sync::Mutex};
@@ -16,6 +17,7 @@ use futures::{
     sink::SinkExt,
     stream::StreamExt,
 };
+use lazy_static::lazy_static;
 #[cfg(feature = "metrics")]
 use prometheus::Registry;
 use rand::Rng;
@@ -29,6 +31,14 @@ use std::{
 use tracing::*;
 use tracing_futures::Instrument;
 
+type MpscListeners = HashMap<u64, mpsc::UnboundedSender<MpscEndpoint>>;
+
+lazy_static! {
+    /// `Networks` of this process that listen on a `ProtocolAddr::Mpsc`
+    static ref MPSC_LISTENERS: std::sync::Mutex<MpscListeners> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
 /// Naming of Channels `x2x`
 ///  - a: api
 ///  - s: scheduler
@@ -46,9 +56,28 @@ struct ParticipantInfo {
 }
 
 type A2sListen = (ProtocolAddr, oneshot::Sender<io::Result<()>>);
-type A2sConnect = (ProtocolAddr, oneshot::Sender<io::Result<Participant>>);
+pub(crate) type A2sConnect = (ProtocolAddr, ConnectRequester);
 type A2sDisconnect = (Pid, S2bShutdownBparticipant);
 
+/// Who is waiting for a channel to be connected
+#[derive(Debug)]
+pub(crate) enum ConnectRequester {
+    /// `Network::connect`, waiting for a new `Participant`
+    Network(oneshot::Sender<io::Result<Participant>>),
+    /// `Participant::add_channel`, waiting for the channel to be added to the
+    /// `Participant` with this `Pid`
+    Participant(Pid, oneshot::Sender<io::Result<()>>),
+}
+
+impl ConnectRequester {
+    fn fail(self, e: io::Error) {
+        match self {
+            ConnectRequester::Network(s) => s.send(Err(e)).unwrap(),
+            ConnectRequester::Participant(_, s) => s.send(Err(e)).unwrap(),
+        }
+    }
+}
+
 #[derive(Debug)]
 struct ControlChannels {
     a2s_listen_r: mpsc::UnboundedReceiver<A2sListen>,
@@ -61,6 +90,7 @@ struct ControlChannels {
 #[derive(Debug, Clone)]
 struct ParticipantChannels {
     s2a_connected_s: mpsc::UnboundedSender<Participant>,
+    a2s_connect_s: mpsc::UnboundedSender<A2sConnect>,
     a2s_disconnect_s: mpsc::UnboundedSender<A2sDisconnect>,
     b2s_prio_statistic_s: mpsc::UnboundedSender<B2sPrioStatistic>,
 }
@@ -108,6 +138,7 @@ impl Scheduler {
 
         let participant_channels = ParticipantChannels {
             s2a_connected_s,
+            a2s_connect_s: a2s_connect_s.clone(),
             a2s_disconnect_s,
             b2s_prio_statistic_s,
         };
@@ -188,15 +219,9 @@ impl Scheduler {
         trace!("Stop listen_mgr");
     }
 
-    async fn connect_mgr(
-        &self,
-        mut a2s_connect_r: mpsc::UnboundedReceiver<(
-            ProtocolAddr,
-            oneshot::Sender<io::Result<Participant>>,
-        )>,
-    ) {
+    async fn connect_mgr(&self, mut a2s_connect_r: mpsc::UnboundedReceiver<A2sConnect>) {
         trace!("Start connect_mgr");
-        while let Some((addr, pid_sender)) = a2s_connect_r.next().await {
+        while let Some((addr, requester)) = a2s_connect_r.next().await {
             let (protocol, handshake) = match addr {
                 ProtocolAddr::Tcp(addr) => {
                     #[cfg(feature = "metrics")]
@@ -207,7 +232,7 @@ impl Scheduler {
                     let stream = match net::TcpStream::connect(addr).await {
                         Ok(stream) => stream,
                         Err(e) => {
-                            pid_sender.send(Err(e)).unwrap();
+                            requester.fail(e);
                             continue;
                         },
                     };
@@ -230,12 +255,12 @@ impl Scheduler {
                     let socket = match net::UdpSocket::bind("0.0.0.0:0").await {
                         Ok(socket) => Arc::new(socket),
                         Err(e) => {
-                            pid_sender.send(Err(e)).unwrap();
+                            requester.fail(e);
                             continue;
                         },
                     };
                     if let Err(e) = socket.connect(addr).await {
-                        pid_sender.send(Err(e)).unwrap();
+                        requester.fail(e);
                         continue;
                     };
                     info!("Connecting Udp to: {}", addr);
@@ -253,9 +278,38 @@ impl Scheduler {
                     );
                     (Protocols::Udp(protocol), true)
                 },
-                _ => unimplemented!(),
+                ProtocolAddr::Mpsc(addr) => {
+                    #[cfg(feature = "metrics")]
+                    self.metrics
+                        .connect_requests_total
+                        .with_label_values(&["mpsc"])
+                        .inc();
+                    let (endpoint, remote_endpoint) = MpscEndpoint::pair();
+                    let listener_found = MPSC_LISTENERS
+                        .lock()
+                        .unwrap()
+                        .get(&addr)
+                        .map_or(false, |listener| {
+                            listener.unbounded_send(remote_endpoint).is_ok()
+                        });
+                    if !listener_found {
+                        requester.fail(io::Error::new(
+                            io::ErrorKind::ConnectionRefused,
+                            "no network is listening on this mpsc address",
+                        ));
+                        continue;
+                    }
+                    info!("Connecting Mpsc to: {}", addr);
+                    let protocol = MpscProtocol::new(
+                        endpoint,
+                        sim::link_conditions(addr),
+                        #[cfg(feature = "metrics")]
+                        Arc::clone(&self.metrics),
+                    );
+                    (Protocols::Mpsc(protocol), false)
+                },
             };
-            self.init_protocol(protocol, Some(pid_sender), handshake)
+            self.init_protocol(protocol, Some(requester), handshake)
                 .await;
         }
         trace!("Stop connect_mgr");
@@ -454,7 +508,44 @@ impl Scheduler {
                     udp_data_sender.send(datavec).await.unwrap();
                 }
             },
-            _ => unimplemented!(),
+            ProtocolAddr::Mpsc(addr) => {
+                let (listener_s, mut listener_r) = mpsc::unbounded::<MpscEndpoint>();
+                {
+                    let mut listeners = MPSC_LISTENERS.lock().unwrap();
+                    if listeners.get(&addr).map_or(false, |l| !l.is_closed()) {
+                        info!(
+                            ?addr,
+                            "Listener couldn't be started as mpsc address is in use"
+                        );
+                        s2a_listen_result_s
+                            .send(Err(io::Error::new(
+                                io::ErrorKind::AddrInUse,
+                                "another network is already listening on this mpsc address",
+                            )))
+                            .unwrap();
+                        return;
+                    }
+                    listeners.insert(addr, listener_s);
+                }
+                s2a_listen_result_s.send(Ok(())).unwrap();
+                trace!(?addr, "Listener bound");
+                let mut end_receiver = s2s_stop_listening_r.fuse();
+                while let Some(endpoint) = select! {
+                    next = listener_r.next().fuse() => next,
+                    _ = end_receiver => None,
+                } {
+                    info!("Accepting Mpsc from: {}", addr);
+                    let protocol = MpscProtocol::new(
+                        endpoint,
+                        sim::link_conditions(addr),
+                        #[cfg(feature = "metrics")]
+                        Arc::clone(&self.metrics),
+                    );
+                    self.init_protocol(Protocols::Mpsc(protocol), None, true)
+                        .await;
+                }
+                MPSC_LISTENERS.lock().unwrap().remove(&addr);
+            },
         }
         trace!(?addr, "Ending channel creator");
     }
@@ -486,7 +577,7 @@ impl Scheduler {
     async fn init_protocol(
         &self,
         protocol: Protocols,
-        s2a_return_pid_s: Option<oneshot::Sender<io::Result<Participant>>>,
+        requester: Option<ConnectRequester>,
         send_handshake: bool,
     ) {
         //channels are unknown till PID is known!
@@ -524,14 +615,37 @@ impl Scheduler {
                     .instrument(tracing::info_span!("handshake", ?cid))
                     .await
                 {
-                    Ok((pid, sid, secret, leftover_cid_frame)) => {
+                    Ok((pid, sid, secret, session_key, leftover_cid_frame)) => {
                         trace!(
                             ?cid,
                             ?pid,
                             "Detected that my channel is ready!, activating it :)"
                         );
+                        if let Some(ConnectRequester::Participant(expected_pid, _)) = &requester {
+                            if *expected_pid != pid {
+                                warn!(
+                                    ?cid,
+                                    ?pid,
+                                    ?expected_pid,
+                                    "Channel to add leads to another participant, dropping it"
+                                );
+                                requester.unwrap().fail(std::io::Error::new(
+                                    std::io::ErrorKind::InvalidData,
+                                    "address belongs to another participant",
+                                ));
+                                return;
+                            }
+                        }
                         let mut participants = participants.lock().await;
                         if !participants.contains_key(&pid) {
+                            if let Some(ConnectRequester::Participant(..)) = &requester {
+                                debug!(?cid, ?pid, "Participant to add the channel to is gone");
+                                requester.unwrap().fail(std::io::Error::new(
+                                    std::io::ErrorKind::NotConnected,
+                                    "participant is no longer connected",
+                                ));
+                                return;
+                            }
                             debug!(?cid, "New participant connected via a channel");
                             let (
                                 bparticipant,
@@ -542,6 +656,7 @@ impl Scheduler {
                             ) = BParticipant::new(
                                 pid,
                                 sid,
+                                session_key,
                                 #[cfg(feature = "metrics")]
                                 Arc::clone(&metrics),
                             );
@@ -551,6 +666,7 @@ impl Scheduler {
                                 pid,
                                 a2b_stream_open_s,
                                 b2a_stream_opened_r,
+                                participant_channels.a2s_connect_s,
                                 participant_channels.a2s_disconnect_s,
                             );
 
@@ -583,7 +699,7 @@ impl Scheduler {
                                 .await
                                 .unwrap();
                             b2s_create_channel_done_r.await.unwrap();
-                            if let Some(pid_oneshot) = s2a_return_pid_s {
+                            if let Some(ConnectRequester::Network(pid_oneshot)) = requester {
                                 // someone is waiting with `connect`, so give them their PID
                                 pid_oneshot.send(Ok(participant)).unwrap();
                             } else {
@@ -607,39 +723,68 @@ impl Scheduler {
                                     ?secret,
                                     "Detected incompatible Secret!, this is probably an attack!"
                                 );
-                                error!(?cid, "Just dropping here, TODO handle this correctly!");
-                                //TODO
-                                if let Some(pid_oneshot) = s2a_return_pid_s {
+                                if let Some(requester) = requester {
                                     // someone is waiting with `connect`, so give them their Error
+                                    requester.fail(std::io::Error::new(
+                                        std::io::ErrorKind::PermissionDenied,
+                                        "invalid secret, denying connection",
+                                    ));
+                                }
+                                return;
+                            }
+                            let mut s2b_create_channel_s = pi.s2b_create_channel_s.clone();
+                            drop(participants);
+                            debug!(?cid, ?pid, "Adding channel to existing participant");
+                            let (b2s_create_channel_done_s, b2s_create_channel_done_r) =
+                                oneshot::channel();
+                            let added = s2b_create_channel_s
+                                .send((
+                                    cid,
+                                    sid,
+                                    protocol,
+                                    leftover_cid_frame,
+                                    b2s_create_channel_done_s,
+                                ))
+                                .await
+                                .is_ok()
+                                && b2s_create_channel_done_r.await.is_ok();
+                            match requester {
+                                Some(ConnectRequester::Participant(_, s)) => {
+                                    s.send(if added {
+                                        Ok(())
+                                    } else {
+                                        Err(std::io::Error::new(
+                                            std::io::ErrorKind::NotConnected,
+                                            "participant is no longer connected",
+                                        ))
+                                    })
+                                    .unwrap();
+                                },
+                                Some(ConnectRequester::Network(pid_oneshot)) => {
+                                    // the channel still serves the existing participant
                                     pid_oneshot
                                         .send(Err(std::io::Error::new(
-                                            std::io::ErrorKind::PermissionDenied,
-                                            "invalid secret, denying connection",
+                                            std::io::ErrorKind::AlreadyExists,
+                                            "already connected to this participant, the channel \
+                                             was added to it",
                                         )))
                                         .unwrap();
-                                }
-                                return;
+                                },
+                                None => {},
                             }
-                            error!(
-                                ?cid,
-                                "Ufff i cant answer the pid_oneshot. as i need to create the SAME \
-                                 participant. maybe switch to ARC"
-                            );
                         }
                         //From now on this CHANNEL can receiver other frames!
                         // move directly to participant!
                     },
                     Err(()) => {
                         debug!(?cid, "Handshake from a new connection failed");
-                        if let Some(pid_oneshot) = s2a_return_pid_s {
+                        if let Some(requester) = requester {
                             // someone is waiting with `connect`, so give them their Error
                             trace!(?cid, "returning the Err to api who requested the connect");
-                            pid_oneshot
-                                .send(Err(std::io::Error::new(
-                                    std::io::ErrorKind::PermissionDenied,
-                                    "Handshake failed, denying connection",
-                                )))
-                                .unwrap();
+                            requester.fail(std::io::Error::new(
+                                std::io::ErrorKind::PermissionDenied,
+                                "Handshake failed, denying connection",
+                            ));
                         }
                     },
                 }
@@ -0,0 +1,285 @@
+//! Simulated network conditions for in-process connections
+//!
+//! [`Networks`] can listen on and connect to [`ProtocolAddr::Mpsc`]
+//! addresses, which connects their [`Participants`] within the same process
+//! without any sockets. Frames on such a connection are held back according
+//! to the [`LinkConditions`] set for its address with
+//! [`set_link_conditions`], so client and server code can be tested against
+//! high latency, jitter, loss and reordering in CI.
+//!
+//! Frames that set up the connection or open and close streams are never lost
+//! nor reordered, so that connections can always be established. A reliable
+//! link behaves like TCP: a lost frame isn't gone but is sent again, and the
+//! frames behind it have to wait for it. On an unreliable link lost message
+//! frames are gone, like on UDP.
+//!
+//! ```rust
+//! use futures::executor::block_on;
+//! use std::time::Duration;
+//! use veloren_network::{
+//!     sim::{set_link_conditions, LinkConditions},
+//!     Network, Pid, Promises, ProtocolAddr,
+//! };
+//!
+//! # fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+//! set_link_conditions(7, LinkConditions {
+//!     latency: Duration::from_millis(50),
+//!     jitter: Duration::from_millis(20),
+//!     loss: 0.1,
+//!     ..LinkConditions::default()
+//! });
+//! let (server, f) = Network::new(Pid::new());
+//! std::thread::spawn(f);
+//! let (client, f) = Network::new(Pid::new());
+//! std::thread::spawn(f);
+//! block_on(async {
+//!     server.listen(ProtocolAddr::Mpsc(7)).await?;
+//!     let p1 = client.connect(ProtocolAddr::Mpsc(7)).await?;
+//!     let mut s1 = p1
+//!         .open(16, Promises::ORDERED | Promises::CONSISTENCY)
+//!         .await?;
+//!     s1.send("Hello Server")?;
+//!     let p2 = server.connected().await?;
+//!     let mut s2 = p2.opened().await?;
+//!     assert_eq!(s2.recv::<String>().await?, "Hello Server");
+//!     Ok(())
+//! })
+//! # }
+//! ```
+//!
+//! [`Networks`]: crate::api::Network
+//! [`Participants`]: crate::api::Participant
+//! [`ProtocolAddr::Mpsc`]: crate::api::ProtocolAddr::Mpsc
+use lazy_static::lazy_static;
+use rand::Rng;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Lowest time after which a lost frame is sent again on a reliable link
+const MIN_RETRANSMIT_DELAY: Duration = Duration::from_millis(200);
+/// Loss on reliable links is capped so that frames are eventually delivered
+const MAX_RELIABLE_LOSS: f32 = 0.9;
+
+lazy_static! {
+    static ref LINK_CONDITIONS: Mutex<HashMap<u64, Arc<RwLock<LinkConditions>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Conditions of the frames sent in either direction over an in-process
+/// connection
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LinkConditions {
+    /// Added to the time each frame takes
+    pub latency: Duration,
+    /// Largest random delay added on top of the latency
+    pub jitter: Duration,
+    /// Chance of each frame being lost, on a reliable link only on its first
+    /// try
+    pub loss: f32,
+    /// Chance of each frame being held back long enough for later frames to
+    /// overtake it. Only unreliable links reorder frames
+    pub reorder: f32,
+    /// Whether lost frames are sent again and frames arrive in order, which
+    /// also makes the [`Participant`] prefer this link for reliable streams
+    ///
+    /// [`Participant`]: crate::api::Participant
+    pub reliable: bool,
+    /// Whether the link is cut, which loses all frames and closes the
+    /// connections over it like a failed network would
+    pub cut: bool,
+}
+
+impl Default for LinkConditions {
+    fn default() -> Self {
+        Self {
+            latency: Duration::default(),
+            jitter: Duration::default(),
+            loss: 0.0,
+            reorder: 0.0,
+            reliable: true,
+            cut: false,
+        }
+    }
+}
+
+/// Sets the conditions of all connections to the [`ProtocolAddr::Mpsc`]
+/// address `addr`. Connections that already exist use them from their next
+/// frame on.
+///
+/// [`ProtocolAddr::Mpsc`]: crate::api::ProtocolAddr::Mpsc
+pub fn set_link_conditions(addr: u64, conditions: LinkConditions) {
+    *link_conditions(addr).write().unwrap() = conditions;
+}
+
+pub(crate) fn link_conditions(addr: u64) -> Arc<RwLock<LinkConditions>> {
+    Arc::clone(
+        LINK_CONDITIONS
+            .lock()
+            .unwrap()
+            .entry(addr)
+            .or_insert_with(|| Arc::new(RwLock::new(LinkConditions::default()))),
+    )
+}
+
+impl LinkConditions {
+    fn delay(&self, rng: &mut impl Rng) -> Duration {
+        self.latency + self.jitter.mul_f32(rng.gen())
+    }
+}
+
+/// Frames in one direction that are held back until they are due
+#[derive(Debug)]
+pub(crate) struct SimulatedLink<T> {
+    /// Held back frames, ordered by the time they are due
+    queue: VecDeque<(Instant, T)>,
+    /// Time the last frame that has to arrive in order is due, which later
+    /// frames can't overtake
+    last_due: Option<Instant>,
+}
+
+impl<T> Default for SimulatedLink<T> {
+    fn default() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            last_due: None,
+        }
+    }
+}
+
+impl<T> SimulatedLink<T> {
+    pub fn is_empty(&self) -> bool { self.queue.is_empty() }
+
+    /// Holds back a frame. `droppable` frames may be lost or reordered, the
+    /// others are only delayed.
+    pub fn push(
+        &mut self,
+        now: Instant,
+        frame: T,
+        droppable: bool,
+        conditions: &LinkConditions,
+        rng: &mut impl Rng,
+    ) {
+        let mut due = now + conditions.delay(rng);
+        let ordered = if !droppable {
+            true
+        } else if conditions.reliable {
+            let retransmit = MIN_RETRANSMIT_DELAY.max(conditions.latency * 2);
+            while rng.gen::<f32>() < conditions.loss.min(MAX_RELIABLE_LOSS) {
+                due += retransmit;
+            }
+            true
+        } else {
+            if rng.gen::<f32>() < conditions.loss {
+                return;
+            }
+            if rng.gen::<f32>() < conditions.reorder {
+                due += conditions.latency + conditions.jitter;
+                false
+            } else {
+                true
+            }
+        };
+        if ordered {
+            if let Some(last_due) = self.last_due {
+                due = due.max(last_due);
+            }
+            self.last_due = Some(due);
+        }
+        let pos = self
+            .queue
+            .iter()
+            .rposition(|(other_due, _)| *other_due <= due)
+            .map_or(0, |i| i + 1);
+        self.queue.insert(pos, (due, frame));
+    }
+
+    /// Returns the next frame that is due at `now`
+    pub fn pop_due(&mut self, now: Instant) -> Option<T> {
+        match self.queue.front() {
+            Some((due, _)) if *due <= now => self.queue.pop_front().map(|(_, frame)| frame),
+            _ => None,
+        }
+    }
+
+    /// Time the next held back frame is due
+    pub fn next_due(&self) -> Option<Instant> { self.queue.front().map(|(due, _)| *due) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sim::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn drain(link: &mut SimulatedLink<u32>, now: Instant) -> Vec<u32> {
+        std::iter::from_fn(|| link.pop_due(now)).collect()
+    }
+
+    #[test]
+    fn frames_are_held_back() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let conditions = LinkConditions {
+            latency: Duration::from_millis(100),
+            jitter: Duration::from_millis(50),
+            ..LinkConditions::default()
+        };
+        let mut link = SimulatedLink::default();
+        let start = Instant::now();
+        for i in 0..10 {
+            link.push(start, i, true, &conditions, &mut rng);
+        }
+        assert!(drain(&mut link, start + Duration::from_millis(99)).is_empty());
+        assert!(link.next_due().unwrap() >= start + Duration::from_millis(100));
+        // jitter doesn't reorder a reliable link
+        assert_eq!(
+            drain(&mut link, start + Duration::from_millis(150)),
+            (0..10).collect::<Vec<_>>()
+        );
+        assert!(link.is_empty());
+    }
+
+    #[test]
+    fn reliable_links_resend_lost_frames() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let conditions = LinkConditions {
+            loss: 0.5,
+            ..LinkConditions::default()
+        };
+        let mut link = SimulatedLink::default();
+        let start = Instant::now();
+        for i in 0..100 {
+            link.push(start, i, true, &conditions, &mut rng);
+        }
+        let now = start + Duration::from_secs(3600);
+        assert_eq!(drain(&mut link, now), (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unreliable_links_lose_and_reorder_frames() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let conditions = LinkConditions {
+            latency: Duration::from_millis(100),
+            loss: 0.2,
+            reorder: 0.2,
+            reliable: false,
+            ..LinkConditions::default()
+        };
+        let mut link = SimulatedLink::default();
+        let start = Instant::now();
+        for i in 0..100 {
+            link.push(start, i, true, &conditions, &mut rng);
+        }
+        // frames that can't be dropped always arrive
+        link.push(start, 100, false, &conditions, &mut rng);
+        let received = drain(&mut link, start + Duration::from_secs(1));
+        assert!(received.len() > 50 && received.len() < 95);
+        assert_ne!(received, {
+            let mut sorted = received.clone();
+            sorted.sort_unstable();
+            sorted
+        });
+        assert!(received.contains(&100));
+    }
+}
@@ -1,6 +1,6 @@
 use bitflags::bitflags;
 use rand::Rng;
-use std::convert::TryFrom;
+use std::{convert::TryFrom, time::Duration};
 
 pub type Mid = u64;
 pub type Cid = u64;
@@ -25,18 +25,61 @@ bitflags! {
         /// [`Stream`](crate::api::Stream)
         #[cfg(feature = "compression")]
         const COMPRESSED = 0b00001000;
-        /// this will enable the internal encryption on this
-        /// [`Stream`](crate::api::Stream)
+        /// EXPERIMENTAL: this will enable the internal encryption on this
+        /// [`Stream`](crate::api::Stream), messages can't be read or altered
+        /// unnoticed by someone that only listens on the way. The key is
+        /// negotiated when the [`Participant`](crate::api::Participant)
+        /// connects, without authenticating the remote side, so a man in the
+        /// middle can read and alter everything. Don't rely on it for secrets
+        /// like passwords.
         const ENCRYPTED = 0b00010000;
+        /// messages on this [`Stream`](crate::api::Stream) are only worth
+        /// something while they are fresh, like voice: they are dropped if
+        /// they couldn't be sent within [`REALTIME_MESSAGE_LIFETIME`], and
+        /// prefer the lowest latency channel even if it loses or reorders
+        /// frames, so `ORDERED` and `GUARANTEED_DELIVERY` aren't kept on such
+        /// a stream
+        const REALTIME = 0b00100000;
     }
 }
 
+/// Time after which messages on a [`Stream`] with [`Promises::REALTIME`] are
+/// dropped if they couldn't be sent yet
+///
+/// [`Stream`]: crate::api::Stream
+pub const REALTIME_MESSAGE_LIFETIME: Duration = Duration::from_millis(100);
+
 impl Promises {
     pub const fn to_le_bytes(self) -> [u8; 1] { self.bits.to_le_bytes() }
 }
 
+/// Algorithm a [`Stream`] with [`Promises::COMPRESSED`] compresses its
+/// messages with. Every message names the algorithm it was compressed with,
+/// so both sides of a `Stream` can choose their own.
+///
+/// [`Stream`]: crate::api::Stream
+#[cfg(feature = "compression")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    /// Fast enough for messages sent every tick
+    Lz4,
+    /// Slower, but compresses large messages like terrain chunks further
+    Deflate,
+}
+
+#[cfg(feature = "compression")]
+impl Default for Compression {
+    fn default() -> Self { Compression::Lz4 }
+}
+
 pub(crate) const VELOREN_MAGIC_NUMBER: [u8; 7] = [86, 69, 76, 79, 82, 69, 78]; //VELOREN
-pub const VELOREN_NETWORK_VERSION: [u32; 3] = [0, 5, 0];
+pub const VELOREN_NETWORK_VERSION: [u32; 3] = [3, 0, 0];
+/// Oldest version of the network protocol that this one can still talk to.
+/// Changes that older peers can cope with only bump the minor version, breaking
+/// changes bump the major version and this one. The layout of the messages
+/// sent over the network counts too, so adding a field to one of them is a
+/// breaking change.
+pub const VELOREN_NETWORK_MIN_VERSION: [u32; 3] = [3, 0, 0];
 pub(crate) const STREAM_ID_OFFSET1: Sid = Sid::new(0);
 pub(crate) const STREAM_ID_OFFSET2: Sid = Sid::new(u64::MAX / 2);
 
@@ -65,6 +108,8 @@ pub(crate) enum Frame {
     Init {
         pid: Pid,
         secret: u128,
+        /// x25519 public key used to derive the keys of encrypted streams
+        public_key: [u8; 32],
     },
     Shutdown, /* Shutdown this channel gracefully, if all channels are shutdown, Participant
                * is deleted */
@@ -139,10 +184,11 @@ impl Frame {
         }
     }
 
-    pub fn gen_init(buf: [u8; 32]) -> Self {
+    pub fn gen_init(buf: [u8; 64]) -> Self {
         Frame::Init {
             pid: Pid::from_le_bytes(*<&[u8; 16]>::try_from(&buf[0..16]).unwrap()),
             secret: u128::from_le_bytes(*<&[u8; 16]>::try_from(&buf[16..32]).unwrap()),
+            public_key: *<&[u8; 32]>::try_from(&buf[32..64]).unwrap(),
         }
     }
 
@@ -289,6 +335,12 @@ impl std::fmt::Display for Sid {
     }
 }
 
+/// Whether a peer on the given network version can connect. Peers on a newer
+/// version decide for themselves whether they can still talk to us.
+pub(crate) fn is_compatible_version(version: [u32; 3]) -> bool {
+    version[0] == VELOREN_NETWORK_VERSION[0] && version >= VELOREN_NETWORK_MIN_VERSION
+}
+
 fn sixlet_to_str(sixlet: u128) -> char {
     b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"[sixlet as usize] as char
 }
@@ -318,6 +370,18 @@ mod tests {
         assert_eq!(format!("{}", Pid::fake(2)), "CCCCCC");
     }
 
+    #[test]
+    fn compatible_versions() {
+        assert!(is_compatible_version(VELOREN_NETWORK_VERSION));
+        assert!(is_compatible_version(VELOREN_NETWORK_MIN_VERSION));
+        let mut newer_minor = VELOREN_NETWORK_VERSION;
+        newer_minor[1] += 1;
+        assert!(is_compatible_version(newer_minor));
+        let mut newer_major = VELOREN_NETWORK_VERSION;
+        newer_major[0] += 1;
+        assert!(!is_compatible_version(newer_major));
+    }
+
     #[test]
     fn test_sixlet_to_str() {
         assert_eq!(sixlet_to_str(0), 'A');
@@ -1,7 +1,7 @@
 use lazy_static::*;
 use std::{
     net::SocketAddr,
-    sync::atomic::{AtomicU16, Ordering},
+    sync::atomic::{AtomicU16, AtomicU64, Ordering},
     thread,
     time::Duration,
 };
@@ -78,3 +78,12 @@ pub fn udp() -> veloren_network::ProtocolAddr {
     let port = PORTS.fetch_add(1, Ordering::Relaxed);
     veloren_network::ProtocolAddr::Udp(SocketAddr::from(([127, 0, 0, 1], port)))
 }
+
+#[allow(dead_code)]
+pub fn mpsc() -> veloren_network::ProtocolAddr {
+    lazy_static! {
+        static ref ADDRS: AtomicU64 = AtomicU64::new(5000);
+    }
+    let addr = ADDRS.fetch_add(1, Ordering::Relaxed);
+    veloren_network::ProtocolAddr::Mpsc(addr)
+}
@@ -2,9 +2,15 @@ use async_std::task;
 use task::block_on;
 use veloren_network::{NetworkError, StreamError};
 mod helper;
-use helper::{network_participant_stream, tcp, udp};
-use std::io::ErrorKind;
-use veloren_network::{Network, Pid, Promises, ProtocolAddr};
+use helper::{mpsc, network_participant_stream, tcp, udp};
+use std::{
+    io::ErrorKind,
+    time::{Duration, Instant},
+};
+use veloren_network::{
+    sim::{set_link_conditions, LinkConditions},
+    Network, Pid, Promises, ProtocolAddr,
+};
 
 #[test]
 #[ignore]
@@ -69,7 +75,53 @@ fn stream_simple_udp_3msg() {
 }
 
 #[test]
-#[ignore]
+fn stream_simple_mpsc() {
+    let (_, _) = helper::setup(false, 0);
+    let (_n_a, _p_a, mut s1_a, _n_b, _p_b, mut s1_b) = block_on(network_participant_stream(mpsc()));
+
+    s1_a.send("Hello World").unwrap();
+    assert_eq!(block_on(s1_b.recv()), Ok("Hello World".to_string()));
+}
+
+#[test]
+fn mpsc_connect_without_listener() {
+    let (_, _) = helper::setup(false, 0);
+    let (network, f) = Network::new(Pid::fake(0));
+    std::thread::spawn(f);
+    match block_on(network.connect(mpsc())) {
+        Err(NetworkError::ConnectFailed(e)) => assert_eq!(e.kind(), ErrorKind::ConnectionRefused),
+        _ => panic!("connecting to a mpsc address nobody listens on must fail"),
+    }
+}
+
+#[test]
+fn mpsc_bad_conditions() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let (_, _) = helper::setup(false, 0);
+    let addr = mpsc();
+    let latency = Duration::from_millis(50);
+    if let ProtocolAddr::Mpsc(addr) = addr {
+        set_link_conditions(addr, LinkConditions {
+            latency,
+            jitter: Duration::from_millis(30),
+            loss: 0.3,
+            ..LinkConditions::default()
+        });
+    }
+    let (_n_a, _p_a, mut s1_a, _n_b, _p_b, mut s1_b) = block_on(network_participant_stream(addr));
+
+    let start = Instant::now();
+    for i in 0..100u32 {
+        s1_a.send(i)?;
+    }
+    // lost frames are sent again, so everything arrives in order
+    for i in 0..100u32 {
+        assert_eq!(block_on(s1_b.recv::<u32>())?, i);
+    }
+    assert!(start.elapsed() >= latency);
+    Ok(())
+}
+
+#[test]
 fn tcp_and_udp_2_connections() -> std::result::Result<(), Box<dyn std::error::Error>> {
     let (_, _) = helper::setup(false, 0);
     let (network, f) = Network::new(Pid::new());
@@ -77,19 +129,127 @@ fn tcp_and_udp_2_connections() -> std::result::Result<(), Box<dyn std::error::Er
     std::thread::spawn(f);
     std::thread::spawn(fr);
     block_on(async {
-        remote
-            .listen(ProtocolAddr::Tcp("0.0.0.0:2000".parse().unwrap()))
-            .await?;
-        remote
-            .listen(ProtocolAddr::Udp("0.0.0.0:2001".parse().unwrap()))
-            .await?;
-        let p1 = network
-            .connect(ProtocolAddr::Tcp("127.0.0.1:2000".parse().unwrap()))
+        let (tcp, udp) = (tcp(), udp());
+        remote.listen(tcp.clone()).await?;
+        remote.listen(udp.clone()).await?;
+        let p1 = network.connect(tcp).await?;
+        let p1_remote = remote.connected().await?;
+        // the second protocol leads to the same participant
+        assert!(network.connect(udp.clone()).await.is_err());
+        p1.add_channel(udp).await?;
+
+        // one stream for each of the channels
+        let mut s_unordered = p1.open(16, Promises::empty()).await?;
+        let mut r_1 = p1_remote.opened().await?;
+        let mut s_ordered = p1
+            .open(16, Promises::ORDERED | Promises::CONSISTENCY)
             .await?;
-        let p2 = network
-            .connect(ProtocolAddr::Udp("127.0.0.1:2001".parse().unwrap()))
+        let mut r_2 = p1_remote.opened().await?;
+        s_unordered.send(42u32)?;
+        s_ordered.send("Hello World")?;
+        assert_eq!(r_1.recv::<u32>().await?, 42);
+        assert_eq!(r_2.recv::<String>().await?, "Hello World".to_string());
+        Ok(())
+    })
+}
+
+#[test]
+fn failed_channel_closes_reliable_streams() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let (_, _) = helper::setup(false, 0);
+    let (network, f) = Network::new(Pid::new());
+    let (remote, fr) = Network::new(Pid::new());
+    std::thread::spawn(f);
+    std::thread::spawn(fr);
+    let addrs = [mpsc(), mpsc(), mpsc()];
+    let ids = addrs
+        .iter()
+        .map(|addr| match addr {
+            ProtocolAddr::Mpsc(id) => *id,
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    // Two reliable channels and an unreliable one in between, all of them losing
+    // frames
+    for (i, id) in ids.iter().enumerate() {
+        set_link_conditions(*id, LinkConditions {
+            reliable: i != 1,
+            loss: 0.2,
+            ..LinkConditions::default()
+        });
+    }
+    block_on(async {
+        for addr in &addrs {
+            remote.listen(addr.clone()).await?;
+        }
+        let p1 = network.connect(addrs[0].clone()).await?;
+        let p1_remote = remote.connected().await?;
+        p1.add_channel(addrs[1].clone()).await?;
+        p1.add_channel(addrs[2].clone()).await?;
+
+        let mut s_ordered = p1
+            .open(16, Promises::ORDERED | Promises::CONSISTENCY)
             .await?;
-        assert_eq!(&p1, &p2);
+        let mut r_ordered = p1_remote.opened().await?;
+        let mut s_unordered = p1.open(16, Promises::empty()).await?;
+        let mut r_unordered = p1_remote.opened().await?;
+        for i in 0..100u32 {
+            s_ordered.send(i)?;
+        }
+        for i in 0..100u32 {
+            assert_eq!(r_ordered.recv::<u32>().await?, i);
+        }
+        // Some of these get lost, but not all of them
+        for i in 0..100u32 {
+            s_unordered.send(i)?;
+        }
+        r_unordered.recv::<u32>().await?;
+
+        // kill the first reliable channel, the ordered stream used it and must close
+        set_link_conditions(ids[0], LinkConditions {
+            cut: true,
+            ..LinkConditions::default()
+        });
+        let start = Instant::now();
+        while s_ordered.send(0u32).is_ok() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            task::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(matches!(
+            s_ordered.send(0u32),
+            Err(StreamError::StreamClosed)
+        ));
+        assert!(matches!(
+            r_ordered.recv::<u32>().await,
+            Err(StreamError::StreamClosed)
+        ));
+
+        // the participant stays connected over the remaining channels, and new
+        // ordered streams use the reliable one: every message arrives, in order
+        for i in 0..100u32 {
+            s_unordered.send(i)?;
+        }
+        r_unordered.recv::<u32>().await?;
+        let mut s_new = p1.open(16, Promises::ORDERED).await?;
+        let mut r_new = p1_remote.opened().await?;
+        for i in 0..100u32 {
+            s_new.send(i)?;
+        }
+        for i in 0..100u32 {
+            assert_eq!(r_new.recv::<u32>().await?, i);
+        }
+
+        // the unreliable channel alone can't carry what has to arrive, so the
+        // participant closes with the last reliable channel
+        set_link_conditions(ids[2], LinkConditions {
+            cut: true,
+            ..LinkConditions::default()
+        });
+        let start = Instant::now();
+        while s_new.send(0u32).is_ok() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            task::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(p1.open(16, Promises::empty()).await.is_err());
         Ok(())
     })
 }
@@ -209,3 +369,32 @@ fn multiple_try_recv() {
     std::thread::sleep(std::time::Duration::from_secs(1));
     assert_eq!(s1_b.try_recv::<String>(), Err(StreamError::StreamClosed));
 }
+
+#[test]
+fn stream_encrypted() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let (_, _) = helper::setup(false, 0);
+    let (n_a, f_a) = Network::new(Pid::fake(0));
+    std::thread::spawn(f_a);
+    let (n_b, f_b) = Network::new(Pid::fake(1));
+    std::thread::spawn(f_b);
+    block_on(async {
+        let addr = tcp();
+        n_a.listen(addr.clone()).await?;
+        let p_b = n_b.connect(addr).await?;
+        let p_a = n_a.connected().await?;
+        let mut s_a = p_a
+            .open(16, Promises::ORDERED | Promises::ENCRYPTED)
+            .await?;
+        let mut s_b = p_b.opened().await?;
+
+        // spans multiple frames, all of them sealed on their own
+        let large: Vec<u8> = (0..5000u32).map(|i| i as u8).collect();
+        s_a.send(&large)?;
+        s_a.send("Hello World")?;
+        assert_eq!(s_b.recv::<Vec<u8>>().await?, large);
+        assert_eq!(s_b.recv::<String>().await?, "Hello World".to_string());
+        s_b.send(1337u32)?;
+        assert_eq!(s_a.recv::<u32>().await?, 1337);
+        Ok(())
+    })
+}
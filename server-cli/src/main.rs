@@ -159,6 +159,10 @@ fn main() -> io::Result<()> {
                 Event::ClientConnected { entity: _ } => info!("Client connected!"),
                 Event::ClientDisconnected { entity: _ } => info!("Client disconnected!"),
                 Event::Chat { entity: _, msg } => info!("[Client] {}", msg),
+                Event::Shutdown {
+                    grace_period,
+                    message,
+                } => shutdown_coordinator.initiate_shutdown(&mut server, grace_period, message),
             }
         }
 
@@ -0,0 +1,137 @@
+use common::{
+    comp::{CharacterState, Pos, Stats},
+    terrain::{self, TerrainGrid},
+};
+use vek::*;
+
+/// Height of the eyes above the feet, used as the origin of line of sight
+/// checks
+const EYE_HEIGHT: f32 = 1.6;
+
+/// Reasons for which an action requested by a client was rejected
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ActionViolation {
+    /// More actions were sent than a player could possibly perform
+    TooFrequent,
+    /// The target of the interaction is hidden behind terrain
+    NoLineOfSight,
+}
+
+impl ActionViolation {
+    /// Label used in metrics and logs
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ActionViolation::TooFrequent => "too_frequent",
+            ActionViolation::NoLineOfSight => "no_line_of_sight",
+        }
+    }
+
+    /// How much a single occurrence adds to a player's suspicion
+    pub fn severity(self) -> f32 {
+        match self {
+            // Lag spikes can bunch up legitimate actions, so be lenient
+            ActionViolation::TooFrequent => 0.5,
+            ActionViolation::NoLineOfSight => 2.0,
+        }
+    }
+}
+
+/// Whether a character is able to act at all, i.e. is neither dead nor
+/// stunned by a hit. Actions of such characters are expected from clients
+/// that haven't learned about the death or the hit yet, so they are dropped
+/// rather than treated as a violation.
+pub fn can_act(stats: Option<&Stats>, character_state: Option<&CharacterState>) -> bool {
+    stats.map_or(true, |s| !s.is_dead) && !character_state.map_or(false, |c| c.is_hit_reaction())
+}
+
+/// Check that nothing solid is between the eyes of a character standing at
+/// `pos` and the point `target`.
+pub fn validate_line_of_sight(
+    pos: &Pos,
+    target: Vec3<f32>,
+    terrain: &TerrainGrid,
+) -> Result<(), ActionViolation> {
+    let eyes = pos.0 + Vec3::unit_z() * EYE_HEIGHT;
+    if terrain::line_of_sight(terrain, eyes, target) {
+        Ok(())
+    } else {
+        Err(ActionViolation::NoLineOfSight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{
+        states,
+        terrain::{Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta},
+        vol::WriteVol,
+    };
+    use std::sync::Arc;
+
+    fn stats() -> Stats {
+        Stats::new(
+            "Tester".to_string(),
+            common::comp::Body::Humanoid(common::comp::humanoid::Body::random()),
+        )
+    }
+
+    #[test]
+    fn dead_characters_cant_act() {
+        let mut stats = stats();
+        assert!(can_act(Some(&stats), Some(&CharacterState::Idle)));
+        stats.is_dead = true;
+        assert!(!can_act(Some(&stats), Some(&CharacterState::Idle)));
+    }
+
+    #[test]
+    fn stunned_characters_cant_act() {
+        let stats = stats();
+        let stagger = CharacterState::Stagger(states::stagger::Data {
+            time_left: states::stagger::STAGGER_DURATION,
+            was_wielded: true,
+        });
+        let flinch = CharacterState::Flinch(states::flinch::Data {
+            time_left: states::flinch::FLINCH_DURATION,
+            was_wielded: true,
+        });
+        assert!(!can_act(Some(&stats), Some(&stagger)));
+        assert!(!can_act(Some(&stats), Some(&flinch)));
+        assert!(can_act(Some(&stats), Some(&CharacterState::Wielding)));
+        assert!(can_act(Some(&stats), None));
+    }
+
+    #[test]
+    fn open_air_has_line_of_sight() {
+        let terrain = TerrainGrid::new().unwrap();
+        assert_eq!(
+            validate_line_of_sight(&Pos(Vec3::zero()), Vec3::new(3.0, 2.0, 1.0), &terrain),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn walls_block_line_of_sight() {
+        // A wall at x = 10 in an otherwise empty chunk
+        let air = Block::air(SpriteKind::Empty);
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let mut chunk = TerrainChunk::new(0, air, air, TerrainChunkMeta::void());
+        for y in 0..32 {
+            for z in 0..8 {
+                chunk.set(Vec3::new(10, y, z), rock).unwrap();
+            }
+        }
+        let mut terrain = TerrainGrid::new().unwrap();
+        terrain.insert(Vec2::zero(), Arc::new(chunk));
+
+        let pos = Pos(Vec3::new(5.5, 16.5, 1.0));
+        assert_eq!(
+            validate_line_of_sight(&pos, Vec3::new(15.5, 16.5, 1.5), &terrain),
+            Err(ActionViolation::NoLineOfSight)
+        );
+        assert_eq!(
+            validate_line_of_sight(&pos, Vec3::new(8.5, 16.5, 1.5), &terrain),
+            Ok(())
+        );
+    }
+}
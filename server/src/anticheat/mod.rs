@@ -0,0 +1,231 @@
+//! Validation of data that the server otherwise has to take on trust from
+//! clients, and bookkeeping of how suspicious each player's behaviour has
+//! been so that admins can act on it.
+
+pub mod actions;
+pub mod movement;
+
+pub use actions::{can_act, validate_line_of_sight, ActionViolation};
+pub use movement::{validate_movement, MovementViolation};
+
+use crate::{metrics::PlayerMetrics, Settings};
+use common::{
+    comp::{Player, Pos},
+    state::Time,
+    terrain::TerrainGrid,
+};
+use serde::{Deserialize, Serialize};
+use specs::{Component, Entity as EcsEntity, FlaggedStorage, World, WorldExt};
+use specs_idvs::IdvStorage;
+use tracing::warn;
+use vek::*;
+
+/// How much suspicion decays per second of game time
+const SUSPICION_DECAY_PER_SEC: f32 = 0.05;
+/// Default suspicion above which the server responds to further violations
+pub const SUSPICION_WARN_THRESHOLD: f32 = 10.0;
+/// Maximum number of discrete actions (control events and actions) a player
+/// may send per second
+const MAX_ACTIONS_PER_SEC: u32 = 30;
+/// How quickly the extra speed granted by a server-applied impulse wears off,
+/// in blocks per second per second. Slower than friction and gravity, so a
+/// knocked back player is never rubber-banded.
+const IMPULSE_DECAY_PER_SEC: f32 = 20.0;
+
+/// What the server does about a player whose suspicion exceeds the
+/// configured threshold
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheatResponse {
+    /// Log a warning for every further violation
+    Log,
+    /// Log, and mark the player so that they show up in admin reports
+    Flag,
+    /// Flag, and disconnect the player
+    Kick,
+    /// Flag, and add the player to the banlist
+    Ban,
+}
+
+/// Server-only record of how often a player was caught sending invalid data.
+#[derive(Clone, Debug, Default)]
+pub struct Suspicion {
+    /// Decaying score, increased by each violation according to its severity
+    pub score: f32,
+    /// Total number of rejected movement updates since the player joined
+    pub movement_rejections: u32,
+    /// Total number of rejected actions since the player joined
+    pub action_rejections: u32,
+    /// Whether the player has exceeded the suspicion threshold
+    pub flagged: bool,
+    /// Time of the last update to `score`
+    last_update: f64,
+    /// Time at which the last `PlayerPhysics` update was accepted
+    pub(crate) last_accepted_physics: Option<f64>,
+    /// Kick or ban that still has to be carried out by the server
+    pending_response: Option<CheatResponse>,
+    /// Start of the current one second window used to rate limit actions
+    action_window_start: f64,
+    /// Number of actions sent during the current window
+    actions_in_window: u32,
+    /// Speed of the last impulse the server applied to the player
+    impulse_speed: f32,
+    /// Time at which `impulse_speed` was applied
+    impulse_time: f64,
+}
+
+impl Suspicion {
+    /// Increase the suspicion score by `amount`, decaying any previous score
+    /// first. Returns the new score.
+    pub fn flag(&mut self, amount: f32, time: f64) -> f32 {
+        self.score = self.score_at(time) + amount;
+        self.last_update = time;
+        self.score
+    }
+
+    /// The suspicion score after decaying it until `time`
+    pub fn score_at(&self, time: f64) -> f32 {
+        let elapsed = (time - self.last_update).max(0.0) as f32;
+        (self.score - elapsed * SUSPICION_DECAY_PER_SEC).max(0.0)
+    }
+
+    /// Record a violation of the given severity. Returns the new score if it
+    /// exceeds `threshold`, in which case `response` is applied.
+    pub fn report(
+        &mut self,
+        severity: f32,
+        time: f64,
+        threshold: f32,
+        response: CheatResponse,
+    ) -> Option<f32> {
+        let score = self.flag(severity, time);
+        if score <= threshold {
+            return None;
+        }
+
+        match response {
+            CheatResponse::Log => {},
+            CheatResponse::Flag => self.flagged = true,
+            CheatResponse::Kick | CheatResponse::Ban => {
+                self.flagged = true;
+                self.pending_response.get_or_insert(response);
+            },
+        }
+        Some(score)
+    }
+
+    /// Take the kick or ban that the server still has to carry out, if any
+    pub fn take_pending_response(&mut self) -> Option<CheatResponse> {
+        self.pending_response.take()
+    }
+
+    /// Record an impulse such as knockback that the server applied at `time`,
+    /// so that the resulting movement isn't rejected
+    pub fn record_impulse(&mut self, impulse: Vec3<f32>, time: f64) {
+        self.impulse_speed = self.impulse_speed_at(time) + impulse.magnitude();
+        self.impulse_time = time;
+    }
+
+    /// Extra speed that server-applied impulses still allow at `time`
+    pub fn impulse_speed_at(&self, time: f64) -> f32 {
+        let elapsed = (time - self.impulse_time).max(0.0) as f32;
+        (self.impulse_speed - elapsed * IMPULSE_DECAY_PER_SEC).max(0.0)
+    }
+
+    /// Count an action sent at `time` towards the rate limit
+    pub fn record_action(&mut self, time: f64) -> Result<(), ActionViolation> {
+        if time - self.action_window_start >= 1.0 {
+            self.action_window_start = time;
+            self.actions_in_window = 0;
+        }
+        self.actions_in_window += 1;
+
+        if self.actions_in_window > MAX_ACTIONS_PER_SEC {
+            Err(ActionViolation::TooFrequent)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Component for Suspicion {
+    type Storage = FlaggedStorage<Self, IdvStorage<Self>>;
+}
+
+/// Whether `entity` can see the point `target`, reporting it as suspicious if
+/// not
+pub fn check_line_of_sight(ecs: &World, entity: EcsEntity, target: Vec3<f32>) -> bool {
+    let verdict = match ecs.read_storage::<Pos>().get(entity) {
+        Some(pos) => validate_line_of_sight(pos, target, &ecs.read_resource::<TerrainGrid>()),
+        None => return false,
+    };
+    if let Err(violation) = verdict {
+        report_action_violation(ecs, entity, violation);
+    }
+    verdict.is_ok()
+}
+
+/// Record an action violation detected outside of the message system, e.g.
+/// while handling a server event.
+pub fn report_action_violation(ecs: &World, entity: EcsEntity, violation: ActionViolation) {
+    ecs.read_resource::<PlayerMetrics>()
+        .action_rejected
+        .with_label_values(&[violation.as_str()])
+        .inc();
+
+    let time = ecs.read_resource::<Time>().0;
+    let settings = ecs.read_resource::<Settings>();
+    let mut suspicions = ecs.write_storage::<Suspicion>();
+    let suspicion = match suspicions.entry(entity) {
+        Ok(entry) => entry.or_insert_with(Suspicion::default),
+        Err(_) => return,
+    };
+    suspicion.action_rejections += 1;
+    if let Some(score) = suspicion.report(
+        violation.severity(),
+        time,
+        settings.cheat_response_threshold,
+        settings.cheat_response,
+    ) {
+        let alias = ecs
+            .read_storage::<Player>()
+            .get(entity)
+            .map(|p| p.alias.clone());
+        warn!(
+            ?alias,
+            ?violation,
+            ?score,
+            "Rejected action from suspicious player"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_spam_is_rejected() {
+        let mut suspicion = Suspicion::default();
+        for _ in 0..MAX_ACTIONS_PER_SEC {
+            assert_eq!(suspicion.record_action(0.5), Ok(()));
+        }
+        assert_eq!(
+            suspicion.record_action(0.9),
+            Err(ActionViolation::TooFrequent)
+        );
+        // A new window starts after a second
+        assert_eq!(suspicion.record_action(1.6), Ok(()));
+    }
+
+    #[test]
+    fn kick_is_queued_once_over_threshold() {
+        let mut suspicion = Suspicion::default();
+        assert_eq!(suspicion.report(5.0, 0.0, 10.0, CheatResponse::Kick), None);
+        assert!(suspicion
+            .report(6.0, 0.0, 10.0, CheatResponse::Kick)
+            .is_some());
+        assert!(suspicion.flagged);
+        assert_eq!(suspicion.take_pending_response(), Some(CheatResponse::Kick));
+        assert_eq!(suspicion.take_pending_response(), None);
+    }
+}
@@ -0,0 +1,217 @@
+use common::{
+    comp::{Body, CharacterState, Pos},
+    terrain::TerrainGrid,
+    vol::ReadVol,
+};
+use vek::*;
+
+/// Any single update moving the player further than this is treated as a
+/// teleport, regardless of how much time has passed.
+const TELEPORT_THRESHOLD: f32 = 100.0;
+/// Distance that is always allowed, to absorb jitter and rounding
+const DISTANCE_LEEWAY: f32 = 2.0;
+/// Extra time granted on top of the measured interval between updates to
+/// absorb network jitter
+const TIME_LEEWAY: f32 = 0.25;
+/// Maximum upwards speed outside of states that launch the player
+const MAX_ASCEND_SPEED: f32 = 25.0;
+/// Acceleration of humanoids, which the speed limits below are tuned for
+const HUMANOID_ACCEL: f32 = 100.0;
+
+/// Reasons for which a reported movement was rejected
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MovementViolation {
+    /// The position moved further than any legitimate movement could
+    Teleport,
+    /// Horizontal speed exceeded the limit for the character state
+    TooFast,
+    /// Vertical speed exceeded the limit for the character state
+    TooHigh,
+    /// The new position is inside solid terrain
+    InsideTerrain,
+}
+
+impl MovementViolation {
+    /// Label used in metrics and logs
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MovementViolation::Teleport => "teleport",
+            MovementViolation::TooFast => "too_fast",
+            MovementViolation::TooHigh => "too_high",
+            MovementViolation::InsideTerrain => "inside_terrain",
+        }
+    }
+
+    /// How much a single occurrence adds to a player's suspicion
+    pub fn severity(self) -> f32 {
+        match self {
+            MovementViolation::Teleport => 5.0,
+            MovementViolation::InsideTerrain => 2.0,
+            MovementViolation::TooFast | MovementViolation::TooHigh => 1.0,
+        }
+    }
+}
+
+/// The maximum horizontal and upwards speed a character in the given state
+/// can legitimately reach. These are deliberately generous; they are meant to
+/// catch cheats, not to duplicate the physics.
+fn max_speeds(character: Option<&CharacterState>, body: Option<&Body>) -> (f32, f32) {
+    // Bodies that accelerate faster than humanoids also move faster
+    let body_factor = body.map_or(1.0, |body| (body.base_accel() / HUMANOID_ACCEL).max(1.0));
+    let (horizontal, ascend) = match character {
+        Some(CharacterState::Glide) | Some(CharacterState::GlideWield) => (60.0, MAX_ASCEND_SPEED),
+        Some(CharacterState::Roll(_)) => (40.0, MAX_ASCEND_SPEED),
+        Some(CharacterState::Boost(_))
+        | Some(CharacterState::DashMelee(_))
+        | Some(CharacterState::LeapMelee(_))
+        | Some(CharacterState::ChargedMelee(_)) => (80.0, 60.0),
+        Some(CharacterState::Climb) => (15.0, MAX_ASCEND_SPEED),
+        Some(CharacterState::Sit) | Some(CharacterState::Dance) => (10.0, 10.0),
+        _ => (40.0, MAX_ASCEND_SPEED),
+    };
+    (horizontal * body_factor, ascend)
+}
+
+/// Check a position reported by a client against its last accepted position.
+///
+/// `dt` is the time since the last accepted update and `impulse_speed` is the
+/// extra speed allowed by impulses the server applied, such as knockback. The
+/// velocity reported by the client is never trusted.
+pub fn validate_movement(
+    old: &Pos,
+    new: &Pos,
+    dt: f32,
+    impulse_speed: f32,
+    character: Option<&CharacterState>,
+    body: Option<&Body>,
+    terrain: &TerrainGrid,
+) -> Result<(), MovementViolation> {
+    let delta = new.0 - old.0;
+    if delta.magnitude_squared() > TELEPORT_THRESHOLD.powi(2) {
+        return Err(MovementViolation::Teleport);
+    }
+
+    let (max_horizontal, max_ascend) = max_speeds(character, body);
+    let dt = dt.max(0.0) + TIME_LEEWAY;
+
+    let max_horizontal_dist = (max_horizontal + impulse_speed) * dt + DISTANCE_LEEWAY;
+    if Vec2::<f32>::from(delta).magnitude_squared() > max_horizontal_dist.powi(2) {
+        return Err(MovementViolation::TooFast);
+    }
+
+    // Falling is unrestricted, only rising is checked
+    if delta.z > (max_ascend + impulse_speed) * dt + DISTANCE_LEEWAY {
+        return Err(MovementViolation::TooHigh);
+    }
+
+    // Check the block at roughly the middle of the body, so that standing on or
+    // stepping up onto partial blocks isn't caught
+    let body_block = (new.0 + Vec3::unit_z()).map(|e| e.floor() as i32);
+    if terrain
+        .get(body_block)
+        .map(|block| block.is_solid())
+        .unwrap_or(false)
+    {
+        return Err(MovementViolation::InsideTerrain);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(from: Vec3<f32>, to: Vec3<f32>, dt: f32) -> Result<(), MovementViolation> {
+        let terrain = TerrainGrid::new().unwrap();
+        validate_movement(&Pos(from), &Pos(to), dt, 0.0, None, None, &terrain)
+    }
+
+    #[test]
+    fn normal_movement_is_accepted() {
+        assert_eq!(
+            validate(Vec3::zero(), Vec3::new(1.0, 0.5, 0.0), 0.1),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn falling_is_unrestricted() {
+        assert_eq!(
+            validate(Vec3::new(0.0, 0.0, 90.0), Vec3::zero(), 0.1),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn teleport_is_rejected() {
+        assert_eq!(
+            validate(Vec3::zero(), Vec3::new(500.0, 0.0, 0.0), 60.0),
+            Err(MovementViolation::Teleport)
+        );
+    }
+
+    #[test]
+    fn speed_hack_is_rejected() {
+        assert_eq!(
+            validate(Vec3::zero(), Vec3::new(30.0, 0.0, 0.0), 0.1),
+            Err(MovementViolation::TooFast)
+        );
+    }
+
+    #[test]
+    fn fly_hack_is_rejected() {
+        assert_eq!(
+            validate(Vec3::zero(), Vec3::new(0.0, 0.0, 30.0), 0.1),
+            Err(MovementViolation::TooHigh)
+        );
+    }
+
+    #[test]
+    fn spoofed_velocity_is_rejected() {
+        use crate::anticheat::Suspicion;
+
+        // The client claims a huge velocity, but only impulses recorded by the
+        // server widen the limits
+        let suspicion = Suspicion::default();
+        let terrain = TerrainGrid::new().unwrap();
+        let spoofed_vel = Vec3::new(1000.0, 0.0, 1000.0);
+        let to = Vec3::new(30.0, 0.0, 30.0);
+        assert!(spoofed_vel.magnitude() * 0.1 > to.magnitude());
+        assert_eq!(
+            validate_movement(
+                &Pos(Vec3::zero()),
+                &Pos(to),
+                0.1,
+                suspicion.impulse_speed_at(0.1),
+                None,
+                None,
+                &terrain,
+            ),
+            Err(MovementViolation::TooFast)
+        );
+    }
+
+    #[test]
+    fn knockback_is_accepted() {
+        use crate::anticheat::Suspicion;
+
+        let mut suspicion = Suspicion::default();
+        let terrain = TerrainGrid::new().unwrap();
+        suspicion.record_impulse(Vec3::new(100.0, 0.0, 0.0), 0.0);
+        assert_eq!(
+            validate_movement(
+                &Pos(Vec3::zero()),
+                &Pos(Vec3::new(30.0, 0.0, 0.0)),
+                0.1,
+                suspicion.impulse_speed_at(0.1),
+                None,
+                None,
+                &terrain,
+            ),
+            Ok(())
+        );
+        // The allowance wears off
+        assert_eq!(suspicion.impulse_speed_at(10.0), 0.0);
+    }
+}
@@ -0,0 +1,271 @@
+//! Filtering of chat messages sent by players
+//!
+//! Each chat channel can be given its own filter in the server settings.
+//! Messages are filtered right before they are sent out, so that messages of
+//! chat commands like `/tell` go through the same filters as those typed into
+//! a channel. Whenever a message is changed, the original and filtered forms
+//! are logged for moderators.
+
+use crate::error::Error;
+use common::comp::{ChatType, UnresolvedChatMsg};
+use hashbrown::HashMap;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// Messages with fewer letters than this are never considered to be shouted
+const MIN_LETTERS_FOR_CAPS: usize = 8;
+/// What removed links are replaced with
+const REMOVED_LINK: &str = "[link removed]";
+
+/// The channels of player messages that can be filtered
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChatChannel {
+    Tell,
+    Say,
+    Shout,
+    Group,
+    Faction,
+    Region,
+    World,
+}
+
+impl ChatChannel {
+    /// The channel of a message, if it was sent by a player
+    pub fn of<G>(chat_type: &ChatType<G>) -> Option<Self> {
+        match chat_type {
+            ChatType::Tell(..) => Some(Self::Tell),
+            ChatType::Say(_) => Some(Self::Say),
+            ChatType::Shout(_) => Some(Self::Shout),
+            ChatType::Group(..) => Some(Self::Group),
+            ChatType::Faction(..) => Some(Self::Faction),
+            ChatType::Region(_) => Some(Self::Region),
+            ChatType::World(_) => Some(Self::World),
+            _ => None,
+        }
+    }
+}
+
+/// What happens to links in messages
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LinkPolicy {
+    Allow,
+    Strip,
+    /// Only links to these domains and their subdomains are kept
+    AllowList(Vec<String>),
+}
+
+impl Default for LinkPolicy {
+    fn default() -> Self { Self::Allow }
+}
+
+/// The filter settings of a chat channel
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelFilterSettings {
+    /// Words that are replaced by asterisks, ignoring case
+    pub banned_words: Vec<String>,
+    /// Regular expressions whose matches are replaced by asterisks, ignoring
+    /// case
+    pub banned_patterns: Vec<String>,
+    /// Whether links, i.e. anything starting with a scheme like `https://` or
+    /// with `www.`, are kept
+    pub links: LinkPolicy,
+    /// Largest fraction of the letters of a message that may be upper case,
+    /// messages with more are turned to lower case
+    pub max_caps_ratio: Option<f32>,
+}
+
+struct ChannelFilter {
+    banned: Option<Regex>,
+    links: LinkPolicy,
+    max_caps_ratio: Option<f32>,
+}
+
+impl ChannelFilter {
+    fn new(settings: &ChannelFilterSettings) -> Result<Self, regex::Error> {
+        let patterns = settings
+            .banned_words
+            .iter()
+            .map(|word| format!(r"\b{}\b", regex::escape(word)))
+            .chain(
+                settings
+                    .banned_patterns
+                    .iter()
+                    .map(|p| format!("(?:{})", p)),
+            )
+            .collect::<Vec<_>>();
+        let banned = if patterns.is_empty() {
+            None
+        } else {
+            Some(
+                RegexBuilder::new(&patterns.join("|"))
+                    .case_insensitive(true)
+                    .build()?,
+            )
+        };
+        Ok(Self {
+            banned,
+            links: settings.links.clone(),
+            max_caps_ratio: settings.max_caps_ratio,
+        })
+    }
+
+    fn apply(&self, message: &str, link_regex: &Regex) -> String {
+        let mut message = match &self.links {
+            LinkPolicy::Allow => message.to_owned(),
+            LinkPolicy::Strip => link_regex.replace_all(message, REMOVED_LINK).into_owned(),
+            LinkPolicy::AllowList(domains) => link_regex
+                .replace_all(message, |caps: &regex::Captures| {
+                    if is_allowed_link(&caps[0], domains) {
+                        caps[0].to_owned()
+                    } else {
+                        REMOVED_LINK.to_owned()
+                    }
+                })
+                .into_owned(),
+        };
+
+        if let Some(banned) = &self.banned {
+            message = banned
+                .replace_all(&message, |caps: &regex::Captures| {
+                    "*".repeat(caps[0].chars().count())
+                })
+                .into_owned();
+        }
+
+        if let Some(max_ratio) = self.max_caps_ratio {
+            let letters = message.chars().filter(|c| c.is_alphabetic()).count();
+            let caps = message.chars().filter(|c| c.is_uppercase()).count();
+            if letters >= MIN_LETTERS_FOR_CAPS && caps as f32 > max_ratio * letters as f32 {
+                message = message.to_lowercase();
+            }
+        }
+
+        message
+    }
+}
+
+/// Whether the link goes to one of the domains or their subdomains
+fn is_allowed_link(link: &str, domains: &[String]) -> bool {
+    let without_scheme = link.splitn(2, "://").last().unwrap_or(link);
+    let host = without_scheme
+        .split(|c| c == '/' || c == ':' || c == '?' || c == '#')
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    domains.iter().any(|domain| {
+        let domain = domain.to_lowercase();
+        host == domain || host.ends_with(&format!(".{}", domain))
+    })
+}
+
+/// The chat filters of all channels, kept as a resource
+pub struct ChatFilter {
+    channels: HashMap<ChatChannel, ChannelFilter>,
+    link_regex: Regex,
+}
+
+impl ChatFilter {
+    pub fn new(settings: &HashMap<ChatChannel, ChannelFilterSettings>) -> Result<Self, Error> {
+        let channels = settings
+            .iter()
+            .map(|(channel, settings)| {
+                ChannelFilter::new(settings)
+                    .map(|filter| (*channel, filter))
+                    .map_err(|e| {
+                        Error::Other(format!("Invalid chat filter for {:?}: {}", channel, e))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            channels,
+            link_regex: RegexBuilder::new(r"\b(?:[a-z][a-z0-9+.-]*://|www\.)\S+")
+                .case_insensitive(true)
+                .build()
+                .expect("Link regex is valid"),
+        })
+    }
+
+    /// Filters the message if it was sent by a player to a channel with a
+    /// filter, logging it if it changed
+    pub fn filter(&self, mut msg: UnresolvedChatMsg) -> UnresolvedChatMsg {
+        let channel = match ChatChannel::of(&msg.chat_type) {
+            Some(channel) => channel,
+            None => return msg,
+        };
+        if let Some(filter) = self.channels.get(&channel) {
+            let filtered = filter.apply(&msg.message, &self.link_regex);
+            if filtered != msg.message {
+                info!(?channel, original = %msg.message, %filtered, "Filtered chat message");
+                msg.message = filtered;
+            }
+        }
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(settings: ChannelFilterSettings, message: &str) -> String {
+        ChannelFilter::new(&settings).unwrap().apply(
+            message,
+            &ChatFilter::new(&HashMap::new()).unwrap().link_regex,
+        )
+    }
+
+    #[test]
+    fn banned_words_and_patterns() {
+        let settings = ChannelFilterSettings {
+            banned_words: vec!["darn".to_owned()],
+            banned_patterns: vec!["h[e3]ck+".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(
+            filter(settings, "Darn it, what the H3ckk, darned"),
+            "**** it, what the *****, darned"
+        );
+    }
+
+    #[test]
+    fn links() {
+        let strip = ChannelFilterSettings {
+            links: LinkPolicy::Strip,
+            ..Default::default()
+        };
+        assert_eq!(
+            filter(strip, "see https://example.com/a and www.foo.org"),
+            "see [link removed] and [link removed]"
+        );
+        let allow_list = ChannelFilterSettings {
+            links: LinkPolicy::AllowList(vec!["veloren.net".to_owned()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter(
+                allow_list,
+                "https://book.veloren.net/x https://veloren.net.evil.com"
+            ),
+            "https://book.veloren.net/x [link removed]"
+        );
+    }
+
+    #[test]
+    fn caps_ratio() {
+        let settings = ChannelFilterSettings {
+            max_caps_ratio: Some(0.5),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter(settings.clone(), "STOP SHOUTING please"),
+            "stop shouting please"
+        );
+        assert_eq!(
+            filter(settings.clone(), "Hello There friends"),
+            "Hello There friends"
+        );
+        assert_eq!(filter(settings, "OK GG"), "OK GG");
+    }
+}
@@ -0,0 +1,92 @@
+//! Disk cache of generated terrain chunks
+//!
+//! Generating a chunk is expensive, so chunks are written to disk after they
+//! have been generated and read back instead of being generated again when
+//! they are requested once more. Cached chunks are grouped by world seed and
+//! game version, since either of those changes the generated terrain.
+
+use common::{
+    generation::{ChunkSupplement, EntityInfo},
+    terrain::{PackedChunk, TerrainChunk},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, warn};
+use vek::*;
+
+/// Everything needed to restore a chunk without generating it
+#[derive(Serialize, Deserialize)]
+struct CachedChunk {
+    chunk: PackedChunk,
+    /// Waypoints are part of the terrain, so they are restored along with it
+    waypoints: Vec<Vec3<f32>>,
+}
+
+pub struct ChunkCache {
+    dir: PathBuf,
+}
+
+impl ChunkCache {
+    /// `data_dir`: Directory in which the cache directory is created
+    pub fn new(data_dir: &Path, world_seed: u32) -> std::io::Result<Self> {
+        let dir = data_dir.join("chunk_cache").join(format!(
+            "{}-{}",
+            world_seed,
+            *common::util::GIT_HASH
+        ));
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: Vec2<i32>) -> PathBuf { self.dir.join(format!("{}_{}.bin", key.x, key.y)) }
+
+    /// Load a previously generated chunk. NPCs aren't spawned again for
+    /// cached chunks, so the supplement only contains waypoints.
+    pub fn load(&self, key: Vec2<i32>) -> Option<(TerrainChunk, ChunkSupplement)> {
+        let compressed = fs::read(self.path(key)).ok()?;
+        let mut data = Vec::with_capacity(compressed.len() * 4);
+        if let Err(e) = lz_fear::raw::decompress_raw(&compressed, &[0; 0], &mut data, usize::MAX) {
+            warn!(?e, ?key, "Failed to decompress cached chunk");
+            return None;
+        }
+        let cached = bincode::deserialize::<CachedChunk>(&data)
+            .map_err(|e| warn!(?e, ?key, "Failed to deserialize cached chunk"))
+            .ok()?;
+
+        let mut supplement = ChunkSupplement::default();
+        for pos in cached.waypoints {
+            supplement.add_entity(EntityInfo::at(pos).into_waypoint());
+        }
+        Some((cached.chunk.unpack()?, supplement))
+    }
+
+    /// Write a freshly generated chunk to the cache
+    pub fn store(&self, key: Vec2<i32>, chunk: &TerrainChunk, supplement: &ChunkSupplement) {
+        let cached = CachedChunk {
+            chunk: PackedChunk::pack(chunk),
+            waypoints: supplement
+                .entities
+                .iter()
+                .filter(|entity| entity.is_waypoint)
+                .map(|entity| entity.pos)
+                .collect(),
+        };
+        let data = match bincode::serialize(&cached) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!(?e, ?key, "Failed to serialize chunk for caching");
+                return;
+            },
+        };
+        let mut compressed = Vec::with_capacity(data.len() / 4 + 10);
+        let mut table = lz_fear::raw::U32Table::default();
+        if let Err(e) = lz_fear::raw::compress2(&data, 0, &mut table, &mut compressed) {
+            warn!(?e, ?key, "Failed to compress chunk for caching");
+        } else if let Err(e) = fs::write(self.path(key), compressed) {
+            debug!(?e, ?key, "Failed to write chunk to the cache");
+        }
+    }
+}
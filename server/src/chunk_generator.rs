@@ -1,41 +1,124 @@
-use crate::metrics::ChunkGenMetrics;
+//! Generation of terrain chunks on the worker threads
+//!
+//! Requests for the same chunk are merged, and only a limited number of chunks
+//! are generated at the same time so that the workers stay available for other
+//! jobs. The rest wait in a queue, in the order they were requested. A request
+//! is cancelled once none of the players that requested the chunk need it
+//! anymore, whether it is still queued or already being generated.
+
 #[cfg(not(feature = "worldgen"))]
 use crate::test_world::{IndexOwned, World};
+use crate::{chunk_cache::ChunkCache, metrics::ChunkGenMetrics};
 use common::{generation::ChunkSupplement, terrain::TerrainChunk};
 use crossbeam::channel;
-use hashbrown::{hash_map::Entry, HashMap};
+use hashbrown::{hash_map::Entry, HashMap, HashSet};
 use specs::Entity as EcsEntity;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Instant,
 };
 use vek::*;
 #[cfg(feature = "worldgen")]
 use world::{IndexOwned, World};
 
+/// The generated chunk, or the players that requested it if it couldn't be
+/// generated
 type ChunkGenResult = (
     Vec2<i32>,
-    Result<(TerrainChunk, ChunkSupplement), Option<EcsEntity>>,
+    Result<(TerrainChunk, ChunkSupplement), Vec<EcsEntity>>,
 );
 
+/// What a worker sends back once it is done with a chunk
+type Generated = (Vec2<i32>, Result<(TerrainChunk, ChunkSupplement), ()>);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct PendingChunk {
+    cancel: Arc<AtomicBool>,
+    /// Players waiting for the chunk. Chunks requested by the server itself
+    /// have none.
+    requesters: HashSet<EcsEntity>,
+    requested: Instant,
+}
+
+/// Chunks waiting for a worker
+#[derive(Default)]
+struct Queue {
+    jobs: VecDeque<(Vec2<i32>, Job)>,
+    /// Number of workers currently generating chunks
+    workers: usize,
+}
+
+/// Gives up the worker if a job panics, so that the queue doesn't count on a
+/// worker that is gone
+struct WorkerGuard<'a>(&'a Mutex<Queue>);
+
+impl Drop for WorkerGuard<'_> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            if let Ok(mut queue) = self.0.lock() {
+                queue.workers -= 1;
+            }
+        }
+    }
+}
+
+/// Sends the outcome of a job back, or a failure if the job panics so that the
+/// chunk doesn't stay pending forever
+struct Reply {
+    key: Vec2<i32>,
+    chunk_tx: Option<channel::Sender<Generated>>,
+}
+
+impl Reply {
+    fn send(mut self, payload: Result<(TerrainChunk, ChunkSupplement), ()>) {
+        if let Some(chunk_tx) = self.chunk_tx.take() {
+            let _ = chunk_tx.send((self.key, payload));
+        }
+    }
+}
+
+impl Drop for Reply {
+    fn drop(&mut self) {
+        if let (Some(chunk_tx), true) = (self.chunk_tx.take(), thread::panicking()) {
+            let _ = chunk_tx.send((self.key, Err(())));
+        }
+    }
+}
+
 pub struct ChunkGenerator {
-    chunk_tx: channel::Sender<ChunkGenResult>,
-    chunk_rx: channel::Receiver<ChunkGenResult>,
-    pending_chunks: HashMap<Vec2<i32>, Arc<AtomicBool>>,
+    chunk_tx: channel::Sender<Generated>,
+    chunk_rx: channel::Receiver<Generated>,
+    pending_chunks: HashMap<Vec2<i32>, PendingChunk>,
+    queue: Arc<Mutex<Queue>>,
+    max_workers: usize,
     metrics: Arc<ChunkGenMetrics>,
+    cache: Option<Arc<ChunkCache>>,
 }
 impl ChunkGenerator {
-    #[allow(clippy::new_without_default)] // TODO: Pending review in #587
-    pub fn new(metrics: ChunkGenMetrics) -> Self {
+    /// `max_workers`: Number of chunks that are generated at the same time
+    pub fn new(metrics: ChunkGenMetrics, max_workers: usize) -> Self {
         let (chunk_tx, chunk_rx) = channel::unbounded();
         Self {
             chunk_tx,
             chunk_rx,
             pending_chunks: HashMap::new(),
+            queue: Arc::new(Mutex::new(Queue::default())),
+            max_workers: max_workers.max(1),
             metrics: Arc::new(metrics),
+            cache: None,
         }
     }
 
+    /// Load chunks from the given cache when possible instead of generating
+    /// them, and store newly generated chunks in it
+    pub fn set_cache(&mut self, cache: ChunkCache) { self.cache = Some(Arc::new(cache)); }
+
     pub fn generate_chunk(
         &mut self,
         entity: Option<EcsEntity>,
@@ -44,51 +127,291 @@ impl ChunkGenerator {
         world: Arc<World>,
         index: IndexOwned,
     ) {
-        let v = if let Entry::Vacant(v) = self.pending_chunks.entry(key) {
-            v
-        } else {
-            return;
-        };
-        let cancel = Arc::new(AtomicBool::new(false));
-        v.insert(Arc::clone(&cancel));
-        let chunk_tx = self.chunk_tx.clone();
-        self.metrics.chunks_requested.inc();
-        thread_pool.execute(move || {
+        let cache = self.cache.clone();
+        let metrics = Arc::clone(&self.metrics);
+        self.request(entity, key, thread_pool, move |cancel| {
+            if let Some(cached) = cache.as_ref().and_then(|cache| cache.load(key)) {
+                metrics.chunks_loaded_from_cache.inc();
+                return Ok(cached);
+            }
+
             let index = index.as_index_ref();
+            let gen_start = Instant::now();
             let payload = world
                 .generate_chunk(index, key, || cancel.load(Ordering::Relaxed))
-                .map_err(|_| entity);
-            let _ = chunk_tx.send((key, payload));
+                .map_err(|_| ());
+            metrics
+                .chunk_gen_time
+                .observe(gen_start.elapsed().as_secs_f64());
+            if let (Some(cache), Ok((chunk, supplement))) = (&cache, &payload) {
+                cache.store(key, chunk, supplement);
+            }
+            payload
         });
     }
 
-    pub fn recv_new_chunk(&mut self) -> Option<ChunkGenResult> {
-        if let Ok((key, res)) = self.chunk_rx.try_recv() {
-            self.pending_chunks.remove(&key);
-            self.metrics.chunks_served.inc();
-            // TODO: do anything else if res is an Err?
-            Some((key, res))
-        } else {
-            None
+    /// Queue `generate` to produce the chunk at `key`, unless that chunk is
+    /// already pending
+    fn request(
+        &mut self,
+        entity: Option<EcsEntity>,
+        key: Vec2<i32>,
+        thread_pool: &mut uvth::ThreadPool,
+        generate: impl FnOnce(&AtomicBool) -> Result<(TerrainChunk, ChunkSupplement), ()>
+            + Send
+            + 'static,
+    ) {
+        let v = match self.pending_chunks.entry(key) {
+            Entry::Vacant(v) => v,
+            Entry::Occupied(mut o) => {
+                // Already on its way, just let the player know about it too
+                o.get_mut().requesters.extend(entity);
+                return;
+            },
+        };
+        let cancel = Arc::new(AtomicBool::new(false));
+        v.insert(PendingChunk {
+            cancel: Arc::clone(&cancel),
+            requesters: entity.into_iter().collect(),
+            requested: Instant::now(),
+        });
+        let reply = Reply {
+            key,
+            chunk_tx: Some(self.chunk_tx.clone()),
+        };
+        self.metrics.chunks_requested.inc();
+        let job = Box::new(move || {
+            if cancel.load(Ordering::Relaxed) {
+                return;
+            }
+            reply.send(generate(&cancel));
+        });
+
+        let mut queue = self.queue.lock().unwrap();
+        queue.jobs.push_back((key, job));
+        if queue.workers < self.max_workers {
+            queue.workers += 1;
+            let shared = Arc::clone(&self.queue);
+            thread_pool.execute(move || Self::work(&shared));
+        }
+        self.metrics.chunks_queued.set(queue.jobs.len() as i64);
+    }
+
+    /// Generate queued chunks until the queue is empty
+    fn work(queue: &Mutex<Queue>) {
+        let _guard = WorkerGuard(queue);
+        loop {
+            let job = {
+                let mut queue = queue.lock().unwrap();
+                match queue.jobs.pop_front() {
+                    Some((_, job)) => job,
+                    None => {
+                        // Giving up the worker while the queue is still locked, so that
+                        // no job can be queued without a worker to run it
+                        queue.workers -= 1;
+                        return;
+                    },
+                }
+            };
+            job();
         }
     }
 
+    pub fn recv_new_chunk(&mut self) -> Option<ChunkGenResult> {
+        let (key, res) = self.chunk_rx.try_recv().ok()?;
+        let requesters = self
+            .pending_chunks
+            .remove(&key)
+            .map(|pending| {
+                self.metrics
+                    .chunk_latency
+                    .observe(pending.requested.elapsed().as_secs_f64());
+                pending.requesters
+            })
+            .unwrap_or_default();
+        self.metrics.chunks_served.inc();
+        self.update_queue_depth();
+        Some((key, res.map_err(|_| requesters.into_iter().collect())))
+    }
+
     pub fn pending_chunks<'a>(&'a self) -> impl Iterator<Item = Vec2<i32>> + 'a {
         self.pending_chunks.keys().copied()
     }
 
+    /// Forget the players that don't want a pending chunk anymore, and cancel
+    /// the chunks that nobody wants. `wanted_by` is called with `None` for
+    /// chunks requested by the server itself.
+    pub fn cancel_unwanted(
+        &mut self,
+        mut wanted_by: impl FnMut(Vec2<i32>, Option<EcsEntity>) -> bool,
+    ) {
+        let unwanted = self
+            .pending_chunks
+            .iter_mut()
+            .filter_map(|(key, pending)| {
+                let wanted = if pending.requesters.is_empty() {
+                    wanted_by(*key, None)
+                } else {
+                    pending
+                        .requesters
+                        .retain(|entity| wanted_by(*key, Some(*entity)));
+                    !pending.requesters.is_empty()
+                };
+                if wanted {
+                    None
+                } else {
+                    Some(*key)
+                }
+            })
+            .collect::<Vec<_>>();
+        unwanted
+            .into_iter()
+            .for_each(|key| self.cancel_if_pending(key));
+    }
+
     pub fn cancel_if_pending(&mut self, key: Vec2<i32>) {
-        if let Some(cancel) = self.pending_chunks.remove(&key) {
-            cancel.store(true, Ordering::Relaxed);
+        if let Some(pending) = self.pending_chunks.remove(&key) {
+            pending.cancel.store(true, Ordering::Relaxed);
             self.metrics.chunks_canceled.inc();
+            self.queue
+                .lock()
+                .unwrap()
+                .jobs
+                .retain(|(queued, _)| *queued != key);
+            self.update_queue_depth();
         }
     }
 
     pub fn cancel_all(&mut self) {
         let metrics = Arc::clone(&self.metrics);
-        self.pending_chunks.drain().for_each(|(_, cancel)| {
-            cancel.store(true, Ordering::Relaxed);
+        self.pending_chunks.drain().for_each(|(_, pending)| {
+            pending.cancel.store(true, Ordering::Relaxed);
             metrics.chunks_canceled.inc();
         });
+        self.queue.lock().unwrap().jobs.clear();
+        self.update_queue_depth();
+    }
+
+    fn update_queue_depth(&self) {
+        let depth = self.queue.lock().unwrap().jobs.len();
+        self.metrics.chunks_queued.set(depth as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::terrain::{Block, BlockKind, SpriteKind, TerrainChunkMeta};
+    use specs::{Builder, World as EcsWorld, WorldExt};
+    use std::{
+        sync::atomic::AtomicUsize,
+        time::{Duration, Instant},
+    };
+
+    fn chunk() -> (TerrainChunk, ChunkSupplement) {
+        let rock = Block::new(BlockKind::Rock, Rgb::zero());
+        let air = Block::air(SpriteKind::Empty);
+        (
+            TerrainChunk::new(0, rock, air, TerrainChunkMeta::void()),
+            ChunkSupplement::default(),
+        )
+    }
+
+    fn generator(max_workers: usize) -> (ChunkGenerator, uvth::ThreadPool) {
+        let (metrics, _) = ChunkGenMetrics::new().unwrap();
+        let thread_pool = uvth::ThreadPoolBuilder::new().num_threads(2).build();
+        (ChunkGenerator::new(metrics, max_workers), thread_pool)
+    }
+
+    fn recv(generator: &mut ChunkGenerator) -> ChunkGenResult {
+        let start = Instant::now();
+        loop {
+            if let Some(result) = generator.recv_new_chunk() {
+                return result;
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "No chunk arrived"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn requests_for_the_same_chunk_are_merged() {
+        let (mut generator, mut thread_pool) = generator(1);
+        let mut ecs = EcsWorld::new();
+        let players = [ecs.create_entity().build(), ecs.create_entity().build()];
+        let generated = Arc::new(AtomicUsize::new(0));
+        for player in players.iter() {
+            let generated = Arc::clone(&generated);
+            generator.request(Some(*player), Vec2::zero(), &mut thread_pool, move |_| {
+                generated.fetch_add(1, Ordering::Relaxed);
+                Err(())
+            });
+        }
+
+        // Both players learn that the chunk failed, but it was only generated once
+        let (key, result) = recv(&mut generator);
+        assert_eq!(key, Vec2::zero());
+        let mut requesters = result.err().expect("Expected the chunk to fail");
+        requesters.sort();
+        assert_eq!(requesters, players.to_vec());
+        assert_eq!(generated.load(Ordering::Relaxed), 1);
+        assert_eq!(generator.pending_chunks().count(), 0);
+    }
+
+    #[test]
+    fn cancelled_chunks_are_not_generated() {
+        let (mut generator, mut thread_pool) = generator(1);
+        // Keep the only worker busy until the second chunk is cancelled
+        let (release_tx, release_rx) = channel::bounded::<()>(0);
+        generator.request(None, Vec2::zero(), &mut thread_pool, move |_| {
+            let _ = release_rx.recv();
+            Ok(chunk())
+        });
+        let generated = Arc::new(AtomicUsize::new(0));
+        let generated_ = Arc::clone(&generated);
+        generator.request(None, Vec2::unit_x(), &mut thread_pool, move |_| {
+            generated_.fetch_add(1, Ordering::Relaxed);
+            Ok(chunk())
+        });
+
+        generator.cancel_if_pending(Vec2::unit_x());
+        assert_eq!(generator.pending_chunks().collect::<Vec<_>>(), vec![
+            Vec2::zero()
+        ]);
+        drop(release_tx);
+
+        let (key, result) = recv(&mut generator);
+        assert_eq!(key, Vec2::zero());
+        assert!(result.is_ok());
+        thread::sleep(Duration::from_millis(50));
+        assert!(generator.recv_new_chunk().is_none());
+        assert_eq!(generated.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn panicking_jobs_give_up_their_worker() {
+        let (mut generator, mut thread_pool) = generator(1);
+        generator.request(None, Vec2::zero(), &mut thread_pool, |_| {
+            panic!("Failed to generate the chunk")
+        });
+
+        // The chunk fails instead of staying pending forever
+        let (key, result) = recv(&mut generator);
+        assert_eq!(key, Vec2::zero());
+        assert!(result.is_err());
+
+        // The worker is given back, so that later chunks still get generated
+        let start = Instant::now();
+        while generator.queue.lock().unwrap().workers != 0 {
+            assert!(start.elapsed() < Duration::from_secs(10), "Worker leaked");
+            thread::sleep(Duration::from_millis(1));
+        }
+        generator.request(None, Vec2::unit_x(), &mut thread_pool, |_| Ok(chunk()));
+        let (key, result) = recv(&mut generator);
+        assert_eq!(key, Vec2::unit_x());
+        assert!(result.is_ok());
     }
 }
@@ -1,13 +1,99 @@
 use crate::error::Error;
-use common::msg::{ClientInGame, ClientType, ServerGeneral, ServerMsg};
+use common::{
+    msg::{
+        Capability, ClientInGame, ClientRegister, ClientType, ServerGeneral, ServerMsg, VoiceRelay,
+    },
+    terrain::ChunkEncoding,
+};
 use hashbrown::HashSet;
-use network::{Participant, Stream};
+use network::{Message, Participant, Stream};
 use serde::{de::DeserializeOwned, Serialize};
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IdvStorage;
-use tracing::debug;
+use tracing::{debug, warn};
 use vek::*;
 
+/// Number of messages queued on a stream after which the client is considered
+/// unable to keep up and gets disconnected
+const MAX_QUEUED_MSGS: u64 = 8192;
+/// Number of messages queued on the general stream after which physics updates,
+/// which are superseded by later ones, are no longer sent
+const PHYSICS_DROP_THRESHOLD: u64 = 512;
+/// Number of voice frames queued for a client after which further frames are
+/// dropped, as they would be too late to be played anyway
+const VOICE_DROP_THRESHOLD: u64 = 16;
+
+/// Stream a general message is sent on
+#[derive(Copy, Clone, Debug)]
+enum GeneralStream {
+    CharacterScreen,
+    InGame,
+    Terrain,
+    General,
+}
+
+impl GeneralStream {
+    fn of(msg: &ServerGeneral) -> Self {
+        match msg {
+            //Character Screen related
+            ServerGeneral::CharacterDataLoadError(_)
+            | ServerGeneral::CharacterListUpdate(_)
+            | ServerGeneral::AccountUnlocks(_)
+            | ServerGeneral::CharacterActionError(_)
+            | ServerGeneral::CharacterSuccess
+            | ServerGeneral::ServerRules(_) => GeneralStream::CharacterScreen,
+            //Ingame related
+            ServerGeneral::GroupUpdate(_)
+            | ServerGeneral::GroupInvite { .. }
+            | ServerGeneral::InvitePending(_)
+            | ServerGeneral::InviteComplete { .. }
+            | ServerGeneral::ExitInGameSuccess
+            | ServerGeneral::InventoryUpdate(_, _)
+            | ServerGeneral::UiPreferences(_)
+            | ServerGeneral::SkillSetUpdate(_)
+            | ServerGeneral::AlchemyJournalUpdate(_)
+            | ServerGeneral::AbilityHotbarUpdate(_)
+            | ServerGeneral::ExpGain { .. }
+            | ServerGeneral::LevelUp(_)
+            | ServerGeneral::SetViewDistance(_)
+            | ServerGeneral::Outcomes(_)
+            | ServerGeneral::CombatEvent(_)
+            | ServerGeneral::SoundEvents(_)
+            | ServerGeneral::Knockback(_)
+            | ServerGeneral::YouDied { .. }
+            | ServerGeneral::Respawned
+            | ServerGeneral::PhysicsAck { .. } => GeneralStream::InGame,
+            // Terrain, decoded off the main thread by the client
+            ServerGeneral::TerrainChunkUpdate { .. } | ServerGeneral::TerrainBlockUpdates(_) => {
+                GeneralStream::Terrain
+            },
+            // Always possible
+            ServerGeneral::PlayerListUpdate(_)
+            | ServerGeneral::ChatMsg(_)
+            | ServerGeneral::SetPlayerEntity(_)
+            | ServerGeneral::TimeOfDay(_)
+            | ServerGeneral::WeatherUpdate(_)
+            | ServerGeneral::EntitySync(_)
+            | ServerGeneral::CompSync(_)
+            | ServerGeneral::CreateEntity(_)
+            | ServerGeneral::DeleteEntity(_)
+            | ServerGeneral::Disconnect(_)
+            | ServerGeneral::Notification(_)
+            | ServerGeneral::WorldMapData(_)
+            | ServerGeneral::NamedLocations(_)
+            | ServerGeneral::SessionExpiring
+            | ServerGeneral::SessionRefreshed(_) => GeneralStream::General,
+        }
+    }
+}
+
+/// A message that was serialized once, so that it can be sent to many clients
+/// without serializing it for each of them
+pub struct PreparedMsg {
+    stream: GeneralStream,
+    message: Message,
+}
+
 pub struct Client {
     pub registered: bool,
     pub client_type: ClientType,
@@ -18,9 +104,37 @@ pub struct Client {
     pub register_stream: Stream,
     pub character_screen_stream: Stream,
     pub in_game_stream: Stream,
+    pub terrain_stream: Stream,
+    /// Only used if the client supports [`Capability::Voice`]
+    pub voice_stream: Stream,
     pub network_error: bool,
     pub last_ping: f64,
     pub login_msg_sent: bool,
+    /// Time at which the player last shouted, to rate limit shouting
+    pub last_shout: Option<f64>,
+    /// Time at which the player last gave any input
+    pub last_input: f64,
+    /// Whether the player is marked as idle in the player list
+    pub is_idle: bool,
+    /// Time at which the player was warned about being disconnected for being
+    /// idle
+    pub idle_warned_at: Option<f64>,
+    /// Time at which the player last authenticated, by registering or
+    /// refreshing the session
+    pub session_start: f64,
+    /// Time at which the player was told that the session is about to expire
+    pub session_expiring_since: Option<f64>,
+    /// Registration that waits for another session of the same account to be
+    /// disconnected
+    pub pending_register: Option<ClientRegister>,
+    /// The player was sent rules of the server and has yet to accept them
+    pub rules_pending: bool,
+    /// Latest encoding of terrain chunks the client understands
+    pub chunk_encoding: ChunkEncoding,
+    /// Optional protocol features the client supports
+    pub capabilities: HashSet<Capability>,
+    /// Latest physics update of the player that is yet to be acknowledged
+    pub physics_ack: Option<u64>,
 }
 
 impl Component for Client {
@@ -30,23 +144,74 @@ impl Component for Client {
 impl Client {
     fn internal_send<M: Serialize>(err: &mut bool, s: &mut Stream, msg: M) {
         if !*err {
-            if let Err(e) = s.send(msg) {
+            if s.queued_messages() > MAX_QUEUED_MSGS {
+                warn!("too many messages queued for client, disconnecting");
+                *err = true;
+            } else if let Err(e) = s.send(msg) {
                 debug!(?e, "got a network error with client");
                 *err = true;
             }
         }
     }
 
-    /*
-    fn internal_send_raw(b: &AtomicBool, s: &mut Stream, msg: Arc<MessageBuffer>) {
-        if !b.load(Ordering::Relaxed) {
-            if let Err(e) = s.send_raw(msg) {
+    /// Whether physics updates should be skipped for this client because it
+    /// isn't receiving messages as fast as they are sent.
+    pub fn should_drop_physics(&self) -> bool {
+        self.general_stream.queued_messages() > PHYSICS_DROP_THRESHOLD
+    }
+
+    /// Bytes of messages that are queued for this client but not sent yet
+    pub fn queued_bytes(&self) -> u64 {
+        [
+            &self.general_stream,
+            &self.ping_stream,
+            &self.register_stream,
+            &self.character_screen_stream,
+            &self.in_game_stream,
+            &self.terrain_stream,
+            &self.voice_stream,
+        ]
+        .iter()
+        .map(|s| s.queued_bytes())
+        .sum()
+    }
+
+    fn internal_send_raw(err: &mut bool, s: &mut Stream, msg: &Message) {
+        if !*err {
+            if s.queued_messages() > MAX_QUEUED_MSGS {
+                warn!("too many messages queued for client, disconnecting");
+                *err = true;
+            } else if let Err(e) = s.send_raw(msg) {
                 debug!(?e, "got a network error with client");
-                b.store(true, Ordering::Relaxed);
+                *err = true;
             }
         }
     }
-     */
+
+    fn stream_for(&mut self, stream: GeneralStream) -> (&mut bool, &mut Stream) {
+        let stream = match stream {
+            GeneralStream::CharacterScreen => &mut self.character_screen_stream,
+            GeneralStream::InGame => &mut self.in_game_stream,
+            GeneralStream::Terrain => &mut self.terrain_stream,
+            GeneralStream::General => &mut self.general_stream,
+        };
+        (&mut self.network_error, stream)
+    }
+
+    /// Serialize a message to send it to several clients with
+    /// [`Client::send_prepared`]
+    pub fn prepare(&mut self, msg: &ServerGeneral) -> PreparedMsg {
+        let stream = GeneralStream::of(msg);
+        PreparedMsg {
+            stream,
+            message: Message::serialize(msg, self.stream_for(stream).1),
+        }
+    }
+
+    pub fn send_prepared(&mut self, msg: &PreparedMsg) {
+        let (err, stream) = self.stream_for(msg.stream);
+        Self::internal_send_raw(err, stream, &msg.message);
+    }
 
     pub fn send_msg<S>(&mut self, msg: S)
     where
@@ -61,37 +226,8 @@ impl Client {
                 Self::internal_send(&mut self.network_error, &mut self.register_stream, &msg)
             },
             ServerMsg::General(msg) => {
-                let stream = match &msg {
-                    //Character Screen related
-                    ServerGeneral::CharacterDataLoadError(_)
-                    | ServerGeneral::CharacterListUpdate(_)
-                    | ServerGeneral::CharacterActionError(_)
-                    | ServerGeneral::CharacterSuccess => &mut self.character_screen_stream,
-                    //Ingame related
-                    ServerGeneral::GroupUpdate(_)
-                    | ServerGeneral::GroupInvite { .. }
-                    | ServerGeneral::InvitePending(_)
-                    | ServerGeneral::InviteComplete { .. }
-                    | ServerGeneral::ExitInGameSuccess
-                    | ServerGeneral::InventoryUpdate(_, _)
-                    | ServerGeneral::TerrainChunkUpdate { .. }
-                    | ServerGeneral::TerrainBlockUpdates(_)
-                    | ServerGeneral::SetViewDistance(_)
-                    | ServerGeneral::Outcomes(_)
-                    | ServerGeneral::Knockback(_) => &mut self.in_game_stream,
-                    // Always possible
-                    ServerGeneral::PlayerListUpdate(_)
-                    | ServerGeneral::ChatMsg(_)
-                    | ServerGeneral::SetPlayerEntity(_)
-                    | ServerGeneral::TimeOfDay(_)
-                    | ServerGeneral::EntitySync(_)
-                    | ServerGeneral::CompSync(_)
-                    | ServerGeneral::CreateEntity(_)
-                    | ServerGeneral::DeleteEntity(_)
-                    | ServerGeneral::Disconnect(_)
-                    | ServerGeneral::Notification(_) => &mut self.general_stream,
-                };
-                Self::internal_send(&mut self.network_error, stream, &msg)
+                let (err, stream) = self.stream_for(GeneralStream::of(&msg));
+                Self::internal_send(err, stream, &msg)
             },
             ServerMsg::Ping(msg) => {
                 Self::internal_send(&mut self.network_error, &mut self.ping_stream, &msg)
@@ -99,6 +235,22 @@ impl Client {
         };
     }
 
+    /// Relay a voice frame of another player to this client, if it supports
+    /// voice chat. Frames are dropped instead of queued when the client can't
+    /// keep up.
+    pub fn send_voice(&mut self, relay: &VoiceRelay) {
+        if self.network_error
+            || !self.capabilities.contains(&Capability::Voice)
+            || self.voice_stream.queued_messages() > VOICE_DROP_THRESHOLD
+        {
+            return;
+        }
+        if let Err(e) = self.voice_stream.send(relay) {
+            debug!(?e, "got a network error with client");
+            self.network_error = true;
+        }
+    }
+
     pub async fn internal_recv<M: DeserializeOwned>(
         err: &mut bool,
         s: &mut Stream,
@@ -3,27 +3,32 @@
 //! `CHAT_COMMANDS` and provide a handler function.
 
 use crate::{
+    anticheat,
     client::Client,
+    persistence::named_location::NamedLocations,
+    scripts::{Script, Variables, MAX_SCRIPT_DEPTH},
     settings::{BanRecord, EditableSetting},
-    Server, StateExt,
+    Server, SpawnPoint, StateExt,
 };
 use chrono::{NaiveTime, Timelike};
 use common::{
-    cmd::{ChatCommand, CHAT_COMMANDS, CHAT_SHORTCUTS},
+    cmd::{Argument, ChatCommand, CHAT_COMMANDS, CHAT_SHORTCUTS},
     comp::{self, ChatType, Item, LightEmitter, WaypointArea},
     event::{EventBus, ServerEvent},
     msg::{DisconnectReason, Notification, PlayerListUpdate, ServerGeneral},
     npc::{self, get_npc_name},
-    state::TimeOfDay,
+    state::{Time, TimeOfDay},
     sync::{Uid, WorldSyncExt},
     terrain::{Block, BlockKind, SpriteKind, TerrainChunkSize},
     util::Dir,
     vol::RectVolSize,
     Explosion, LoadoutBuilder,
 };
+use hashbrown::HashMap;
 use rand::Rng;
-use specs::{Builder, Entity as EcsEntity, Join, WorldExt};
-use std::convert::TryFrom;
+use specs::{Builder, Component, Entity as EcsEntity, Join, WorldExt};
+use specs_idvs::IdvStorage;
+use std::{cmp::Ordering, convert::TryFrom};
 use vek::*;
 use world::util::Sampler;
 
@@ -46,12 +51,90 @@ impl ChatCommandExt for ChatCommand {
                 )),
             );
             return;
+        }
+        let parsed_args = match self.parse_args(&args) {
+            Ok(parsed_args) => parsed_args,
+            Err(error) => {
+                server.notify_client(entity, ChatType::CommandError.server_msg(error));
+                server.notify_client(
+                    entity,
+                    ChatType::CommandError.server_msg(self.help_string()),
+                );
+                return;
+            },
+        };
+
+        let time = server.state.ecs().read_resource::<Time>().0;
+        let cooldown = server
+            .state
+            .ecs()
+            .read_storage::<CommandCooldowns>()
+            .get(entity)
+            .map_or(Ok(()), |cooldowns| cooldowns.check(self, time));
+        if let Err(remaining) = cooldown {
+            server.notify_client(
+                entity,
+                ChatType::CommandError.server_msg(format!(
+                    "You have to wait {} seconds before using '/{}' again.",
+                    remaining.ceil(),
+                    self.keyword()
+                )),
+            );
         } else {
-            get_handler(self)(server, entity, entity, args, &self);
+            get_handler(self)(server, entity, entity, args, &parsed_args, &self);
         }
     }
 }
 
+/// When a player last used each of the commands that have a cooldown
+#[derive(Default)]
+pub struct CommandCooldowns(HashMap<&'static str, f64>);
+
+impl CommandCooldowns {
+    /// Return the seconds left if a command is still cooling down at the given
+    /// time
+    fn check(&self, cmd: &ChatCommand, time: f64) -> Result<(), f64> {
+        if let Some(cooldown) = cmd.cooldown() {
+            let ready_at = self
+                .0
+                .get(cmd.keyword())
+                .map_or(time, |used| used + cooldown.as_secs_f64());
+            if ready_at > time {
+                return Err(ready_at - time);
+            }
+        }
+        Ok(())
+    }
+
+    /// Start the cooldown of a command that was successfully used at the
+    /// given time
+    fn start(&mut self, cmd: &ChatCommand, time: f64) {
+        if cmd.cooldown().is_some() {
+            self.0.insert(cmd.keyword(), time);
+        }
+    }
+}
+
+/// Start the cooldown of a command once it had an effect, so that failed
+/// attempts can be retried right away
+fn start_cooldown(server: &mut Server, entity: EcsEntity, cmd: &ChatCommand) {
+    let time = server.state.ecs().read_resource::<Time>().0;
+    if let Ok(entry) = server
+        .state
+        .ecs()
+        .write_storage::<CommandCooldowns>()
+        .entry(entity)
+    {
+        entry
+            .or_insert_with(CommandCooldowns::default)
+            .start(cmd, time);
+    }
+}
+
+impl Component for CommandCooldowns {
+    type Storage = IdvStorage<Self>;
+}
+
 /// Handler function called when the command is executed.
 /// # Arguments
 /// * `&mut Server` - the `Server` instance executing the command.
@@ -61,10 +144,13 @@ impl ChatCommandExt for ChatCommand {
 ///   This differs from the previous argument when using /sudo
 /// * `String` - a `String` containing the part of the command after the
 ///   keyword.
+/// * `&[Argument]` - the arguments, already parsed and checked according to the
+///   command's `ArgumentSpec`s. Optional arguments that were left out are
+///   missing from the end.
 /// * `&ChatCommand` - the command to execute with the above arguments.
-/// Handler functions must parse arguments from the the given `String`
-/// (`scan_fmt!` is included for this purpose).
-type CommandHandler = fn(&mut Server, EcsEntity, EcsEntity, String, &ChatCommand);
+/// Handler functions can take their arguments from the parsed ones, or parse
+/// the given `String` themselves (`scan_fmt!` is included for this purpose).
+type CommandHandler = fn(&mut Server, EcsEntity, EcsEntity, String, &[Argument], &ChatCommand);
 fn get_handler(cmd: &ChatCommand) -> CommandHandler {
     match cmd {
         ChatCommand::Adminify => handle_adminify,
@@ -83,6 +169,7 @@ fn get_handler(cmd: &ChatCommand) -> CommandHandler {
         ChatCommand::Group => handle_group,
         ChatCommand::Health => handle_health,
         ChatCommand::Help => handle_help,
+        ChatCommand::Home => handle_home,
         ChatCommand::JoinFaction => handle_join_faction,
         ChatCommand::Jump => handle_jump,
         ChatCommand::Kick => handle_kick,
@@ -90,6 +177,7 @@ fn get_handler(cmd: &ChatCommand) -> CommandHandler {
         ChatCommand::KillNpcs => handle_kill_npcs,
         ChatCommand::Lantern => handle_lantern,
         ChatCommand::Light => handle_light,
+        ChatCommand::Location => handle_location,
         ChatCommand::MakeBlock => handle_make_block,
         ChatCommand::MakeSprite => handle_make_sprite,
         ChatCommand::Motd => handle_motd,
@@ -97,14 +185,19 @@ fn get_handler(cmd: &ChatCommand) -> CommandHandler {
         ChatCommand::Players => handle_players,
         ChatCommand::Region => handle_region,
         ChatCommand::RemoveLights => handle_remove_lights,
+        ChatCommand::RunScript => handle_run_script,
         ChatCommand::Say => handle_say,
         ChatCommand::SetLevel => handle_set_level,
         ChatCommand::SetMotd => handle_set_motd,
+        ChatCommand::Shout => handle_shout,
+        ChatCommand::Site => handle_site,
         ChatCommand::Spawn => handle_spawn,
         ChatCommand::Sudo => handle_sudo,
+        ChatCommand::Suspects => handle_suspects,
         ChatCommand::Tell => handle_tell,
         ChatCommand::Time => handle_time,
         ChatCommand::Tp => handle_tp,
+        ChatCommand::TpPos => handle_goto,
         ChatCommand::Unban => handle_unban,
         ChatCommand::Version => handle_version,
         ChatCommand::Waypoint => handle_waypoint,
@@ -119,6 +212,7 @@ fn handle_give_item(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let (Some(item_name), give_amount_opt) =
@@ -194,6 +288,7 @@ fn handle_make_block(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let Some(block_name) = scan_fmt_some!(&args, &action.arg_fmt(), String) {
@@ -227,6 +322,7 @@ fn handle_make_sprite(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let Some(sprite_name) = scan_fmt_some!(&args, &action.arg_fmt(), String) {
@@ -266,6 +362,7 @@ fn handle_motd(
     client: EcsEntity,
     _target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     server.notify_client(
@@ -279,6 +376,7 @@ fn handle_set_motd(
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     let data_dir = server.data_dir();
@@ -310,16 +408,14 @@ fn handle_jump(
     server: &mut Server,
     client: EcsEntity,
     target: EcsEntity,
-    args: String,
-    action: &ChatCommand,
+    _args: String,
+    parsed_args: &[Argument],
+    _action: &ChatCommand,
 ) {
-    if let Ok((x, y, z)) = scan_fmt!(&args, &action.arg_fmt(), f32, f32, f32) {
+    if let [Argument::Position(offset)] = parsed_args {
         match server.state.read_component_copied::<comp::Pos>(target) {
             Some(current_pos) => {
-                server
-                    .state
-                    .write_component(target, comp::Pos(current_pos.0 + Vec3::new(x, y, z)));
-                server.state.write_component(target, comp::ForceUpdate);
+                teleport(server, client, target, current_pos.0 + *offset);
             },
             None => server.notify_client(
                 client,
@@ -333,24 +429,13 @@ fn handle_goto(
     server: &mut Server,
     client: EcsEntity,
     target: EcsEntity,
-    args: String,
+    _args: String,
+    parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
-    if let Ok((x, y, z)) = scan_fmt!(&args, &action.arg_fmt(), f32, f32, f32) {
-        if server
-            .state
-            .read_component_copied::<comp::Pos>(target)
-            .is_some()
-        {
-            server
-                .state
-                .write_component(target, comp::Pos(Vec3::new(x, y, z)));
-            server.state.write_component(target, comp::ForceUpdate);
-        } else {
-            server.notify_client(
-                client,
-                ChatType::CommandError.server_msg("You have no position."),
-            );
+    if let [Argument::Position(pos)] = parsed_args {
+        if teleport(server, client, target, *pos) {
+            start_cooldown(server, client, action);
         }
     } else {
         server.notify_client(
@@ -360,17 +445,72 @@ fn handle_goto(
     }
 }
 
+fn handle_home(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    _args: String,
+    _parsed_args: &[Argument],
+    action: &ChatCommand,
+) {
+    let in_combat = server
+        .state
+        .ecs()
+        .read_storage::<comp::Stats>()
+        .get(target)
+        .map_or(false, crate::sys::message::is_in_combat);
+    if in_combat {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg("You can't go home while in combat."),
+        );
+        return;
+    }
+    let home = server
+        .state
+        .read_component_copied::<comp::Waypoint>(target)
+        .map(|waypoint| waypoint.get_pos())
+        .unwrap_or_else(|| server.state.ecs().read_resource::<SpawnPoint>().0);
+    if teleport(server, client, target, home) {
+        start_cooldown(server, client, action);
+    }
+}
+
+/// Move the target to a position, forcing its client to snap there instead of
+/// smoothing the movement. Returns whether the target could be moved.
+fn teleport(server: &mut Server, client: EcsEntity, target: EcsEntity, pos: Vec3<f32>) -> bool {
+    if server
+        .state
+        .read_component_copied::<comp::Pos>(target)
+        .is_some()
+    {
+        server.state.write_component(target, comp::Pos(pos));
+        server.state.write_component(target, comp::ForceUpdate);
+        true
+    } else {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg("You have no position."),
+        );
+        false
+    }
+}
+
 fn handle_kill(
     server: &mut Server,
     client: EcsEntity,
     target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     let reason = if client == target {
         comp::HealthSource::Suicide
     } else if let Some(uid) = server.state.read_storage::<Uid>().get(client) {
-        comp::HealthSource::Attack { by: *uid }
+        comp::HealthSource::Attack {
+            by: *uid,
+            ability: None,
+        }
     } else {
         comp::HealthSource::Command
     };
@@ -387,6 +527,7 @@ fn handle_time(
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     let time = scan_fmt_some!(&args, &action.arg_fmt(), String);
@@ -428,8 +569,10 @@ fn handle_time(
         },
     };
 
-    server.state.ecs_mut().write_resource::<TimeOfDay>().0 =
-        new_time.num_seconds_from_midnight() as f64;
+    let new_time_of_day = TimeOfDay(new_time.num_seconds_from_midnight() as f64);
+    *server.state.ecs_mut().write_resource::<TimeOfDay>() = new_time_of_day;
+    // Don't wait for the next periodic sync
+    server.notify_registered_clients(ServerGeneral::TimeOfDay(new_time_of_day));
 
     server.notify_client(
         client,
@@ -444,10 +587,12 @@ fn handle_health(
     server: &mut Server,
     client: EcsEntity,
     target: EcsEntity,
-    args: String,
-    action: &ChatCommand,
+    _args: String,
+    parsed_args: &[Argument],
+    _action: &ChatCommand,
 ) {
-    if let Ok(hp) = scan_fmt!(&args, &action.arg_fmt(), u32) {
+    if let [Argument::Integer(hp)] = parsed_args {
+        let hp = (*hp).max(0) as u32;
         if let Some(stats) = server
             .state
             .ecs()
@@ -474,6 +619,7 @@ fn handle_alias(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if client != target {
@@ -531,13 +677,16 @@ fn handle_tp(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
-    let opt_player = if let Some(alias) = scan_fmt_some!(&args, &action.arg_fmt(), String) {
+    // Location names can have spaces in them
+    let name = Some(join_words(args.split_whitespace())).filter(|name| !name.is_empty());
+    let opt_player = if let Some(alias) = &name {
         let ecs = server.state.ecs();
         (&ecs.entities(), &ecs.read_storage::<comp::Player>())
             .join()
-            .find(|(_, player)| player.alias == alias)
+            .find(|(_, player)| &player.alias == alias)
             .map(|(entity, _)| entity)
     } else if client != target {
         Some(client)
@@ -555,18 +704,27 @@ fn handle_tp(
     if let Some(_pos) = server.state.read_component_copied::<comp::Pos>(target) {
         if let Some(player) = opt_player {
             if let Some(pos) = server.state.read_component_copied::<comp::Pos>(player) {
-                server.state.write_component(target, pos);
-                server.state.write_component(target, comp::ForceUpdate);
+                teleport(server, client, target, pos.0);
+                start_cooldown(server, client, action);
             } else {
                 server.notify_client(
                     client,
                     ChatType::CommandError.server_msg("Unable to teleport to player!"),
                 );
             }
+        } else if let Some(pos) = name.and_then(|name| {
+            server
+                .state
+                .ecs()
+                .read_resource::<NamedLocations>()
+                .get(&name)
+        }) {
+            teleport(server, client, target, pos);
+            start_cooldown(server, client, action);
         } else {
             server.notify_client(
                 client,
-                ChatType::CommandError.server_msg("Player not found!"),
+                ChatType::CommandError.server_msg("Player or location not found!"),
             );
             server.notify_client(
                 client,
@@ -586,6 +744,7 @@ fn handle_spawn(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     match scan_fmt_some!(
@@ -722,6 +881,7 @@ fn handle_spawn_training_dummy(
     client: EcsEntity,
     target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     match server.state.read_component_copied::<comp::Pos>(target) {
@@ -763,6 +923,7 @@ fn handle_spawn_campfire(
     client: EcsEntity,
     target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     match server.state.read_component_copied::<comp::Pos>(target) {
@@ -791,11 +952,55 @@ fn handle_spawn_campfire(
     }
 }
 
+fn handle_suspects(
+    server: &mut Server,
+    client: EcsEntity,
+    _target: EcsEntity,
+    _args: String,
+    _parsed_args: &[Argument],
+    _action: &ChatCommand,
+) {
+    let ecs = server.state.ecs();
+    let time = ecs.read_resource::<Time>().0;
+
+    let mut suspects = (
+        &ecs.read_storage::<comp::Player>(),
+        &ecs.read_storage::<anticheat::Suspicion>(),
+    )
+        .join()
+        .map(|(player, suspicion)| (player, suspicion, suspicion.score_at(time)))
+        .filter(|(_, suspicion, score)| {
+            *score > 0.0
+                || suspicion.flagged
+                || suspicion.movement_rejections > 0
+                || suspicion.action_rejections > 0
+        })
+        .collect::<Vec<_>>();
+    suspects.sort_by(|(_, _, a), (_, _, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+    let msg = suspects.iter().fold(
+        format!("{} suspected players:", suspects.len()),
+        |s, (player, suspicion, score)| {
+            format!(
+                "{}\n[{}] score {:.1}, {} rejected movements, {} rejected actions{}",
+                s,
+                player.alias,
+                score,
+                suspicion.movement_rejections,
+                suspicion.action_rejections,
+                if suspicion.flagged { " (flagged)" } else { "" },
+            )
+        },
+    );
+    server.notify_client(client, ChatType::CommandInfo.server_msg(msg));
+}
+
 fn handle_players(
     server: &mut Server,
     client: EcsEntity,
     _target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     let ecs = server.state.ecs();
@@ -828,6 +1033,7 @@ fn handle_build(
     client: EcsEntity,
     target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     if server
@@ -863,6 +1069,7 @@ fn handle_help(
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let Some(cmd) = scan_fmt_some!(&args, &action.arg_fmt(), ChatCommand) {
@@ -898,6 +1105,7 @@ fn handle_kill_npcs(
     client: EcsEntity,
     _target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     let ecs = server.state.ecs();
@@ -924,6 +1132,7 @@ fn handle_object(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     let obj_type = scan_fmt!(&args, &action.arg_fmt(), String);
@@ -992,6 +1201,7 @@ fn handle_light(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     let (opt_r, opt_g, opt_b, opt_x, opt_y, opt_z, opt_s) =
@@ -1052,12 +1262,89 @@ fn handle_light(
     }
 }
 
+fn handle_location(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: String,
+    _parsed_args: &[Argument],
+    action: &ChatCommand,
+) {
+    // The name is made of all the remaining words
+    let mut words = args.split_whitespace();
+    let parsed = words
+        .next()
+        .map(|action| (action, join_words(words)))
+        .filter(|(_, name)| !name.is_empty());
+    let msg = match parsed {
+        Some((action, name)) if action == "add" => {
+            match server.state.read_component_copied::<comp::Pos>(target) {
+                Some(pos) => {
+                    server
+                        .state
+                        .ecs()
+                        .write_resource::<NamedLocations>()
+                        .set(name.clone(), pos.0);
+                    format!("Added location '{}'.", name)
+                },
+                None => {
+                    server.notify_client(
+                        client,
+                        ChatType::CommandError.server_msg("You have no position!"),
+                    );
+                    return;
+                },
+            }
+        },
+        Some((action, name)) if action == "remove" => {
+            if server
+                .state
+                .ecs()
+                .write_resource::<NamedLocations>()
+                .remove(&name)
+            {
+                format!("Removed location '{}'.", name)
+            } else {
+                server.notify_client(
+                    client,
+                    ChatType::CommandError.server_msg(format!("No location named '{}'.", name)),
+                );
+                return;
+            }
+        },
+        _ => {
+            server.notify_client(
+                client,
+                ChatType::CommandError.server_msg(action.help_string()),
+            );
+            return;
+        },
+    };
+
+    let locations = server
+        .state
+        .ecs()
+        .read_resource::<NamedLocations>()
+        .to_msg();
+    server
+        .state
+        .notify_registered_clients(ServerGeneral::NamedLocations(locations));
+    server.notify_client(client, ChatType::CommandInfo.server_msg(msg));
+}
+
+/// Joins words with single spaces, so that names are the same however many
+/// spaces they were typed with
+fn join_words<'a>(words: impl Iterator<Item = &'a str>) -> String {
+    words.collect::<Vec<_>>().join(" ")
+}
+
 #[allow(clippy::useless_conversion)] // TODO: Pending review in #587
 fn handle_lantern(
     server: &mut Server,
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let (Some(s), r, g, b) = scan_fmt_some!(&args, &action.arg_fmt(), f32, f32, f32, f32) {
@@ -1104,6 +1391,7 @@ fn handle_explosion(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     let power = scan_fmt!(&args, &action.arg_fmt(), f32).unwrap_or(8.0);
@@ -1131,12 +1419,14 @@ fn handle_explosion(
                     pos: pos.0,
                     explosion: Explosion {
                         radius: 3.0 * power,
+                        radius_per_power: 0.0,
                         max_damage: (100.0 * power) as u32,
                         min_damage: 0,
                         max_heal: 0,
                         min_heal: 0,
                         terrain_destruction_power: power,
                         energy_regen: 0,
+                        knockback: 10.0 * power,
                     },
                     owner: ecs.read_storage::<Uid>().get(target).copied(),
                     friendly_damage: true,
@@ -1155,6 +1445,7 @@ fn handle_waypoint(
     client: EcsEntity,
     target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     match server.state.read_component_copied::<comp::Pos>(target) {
@@ -1184,6 +1475,7 @@ fn handle_adminify(
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let Ok(alias) = scan_fmt!(&args, &action.arg_fmt(), String) {
@@ -1235,6 +1527,7 @@ fn handle_tell(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if client != target {
@@ -1294,6 +1587,7 @@ fn handle_faction(
     client: EcsEntity,
     target: EcsEntity,
     msg: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     if client != target {
@@ -1326,6 +1620,7 @@ fn handle_group(
     client: EcsEntity,
     target: EcsEntity,
     msg: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     if client != target {
@@ -1358,6 +1653,7 @@ fn handle_region(
     client: EcsEntity,
     target: EcsEntity,
     msg: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     if client != target {
@@ -1386,6 +1682,7 @@ fn handle_say(
     client: EcsEntity,
     target: EcsEntity,
     msg: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     if client != target {
@@ -1409,11 +1706,81 @@ fn handle_say(
     }
 }
 
+/// Energy it costs to shout a message
+const SHOUT_ENERGY_COST: i32 = 100;
+/// Minimum time between two shouts of a player, in seconds
+const SHOUT_COOLDOWN: f64 = 10.0;
+
+fn handle_shout(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    msg: String,
+    _parsed_args: &[Argument],
+    action: &ChatCommand,
+) {
+    if client != target {
+        // This happens when [ab]using /sudo
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg("It's rude to impersonate people"),
+        );
+        return;
+    }
+    if msg.is_empty() {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg(action.help_string()),
+        );
+        return;
+    }
+
+    let now = server.state.get_time();
+    let ecs = server.state.ecs();
+    let on_cooldown = ecs
+        .read_storage::<Client>()
+        .get(client)
+        .and_then(|client| client.last_shout)
+        .map_or(false, |last_shout| now - last_shout < SHOUT_COOLDOWN);
+    if on_cooldown {
+        server.notify_client(
+            client,
+            ChatType::CommandError
+                .server_msg("You need to catch your breath before shouting again"),
+        );
+        return;
+    }
+
+    let paid = ecs
+        .write_storage::<comp::Energy>()
+        .get_mut(client)
+        .map_or(false, |energy| {
+            energy
+                .try_change_by(-SHOUT_ENERGY_COST, comp::EnergySource::Ability)
+                .is_ok()
+        });
+    if !paid {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg("You don't have enough energy to shout"),
+        );
+        return;
+    }
+
+    if let Some(client) = ecs.write_storage::<Client>().get_mut(client) {
+        client.last_shout = Some(now);
+    }
+    if let Some(uid) = ecs.read_storage::<Uid>().get(client).copied() {
+        server.state.send_chat(ChatType::Shout(uid).chat_msg(msg));
+    }
+}
+
 fn handle_world(
     server: &mut Server,
     client: EcsEntity,
     target: EcsEntity,
     msg: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     if client != target {
@@ -1442,6 +1809,7 @@ fn handle_join_faction(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if client != target {
@@ -1499,12 +1867,83 @@ fn handle_join_faction(
     }
 }
 
+#[cfg(not(feature = "worldgen"))]
+fn handle_site(
+    server: &mut Server,
+    client: EcsEntity,
+    _target: EcsEntity,
+    _args: String,
+    _parsed_args: &[Argument],
+    _action: &ChatCommand,
+) {
+    server.notify_client(
+        client,
+        ChatType::CommandError.server_msg("Unsupported without worldgen enabled"),
+    );
+}
+
+#[cfg(feature = "worldgen")]
+fn handle_site(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: String,
+    _parsed_args: &[Argument],
+    action: &ChatCommand,
+) {
+    let name = args.trim();
+    if name.is_empty() {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg(action.help_string()),
+        );
+        return;
+    }
+    let site = server
+        .world
+        .sites()
+        .find(|site| site.name.eq_ignore_ascii_case(name));
+    let site = if let Some(site) = site {
+        site
+    } else {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg(format!("No site is called '{}'.", name)),
+        );
+        return;
+    };
+
+    if server
+        .state
+        .read_component_copied::<comp::Pos>(target)
+        .is_some()
+    {
+        let alt = server.world.sim().get_alt_approx(site.wpos).unwrap_or(0.0);
+        server.state.write_component(
+            target,
+            comp::Pos(site.wpos.map(|e| e as f32).with_z(alt + 10.0)),
+        );
+        server.state.write_component(target, comp::ForceUpdate);
+        server.notify_client(
+            client,
+            ChatType::CommandInfo
+                .server_msg(format!("Teleported to {} ({:?}).", site.name, site.kind)),
+        );
+    } else {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg("You have no position."),
+        );
+    }
+}
+
 #[cfg(not(feature = "worldgen"))]
 fn handle_debug_column(
     server: &mut Server,
     client: EcsEntity,
     target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     server.notify_client(
@@ -1519,6 +1958,7 @@ fn handle_debug_column(
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     let sim = server.world.sim();
@@ -1546,6 +1986,7 @@ fn handle_debug_column(
             let downhill = chunk.downhill;
             let river = &chunk.river;
             let flux = chunk.flux;
+            let biome = server.world.get_biome(wpos)?;
 
             Some(format!(
                 r#"wpos: {:?}
@@ -1560,7 +2001,8 @@ temp {:?}
 humidity {:?}
 rockiness {:?}
 tree_density {:?}
-spawn_rate {:?} "#,
+spawn_rate {:?}
+biome {:?} "#,
                 wpos,
                 alt,
                 col.alt,
@@ -1575,7 +2017,8 @@ spawn_rate {:?} "#,
                 humidity,
                 rockiness,
                 tree_density,
-                spawn_rate
+                spawn_rate,
+                biome
             ))
         };
         if let Some(s) = msg_generator() {
@@ -1617,6 +2060,7 @@ fn handle_give_exp(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     let (a_exp, a_alias) = scan_fmt_some!(&args, &action.arg_fmt(), i64, String);
@@ -1629,8 +2073,13 @@ fn handle_give_exp(
 
         match target {
             Ok(player) => {
-                if let Some(stats) = ecs.write_storage::<comp::Stats>().get_mut(player) {
-                    stats.exp.change_by(exp);
+                if ecs.read_storage::<comp::Stats>().contains(player) {
+                    ecs.read_resource::<EventBus<ServerEvent>>()
+                        .emit_now(ServerEvent::ExpGain {
+                            entity: player,
+                            exp,
+                            source: comp::ExpSource::Command,
+                        });
                 } else {
                     error_msg = Some(ChatType::CommandError.server_msg("Player has no stats!"));
                 }
@@ -1651,6 +2100,7 @@ fn handle_set_level(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     let (a_lvl, a_alias) = scan_fmt_some!(&args, &action.arg_fmt(), u32, String);
@@ -1706,6 +2156,7 @@ fn handle_debug(
     client: EcsEntity,
     target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     if let Ok(items) = comp::Item::new_from_asset_glob("common.items.debug.*") {
@@ -1736,6 +2187,7 @@ fn handle_remove_lights(
     client: EcsEntity,
     target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     let opt_radius = scan_fmt_some!(&args, &action.arg_fmt(), f32);
@@ -1782,11 +2234,67 @@ fn handle_remove_lights(
     );
 }
 
+fn handle_run_script(
+    server: &mut Server,
+    client: EcsEntity,
+    target: EcsEntity,
+    args: String,
+    _parsed_args: &[Argument],
+    action: &ChatCommand,
+) {
+    if let (Some(name), script_args) = scan_fmt_some!(&args, &action.arg_fmt(), String, String) {
+        if server.script_depth >= MAX_SCRIPT_DEPTH {
+            server.notify_client(
+                client,
+                ChatType::CommandError.server_msg("Scripts can't run other scripts"),
+            );
+            return;
+        }
+        let script = match Script::load(&server.data_dir().path, &name) {
+            Ok(script) => script,
+            Err(e) => {
+                server.notify_client(
+                    client,
+                    ChatType::CommandError
+                        .server_msg(format!("Could not load script '{}': {}", name, e)),
+                );
+                return;
+            },
+        };
+        let ecs = server.state.ecs();
+        let player = ecs
+            .read_storage::<comp::Player>()
+            .get(target)
+            .map(|player| player.alias.clone());
+        let pos = ecs
+            .read_storage::<comp::Pos>()
+            .get(target)
+            .map_or_else(|| ecs.read_resource::<SpawnPoint>().0, |pos| pos.0);
+        let vars = Variables::new(player.as_deref(), pos, script_args.as_deref().unwrap_or(""));
+        match script.commands(&vars) {
+            Ok(commands) => {
+                server.script_depth += 1;
+                for command in commands {
+                    server.process_chat_cmd(client, command);
+                }
+                server.script_depth -= 1;
+            },
+            Err(e) => server.notify_client(client, ChatType::CommandError.server_msg(e)),
+        }
+    } else {
+        server.notify_client(
+            client,
+            ChatType::CommandError.server_msg(action.help_string()),
+        );
+    }
+}
+
 fn handle_sudo(
     server: &mut Server,
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let (Some(player_alias), Some(cmd), cmd_args) =
@@ -1800,7 +2308,23 @@ fn handle_sudo(
                 .find(|(_, player)| player.alias == player_alias)
                 .map(|(entity, _)| entity);
             if let Some(entity) = entity_opt {
-                get_handler(&action)(server, client, entity, cmd_args, &action);
+                match action.parse_args(&cmd_args) {
+                    Ok(parsed_args) => get_handler(&action)(
+                        server,
+                        client,
+                        entity,
+                        cmd_args,
+                        &parsed_args,
+                        &action,
+                    ),
+                    Err(error) => {
+                        server.notify_client(client, ChatType::CommandError.server_msg(error));
+                        server.notify_client(
+                            client,
+                            ChatType::CommandError.server_msg(action.help_string()),
+                        );
+                    },
+                }
             } else {
                 server.notify_client(
                     client,
@@ -1826,6 +2350,7 @@ fn handle_version(
     client: EcsEntity,
     _target: EcsEntity,
     _args: String,
+    _parsed_args: &[Argument],
     _action: &ChatCommand,
 ) {
     server.notify_client(
@@ -1843,6 +2368,7 @@ fn handle_whitelist(
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let Ok((whitelist_action, username)) = scan_fmt!(&args, &action.arg_fmt(), String, String) {
@@ -1902,7 +2428,7 @@ fn handle_whitelist(
     }
 }
 
-fn kick_player(server: &mut Server, target_player: EcsEntity, reason: &str) {
+pub(crate) fn kick_player(server: &mut Server, target_player: EcsEntity, reason: &str) {
     server
         .state
         .ecs()
@@ -1919,6 +2445,7 @@ fn handle_kick(
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let (Some(target_alias), reason_opt) =
@@ -1960,6 +2487,7 @@ fn handle_ban(
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let (Some(target_alias), reason_opt) =
@@ -2029,6 +2557,7 @@ fn handle_unban(
     client: EcsEntity,
     _target: EcsEntity,
     args: String,
+    _parsed_args: &[Argument],
     action: &ChatCommand,
 ) {
     if let Ok(username) = scan_fmt!(&args, &action.arg_fmt(), String) {
@@ -2065,3 +2594,26 @@ fn handle_unban(
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cooldowns_start_once_used() {
+        let mut cooldowns = CommandCooldowns::default();
+        assert_eq!(cooldowns.check(&ChatCommand::Home, 0.0), Ok(()));
+        // Checking alone, like a failed attempt, doesn't start the cooldown
+        assert_eq!(cooldowns.check(&ChatCommand::Home, 1.0), Ok(()));
+
+        cooldowns.start(&ChatCommand::Home, 10.0);
+        assert_eq!(cooldowns.check(&ChatCommand::Home, 70.0), Err(240.0));
+        assert_eq!(cooldowns.check(&ChatCommand::Home, 310.0), Ok(()));
+        // Each command cools down separately
+        assert_eq!(cooldowns.check(&ChatCommand::TpPos, 70.0), Ok(()));
+
+        // Commands without a cooldown never wait
+        cooldowns.start(&ChatCommand::Help, 10.0);
+        assert_eq!(cooldowns.check(&ChatCommand::Help, 10.0), Ok(()));
+    }
+}
@@ -1,10 +1,12 @@
 use crate::{Client, ClientType, ServerInfo};
+use common::{msg::Capability, terrain::ChunkEncoding};
 use crossbeam::{bounded, unbounded, Receiver, Sender};
 use futures_channel::oneshot;
 use futures_executor::block_on;
 use futures_timer::Delay;
 use futures_util::{select, FutureExt};
-use network::{Network, Participant, Promises};
+use hashbrown::HashSet;
+use network::{Compression, Network, Participant, Promises};
 use std::{sync::Arc, thread, time::Duration};
 use tracing::{debug, error, trace, warn};
 
@@ -107,6 +109,11 @@ impl ConnectionHandler {
         let mut register_stream = participant.open(10, reliablec).await?;
         let character_screen_stream = participant.open(10, reliablec).await?;
         let in_game_stream = participant.open(10, reliablec).await?;
+        let mut terrain_stream = participant.open(10, reliablec).await?;
+        // Chunks are large and sent rarely, so they are worth the slower compression
+        terrain_stream.set_compression(Compression::Deflate);
+        // Only accepted by clients that support voice chat, after they got the info
+        let voice_stream = participant.open(3, Promises::REALTIME).await?;
 
         let server_data = receiver.recv()?;
 
@@ -134,9 +141,22 @@ impl ConnectionHandler {
             register_stream,
             in_game_stream,
             character_screen_stream,
+            terrain_stream,
+            voice_stream,
             network_error: false,
             last_ping: server_data.time,
             login_msg_sent: false,
+            last_shout: None,
+            last_input: server_data.time,
+            is_idle: false,
+            idle_warned_at: None,
+            session_start: server_data.time,
+            session_expiring_since: None,
+            pending_register: None,
+            rules_pending: false,
+            chunk_encoding: ChunkEncoding::default(),
+            capabilities: HashSet::new(),
+            physics_ack: None,
         };
 
         client_sender.send(client)?;
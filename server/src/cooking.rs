@@ -0,0 +1,49 @@
+//! Food that players cook over campfires
+//!
+//! Crafting a recipe that has to be cooked takes its ingredients right away,
+//! and the food is handed out once it is done. The longer the cook leaves the
+//! fire while the food is cooking, the more likely it is to burn.
+
+use common::{
+    comp::{object, Body, Pos},
+    recipe::Cooking,
+};
+use specs::{Component, Entities, Entity as EcsEntity, Join, ReadStorage};
+use specs_idvs::IdvStorage;
+use vek::*;
+
+/// Distance within which a fire can be cooked over, and the cook has to stay
+/// so that the food doesn't burn
+pub const FIRE_RANGE: f32 = 5.0;
+
+/// Food being cooked for an entity
+pub struct Meal {
+    pub recipe: String,
+    pub cooking: Cooking,
+    /// Fire the food is cooked over
+    pub fire: EcsEntity,
+    /// Seconds the food has been cooking
+    pub time: f32,
+    /// Seconds the cook spent away from the fire while the food was cooking
+    pub away: f32,
+}
+
+impl Component for Meal {
+    type Storage = IdvStorage<Self>;
+}
+
+/// Closest lit campfire within range of `pos`
+pub fn fire_near(
+    pos: Vec3<f32>,
+    entities: &Entities,
+    bodies: &ReadStorage<Body>,
+    positions: &ReadStorage<Pos>,
+) -> Option<EcsEntity> {
+    (entities, bodies, positions)
+        .join()
+        .filter(|(_, body, _)| matches!(body, Body::Object(object::Body::CampfireLit)))
+        .map(|(entity, _, fire_pos)| (entity, fire_pos.0.distance_squared(pos)))
+        .filter(|(_, dist_sqr)| *dist_sqr < FIRE_RANGE.powi(2))
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(entity, _)| entity)
+}
@@ -1,4 +1,4 @@
-use crate::{sys, Server, StateExt};
+use crate::{spawning::Spawned, sys, Server, StateExt};
 use common::{
     character::CharacterId,
     comp::{
@@ -43,6 +43,7 @@ pub fn handle_create_npc(
     alignment: Alignment,
     scale: Scale,
     drop_item: Option<Item>,
+    spawned: bool,
 ) {
     let group = match alignment {
         Alignment::Wild => None,
@@ -53,6 +54,7 @@ pub fn handle_create_npc(
         Alignment::Owned(_) => None,
     };
 
+    let time = server.state.get_time();
     let entity = server
         .state
         .create_npc(pos, stats, loadout, body)
@@ -77,6 +79,14 @@ pub fn handle_create_npc(
         entity
     };
 
+    let entity = if spawned {
+        entity.with(Spawned {
+            last_observed: time,
+        })
+    } else {
+        entity
+    };
+
     entity.build();
 }
 
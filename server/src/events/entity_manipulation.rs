@@ -1,23 +1,28 @@
+use super::{interaction::unmount, player::handle_persist_character};
 use crate::{
+    anticheat::Suspicion,
     client::Client,
     comp::{biped_large, quadruped_medium, quadruped_small},
     Server, SpawnPoint, StateExt,
 };
 use common::{
     assets::Asset,
+    combat::CombatEvent,
     comp::{
         self,
         chat::{KillSource, KillType},
-        object, Alignment, Body, Damage, DamageSource, Group, HealthChange, HealthSource, Item,
-        Player, Pos, Stats,
+        object, Alignment, Body, Damage, DamageSource, ExpSource, Group, Hazard, HealthChange,
+        HealthSource, HitModifiers, Item, Player, Pos, SkillGroupType, Stats,
     },
+    difficulty,
+    event::{EventBus, ServerEvent},
     lottery::Lottery,
     msg::{PlayerListUpdate, ServerGeneral},
     outcome::Outcome,
-    state::BlockChange,
+    state::{BlockChange, Time},
+    states::{basic_block, stagger},
     sync::{Uid, UidAllocator, WorldSyncExt},
-    sys::combat::BLOCK_ANGLE,
-    terrain::{Block, TerrainGrid},
+    terrain::{self, Block, TerrainGrid},
     vol::ReadVol,
     Explosion,
 };
@@ -27,16 +32,95 @@ use specs::{join::Join, saveload::MarkerAllocator, Entity as EcsEntity, WorldExt
 use tracing::error;
 use vek::Vec3;
 
-pub fn handle_damage(server: &Server, uid: Uid, change: HealthChange) {
+/// Skill points granted for each level gained
+const SKILL_POINTS_PER_LEVEL: u8 = 1;
+
+pub fn handle_damage(
+    server: &Server,
+    uid: Uid,
+    change: HealthChange,
+    source: DamageSource,
+    hit: HitModifiers,
+) {
     let state = &server.state;
     let ecs = state.ecs();
     if let Some(entity) = ecs.entity_from_uid(uid.into()) {
         if let Some(stats) = ecs.write_storage::<Stats>().get_mut(entity) {
-            stats.health.change_by(change);
+            change_health(stats, change);
+            if change.amount < 0 && !stats.should_die() && react_to_hits(source) {
+                let stability = comp::HitReaction::stability(
+                    &stats.body_type,
+                    ecs.read_storage::<comp::Loadout>().get(entity),
+                );
+                let reaction = comp::HitReaction::from_damage(
+                    change.amount.abs() as u32,
+                    stats.health.maximum(),
+                    stability,
+                );
+                if let (Some(reaction), Some(character_state)) = (
+                    reaction,
+                    ecs.write_storage::<comp::CharacterState>().get_mut(entity),
+                ) {
+                    if let Some(new_state) = reaction.interrupt(character_state) {
+                        *character_state = new_state;
+                    }
+                }
+            }
+        }
+        // Getting hurt throws riders off their mounts, whether the rider or the
+        // mount was hit
+        if change.amount < 0 {
+            let rider = match ecs.read_storage::<comp::MountState>().get(entity) {
+                Some(comp::MountState::MountedBy(rider)) => ecs.entity_from_uid((*rider).into()),
+                _ => None,
+            };
+            unmount(ecs, entity);
+            if let Some(rider) = rider {
+                unmount(ecs, rider);
+            }
+        }
+        if let Some(pos) = ecs.read_storage::<Pos>().get(entity) {
+            ecs.write_resource::<Vec<CombatEvent>>()
+                .push(CombatEvent::Damage {
+                    pos: pos.0,
+                    target: uid,
+                    change,
+                    source,
+                    hit,
+                });
         }
     }
 }
 
+/// Whether damage of this kind makes characters flinch or stagger. Damage over
+/// time and falls hurt without knocking anyone around.
+fn react_to_hits(source: DamageSource) -> bool {
+    !matches!(
+        source,
+        DamageSource::Buff | DamageSource::Falling | DamageSource::Healing
+    )
+}
+
+/// Applies a change in health, keeping training dummies from ever dying so that
+/// they can be used to measure damage output
+fn change_health(stats: &mut Stats, change: HealthChange) {
+    stats.health.change_by(change);
+    if stats.body_type == Body::Object(object::Body::TrainingDummy) && stats.health.current() == 0 {
+        stats
+            .health
+            .set_to(stats.health.maximum(), HealthSource::Revive);
+        // Keep the damage as the last change so that it still shows up
+        stats.health.last_change = (0.0, change);
+    }
+}
+
+pub fn handle_buff(server: &Server, uid: Uid, buff: comp::BuffData, source: Option<Uid>) {
+    let state = &server.state;
+    if let Some(entity) = state.ecs().entity_from_uid(uid.into()) {
+        state.apply_buff(entity, buff, source);
+    }
+}
+
 pub fn handle_knockback(server: &Server, entity: EcsEntity, impulse: Vec3<f32>) {
     let state = &server.state;
     let mut velocities = state.ecs().write_storage::<comp::Vel>();
@@ -46,6 +130,47 @@ pub fn handle_knockback(server: &Server, entity: EcsEntity, impulse: Vec3<f32>)
     let mut clients = state.ecs().write_storage::<Client>();
     if let Some(client) = clients.get_mut(entity) {
         client.send_msg(ServerGeneral::Knockback(impulse));
+        // Let movement validation accept the movement caused by the impulse
+        let time = state.ecs().read_resource::<Time>().0;
+        if let Ok(entry) = state.ecs().write_storage::<Suspicion>().entry(entity) {
+            entry
+                .or_insert_with(Suspicion::default)
+                .record_impulse(impulse, time);
+        }
+    }
+}
+
+/// Wear down the poise of an entity, staggering it once poise breaks
+pub fn handle_poise_damage(server: &Server, entity: EcsEntity, amount: u32) {
+    let ecs = server.state.ecs();
+    let broken = ecs
+        .write_storage::<comp::Poise>()
+        .get_mut(entity)
+        .map_or(false, |poise| poise.damage(amount));
+    let alive = ecs
+        .read_storage::<Stats>()
+        .get(entity)
+        .map_or(false, |stats| !stats.should_die());
+    if broken && alive {
+        if let Some(character_state) = ecs.write_storage::<comp::CharacterState>().get_mut(entity) {
+            if let Some(new_state) = comp::HitReaction::Stagger.interrupt(character_state) {
+                *character_state = new_state;
+            }
+        }
+    }
+}
+
+/// Stagger an entity whose block was overwhelmed
+pub fn handle_guard_break(server: &Server, entity: EcsEntity) {
+    let mut character_states = server.state.ecs().write_storage::<comp::CharacterState>();
+    if let Some(character_state) = character_states.get_mut(entity) {
+        // A hit that breaks the guard may already have staggered the blocker
+        if character_state.is_block() || character_state.is_hit_reaction() {
+            *character_state = comp::CharacterState::Stagger(stagger::Data {
+                time_left: basic_block::GUARD_BREAK_DURATION,
+                was_wielded: character_state.returns_to_wield(),
+            });
+        }
     }
 }
 
@@ -75,122 +200,74 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
     // If it was a player that died
     if let Some(_player) = state.ecs().read_storage::<Player>().get(entity) {
         if let Some(uid) = state.ecs().read_storage::<Uid>().get(entity) {
-            let kill_source = match cause {
-                HealthSource::Attack { by } => {
-                    // Get attacker entity
-                    if let Some(char_entity) = state.ecs().entity_from_uid(by.into()) {
-                        // Check if attacker is another player or entity with stats (npc)
-                        if state
-                            .ecs()
-                            .read_storage::<Player>()
-                            .get(char_entity)
-                            .is_some()
-                        {
-                            KillSource::Player(by, KillType::Melee)
-                        } else if let Some(stats) =
-                            state.ecs().read_storage::<Stats>().get(char_entity)
-                        {
-                            KillSource::NonPlayer(stats.name.clone(), KillType::Melee)
-                        } else {
-                            KillSource::NonPlayer("<?>".to_string(), KillType::Melee)
-                        }
-                    } else {
-                        KillSource::NonPlayer("<?>".to_string(), KillType::Melee)
-                    }
-                },
-                HealthSource::Projectile { owner: Some(by) } => {
-                    // Get projectile owner entity TODO: add names to projectiles and send in
-                    // message
-                    if let Some(char_entity) = state.ecs().entity_from_uid(by.into()) {
-                        // Check if attacker is another player or entity with stats (npc)
-                        if state
-                            .ecs()
-                            .read_storage::<Player>()
-                            .get(char_entity)
-                            .is_some()
-                        {
-                            KillSource::Player(by, KillType::Projectile)
-                        } else if let Some(stats) =
-                            state.ecs().read_storage::<Stats>().get(char_entity)
-                        {
-                            KillSource::NonPlayer(stats.name.clone(), KillType::Projectile)
-                        } else {
-                            KillSource::NonPlayer("<?>".to_string(), KillType::Projectile)
-                        }
-                    } else {
-                        KillSource::NonPlayer("<?>".to_string(), KillType::Projectile)
-                    }
-                },
-                HealthSource::Explosion { owner: Some(by) } => {
-                    // Get explosion owner entity
-                    if let Some(char_entity) = state.ecs().entity_from_uid(by.into()) {
-                        // Check if attacker is another player or entity with stats (npc)
-                        if state
+            let kill_type = match cause {
+                HealthSource::Attack { .. } => Some(KillType::Melee),
+                HealthSource::Projectile { .. } => Some(KillType::Projectile),
+                HealthSource::Explosion { .. } => Some(KillType::Explosion),
+                HealthSource::Energy { .. } => Some(KillType::Energy),
+                HealthSource::Buff { .. } => Some(KillType::Buff),
+                _ => None,
+            };
+            let kill_source = match (cause.attacker(), kill_type) {
+                (Some(by), Some(kill_type)) => {
+                    // Check if attacker is another player or entity with stats (npc)
+                    let attacker = state.ecs().entity_from_uid(by.into());
+                    if attacker.map_or(false, |a| {
+                        state.ecs().read_storage::<Player>().get(a).is_some()
+                    }) {
+                        KillSource::Player(by, kill_type)
+                    } else if let Some(name) = attacker.and_then(|a| {
+                        state
                             .ecs()
-                            .read_storage::<Player>()
-                            .get(char_entity)
-                            .is_some()
-                        {
-                            KillSource::Player(by, KillType::Explosion)
-                        } else if let Some(stats) =
-                            state.ecs().read_storage::<Stats>().get(char_entity)
-                        {
-                            KillSource::NonPlayer(stats.name.clone(), KillType::Explosion)
-                        } else {
-                            KillSource::NonPlayer("<?>".to_string(), KillType::Explosion)
-                        }
+                            .read_storage::<Stats>()
+                            .get(a)
+                            .map(|stats| stats.name.clone())
+                    }) {
+                        KillSource::NonPlayer(name, kill_type)
                     } else {
-                        KillSource::NonPlayer("<?>".to_string(), KillType::Explosion)
+                        KillSource::NonPlayer("<?>".to_string(), kill_type)
                     }
                 },
-                HealthSource::Energy { owner: Some(by) } => {
-                    // Get energy owner entity
-                    if let Some(char_entity) = state.ecs().entity_from_uid(by.into()) {
-                        // Check if attacker is another player or entity with stats (npc)
-                        if state
-                            .ecs()
-                            .read_storage::<Player>()
-                            .get(char_entity)
-                            .is_some()
-                        {
-                            KillSource::Player(by, KillType::Energy)
-                        } else if let Some(stats) =
-                            state.ecs().read_storage::<Stats>().get(char_entity)
-                        {
-                            KillSource::NonPlayer(stats.name.clone(), KillType::Energy)
-                        } else {
-                            KillSource::NonPlayer("<?>".to_string(), KillType::Energy)
-                        }
-                    } else {
-                        KillSource::NonPlayer("<?>".to_string(), KillType::Energy)
-                    }
+                _ => match cause {
+                    HealthSource::Hazard(Hazard::Falling) => KillSource::FallDamage,
+                    HealthSource::Suicide => KillSource::Suicide,
+                    _ => KillSource::Other,
                 },
-                HealthSource::World => KillSource::FallDamage,
-                HealthSource::Suicide => KillSource::Suicide,
-                HealthSource::Projectile { owner: None }
-                | HealthSource::Explosion { owner: None }
-                | HealthSource::Energy { owner: None }
-                | HealthSource::Revive
-                | HealthSource::Command
-                | HealthSource::LevelUp
-                | HealthSource::Item
-                | HealthSource::Healing { by: _ }
-                | HealthSource::Unknown => KillSource::Other,
             };
+            // Tell the player why they died, so they can be shown a death screen
+            if let Some(client) = state.ecs().write_storage::<Client>().get_mut(entity) {
+                client.send_msg(ServerGeneral::YouDied {
+                    killer: cause.attacker(),
+                    cause: kill_source.clone(),
+                });
+            }
             state.notify_registered_clients(
                 comp::ChatType::Kill(kill_source, *uid).server_msg("".to_string()),
             );
         }
     }
 
+    // Let nearby clients show the kill in their kill feed
+    if let (Some(pos), Some(uid), true) = (
+        state.read_component_copied::<Pos>(entity),
+        state.read_component_copied::<Uid>(entity),
+        state.ecs().read_storage::<Stats>().contains(entity),
+    ) {
+        state
+            .ecs()
+            .write_resource::<Vec<CombatEvent>>()
+            .push(CombatEvent::Kill {
+                pos: pos.0,
+                target: uid,
+                killer: cause.attacker(),
+            });
+    }
+
     // Give EXP to the killer if entity had stats
+    let mut exp_rewards = Vec::new();
     (|| {
-        let mut stats = state.ecs().write_storage::<Stats>();
-        let by = if let HealthSource::Attack { by }
-        | HealthSource::Projectile { owner: Some(by) }
-        | HealthSource::Energy { owner: Some(by) }
-        | HealthSource::Explosion { owner: Some(by) } = cause
-        {
+        let stats = state.ecs().read_storage::<Stats>();
+        let by = if let Some(by) = cause.attacker() {
             by
         } else {
             return;
@@ -254,19 +331,25 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
                 .collect::<Vec<_>>();
             let exp = exp_reward / (num_not_pets_in_range as f32 + ATTACKER_EXP_WEIGHT);
             exp_reward = exp * ATTACKER_EXP_WEIGHT;
-            members_in_range.into_iter().for_each(|e| {
-                if let Some(stats) = stats.get_mut(e) {
-                    stats.exp.change_by(exp.ceil() as i64);
-                }
-            });
+            members_in_range
+                .into_iter()
+                .for_each(|e| exp_rewards.push((e, exp.ceil() as i64)));
         }
 
-        if let Some(attacker_stats) = stats.get_mut(attacker) {
-            // TODO: Discuss whether we should give EXP by Player
-            // Killing or not.
-            attacker_stats.exp.change_by(exp_reward.ceil() as i64);
-        }
+        // TODO: Discuss whether we should give EXP by Player
+        // Killing or not.
+        exp_rewards.push((attacker, exp_reward.ceil() as i64));
     })();
+    {
+        let server_eventbus = state.ecs().read_resource::<EventBus<ServerEvent>>();
+        for (entity, exp) in exp_rewards {
+            server_eventbus.emit_now(ServerEvent::ExpGain {
+                entity,
+                exp,
+                source: ExpSource::Kill,
+            });
+        }
+    }
 
     if state
         .ecs()
@@ -302,8 +385,8 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
     } else if state.ecs().read_storage::<comp::Agent>().contains(entity) {
         use specs::Builder;
 
-        // Decide for a loot drop before turning into a lootbag
-        let old_body = state.ecs().write_storage::<Body>().remove(entity);
+        // Decide for a loot drop before the body is left behind as a corpse
+        let old_body = state.ecs().read_storage::<Body>().get(entity).copied();
         let mut rng = rand::thread_rng();
         let mut lottery = || {
             Lottery::<String>::load_expect(match old_body {
@@ -385,23 +468,41 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
             })
         };
 
-        let item = {
+        // Stronger NPCs get more rolls on their loot table
+        let level = state
+            .ecs()
+            .read_storage::<Stats>()
+            .get(entity)
+            .map_or(1, |stats| stats.level.level());
+        let items = {
             let mut item_drops = state.ecs().write_storage::<comp::ItemDrop>();
             item_drops.remove(entity).map_or_else(
-                || Item::new_from_asset_expect(lottery().choose()),
-                |item_drop| item_drop.0,
+                || {
+                    (0..difficulty::loot_rolls(level))
+                        .map(|_| Item::new_from_asset_expect(lottery().choose()))
+                        .collect()
+                },
+                |item_drop| vec![item_drop.0],
             )
         };
 
         let pos = state.ecs().read_storage::<comp::Pos>().get(entity).cloned();
         if let Some(pos) = pos {
-            let _ = state
-                .create_object(
-                    comp::Pos(pos.0 + Vec3::unit_z() * 0.25),
-                    object::Body::Pouch,
-                )
-                .with(item)
-                .build();
+            let scatter = items.len() > 1;
+            for item in items {
+                let drop = state
+                    .create_object(
+                        comp::Pos(pos.0 + Vec3::unit_z() * 0.25),
+                        object::Body::Pouch,
+                    )
+                    .with(item)
+                    .build();
+                // Toss multiple drops apart so they don't end up in a single pile
+                if scatter {
+                    let vel = Vec3::new(rng.gen_range(-2.0, 2.0), rng.gen_range(-2.0, 2.0), 5.0);
+                    let _ = state.ecs().write_storage().insert(drop, comp::Vel(vel));
+                }
+            }
         } else {
             error!(
                 ?entity,
@@ -409,9 +510,17 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
             )
         }
 
+        // Leave the body lying around as a corpse until it is cleaned up as
+        // litter, so that it can be seen falling and looted
+        state.ecs().write_storage::<comp::Agent>().remove(entity);
+        state
+            .ecs()
+            .write_storage::<comp::LightEmitter>()
+            .remove(entity);
         let _ = state
-            .delete_entity_recorded(entity)
-            .map_err(|e| error!(?e, ?entity, "Failed to delete destroyed entity"));
+            .ecs()
+            .write_storage()
+            .insert(entity, comp::Vel(Vec3::zero()));
     } else {
         let _ = state
             .delete_entity_recorded(entity)
@@ -427,6 +536,16 @@ pub fn handle_destroy(server: &mut Server, entity: EcsEntity, cause: HealthSourc
     */
 }
 
+pub fn handle_expire(server: &mut Server, entity: EcsEntity) {
+    let state = server.state_mut();
+    if !state.ecs().is_alive(entity) {
+        return;
+    }
+    let _ = state
+        .delete_entity_recorded(entity)
+        .map_err(|e| error!(?e, ?entity, "Failed to delete expired entity"));
+}
+
 pub fn handle_land_on_ground(server: &Server, entity: EcsEntity, vel: Vec3<f32>) {
     let state = &server.state;
     if vel.z <= -30.0 {
@@ -436,19 +555,60 @@ pub fn handle_land_on_ground(server: &Server, entity: EcsEntity, vel: Vec3<f32>)
                 healthchange: -falldmg,
                 source: DamageSource::Falling,
             };
-            if let Some(loadout) = state.ecs().read_storage::<comp::Loadout>().get(entity) {
-                damage.modify_damage(false, loadout);
-            }
-            stats.health.change_by(comp::HealthChange {
+            let hit = state
+                .ecs()
+                .read_storage::<comp::Loadout>()
+                .get(entity)
+                .map(|loadout| damage.modify_damage(None, loadout))
+                .unwrap_or_default();
+            let change = comp::HealthChange {
                 amount: damage.healthchange as i32,
-                cause: comp::HealthSource::World,
-            });
+                cause: comp::HealthSource::Hazard(comp::Hazard::Falling),
+            };
+            change_health(stats, change);
+            if let (Some(pos), Some(uid)) = (
+                state.read_component_copied::<Pos>(entity),
+                state.read_component_copied::<Uid>(entity),
+            ) {
+                state
+                    .ecs()
+                    .write_resource::<Vec<CombatEvent>>()
+                    .push(CombatEvent::Damage {
+                        pos: pos.0,
+                        target: uid,
+                        change,
+                        source: damage.source,
+                        hit,
+                    });
+            }
         }
     }
 }
 
-pub fn handle_respawn(server: &Server, entity: EcsEntity) {
-    let state = &server.state;
+/// Leave a copy of a dead player's body where they died, so that other
+/// players don't see it vanish as soon as the player respawns
+fn leave_corpse(state: &mut common::state::State, entity: EcsEntity) {
+    let ecs = state.ecs();
+    let corpse = (
+        ecs.read_storage::<Pos>().get(entity).copied(),
+        ecs.read_storage::<comp::Ori>().get(entity).copied(),
+        ecs.read_storage::<Body>().get(entity).copied(),
+        ecs.read_storage::<Stats>().get(entity).cloned(),
+        ecs.read_storage::<comp::Loadout>().get(entity).cloned(),
+    );
+    if let (Some(pos), Some(ori), Some(body), Some(stats), Some(loadout)) = corpse {
+        if stats.is_dead {
+            state
+                .create_npc(pos, stats, loadout, body)
+                .with(ori)
+                .with(Alignment::Passive)
+                .build();
+        }
+    }
+}
+
+pub fn handle_respawn(server: &mut Server, entity: EcsEntity) {
+    let state = &mut server.state;
 
     // Only clients can respawn
     if state
@@ -462,11 +622,16 @@ pub fn handle_respawn(server: &Server, entity: EcsEntity) {
             .map(|wp| wp.get_pos())
             .unwrap_or(state.ecs().read_resource::<SpawnPoint>().0);
 
+        leave_corpse(state, entity);
+
         state
             .ecs()
             .write_storage::<comp::Stats>()
             .get_mut(entity)
             .map(|stats| stats.revive());
+        if let Some(client) = state.ecs().write_storage::<Client>().get_mut(entity) {
+            client.send_msg(ServerGeneral::Respawned);
+        }
         state
             .ecs()
             .write_storage::<comp::Pos>()
@@ -505,22 +670,33 @@ pub fn handle_explosion(
             .max(explosion.max_damage as f32 / 100.0)
     };
     // Add an outcome
-    ecs.write_resource::<Vec<Outcome>>()
-        .push(Outcome::Explosion {
-            pos,
+    let mut outcomes = ecs.write_resource::<Vec<Outcome>>();
+    outcomes.push(Outcome::Explosion {
+        pos,
+        power: outcome_power,
+        radius: explosion.radius,
+        is_attack: explosion.max_heal > 0 || explosion.max_damage > 0,
+        reagent,
+    });
+    ecs.read_resource::<EventBus<comp::Sound>>()
+        .emit_now(comp::Sound::new(pos, comp::SoundKind::Explosion {
             power: outcome_power,
-            radius: explosion.radius,
-            is_attack: explosion.max_heal > 0 || explosion.max_damage > 0,
-            reagent,
-        });
+        }));
     let owner_entity = owner.and_then(|uid| {
         ecs.read_resource::<UidAllocator>()
             .retrieve_entity_internal(uid.into())
     });
     let groups = ecs.read_storage::<comp::Group>();
-
-    for (entity_b, pos_b, ori_b, character_b, stats_b, loadout_b) in (
+    let mut knockbacks = Vec::new();
+    let mut guard_breaks = Vec::new();
+    let mut poise_damages = Vec::new();
+    let mut combat_events = ecs.write_resource::<Vec<CombatEvent>>();
+    let loadouts = ecs.read_storage::<comp::Loadout>();
+    let owner_loadout = owner_entity.and_then(|e| loadouts.get(e));
+
+    for (entity_b, uid_b, pos_b, ori_b, character_b, stats_b, loadout_b) in (
         &ecs.entities(),
+        &ecs.read_storage::<Uid>(),
         &ecs.read_storage::<comp::Pos>(),
         &ecs.read_storage::<comp::Ori>(),
         ecs.read_storage::<comp::CharacterState>().maybe(),
@@ -552,6 +728,12 @@ pub fn handle_explosion(
                 continue;
             }
 
+            // Terrain shields entities from the blast
+            let body_center = pos_b.0 + Vec3::unit_z();
+            if !terrain::line_of_sight(&*ecs.read_resource::<TerrainGrid>(), pos, body_center) {
+                continue;
+            }
+
             // Weapon gives base damage
             let source = if is_heal {
                 DamageSource::Healing
@@ -572,22 +754,34 @@ pub fn handle_explosion(
                 source,
             };
 
-            let block = character_b.map(|c_b| c_b.is_block()).unwrap_or(false)
-                && ori_b.0.angle_between(pos - pos_b.0) < BLOCK_ANGLE.to_radians() / 2.0;
-
-            if let Some(loadout) = loadout_b {
-                damage.modify_damage(block, loadout);
+            let block = character_b
+                .and_then(|c_b| c_b.block())
+                .filter(|block| block.covers(pos_b.0, ori_b, pos));
+            if block.map_or(false, |block| block.breaks_guard(-damage.healthchange)) {
+                guard_breaks.push(entity_b);
             }
 
+            let hit = loadout_b
+                .map(|loadout| damage.modify_damage(block, loadout))
+                .unwrap_or_default();
+
             if damage.healthchange != 0.0 {
                 let cause = if is_heal {
                     HealthSource::Healing { by: owner }
                 } else {
                     HealthSource::Explosion { owner }
                 };
-                stats_b.health.change_by(HealthChange {
+                let change = HealthChange {
                     amount: damage.healthchange as i32,
                     cause,
+                };
+                change_health(stats_b, change);
+                combat_events.push(CombatEvent::Damage {
+                    pos: pos_b.0,
+                    target: *uid_b,
+                    change,
+                    source: damage.source,
+                    hit,
                 });
                 if let Some(owner) = owner_entity {
                     if let Some(energy) = ecs.write_storage::<comp::Energy>().get_mut(owner) {
@@ -595,10 +789,35 @@ pub fn handle_explosion(
                             .change_by(explosion.energy_regen as i32, comp::EnergySource::HitEnemy);
                     }
                 }
+                poise_damages.push((
+                    entity_b,
+                    comp::Poise::damage_from_hit(damage.healthchange, owner_loadout),
+                ));
+            }
+
+            if is_damage && explosion.knockback > 0.0 {
+                // Fling entities away from the centre, and a little upwards so they leave the
+                // ground
+                let dir = (body_center - pos)
+                    .try_normalized()
+                    .unwrap_or_else(Vec3::unit_z);
+                let impulse =
+                    (dir + Vec3::unit_z() * 0.5).normalized() * explosion.knockback * strength;
+                knockbacks.push((entity_b, impulse));
             }
         }
     }
 
+    for (entity, impulse) in knockbacks {
+        handle_knockback(server, entity, impulse);
+    }
+    for entity in guard_breaks {
+        handle_guard_break(server, entity);
+    }
+    for (entity, amount) in poise_damages {
+        handle_poise_damage(server, entity, amount);
+    }
+
     const RAYS: usize = 500;
 
     // Color terrain
@@ -662,15 +881,47 @@ pub fn handle_explosion(
     }
 }
 
+/// Changes the experience of an entity and tells its client how much it gained
+/// and why. Level ups happen in the stats system once enough experience was
+/// gained.
+pub fn handle_exp_gain(server: &mut Server, entity: EcsEntity, exp: i64, source: ExpSource) {
+    if exp == 0 {
+        return;
+    }
+    if let Some(stats) = server.state.ecs().write_storage::<Stats>().get_mut(entity) {
+        stats.exp.change_by(exp);
+    } else {
+        return;
+    }
+    server.notify_client(entity, ServerGeneral::ExpGain { exp, source });
+}
+
 pub fn handle_level_up(server: &mut Server, entity: EcsEntity, new_level: u32) {
-    let uids = server.state.ecs().read_storage::<Uid>();
-    let uid = uids
+    if let Some(stats) = server.state.ecs().write_storage::<Stats>().get_mut(entity) {
+        let skill_set = &mut stats.skill_set;
+        if skill_set
+            .check_unlock_skill_group(SkillGroupType::T1)
+            .is_ok()
+        {
+            skill_set.unlock_skill_group(SkillGroupType::T1);
+        }
+        skill_set.add_skill_points(SkillGroupType::T1, SKILL_POINTS_PER_LEVEL);
+    }
+
+    let uid = *server
+        .state
+        .ecs()
+        .read_storage::<Uid>()
         .get(entity)
         .expect("Failed to fetch uid component for entity.");
 
     server
         .state
         .notify_registered_clients(ServerGeneral::PlayerListUpdate(
-            PlayerListUpdate::LevelChange(*uid, new_level),
+            PlayerListUpdate::LevelChange(uid, new_level),
         ));
+    server.notify_client(entity, ServerGeneral::LevelUp(new_level));
+
+    // Don't risk losing a level to a crash before the next periodic save
+    handle_persist_character(server, entity);
 }
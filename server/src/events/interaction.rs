@@ -1,14 +1,27 @@
 use crate::{
+    anticheat,
     client::{Client, RegionSubscription},
+    persistence::block_diff::BlockDiffs,
+    state_ext::StateExt,
     Server,
 };
 use common::{
-    comp::{self, item},
-    msg::ServerGeneral,
+    assets::Asset,
+    comp::{self, item, MAX_MOUNT_RANGE_SQR},
+    lottery::Lottery,
+    msg::{Notification, ServerGeneral},
+    state::Time,
+    states::fishing,
     sync::{Uid, WorldSyncExt},
+    terrain::{sign::is_sign, BiomeKind, Block, TerrainGrid},
+    util::Dir,
+    vol::ReadVol,
 };
-use specs::{world::WorldExt, Entity as EcsEntity};
+use rand::{thread_rng, Rng};
+use specs::{world::WorldExt, Entity as EcsEntity, Join};
+use std::time::Duration;
 use tracing::error;
+use vek::*;
 
 pub fn handle_lantern(server: &mut Server, entity: EcsEntity, enable: bool) {
     let ecs = server.state_mut().ecs();
@@ -65,7 +78,22 @@ pub fn handle_mount(server: &mut Server, mounter: EcsEntity, mountee: EcsEntity)
             Some(comp::MountState::Unmounted)
         );
 
-        if not_mounting_yet {
+        // The range is stored in thousandths of a block, squared
+        let in_range = {
+            let positions = state.ecs().read_storage::<comp::Pos>();
+            match (positions.get(mounter), positions.get(mountee)) {
+                (Some(a), Some(b)) => {
+                    a.0.distance_squared(b.0) * 1000.0 <= MAX_MOUNT_RANGE_SQR as f32
+                },
+                _ => false,
+            }
+        };
+        let mountee_pos = state.read_component_copied::<comp::Pos>(mountee);
+        let visible = mountee_pos.map_or(false, |pos| {
+            anticheat::check_line_of_sight(state.ecs(), mounter, pos.0 + Vec3::unit_z())
+        });
+
+        if not_mounting_yet && in_range && visible {
             if let (Some(mounter_uid), Some(mountee_uid)) = (
                 state.ecs().uid_from_entity(mounter),
                 state.ecs().uid_from_entity(mountee),
@@ -78,20 +106,64 @@ pub fn handle_mount(server: &mut Server, mounter: EcsEntity, mountee: EcsEntity)
 }
 
 pub fn handle_unmount(server: &mut Server, mounter: EcsEntity) {
-    let state = server.state_mut();
-    let mountee_entity = state
-        .ecs()
+    unmount(server.state.ecs(), mounter);
+}
+
+/// Takes a rider off the entity it is mounted on, if any
+pub fn unmount(ecs: &specs::World, mounter: EcsEntity) {
+    let mountee_entity = ecs
         .write_storage::<comp::Mounting>()
-        .get(mounter)
-        .and_then(|mountee| state.ecs().entity_from_uid(mountee.0.into()));
+        .remove(mounter)
+        .and_then(|mountee| ecs.entity_from_uid(mountee.0.into()));
     if let Some(mountee_entity) = mountee_entity {
-        state
-            .ecs()
-            .write_storage::<comp::MountState>()
+        ecs.write_storage::<comp::MountState>()
             .get_mut(mountee_entity)
             .map(|ms| *ms = comp::MountState::Unmounted);
     }
-    state.delete_component::<comp::Mounting>(mounter);
+}
+
+/// Makes the waypoint the player is resting at the place they respawn at
+pub fn handle_set_waypoint(server: &Server, entity: EcsEntity) {
+    let ecs = server.state().ecs();
+    let positions = ecs.read_storage::<comp::Pos>();
+    let player_pos = match positions.get(entity) {
+        Some(pos) => pos.0,
+        None => return,
+    };
+
+    let at_waypoint = (&positions, &ecs.read_storage::<comp::WaypointArea>())
+        .join()
+        .any(|(pos, area)| player_pos.distance_squared(pos.0) < area.radius().powi(2));
+    if !at_waypoint {
+        return;
+    }
+
+    let time = *ecs.read_resource::<Time>();
+    if let Err(e) = ecs
+        .write_storage()
+        .insert(entity, comp::Waypoint::new(player_pos, time))
+    {
+        error!(?e, "Failed to set waypoint");
+        return;
+    }
+    if let Some(client) = ecs.write_storage::<Client>().get_mut(entity) {
+        client.send_msg(ServerGeneral::Notification(Notification::WaypointSaved));
+    }
+}
+
+pub fn handle_edit_sign(server: &Server, pos: Vec3<i32>, text: String) {
+    let state = server.state();
+    // The sign may have been broken since the edit was sent
+    if !state.terrain().get(pos).map_or(false, is_sign) {
+        return;
+    }
+
+    let text = Some(text).filter(|text| !text.trim().is_empty());
+    state.set_sign(pos, text.clone());
+    state
+        .ecs()
+        .write_resource::<BlockDiffs>()
+        .set_sign(pos, text);
 }
 
 #[allow(clippy::nonminimal_bool)] // TODO: Pending review in #587
@@ -130,13 +202,9 @@ pub fn handle_possess(server: &Server, possessor_uid: Uid, possesse_uid: Uid) {
 
                 let item = comp::Item::new_from_asset_expect("common.items.debug.possess");
                 if let item::ItemKind::Tool(tool) = item.kind() {
-                    let mut abilities = tool.get_abilities();
-                    let mut ability_drain = abilities.drain(..);
                     let debug_item = comp::ItemConfig {
+                        abilities: tool.get_abilities(),
                         item,
-                        ability1: ability_drain.next(),
-                        ability2: ability_drain.next(),
-                        ability3: ability_drain.next(),
                         block_ability: None,
                         dodge_ability: None,
                     };
@@ -193,3 +261,85 @@ pub fn handle_possess(server: &Server, possessor_uid: Uid, possesse_uid: Uid) {
         }
     }
 }
+
+/// How far in front of the angler a cast line lands
+const CAST_DISTANCE: f32 = 8.0;
+/// How far below the angler the line can reach down to the water
+const MAX_LINE_DROP: f32 = 20.0;
+
+/// Check that a freshly cast line landed in water and decide when a fish will
+/// bite
+pub fn handle_cast_line(server: &Server, entity: EcsEntity, dir: Dir) {
+    let ecs = server.state().ecs();
+    let pos = match ecs.read_storage::<comp::Pos>().get(entity) {
+        Some(pos) => pos.0,
+        None => return,
+    };
+
+    let forward = Vec2::from(*dir)
+        .try_normalized()
+        .unwrap_or_else(Vec2::unit_y);
+    let above = pos + Vec3::from(forward * CAST_DISTANCE) + Vec3::unit_z() * 2.0;
+    let (dist, block) = ecs
+        .read_resource::<TerrainGrid>()
+        .ray(above, above - Vec3::unit_z() * MAX_LINE_DROP)
+        .until(|block: &Block| !block.is_air())
+        .cast();
+    let in_water = matches!(block, Ok(Some(block)) if block.is_liquid());
+
+    let mut character_states = ecs.write_storage::<comp::CharacterState>();
+    if let Some(character_state) = character_states.get_mut(entity) {
+        match character_state {
+            comp::CharacterState::Fishing(data) if in_water => {
+                if let fishing::Stage::Waiting(None) = data.stage {
+                    data.stage = fishing::Stage::Waiting(Some(fishing::Line {
+                        bobber: above - Vec3::unit_z() * dist,
+                        bite_at: Duration::from_secs_f32(thread_rng().gen_range(3.0, 12.0)),
+                    }));
+                }
+            },
+            comp::CharacterState::Fishing(_) => {
+                *character_state = comp::CharacterState::Wielding;
+                if let Some(client) = ecs.write_storage::<Client>().get_mut(entity) {
+                    client.send_msg(
+                        comp::ChatType::Meta
+                            .server_msg("Your line needs to land in water.".to_owned()),
+                    );
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Give the angler a fish caught where the line ended, depending on the biome
+pub fn handle_catch(server: &mut Server, entity: EcsEntity, bobber: Vec3<f32>) {
+    let state = server.state_mut();
+    let biome = {
+        let terrain = state.terrain();
+        terrain
+            .get_key(terrain.pos_key(bobber.map(|e| e.floor() as i32)))
+            .map(|chunk| chunk.meta().biome())
+    };
+    let table = match biome {
+        Some(BiomeKind::Ocean) => "common.loot_tables.loot_table_fishing_ocean",
+        Some(BiomeKind::Swamp) => "common.loot_tables.loot_table_fishing_swamp",
+        Some(BiomeKind::Snowlands) | Some(BiomeKind::Mountain) => {
+            "common.loot_tables.loot_table_fishing_cold"
+        },
+        _ => "common.loot_tables.loot_table_fishing_river",
+    };
+    let item = comp::Item::new_from_asset_expect(Lottery::<String>::load_expect(table).choose());
+
+    let event = match state
+        .ecs()
+        .write_storage::<comp::Inventory>()
+        .get_mut(entity)
+        .map(|inventory| inventory.push(item.clone()))
+    {
+        Some(None) => comp::InventoryUpdateEvent::Collected(item),
+        Some(Some(_)) => comp::InventoryUpdateEvent::CollectFailed,
+        None => return,
+    };
+    state.write_component(entity, comp::InventoryUpdate::new(event));
+}
@@ -1,18 +1,28 @@
-use crate::{client::Client, Server, StateExt};
+use super::entity_creation::handle_shoot;
+use crate::{
+    anticheat,
+    client::Client,
+    cooking::{fire_near, Meal},
+    Server, StateExt,
+};
 use common::{
+    alchemy::default_alchemy,
     comp::{
         self, item,
         slot::{self, Slot},
         Pos, MAX_PICKUP_RANGE_SQR,
     },
+    effect::Effect,
     msg::ServerGeneral,
-    recipe::default_recipe_book,
+    recipe::{default_recipe_book, CraftError, Crafted},
+    state::State,
     sync::{Uid, WorldSyncExt},
     vol::ReadVol,
 };
 use comp::LightEmitter;
 use rand::Rng;
 use specs::{join::Join, world::WorldExt, Builder, Entity as EcsEntity, WriteStorage};
+use std::sync::Arc;
 use tracing::{debug, error};
 use vek::{Rgb, Vec3};
 
@@ -37,6 +47,7 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
     let state = server.state_mut();
     let mut dropped_items = Vec::new();
     let mut thrown_items = Vec::new();
+    let mut thrown_projectiles = Vec::new();
 
     match manip {
         comp::InventoryManip::Pickup(uid) => {
@@ -65,6 +76,20 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                     debug!("Failed to pick up item as not within range, Uid: {}", uid);
                     return;
                 };
+                let item_pos = state
+                    .ecs()
+                    .read_storage::<Pos>()
+                    .get(item_entity)
+                    .map(|p| p.0);
+                if !item_pos.map_or(false, |p| {
+                    anticheat::check_line_of_sight(state.ecs(), entity, p)
+                }) {
+                    debug!(
+                        "Failed to pick up item as it is behind terrain, Uid: {}",
+                        uid
+                    );
+                    return;
+                }
 
                 // Attempt to add the item to the player's inventory
                 match inv.push(item) {
@@ -96,6 +121,22 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
         },
 
         comp::InventoryManip::Collect(pos) => {
+            let block_center = pos.map(|e| e as f32 + 0.5);
+            if !within_pickup_range(
+                state.ecs().read_storage::<Pos>().get(entity),
+                Some(&Pos(block_center)),
+            ) {
+                debug!("Failed to collect block as not within range, pos={}", pos);
+                return;
+            }
+            if !anticheat::check_line_of_sight(state.ecs(), entity, block_center) {
+                debug!(
+                    "Failed to collect block as it is behind terrain, pos={}",
+                    pos
+                );
+                return;
+            }
+
             let block = state.terrain().get(pos).ok().copied();
 
             if let Some(block) = block {
@@ -191,8 +232,14 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                     } else if let Some(item) = inventory.take(slot) {
                         match item.kind() {
                             ItemKind::Consumable { kind, effect, .. } => {
-                                maybe_effect = Some(*effect);
-                                Some(comp::InventoryUpdateEvent::Consumed(kind.clone()))
+                                // Only use up the item if its effect can actually be applied
+                                if can_receive_effect(state, entity, effect) {
+                                    maybe_effect = Some(*effect);
+                                    Some(comp::InventoryUpdateEvent::Consumed(kind.clone()))
+                                } else {
+                                    inventory.insert_or_stack(slot, item).unwrap();
+                                    None
+                                }
                             },
                             ItemKind::Throwable { kind, .. } => {
                                 if let Some(pos) =
@@ -211,6 +258,30 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
                                 }
                                 Some(comp::InventoryUpdateEvent::Used)
                             },
+                            ItemKind::Thrown { projectile, .. } => {
+                                // Only use up the item if it can actually be thrown
+                                let alive = state
+                                    .read_storage::<comp::Stats>()
+                                    .get(entity)
+                                    .map_or(false, |stats| !stats.is_dead);
+                                if alive && state.ecs().read_storage::<Pos>().contains(entity) {
+                                    let dir = state
+                                        .read_storage::<comp::Controller>()
+                                        .get(entity)
+                                        .map(|c| c.inputs.look_dir)
+                                        .or_else(|| {
+                                            state
+                                                .read_component_copied::<comp::Ori>(entity)
+                                                .map(|ori| ori.0)
+                                        })
+                                        .unwrap_or_default();
+                                    thrown_projectiles.push((dir, projectile.clone()));
+                                    Some(comp::InventoryUpdateEvent::Used)
+                                } else {
+                                    inventory.insert_or_stack(slot, item).unwrap();
+                                    None
+                                }
+                            },
                             ItemKind::Utility {
                                 kind: comp::item::Utility::Collar,
                                 ..
@@ -343,16 +414,16 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
             let inventory = inventories.get_mut(entity);
             let loadout = loadouts.get_mut(entity);
 
-            slot::swap(a, b, inventory, loadout);
+            let event = match slot::swap(a, b, inventory, loadout) {
+                Ok(()) => comp::InventoryUpdateEvent::Swapped,
+                Err(e) => comp::InventoryUpdateEvent::EquipFailed(e),
+            };
 
             // :/
             drop(loadouts);
             drop(inventories);
 
-            state.write_component(
-                entity,
-                comp::InventoryUpdate::new(comp::InventoryUpdateEvent::Swapped),
-            );
+            state.write_component(entity, comp::InventoryUpdate::new(event));
         },
 
         comp::InventoryManip::Drop(slot) => {
@@ -389,36 +460,201 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
         },
 
         comp::InventoryManip::CraftRecipe(recipe) => {
+            let recipe_book = default_recipe_book();
+            let already_cooking = state.ecs().read_storage::<Meal>().contains(entity);
+            let fire = state
+                .read_component_copied::<comp::Pos>(entity)
+                .and_then(|pos| {
+                    let ecs = state.ecs();
+                    fire_near(
+                        pos.0,
+                        &ecs.entities(),
+                        &ecs.read_storage(),
+                        &ecs.read_storage(),
+                    )
+                });
             if let Some(inv) = state
                 .ecs()
                 .write_storage::<comp::Inventory>()
                 .get_mut(entity)
             {
-                let recipe_book = default_recipe_book();
-                let craft_result = recipe_book.get(&recipe).and_then(|r| r.perform(inv).ok());
+                let craft_result = match recipe_book.get(&recipe) {
+                    Some(r) if r.cooking.is_some() && already_cooking => {
+                        Err(CraftError::AlreadyCooking)
+                    },
+                    _ => recipe_book.craft(&recipe, inv, fire.is_some()),
+                };
 
                 // FIXME: We should really require the drop and write to be atomic!
-                if craft_result.is_some() {
-                    let _ = state.ecs().write_storage().insert(
-                        entity,
-                        comp::InventoryUpdate::new(comp::InventoryUpdateEvent::Craft),
-                    );
+                let event = match &craft_result {
+                    Ok(_) => comp::InventoryUpdateEvent::Craft,
+                    Err(error) => {
+                        debug!(?entity, ?recipe, ?error, "Failed to craft recipe");
+                        comp::InventoryUpdateEvent::CraftFailed(error.clone())
+                    },
+                };
+                let _ = state
+                    .ecs()
+                    .write_storage()
+                    .insert(entity, comp::InventoryUpdate::new(event));
+
+                match (craft_result, fire) {
+                    // Drop the item if there wasn't enough space
+                    (Ok(Crafted::Done(Some((item, amount)))), _) => {
+                        for _ in 0..amount {
+                            dropped_items.push((
+                                state
+                                    .read_component_copied::<comp::Pos>(entity)
+                                    .unwrap_or_default(),
+                                state
+                                    .read_component_copied::<comp::Ori>(entity)
+                                    .unwrap_or_default(),
+                                item.clone(),
+                            ));
+                        }
+                    },
+                    (Ok(Crafted::Cooking(cooking)), Some(fire)) => {
+                        let _ = state.ecs().write_storage().insert(entity, Meal {
+                            recipe,
+                            cooking: cooking.clone(),
+                            fire,
+                            time: 0.0,
+                            away: 0.0,
+                        });
+                    },
+                    _ => {},
                 }
+            }
+        },
 
-                // Drop the item if there wasn't enough space
-                if let Some(Some((item, amount))) = craft_result {
-                    for _ in 0..amount {
-                        dropped_items.push((
-                            state
-                                .read_component_copied::<comp::Pos>(entity)
-                                .unwrap_or_default(),
-                            state
-                                .read_component_copied::<comp::Ori>(entity)
-                                .unwrap_or_default(),
-                            item.clone(),
-                        ));
-                    }
-                }
+        comp::InventoryManip::Sort => {
+            if let Some(inventory) = state
+                .ecs()
+                .write_storage::<comp::Inventory>()
+                .get_mut(entity)
+            {
+                *inventory = inventory.sorted();
+            }
+            state.write_component(
+                entity,
+                comp::InventoryUpdate::new(comp::InventoryUpdateEvent::Swapped),
+            );
+        },
+
+        comp::InventoryManip::MergeStacks(from, to) => {
+            let merged = state
+                .ecs()
+                .write_storage::<comp::Inventory>()
+                .get_mut(entity)
+                .map_or(false, |inventory| inventory.stack_merge(from, to));
+            if merged {
+                state.write_component(
+                    entity,
+                    comp::InventoryUpdate::new(comp::InventoryUpdateEvent::Swapped),
+                );
+            } else {
+                debug!(?entity, ?from, ?to, "Rejected merging inventory stacks");
+            }
+        },
+
+        comp::InventoryManip::SplitStack(slot, amount) => {
+            let split = state
+                .ecs()
+                .write_storage::<comp::Inventory>()
+                .get_mut(entity)
+                .map_or(false, |inventory| inventory.split_stack(slot, amount));
+            if split {
+                state.write_component(
+                    entity,
+                    comp::InventoryUpdate::new(comp::InventoryUpdateEvent::Swapped),
+                );
+            } else {
+                debug!(
+                    ?entity,
+                    ?slot,
+                    ?amount,
+                    "Rejected splitting inventory stack"
+                );
+            }
+        },
+
+        comp::InventoryManip::Brew(slots) => {
+            let alchemy = default_alchemy();
+            let ecs = state.ecs();
+            let mut inventories = ecs.write_storage::<comp::Inventory>();
+            let mut journals = ecs.write_storage::<comp::AlchemyJournal>();
+            if let (Some(inv), Some(journal)) =
+                (inventories.get_mut(entity), journals.get_mut(entity))
+            {
+                let event = match alchemy.brew_from(inv, &slots, journal) {
+                    Ok((potions, learned)) => {
+                        let names = potions.iter().map(|p| p.name().to_string()).collect();
+                        // Drop the potions if there isn't enough space
+                        for potion in potions {
+                            if let Some(potion) = inv.push(potion) {
+                                dropped_items.push((
+                                    state
+                                        .read_component_copied::<comp::Pos>(entity)
+                                        .unwrap_or_default(),
+                                    state
+                                        .read_component_copied::<comp::Ori>(entity)
+                                        .unwrap_or_default(),
+                                    potion,
+                                ));
+                            }
+                        }
+                        if learned > 0 {
+                            if let Some(client) = ecs.write_storage::<Client>().get_mut(entity) {
+                                client
+                                    .send_msg(ServerGeneral::AlchemyJournalUpdate(journal.clone()));
+                            }
+                        }
+                        comp::InventoryUpdateEvent::Brewed {
+                            potions: names,
+                            learned,
+                        }
+                    },
+                    Err(error) => {
+                        debug!(?entity, ?slots, ?error, "Failed to brew");
+                        comp::InventoryUpdateEvent::BrewFailed(error)
+                    },
+                };
+                let _ = ecs
+                    .write_storage()
+                    .insert(entity, comp::InventoryUpdate::new(event));
+            }
+        },
+
+        comp::InventoryManip::Dye(dye_slot, target) => {
+            let ecs = state.ecs();
+            let mut inventories = ecs.write_storage::<comp::Inventory>();
+            let mut loadouts = ecs.write_storage::<comp::Loadout>();
+            let dyed = match (inventories.get_mut(entity), loadouts.get_mut(entity)) {
+                (Some(inventory), Some(loadout)) => slot::dye(dye_slot, target, inventory, loadout),
+                _ => false,
+            };
+            if dyed {
+                let _ = ecs.write_storage().insert(
+                    entity,
+                    comp::InventoryUpdate::new(comp::InventoryUpdateEvent::Used),
+                );
+            } else {
+                debug!(?entity, ?dye_slot, ?target, "Rejected dyeing item");
+            }
+        },
+
+        comp::InventoryManip::SetAppearance(armor_slot, choice) => {
+            let ecs = state.ecs();
+            let inventories = ecs.read_storage::<comp::Inventory>();
+            let mut loadouts = ecs.write_storage::<comp::Loadout>();
+            let valid = match (inventories.get(entity), loadouts.get_mut(entity)) {
+                (Some(inventory), Some(loadout)) => {
+                    slot::set_appearance(armor_slot, choice, inventory, loadout)
+                },
+                _ => false,
+            };
+            if !valid {
+                debug!(?entity, ?armor_slot, ?choice, "Rejected appearance");
             }
         },
     }
@@ -499,6 +735,77 @@ pub fn handle_inventory(server: &mut Server, entity: EcsEntity, manip: comp::Inv
 
         new_entity.build();
     }
+
+    // Throw items that fly as projectiles
+    let uid = state.read_component_copied::<Uid>(entity);
+    for (dir, thrown) in thrown_projectiles {
+        handle_shoot(
+            server,
+            entity,
+            dir,
+            comp::Body::Object(thrown.body),
+            None,
+            thrown.projectile(uid),
+            Some(comp::Gravity(thrown.gravity)),
+            thrown.speed,
+        );
+    }
+}
+
+/// Whether the entity is in a state to benefit from a consumable's effect
+/// Hand out food that is done cooking, dropping what doesn't fit into the
+/// inventory
+pub fn handle_cooked(server: &mut Server, entity: EcsEntity, recipe: String, burnt: bool) {
+    let state = server.state_mut();
+    let recipe = match default_recipe_book().get(&recipe) {
+        Some(recipe) => recipe.clone(),
+        None => return,
+    };
+    let item_def = match (&recipe.cooking, burnt) {
+        (Some(cooking), true) => &cooking.burnt,
+        _ => &recipe.output.0,
+    };
+    let leftover = match state
+        .ecs()
+        .write_storage::<comp::Inventory>()
+        .get_mut(entity)
+    {
+        Some(inv) => recipe.give(item_def, inv),
+        None => return,
+    };
+    state.write_component(
+        entity,
+        comp::InventoryUpdate::new(comp::InventoryUpdateEvent::Cooked {
+            item: comp::Item::new(Arc::clone(item_def)),
+            burnt,
+        }),
+    );
+
+    if let Some((item, amount)) = leftover {
+        let pos = state
+            .read_component_copied::<comp::Pos>(entity)
+            .unwrap_or_default();
+        for _ in 0..amount {
+            state
+                .create_object(Default::default(), comp::object::Body::Pouch)
+                .with(comp::Pos(pos.0 + Vec3::unit_z() * 0.25))
+                .with(item.clone())
+                .with(comp::Vel(Vec3::unit_z() * 5.0))
+                .build();
+        }
+    }
+}
+
+fn can_receive_effect(state: &State, entity: EcsEntity, effect: &Effect) -> bool {
+    let alive = state
+        .ecs()
+        .read_storage::<comp::Stats>()
+        .get(entity)
+        .map_or(false, |stats| !stats.is_dead);
+    match effect {
+        Effect::Energy(_) => alive && state.ecs().read_storage::<comp::Energy>().contains(entity),
+        Effect::Health(_) | Effect::Xp(_) | Effect::Buff(_) => alive,
+    }
 }
 
 fn within_pickup_range(player_position: Option<&Pos>, item_position: Option<&Pos>) -> bool {
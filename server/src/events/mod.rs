@@ -8,14 +8,22 @@ use entity_creation::{
     handle_loaded_character_data, handle_shockwave, handle_shoot,
 };
 use entity_manipulation::{
-    handle_damage, handle_destroy, handle_explosion, handle_knockback, handle_land_on_ground,
-    handle_level_up, handle_respawn,
+    handle_buff, handle_damage, handle_destroy, handle_exp_gain, handle_expire, handle_explosion,
+    handle_guard_break, handle_knockback, handle_land_on_ground, handle_level_up,
+    handle_poise_damage, handle_respawn,
 };
 use group_manip::handle_group;
-use interaction::{handle_lantern, handle_mount, handle_possess, handle_unmount};
-use inventory_manip::handle_inventory;
-use player::{handle_client_disconnect, handle_exit_ingame};
+use interaction::{
+    handle_cast_line, handle_catch, handle_edit_sign, handle_lantern, handle_mount, handle_possess,
+    handle_set_waypoint, handle_unmount,
+};
+use inventory_manip::{handle_cooked, handle_inventory};
+use player::{
+    handle_client_disconnect, handle_exit_ingame, handle_load_ui_preferences,
+    handle_persist_character, handle_store_ui_preferences,
+};
 use specs::{Entity as EcsEntity, WorldExt};
+use std::time::Duration;
 
 mod entity_creation;
 mod entity_manipulation;
@@ -35,6 +43,12 @@ pub enum Event {
         entity: Option<EcsEntity>,
         msg: String,
     },
+    /// The server wants to shut down after warning players for the grace
+    /// period, e.g. for a scheduled restart
+    Shutdown {
+        grace_period: Duration,
+        message: String,
+    },
 }
 
 impl Server {
@@ -83,11 +97,31 @@ impl Server {
                 ServerEvent::Knockback { entity, impulse } => {
                     handle_knockback(&self, entity, impulse)
                 },
-                ServerEvent::Damage { uid, change } => handle_damage(&self, uid, change),
+                ServerEvent::GuardBreak { entity } => handle_guard_break(&self, entity),
+                ServerEvent::PoiseDamage { entity, amount } => {
+                    handle_poise_damage(&self, entity, amount)
+                },
+                ServerEvent::CastLine { entity, dir } => handle_cast_line(&self, entity, dir),
+                ServerEvent::Catch { entity, bobber } => handle_catch(self, entity, bobber),
+                ServerEvent::Damage {
+                    uid,
+                    change,
+                    source,
+                    hit,
+                } => handle_damage(&self, uid, change, source, hit),
+                ServerEvent::Buff { uid, buff, source } => handle_buff(&self, uid, buff, source),
                 ServerEvent::Destroy { entity, cause } => handle_destroy(self, entity, cause),
+                ServerEvent::Expire(entity) => handle_expire(self, entity),
                 ServerEvent::InventoryManip(entity, manip) => handle_inventory(self, entity, manip),
+                ServerEvent::Cooked {
+                    entity,
+                    recipe,
+                    burnt,
+                } => handle_cooked(self, entity, recipe, burnt),
                 ServerEvent::GroupManip(entity, manip) => handle_group(self, entity, manip),
-                ServerEvent::Respawn(entity) => handle_respawn(&self, entity),
+                ServerEvent::Respawn(entity) => handle_respawn(self, entity),
+                ServerEvent::SetWaypoint(entity) => handle_set_waypoint(&self, entity),
+                ServerEvent::EditSign { pos, text } => handle_edit_sign(&self, pos, text),
                 ServerEvent::LandOnGround { entity, vel } => {
                     handle_land_on_ground(&self, entity, vel)
                 },
@@ -105,8 +139,18 @@ impl Server {
                 ServerEvent::UpdateCharacterData { entity, components } => {
                     handle_loaded_character_data(self, entity, components);
                 },
+                ServerEvent::ExpGain {
+                    entity,
+                    exp,
+                    source,
+                } => handle_exp_gain(self, entity, exp, source),
                 ServerEvent::LevelUp(entity, new_level) => handle_level_up(self, entity, new_level),
                 ServerEvent::ExitIngame { entity } => handle_exit_ingame(self, entity),
+                ServerEvent::LoadUiPreferences(entity) => handle_load_ui_preferences(self, entity),
+                ServerEvent::StoreUiPreferences { entity, data } => {
+                    handle_store_ui_preferences(self, entity, data)
+                },
+                ServerEvent::PersistCharacter(entity) => handle_persist_character(self, entity),
                 ServerEvent::CreateNpc {
                     pos,
                     stats,
@@ -116,8 +160,9 @@ impl Server {
                     alignment,
                     scale,
                     drop_item,
+                    spawned,
                 } => handle_create_npc(
-                    self, pos, stats, loadout, body, agent, alignment, scale, drop_item,
+                    self, pos, stats, loadout, body, agent, alignment, scale, drop_item, spawned,
                 ),
                 ServerEvent::CreateWaypoint(pos) => handle_create_waypoint(self, pos),
                 ServerEvent::ClientDisconnect(entity) => {
@@ -3,10 +3,12 @@ use crate::{
     client::Client, login_provider::LoginProvider, persistence, state_ext::StateExt, Server,
 };
 use common::{
+    character::CharacterId,
     comp,
     comp::{group, Player},
     msg::{PlayerListUpdate, ServerGeneral},
     span,
+    state::State,
     sync::{Uid, UidAllocator},
 };
 use futures_executor::block_on;
@@ -87,6 +89,84 @@ pub fn handle_exit_ingame(server: &mut Server, entity: EcsEntity) {
     }
 }
 
+fn character_id(server: &Server, entity: EcsEntity) -> Option<CharacterId> {
+    server
+        .state()
+        .read_storage::<Player>()
+        .get(entity)
+        .and_then(|player| player.character_id)
+}
+
+pub fn handle_load_ui_preferences(server: &mut Server, entity: EcsEntity) {
+    match character_id(server, entity) {
+        Some(character_id) => server
+            .state()
+            .ecs()
+            .read_resource::<persistence::ui_preferences::UiPreferenceStore>()
+            .load(entity, character_id),
+        None => debug!(
+            ?entity,
+            "Not playing a character, can't load UI preferences"
+        ),
+    }
+}
+
+pub fn handle_store_ui_preferences(server: &mut Server, entity: EcsEntity, data: Vec<u8>) {
+    match character_id(server, entity) {
+        Some(character_id) => server
+            .state()
+            .ecs()
+            .read_resource::<persistence::ui_preferences::UiPreferenceStore>()
+            .store(character_id, data),
+        None => debug!(
+            ?entity,
+            "Not playing a character, can't store UI preferences"
+        ),
+    }
+}
+
+/// Saves the character data of a player right away, rather than waiting for
+/// the periodic save
+pub fn handle_persist_character(server: &mut Server, entity: EcsEntity) {
+    persist_character(server.state(), entity);
+}
+
+fn persist_character(state: &State, entity: EcsEntity) {
+    if let (
+        Some(player),
+        Some(stats),
+        Some(inventory),
+        Some(loadout),
+        Some(hotbar),
+        Some(journal),
+        waypoint,
+        updater,
+    ) = (
+        state.read_storage::<Player>().get(entity),
+        state.read_storage::<comp::Stats>().get(entity),
+        state.read_storage::<comp::Inventory>().get(entity),
+        state.read_storage::<comp::Loadout>().get(entity),
+        state.read_storage::<comp::AbilityHotbar>().get(entity),
+        state.read_storage::<comp::AlchemyJournal>().get(entity),
+        state.read_storage::<comp::Waypoint>().get(entity),
+        state
+            .ecs()
+            .read_resource::<persistence::character_updater::CharacterUpdater>(),
+    ) {
+        if let Some(character_id) = player.character_id {
+            updater.update(
+                character_id,
+                stats,
+                inventory,
+                loadout,
+                hotbar,
+                journal,
+                waypoint,
+            );
+        }
+    }
+}
+
 pub fn handle_client_disconnect(server: &mut Server, entity: EcsEntity) -> Event {
     span!(_guard, "handle_client_disconnect");
     if let Some(client) = server
@@ -141,19 +221,7 @@ pub fn handle_client_disconnect(server: &mut Server, entity: EcsEntity) -> Event
     }
 
     // Sync the player's character data to the database
-    if let (Some(player), Some(stats), Some(inventory), Some(loadout), updater) = (
-        state.read_storage::<Player>().get(entity),
-        state.read_storage::<comp::Stats>().get(entity),
-        state.read_storage::<comp::Inventory>().get(entity),
-        state.read_storage::<comp::Loadout>().get(entity),
-        state
-            .ecs()
-            .read_resource::<persistence::character_updater::CharacterUpdater>(),
-    ) {
-        if let Some(character_id) = player.character_id {
-            updater.update(character_id, stats, inventory, loadout);
-        }
-    }
+    persist_character(state, entity);
 
     // Delete client entity
     if let Err(e) = state.delete_entity_recorded(entity) {
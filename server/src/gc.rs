@@ -0,0 +1,88 @@
+//! Cleaning up objects that were left lying around in the world
+//!
+//! Dropped items, projectiles stuck in terrain, bodies of dead NPCs and
+//! projectiles whose owner left the server would otherwise pile up on servers
+//! that run for a long time. The first time such an object is noticed it is
+//! marked as litter, and it is deleted once it has been litter of the same kind
+//! for longer than the configured lifetime of that kind.
+
+use serde::{Deserialize, Serialize};
+use specs::Component;
+use specs_idvs::IdvStorage;
+use std::time::Duration;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LitterKind {
+    DroppedItem,
+    /// A projectile that hit terrain and is stuck in it
+    StuckProjectile,
+    /// A dead NPC that wasn't deleted when it died
+    Corpse,
+    /// A projectile whose owner isn't around anymore
+    OrphanedProjectile,
+}
+
+/// Server-only mark of an entity that is deleted after a while
+pub struct Litter {
+    pub kind: LitterKind,
+    /// Time at which the entity became litter of this kind
+    pub since: f64,
+}
+
+impl Litter {
+    pub fn is_expired(&self, time: f64, lifetimes: &Lifetimes) -> bool {
+        time - self.since >= lifetimes.of(self.kind).as_secs_f64()
+    }
+}
+
+impl Component for Litter {
+    type Storage = IdvStorage<Self>;
+}
+
+/// How long each kind of litter is left lying around before it is deleted
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Lifetimes {
+    pub dropped_items: Duration,
+    pub stuck_projectiles: Duration,
+    pub corpses: Duration,
+    pub orphaned_projectiles: Duration,
+}
+
+impl Lifetimes {
+    pub fn of(&self, kind: LitterKind) -> Duration {
+        match kind {
+            LitterKind::DroppedItem => self.dropped_items,
+            LitterKind::StuckProjectile => self.stuck_projectiles,
+            LitterKind::Corpse => self.corpses,
+            LitterKind::OrphanedProjectile => self.orphaned_projectiles,
+        }
+    }
+}
+
+impl Default for Lifetimes {
+    fn default() -> Self {
+        Self {
+            dropped_items: Duration::from_secs(5 * 60),
+            stuck_projectiles: Duration::from_secs(30),
+            corpses: Duration::from_secs(60),
+            orphaned_projectiles: Duration::from_secs(10),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn litter_expires_after_lifetime_of_its_kind() {
+        let lifetimes = Lifetimes::default();
+        let litter = Litter {
+            kind: LitterKind::StuckProjectile,
+            since: 100.0,
+        };
+        assert!(!litter.is_expired(129.0, &lifetimes));
+        assert!(litter.is_expired(130.0, &lifetimes));
+    }
+}
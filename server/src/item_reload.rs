@@ -0,0 +1,56 @@
+//! Reloading of item definitions and weapon abilities while the server runs
+//!
+//! Meant for test servers: every item definition and the ability map are
+//! watched, and the asset cache is updated as soon as one of them is saved.
+//! The server then points the items held by entities at the new definitions
+//! and abilities, so that the next character state created from a weapon uses
+//! the new damage, durations and other stats. States that are already running
+//! keep their values.
+
+use common::{
+    assets::{watch::ReloadIndicator, Asset},
+    cmd::ITEM_SPECS,
+    comp::item::{
+        tool::{AbilityMap, ABILITY_MAP_SPECIFIER},
+        ItemDef,
+    },
+};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+pub struct ItemReloader {
+    indicator: ReloadIndicator,
+    /// The ability map the abilities of items were last created from
+    ability_map: Arc<AbilityMap>,
+}
+
+impl ItemReloader {
+    pub fn new() -> Self {
+        let mut indicator = ReloadIndicator::new();
+        for specifier in ITEM_SPECS.iter() {
+            if let Err(e) = ItemDef::load_watched(specifier, &mut indicator) {
+                warn!(?e, ?specifier, "Failed to watch item definition");
+            }
+        }
+        let ability_map = AbilityMap::load_watched(ABILITY_MAP_SPECIFIER, &mut indicator)
+            .unwrap_or_else(|e| {
+                warn!(?e, "Failed to watch the ability map");
+                AbilityMap::load_expect(ABILITY_MAP_SPECIFIER)
+            });
+        info!("Watching {} item definitions for changes", ITEM_SPECS.len());
+        Self {
+            indicator,
+            ability_map,
+        }
+    }
+
+    /// If any item definition or the ability map changed since the last call,
+    /// returns the ability map that was in use before
+    pub fn reloaded(&mut self) -> Option<Arc<AbilityMap>> {
+        if !self.indicator.reloaded() {
+            return None;
+        }
+        let ability_map = AbilityMap::load_expect(ABILITY_MAP_SPECIFIER);
+        Some(std::mem::replace(&mut self.ability_map, ability_map))
+    }
+}
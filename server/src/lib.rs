@@ -5,19 +5,29 @@
 #![cfg_attr(not(feature = "worldgen"), feature(const_panic))]
 
 pub mod alias_validator;
+pub mod anticheat;
 mod character_creator;
+pub mod chat_filter;
+mod chunk_cache;
 pub mod chunk_generator;
 pub mod client;
 pub mod cmd;
 pub mod connection_handler;
+pub mod cooking;
 mod data_dir;
 pub mod error;
 pub mod events;
+pub mod gc;
 pub mod input;
+mod item_reload;
 pub mod login_provider;
 pub mod metrics;
 pub mod persistence;
+pub mod rcon;
+pub mod schedule;
+pub mod scripts;
 pub mod settings;
+pub mod spawning;
 pub mod state_ext;
 pub mod sys;
 #[cfg(not(feature = "worldgen"))] mod test_world;
@@ -33,35 +43,53 @@ pub use crate::{
 
 use crate::{
     alias_validator::AliasValidator,
+    anticheat::CheatResponse,
+    chat_filter::ChatFilter,
+    chunk_cache::ChunkCache,
     chunk_generator::ChunkGenerator,
     client::{Client, RegionSubscription},
     cmd::ChatCommandExt,
     connection_handler::ConnectionHandler,
     data_dir::DataDir,
+    item_reload::ItemReloader,
     login_provider::LoginProvider,
+    rcon::{RconListener, RconOutput},
+    schedule::{ActionSchedule, ScheduledAction},
+    scripts::{Script, ScriptSchedule, Variables},
+    settings::{BanRecord, EditableSetting},
     state_ext::StateExt,
     sys::sentinel::{DeletedEntities, TrackedComps},
 };
 use common::{
+    clock::FixedTimestep,
     cmd::ChatCommand,
+    combat::CombatEvent,
     comp::{self, ChatType},
     event::{EventBus, ServerEvent},
+    memory::{MemoryReport, MemoryUsage},
     msg::{
-        ClientType, DisconnectReason, ServerGeneral, ServerInfo, ServerInit, ServerMsg, WorldMapMsg,
+        Capability, ClientType, DisconnectReason, PlayerListUpdate, ServerGeneral, ServerInfo,
+        ServerInit, ServerMsg, WorldMapMsg,
     },
     outcome::Outcome,
     recipe::default_recipe_book,
-    state::{State, TimeOfDay},
-    sync::WorldSyncExt,
+    state::{DayCycleFactor, State, TimeOfDay},
+    sync::{Uid, WorldSyncExt},
+    sys::agent::AgentSchedule,
     terrain::TerrainChunkSize,
     vol::{ReadVol, RectVolSize},
+    weather::Weather,
 };
 use futures_executor::block_on;
 use metrics::{ServerMetrics, StateTickMetrics, TickMetrics};
 use network::{Network, Pid, ProtocolAddr};
 use persistence::{
+    block_diff::BlockDiffs,
     character_loader::{CharacterLoader, CharacterLoaderResponseType},
     character_updater::CharacterUpdater,
+    named_location::NamedLocations,
+    rules::RulesAcknowledgements,
+    ui_preferences::UiPreferenceStore,
 };
 use specs::{join::Join, Builder, Entity as EcsEntity, RunNow, SystemData, WorldExt};
 use std::{
@@ -72,7 +100,7 @@ use std::{
 };
 #[cfg(not(feature = "worldgen"))]
 use test_world::{IndexOwned, World};
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 use uvth::{ThreadPool, ThreadPoolBuilder};
 use vek::*;
 #[cfg(feature = "worldgen")]
@@ -85,6 +113,10 @@ use world::{
 #[macro_use] extern crate diesel;
 #[macro_use] extern crate diesel_migrations;
 
+/// Most ticks of the simulation run in a single server tick. Time beyond that
+/// is dropped, slowing the game down instead of lagging further behind.
+const MAX_SIMULATION_STEPS: u32 = 4;
+
 #[derive(Copy, Clone)]
 struct SpawnPoint(Vec3<f32>);
 
@@ -106,6 +138,17 @@ pub struct Server {
     metrics: ServerMetrics,
     tick_metrics: TickMetrics,
     state_tick_metrics: StateTickMetrics,
+
+    rcon: Option<RconListener>,
+    item_reloader: Option<ItemReloader>,
+    script_schedule: ScriptSchedule,
+    /// Number of scripts run with /runscript that are currently running
+    script_depth: u32,
+
+    simulation: FixedTimestep,
+    sync: FixedTimestep,
+    /// Whether clients were synchronised during the last tick
+    synced: bool,
 }
 
 impl Server {
@@ -150,16 +193,35 @@ impl Server {
         state.ecs_mut().insert(Tick(0));
         state.ecs_mut().insert(network_request_metrics);
         state.ecs_mut().insert(player_metrics);
-        state
-            .ecs_mut()
-            .insert(ChunkGenerator::new(chunk_gen_metrics));
+        let mut chunk_generator =
+            ChunkGenerator::new(chunk_gen_metrics, settings.chunk_gen_workers);
+        if settings.cache_chunks {
+            match ChunkCache::new(data_dir, settings.world_seed) {
+                Ok(cache) => chunk_generator.set_cache(cache),
+                Err(e) => warn!(?e, "Failed to create the chunk cache directory"),
+            }
+        }
+        state.ecs_mut().insert(chunk_generator);
         state
             .ecs_mut()
             .insert(CharacterUpdater::new(&persistence_db_dir)?);
         state
             .ecs_mut()
             .insert(CharacterLoader::new(&persistence_db_dir)?);
+        state
+            .ecs_mut()
+            .insert(BlockDiffs::new(&persistence_db_dir)?);
+        state
+            .ecs_mut()
+            .insert(NamedLocations::new(&persistence_db_dir)?);
+        state
+            .ecs_mut()
+            .insert(RulesAcknowledgements::new(&persistence_db_dir)?);
+        state
+            .ecs_mut()
+            .insert(UiPreferenceStore::new(&persistence_db_dir)?);
         state.ecs_mut().insert(Vec::<Outcome>::new());
+        state.ecs_mut().insert(Vec::<CombatEvent>::new());
 
         // System timers for performance monitoring
         state.ecs_mut().insert(sys::EntitySyncTimer::default());
@@ -169,6 +231,7 @@ impl Server {
         state.ecs_mut().insert(sys::TerrainSyncTimer::default());
         state.ecs_mut().insert(sys::TerrainTimer::default());
         state.ecs_mut().insert(sys::WaypointTimer::default());
+        state.ecs_mut().insert(sys::VoiceTimer::default());
         state.ecs_mut().insert(sys::InviteTimeoutTimer::default());
         state.ecs_mut().insert(sys::PersistenceTimer::default());
 
@@ -176,10 +239,28 @@ impl Server {
         state
             .ecs_mut()
             .insert(sys::PersistenceScheduler::every(Duration::from_secs(10)));
+        state
+            .ecs_mut()
+            .insert(sys::SpawningScheduler::every(settings.spawning.interval));
+        state
+            .ecs_mut()
+            .insert(sys::GcScheduler::every(Duration::from_secs(1)));
+        state.ecs_mut().insert(AgentSchedule::every(
+            settings.simulation_tps / settings.agent_tps.max(1),
+        ));
+        state
+            .ecs_mut()
+            .insert(ActionSchedule::new(&settings.schedule));
 
         // Server-only components
         state.ecs_mut().register::<RegionSubscription>();
         state.ecs_mut().register::<Client>();
+        state.ecs_mut().register::<anticheat::Suspicion>();
+        state.ecs_mut().register::<cooking::Meal>();
+        state.ecs_mut().register::<gc::Litter>();
+        state.ecs_mut().register::<spawning::Spawned>();
+        state.ecs_mut().register::<cmd::CommandCooldowns>();
+        state.ecs_mut().register::<RconOutput>();
 
         //Alias validator
         let banned_words_paths = &settings.banned_words_files;
@@ -211,6 +292,9 @@ impl Server {
         tracing::debug!(?banned_words_count);
         tracing::trace!(?banned_words);
         state.ecs_mut().insert(AliasValidator::new(banned_words));
+        state
+            .ecs_mut()
+            .insert(ChatFilter::new(&settings.chat_filters)?);
 
         #[cfg(feature = "worldgen")]
         let (world, index) = World::generate(settings.world_seed, WorldOpts {
@@ -305,13 +389,37 @@ impl Server {
         // set the spawn point we calculated above
         state.ecs_mut().insert(SpawnPoint(spawn_point));
 
+        #[cfg(feature = "worldgen")]
+        let site_positions = spawning::SitePositions(
+            world
+                .sites()
+                .map(|site| site.wpos.map(|e| e as f32))
+                .collect(),
+        );
+        #[cfg(not(feature = "worldgen"))]
+        let site_positions = spawning::SitePositions::default();
+        state.ecs_mut().insert(site_positions);
+
         // Set starting time for the server.
         state.ecs_mut().write_resource::<TimeOfDay>().0 = settings.start_time;
+        state.ecs_mut().write_resource::<DayCycleFactor>().0 = settings.day_cycle_factor();
+
+        // Summary of the world that clients request for their maps
+        state.ecs_mut().insert(world.get_overview());
+
+        // Start the weather simulation for the whole world
+        state.ecs_mut().insert(Weather::new(
+            map.dimensions_lg.map(|e| 1 << e),
+            settings.world_seed,
+        ));
 
         // Register trackers
         sys::sentinel::register_trackers(&mut state.ecs_mut());
 
         state.ecs_mut().insert(DeletedEntities::default());
+        state
+            .ecs_mut()
+            .insert(sys::entity_sync::SlowSync::default());
 
         let mut metrics = ServerMetrics::new();
         // register all metrics submodules here
@@ -335,8 +443,11 @@ impl Server {
         thread_pool.execute(f);
         block_on(network.listen(ProtocolAddr::Tcp(settings.gameserver_address)))?;
         let connection_handler = ConnectionHandler::new(network);
+        let rcon = RconListener::start(&settings.rcon, data_dir)
+            .map_err(|e| Error::Other(format!("Failed to start the remote console: {}", e)))?;
+        let item_reloader = settings.hot_reload_items.then(ItemReloader::new);
 
-        let this = Self {
+        let mut this = Self {
             state,
             world: Arc::new(world),
             index,
@@ -349,6 +460,15 @@ impl Server {
             metrics,
             tick_metrics,
             state_tick_metrics,
+
+            rcon,
+            item_reloader,
+            script_schedule: ScriptSchedule::new(&settings.scheduled_scripts, Instant::now()),
+            script_depth: 0,
+
+            simulation: FixedTimestep::new(settings.simulation_tps, MAX_SIMULATION_STEPS),
+            sync: FixedTimestep::new(settings.sync_tps, 1),
+            synced: false,
         };
 
         debug!(?settings, "created veloren server with");
@@ -360,6 +480,10 @@ impl Server {
         info!(?version, "Server version");
         debug!(?git_hash, ?git_date, ?git_time, "detailed Server version");
 
+        for name in &settings.startup_scripts {
+            this.run_script(name);
+        }
+
         Ok(this)
     }
 
@@ -372,6 +496,12 @@ impl Server {
             git_hash: common::util::GIT_HASH.to_string(),
             git_date: common::util::GIT_DATE.to_string(),
             auth_provider: settings.auth_server_address.clone(),
+            capabilities: Capability::supported()
+                .into_iter()
+                .filter(|name| {
+                    settings.store_ui_preferences || name != Capability::UiPreferences.name()
+                })
+                .collect(),
         }
     }
 
@@ -433,8 +563,8 @@ impl Server {
         //    and apply them to the state of the game
         // 3) Go through all incoming client network communications, apply them to
         //    the game state
-        // 4) Perform a single LocalState tick (i.e: update the world and entities
-        //    in the world)
+        // 4) Perform as many fixed LocalState ticks as the time passed calls for
+        //    (i.e: update the world and entities in the world)
         // 5) Go through the terrain update queue and apply all changes to
         //    the terrain
         // 6) Send relevant state updates to all clients
@@ -459,32 +589,67 @@ impl Server {
         // Run message receiving sys before the systems in common for decreased latency
         // (e.g. run before controller system)
         sys::message::Sys.run_now(&self.state.ecs());
-
-        let before_state_tick = Instant::now();
-
-        // 4) Tick the server's LocalState.
+        self.handle_cheat_responses();
+        self.handle_idle_players();
+        self.handle_expiring_sessions();
+        self.handle_rcon_requests();
+        self.handle_item_reloads();
+        self.handle_scheduled_scripts();
+        self.handle_scheduled_actions(&mut frontend_events);
+
+        // 4) Tick the server's LocalState as many times as the time passed calls for,
+        //    so that every tick of the simulation lasts the same amount of time.
         // 5) Fetch any generated `TerrainChunk`s and insert them into the terrain.
         // in sys/terrain.rs
-        self.state.tick(dt, sys::add_server_systems, false);
+        let steps = self.simulation.advance(dt);
+        let mut state_tick_time = Duration::default();
+        let mut handle_events_time = Duration::default();
+        let mut update_terrain_time = Duration::default();
+        for _ in 0..steps {
+            let before_state_tick = Instant::now();
+            self.state
+                .tick(self.simulation.step(), sys::add_server_systems, false);
+
+            let before_handle_events = Instant::now();
 
-        let before_handle_events = Instant::now();
+            // Handle game events
+            frontend_events.append(&mut self.handle_events());
 
-        // Handle game events
-        frontend_events.append(&mut self.handle_events());
+            let before_update_terrain = Instant::now();
 
-        let before_update_terrain_and_regions = Instant::now();
+            // Apply terrain changes after processing server events so that changes
+            // made by server events are visible to the next tick and to client
+            // synchronization systems, minimizing the latency of `ServerEvent`
+            // mediated effects
+            self.state.apply_terrain_changes();
 
-        // Apply terrain changes and update the region map after processing server
-        // events so that changes made by server events will be immediately
-        // visible to client synchronization systems, minimizing the latency of
-        // `ServerEvent` mediated effects
-        self.state.update_region_map();
-        self.state.apply_terrain_changes();
+            state_tick_time += before_handle_events - before_state_tick;
+            handle_events_time += before_update_terrain - before_handle_events;
+            update_terrain_time += before_update_terrain.elapsed();
+        }
 
         let before_sync = Instant::now();
 
-        // 6) Synchronise clients with the new state of the world.
-        sys::run_sync_systems(self.state.ecs_mut());
+        // 6) Synchronise clients with the new state of the world. Changes to the
+        //    terrain are kept until then, so that clients don't miss any.
+        self.synced = self.sync.advance(dt) > 0;
+        if self.synced {
+            self.state.update_region_map();
+
+            // Persist the changes made to the terrain
+            let removed_signs = self.state.ecs().write_resource::<BlockDiffs>().record(
+                self.state
+                    .terrain_changes()
+                    .modified_blocks
+                    .iter()
+                    .map(|(pos, block)| (*pos, *block)),
+            );
+            for pos in removed_signs {
+                self.state.set_sign(pos, None);
+            }
+
+            sys::run_sync_systems(self.state.ecs_mut());
+        }
 
         let before_world_tick = Instant::now();
 
@@ -560,6 +725,36 @@ impl Server {
                         .read_resource::<EventBus<ServerEvent>>()
                         .emit_now(message);
                 },
+                CharacterLoaderResponseType::AccountUnlocks(result) => match result {
+                    Ok(unlocks) => self
+                        .notify_client(query_result.entity, ServerGeneral::AccountUnlocks(unlocks)),
+                    Err(error) => self.notify_client(
+                        query_result.entity,
+                        ServerGeneral::CharacterActionError(error.to_string()),
+                    ),
+                },
+            });
+
+        // Send loaded UI preferences to clients that still play the same character
+        self.state
+            .ecs()
+            .read_resource::<UiPreferenceStore>()
+            .responses()
+            .for_each(|response| match response.result {
+                Ok(data) => {
+                    let same_character = self
+                        .state
+                        .ecs()
+                        .read_storage::<comp::Player>()
+                        .get(response.entity)
+                        .map_or(false, |player| {
+                            player.character_id == Some(response.character_id)
+                        });
+                    if same_character {
+                        self.notify_client(response.entity, ServerGeneral::UiPreferences(data));
+                    }
+                },
+                Err(e) => error!(?e, "Failed to load UI preferences"),
             });
 
         {
@@ -621,6 +816,7 @@ impl Server {
             .nanos as i64;
         let terrain_nanos = self.state.ecs().read_resource::<sys::TerrainTimer>().nanos as i64;
         let waypoint_nanos = self.state.ecs().read_resource::<sys::WaypointTimer>().nanos as i64;
+        let voice_nanos = self.state.ecs().read_resource::<sys::VoiceTimer>().nanos as i64;
         let invite_timeout_nanos = self
             .state
             .ecs()
@@ -632,76 +828,68 @@ impl Server {
             .read_resource::<sys::PersistenceTimer>()
             .nanos as i64;
         let total_sys_ran_in_dispatcher_nanos =
-            terrain_nanos + waypoint_nanos + invite_timeout_nanos;
+            terrain_nanos + waypoint_nanos + invite_timeout_nanos + voice_nanos;
 
         // Report timing info
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["new connections"])
-            .set((before_message_system - before_new_connections).as_nanos() as i64);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["state tick"])
-            .set(
-                (before_handle_events - before_state_tick).as_nanos() as i64
-                    - total_sys_ran_in_dispatcher_nanos,
-            );
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["handle server events"])
-            .set((before_update_terrain_and_regions - before_handle_events).as_nanos() as i64);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["update terrain and region map"])
-            .set((before_sync - before_update_terrain_and_regions).as_nanos() as i64);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["world tick"])
-            .set((before_entity_cleanup - before_world_tick).as_nanos() as i64);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["entity cleanup"])
-            .set((before_persistence_updates - before_entity_cleanup).as_nanos() as i64);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["persistence_updates"])
-            .set((end_of_server_tick - before_persistence_updates).as_nanos() as i64);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["entity sync"])
-            .set(entity_sync_nanos);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["message"])
-            .set(message_nanos);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["sentinel"])
-            .set(sentinel_nanos);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["subscription"])
-            .set(subscription_nanos);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["terrain sync"])
-            .set(terrain_sync_nanos);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["terrain"])
-            .set(terrain_nanos);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["waypoint"])
-            .set(waypoint_nanos);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["invite timeout"])
-            .set(invite_timeout_nanos);
-        self.tick_metrics
-            .tick_time
-            .with_label_values(&["persistence:stats"])
-            .set(stats_persistence_nanos);
+        const NANOSEC_PER_SEC: f64 = Duration::from_secs(1).as_nanos() as f64;
+        let stage_nanos = [
+            (
+                "new connections",
+                (before_message_system - before_new_connections).as_nanos() as i64,
+            ),
+            (
+                "state tick",
+                state_tick_time.as_nanos() as i64 - total_sys_ran_in_dispatcher_nanos,
+            ),
+            ("handle server events", handle_events_time.as_nanos() as i64),
+            ("update terrain", update_terrain_time.as_nanos() as i64),
+            ("sync", (before_world_tick - before_sync).as_nanos() as i64),
+            (
+                "world tick",
+                (before_entity_cleanup - before_world_tick).as_nanos() as i64,
+            ),
+            (
+                "entity cleanup",
+                (before_persistence_updates - before_entity_cleanup).as_nanos() as i64,
+            ),
+            (
+                "persistence_updates",
+                (end_of_server_tick - before_persistence_updates).as_nanos() as i64,
+            ),
+        ];
+        let sys_nanos = [
+            ("entity sync", entity_sync_nanos),
+            ("message", message_nanos),
+            ("sentinel", sentinel_nanos),
+            ("subscription", subscription_nanos),
+            ("terrain sync", terrain_sync_nanos),
+            ("terrain", terrain_nanos),
+            ("waypoint", waypoint_nanos),
+            ("invite timeout", invite_timeout_nanos),
+            ("voice", voice_nanos),
+            ("persistence:stats", stats_persistence_nanos),
+        ];
+        for (stage, nanos) in stage_nanos.iter() {
+            self.tick_metrics
+                .tick_time
+                .with_label_values(&[stage])
+                .set(*nanos);
+            self.tick_metrics
+                .tick_stage_time_hist
+                .with_label_values(&[stage])
+                .observe(*nanos as f64 / NANOSEC_PER_SEC);
+        }
+        for (system, nanos) in sys_nanos.iter() {
+            self.tick_metrics
+                .tick_time
+                .with_label_values(&[system])
+                .set(*nanos);
+            self.state_tick_metrics
+                .server_sys_time_hist
+                .with_label_values(&[system])
+                .observe(*nanos as f64 / NANOSEC_PER_SEC);
+        }
+        self.tick_metrics.simulation_steps.set(steps as i64);
 
         //detailed state metrics
         {
@@ -736,7 +924,6 @@ impl Server {
             c.with_label_values(&[common::sys::COMBAT_SYS])
                 .inc_by(combat_ns);
 
-            const NANOSEC_PER_SEC: f64 = Duration::from_secs(1).as_nanos() as f64;
             let h = &self.state_tick_metrics.state_tick_time_hist;
             h.with_label_values(&[common::sys::AGENT_SYS])
                 .observe(agent_ns as f64 / NANOSEC_PER_SEC);
@@ -774,6 +961,24 @@ impl Server {
 
             let entity_count = self.state.ecs().entities().join().count();
             self.tick_metrics.entity_count.set(entity_count as i64);
+
+            let network = (&self.state.ecs().read_storage::<Client>())
+                .join()
+                .map(|client| client.queued_bytes())
+                .sum::<u64>();
+            let report = MemoryReport {
+                terrain: self.state.terrain().memory_usage(),
+                ecs: common::memory::ecs_usage(self.state.ecs()),
+                network: network as usize,
+                assets: common::assets::cache_size(),
+                ..MemoryReport::default()
+            };
+            for (subsystem, bytes) in report.iter() {
+                self.tick_metrics
+                    .memory_usage
+                    .with_label_values(&[subsystem])
+                    .set(bytes as i64);
+            }
         }
         //self.metrics.entity_count.set(self.state.);
         self.tick_metrics
@@ -789,8 +994,275 @@ impl Server {
 
     /// Clean up the server after a tick.
     pub fn cleanup(&mut self) {
-        // Cleanup the local state
-        self.state.cleanup();
+        // Cleanup the local state, once clients have been sent the changes
+        if self.synced {
+            self.state.cleanup();
+        }
+    }
+
+    /// Kick or ban players whose suspicion exceeded the configured threshold.
+    fn handle_cheat_responses(&mut self) {
+        let responses = {
+            let ecs = self.state.ecs();
+            (
+                &ecs.entities(),
+                &ecs.read_storage::<comp::Player>(),
+                &mut ecs.write_storage::<anticheat::Suspicion>(),
+            )
+                .join()
+                .filter_map(|(entity, player, suspicion)| {
+                    suspicion
+                        .take_pending_response()
+                        .map(|response| (entity, player.alias.clone(), player.uuid(), response))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for (entity, alias, uuid, response) in responses {
+            let reason = "Suspected of cheating";
+            if response == CheatResponse::Ban {
+                info!(?alias, "Banning suspected cheater");
+                self.editable_settings_mut()
+                    .banlist
+                    .edit(self.data_dir().as_ref(), |b| {
+                        b.insert(uuid, BanRecord {
+                            username_when_banned: alias.clone(),
+                            reason: reason.to_string(),
+                        });
+                    });
+            } else {
+                info!(?alias, "Kicking suspected cheater");
+            }
+            cmd::kick_player(self, entity, reason);
+        }
+    }
+
+    /// Mark players that haven't given any input for a while as idle and, while
+    /// the server is full, disconnect them after a warning.
+    fn handle_idle_players(&mut self) {
+        let now = self.state.get_time();
+        let settings = self.settings();
+        let idle_timeout = settings.idle_timeout.as_secs_f64();
+        let grace = settings.idle_kick_grace.as_secs_f64();
+        let kick_idle = settings.kick_idle_when_full
+            && self.state.ecs().read_storage::<Client>().join().count() >= settings.max_players;
+
+        let mut idle_changes = Vec::new();
+        let mut to_warn = Vec::new();
+        let mut to_kick = Vec::new();
+        {
+            let ecs = self.state.ecs();
+            for (entity, uid, client) in (
+                &ecs.entities(),
+                &ecs.read_storage::<Uid>(),
+                &mut ecs.write_storage::<Client>(),
+            )
+                .join()
+                .filter(|(_, _, client)| client.registered)
+            {
+                let is_idle = now - client.last_input > idle_timeout;
+                if is_idle != client.is_idle {
+                    client.is_idle = is_idle;
+                    idle_changes.push((*uid, is_idle));
+                }
+                if !is_idle || !kick_idle {
+                    client.idle_warned_at = None;
+                    continue;
+                }
+                match client.idle_warned_at {
+                    None => {
+                        client.idle_warned_at = Some(now);
+                        to_warn.push(entity);
+                    },
+                    Some(warned_at) if now - warned_at > grace => to_kick.push(entity),
+                    Some(_) => {},
+                }
+            }
+        }
+
+        for (uid, is_idle) in idle_changes {
+            self.state
+                .notify_registered_clients(ServerGeneral::PlayerListUpdate(
+                    PlayerListUpdate::Idle(uid, is_idle),
+                ));
+        }
+        for entity in to_warn {
+            self.notify_client(
+                entity,
+                ChatType::CommandError.server_msg(format!(
+                    "The server is full, you will be disconnected in {} seconds unless you stop \
+                     being idle",
+                    grace as u64
+                )),
+            );
+        }
+        for entity in to_kick {
+            cmd::kick_player(
+                self,
+                entity,
+                "Disconnected for being idle while the server is full",
+            );
+        }
+    }
+
+    /// Ask players whose auth token is about to expire for a new one, and
+    /// disconnect them if they don't send one in time.
+    fn handle_expiring_sessions(&mut self) {
+        let lifetime = match self.settings().auth_session_lifetime {
+            Some(lifetime) if self.state.ecs().fetch::<LoginProvider>().uses_auth_server() => {
+                lifetime.as_secs_f64()
+            },
+            _ => return,
+        };
+        let grace = self.settings().auth_session_grace.as_secs_f64();
+        let now = self.state.get_time();
+
+        let mut to_kick = Vec::new();
+        for (entity, client) in (
+            &self.state.ecs().entities(),
+            &mut self.state.ecs().write_storage::<Client>(),
+        )
+            .join()
+            .filter(|(_, client)| client.registered)
+        {
+            match client.session_expiring_since {
+                None if now - client.session_start > lifetime => {
+                    client.session_expiring_since = Some(now);
+                    client.send_msg(ServerGeneral::SessionExpiring);
+                },
+                Some(since) if now - since > grace => to_kick.push(entity),
+                _ => {},
+            }
+        }
+
+        for entity in to_kick {
+            cmd::kick_player(self, entity, "Your session expired, please log in again");
+        }
+    }
+
+    /// Run the commands received from remote consoles. Each one runs as a
+    /// temporary admin entity whose messages are sent back as the output.
+    /// Points the items of all entities at the item definitions that were
+    /// reloaded, so that character states created from now on use them
+    fn handle_item_reloads(&mut self) {
+        let previous_abilities = match self.item_reloader.as_mut().and_then(|r| r.reloaded()) {
+            Some(previous_abilities) => previous_abilities,
+            None => return,
+        };
+
+        let ecs = self.state.ecs();
+        let mut changed = 0;
+        for loadout in (&mut ecs.write_storage::<comp::Loadout>()).join() {
+            for item_config in loadout
+                .active_item
+                .iter_mut()
+                .chain(loadout.second_item.iter_mut())
+            {
+                changed += item_config.refresh_definition(&previous_abilities) as usize;
+            }
+        }
+        for inventory in (&mut ecs.write_storage::<comp::Inventory>()).join() {
+            for item in inventory.items_mut() {
+                changed += item.refresh_definition() as usize;
+            }
+        }
+        info!(?changed, "Reloaded item definitions");
+    }
+
+    fn handle_rcon_requests(&mut self) {
+        let requests = match &self.rcon {
+            Some(rcon) => rcon.requests().collect::<Vec<_>>(),
+            None => return,
+        };
+
+        for request in requests {
+            if let Some(rcon) = &self.rcon {
+                rcon.audit(request.peer, &request.command);
+            }
+            let output = self.run_as_console(request.command.clone());
+            request.respond(output);
+        }
+    }
+
+    fn handle_scheduled_actions(&mut self, frontend_events: &mut Vec<Event>) {
+        let actions = self
+            .state
+            .ecs()
+            .write_resource::<ActionSchedule>()
+            .due(chrono::Utc::now());
+        for action in actions {
+            info!(?action, "Running scheduled action");
+            match action {
+                ScheduledAction::Announce(msg) => {
+                    self.notify_registered_clients(ChatType::Meta.server_msg(msg));
+                },
+                ScheduledAction::Save => self
+                    .state
+                    .ecs()
+                    .write_resource::<sys::PersistenceScheduler>()
+                    .run_next(),
+                ScheduledAction::Restart { countdown, message } => {
+                    frontend_events.push(Event::Shutdown {
+                        grace_period: countdown,
+                        message,
+                    });
+                },
+            }
+        }
+    }
+
+    fn handle_scheduled_scripts(&mut self) {
+        for name in self.script_schedule.due(Instant::now()) {
+            self.run_script(&name);
+        }
+    }
+
+    /// Runs a command script with admin rights, logging its output
+    fn run_script(&mut self, name: &str) {
+        let script = match Script::load(&self.data_dir().path, name) {
+            Ok(script) => script,
+            Err(e) => {
+                warn!(?e, ?name, "Failed to load command script");
+                return;
+            },
+        };
+        let spawn = self.state.ecs().read_resource::<SpawnPoint>().0;
+        let commands = match script.commands(&Variables::new(None, spawn, "")) {
+            Ok(commands) => commands,
+            Err(e) => {
+                warn!(?name, "Command script can't be run: {}", e);
+                return;
+            },
+        };
+        info!(?name, "Running command script");
+        for command in commands {
+            for line in self.run_as_console(command) {
+                info!(?name, "{}", line);
+            }
+        }
+    }
+
+    /// Runs a chat command with admin rights as a temporary entity, returning
+    /// the messages sent to it
+    fn run_as_console(&mut self, command: String) -> Vec<String> {
+        let entity = self
+            .state
+            .ecs_mut()
+            .create_entity()
+            .with(comp::Admin)
+            .with(RconOutput::default())
+            .build();
+        self.process_chat_cmd(entity, command);
+        let output = self
+            .state
+            .ecs()
+            .write_storage::<RconOutput>()
+            .remove(entity)
+            .map_or_else(Vec::new, |output| output.0);
+        if let Err(e) = self.state.ecs_mut().delete_entity(entity) {
+            warn!(?e, "Failed to delete remote console entity");
+        }
+        output
     }
 
     /// Handle new client connections.
@@ -830,7 +1302,7 @@ impl Server {
                 .clients_connected
                 .inc();
             // Send client all the tracked components currently attached to its entity as
-            // well as synced resources (currently only `TimeOfDay`)
+            // well as synced resources such as the time of day and the weather
             debug!("Starting initial sync with client.");
             self.state
                 .ecs()
@@ -843,10 +1315,17 @@ impl Server {
                     entity_package: TrackedComps::fetch(&self.state.ecs())
                         .create_entity_package(entity, None, None, None),
                     time_of_day: *self.state.ecs().read_resource(),
+                    day_cycle_factor: *self.state.ecs().read_resource(),
+                    weather: (*self.state.weather()).clone(),
                     max_group_size: self.settings().max_player_group_size,
                     client_timeout: self.settings().client_timeout,
                     world_map: self.map.clone(),
                     recipe_book: (&*default_recipe_book()).clone(),
+                    danger_origin: if self.settings().scale_npc_levels {
+                        Some(self.state.ecs().read_resource::<SpawnPoint>().0.xy())
+                    } else {
+                        None
+                    },
                 })?;
 
             frontend_events.push(Event::ClientConnected { entity });
@@ -859,8 +1338,19 @@ impl Server {
     where
         S: Into<ServerMsg>,
     {
+        let msg: ServerMsg = msg.into();
+        if let Some(output) = self
+            .state
+            .ecs()
+            .write_storage::<RconOutput>()
+            .get_mut(entity)
+        {
+            if let ServerMsg::General(ServerGeneral::ChatMsg(chat)) = &msg {
+                output.0.push(chat.message.clone());
+            }
+        }
         if let Some(client) = self.state.ecs().write_storage::<Client>().get_mut(entity) {
-            client.send_msg(msg.into())
+            client.send_msg(msg)
         }
     }
 
@@ -2,9 +2,23 @@ use crate::settings::BanRecord;
 use authc::{AuthClient, AuthClientError, AuthToken, Uuid};
 use common::msg::RegisterError;
 use hashbrown::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use tracing::{error, info};
 
+/// What the server does when an account that is already logged in logs in
+/// again
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DuplicateLogin {
+    /// Reject the new login
+    RejectNew,
+    /// Disconnect the session that is logged in, and let the new one take over
+    KickOld,
+    /// Like `KickOld`, unless the player who is logged in is in combat, so that
+    /// logging in again can't be used to get out of a fight
+    KickOldUnlessInCombat,
+}
+
 fn derive_uuid(username: &str) -> Uuid {
     let mut state = 144066263297769815596495629667062367629;
 
@@ -34,7 +48,7 @@ impl LoginProvider {
         }
     }
 
-    fn login(&mut self, uuid: Uuid, username: String) -> Result<(), RegisterError> {
+    pub fn login(&mut self, uuid: Uuid, username: String) -> Result<(), RegisterError> {
         // make sure that the user is not logged in already
         if self.accounts.contains_key(&uuid) {
             return Err(RegisterError::AlreadyLoggedIn);
@@ -50,7 +64,11 @@ impl LoginProvider {
         };
     }
 
-    pub fn try_login(
+    pub fn is_logged_in(&self, uuid: Uuid) -> bool { self.accounts.contains_key(&uuid) }
+
+    /// Resolve the user and check whether they are allowed to join. They still
+    /// have to be logged in with [`LoginProvider::login`] afterwards.
+    pub fn authenticate(
         &mut self,
         username_or_token: &str,
         admins: &HashSet<Uuid>,
@@ -74,9 +92,6 @@ impl LoginProvider {
                     return Err(RegisterError::NotOnWhitelist);
                 }
 
-                // add the user to self.accounts
-                self.login(uuid, username.clone())?;
-
                 Ok((username, uuid))
             })
     }
@@ -106,6 +121,10 @@ impl LoginProvider {
         }
     }
 
+    /// Whether players authenticate with tokens from an auth server rather
+    /// than just a username
+    pub fn uses_auth_server(&self) -> bool { self.auth_server.is_some() }
+
     pub fn username_to_uuid(&self, username: &str) -> Result<Uuid, AuthClientError> {
         self.auth_server.as_ref().map_or_else(
             || Ok(derive_uuid(username)),
@@ -1,6 +1,6 @@
 use prometheus::{
-    Encoder, Gauge, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
-    Opts, Registry, TextEncoder,
+    Encoder, Gauge, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
 };
 use std::{
     convert::TryInto,
@@ -22,24 +22,36 @@ pub struct StateTickMetrics {
     // need the Historgram
     pub state_tick_time_hist: HistogramVec,
     pub state_tick_time_count: IntCounterVec,
+    /// Time spent in each server-only system
+    pub server_sys_time_hist: HistogramVec,
 }
 
 pub struct PlayerMetrics {
     pub clients_connected: IntCounter,
     pub players_connected: IntCounter,
     pub clients_disconnected: IntCounterVec, // timeout, network_error, gracefully
+    pub movement_rejected: IntCounterVec,    // teleport, too_fast, too_high, inside_terrain
+    pub action_rejected: IntCounterVec,      // too_frequent, no_line_of_sight, while_dead
 }
 
 pub struct NetworkRequestMetrics {
     pub chunks_request_dropped: IntCounter,
     pub chunks_served_from_memory: IntCounter,
     pub chunks_generation_triggered: IntCounter,
+    pub physics_updates_dropped: IntCounter,
+    pub client_msgs: IntCounterVec, // by message kind
 }
 
 pub struct ChunkGenMetrics {
     pub chunks_requested: IntCounter,
     pub chunks_served: IntCounter,
     pub chunks_canceled: IntCounter,
+    pub chunks_loaded_from_cache: IntCounter,
+    pub chunks_queued: IntGauge,
+    /// Time from a chunk being requested until it is served
+    pub chunk_latency: Histogram,
+    /// Time a worker spends generating a chunk
+    pub chunk_gen_time: Histogram,
 }
 
 pub struct TickMetrics {
@@ -48,10 +60,13 @@ pub struct TickMetrics {
     pub chunk_groups_count: IntGauge,
     pub entity_count: IntGauge,
     pub tick_time: IntGaugeVec,
+    pub tick_stage_time_hist: HistogramVec,
     pub build_info: IntGauge,
     pub start_time: IntGauge,
     pub time_of_day: Gauge,
     pub light_count: IntGauge,
+    pub simulation_steps: IntGauge,
+    pub memory_usage: IntGaugeVec,
     tick: Arc<AtomicU64>,
 }
 
@@ -62,28 +77,32 @@ pub struct ServerMetrics {
     tick: Arc<AtomicU64>,
 }
 
+/// Histogram buckets in seconds, fit for the duration of parts of a tick
+fn tick_buckets() -> Vec<f64> {
+    vec![
+        Duration::from_micros(1).as_secs_f64(),
+        Duration::from_micros(10).as_secs_f64(),
+        Duration::from_micros(100).as_secs_f64(),
+        Duration::from_micros(200).as_secs_f64(),
+        Duration::from_micros(400).as_secs_f64(),
+        Duration::from_millis(2).as_secs_f64(),
+        Duration::from_millis(5).as_secs_f64(),
+        Duration::from_millis(10).as_secs_f64(),
+        Duration::from_millis(20).as_secs_f64(),
+        Duration::from_millis(30).as_secs_f64(),
+        Duration::from_millis(50).as_secs_f64(),
+        Duration::from_millis(100).as_secs_f64(),
+    ]
+}
+
 impl StateTickMetrics {
     pub fn new() -> Result<(Self, RegistryFn), prometheus::Error> {
-        let bucket = vec![
-            Duration::from_micros(1).as_secs_f64(),
-            Duration::from_micros(10).as_secs_f64(),
-            Duration::from_micros(100).as_secs_f64(),
-            Duration::from_micros(200).as_secs_f64(),
-            Duration::from_micros(400).as_secs_f64(),
-            Duration::from_millis(2).as_secs_f64(),
-            Duration::from_millis(5).as_secs_f64(),
-            Duration::from_millis(10).as_secs_f64(),
-            Duration::from_millis(20).as_secs_f64(),
-            Duration::from_millis(30).as_secs_f64(),
-            Duration::from_millis(50).as_secs_f64(),
-            Duration::from_millis(100).as_secs_f64(),
-        ];
         let state_tick_time_hist = HistogramVec::new(
             HistogramOpts::new(
                 "state_tick_time_hist",
                 "shows the number of clients joined to the server",
             )
-            .buckets(bucket),
+            .buckets(tick_buckets()),
             &["system"],
         )?;
         let state_tick_time_count = IntCounterVec::new(
@@ -93,13 +112,23 @@ impl StateTickMetrics {
             ),
             &["system"],
         )?;
+        let server_sys_time_hist = HistogramVec::new(
+            HistogramOpts::new(
+                "server_sys_time_hist",
+                "time in seconds each server system took to run",
+            )
+            .buckets(tick_buckets()),
+            &["system"],
+        )?;
 
         let state_tick_time_hist_clone = state_tick_time_hist.clone();
         let state_tick_time_count_clone = state_tick_time_count.clone();
+        let server_sys_time_hist_clone = server_sys_time_hist.clone();
 
         let f = |registry: &Registry| {
             registry.register(Box::new(state_tick_time_hist_clone))?;
             registry.register(Box::new(state_tick_time_count_clone))?;
+            registry.register(Box::new(server_sys_time_hist_clone))?;
             Ok(())
         };
 
@@ -107,6 +136,7 @@ impl StateTickMetrics {
             Self {
                 state_tick_time_hist,
                 state_tick_time_count,
+                server_sys_time_hist,
             },
             Box::new(f),
         ))
@@ -131,15 +161,34 @@ impl PlayerMetrics {
             ),
             &["reason"],
         )?;
+        let movement_rejected = IntCounterVec::new(
+            Opts::new(
+                "movement_rejected",
+                "shows the number of player movement updates rejected by validation and the \
+                 reason",
+            ),
+            &["reason"],
+        )?;
+        let action_rejected = IntCounterVec::new(
+            Opts::new(
+                "action_rejected",
+                "shows the number of player actions rejected by validation and the reason",
+            ),
+            &["reason"],
+        )?;
 
         let clients_connected_clone = clients_connected.clone();
         let players_connected_clone = players_connected.clone();
         let clients_disconnected_clone = clients_disconnected.clone();
+        let movement_rejected_clone = movement_rejected.clone();
+        let action_rejected_clone = action_rejected.clone();
 
         let f = |registry: &Registry| {
             registry.register(Box::new(clients_connected_clone))?;
             registry.register(Box::new(players_connected_clone))?;
             registry.register(Box::new(clients_disconnected_clone))?;
+            registry.register(Box::new(movement_rejected_clone))?;
+            registry.register(Box::new(action_rejected_clone))?;
             Ok(())
         };
 
@@ -148,6 +197,8 @@ impl PlayerMetrics {
                 clients_connected,
                 players_connected,
                 clients_disconnected,
+                movement_rejected,
+                action_rejected,
             },
             Box::new(f),
         ))
@@ -168,15 +219,30 @@ impl NetworkRequestMetrics {
             "chunks_generation_triggered",
             "number of all chunks that were requested and needs to be generated",
         ))?;
+        let physics_updates_dropped = IntCounter::with_opts(Opts::new(
+            "physics_updates_dropped",
+            "number of physics updates not sent because the client couldn't keep up",
+        ))?;
+        let client_msgs = IntCounterVec::new(
+            Opts::new(
+                "client_msgs",
+                "number of messages received from clients, by kind of message",
+            ),
+            &["msg"],
+        )?;
 
         let chunks_request_dropped_clone = chunks_request_dropped.clone();
         let chunks_served_from_memory_clone = chunks_served_from_memory.clone();
         let chunks_generation_triggered_clone = chunks_generation_triggered.clone();
+        let physics_updates_dropped_clone = physics_updates_dropped.clone();
+        let client_msgs_clone = client_msgs.clone();
 
         let f = |registry: &Registry| {
             registry.register(Box::new(chunks_request_dropped_clone))?;
             registry.register(Box::new(chunks_served_from_memory_clone))?;
             registry.register(Box::new(chunks_generation_triggered_clone))?;
+            registry.register(Box::new(physics_updates_dropped_clone))?;
+            registry.register(Box::new(client_msgs_clone))?;
             Ok(())
         };
 
@@ -185,6 +251,8 @@ impl NetworkRequestMetrics {
                 chunks_request_dropped,
                 chunks_served_from_memory,
                 chunks_generation_triggered,
+                physics_updates_dropped,
+                client_msgs,
             },
             Box::new(f),
         ))
@@ -205,15 +273,46 @@ impl ChunkGenMetrics {
             "chunks_canceled",
             "number of all canceled chunks on the server",
         ))?;
+        let chunks_loaded_from_cache = IntCounter::with_opts(Opts::new(
+            "chunks_loaded_from_cache",
+            "number of all requested chunks loaded from the disk cache instead of generated",
+        ))?;
+        let chunks_queued = IntGauge::with_opts(Opts::new(
+            "chunks_queued",
+            "number of requested chunks waiting for a worker to generate them",
+        ))?;
+        let latency_buckets = vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+        let chunk_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "chunk_latency",
+                "time in seconds from a chunk being requested until it is served",
+            )
+            .buckets(latency_buckets.clone()),
+        )?;
+        let chunk_gen_time = Histogram::with_opts(
+            HistogramOpts::new(
+                "chunk_gen_time",
+                "time in seconds a worker spent generating a chunk",
+            )
+            .buckets(latency_buckets),
+        )?;
 
         let chunks_requested_clone = chunks_requested.clone();
         let chunks_served_clone = chunks_served.clone();
         let chunks_canceled_clone = chunks_canceled.clone();
+        let chunks_loaded_from_cache_clone = chunks_loaded_from_cache.clone();
+        let chunks_queued_clone = chunks_queued.clone();
+        let chunk_latency_clone = chunk_latency.clone();
+        let chunk_gen_time_clone = chunk_gen_time.clone();
 
         let f = |registry: &Registry| {
             registry.register(Box::new(chunks_requested_clone))?;
             registry.register(Box::new(chunks_served_clone))?;
             registry.register(Box::new(chunks_canceled_clone))?;
+            registry.register(Box::new(chunks_loaded_from_cache_clone))?;
+            registry.register(Box::new(chunks_queued_clone))?;
+            registry.register(Box::new(chunk_latency_clone))?;
+            registry.register(Box::new(chunk_gen_time_clone))?;
             Ok(())
         };
 
@@ -222,6 +321,10 @@ impl ChunkGenMetrics {
                 chunks_requested,
                 chunks_served,
                 chunks_canceled,
+                chunks_loaded_from_cache,
+                chunks_queued,
+                chunk_latency,
+                chunk_gen_time,
             },
             Box::new(f),
         ))
@@ -260,10 +363,29 @@ impl TickMetrics {
             "light_count",
             "number of all lights currently active on the server",
         ))?;
+        let simulation_steps = IntGauge::with_opts(Opts::new(
+            "simulation_steps",
+            "number of simulation ticks run during the last tick of the server",
+        ))?;
         let tick_time = IntGaugeVec::new(
             Opts::new("tick_time", "time in ns required for a tick of the server"),
             &["period"],
         )?;
+        let memory_usage = IntGaugeVec::new(
+            Opts::new(
+                "memory_usage",
+                "approximate bytes used by each subsystem of the server",
+            ),
+            &["subsystem"],
+        )?;
+        let tick_stage_time_hist = HistogramVec::new(
+            HistogramOpts::new(
+                "tick_stage_time_hist",
+                "time in seconds each stage of a server tick took",
+            )
+            .buckets(tick_buckets()),
+            &["stage"],
+        )?;
 
         let since_the_epoch = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -278,7 +400,10 @@ impl TickMetrics {
         let start_time_clone = start_time.clone();
         let time_of_day_clone = time_of_day.clone();
         let light_count_clone = light_count.clone();
+        let simulation_steps_clone = simulation_steps.clone();
         let tick_time_clone = tick_time.clone();
+        let tick_stage_time_hist_clone = tick_stage_time_hist.clone();
+        let memory_usage_clone = memory_usage.clone();
 
         let f = |registry: &Registry| {
             registry.register(Box::new(chonks_count_clone))?;
@@ -289,7 +414,10 @@ impl TickMetrics {
             registry.register(Box::new(start_time_clone))?;
             registry.register(Box::new(time_of_day_clone))?;
             registry.register(Box::new(light_count_clone))?;
+            registry.register(Box::new(simulation_steps_clone))?;
             registry.register(Box::new(tick_time_clone))?;
+            registry.register(Box::new(tick_stage_time_hist_clone))?;
+            registry.register(Box::new(memory_usage_clone))?;
             Ok(())
         };
 
@@ -300,10 +428,13 @@ impl TickMetrics {
                 chunk_groups_count,
                 entity_count,
                 tick_time,
+                tick_stage_time_hist,
                 build_info,
                 start_time,
                 time_of_day,
                 light_count,
+                simulation_steps,
+                memory_usage,
                 tick,
             },
             Box::new(f),
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+
+DROP INDEX idx_block_diff_pos;
+DROP TABLE block_diff;
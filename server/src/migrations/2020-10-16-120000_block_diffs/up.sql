@@ -0,0 +1,14 @@
+-- Stores changes made to the terrain, so that they can be reapplied whenever
+-- the chunk they belong to is generated again
+
+CREATE TABLE block_diff
+(
+    block_diff_id INTEGER NOT NULL
+        PRIMARY KEY AUTOINCREMENT,
+    x             INTEGER NOT NULL,
+    y             INTEGER NOT NULL,
+    z             INTEGER NOT NULL,
+    block         TEXT NOT NULL
+);
+
+CREATE INDEX idx_block_diff_pos ON block_diff (x, y, z);
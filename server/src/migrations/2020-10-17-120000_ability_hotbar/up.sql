@@ -0,0 +1,9 @@
+-- Stores the abilities each character has assigned to their ability hotbar
+
+CREATE TABLE ability_hotbar
+(
+    character_id INTEGER NOT NULL
+        PRIMARY KEY
+        REFERENCES character(character_id),
+    slots        TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+-- Stores which properties of alchemy ingredients each character has found out
+-- about
+
+CREATE TABLE alchemy_journal
+(
+    character_id INTEGER NOT NULL
+        PRIMARY KEY
+        REFERENCES character(character_id),
+    known        TEXT NOT NULL
+);
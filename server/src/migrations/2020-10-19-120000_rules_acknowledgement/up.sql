@@ -0,0 +1,9 @@
+-- Stores the server rules each account last accepted, so that players are only
+-- asked to accept them again once they change
+
+CREATE TABLE rules_acknowledgement
+(
+    player_uuid TEXT NOT NULL
+        PRIMARY KEY,
+    rules       TEXT NOT NULL
+);
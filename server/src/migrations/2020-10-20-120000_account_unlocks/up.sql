@@ -0,0 +1,9 @@
+-- Stores what each account unlocked, such as extra character slots and
+-- cosmetics, as opposed to the progress of a single character
+
+CREATE TABLE account_unlocks
+(
+    player_uuid TEXT NOT NULL
+        PRIMARY KEY,
+    unlocks     TEXT NOT NULL
+);
@@ -0,0 +1,30 @@
+PRAGMA foreign_keys=off;
+
+-- SQLite does not support removing columns from tables so we must rename the current table,
+-- recreate the previous version of the table, then copy over the data from the renamed table
+ALTER TABLE item RENAME TO _item_old;
+
+CREATE TABLE item
+(
+    item_id                  INTEGER NOT NULL
+        PRIMARY KEY
+        REFERENCES entity(entity_id),
+    parent_container_item_id INTEGER NOT NULL
+        REFERENCES item(item_id),
+    item_definition_id       TEXT NOT NULL,
+    stack_size               INTEGER NOT NULL,
+    position                 TEXT NOT NULL
+);
+
+INSERT INTO item (item_id, parent_container_item_id, item_definition_id, stack_size, position)
+SELECT item_id, parent_container_item_id, item_definition_id, stack_size, position FROM _item_old;
+
+DROP TABLE _item_old;
+
+CREATE UNIQUE INDEX idx_parent_container_item_id_position
+    ON item(parent_container_item_id, position);
+
+CREATE INDEX idx_item_definition_id
+    ON item(item_definition_id);
+
+PRAGMA foreign_keys=on;
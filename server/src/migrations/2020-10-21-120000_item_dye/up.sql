@@ -0,0 +1,3 @@
+-- Color an item was dyed in, packed as 0xRRGGBB, or NULL if it wasn't dyed
+
+ALTER TABLE item ADD COLUMN dye INTEGER;
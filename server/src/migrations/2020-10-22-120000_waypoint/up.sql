@@ -0,0 +1,11 @@
+-- Stores the waypoint each character respawns at
+
+CREATE TABLE waypoint
+(
+    character_id INTEGER NOT NULL
+        PRIMARY KEY
+        REFERENCES character(character_id),
+    x            REAL NOT NULL,
+    y            REAL NOT NULL,
+    z            REAL NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Stores the UI preferences clients keep on the server for each character
+
+CREATE TABLE ui_preferences
+(
+    character_id INTEGER NOT NULL
+        PRIMARY KEY
+        REFERENCES character(character_id),
+    data         BLOB NOT NULL
+);
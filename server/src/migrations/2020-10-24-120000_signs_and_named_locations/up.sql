@@ -0,0 +1,22 @@
+-- Stores the text players wrote on signs, reapplied along with the block
+-- changes whenever the chunk of a sign is generated again
+
+CREATE TABLE sign
+(
+    x    INTEGER NOT NULL,
+    y    INTEGER NOT NULL,
+    z    INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    PRIMARY KEY (x, y, z)
+);
+
+-- Stores the places in the world admins gave a name
+
+CREATE TABLE named_location
+(
+    name TEXT NOT NULL
+        PRIMARY KEY,
+    x    REAL NOT NULL,
+    y    REAL NOT NULL,
+    z    REAL NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Stores the skill groups and unlocked skills of each character
+
+CREATE TABLE skill_set
+(
+    character_id INTEGER NOT NULL
+        PRIMARY KEY
+        REFERENCES character(character_id),
+    data         TEXT NOT NULL
+);
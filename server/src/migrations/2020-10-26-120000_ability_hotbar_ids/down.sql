@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+
+UPDATE ability_hotbar SET slots = '[2,3,4,5,6]';
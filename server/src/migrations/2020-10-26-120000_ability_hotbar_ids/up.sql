@@ -0,0 +1,5 @@
+-- Hotbar slots now refer to abilities by id instead of by index. The old
+-- indices can't be converted without the items, so every slot goes back to the
+-- skill at its position.
+
+UPDATE ability_hotbar SET slots = '[null,null,null,null,null]';
@@ -0,0 +1,260 @@
+//! Persistence of changes made to the terrain
+//!
+//! Every block change is recorded in the database and reapplied whenever the
+//! chunk it belongs to is generated again, so that building survives chunks
+//! being unloaded and server restarts. Since the same block may be changed
+//! many times, superseded records are periodically compacted away.
+//!
+//! The text written on signs is kept alongside, and dropped once the sign is
+//! replaced by another block.
+
+use crate::persistence::{
+    establish_connection,
+    models::{BlockDiff, NewBlockDiff, Sign},
+    schema, VelorenConnection,
+};
+use common::{
+    terrain::{sign::is_sign, Block, TerrainChunk, TerrainChunkSize},
+    vol::{ReadVol, RectVolSize, WriteVol},
+};
+use crossbeam::channel;
+use diesel::prelude::*;
+use hashbrown::HashMap;
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+use tracing::{error, info, trace, warn};
+use vek::*;
+
+/// How often superseded block changes are removed from the database
+const COMPACT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+enum BlockDiffRequest {
+    Record(Vec<(Vec3<i32>, Block)>),
+    Sign(Vec3<i32>, Option<String>),
+    Compact,
+}
+
+/// The block changes made to every chunk, written to the database in a
+/// background thread.
+pub struct BlockDiffs {
+    chunks: HashMap<Vec2<i32>, HashMap<Vec3<i32>, Block>>,
+    signs: HashMap<Vec2<i32>, HashMap<Vec3<i32>, String>>,
+    last_compacted: Instant,
+    update_tx: Option<channel::Sender<BlockDiffRequest>>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl BlockDiffs {
+    /// Load all recorded block changes from the database
+    pub fn new(db_dir: &Path) -> diesel::QueryResult<Self> {
+        let mut conn = establish_connection(db_dir)?;
+        compact(&mut conn);
+
+        let mut chunks = HashMap::<_, HashMap<_, _>>::new();
+        let rows = conn.transaction::<_, diesel::result::Error, _>(|txn| {
+            schema::block_diff::dsl::block_diff
+                .order(schema::block_diff::dsl::block_diff_id.asc())
+                .load::<BlockDiff>(&*txn)
+        })?;
+        let count = rows.len();
+        for row in rows {
+            let pos = Vec3::new(row.x, row.y, row.z);
+            match serde_json::from_str::<Block>(&row.block) {
+                Ok(block) => {
+                    chunks.entry(chunk_key(pos)).or_default().insert(pos, block);
+                },
+                Err(e) => warn!(?e, ?pos, "Skipping block change that failed to deserialize"),
+            }
+        }
+        info!(?count, "Loaded persisted block changes");
+
+        let mut signs = HashMap::<_, HashMap<_, _>>::new();
+        let rows = conn.transaction::<_, diesel::result::Error, _>(|txn| {
+            schema::sign::dsl::sign.load::<Sign>(&*txn)
+        })?;
+        let count = rows.len();
+        for row in rows {
+            let pos = Vec3::new(row.x, row.y, row.z);
+            signs
+                .entry(chunk_key(pos))
+                .or_default()
+                .insert(pos, row.text);
+        }
+        info!(?count, "Loaded persisted signs");
+
+        let (update_tx, update_rx) = channel::unbounded::<BlockDiffRequest>();
+        let handle = std::thread::spawn(move || {
+            while let Ok(request) = update_rx.recv() {
+                match request {
+                    BlockDiffRequest::Record(changes) => {
+                        trace!("Persisting {} block changes", changes.len());
+                        record(changes, &mut conn);
+                    },
+                    BlockDiffRequest::Sign(pos, text) => record_sign(pos, text, &mut conn),
+                    BlockDiffRequest::Compact => compact(&mut conn),
+                }
+            }
+        });
+
+        Ok(Self {
+            chunks,
+            signs,
+            last_compacted: Instant::now(),
+            update_tx: Some(update_tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// Record changes to the terrain, returning the positions of signs that
+    /// were replaced and lost their text
+    pub fn record(&mut self, changes: impl Iterator<Item = (Vec3<i32>, Block)>) -> Vec<Vec3<i32>> {
+        let changes = changes.collect::<Vec<_>>();
+        if changes.is_empty() {
+            return Vec::new();
+        }
+        let mut removed_signs = Vec::new();
+        for (pos, block) in changes.iter() {
+            self.chunks
+                .entry(chunk_key(*pos))
+                .or_default()
+                .insert(*pos, *block);
+            if !is_sign(block) && self.sign(*pos).is_some() {
+                removed_signs.push(*pos);
+            }
+        }
+        for pos in removed_signs.iter() {
+            self.set_sign(*pos, None);
+        }
+        self.send(BlockDiffRequest::Record(changes));
+
+        if self.last_compacted.elapsed() > COMPACT_INTERVAL {
+            self.last_compacted = Instant::now();
+            self.send(BlockDiffRequest::Compact);
+        }
+        removed_signs
+    }
+
+    pub fn sign(&self, pos: Vec3<i32>) -> Option<&str> {
+        self.signs
+            .get(&chunk_key(pos))
+            .and_then(|signs| signs.get(&pos))
+            .map(String::as_str)
+    }
+
+    /// Record the text of a sign, or that it was removed with `None`
+    pub fn set_sign(&mut self, pos: Vec3<i32>, text: Option<String>) {
+        let signs = self.signs.entry(chunk_key(pos)).or_default();
+        match &text {
+            Some(text) => signs.insert(pos, text.clone()),
+            None => signs.remove(&pos),
+        };
+        self.send(BlockDiffRequest::Sign(pos, text));
+    }
+
+    /// Reapply the recorded changes to a freshly generated chunk
+    pub fn apply(&self, key: Vec2<i32>, chunk: &mut TerrainChunk) {
+        if let Some(changes) = self.chunks.get(&key) {
+            let offs = Vec3::from(key * TerrainChunkSize::RECT_SIZE.map(|e| e as i32));
+            for (pos, block) in changes {
+                let _ = chunk.set(*pos - offs, *block);
+            }
+        }
+        if let Some(signs) = self.signs.get(&key) {
+            let offs = Vec3::from(key * TerrainChunkSize::RECT_SIZE.map(|e| e as i32));
+            for (pos, text) in signs {
+                if chunk.get(*pos - offs).map_or(false, is_sign) {
+                    chunk.meta_mut().set_sign(*pos, Some(text.clone()));
+                }
+            }
+        }
+    }
+
+    fn send(&self, request: BlockDiffRequest) {
+        if let Err(e) = self.update_tx.as_ref().unwrap().send(request) {
+            error!(?e, "Could not send block changes to be persisted");
+        }
+    }
+}
+
+fn chunk_key(pos: Vec3<i32>) -> Vec2<i32> {
+    pos.xy()
+        .map2(TerrainChunkSize::RECT_SIZE, |e, sz| e.div_euclid(sz as i32))
+}
+
+fn record(changes: Vec<(Vec3<i32>, Block)>, connection: &mut VelorenConnection) {
+    let rows = changes
+        .into_iter()
+        .filter_map(|(pos, block)| {
+            serde_json::to_string(&block)
+                .map_err(|e| error!(?e, ?pos, "Failed to serialize block change"))
+                .ok()
+                .map(|block| NewBlockDiff {
+                    x: pos.x,
+                    y: pos.y,
+                    z: pos.z,
+                    block,
+                })
+        })
+        .collect::<Vec<_>>();
+
+    if let Err(e) = connection.transaction::<_, diesel::result::Error, _>(|txn| {
+        diesel::insert_into(schema::block_diff::table)
+            .values(&rows)
+            .execute(&*txn)
+            .map(|_| ())
+    }) {
+        error!(?e, "Error while persisting block changes");
+    }
+}
+
+fn record_sign(pos: Vec3<i32>, text: Option<String>, connection: &mut VelorenConnection) {
+    use schema::sign::dsl;
+
+    if let Err(e) = connection.transaction::<_, diesel::result::Error, _>(|txn| {
+        match text {
+            Some(text) => diesel::replace_into(schema::sign::table)
+                .values(&Sign {
+                    x: pos.x,
+                    y: pos.y,
+                    z: pos.z,
+                    text,
+                })
+                .execute(&*txn),
+            None => diesel::delete(
+                dsl::sign
+                    .filter(dsl::x.eq(pos.x))
+                    .filter(dsl::y.eq(pos.y))
+                    .filter(dsl::z.eq(pos.z)),
+            )
+            .execute(&*txn),
+        }
+        .map(|_| ())
+    }) {
+        error!(?e, ?pos, "Error while persisting sign");
+    }
+}
+
+/// Remove every record of a block that was changed again later on
+fn compact(connection: &mut VelorenConnection) {
+    if let Err(e) = connection.transaction::<_, diesel::result::Error, _>(|txn| {
+        diesel::sql_query(
+            "DELETE FROM block_diff WHERE block_diff_id NOT IN (SELECT MAX(block_diff_id) FROM \
+             block_diff GROUP BY x, y, z)",
+        )
+        .execute(&*txn)
+        .map(|removed| trace!(?removed, "Compacted persisted block changes"))
+    }) {
+        error!(?e, "Error while compacting persisted block changes");
+    }
+}
+
+impl Drop for BlockDiffs {
+    fn drop(&mut self) {
+        drop(self.update_tx.take());
+        if let Err(e) = self.handle.take().unwrap().join() {
+            error!(?e, "Error from joining block change persistence thread");
+        }
+    }
+}
@@ -11,17 +11,21 @@ use crate::{
     comp,
     persistence::{
         character::conversions::{
-            convert_body_from_database, convert_body_to_database_json,
-            convert_character_from_database, convert_inventory_from_database_items,
-            convert_items_to_database_items, convert_loadout_from_database_items,
-            convert_stats_from_database, convert_stats_to_database,
+            convert_ability_hotbar_from_database, convert_ability_hotbar_to_database,
+            convert_account_unlocks_from_database, convert_alchemy_journal_from_database,
+            convert_alchemy_journal_to_database, convert_body_from_database,
+            convert_body_to_database_json, convert_character_from_database,
+            convert_inventory_from_database_items, convert_items_to_database_items,
+            convert_loadout_from_database_items, convert_skill_set_from_database,
+            convert_skill_set_to_database, convert_stats_from_database, convert_stats_to_database,
+            convert_waypoint_from_database, convert_waypoint_to_database,
         },
-        character_loader::{CharacterDataResult, CharacterListResult},
+        character_loader::{AccountUnlocksResult, CharacterDataResult, CharacterListResult},
         error::Error::DatabaseError,
         PersistedComponents,
     },
 };
-use common::character::{CharacterId, CharacterItem, MAX_CHARACTERS_PER_PLAYER};
+use common::character::{CharacterId, CharacterItem};
 use core::ops::Range;
 use diesel::{prelude::*, sql_query, sql_types::BigInt};
 use std::sync::Arc;
@@ -83,11 +87,49 @@ pub fn load_character_data(
         .filter(schema::body::dsl::body_id.eq(char_id))
         .first::<Body>(&*connection)?;
 
+    // Characters created before the hotbar was persisted don't have one yet
+    let char_hotbar = schema::ability_hotbar::table
+        .filter(schema::ability_hotbar::dsl::character_id.eq(char_id))
+        .first::<AbilityHotbar>(&*connection)
+        .optional()?;
+
+    // Same for the alchemy journal
+    let char_journal = schema::alchemy_journal::table
+        .filter(schema::alchemy_journal::dsl::character_id.eq(char_id))
+        .first::<AlchemyJournal>(&*connection)
+        .optional()?;
+
+    // Characters that never set a waypoint don't have one
+    let char_waypoint = schema::waypoint::table
+        .filter(schema::waypoint::dsl::character_id.eq(char_id))
+        .first::<Waypoint>(&*connection)
+        .optional()?;
+
+    // Characters that never changed their skills start with the default set
+    let char_skill_set = schema::skill_set::table
+        .filter(schema::skill_set::dsl::character_id.eq(char_id))
+        .first::<SkillSet>(&*connection)
+        .optional()?;
+
+    let mut char_stats = convert_stats_from_database(&stats_data, character_data.alias);
+    if let Some(skill_set) = char_skill_set {
+        char_stats.skill_set = convert_skill_set_from_database(&skill_set)?;
+    }
+
     Ok((
         convert_body_from_database(&char_body)?,
-        convert_stats_from_database(&stats_data, character_data.alias),
+        char_stats,
         convert_inventory_from_database_items(&inventory_items)?,
         convert_loadout_from_database_items(&loadout_items)?,
+        char_hotbar
+            .map(|hotbar| convert_ability_hotbar_from_database(&hotbar))
+            .transpose()?
+            .unwrap_or_default(),
+        char_journal
+            .map(|journal| convert_alchemy_journal_from_database(&journal))
+            .transpose()?
+            .unwrap_or_default(),
+        char_waypoint.map(|waypoint| convert_waypoint_from_database(&waypoint)),
     ))
 }
 
@@ -155,9 +197,10 @@ pub fn create_character(
 
     check_character_limit(uuid, connection)?;
 
-    use schema::{body, character, stats};
+    use schema::{ability_hotbar, alchemy_journal, body, character, stats};
 
-    let (body, stats, inventory, loadout) = persisted_components;
+    // New characters haven't set a waypoint yet
+    let (body, stats, inventory, loadout, hotbar, journal, _) = persisted_components;
 
     // Fetch new entity IDs for character, inventory and loadout
     let mut new_entity_ids = get_new_entity_ids(connection, |next_id| next_id + 3)?;
@@ -173,6 +216,7 @@ pub fn create_character(
             parent_container_item_id: WORLD_PSEUDO_CONTAINER_ID,
             item_definition_id: CHARACTER_PSEUDO_CONTAINER_DEF_ID.to_owned(),
             position: character_id.to_string(),
+            dye: None,
         },
         Item {
             stack_size: 1,
@@ -180,6 +224,7 @@ pub fn create_character(
             parent_container_item_id: character_id,
             item_definition_id: INVENTORY_PSEUDO_CONTAINER_DEF_ID.to_owned(),
             position: INVENTORY_PSEUDO_CONTAINER_POSITION.to_owned(),
+            dye: None,
         },
         Item {
             stack_size: 1,
@@ -187,6 +232,7 @@ pub fn create_character(
             parent_container_item_id: character_id,
             item_definition_id: LOADOUT_PSEUDO_CONTAINER_DEF_ID.to_owned(),
             position: LOADOUT_PSEUDO_CONTAINER_POSITION.to_owned(),
+            dye: None,
         },
     ];
     let pseudo_container_count = diesel::insert_into(item)
@@ -248,6 +294,32 @@ pub fn create_character(
         )));
     }
 
+    // Insert ability hotbar record
+    let db_hotbar = convert_ability_hotbar_to_database(character_id, &hotbar)?;
+    let hotbar_count = diesel::insert_into(ability_hotbar::table)
+        .values(&db_hotbar)
+        .execute(&*connection)?;
+
+    if hotbar_count != 1 {
+        return Err(Error::OtherError(format!(
+            "Error inserting into ability_hotbar table for char_id {}",
+            character_id
+        )));
+    }
+
+    // Insert alchemy journal record
+    let db_journal = convert_alchemy_journal_to_database(character_id, &journal)?;
+    let journal_count = diesel::insert_into(alchemy_journal::table)
+        .values(&db_journal)
+        .execute(&*connection)?;
+
+    if journal_count != 1 {
+        return Err(Error::OtherError(format!(
+            "Error inserting into alchemy_journal table for char_id {}",
+            character_id
+        )));
+    }
+
     // Insert default inventory and loadout item records
     let mut inserts = Vec::new();
 
@@ -300,6 +372,36 @@ pub fn delete_character(
         )
         .first::<Character>(&*connection)?;
 
+    // Delete ability hotbar, which characters created before it was persisted
+    // may not have
+    diesel::delete(
+        schema::ability_hotbar::table.filter(schema::ability_hotbar::dsl::character_id.eq(char_id)),
+    )
+    .execute(&*connection)?;
+
+    // Likewise for the alchemy journal
+    diesel::delete(
+        schema::alchemy_journal::table
+            .filter(schema::alchemy_journal::dsl::character_id.eq(char_id)),
+    )
+    .execute(&*connection)?;
+
+    // Delete waypoint, if the character set one
+    diesel::delete(schema::waypoint::table.filter(schema::waypoint::dsl::character_id.eq(char_id)))
+        .execute(&*connection)?;
+
+    // Delete the skill set, if the character changed their skills
+    diesel::delete(
+        schema::skill_set::table.filter(schema::skill_set::dsl::character_id.eq(char_id)),
+    )
+    .execute(&*connection)?;
+
+    // Delete the UI preferences, if the client stored any
+    diesel::delete(
+        schema::ui_preferences::table.filter(schema::ui_preferences::dsl::character_id.eq(char_id)),
+    )
+    .execute(&*connection)?;
+
     // Delete character
     let character_count = diesel::delete(
         character
@@ -370,12 +472,24 @@ pub fn delete_character(
     load_character_list(requesting_player_uuid, connection)
 }
 
+/// Load what the account unlocked, which is nothing if it has no entry yet
+pub fn load_account_unlocks(uuid: &str, connection: VelorenTransaction) -> AccountUnlocksResult {
+    schema::account_unlocks::table
+        .filter(schema::account_unlocks::dsl::player_uuid.eq(uuid))
+        .first::<AccountUnlocks>(&*connection)
+        .optional()?
+        .map(|unlocks| convert_account_unlocks_from_database(&unlocks))
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
 /// Before creating a character, we ensure that the limit on the number of
 /// characters has not been exceeded
 pub fn check_character_limit(uuid: &str, connection: VelorenTransaction) -> Result<(), Error> {
     use diesel::dsl::count_star;
     use schema::character::dsl::*;
 
+    let character_slots = load_account_unlocks(uuid, connection)?.character_slots();
     let character_count = character
         .select(count_star())
         .filter(player_uuid.eq(uuid))
@@ -383,7 +497,7 @@ pub fn check_character_limit(uuid: &str, connection: VelorenTransaction) -> Resu
 
     match character_count.first() {
         Some(count) => {
-            if count < &(MAX_CHARACTERS_PER_PLAYER as i64) {
+            if count < &(character_slots as i64) {
                 Ok(())
             } else {
                 Err(Error::CharacterLimitReached)
@@ -505,11 +619,15 @@ fn get_pseudo_container_id(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn update(
     char_id: CharacterId,
     char_stats: comp::Stats,
     inventory: comp::Inventory,
     loadout: comp::Loadout,
+    hotbar: comp::AbilityHotbar,
+    journal: comp::AlchemyJournal,
+    waypoint: Option<comp::Waypoint>,
     connection: VelorenTransaction,
 ) -> Result<Vec<Arc<common::comp::item::ItemId>>, Error> {
     use super::schema::{item::dsl::*, stats::dsl::*};
@@ -587,5 +705,26 @@ pub fn update(
         )));
     }
 
+    let db_skill_set = convert_skill_set_to_database(char_id, &char_stats.skill_set)?;
+    diesel::replace_into(schema::skill_set::table)
+        .values(&db_skill_set)
+        .execute(&*connection)?;
+
+    let db_hotbar = convert_ability_hotbar_to_database(char_id, &hotbar)?;
+    diesel::replace_into(schema::ability_hotbar::table)
+        .values(&db_hotbar)
+        .execute(&*connection)?;
+
+    let db_journal = convert_alchemy_journal_to_database(char_id, &journal)?;
+    diesel::replace_into(schema::alchemy_journal::table)
+        .values(&db_journal)
+        .execute(&*connection)?;
+
+    if let Some(waypoint) = waypoint {
+        diesel::replace_into(schema::waypoint::table)
+            .values(&convert_waypoint_to_database(char_id, &waypoint))
+            .execute(&*connection)?;
+    }
+
     Ok(upserted_comps)
 }
@@ -1,17 +1,22 @@
 use crate::persistence::{
     character::EntityId,
-    models::{Body, Character, Item, Stats},
+    models::{
+        AbilityHotbar as DbAbilityHotbar, AccountUnlocks as DbAccountUnlocks,
+        AlchemyJournal as DbAlchemyJournal, Body, Character, Item, SkillSet as DbSkillSet, Stats,
+        Waypoint as DbWaypoint,
+    },
 };
 
 use crate::persistence::{error::Error, json_models::HumanoidBody};
 use common::{
-    character::CharacterId,
+    character::{AccountUnlocks, CharacterId},
     comp::{Body as CompBody, *},
     loadout_builder,
 };
 use core::{convert::TryFrom, num::NonZeroU64};
 use itertools::{Either, Itertools};
 use std::sync::Arc;
+use vek::{Rgb, Vec3};
 
 pub struct ItemModelPair {
     pub comp: Arc<common::comp::item::ItemId>,
@@ -145,6 +150,7 @@ pub fn convert_items_to_database_items(
                         } else {
                             1
                         },
+                        dye: item.dye().map(convert_dye_to_database),
                     },
                     // Continue to remember the atomic, in case we detect an error later and want
                     // to roll back to preserve liveness.
@@ -156,6 +162,15 @@ pub fn convert_items_to_database_items(
         })
 }
 
+/// Dyes are stored with their color packed as 0xRRGGBB
+fn convert_dye_to_database(dye: Rgb<u8>) -> i32 {
+    i32::from(dye.r) << 16 | i32::from(dye.g) << 8 | i32::from(dye.b)
+}
+
+fn convert_dye_from_database(dye: i32) -> Rgb<u8> {
+    Rgb::new((dye >> 16) as u8, (dye >> 8) as u8, dye as u8)
+}
+
 pub fn convert_body_to_database_json(body: &CompBody) -> Result<String, Error> {
     let json_model = match body {
         common::comp::Body::Humanoid(humanoid_body) => HumanoidBody::from(humanoid_body),
@@ -165,6 +180,46 @@ pub fn convert_body_to_database_json(body: &CompBody) -> Result<String, Error> {
     serde_json::to_string(&json_model).map_err(Error::SerializationError)
 }
 
+pub fn convert_ability_hotbar_to_database(
+    character_id: CharacterId,
+    hotbar: &AbilityHotbar,
+) -> Result<DbAbilityHotbar, Error> {
+    Ok(DbAbilityHotbar {
+        character_id,
+        slots: serde_json::to_string(&hotbar.slots).map_err(Error::SerializationError)?,
+    })
+}
+
+pub fn convert_skill_set_to_database(
+    character_id: CharacterId,
+    skill_set: &SkillSet,
+) -> Result<DbSkillSet, Error> {
+    Ok(DbSkillSet {
+        character_id,
+        data: serde_json::to_string(skill_set).map_err(Error::SerializationError)?,
+    })
+}
+
+pub fn convert_alchemy_journal_to_database(
+    character_id: CharacterId,
+    journal: &AlchemyJournal,
+) -> Result<DbAlchemyJournal, Error> {
+    Ok(DbAlchemyJournal {
+        character_id,
+        known: serde_json::to_string(journal).map_err(Error::SerializationError)?,
+    })
+}
+
+pub fn convert_waypoint_to_database(character_id: CharacterId, waypoint: &Waypoint) -> DbWaypoint {
+    let pos = waypoint.get_pos();
+    DbWaypoint {
+        character_id,
+        x: pos.x,
+        y: pos.y,
+        z: pos.z,
+    }
+}
+
 pub fn convert_stats_to_database(character_id: CharacterId, stats: &common::comp::Stats) -> Stats {
     Stats {
         stats_id: character_id,
@@ -189,6 +244,8 @@ pub fn convert_inventory_from_database_items(database_items: &[Item]) -> Result<
             |_| Error::ConversionError("Item with zero item_id".to_owned()),
         )?));
 
+        item.set_dye(db_item.dye.map(convert_dye_from_database));
+
         // Stack Size
         if db_item.stack_size == 1 || item.is_stackable() {
             item.set_amount(u32::try_from(db_item.stack_size).map_err(|_| {
@@ -233,7 +290,8 @@ pub fn convert_inventory_from_database_items(database_items: &[Item]) -> Result<
 pub fn convert_loadout_from_database_items(database_items: &[Item]) -> Result<Loadout, Error> {
     let mut loadout = loadout_builder::LoadoutBuilder::new();
     for db_item in database_items.iter() {
-        let item = common::comp::Item::new_from_asset(db_item.item_definition_id.as_str())?;
+        let mut item = common::comp::Item::new_from_asset(db_item.item_definition_id.as_str())?;
+        item.set_dye(db_item.dye.map(convert_dye_from_database));
         // NOTE: item id is currently *unique*, so we can store the ID safely.
         let comp = item.get_item_id_for_database();
         comp.store(Some(NonZeroU64::try_from(db_item.item_id as u64).map_err(
@@ -329,3 +387,35 @@ pub fn convert_stats_from_database(stats: &Stats, alias: String) -> common::comp
 
     new_stats
 }
+
+pub fn convert_ability_hotbar_from_database(
+    hotbar: &DbAbilityHotbar,
+) -> Result<AbilityHotbar, Error> {
+    Ok(AbilityHotbar {
+        slots: serde_json::from_str(&hotbar.slots).map_err(Error::SerializationError)?,
+    })
+}
+
+pub fn convert_skill_set_from_database(skill_set: &DbSkillSet) -> Result<SkillSet, Error> {
+    serde_json::from_str(&skill_set.data).map_err(Error::SerializationError)
+}
+
+pub fn convert_alchemy_journal_from_database(
+    journal: &DbAlchemyJournal,
+) -> Result<AlchemyJournal, Error> {
+    serde_json::from_str(&journal.known).map_err(Error::SerializationError)
+}
+
+/// Only the position of a waypoint is persisted, not the time it was saved at
+pub fn convert_waypoint_from_database(waypoint: &DbWaypoint) -> Waypoint {
+    Waypoint::new(
+        Vec3::new(waypoint.x, waypoint.y, waypoint.z),
+        common::state::Time(0.0),
+    )
+}
+
+pub fn convert_account_unlocks_from_database(
+    unlocks: &DbAccountUnlocks,
+) -> Result<AccountUnlocks, Error> {
+    serde_json::from_str(&unlocks.unlocks).map_err(Error::SerializationError)
+}
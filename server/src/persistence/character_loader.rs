@@ -1,15 +1,19 @@
 use crate::persistence::{
-    character::{create_character, delete_character, load_character_data, load_character_list},
+    character::{
+        create_character, delete_character, load_account_unlocks, load_character_data,
+        load_character_list,
+    },
     error::Error,
     establish_connection, PersistedComponents,
 };
-use common::character::{CharacterId, CharacterItem};
+use common::character::{AccountUnlocks, CharacterId, CharacterItem};
 use crossbeam::{channel, channel::TryIter};
 use std::path::Path;
 use tracing::error;
 
 pub(crate) type CharacterListResult = Result<Vec<CharacterItem>, Error>;
 pub(crate) type CharacterDataResult = Result<PersistedComponents, Error>;
+pub(crate) type AccountUnlocksResult = Result<AccountUnlocks, Error>;
 type CharacterLoaderRequest = (specs::Entity, CharacterLoaderRequestKind);
 
 /// Available database operations when modifying a player's character list
@@ -30,14 +34,19 @@ enum CharacterLoaderRequestKind {
         player_uuid: String,
         character_id: CharacterId,
     },
+    LoadAccountUnlocks {
+        player_uuid: String,
+    },
 }
 
 /// Wrapper for results for character actions. Can be a list of
-/// characters, or component data belonging to an individual character
+/// characters, component data belonging to an individual character, or what
+/// the account of the player unlocked
 #[derive(Debug)]
 pub enum CharacterLoaderResponseType {
     CharacterList(CharacterListResult),
     CharacterData(Box<CharacterDataResult>),
+    AccountUnlocks(AccountUnlocksResult),
 }
 
 /// Common message format dispatched in response to an update request
@@ -111,6 +120,11 @@ impl CharacterLoader {
                                 |txn| load_character_data(player_uuid, character_id, txn),
                             )))
                         },
+                        CharacterLoaderRequestKind::LoadAccountUnlocks { player_uuid } => {
+                            CharacterLoaderResponseType::AccountUnlocks(
+                                conn.transaction(|txn| load_account_unlocks(&player_uuid, txn)),
+                            )
+                        },
                     },
                 }) {
                     error!(?e, "Could not send send persistence request");
@@ -194,6 +208,19 @@ impl CharacterLoader {
         }
     }
 
+    /// Loads what the account of the player identified by `player_uuid`
+    /// unlocked
+    pub fn load_account_unlocks(&self, entity: specs::Entity, player_uuid: String) {
+        if let Err(e) = self
+            .update_tx
+            .send((entity, CharacterLoaderRequestKind::LoadAccountUnlocks {
+                player_uuid,
+            }))
+        {
+            error!(?e, "Could not send account unlocks load request");
+        }
+    }
+
     /// Returns a non-blocking iterator over CharacterLoaderResponse messages
     pub fn messages(&self) -> TryIter<CharacterLoaderResponse> { self.update_rx.try_iter() }
 }
@@ -6,7 +6,14 @@ use crossbeam::channel;
 use std::{path::Path, sync::Arc};
 use tracing::{error, trace};
 
-pub type CharacterUpdateData = (comp::Stats, comp::Inventory, comp::Loadout);
+pub type CharacterUpdateData = (
+    comp::Stats,
+    comp::Inventory,
+    comp::Loadout,
+    comp::AbilityHotbar,
+    comp::AlchemyJournal,
+    Option<comp::Waypoint>,
+);
 
 /// A unidirectional messaging resource for saving characters in a
 /// background thread.
@@ -48,17 +55,29 @@ impl CharacterUpdater {
                 &'a comp::Stats,
                 &'a comp::Inventory,
                 &'a comp::Loadout,
+                &'a comp::AbilityHotbar,
+                &'a comp::AlchemyJournal,
+                Option<&'a comp::Waypoint>,
             ),
         >,
     ) {
         let updates = updates
-            .map(|(character_id, stats, inventory, loadout)| {
-                (
-                    character_id,
-                    (stats.clone(), inventory.clone(), loadout.clone()),
-                )
-            })
-            .collect::<Vec<(CharacterId, (comp::Stats, comp::Inventory, comp::Loadout))>>();
+            .map(
+                |(character_id, stats, inventory, loadout, hotbar, journal, waypoint)| {
+                    (
+                        character_id,
+                        (
+                            stats.clone(),
+                            inventory.clone(),
+                            loadout.clone(),
+                            hotbar.clone(),
+                            journal.clone(),
+                            waypoint.copied(),
+                        ),
+                    )
+                },
+            )
+            .collect::<Vec<(CharacterId, CharacterUpdateData)>>();
 
         if let Err(e) = self.update_tx.as_ref().unwrap().send(updates) {
             error!(?e, "Could not send stats updates");
@@ -66,14 +85,26 @@ impl CharacterUpdater {
     }
 
     /// Updates a single character based on their id and components
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &self,
         character_id: CharacterId,
         stats: &comp::Stats,
         inventory: &comp::Inventory,
         loadout: &comp::Loadout,
+        hotbar: &comp::AbilityHotbar,
+        journal: &comp::AlchemyJournal,
+        waypoint: Option<&comp::Waypoint>,
     ) {
-        self.batch_update(std::iter::once((character_id, stats, inventory, loadout)));
+        self.batch_update(std::iter::once((
+            character_id,
+            stats,
+            inventory,
+            loadout,
+            hotbar,
+            journal,
+            waypoint,
+        )));
     }
 }
 
@@ -84,12 +115,15 @@ fn execute_batch_update(
     let mut inserted_items = Vec::<Arc<ItemId>>::new();
 
     if let Err(e) = connection.transaction::<_, super::error::Error, _>(|txn| {
-        for (character_id, (stats, inventory, loadout)) in updates {
+        for (character_id, (stats, inventory, loadout, hotbar, journal, waypoint)) in updates {
             inserted_items.append(&mut super::character::update(
                 character_id,
                 stats,
                 inventory,
                 loadout,
+                hotbar,
+                journal,
+                waypoint,
                 txn,
             )?);
         }
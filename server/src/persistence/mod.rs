@@ -6,13 +6,17 @@
 //! - [`diesel-cli`](https://github.com/diesel-rs/diesel/tree/master/diesel_cli/)
 //!   for generating and testing migrations
 
+pub mod block_diff;
 pub(in crate::persistence) mod character;
 pub mod character_loader;
 pub mod character_updater;
 mod error;
 mod json_models;
 mod models;
+pub mod named_location;
+pub mod rules;
 mod schema;
+pub mod ui_preferences;
 
 use common::comp;
 use diesel::{connection::SimpleConnection, prelude::*};
@@ -21,7 +25,15 @@ use std::{fs, path::Path};
 use tracing::info;
 
 /// A tuple of the components that are persisted to the DB for each character
-pub type PersistedComponents = (comp::Body, comp::Stats, comp::Inventory, comp::Loadout);
+pub type PersistedComponents = (
+    comp::Body,
+    comp::Stats,
+    comp::Inventory,
+    comp::Loadout,
+    comp::AbilityHotbar,
+    comp::AlchemyJournal,
+    Option<comp::Waypoint>,
+);
 
 // See: https://docs.rs/diesel_migrations/1.4.0/diesel_migrations/macro.embed_migrations.html
 // This macro is called at build-time, and produces the necessary migration info
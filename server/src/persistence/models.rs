@@ -1,6 +1,9 @@
 extern crate serde_json;
 
-use super::schema::{body, character, entity, item, stats};
+use super::schema::{
+    ability_hotbar, account_unlocks, alchemy_journal, block_diff, body, character, entity, item,
+    named_location, rules_acknowledgement, sign, skill_set, stats, ui_preferences, waypoint,
+};
 
 #[derive(Debug, Insertable, PartialEq)]
 #[table_name = "entity"]
@@ -34,6 +37,7 @@ pub struct Item {
     pub item_definition_id: String,
     pub stack_size: i32,
     pub position: String,
+    pub dye: Option<i32>,
 }
 
 #[derive(Associations, AsChangeset, Identifiable, Queryable, Debug, Insertable)]
@@ -56,3 +60,96 @@ pub struct Body {
     pub variant: String,
     pub body_data: String,
 }
+
+#[derive(Identifiable, Insertable, Queryable, Debug)]
+#[primary_key(character_id)]
+#[table_name = "ability_hotbar"]
+pub struct AbilityHotbar {
+    pub character_id: i64,
+    pub slots: String,
+}
+
+#[derive(Identifiable, Insertable, Queryable, Debug)]
+#[primary_key(character_id)]
+#[table_name = "alchemy_journal"]
+pub struct AlchemyJournal {
+    pub character_id: i64,
+    pub known: String,
+}
+
+#[derive(Identifiable, Insertable, Queryable, Debug)]
+#[primary_key(character_id)]
+#[table_name = "skill_set"]
+pub struct SkillSet {
+    pub character_id: i64,
+    pub data: String,
+}
+
+#[derive(Identifiable, Insertable, Queryable, Debug)]
+#[primary_key(character_id)]
+#[table_name = "waypoint"]
+pub struct Waypoint {
+    pub character_id: i64,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[derive(Identifiable, Insertable, Queryable, Debug)]
+#[primary_key(character_id)]
+#[table_name = "ui_preferences"]
+pub struct UiPreferences {
+    pub character_id: i64,
+    pub data: Vec<u8>,
+}
+
+#[derive(Identifiable, Insertable, Queryable, Debug)]
+#[primary_key(player_uuid)]
+#[table_name = "account_unlocks"]
+pub struct AccountUnlocks {
+    pub player_uuid: String,
+    pub unlocks: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "block_diff"]
+pub struct NewBlockDiff {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub block: String,
+}
+
+#[derive(Queryable, Debug)]
+pub struct BlockDiff {
+    pub block_diff_id: i64,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub block: String,
+}
+
+#[derive(Insertable, Queryable, Debug)]
+#[table_name = "rules_acknowledgement"]
+pub struct RulesAcknowledgement {
+    pub player_uuid: String,
+    pub rules: String,
+}
+
+#[derive(Insertable, Queryable, Debug)]
+#[table_name = "sign"]
+pub struct Sign {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub text: String,
+}
+
+#[derive(Insertable, Queryable, Debug)]
+#[table_name = "named_location"]
+pub struct NamedLocation {
+    pub name: String,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
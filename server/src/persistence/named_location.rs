@@ -0,0 +1,137 @@
+//! Persistence of named locations
+//!
+//! Admins can give names to places in the world, which are shown on the map
+//! and can be teleported to. They are part of the world save, so they survive
+//! server restarts.
+
+use crate::persistence::{establish_connection, models, schema, VelorenConnection};
+use common::msg::NamedLocation;
+use crossbeam::channel;
+use diesel::prelude::*;
+use std::{collections::BTreeMap, path::Path};
+use tracing::{error, info};
+use vek::*;
+
+enum NamedLocationRequest {
+    Set(models::NamedLocation),
+    Remove(String),
+}
+
+/// The named locations of the world, written to the database in a background
+/// thread.
+pub struct NamedLocations {
+    locations: BTreeMap<String, Vec3<f32>>,
+    update_tx: Option<channel::Sender<NamedLocationRequest>>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl NamedLocations {
+    /// Load all named locations from the database
+    pub fn new(db_dir: &Path) -> diesel::QueryResult<Self> {
+        let mut conn = establish_connection(db_dir)?;
+
+        let rows = conn.transaction::<_, diesel::result::Error, _>(|txn| {
+            schema::named_location::dsl::named_location.load::<models::NamedLocation>(&*txn)
+        })?;
+        let count = rows.len();
+        let locations = rows
+            .into_iter()
+            .map(|row| (row.name, Vec3::new(row.x, row.y, row.z)))
+            .collect();
+        info!(?count, "Loaded named locations");
+
+        let (update_tx, update_rx) = channel::unbounded::<NamedLocationRequest>();
+        let handle = std::thread::spawn(move || {
+            while let Ok(request) = update_rx.recv() {
+                record(request, &mut conn);
+            }
+        });
+
+        Ok(Self {
+            locations,
+            update_tx: Some(update_tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// The position of the location, ignoring the case of its name
+    pub fn get(&self, name: &str) -> Option<Vec3<f32>> {
+        self.locations
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, pos)| *pos)
+    }
+
+    /// Add a location, replacing any with the same name
+    pub fn set(&mut self, name: String, pos: Vec3<f32>) {
+        self.remove(&name);
+        self.locations.insert(name.clone(), pos);
+        self.send(NamedLocationRequest::Set(models::NamedLocation {
+            name,
+            x: pos.x,
+            y: pos.y,
+            z: pos.z,
+        }));
+    }
+
+    /// Remove a location, returning whether it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let existing = self
+            .locations
+            .keys()
+            .find(|n| n.eq_ignore_ascii_case(name))
+            .cloned();
+        match existing {
+            Some(name) => {
+                self.locations.remove(&name);
+                self.send(NamedLocationRequest::Remove(name));
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// All locations, as sent to clients
+    pub fn to_msg(&self) -> Vec<NamedLocation> {
+        self.locations
+            .iter()
+            .map(|(name, pos)| NamedLocation {
+                name: name.clone(),
+                pos: *pos,
+            })
+            .collect()
+    }
+
+    fn send(&self, request: NamedLocationRequest) {
+        if let Err(e) = self.update_tx.as_ref().unwrap().send(request) {
+            error!(?e, "Could not send named location to be persisted");
+        }
+    }
+}
+
+fn record(request: NamedLocationRequest, connection: &mut VelorenConnection) {
+    use schema::named_location::dsl;
+
+    if let Err(e) = connection.transaction::<_, diesel::result::Error, _>(|txn| {
+        match request {
+            NamedLocationRequest::Set(row) => diesel::replace_into(schema::named_location::table)
+                .values(&row)
+                .execute(&*txn),
+            NamedLocationRequest::Remove(name) => {
+                diesel::delete(dsl::named_location.filter(dsl::name.eq(name))).execute(&*txn)
+            },
+        }
+        .map(|_| ())
+    }) {
+        error!(?e, "Error while persisting named location");
+    }
+}
+
+impl Drop for NamedLocations {
+    fn drop(&mut self) {
+        drop(self.update_tx.take());
+        if let Err(e) = self.handle.take().unwrap().join() {
+            error!(?e, "Error from joining named location persistence thread");
+        }
+    }
+}
@@ -0,0 +1,95 @@
+//! Persistence of which server rules players accepted
+//!
+//! Players have to accept the rules of a server before they can play on it.
+//! The rules each account accepted are stored, so that players are only asked
+//! again once the rules change.
+
+use crate::persistence::{
+    establish_connection, models::RulesAcknowledgement, schema, VelorenConnection,
+};
+use crossbeam::channel;
+use diesel::prelude::*;
+use hashbrown::HashMap;
+use std::path::Path;
+use tracing::{error, info};
+
+/// The rules accepted by each account, written to the database in a
+/// background thread.
+pub struct RulesAcknowledgements {
+    accepted: HashMap<String, String>,
+    update_tx: Option<channel::Sender<RulesAcknowledgement>>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl RulesAcknowledgements {
+    /// Load the rules accepted by all accounts from the database
+    pub fn new(db_dir: &Path) -> diesel::QueryResult<Self> {
+        let mut conn = establish_connection(db_dir)?;
+
+        let rows = conn.transaction::<_, diesel::result::Error, _>(|txn| {
+            schema::rules_acknowledgement::dsl::rules_acknowledgement
+                .load::<RulesAcknowledgement>(&*txn)
+        })?;
+        let count = rows.len();
+        let accepted = rows
+            .into_iter()
+            .map(|row| (row.player_uuid, row.rules))
+            .collect();
+        info!(?count, "Loaded accepted server rules");
+
+        let (update_tx, update_rx) = channel::unbounded::<RulesAcknowledgement>();
+        let handle = std::thread::spawn(move || {
+            while let Ok(row) = update_rx.recv() {
+                record(row, &mut conn);
+            }
+        });
+
+        Ok(Self {
+            accepted,
+            update_tx: Some(update_tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// Whether the account has to accept the rules before playing
+    pub fn must_accept(&self, player_uuid: &str, rules: &str) -> bool {
+        !rules.is_empty()
+            && self
+                .accepted
+                .get(player_uuid)
+                .map_or(true, |accepted| accepted != rules)
+    }
+
+    /// Record that the account accepted the rules
+    pub fn accept(&mut self, player_uuid: String, rules: String) {
+        self.accepted.insert(player_uuid.clone(), rules.clone());
+        if let Err(e) = self
+            .update_tx
+            .as_ref()
+            .unwrap()
+            .send(RulesAcknowledgement { player_uuid, rules })
+        {
+            error!(?e, "Could not send accepted rules to be persisted");
+        }
+    }
+}
+
+fn record(row: RulesAcknowledgement, connection: &mut VelorenConnection) {
+    if let Err(e) = connection.transaction::<_, diesel::result::Error, _>(|txn| {
+        diesel::replace_into(schema::rules_acknowledgement::table)
+            .values(&row)
+            .execute(&*txn)
+            .map(|_| ())
+    }) {
+        error!(?e, "Error while persisting accepted rules");
+    }
+}
+
+impl Drop for RulesAcknowledgements {
+    fn drop(&mut self) {
+        drop(self.update_tx.take());
+        if let Err(e) = self.handle.take().unwrap().join() {
+            error!(?e, "Error from joining rules persistence thread");
+        }
+    }
+}
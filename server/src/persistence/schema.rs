@@ -1,3 +1,34 @@
+table! {
+    ability_hotbar (character_id) {
+        character_id -> BigInt,
+        slots -> Text,
+    }
+}
+
+table! {
+    account_unlocks (player_uuid) {
+        player_uuid -> Text,
+        unlocks -> Text,
+    }
+}
+
+table! {
+    alchemy_journal (character_id) {
+        character_id -> BigInt,
+        known -> Text,
+    }
+}
+
+table! {
+    block_diff (block_diff_id) {
+        block_diff_id -> BigInt,
+        x -> Integer,
+        y -> Integer,
+        z -> Integer,
+        block -> Text,
+    }
+}
+
 table! {
     body (body_id) {
         body_id -> BigInt,
@@ -27,6 +58,39 @@ table! {
         item_definition_id -> Text,
         stack_size -> Integer,
         position -> Text,
+        dye -> Nullable<Integer>,
+    }
+}
+
+table! {
+    named_location (name) {
+        name -> Text,
+        x -> Float,
+        y -> Float,
+        z -> Float,
+    }
+}
+
+table! {
+    rules_acknowledgement (player_uuid) {
+        player_uuid -> Text,
+        rules -> Text,
+    }
+}
+
+table! {
+    sign (x, y, z) {
+        x -> Integer,
+        y -> Integer,
+        z -> Integer,
+        text -> Text,
+    }
+}
+
+table! {
+    skill_set (character_id) {
+        character_id -> BigInt,
+        data -> Text,
     }
 }
 
@@ -41,7 +105,39 @@ table! {
     }
 }
 
+table! {
+    ui_preferences (character_id) {
+        character_id -> BigInt,
+        data -> Binary,
+    }
+}
+
+table! {
+    waypoint (character_id) {
+        character_id -> BigInt,
+        x -> Float,
+        y -> Float,
+        z -> Float,
+    }
+}
+
 joinable!(character -> body (character_id));
 joinable!(character -> stats (character_id));
 
-allow_tables_to_appear_in_same_query!(body, character, entity, item, stats,);
+allow_tables_to_appear_in_same_query!(
+    ability_hotbar,
+    account_unlocks,
+    alchemy_journal,
+    block_diff,
+    body,
+    character,
+    entity,
+    item,
+    named_location,
+    rules_acknowledgement,
+    sign,
+    skill_set,
+    stats,
+    ui_preferences,
+    waypoint,
+);
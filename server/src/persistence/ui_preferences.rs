@@ -0,0 +1,141 @@
+//! Persistence of the UI preferences of characters
+//!
+//! Clients can store their UI setup for a character on the server so that
+//! players get the same setup when playing from another machine. Voxygen
+//! currently only stores the layout of the hotbar. The server doesn't look
+//! into the preferences, it only limits their size to
+//! [`MAX_BYTES_UI_PREFERENCES`].
+//!
+//! [`MAX_BYTES_UI_PREFERENCES`]: common::msg::MAX_BYTES_UI_PREFERENCES
+
+use crate::persistence::{establish_connection, models::UiPreferences, schema, VelorenConnection};
+use common::character::CharacterId;
+use crossbeam::{channel, channel::TryIter};
+use diesel::{prelude::*, result::Error};
+use std::path::Path;
+use tracing::error;
+
+enum UiPreferencesRequest {
+    Load {
+        entity: specs::Entity,
+        character_id: CharacterId,
+    },
+    Store {
+        character_id: CharacterId,
+        data: Vec<u8>,
+    },
+}
+
+/// The preferences stored for the character of an entity, if it has any
+#[derive(Debug)]
+pub struct UiPreferencesResponse {
+    pub entity: specs::Entity,
+    pub character_id: CharacterId,
+    pub result: Result<Option<Vec<u8>>, Error>,
+}
+
+/// Loads and stores UI preferences in a background thread. Responses to loads
+/// are polled on each server tick.
+pub struct UiPreferenceStore {
+    update_rx: channel::Receiver<UiPreferencesResponse>,
+    update_tx: Option<channel::Sender<UiPreferencesRequest>>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl UiPreferenceStore {
+    pub fn new(db_dir: &Path) -> diesel::QueryResult<Self> {
+        let (update_tx, internal_rx) = channel::unbounded::<UiPreferencesRequest>();
+        let (internal_tx, update_rx) = channel::unbounded::<UiPreferencesResponse>();
+
+        let mut conn = establish_connection(db_dir)?;
+
+        let handle = std::thread::spawn(move || {
+            for request in internal_rx {
+                match request {
+                    UiPreferencesRequest::Load {
+                        entity,
+                        character_id,
+                    } => {
+                        let result = load(character_id, &mut conn);
+                        if let Err(e) = internal_tx.send(UiPreferencesResponse {
+                            entity,
+                            character_id,
+                            result,
+                        }) {
+                            error!(?e, "Could not send UI preferences load response");
+                        }
+                    },
+                    UiPreferencesRequest::Store { character_id, data } => {
+                        if let Err(e) = store(character_id, data, &mut conn) {
+                            error!(?e, ?character_id, "Error while persisting UI preferences");
+                        }
+                    },
+                }
+            }
+        });
+
+        Ok(Self {
+            update_rx,
+            update_tx: Some(update_tx),
+            handle: Some(handle),
+        })
+    }
+
+    /// Load the preferences of a character, answered in a later
+    /// [`UiPreferenceStore::responses`]
+    pub fn load(&self, entity: specs::Entity, character_id: CharacterId) {
+        self.send(UiPreferencesRequest::Load {
+            entity,
+            character_id,
+        });
+    }
+
+    /// Replace the preferences of a character
+    pub fn store(&self, character_id: CharacterId, data: Vec<u8>) {
+        self.send(UiPreferencesRequest::Store { character_id, data });
+    }
+
+    /// Returns a non-blocking iterator over the loaded preferences
+    pub fn responses(&self) -> TryIter<UiPreferencesResponse> { self.update_rx.try_iter() }
+
+    fn send(&self, request: UiPreferencesRequest) {
+        if let Err(e) = self.update_tx.as_ref().unwrap().send(request) {
+            error!(?e, "Could not send UI preferences request");
+        }
+    }
+}
+
+fn load(
+    character_id: CharacterId,
+    connection: &mut VelorenConnection,
+) -> Result<Option<Vec<u8>>, Error> {
+    connection.transaction(|txn| {
+        Ok(schema::ui_preferences::table
+            .filter(schema::ui_preferences::dsl::character_id.eq(character_id))
+            .first::<UiPreferences>(&*txn)
+            .optional()?
+            .map(|row| row.data))
+    })
+}
+
+fn store(
+    character_id: CharacterId,
+    data: Vec<u8>,
+    connection: &mut VelorenConnection,
+) -> Result<(), Error> {
+    connection.transaction(|txn| {
+        diesel::replace_into(schema::ui_preferences::table)
+            .values(&UiPreferences { character_id, data })
+            .execute(&*txn)?;
+        Ok(())
+    })
+}
+
+impl Drop for UiPreferenceStore {
+    fn drop(&mut self) {
+        drop(self.update_tx.take());
+        if let Err(e) = self.handle.take().unwrap().join() {
+            error!(?e, "Error from joining UI preferences persistence thread");
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! Remote admin console
+//!
+//! Lets server hosts run chat commands without joining the game. Operators
+//! connect over TCP and send the configured token as the first line, after
+//! which every line is run as a chat command with admin rights. The output of
+//! each command is sent back line by line, followed by an empty line.
+//!
+//! Connections are handled on their own threads, of which only a few may run
+//! at once; commands are handed to the server through a channel and run
+//! during its tick like any other chat command. Every command is written to
+//! an audit log in the data directory.
+
+use crossbeam::channel::{self, Receiver, Sender};
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use specs::Component;
+use specs_idvs::IdvStorage;
+use std::{
+    fs::OpenOptions,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{IpAddr, SocketAddr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, info, warn};
+
+const AUDIT_LOG_FILENAME: &str = "rcon_audit.log";
+/// How long a connection waits for the server to run a command
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Connections that don't authenticate within this time are closed
+const AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+/// Delay before answering a wrong token, to slow down guessing
+const AUTH_FAILURE_DELAY: Duration = Duration::from_secs(2);
+/// Longest line accepted from a connection, in bytes
+const MAX_LINE_LEN: u64 = 1024;
+/// Number of connections that may be open at the same time
+const MAX_CONNECTIONS: usize = 4;
+/// Number of failed authentication attempts after which an address is
+/// refused until `AUTH_FAILURE_WINDOW` has passed since the last one
+const MAX_AUTH_FAILURES: u32 = 5;
+const AUTH_FAILURE_WINDOW: Duration = Duration::from_secs(600);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RconSettings {
+    /// Address to listen on, or None to disable the remote console
+    pub address: Option<SocketAddr>,
+    /// Secret that clients send to authenticate. The console stays disabled
+    /// while this is empty.
+    pub token: String,
+    /// Number of commands a single connection may run per minute
+    pub commands_per_minute: u32,
+}
+
+impl Default for RconSettings {
+    fn default() -> Self {
+        Self {
+            address: None,
+            token: String::new(),
+            commands_per_minute: 30,
+        }
+    }
+}
+
+/// A command received from a remote console
+pub struct RconRequest {
+    pub peer: SocketAddr,
+    pub command: String,
+    reply: Sender<Vec<String>>,
+}
+
+impl RconRequest {
+    pub fn respond(self, lines: Vec<String>) { let _ = self.reply.send(lines); }
+}
+
+/// Server-only component of the temporary entity a remote command runs as,
+/// collecting the messages sent to it
+#[derive(Default)]
+pub struct RconOutput(pub Vec<String>);
+
+impl Component for RconOutput {
+    type Storage = IdvStorage<Self>;
+}
+
+/// Allows a burst of `capacity` commands, refilled evenly over a minute
+pub struct RateLimiter {
+    capacity: f64,
+    available: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    pub fn per_minute(capacity: u32, now: Instant) -> Self {
+        Self {
+            capacity: capacity as f64,
+            available: capacity as f64,
+            last: now,
+        }
+    }
+
+    pub fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.available = (self.available + elapsed * self.capacity / 60.0).min(self.capacity);
+        if self.available >= 1.0 {
+            self.available -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Failed authentication attempts of a single address
+struct AuthFailures {
+    count: u32,
+    last: Instant,
+}
+
+/// Open connections and failed authentication attempts, shared between the
+/// listener and the connection threads
+#[derive(Default)]
+struct PeerLimits {
+    connections: usize,
+    failures: HashMap<IpAddr, AuthFailures>,
+}
+
+impl PeerLimits {
+    /// Whether a new connection from `ip` may be handled, counting it as open
+    /// if so
+    fn try_connect(&mut self, ip: IpAddr, now: Instant) -> bool {
+        self.failures.retain(|_, failures| {
+            now.saturating_duration_since(failures.last) < AUTH_FAILURE_WINDOW
+        });
+        let blocked = self
+            .failures
+            .get(&ip)
+            .map_or(false, |failures| failures.count >= MAX_AUTH_FAILURES);
+        if blocked || self.connections >= MAX_CONNECTIONS {
+            false
+        } else {
+            self.connections += 1;
+            true
+        }
+    }
+
+    fn disconnect(&mut self) { self.connections = self.connections.saturating_sub(1); }
+
+    fn auth_failed(&mut self, ip: IpAddr, now: Instant) {
+        let failures = self.failures.entry(ip).or_insert(AuthFailures {
+            count: 0,
+            last: now,
+        });
+        failures.count += 1;
+        failures.last = now;
+    }
+
+    fn auth_succeeded(&mut self, ip: IpAddr) { self.failures.remove(&ip); }
+}
+
+/// Compare a token sent by a client to the configured one, taking the same
+/// time no matter how many leading bytes match
+fn token_matches(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Read a line of at most `MAX_LINE_LEN` bytes, giving up once `deadline`
+/// has passed. Returns None when the connection was closed.
+fn read_line(
+    reader: &mut BufReader<TcpStream>,
+    deadline: Option<Instant>,
+) -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let mut limited = reader.take(MAX_LINE_LEN);
+    loop {
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining == Duration::from_secs(0) {
+                return Err(io::ErrorKind::TimedOut.into());
+            }
+            limited
+                .get_ref()
+                .get_ref()
+                .set_read_timeout(Some(remaining))?;
+        }
+        let available = limited.fill_buf()?;
+        if available.is_empty() {
+            return if limited.limit() == 0 {
+                Err(io::Error::new(io::ErrorKind::InvalidData, "line too long"))
+            } else if line.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+            };
+        }
+        let (len, done) = match available.iter().position(|&b| b == b'\n') {
+            Some(end) => (end + 1, true),
+            None => (available.len(), false),
+        };
+        line.extend_from_slice(&available[..len]);
+        limited.consume(len);
+        if done {
+            return Ok(Some(String::from_utf8_lossy(&line).into_owned()));
+        }
+    }
+}
+
+pub struct RconListener {
+    requests: Receiver<RconRequest>,
+    audit_log: PathBuf,
+    running: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl RconListener {
+    pub fn start(settings: &RconSettings, data_dir: &Path) -> io::Result<Option<Self>> {
+        let address = match settings.address {
+            Some(address) if !settings.token.is_empty() => address,
+            Some(_) => {
+                warn!("Remote console is disabled because no token is set");
+                return Ok(None);
+            },
+            None => return Ok(None),
+        };
+
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        info!(?address, "Remote console is listening");
+
+        let (sender, requests) = channel::unbounded();
+        let running = Arc::new(AtomicBool::new(true));
+        let running2 = Arc::clone(&running);
+        let token = settings.token.clone();
+        let commands_per_minute = settings.commands_per_minute;
+        let limits = Arc::new(Mutex::new(PeerLimits::default()));
+        let handle = thread::spawn(move || {
+            while running2.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, peer)) => {
+                        if !limits
+                            .lock()
+                            .unwrap()
+                            .try_connect(peer.ip(), Instant::now())
+                        {
+                            debug!(?peer, "Refused remote console connection");
+                            continue;
+                        }
+                        let sender = sender.clone();
+                        let token = token.clone();
+                        let limits = Arc::clone(&limits);
+                        thread::spawn(move || {
+                            if let Err(e) = handle_connection(
+                                stream,
+                                peer,
+                                &token,
+                                commands_per_minute,
+                                sender,
+                                &limits,
+                            ) {
+                                debug!(?e, ?peer, "Remote console connection closed");
+                            }
+                            limits.lock().unwrap().disconnect();
+                        });
+                    },
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(100));
+                    },
+                    Err(e) => {
+                        error!(?e, "Remote console listener error");
+                        break;
+                    },
+                }
+            }
+        });
+
+        Ok(Some(Self {
+            requests,
+            audit_log: data_dir.join(AUDIT_LOG_FILENAME),
+            running,
+            handle: Some(handle),
+        }))
+    }
+
+    /// Commands received since the last call
+    pub fn requests(&self) -> impl Iterator<Item = RconRequest> + '_ { self.requests.try_iter() }
+
+    /// Record a remote command in the audit log
+    pub fn audit(&self, peer: SocketAddr, command: &str) {
+        info!(?peer, ?command, "Remote console command");
+        let line = format!("{} {} {}\n", chrono::Utc::now().to_rfc3339(), peer, command);
+        if let Err(e) = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.audit_log)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+        {
+            error!(?e, "Failed to write to the remote console audit log");
+        }
+    }
+}
+
+impl Drop for RconListener {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    peer: SocketAddr,
+    token: &str,
+    commands_per_minute: u32,
+    requests: Sender<RconRequest>,
+    limits: &Mutex<PeerLimits>,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    match read_line(&mut reader, Some(Instant::now() + AUTH_TIMEOUT)) {
+        Ok(Some(line)) if token_matches(line.trim_end(), token) => {
+            info!(?peer, "Remote console authenticated");
+            limits.lock().unwrap().auth_succeeded(peer.ip());
+            writeln!(writer, "OK")?;
+        },
+        _ => {
+            warn!(?peer, "Remote console authentication failed");
+            limits
+                .lock()
+                .unwrap()
+                .auth_failed(peer.ip(), Instant::now());
+            thread::sleep(AUTH_FAILURE_DELAY);
+            writeln!(writer, "ERR invalid token")?;
+            return Ok(());
+        },
+    }
+    // Both halves share the socket, so this lifts the timeout for reading too
+    writer.set_read_timeout(None)?;
+
+    let mut rate_limiter = RateLimiter::per_minute(commands_per_minute, Instant::now());
+    while let Some(line) = read_line(&mut reader, None)? {
+        let command = line.trim().trim_start_matches('/').to_string();
+        if command.is_empty() {
+            continue;
+        }
+        if !rate_limiter.try_take(Instant::now()) {
+            writeln!(writer, "ERR too many commands, slow down\n")?;
+            continue;
+        }
+
+        let (reply, response) = channel::bounded(1);
+        if requests
+            .send(RconRequest {
+                peer,
+                command,
+                reply,
+            })
+            .is_err()
+        {
+            // The server shut down
+            return Ok(());
+        }
+        match response.recv_timeout(REPLY_TIMEOUT) {
+            Ok(output) => {
+                for line in output.iter().flat_map(|message| message.lines()) {
+                    writeln!(writer, "{}", line)?;
+                }
+                writeln!(writer)?;
+            },
+            Err(_) => writeln!(writer, "ERR the server didn't respond\n")?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limiter_refills_over_time() {
+        let start = Instant::now();
+        let mut limiter = RateLimiter::per_minute(2, start);
+        assert!(limiter.try_take(start));
+        assert!(limiter.try_take(start));
+        assert!(!limiter.try_take(start));
+        assert!(!limiter.try_take(start + Duration::from_secs(10)));
+        assert!(limiter.try_take(start + Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn tokens_are_compared_exactly() {
+        assert!(token_matches("secret", "secret"));
+        assert!(!token_matches("secreT", "secret"));
+        assert!(!token_matches("secret2", "secret"));
+        assert!(!token_matches("", "secret"));
+    }
+
+    #[test]
+    fn connections_are_limited() {
+        let now = Instant::now();
+        let ip = IpAddr::from([127, 0, 0, 1]);
+        let mut limits = PeerLimits::default();
+        for _ in 0..MAX_CONNECTIONS {
+            assert!(limits.try_connect(ip, now));
+        }
+        assert!(!limits.try_connect(ip, now));
+        limits.disconnect();
+        assert!(limits.try_connect(ip, now));
+    }
+
+    #[test]
+    fn repeated_auth_failures_block_the_address() {
+        let now = Instant::now();
+        let ip = IpAddr::from([127, 0, 0, 1]);
+        let other = IpAddr::from([127, 0, 0, 2]);
+        let mut limits = PeerLimits::default();
+        for _ in 0..MAX_AUTH_FAILURES {
+            assert!(limits.try_connect(ip, now));
+            limits.auth_failed(ip, now);
+            limits.disconnect();
+        }
+        assert!(!limits.try_connect(ip, now));
+        assert!(limits.try_connect(other, now));
+        assert!(limits.try_connect(ip, now + AUTH_FAILURE_WINDOW));
+    }
+}
@@ -0,0 +1,238 @@
+//! Recurring actions configured in the server settings
+//!
+//! Every entry of `schedule` in the settings pairs a time in the style of a
+//! crontab line with an action, e.g. `(time: "0 */2 * * *", action:
+//! Announce("Remember to drink water!"))`. The five fields of the time are
+//! minute, hour, day of the month, month and day of the week (0 or 7 is
+//! Sunday), always in UTC. A field is either `*`, a number, a range like
+//! `1-5`, a step like `*/15` or `10-50/20`, or a list of those separated by
+//! commas. Like in cron, when both the day of the month and the day of the
+//! week are restricted (don't start with `*`), a day matching either is
+//! enough.
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tracing::warn;
+
+/// Lowest and highest value of each field of a time
+const FIELD_RANGES: [(u32, u32); 5] = [(0, 59), (0, 23), (1, 31), (1, 12), (0, 7)];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ScheduledAction {
+    /// Sends a message to all players
+    Announce(String),
+    /// Saves all characters right away
+    Save,
+    /// Shuts the server down after warning players for `countdown`, for the
+    /// process supervisor to start it again
+    Restart {
+        countdown: Duration,
+        message: String,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    pub time: String,
+    pub action: ScheduledAction,
+}
+
+/// The minutes a time matches, one bit per allowed value of each field
+#[derive(Clone, Debug, PartialEq)]
+pub struct CronTime {
+    fields: [u64; 5],
+    /// Whether both the day of the month and the day of the week were
+    /// restricted, in which case either of them matching is enough
+    either_day: bool,
+}
+
+impl CronTime {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let parts = s.split_whitespace().collect::<Vec<_>>();
+        if parts.len() != FIELD_RANGES.len() {
+            return Err(format!("expected 5 fields, found {}", parts.len()));
+        }
+        let mut fields = [0; 5];
+        for (i, (part, (min, max))) in parts.iter().zip(FIELD_RANGES.iter()).enumerate() {
+            fields[i] = parse_field(part, *min, *max)?;
+        }
+        // Sunday can be written as 0 or 7
+        if fields[4] & (1 << 7) != 0 {
+            fields[4] |= 1;
+        }
+        let either_day = !parts[2].starts_with('*') && !parts[4].starts_with('*');
+        Ok(Self { fields, either_day })
+    }
+
+    pub fn matches(&self, time: &DateTime<Utc>) -> bool {
+        let values = [
+            time.minute(),
+            time.hour(),
+            time.day(),
+            time.month(),
+            time.weekday().num_days_from_sunday(),
+        ];
+        let field_matches = |i: usize| self.fields[i] & (1 << values[i]) != 0;
+        let day_matches = if self.either_day {
+            field_matches(2) || field_matches(4)
+        } else {
+            field_matches(2) && field_matches(4)
+        };
+        field_matches(0) && field_matches(1) && field_matches(3) && day_matches
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let parse_value = |s: &str| match s.parse::<u32>() {
+        Ok(value) if value >= min && value <= max => Ok(value),
+        _ => Err(format!("{} is not a number from {} to {}", s, min, max)),
+    };
+    let mut bits = 0;
+    for item in field.split(',') {
+        let mut parts = item.splitn(2, '/');
+        let range = parts.next().unwrap_or_default();
+        let step = match parts.next() {
+            Some(step) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => step,
+                _ => return Err(format!("invalid step {}", step)),
+            },
+            None => 1,
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else {
+            let mut bounds = range.splitn(2, '-');
+            let start = parse_value(bounds.next().unwrap_or_default())?;
+            let end = match bounds.next() {
+                Some(end) => parse_value(end)?,
+                None => start,
+            };
+            if end < start {
+                return Err(format!("range {} is backwards", range));
+            }
+            (start, end)
+        };
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+/// Resource keeping track of the scheduled actions and the minute they were
+/// last checked for
+pub struct ActionSchedule {
+    entries: Vec<(CronTime, ScheduledAction)>,
+    last_minute: Option<i64>,
+}
+
+impl ActionSchedule {
+    /// Entries with an invalid time are skipped with a warning
+    pub fn new(entries: &[ScheduleEntry]) -> Self {
+        let entries = entries
+            .iter()
+            .filter_map(|entry| match CronTime::parse(&entry.time) {
+                Ok(time) => Some((time, entry.action.clone())),
+                Err(e) => {
+                    warn!(?e, ?entry, "Ignoring scheduled action with an invalid time");
+                    None
+                },
+            })
+            .collect();
+        Self {
+            entries,
+            last_minute: None,
+        }
+    }
+
+    /// Actions that are due at `now`, each at most once per minute
+    pub fn due(&mut self, now: DateTime<Utc>) -> Vec<ScheduledAction> {
+        let minute = now.timestamp().div_euclid(60);
+        let last_minute = self.last_minute.replace(minute);
+        // Nothing is run in the minute the server starts, so that a server that
+        // was restarted by the schedule doesn't restart again right away
+        if last_minute.map_or(true, |last_minute| last_minute == minute) {
+            return Vec::new();
+        }
+        self.entries
+            .iter()
+            .filter(|(time, _)| time.matches(&now))
+            .map(|(_, action)| action.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_cron_times() {
+        let time = CronTime::parse("*/15 4,16 * 1-3 1-5").unwrap();
+        // Wednesday
+        assert!(time.matches(&Utc.ymd(2020, 1, 15).and_hms(4, 30, 10)));
+        assert!(time.matches(&Utc.ymd(2020, 3, 2).and_hms(16, 45, 0)));
+        assert!(!time.matches(&Utc.ymd(2020, 1, 15).and_hms(4, 31, 0)));
+        assert!(!time.matches(&Utc.ymd(2020, 1, 15).and_hms(5, 0, 0)));
+        assert!(!time.matches(&Utc.ymd(2020, 4, 15).and_hms(4, 0, 0)));
+        // Sunday
+        assert!(!time.matches(&Utc.ymd(2020, 1, 19).and_hms(4, 0, 0)));
+        assert!(CronTime::parse("0 0 * * 7")
+            .unwrap()
+            .matches(&Utc.ymd(2020, 1, 19).and_hms(0, 0, 0)));
+
+        assert!(CronTime::parse("* * * *").is_err());
+        assert!(CronTime::parse("60 * * * *").is_err());
+        assert!(CronTime::parse("*/0 * * * *").is_err());
+        assert!(CronTime::parse("5-1 * * * *").is_err());
+        assert!(CronTime::parse("* * 0 * *").is_err());
+    }
+
+    #[test]
+    fn either_day_matches_when_both_are_restricted() {
+        // The 13th of each month, and every Friday
+        let time = CronTime::parse("0 0 13 * 5").unwrap();
+        // Monday the 13th
+        assert!(time.matches(&Utc.ymd(2020, 1, 13).and_hms(0, 0, 0)));
+        // Friday the 17th
+        assert!(time.matches(&Utc.ymd(2020, 1, 17).and_hms(0, 0, 0)));
+        // Thursday the 16th
+        assert!(!time.matches(&Utc.ymd(2020, 1, 16).and_hms(0, 0, 0)));
+
+        // With only one of them restricted, that one has to match
+        let time = CronTime::parse("0 0 */2 * 5").unwrap();
+        assert!(time.matches(&Utc.ymd(2020, 1, 17).and_hms(0, 0, 0)));
+        assert!(!time.matches(&Utc.ymd(2020, 1, 24).and_hms(0, 0, 0)));
+        assert!(!time.matches(&Utc.ymd(2020, 1, 13).and_hms(0, 0, 0)));
+    }
+
+    #[test]
+    fn actions_run_once_per_minute() {
+        let mut schedule = ActionSchedule::new(&[
+            ScheduleEntry {
+                time: "0 * * * *".to_string(),
+                action: ScheduledAction::Save,
+            },
+            ScheduleEntry {
+                time: "invalid".to_string(),
+                action: ScheduledAction::Save,
+            },
+        ]);
+        let start = Utc.ymd(2020, 1, 15).and_hms(12, 0, 5);
+        assert!(schedule.due(start).is_empty());
+        assert!(schedule
+            .due(Utc.ymd(2020, 1, 15).and_hms(12, 0, 50))
+            .is_empty());
+        assert!(schedule
+            .due(Utc.ymd(2020, 1, 15).and_hms(12, 1, 0))
+            .is_empty());
+        assert_eq!(schedule.due(Utc.ymd(2020, 1, 15).and_hms(13, 0, 1)), vec![
+            ScheduledAction::Save
+        ]);
+        assert!(schedule
+            .due(Utc.ymd(2020, 1, 15).and_hms(13, 0, 30))
+            .is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+//! Command scripts
+//!
+//! A script is a text file in the `scripts` directory of the data directory,
+//! e.g. `scripts/arena.txt`, with one chat command per line. Empty lines and
+//! lines starting with `#` are skipped, and the leading `/` of a command is
+//! optional. Scripts run at startup, at fixed intervals or through
+//! `/runscript`, every line as if the one running the script had typed it.
+//!
+//! Commands can refer to variables with `$name`, and `$$` stands for a `$`:
+//! * `$player`: alias of the player running the script
+//! * `$x`, `$y`, `$z`: position of that player, or the world spawn for scripts
+//!   that are run by the server
+//! * `$1` to `$9`: arguments given to `/runscript` after the name of the script
+
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, Instant},
+};
+use vek::*;
+
+/// Directory of the scripts, relative to the data directory
+pub const SCRIPTS_DIR: &str = "scripts";
+const SCRIPT_EXTENSION: &str = "txt";
+/// How many scripts may run inside each other. Scripts can't run other
+/// scripts, so this only catches ways around that check.
+pub const MAX_SCRIPT_DEPTH: u32 = 1;
+
+/// A script that the server runs over and over again
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledScript {
+    pub name: String,
+    /// Time between two runs of the script, the first one following the
+    /// server start after this time
+    pub interval: Duration,
+}
+
+pub struct Script {
+    /// Commands with the number of the line they are on
+    lines: Vec<(usize, String)>,
+}
+
+impl Script {
+    /// Loads the script with the given name from the data directory. Names may
+    /// only contain letters, digits, `_` and `-`.
+    pub fn load(data_dir: &Path, name: &str) -> io::Result<Self> {
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid script name",
+            ));
+        }
+        let path = data_dir
+            .join(SCRIPTS_DIR)
+            .join(format!("{}.{}", name, SCRIPT_EXTENSION));
+        fs::read_to_string(path).map(|source| Self::parse(&source))
+    }
+
+    fn parse(source: &str) -> Self {
+        let lines = source
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(i, line)| (i, line.trim_start_matches('/').to_string()))
+            .collect();
+        Self { lines }
+    }
+
+    /// The commands of the script with all variables replaced, or a
+    /// description of the first line that can't be run
+    pub fn commands(&self, vars: &Variables) -> Result<Vec<String>, String> {
+        self.lines
+            .iter()
+            .map(|(i, line)| {
+                let command = vars
+                    .substitute(line)
+                    .map_err(|name| format!("Line {}: unknown variable ${}", i, name))?;
+                // Scripts running each other could loop forever
+                if runs_script(&command) {
+                    return Err(format!("Line {}: scripts can't run other scripts", i));
+                }
+                Ok(command)
+            })
+            .collect()
+    }
+}
+
+/// Whether a command runs a script, directly or through `/sudo`
+fn runs_script(command: &str) -> bool {
+    let mut words = command.split_whitespace();
+    loop {
+        match words.next().map(|word| word.trim_start_matches('/')) {
+            Some("runscript") => return true,
+            // Skip the player that the command is run as
+            Some("sudo") => {
+                words.next();
+            },
+            _ => return false,
+        }
+    }
+}
+
+/// Values of the variables that the commands of a script can use
+pub struct Variables(HashMap<String, String>);
+
+impl Variables {
+    pub fn new(player: Option<&str>, pos: Vec3<f32>, args: &str) -> Self {
+        let mut vars = HashMap::new();
+        if let Some(player) = player {
+            vars.insert("player".to_string(), player.to_string());
+        }
+        vars.insert("x".to_string(), pos.x.to_string());
+        vars.insert("y".to_string(), pos.y.to_string());
+        vars.insert("z".to_string(), pos.z.to_string());
+        for (i, arg) in args.split_whitespace().take(9).enumerate() {
+            vars.insert((i + 1).to_string(), arg.to_string());
+        }
+        Self(vars)
+    }
+
+    /// Replaces the variables in a command with their values, or returns the
+    /// name of the first variable that has no value
+    fn substitute(&self, command: &str) -> Result<String, String> {
+        let mut result = String::with_capacity(command.len());
+        let mut chars = command.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                result.push(c);
+                continue;
+            }
+            if chars.peek() == Some(&'$') {
+                chars.next();
+                result.push('$');
+                continue;
+            }
+            let mut name = String::new();
+            while let Some(&c) = chars.peek() {
+                if !c.is_ascii_alphanumeric() && c != '_' {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            match self.0.get(&name) {
+                Some(value) => result.push_str(value),
+                None => return Err(name),
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Keeps track of when the scheduled scripts run next
+pub struct ScriptSchedule {
+    next_runs: Vec<(ScheduledScript, Instant)>,
+}
+
+impl ScriptSchedule {
+    pub fn new(scripts: &[ScheduledScript], now: Instant) -> Self {
+        Self {
+            next_runs: scripts
+                .iter()
+                .map(|script| (script.clone(), now + script.interval))
+                .collect(),
+        }
+    }
+
+    /// Names of the scripts that are due to run, whose next runs are scheduled
+    /// in turn
+    pub fn due(&mut self, now: Instant) -> Vec<String> {
+        self.next_runs
+            .iter_mut()
+            .filter(|(_, next_run)| *next_run <= now)
+            .map(|(script, next_run)| {
+                *next_run = now + script.interval;
+                script.name.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_substitute_variables() {
+        let script = Script::parse(
+            "# Arena setup\n\n/tp_pos $x $y $z\n  spawn wild bat $1\ntell $player Cost: $$5\n",
+        );
+        let vars = Variables::new(Some("Alice"), Vec3::new(1.0, 2.5, 3.0), "4 ignored");
+        assert_eq!(
+            script.commands(&vars),
+            Ok(vec![
+                "tp_pos 1 2.5 3".to_string(),
+                "spawn wild bat 4".to_string(),
+                "tell Alice Cost: $5".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn commands_report_failing_lines() {
+        let vars = Variables::new(None, Vec3::zero(), "");
+        assert_eq!(
+            Script::parse("say hi\nsay $player").commands(&vars),
+            Err("Line 2: unknown variable $player".to_string())
+        );
+        assert_eq!(
+            Script::parse("/runscript arena").commands(&vars),
+            Err("Line 1: scripts can't run other scripts".to_string())
+        );
+    }
+
+    #[test]
+    fn commands_cant_run_scripts_in_disguise() {
+        let vars = Variables::new(Some("Alice"), Vec3::zero(), "runscript");
+        for source in &[
+            "$1 arena",
+            "sudo $player runscript arena",
+            "sudo Alice /sudo Bob runscript arena",
+        ] {
+            assert_eq!(
+                Script::parse(source).commands(&vars),
+                Err("Line 1: scripts can't run other scripts".to_string())
+            );
+        }
+        assert!(Script::parse("sudo Alice say runscript")
+            .commands(&vars)
+            .is_ok());
+    }
+
+    #[test]
+    fn scheduled_scripts_repeat() {
+        let start = Instant::now();
+        let mut schedule = ScriptSchedule::new(
+            &[ScheduledScript {
+                name: "announce".to_string(),
+                interval: Duration::from_secs(60),
+            }],
+            start,
+        );
+        assert!(schedule.due(start).is_empty());
+        assert_eq!(schedule.due(start + Duration::from_secs(61)), vec![
+            "announce".to_string()
+        ]);
+        assert!(schedule.due(start + Duration::from_secs(100)).is_empty());
+        assert_eq!(schedule.due(start + Duration::from_secs(121)).len(), 1);
+    }
+}
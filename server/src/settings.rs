@@ -2,7 +2,18 @@ mod editable;
 
 pub use editable::EditableSetting;
 
+use crate::{
+    anticheat::{self, CheatResponse},
+    chat_filter::{ChannelFilterSettings, ChatChannel},
+    gc,
+    login_provider::DuplicateLogin,
+    rcon::RconSettings,
+    schedule::ScheduleEntry,
+    scripts::ScheduledScript,
+    spawning::SpawnSettings,
+};
 use authc::Uuid;
+use common::comp::ChatMsg;
 use hashbrown::{HashMap, HashSet};
 use portpicker::pick_unused_port;
 use serde::{Deserialize, Serialize};
@@ -23,6 +34,7 @@ const WHITELIST_FILENAME: &str = "whitelist.ron";
 const BANLIST_FILENAME: &str = "banlist.ron";
 const SERVER_DESCRIPTION_FILENAME: &str = "description.ron";
 const ADMINS_FILENAME: &str = "admins.ron";
+const SERVER_RULES_FILENAME: &str = "rules.ron";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
@@ -35,13 +47,84 @@ pub struct Settings {
     //pub pvp_enabled: bool,
     pub server_name: String,
     pub start_time: f64,
+    /// Length of a full in-game day, in real minutes
+    pub day_length: f64,
     /// When set to None, loads the default map file (if available); otherwise,
     /// uses the value of the file options to decide how to proceed.
     pub map_file: Option<FileOpts>,
     pub max_view_distance: Option<u32>,
     pub banned_words_files: Vec<PathBuf>,
+    /// Filters applied to the messages players send to each chat channel.
+    /// Channels without an entry aren't filtered.
+    pub chat_filters: HashMap<ChatChannel, ChannelFilterSettings>,
     pub max_player_group_size: u32,
     pub client_timeout: Duration,
+    /// Reject player movement updates that exceed speed limits, pass through
+    /// terrain or teleport, and rubber-band the player back instead
+    pub validate_player_movement: bool,
+    /// Suspicion score above which the server responds to cheating
+    pub cheat_response_threshold: f32,
+    /// How the server responds to players exceeding the suspicion threshold
+    pub cheat_response: CheatResponse,
+    /// Distance in blocks within which `Say` messages are heard
+    pub say_distance: f32,
+    /// Distance in blocks within which shouted messages are heard
+    pub shout_distance: f32,
+    /// Distance in blocks within which players hear each other in voice chat
+    pub voice_distance: f32,
+    /// Raise the level of naturally spawned NPCs the further away from the
+    /// world spawn they are
+    pub scale_npc_levels: bool,
+    /// Time without any input after which players are marked as idle
+    pub idle_timeout: Duration,
+    /// Disconnect idle players while the server is full
+    pub kick_idle_when_full: bool,
+    /// Time between warning idle players and disconnecting them
+    pub idle_kick_grace: Duration,
+    /// Keep generated chunks on disk and load them from there instead of
+    /// generating them again. NPCs don't spawn again in chunks loaded from the
+    /// cache. Delete the `chunk_cache` directory after changing the map file.
+    pub cache_chunks: bool,
+    /// Number of chunks generated at the same time. Further requested chunks
+    /// wait in a queue, so that worker threads stay free for other jobs.
+    pub chunk_gen_workers: usize,
+    /// Rate in Hz at which the game is simulated. Every tick of the simulation
+    /// advances it by the same amount of time.
+    pub simulation_tps: u32,
+    /// Rate in Hz at which clients are sent the state of the world. Rates
+    /// above the 30 Hz of the server loop have no effect.
+    pub sync_tps: u32,
+    /// Rate in Hz at which NPCs make decisions, at most `simulation_tps`
+    pub agent_tps: u32,
+    /// How long dropped items, stuck projectiles and other objects left lying
+    /// around are kept before they are deleted
+    pub object_lifetimes: gc::Lifetimes,
+    /// Time after which players authenticated by the auth server have to send
+    /// a new token, or None to keep sessions going for as long as players stay
+    /// connected
+    pub auth_session_lifetime: Option<Duration>,
+    /// Time players have to send a new token after being told that their
+    /// session expires, before they are disconnected
+    pub auth_session_grace: Duration,
+    /// What happens when an account that is already logged in logs in again
+    pub duplicate_login: DuplicateLogin,
+    /// Where, how often and which NPCs are spawned around players
+    pub spawning: SpawnSettings,
+    /// Remote admin console for running commands without joining the game
+    pub rcon: RconSettings,
+    /// Development mode: watch the item definitions and the ability map and
+    /// apply changes to them while the server runs, so that weapon abilities
+    /// can be tuned without a restart. Enabled by default in debug builds.
+    pub hot_reload_items: bool,
+    /// Names of the command scripts run when the server starts
+    pub startup_scripts: Vec<String>,
+    /// Command scripts run at fixed intervals, e.g. for announcements
+    pub scheduled_scripts: Vec<ScheduledScript>,
+    /// Announcements, saves and restarts at fixed times of the day or week
+    pub schedule: Vec<ScheduleEntry>,
+    /// Whether clients may keep the UI preferences of their characters, like
+    /// the hotbar layout, on the server
+    pub store_ui_preferences: bool,
 }
 
 impl Default for Settings {
@@ -54,11 +137,39 @@ impl Default for Settings {
             server_name: "Veloren Alpha".into(),
             max_players: 100,
             start_time: 9.0 * 3600.0,
+            day_length: 30.0,
             map_file: None,
             max_view_distance: Some(30),
             banned_words_files: Vec::new(),
+            chat_filters: HashMap::new(),
             max_player_group_size: 6,
             client_timeout: Duration::from_secs(40),
+            validate_player_movement: true,
+            cheat_response_threshold: anticheat::SUSPICION_WARN_THRESHOLD,
+            cheat_response: CheatResponse::Log,
+            say_distance: ChatMsg::SAY_DISTANCE,
+            shout_distance: ChatMsg::SHOUT_DISTANCE,
+            voice_distance: 64.0,
+            scale_npc_levels: true,
+            idle_timeout: Duration::from_secs(10 * 60),
+            kick_idle_when_full: true,
+            idle_kick_grace: Duration::from_secs(60),
+            cache_chunks: false,
+            chunk_gen_workers: 4,
+            simulation_tps: 30,
+            sync_tps: 30,
+            agent_tps: 30,
+            object_lifetimes: gc::Lifetimes::default(),
+            auth_session_lifetime: Some(Duration::from_secs(12 * 3600)),
+            auth_session_grace: Duration::from_secs(60),
+            duplicate_login: DuplicateLogin::KickOldUnlessInCombat,
+            spawning: SpawnSettings::default(),
+            rcon: RconSettings::default(),
+            hot_reload_items: cfg!(debug_assertions),
+            startup_scripts: Vec::new(),
+            scheduled_scripts: Vec::new(),
+            schedule: Vec::new(),
+            store_ui_preferences: true,
         }
     }
 }
@@ -138,6 +249,12 @@ impl Settings {
         }
     }
 
+    /// How much faster an in-game day passes than a real day
+    pub fn day_cycle_factor(&self) -> f64 {
+        // Don't allow days so short that the sky would flicker
+        24.0 * 60.0 / self.day_length.max(1.0)
+    }
+
     fn get_settings_path(path: &Path) -> PathBuf {
         let mut path = with_config_dir(path);
         path.push(SETTINGS_FILENAME);
@@ -176,12 +293,19 @@ impl Default for ServerDescription {
 #[serde(transparent)]
 pub struct Admins(HashSet<Uuid>);
 
+/// Rules players have to accept before they can play, or empty if there are
+/// none
+#[derive(Deserialize, Serialize, Default)]
+#[serde(transparent)]
+pub struct ServerRules(String);
+
 /// Combines all the editable settings into one struct that is stored in the ecs
 pub struct EditableSettings {
     pub whitelist: Whitelist,
     pub banlist: Banlist,
     pub server_description: ServerDescription,
     pub admins: Admins,
+    pub server_rules: ServerRules,
 }
 
 impl EditableSettings {
@@ -191,6 +315,7 @@ impl EditableSettings {
             banlist: Banlist::load(data_dir),
             server_description: ServerDescription::load(data_dir),
             admins: Admins::load(data_dir),
+            server_rules: ServerRules::load(data_dir),
         }
     }
 
@@ -198,6 +323,7 @@ impl EditableSettings {
         let load = Self::load(data_dir);
         Self {
             server_description: ServerDescription("Who needs friends anyway?".into()),
+            server_rules: ServerRules::default(),
             // TODO: Let the player choose if they want to use admin commands or not
             admins: Admins(
                 std::iter::once(
@@ -229,6 +355,10 @@ impl EditableSetting for Admins {
     const FILENAME: &'static str = ADMINS_FILENAME;
 }
 
+impl EditableSetting for ServerRules {
+    const FILENAME: &'static str = SERVER_RULES_FILENAME;
+}
+
 impl Deref for Whitelist {
     type Target = HashSet<Uuid>;
 
@@ -268,3 +398,13 @@ impl Deref for Admins {
 impl DerefMut for Admins {
     fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
 }
+
+impl Deref for ServerRules {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target { &self.0 }
+}
+
+impl DerefMut for ServerRules {
+    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.0 }
+}
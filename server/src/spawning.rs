@@ -0,0 +1,232 @@
+//! Populating the world around players with wildlife and hostile NPCs
+//!
+//! Chunks get a few NPCs when they are generated, but those never come back
+//! once they are killed. The spawner keeps the loaded parts of the world alive
+//! by spawning NPCs out of sight of players, picked by the biome they spawn in,
+//! whether it is night and how close the nearest site is. NPCs it spawned are
+//! deleted again once no player has been near them for a while, and the number
+//! of NPCs in each region is capped so that they don't pile up.
+
+use common::{
+    comp::{self, bird_medium, quadruped_low, quadruped_medium, quadruped_small, Body},
+    terrain::BiomeKind,
+};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use specs::Component;
+use specs_idvs::IdvStorage;
+use std::time::Duration;
+use vek::*;
+
+/// Server-only mark of an NPC created by the spawner, which is deleted when no
+/// player is around
+pub struct Spawned {
+    /// Time at which a player was last close enough to see the NPC
+    pub last_observed: f64,
+}
+
+impl Component for Spawned {
+    type Storage = IdvStorage<Self>;
+}
+
+/// Centers of the sites of the world, hostile NPCs don't spawn close to them
+#[derive(Default)]
+pub struct SitePositions(pub Vec<Vec2<f32>>);
+
+impl SitePositions {
+    pub fn distance_to_nearest(&self, wpos: Vec2<f32>) -> f32 {
+        self.0
+            .iter()
+            .map(|site| site.distance(wpos))
+            .fold(f32::INFINITY, f32::min)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpawnSettings {
+    pub enabled: bool,
+    /// Time between spawning rounds
+    pub interval: Duration,
+    /// Number of places around each player tried each round
+    pub attempts_per_player: usize,
+    /// NPCs are spawned at least this far from any player, so that they
+    /// don't appear in plain sight...
+    pub min_distance: f32,
+    /// ...and at most this far from the player they are spawned for
+    pub max_distance: f32,
+    /// Regions with this many NPCs, spawned or not, get no more of them
+    pub region_cap: usize,
+    /// Chance of a spawned NPC being hostile during the day...
+    pub hostile_chance_day: f32,
+    /// ...and at night
+    pub hostile_chance_night: f32,
+    /// Hostile NPCs don't spawn within this distance of a site
+    pub site_safe_radius: f32,
+    /// Spawned NPCs that no player came close to for this long are deleted
+    pub despawn_after: Duration,
+    /// Distance within which a player counts as observing a spawned NPC
+    pub observe_distance: f32,
+}
+
+impl Default for SpawnSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(2),
+            attempts_per_player: 1,
+            min_distance: 48.0,
+            max_distance: 128.0,
+            region_cap: 24,
+            hostile_chance_day: 0.25,
+            hostile_chance_night: 0.6,
+            site_safe_radius: 160.0,
+            despawn_after: Duration::from_secs(60),
+            observe_distance: 192.0,
+        }
+    }
+}
+
+/// Whether it is night at the given time of day
+pub fn is_night(time_of_day: f64) -> bool {
+    let hour = time_of_day.rem_euclid(24.0 * 3600.0) / 3600.0;
+    hour < 6.0 || hour >= 20.0
+}
+
+#[derive(Copy, Clone)]
+enum Species {
+    Small(quadruped_small::Species),
+    Medium(quadruped_medium::Species),
+    Low(quadruped_low::Species),
+    Bird(bird_medium::Species),
+}
+
+impl Species {
+    fn random_body(self, rng: &mut impl Rng) -> Body {
+        match self {
+            Species::Small(s) => Body::QuadrupedSmall(quadruped_small::Body::random_with(rng, &s)),
+            Species::Medium(s) => {
+                Body::QuadrupedMedium(quadruped_medium::Body::random_with(rng, &s))
+            },
+            Species::Low(s) => Body::QuadrupedLow(quadruped_low::Body::random_with(rng, &s)),
+            Species::Bird(s) => Body::BirdMedium(bird_medium::Body::random_with(rng, &s)),
+        }
+    }
+}
+
+/// Wildlife and hostile species living in a biome
+fn species_of(biome: BiomeKind) -> (&'static [Species], &'static [Species]) {
+    use bird_medium::Species::*;
+    use quadruped_low::Species::*;
+    use quadruped_medium::Species::*;
+    use quadruped_small::Species::*;
+    use Species::{Bird, Low, Medium, Small};
+
+    match biome {
+        BiomeKind::Grassland => (
+            &[
+                Small(Rabbit),
+                Small(Sheep),
+                Small(Pig),
+                Small(Fox),
+                Medium(Deer),
+                Medium(Mouflon),
+                Bird(Chicken),
+                Bird(Goose),
+            ],
+            &[Medium(Wolf), Small(Boar)],
+        ),
+        BiomeKind::Forest => (
+            &[
+                Small(Squirrel),
+                Small(Fox),
+                Small(Raccoon),
+                Small(Porcupine),
+                Small(Beaver),
+                Medium(Deer),
+                Medium(Hirdrasil),
+                Bird(Peacock),
+            ],
+            &[Medium(Wolf), Medium(Saber), Medium(Grolgar)],
+        ),
+        BiomeKind::Desert => (
+            &[
+                Small(Gecko),
+                Small(Jackalope),
+                Small(Holladon),
+                Medium(Catoblepas),
+            ],
+            &[Small(Hyena), Medium(Lion), Medium(Bonerattler), Low(Asp)],
+        ),
+        BiomeKind::Snowlands => (
+            &[
+                Small(Rabbit),
+                Medium(Mouflon),
+                Medium(Tuskram),
+                Bird(Snowyowl),
+            ],
+            &[Medium(Frostfang), Medium(Roshwalr)],
+        ),
+        BiomeKind::Swamp => (
+            &[Small(Frog), Small(Axolotl), Small(Turtle), Bird(Duck)],
+            &[Low(Crocodile), Low(Alligator), Low(Maneater)],
+        ),
+        BiomeKind::Mountain => (
+            &[
+                Small(Dodarock),
+                Medium(Mouflon),
+                Medium(Tuskram),
+                Bird(Eagle),
+            ],
+            &[Medium(Saber), Medium(Tarasque)],
+        ),
+        BiomeKind::Ocean | BiomeKind::Void => (&[], &[]),
+    }
+}
+
+/// Pick the body and alignment of an NPC to spawn in a biome, or None if
+/// nothing lives there
+pub fn choose_npc(
+    biome: BiomeKind,
+    night: bool,
+    near_site: bool,
+    settings: &SpawnSettings,
+    rng: &mut impl Rng,
+) -> Option<(Body, comp::Alignment)> {
+    let (wildlife, hostile) = species_of(biome);
+    let hostile_chance = if near_site {
+        0.0
+    } else if night {
+        settings.hostile_chance_night
+    } else {
+        settings.hostile_chance_day
+    };
+    let (species, alignment) = if rng.gen::<f32>() < hostile_chance && !hostile.is_empty() {
+        (hostile, comp::Alignment::Enemy)
+    } else {
+        (wildlife, comp::Alignment::Wild)
+    };
+    species
+        .choose(rng)
+        .map(|species| (species.random_body(rng), alignment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_hostile_npcs_near_sites() {
+        let settings = SpawnSettings {
+            hostile_chance_night: 1.0,
+            ..SpawnSettings::default()
+        };
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let (_, alignment) =
+                choose_npc(BiomeKind::Forest, true, true, &settings, &mut rng).unwrap();
+            assert_eq!(alignment, comp::Alignment::Wild);
+        }
+        assert!(choose_npc(BiomeKind::Ocean, false, false, &settings, &mut rng).is_none());
+    }
+}
@@ -1,25 +1,33 @@
 use crate::{
-    client::Client, persistence::PersistedComponents, sys::sentinel::DeletedEntities, SpawnPoint,
+    chat_filter::ChatFilter, client::Client, persistence::PersistedComponents,
+    settings::Settings, sys::sentinel::DeletedEntities, SpawnPoint,
 };
 use common::{
     character::CharacterId,
     comp,
     effect::Effect,
-    msg::{CharacterInfo, ClientInGame, PlayerListUpdate, ServerGeneral, ServerMsg},
-    state::State,
+    event::{EventBus, ServerEvent},
+    msg::{Capability, CharacterInfo, ClientInGame, PlayerListUpdate, ServerGeneral, ServerMsg},
+    region::{regions_in_vd, RegionMap},
+    state::{State, TerrainChanges},
     sync::{Uid, UidAllocator, WorldSyncExt},
+    terrain::TerrainGrid,
     util::Dir,
 };
 use specs::{
     saveload::MarkerAllocator, Builder, Entity as EcsEntity, EntityBuilder as EcsEntityBuilder,
     Join, WorldExt,
 };
+use std::sync::Arc;
 use tracing::warn;
 use vek::*;
 
 pub trait StateExt {
     /// Updates a component associated with the entity based on the `Effect`
     fn apply_effect(&mut self, entity: EcsEntity, effect: Effect);
+    /// Adds a buff to the entity, stacking it with existing ones of the same
+    /// kind
+    fn apply_buff(&self, entity: EcsEntity, buff: comp::BuffData, source: Option<Uid>);
     /// Build a non-player character
     fn create_npc(
         &mut self,
@@ -61,6 +69,9 @@ pub trait StateExt {
     fn send_chat(&self, msg: comp::UnresolvedChatMsg);
     fn notify_registered_clients(&self, msg: ServerGeneral);
     fn notify_in_game_clients(&self, msg: ServerGeneral);
+    /// Sets the text of the sign at the position, resending its chunk to the
+    /// clients that have it
+    fn set_sign(&self, pos: Vec3<i32>, text: Option<String>);
     /// Delete an entity, recording the deletion in [`DeletedEntities`]
     fn delete_entity_recorded(
         &mut self,
@@ -79,10 +90,26 @@ impl StateExt for State {
             },
             Effect::Xp(xp) => {
                 self.ecs()
-                    .write_storage::<comp::Stats>()
+                    .read_resource::<EventBus<ServerEvent>>()
+                    .emit_now(ServerEvent::ExpGain {
+                        entity,
+                        exp: xp,
+                        source: comp::ExpSource::Item,
+                    });
+            },
+            Effect::Energy(amount) => {
+                self.ecs()
+                    .write_storage::<comp::Energy>()
                     .get_mut(entity)
-                    .map(|stats| stats.exp.change_by(xp));
+                    .map(|energy| energy.change_by(amount, comp::EnergySource::Item));
             },
+            Effect::Buff(buff) => self.apply_buff(entity, buff, None),
+        }
+    }
+
+    fn apply_buff(&self, entity: EcsEntity, buff: comp::BuffData, source: Option<Uid>) {
+        if let Ok(entry) = self.ecs().write_storage::<comp::Buffs>().entry(entity) {
+            entry.or_insert_with(comp::Buffs::default).add(buff, source);
         }
     }
 
@@ -108,6 +135,7 @@ impl StateExt for State {
             .with(stats)
             .with(comp::Alignment::Npc)
             .with(comp::Energy::new(body.base_energy()))
+            .with(comp::Poise::new(body.base_poise()))
             .with(comp::Gravity(1.0))
             .with(comp::CharacterState::default())
             .with(loadout)
@@ -186,7 +214,14 @@ impl StateExt for State {
     fn initialize_character_data(&mut self, entity: EcsEntity, character_id: CharacterId) {
         let spawn_point = self.ecs().read_resource::<SpawnPoint>().0;
 
+        let poise = self
+            .ecs()
+            .read_storage::<comp::Body>()
+            .get(entity)
+            .map_or(0, |body| body.base_poise());
+
         self.write_component(entity, comp::Energy::new(1000));
+        self.write_component(entity, comp::Poise::new(poise));
         self.write_component(entity, comp::Controller::default());
         self.write_component(entity, comp::Pos(spawn_point));
         self.write_component(entity, comp::Vel(Vec3::zero()));
@@ -226,7 +261,7 @@ impl StateExt for State {
     }
 
     fn update_character_data(&mut self, entity: EcsEntity, components: PersistedComponents) {
-        let (body, stats, inventory, loadout) = components;
+        let (body, stats, inventory, loadout, hotbar, journal, waypoint) = components;
 
         if let Some(player_uid) = self.read_component_copied::<Uid>(entity) {
             // Notify clients of a player list update
@@ -246,6 +281,16 @@ impl StateExt for State {
             self.write_component(entity, stats);
             self.write_component(entity, inventory);
             self.write_component(entity, loadout);
+            // Only the owner gets to read their journal and hotbar
+            if let Some(client) = self.ecs().write_storage::<Client>().get_mut(entity) {
+                client.send_msg(ServerGeneral::AlchemyJournalUpdate(journal.clone()));
+                client.send_msg(ServerGeneral::AbilityHotbarUpdate(hotbar.clone()));
+            }
+            self.write_component(entity, hotbar);
+            self.write_component(entity, journal);
+            if let Some(waypoint) = waypoint {
+                self.write_component(entity, waypoint);
+            }
 
             self.write_component(
                 entity,
@@ -255,11 +300,11 @@ impl StateExt for State {
     }
 
     /// Send the chat message to the proper players. Say and region are limited
-    /// by location. Faction and group are limited by component.
+    /// by location. Faction and group are limited by component. Messages of
+    /// players are filtered first.
     fn send_chat(&self, msg: comp::UnresolvedChatMsg) {
         let ecs = self.ecs();
-        let is_within =
-            |target, a: &comp::Pos, b: &comp::Pos| a.0.distance_squared(b.0) < target * target;
+        let msg = ecs.read_resource::<ChatFilter>().filter(msg);
 
         let group_manager = ecs.read_resource::<comp::group::GroupManager>();
         let resolved_msg = msg.clone().map_group(|group_id| {
@@ -304,40 +349,18 @@ impl StateExt for State {
             },
 
             comp::ChatType::Say(uid) => {
-                let entity_opt =
-                    (*ecs.read_resource::<UidAllocator>()).retrieve_entity_internal(uid.0);
-                let positions = ecs.read_storage::<comp::Pos>();
-                if let Some(speaker_pos) = entity_opt.and_then(|e| positions.get(e)) {
-                    for (client, pos) in (&mut ecs.write_storage::<Client>(), &positions).join() {
-                        if is_within(comp::ChatMsg::SAY_DISTANCE, pos, speaker_pos) {
-                            client.send_msg(ServerGeneral::ChatMsg(resolved_msg.clone()));
-                        }
-                    }
-                }
+                let say_distance = ecs.read_resource::<Settings>().say_distance;
+                send_local_chat(ecs, uid, say_distance, &resolved_msg);
+            },
+            comp::ChatType::Shout(uid) => {
+                let shout_distance = ecs.read_resource::<Settings>().shout_distance;
+                send_local_chat(ecs, uid, shout_distance, &resolved_msg);
             },
             comp::ChatType::Region(uid) => {
-                let entity_opt =
-                    (*ecs.read_resource::<UidAllocator>()).retrieve_entity_internal(uid.0);
-                let positions = ecs.read_storage::<comp::Pos>();
-                if let Some(speaker_pos) = entity_opt.and_then(|e| positions.get(e)) {
-                    for (client, pos) in (&mut ecs.write_storage::<Client>(), &positions).join() {
-                        if is_within(comp::ChatMsg::REGION_DISTANCE, pos, speaker_pos) {
-                            client.send_msg(ServerGeneral::ChatMsg(resolved_msg.clone()));
-                        }
-                    }
-                }
+                send_local_chat(ecs, uid, comp::ChatMsg::REGION_DISTANCE, &resolved_msg);
             },
             comp::ChatType::Npc(uid, _r) => {
-                let entity_opt =
-                    (*ecs.read_resource::<UidAllocator>()).retrieve_entity_internal(uid.0);
-                let positions = ecs.read_storage::<comp::Pos>();
-                if let Some(speaker_pos) = entity_opt.and_then(|e| positions.get(e)) {
-                    for (client, pos) in (&mut ecs.write_storage::<Client>(), &positions).join() {
-                        if is_within(comp::ChatMsg::NPC_DISTANCE, pos, speaker_pos) {
-                            client.send_msg(ServerGeneral::ChatMsg(resolved_msg.clone()));
-                        }
-                    }
-                }
+                send_local_chat(ecs, uid, comp::ChatMsg::NPC_DISTANCE, &resolved_msg);
             },
 
             comp::ChatType::FactionMeta(s) | comp::ChatType::Faction(_, s) => {
@@ -389,6 +412,23 @@ impl StateExt for State {
         }
     }
 
+    fn set_sign(&self, pos: Vec3<i32>, text: Option<String>) {
+        let key = self.terrain().pos_key(pos);
+        let chunk = match self.terrain().get_key_arc(key).cloned() {
+            Some(chunk) => chunk,
+            None => return,
+        };
+        let mut chunk = (*chunk).clone();
+        chunk.meta_mut().set_sign(pos, text);
+        self.ecs()
+            .write_resource::<TerrainGrid>()
+            .insert(key, Arc::new(chunk));
+        self.ecs()
+            .write_resource::<TerrainChanges>()
+            .modified_chunks
+            .insert(key);
+    }
+
     fn delete_entity_recorded(
         &mut self,
         entity: EcsEntity,
@@ -449,3 +489,40 @@ impl StateExt for State {
         res
     }
 }
+
+/// Send a chat message to every client within `distance` blocks of the
+/// speaker, only looking at the regions around them
+fn send_local_chat(ecs: &specs::World, speaker: &Uid, distance: f32, msg: &comp::ChatMsg) {
+    let speaker_pos = match (*ecs.read_resource::<UidAllocator>())
+        .retrieve_entity_internal(speaker.0)
+        .and_then(|e| ecs.read_storage::<comp::Pos>().get(e).copied())
+    {
+        Some(pos) => pos,
+        None => return,
+    };
+
+    // Clients that don't know about shouting get shouts as regular local chat
+    let fallback = match msg.chat_type {
+        comp::ChatType::Shout(uid) => Some(comp::ChatType::Say(uid).chat_msg(msg.message.clone())),
+        _ => None,
+    };
+
+    let region_map = ecs.read_resource::<RegionMap>();
+    let positions = ecs.read_storage::<comp::Pos>();
+    let mut clients = ecs.write_storage::<Client>();
+    for key in regions_in_vd(speaker_pos.0, distance) {
+        if let Some(region) = region_map.get(key) {
+            for (_, client, pos) in (region.entities(), &mut clients, &positions).join() {
+                if pos.0.distance_squared(speaker_pos.0) < distance.powi(2) {
+                    let msg = match &fallback {
+                        Some(fallback) if !client.capabilities.contains(&Capability::ShoutChat) => {
+                            fallback
+                        },
+                        _ => msg,
+                    };
+                    client.send_msg(ServerGeneral::ChatMsg(msg.clone()));
+                }
+            }
+        }
+    }
+}
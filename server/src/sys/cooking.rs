@@ -0,0 +1,54 @@
+use crate::cooking::{Meal, FIRE_RANGE};
+use common::{
+    comp::Pos,
+    event::{EventBus, ServerEvent},
+    span,
+    state::DeltaTime,
+};
+use rand::Rng;
+use specs::{Entities, Join, Read, ReadStorage, System, WriteStorage};
+
+/// This system cooks the food of entities, and hands it out once it is done
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, DeltaTime>,
+        Read<'a, EventBus<ServerEvent>>,
+        ReadStorage<'a, Pos>,
+        WriteStorage<'a, Meal>,
+    );
+
+    fn run(&mut self, (entities, dt, server_bus, positions, mut meals): Self::SystemData) {
+        span!(_guard, "run", "cooking::Sys::run");
+        let mut server_emitter = server_bus.emitter();
+        let mut rng = rand::thread_rng();
+
+        let mut done = Vec::new();
+        for (entity, pos, meal) in (&entities, &positions, &mut meals).join() {
+            meal.time += dt.0;
+            // The fire going out counts as leaving it
+            let at_fire = positions.get(meal.fire).map_or(false, |fire_pos| {
+                fire_pos.0.distance_squared(pos.0) < FIRE_RANGE.powi(2)
+            });
+            if !at_fire {
+                meal.away += dt.0;
+            }
+
+            if meal.time >= meal.cooking.duration {
+                let burnt = rng.gen::<f32>() < meal.cooking.burn_probability(meal.away);
+                server_emitter.emit(ServerEvent::Cooked {
+                    entity,
+                    recipe: meal.recipe.clone(),
+                    burnt,
+                });
+                done.push(entity);
+            }
+        }
+
+        for entity in done {
+            meals.remove(entity);
+        }
+    }
+}
@@ -4,24 +4,105 @@ use super::{
 };
 use crate::{
     client::{Client, RegionSubscription},
+    metrics::NetworkRequestMetrics,
     Tick,
 };
 use common::{
-    comp::{ForceUpdate, Inventory, InventoryUpdate, Last, Ori, Player, Pos, Vel},
-    msg::ServerGeneral,
+    combat::CombatEvent,
+    comp::{ForceUpdate, Inventory, InventoryUpdate, Last, Ori, Player, Pos, Sound, Vel},
+    event::EventBus,
+    msg::{EcsCompPacket, ServerGeneral},
     outcome::Outcome,
     region::{Event as RegionEvent, RegionMap},
     span,
     state::TimeOfDay,
-    sync::{CompSyncPackage, Uid},
+    sync::{CompSyncPackage, CompUpdateKind, EntitySyncPackage, Uid},
     terrain::TerrainChunkSize,
     vol::RectVolSize,
+    weather::Weather,
 };
+use hashbrown::{HashMap, HashSet};
 use specs::{
     Entities, Entity as EcsEntity, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage,
 };
 use vek::*;
 
+/// How often the time of day is resent to clients, in ticks. Clients advance
+/// it themselves in between.
+const TIME_OF_DAY_SYNC_TICKS: u64 = 150;
+/// How often the weather is resent to clients to correct any drift between
+/// their simulation and the server's, in ticks
+const WEATHER_SYNC_TICKS: u64 = 300;
+/// How often changes to slowly synced components are sent to clients other
+/// than the one controlling the entity, in ticks
+const SLOW_SYNC_TICKS: u64 = 10;
+
+/// Whether changes to a component are held back for `SLOW_SYNC_TICKS`. These
+/// change often, but other clients don't need to know about every change.
+fn is_slow_synced(packet: &EcsCompPacket) -> bool {
+    matches!(
+        packet,
+        EcsCompPacket::Stats(_) | EcsCompPacket::Energy(_) | EcsCompPacket::Poise(_)
+    )
+}
+
+/// Modifications of slowly synced components that haven't been sent to every
+/// client yet, by entity uid
+#[derive(Default)]
+pub struct SlowSync {
+    pending: HashMap<u64, CompSyncPackage<EcsCompPacket>>,
+}
+
+impl SlowSync {
+    /// Move the modifications of slowly synced components out of `package`,
+    /// returning them. Entities that also gained or lost components keep all of
+    /// their updates, preceded by the ones held back earlier, so that they are
+    /// applied in order.
+    fn hold(
+        &mut self,
+        package: &mut CompSyncPackage<EcsCompPacket>,
+    ) -> CompSyncPackage<EcsCompPacket> {
+        let structural = package
+            .comp_updates
+            .iter()
+            .filter(|(_, update)| !matches!(update, CompUpdateKind::Modified(_)))
+            .map(|(uid, _)| *uid)
+            .collect::<HashSet<_>>();
+
+        let mut sent = CompSyncPackage::new();
+        for uid in &structural {
+            if let Some(pending) = self.pending.remove(uid) {
+                sent.merge(&pending);
+            }
+        }
+        let mut held = CompSyncPackage::new();
+        for (uid, update) in package.comp_updates.drain(..) {
+            let hold = matches!(&update, CompUpdateKind::Modified(packet) if is_slow_synced(packet))
+                && !structural.contains(&uid);
+            if hold {
+                self.pending
+                    .entry(uid)
+                    .or_insert_with(CompSyncPackage::new)
+                    .comp_updates
+                    .push((uid, update.clone()));
+                held.comp_updates.push((uid, update));
+            } else {
+                sent.comp_updates.push((uid, update));
+            }
+        }
+        *package = sent;
+        held
+    }
+}
+
+/// Sync packages for a single client, collected over all regions it is
+/// subscribed to so that they can be sent as one message each per tick
+#[derive(Default)]
+struct ClientBatch {
+    entity_sync: EntitySyncPackage,
+    comp_sync: CompSyncPackage<EcsCompPacket>,
+}
+
 /// This system will send physics updates to the client
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -30,7 +111,9 @@ impl<'a> System<'a> for Sys {
         Entities<'a>,
         Read<'a, Tick>,
         ReadExpect<'a, TimeOfDay>,
+        ReadExpect<'a, Weather>,
         ReadExpect<'a, RegionMap>,
+        ReadExpect<'a, NetworkRequestMetrics>,
         Write<'a, SysTimer<Self>>,
         ReadStorage<'a, Uid>,
         ReadStorage<'a, Pos>,
@@ -46,7 +129,10 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, ForceUpdate>,
         WriteStorage<'a, InventoryUpdate>,
         Write<'a, DeletedEntities>,
+        Write<'a, SlowSync>,
         Write<'a, Vec<Outcome>>,
+        Write<'a, Vec<CombatEvent>>,
+        Read<'a, EventBus<Sound>>,
         TrackedComps<'a>,
         ReadTrackers<'a>,
     );
@@ -57,7 +143,9 @@ impl<'a> System<'a> for Sys {
             entities,
             tick,
             time_of_day,
+            weather,
             region_map,
+            network_metrics,
             mut timer,
             uids,
             positions,
@@ -73,7 +161,10 @@ impl<'a> System<'a> for Sys {
             mut force_updates,
             mut inventory_updates,
             mut deleted_entities,
+            mut slow_sync,
             mut outcomes,
+            mut combat_events,
+            sound_bus,
             tracked_comps,
             trackers,
         ): Self::SystemData,
@@ -98,6 +189,15 @@ impl<'a> System<'a> for Sys {
         // 4. Iterate through entities in that region
         // 5. Inform clients of the component changes for that entity
         //     - Throttle update rate base on distance to each client
+        //     - Hold back changes to slowly synced components, except for the client
+        //       controlling the entity
+        // 6. Send each client the sync packages batched over all regions
+
+        // Sync packages to send to each client at the end of the tick
+        let mut batches: HashMap<EcsEntity, ClientBatch> = HashMap::new();
+
+        // Physics updates not sent because the client isn't keeping up
+        let mut dropped_physics = 0;
 
         // Sync physics
         // via iterating through regions
@@ -135,6 +235,7 @@ impl<'a> System<'a> for Sys {
                                     vel.copied(),
                                     ori.copied(),
                                 ));
+                            let mut prepared = None;
                             for (client, regions, client_entity, _) in &mut subscribers {
                                 if maybe_key
                                     .as_ref()
@@ -143,7 +244,9 @@ impl<'a> System<'a> for Sys {
                                     // Client doesn't need to know about itself
                                     && *client_entity != entity
                                 {
-                                    client.send_msg(create_msg.clone());
+                                    let msg =
+                                        prepared.get_or_insert_with(|| client.prepare(&create_msg));
+                                    client.send_prepared(msg);
                                 }
                             }
                         }
@@ -167,25 +270,44 @@ impl<'a> System<'a> for Sys {
 
             // Sync tracked components
             // Get deleted entities in this region from DeletedEntities
-            let (entity_sync_package, comp_sync_package) = trackers.create_sync_packages(
+            let (entity_sync_package, mut comp_sync_package) = trackers.create_sync_packages(
                 &tracked_comps,
                 region.entities(),
                 deleted_entities
                     .take_deleted_in_region(key)
                     .unwrap_or_default(),
             );
-            let entity_sync_msg = ServerGeneral::EntitySync(entity_sync_package);
-            let comp_sync_msg = ServerGeneral::CompSync(comp_sync_package);
-            subscribers.iter_mut().for_each(move |(client, _, _, _)| {
-                client.send_msg(entity_sync_msg.clone());
-                client.send_msg(comp_sync_msg.clone());
-            });
-
-            let mut send_msg = |msg: ServerGeneral,
-                                entity: EcsEntity,
-                                pos: Pos,
-                                force_update: Option<&ForceUpdate>,
-                                throttle: bool| {
+            let held = slow_sync.hold(&mut comp_sync_package);
+            if tick % SLOW_SYNC_TICKS == 0 {
+                for (_, uid) in (region.entities(), &uids).join() {
+                    if let Some(pending) = slow_sync.pending.remove(&u64::from(*uid)) {
+                        comp_sync_package.merge(&pending);
+                    }
+                }
+            }
+            for (_, _, client_entity, _) in &subscribers {
+                let batch = batches.entry(*client_entity).or_default();
+                batch.entity_sync.merge(&entity_sync_package);
+                batch.comp_sync.merge(&comp_sync_package);
+                // The client controlling an entity sees its changes right away
+                if let Some(&client_uid) = uids.get(*client_entity) {
+                    batch.comp_sync.comp_updates.extend(
+                        held.comp_updates
+                            .iter()
+                            .filter(|(uid, _)| *uid == u64::from(client_uid))
+                            .cloned(),
+                    );
+                }
+            }
+
+            let mut queue_physics = |comp_sync_package: &CompSyncPackage<EcsCompPacket>,
+                                     entity: EcsEntity,
+                                     pos: Pos,
+                                     force_update: Option<&ForceUpdate>,
+                                     throttle: bool| {
+                if comp_sync_package.is_empty() {
+                    return;
+                }
                 for (client, _, client_entity, client_pos) in &mut subscribers {
                     if if client_entity == &entity {
                         // Don't send client physics updates about itself unless force update is set
@@ -193,6 +315,10 @@ impl<'a> System<'a> for Sys {
                     } else if !throttle {
                         // Send the message if not throttling
                         true
+                    } else if client.should_drop_physics() {
+                        // The client isn't keeping up, a later update will supersede this one
+                        dropped_physics += 1;
+                        false
                     } else {
                         // Throttle update rate based on distance to client
                         let distance_sq = client_pos.0.distance_squared(pos.0);
@@ -212,12 +338,17 @@ impl<'a> System<'a> for Sys {
                             true // Closer than 100 blocks
                         }
                     } {
-                        client.send_msg(msg.clone());
+                        batches
+                            .entry(*client_entity)
+                            .or_default()
+                            .comp_sync
+                            .merge(comp_sync_package);
                     }
                 }
             };
 
-            // Sync physics components
+            // Sync physics components, reusing the same package for every entity
+            let mut comp_sync_package = CompSyncPackage::new();
             for (_, entity, &uid, &pos, maybe_vel, maybe_ori, force_update) in (
                 region.entities(),
                 &entities,
@@ -229,7 +360,7 @@ impl<'a> System<'a> for Sys {
             )
                 .join()
             {
-                let mut comp_sync_package = CompSyncPackage::new();
+                comp_sync_package.clear();
                 let mut throttle = true;
                 // TODO: An entity that stopped moving on a tick that it wasn't sent to the
                 // player will never have its position updated
@@ -286,16 +417,19 @@ impl<'a> System<'a> for Sys {
                     comp_sync_package.comp_removed::<Ori>(uid);
                 }
 
-                send_msg(
-                    ServerGeneral::CompSync(comp_sync_package),
-                    entity,
-                    pos,
-                    force_update,
-                    throttle,
-                );
+                queue_physics(&comp_sync_package, entity, pos, force_update, throttle);
             }
         }
 
+        network_metrics
+            .physics_updates_dropped
+            .inc_by(dropped_physics);
+
+        // What's left belongs to entities that were deleted
+        if tick % SLOW_SYNC_TICKS == 0 {
+            slow_sync.pending.clear();
+        }
+
         // Handle entity deletion in regions that don't exist in RegionMap
         // (theoretically none)
         for (region_key, deleted) in deleted_entities.take_remaining_deleted() {
@@ -316,6 +450,20 @@ impl<'a> System<'a> for Sys {
             }
         }
 
+        // Send the batched sync packages, only the latest state of each physics
+        // component is kept
+        for (client_entity, client) in (&entities, &mut clients).join() {
+            if let Some(mut batch) = batches.remove(&client_entity) {
+                if !batch.entity_sync.is_empty() {
+                    client.send_msg(ServerGeneral::EntitySync(batch.entity_sync));
+                }
+                batch.comp_sync.coalesce();
+                if !batch.comp_sync.is_empty() {
+                    client.send_msg(ServerGeneral::CompSync(batch.comp_sync));
+                }
+            }
+        }
+
         // TODO: Sync clients that don't have a position?
 
         // Sync inventories
@@ -343,8 +491,32 @@ impl<'a> System<'a> for Sys {
             if !outcomes.is_empty() {
                 client.send_msg(ServerGeneral::Outcomes(outcomes));
             }
+
+            // Combat events go to the same clients as outcomes
+            for event in combat_events
+                .iter()
+                .filter(|e| is_near(e.pos()).unwrap_or(true))
+            {
+                client.send_msg(ServerGeneral::CombatEvent(event.clone()));
+            }
         }
         outcomes.clear();
+        combat_events.clear();
+
+        // Sync sounds to the clients within hearing range
+        let sounds = sound_bus.recv_all().collect::<Vec<_>>();
+        if !sounds.is_empty() {
+            for (client, uid, pos) in (&mut clients, &uids, &positions).join() {
+                let audible = sounds
+                    .iter()
+                    .filter(|s| s.is_sent_to(*uid, pos.0))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !audible.is_empty() {
+                    client.send_msg(ServerGeneral::SoundEvents(audible));
+                }
+            }
+        }
 
         // Remove all force flags.
         force_updates.clear();
@@ -353,9 +525,22 @@ impl<'a> System<'a> for Sys {
         // Sync resources
         // TODO: doesn't really belong in this system (rename system or create another
         // system?)
-        let tof_msg = ServerGeneral::TimeOfDay(*time_of_day);
-        for client in (&mut clients).join() {
-            client.send_msg(tof_msg.clone());
+        if tick % TIME_OF_DAY_SYNC_TICKS == 0 {
+            let tof_msg = ServerGeneral::TimeOfDay(*time_of_day);
+            let mut prepared = None;
+            for client in (&mut clients).join() {
+                let msg = prepared.get_or_insert_with(|| client.prepare(&tof_msg));
+                client.send_prepared(msg);
+            }
+        }
+
+        if tick % WEATHER_SYNC_TICKS == 0 {
+            let weather_msg = ServerGeneral::WeatherUpdate((*weather).clone());
+            let mut prepared = None;
+            for client in (&mut clients).join() {
+                let msg = prepared.get_or_insert_with(|| client.prepare(&weather_msg));
+                client.send_prepared(msg);
+            }
         }
 
         timer.end();
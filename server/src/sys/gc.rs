@@ -0,0 +1,106 @@
+use super::SysScheduler;
+use crate::{
+    gc::{Litter, LitterKind},
+    settings::Settings,
+};
+use common::{
+    comp::{Item, PhysicsState, Player, Projectile, Stats},
+    event::{EventBus, ServerEvent},
+    span,
+    state::Time,
+    sync::UidAllocator,
+};
+use specs::{
+    saveload::MarkerAllocator, Entities, Join, Read, ReadStorage, System, Write, WriteStorage,
+};
+
+/// This system marks objects left lying around in the world as litter, and
+/// deletes litter that has been around for longer than its lifetime. It runs
+/// once a second, so litter can outlive its lifetime by up to a second.
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        Read<'a, Settings>,
+        Read<'a, UidAllocator>,
+        Read<'a, EventBus<ServerEvent>>,
+        Write<'a, SysScheduler<Self>>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Projectile>,
+        ReadStorage<'a, PhysicsState>,
+        ReadStorage<'a, Stats>,
+        ReadStorage<'a, Player>,
+        WriteStorage<'a, Litter>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            time,
+            settings,
+            uid_allocator,
+            server_bus,
+            mut scheduler,
+            items,
+            projectiles,
+            physics_states,
+            stats,
+            players,
+            mut litter,
+        ): Self::SystemData,
+    ) {
+        span!(_guard, "run", "gc::Sys::run");
+        if !scheduler.should_run() {
+            return;
+        }
+        let mut server_emitter = server_bus.emitter();
+
+        for entity in (&entities).join() {
+            let kind = if let Some(projectile) = projectiles.get(entity) {
+                if projectile.owner.map_or(false, |owner| {
+                    uid_allocator
+                        .retrieve_entity_internal(u64::from(owner))
+                        .is_none()
+                }) {
+                    Some(LitterKind::OrphanedProjectile)
+                } else if physics_states
+                    .get(entity)
+                    .map_or(false, |physics| physics.on_surface().is_some())
+                {
+                    Some(LitterKind::StuckProjectile)
+                } else {
+                    None
+                }
+            } else if items.get(entity).is_some() {
+                Some(LitterKind::DroppedItem)
+            } else if stats.get(entity).map_or(false, |stats| stats.is_dead)
+                && players.get(entity).is_none()
+            {
+                Some(LitterKind::Corpse)
+            } else {
+                None
+            };
+
+            match (kind, litter.get(entity)) {
+                (Some(kind), Some(mark)) if mark.kind == kind => {
+                    if mark.is_expired(time.0, &settings.object_lifetimes) {
+                        server_emitter.emit(ServerEvent::Expire(entity));
+                    }
+                },
+                (Some(kind), _) => {
+                    let _ = litter.insert(entity, Litter {
+                        kind,
+                        since: time.0,
+                    });
+                },
+                (None, Some(_)) => {
+                    litter.remove(entity);
+                },
+                (None, None) => {},
+            }
+        }
+    }
+}
@@ -1,28 +1,36 @@
 use super::SysTimer;
 use crate::{
     alias_validator::AliasValidator,
+    anticheat::{self, Suspicion},
     character_creator,
     client::Client,
-    login_provider::LoginProvider,
+    login_provider::{DuplicateLogin, LoginProvider},
     metrics::{NetworkRequestMetrics, PlayerMetrics},
-    persistence::character_loader::CharacterLoader,
+    persistence::{
+        character_loader::CharacterLoader, named_location::NamedLocations,
+        rules::RulesAcknowledgements,
+    },
     EditableSettings, Settings,
 };
 use common::{
     comp::{
-        Admin, CanBuild, ChatMode, ChatType, ControlEvent, Controller, ForceUpdate, Ori, Player,
-        Pos, Stats, UnresolvedChatMsg, Vel,
+        AbilityHotbar, Admin, Body, CanBuild, CharacterState, ChatMode, ChatType, ControlEvent,
+        Controller, ForceUpdate, InventoryManip, Ori, Player, Pos, Stats, UnresolvedChatMsg, Vel,
     },
     event::{EventBus, ServerEvent},
     msg::{
-        validate_chat_msg, CharacterInfo, ChatMsgValidationError, ClientGeneral, ClientInGame,
-        ClientRegister, DisconnectReason, PingMsg, PlayerInfo, PlayerListUpdate, RegisterError,
-        ServerGeneral, ServerRegisterAnswer, MAX_BYTES_CHAT_MSG,
+        validate_chat_msg, world_msg::MAX_OVERVIEW_TILES_PER_REQUEST, Capability, CharacterInfo,
+        ChatMsgValidationError, ClientGeneral, ClientInGame, ClientRegister, ClientType,
+        DisconnectReason, PingMsg, PlayerInfo, PlayerListUpdate, RegisterError, ServerGeneral,
+        ServerRegisterAnswer, WorldOverview, MAX_BYTES_CHAT_MSG, MAX_BYTES_UI_PREFERENCES,
     },
     span,
     state::{BlockChange, Time},
     sync::Uid,
-    terrain::{TerrainChunkSize, TerrainGrid},
+    terrain::{
+        sign::{validate_sign_text, MAX_SIGN_EDIT_RANGE},
+        PackedChunk, TerrainChunkSize, TerrainGrid,
+    },
     vol::{ReadVol, RectVolSize},
 };
 use futures_executor::block_on;
@@ -33,6 +41,11 @@ use specs::{
     Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage,
 };
 use tracing::{debug, error, info, trace, warn};
+use vek::Vec2;
+
+/// Seconds after being hurt by someone during which a player counts as being
+/// in combat
+const IN_COMBAT_TIME: f64 = 10.0;
 
 impl Sys {
     #[allow(clippy::too_many_arguments)]
@@ -44,9 +57,23 @@ impl Sys {
         player_metrics: &ReadExpect<'_, PlayerMetrics>,
         uids: &ReadStorage<'_, Uid>,
         chat_modes: &ReadStorage<'_, ChatMode>,
+        world_overview: &ReadExpect<'_, WorldOverview>,
+        time: f64,
+        login_provider: &mut WriteExpect<'_, LoginProvider>,
+        players: &WriteStorage<'_, Player>,
         msg: ClientGeneral,
     ) -> Result<(), crate::error::Error> {
         match msg {
+            ClientGeneral::RequestWorldMap(tiles) => {
+                if tiles.len() > MAX_OVERVIEW_TILES_PER_REQUEST {
+                    debug!(?entity, "Client requested too many world map tiles at once");
+                }
+                tiles
+                    .into_iter()
+                    .take(MAX_OVERVIEW_TILES_PER_REQUEST)
+                    .filter_map(|pos| world_overview.tile(pos))
+                    .for_each(|tile| client.send_msg(ServerGeneral::WorldMapData(tile)));
+            },
             ClientGeneral::ChatMsg(message) => {
                 if client.registered {
                     match validate_chat_msg(&message) {
@@ -67,6 +94,26 @@ impl Sys {
                     }
                 }
             },
+            ClientGeneral::RefreshSession(token) => {
+                // The new token has to belong to the same account the player
+                // registered with
+                let result = login_provider.query(&token).and_then(|(_, uuid)| {
+                    if players.get(entity).map_or(false, |p| p.uuid() == uuid) {
+                        Ok(())
+                    } else {
+                        Err(RegisterError::AuthError(
+                            "Token belongs to a different account".to_string(),
+                        ))
+                    }
+                });
+                if result.is_ok() {
+                    client.session_start = time;
+                    client.session_expiring_since = None;
+                } else {
+                    debug!(?entity, ?result, "Client failed to refresh its session");
+                }
+                client.send_msg(ServerGeneral::SessionRefreshed(result));
+            },
             ClientGeneral::Disconnect => {
                 client.send_msg(ServerGeneral::Disconnect(DisconnectReason::Requested));
             },
@@ -90,8 +137,13 @@ impl Sys {
         client: &mut Client,
         terrain: &ReadExpect<'_, TerrainGrid>,
         network_metrics: &ReadExpect<'_, NetworkRequestMetrics>,
+        player_metrics: &ReadExpect<'_, PlayerMetrics>,
+        time: f64,
         can_build: &ReadStorage<'_, CanBuild>,
-        force_updates: &ReadStorage<'_, ForceUpdate>,
+        character_states: &ReadStorage<'_, CharacterState>,
+        bodies: &ReadStorage<'_, Body>,
+        force_updates: &mut WriteStorage<'_, ForceUpdate>,
+        suspicions: &mut WriteStorage<'_, Suspicion>,
         stats: &mut WriteStorage<'_, Stats>,
         block_changes: &mut Write<'_, BlockChange>,
         positions: &mut WriteStorage<'_, Pos>,
@@ -99,6 +151,7 @@ impl Sys {
         orientations: &mut WriteStorage<'_, Ori>,
         players: &mut WriteStorage<'_, Player>,
         controllers: &mut WriteStorage<'_, Controller>,
+        ability_hotbars: &mut WriteStorage<'_, AbilityHotbar>,
         settings: &Read<'_, Settings>,
         msg: ClientGeneral,
     ) -> Result<(), crate::error::Error> {
@@ -153,6 +206,30 @@ impl Sys {
                             //Todo: comment why return!
                             return Ok(());
                         }
+                    } else {
+                        // Some events, such as managing the group, remain possible while dead
+                        // or stunned. The others are dropped without suspicion, since clients
+                        // send them before they learn about their death or the hit.
+                        let needs_alive = matches!(
+                            event,
+                            ControlEvent::Mount(_)
+                                | ControlEvent::InventoryManip(InventoryManip::Pickup(_))
+                                | ControlEvent::InventoryManip(InventoryManip::Collect(_))
+                        );
+                        if needs_alive
+                            && !anticheat::can_act(stats.get(entity), character_states.get(entity))
+                        {
+                            return Ok(());
+                        }
+                        if let Err(violation) =
+                            check_action(entity, time, suspicions, players, settings)
+                        {
+                            player_metrics
+                                .action_rejected
+                                .with_label_values(&[violation.as_str()])
+                                .inc();
+                            return Ok(());
+                        }
                     }
                     if let Some(controller) = controllers.get_mut(entity) {
                         controller.events.push(event);
@@ -161,19 +238,84 @@ impl Sys {
             },
             ClientGeneral::ControlAction(event) => {
                 if let Some(ClientInGame::Character) = client.in_game {
+                    // Dropped without suspicion, see the control events above
+                    if !anticheat::can_act(stats.get(entity), character_states.get(entity)) {
+                        return Ok(());
+                    }
+                    if let Err(violation) =
+                        check_action(entity, time, suspicions, players, settings)
+                    {
+                        player_metrics
+                            .action_rejected
+                            .with_label_values(&[violation.as_str()])
+                            .inc();
+                        return Ok(());
+                    }
                     if let Some(controller) = controllers.get_mut(entity) {
                         controller.actions.push(event);
                     }
                 }
             },
-            ClientGeneral::PlayerPhysics { pos, vel, ori } => {
+            ClientGeneral::PlayerPhysics { pos, vel, ori, seq } => {
                 if let Some(ClientInGame::Character) = client.in_game {
                     if force_updates.get(entity).is_none()
                         && stats.get(entity).map_or(true, |s| !s.is_dead)
                     {
-                        let _ = positions.insert(entity, pos);
-                        let _ = velocities.insert(entity, vel);
-                        let _ = orientations.insert(entity, ori);
+                        let suspicion = match suspicions.entry(entity) {
+                            Ok(entry) => entry.or_insert_with(Suspicion::default),
+                            Err(_) => return Ok(()),
+                        };
+                        let verdict = match (
+                            settings.validate_player_movement,
+                            positions.get(entity),
+                            suspicion.last_accepted_physics,
+                        ) {
+                            (true, Some(old_pos), Some(last_accepted)) => {
+                                anticheat::validate_movement(
+                                    old_pos,
+                                    &pos,
+                                    (time - last_accepted) as f32,
+                                    suspicion.impulse_speed_at(time),
+                                    character_states.get(entity),
+                                    bodies.get(entity),
+                                    terrain,
+                                )
+                            },
+                            _ => Ok(()),
+                        };
+
+                        client.physics_ack = Some(seq);
+                        match verdict {
+                            Ok(()) => {
+                                suspicion.last_accepted_physics = Some(time);
+                                let _ = positions.insert(entity, pos);
+                                let _ = velocities.insert(entity, vel);
+                                let _ = orientations.insert(entity, ori);
+                            },
+                            Err(violation) => {
+                                player_metrics
+                                    .movement_rejected
+                                    .with_label_values(&[violation.as_str()])
+                                    .inc();
+                                suspicion.movement_rejections += 1;
+                                if let Some(score) = suspicion.report(
+                                    violation.severity(),
+                                    time,
+                                    settings.cheat_response_threshold,
+                                    settings.cheat_response,
+                                ) {
+                                    let alias = players.get(entity).map(|p| p.alias.clone());
+                                    warn!(
+                                        ?alias,
+                                        ?violation,
+                                        ?score,
+                                        "Rejected movement from suspicious player"
+                                    );
+                                }
+                                // Rubber-band the client back to the last accepted position
+                                let _ = force_updates.insert(entity, ForceUpdate);
+                            },
+                        }
                     }
                 }
             },
@@ -187,6 +329,17 @@ impl Sys {
                     block_changes.try_set(pos, block);
                 }
             },
+            ClientGeneral::EditSign { pos, text } => {
+                let in_range = positions.get(entity).map_or(false, |p| {
+                    p.0.distance_squared(pos.map(|e| e as f32 + 0.5)) < MAX_SIGN_EDIT_RANGE.powi(2)
+                });
+                if can_build.get(entity).is_some() && in_range {
+                    match validate_sign_text(&text) {
+                        Ok(()) => server_emitter.emit(ServerEvent::EditSign { pos, text }),
+                        Err(e) => debug!(?e, ?entity, "Rejected sign text"),
+                    }
+                }
+            },
             ClientGeneral::TerrainChunkRequest { key } => {
                 let in_vd = if let (Some(view_distance), Some(pos)) = (
                     players.get(entity).and_then(|p| p.view_distance),
@@ -205,7 +358,7 @@ impl Sys {
                             network_metrics.chunks_served_from_memory.inc();
                             client.send_msg(ServerGeneral::TerrainChunkUpdate {
                                 key,
-                                chunk: Ok(Box::new(chunk.clone())),
+                                chunk: Ok(PackedChunk::pack_with(chunk, client.chunk_encoding)),
                             })
                         },
                         None => {
@@ -217,20 +370,72 @@ impl Sys {
                     network_metrics.chunks_request_dropped.inc();
                 }
             },
+            // Skill requests are checked before being applied, and the resulting skill set
+            // is always sent back so the client can't get out of sync
             ClientGeneral::UnlockSkill(skill) => {
-                stats
-                    .get_mut(entity)
-                    .map(|s| s.skill_set.unlock_skill(skill));
+                if let Some(stats) = stats.get_mut(entity) {
+                    match stats.skill_set.check_unlock_skill(skill) {
+                        Ok(_) => {
+                            stats.skill_set.unlock_skill(skill);
+                            server_emitter.emit(ServerEvent::PersistCharacter(entity));
+                        },
+                        Err(e) => debug!(?e, ?skill, ?entity, "Rejected skill unlock"),
+                    }
+                    client.send_msg(ServerGeneral::SkillSetUpdate(stats.skill_set.clone()));
+                }
             },
             ClientGeneral::RefundSkill(skill) => {
-                stats
-                    .get_mut(entity)
-                    .map(|s| s.skill_set.refund_skill(skill));
+                if let Some(stats) = stats.get_mut(entity) {
+                    match stats.skill_set.check_refund_skill(skill) {
+                        Ok(_) => {
+                            stats.skill_set.refund_skill(skill);
+                            server_emitter.emit(ServerEvent::PersistCharacter(entity));
+                        },
+                        Err(e) => debug!(?e, ?skill, ?entity, "Rejected skill refund"),
+                    }
+                    client.send_msg(ServerGeneral::SkillSetUpdate(stats.skill_set.clone()));
+                }
             },
             ClientGeneral::UnlockSkillGroup(skill_group_type) => {
-                stats
-                    .get_mut(entity)
-                    .map(|s| s.skill_set.unlock_skill_group(skill_group_type));
+                if let Some(stats) = stats.get_mut(entity) {
+                    match stats.skill_set.check_purchase_skill_group(skill_group_type) {
+                        Ok(_) => {
+                            stats.skill_set.purchase_skill_group(skill_group_type);
+                            server_emitter.emit(ServerEvent::PersistCharacter(entity));
+                        },
+                        Err(e) => debug!(
+                            ?e,
+                            ?skill_group_type,
+                            ?entity,
+                            "Rejected skill group unlock"
+                        ),
+                    }
+                    client.send_msg(ServerGeneral::SkillSetUpdate(stats.skill_set.clone()));
+                }
+            },
+            ClientGeneral::SetAbilitySlot { slot, ability } => {
+                if let Some(hotbar) = ability_hotbars.get_mut(entity) {
+                    if hotbar.assign(slot, ability) {
+                        client.send_msg(ServerGeneral::AbilityHotbarUpdate(hotbar.clone()));
+                    }
+                }
+            },
+            ClientGeneral::RequestUiPreferences if settings.store_ui_preferences => {
+                server_emitter.emit(ServerEvent::LoadUiPreferences(entity));
+            },
+            ClientGeneral::SetUiPreferences(data) if settings.store_ui_preferences => {
+                if data.len() <= MAX_BYTES_UI_PREFERENCES {
+                    server_emitter.emit(ServerEvent::StoreUiPreferences { entity, data });
+                } else {
+                    warn!(
+                        ?entity,
+                        len = data.len(),
+                        "UI preferences are too large, ignoring"
+                    );
+                }
+            },
+            ClientGeneral::RequestUiPreferences | ClientGeneral::SetUiPreferences(_) => {
+                debug!(?entity, "storing UI preferences is disabled, ignoring msg");
             },
             _ => unreachable!("not a client_in_game msg"),
         }
@@ -248,9 +453,25 @@ impl Sys {
         players: &mut WriteStorage<'_, Player>,
         editable_settings: &ReadExpect<'_, EditableSettings>,
         alias_validator: &ReadExpect<'_, AliasValidator>,
+        rules_acknowledgements: &mut WriteExpect<'_, RulesAcknowledgements>,
         msg: ClientGeneral,
     ) -> Result<(), crate::error::Error> {
         match msg {
+            ClientGeneral::Character(_) | ClientGeneral::Spectate if client.rules_pending => {
+                debug!("dropped msg from client that has yet to accept the rules");
+                client.send_msg(ServerGeneral::CharacterDataLoadError(String::from(
+                    "The server rules have to be accepted first",
+                )));
+            },
+            ClientGeneral::AcceptRules => {
+                if let (true, Some(player)) = (client.rules_pending, players.get(entity)) {
+                    rules_acknowledgements.accept(
+                        player.uuid().to_string(),
+                        (*editable_settings.server_rules).clone(),
+                    );
+                    client.rules_pending = false;
+                }
+            },
             // Request spectator state
             ClientGeneral::Spectate if client.registered => {
                 client.in_game = Some(ClientInGame::Spectator)
@@ -307,7 +528,8 @@ impl Sys {
             },
             ClientGeneral::RequestCharacterList => {
                 if let Some(player) = players.get(entity) {
-                    character_loader.load_character_list(entity, player.uuid().to_string())
+                    character_loader.load_character_list(entity, player.uuid().to_string());
+                    character_loader.load_account_unlocks(entity, player.uuid().to_string());
                 }
             },
             ClientGeneral::CreateCharacter { alias, tool, body } => {
@@ -359,9 +581,16 @@ impl Sys {
         admins: &mut WriteStorage<'_, Admin>,
         players: &mut WriteStorage<'_, Player>,
         editable_settings: &ReadExpect<'_, EditableSettings>,
+        time: f64,
+        entities: &Entities<'_>,
+        stats: &WriteStorage<'_, Stats>,
+        settings: &Read<'_, Settings>,
+        takeovers: &mut Vec<specs::Entity>,
+        rules_acknowledgements: &WriteExpect<'_, RulesAcknowledgements>,
+        named_locations: &ReadExpect<'_, NamedLocations>,
         msg: ClientRegister,
     ) -> Result<(), crate::error::Error> {
-        let (username, uuid) = match login_provider.try_login(
+        let (username, uuid) = match login_provider.authenticate(
             &msg.token_or_username,
             &*editable_settings.admins,
             &*editable_settings.whitelist,
@@ -376,6 +605,42 @@ impl Sys {
             Ok((username, uuid)) => (username, uuid),
         };
 
+        if login_provider.is_logged_in(uuid) {
+            let old = (entities, &*players)
+                .join()
+                .find(|(_, player)| player.uuid() == uuid)
+                .map(|(old, _)| old);
+            match (old, settings.duplicate_login) {
+                (Some(_), DuplicateLogin::RejectNew) => {
+                    client
+                        .register_stream
+                        .send(ServerRegisterAnswer::Err(RegisterError::AlreadyLoggedIn))?;
+                    return Ok(());
+                },
+                (Some(old), DuplicateLogin::KickOldUnlessInCombat)
+                    if stats.get(old).map_or(false, is_in_combat) =>
+                {
+                    client
+                        .register_stream
+                        .send(ServerRegisterAnswer::Err(RegisterError::InCombat))?;
+                    return Ok(());
+                },
+                (Some(old), _) => {
+                    // The new session registers once the old one is gone, and
+                    // with it its character has been saved
+                    if !takeovers.contains(&old) {
+                        takeovers.push(old);
+                    }
+                    client.pending_register = Some(msg);
+                    return Ok(());
+                },
+                (None, _) => {
+                    warn!(?uuid, "Account was logged in without a player, taking over");
+                    login_provider.logout(uuid);
+                },
+            }
+        }
+
         const INITIAL_VD: Option<u32> = Some(5); //will be changed after login
         let player = Player::new(username, None, INITIAL_VD, uuid);
         let is_admin = editable_settings.admins.contains(&uuid);
@@ -389,6 +654,13 @@ impl Sys {
         }
 
         if !client.registered && client.in_game.is_none() {
+            if let Err(err) = login_provider.login(uuid, player.alias.clone()) {
+                client
+                    .register_stream
+                    .send(ServerRegisterAnswer::Err(err))?;
+                return Ok(());
+            }
+
             // Add Player component to this client
             let _ = players.insert(entity, player);
             player_metrics.players_connected.inc();
@@ -401,12 +673,25 @@ impl Sys {
 
             // Tell the client its request was successful.
             client.registered = true;
+            client.session_start = time;
+            client.chunk_encoding = msg.chunk_encoding;
+            client.capabilities = Capability::parse_all(&msg.capabilities);
             client.register_stream.send(ServerRegisterAnswer::Ok(()))?;
 
+            // Players have to accept the rules before choosing a character
+            let rules = &*editable_settings.server_rules;
+            if client.client_type == ClientType::Game
+                && rules_acknowledgements.must_accept(&uuid.to_string(), rules)
+            {
+                client.rules_pending = true;
+                client.send_msg(ServerGeneral::ServerRules(rules.clone()));
+            }
+
             // Send initial player list
             client.send_msg(ServerGeneral::PlayerListUpdate(PlayerListUpdate::Init(
                 player_list.clone(),
             )));
+            client.send_msg(ServerGeneral::NamedLocations(named_locations.to_msg()));
 
             // Add to list to notify all clients of the new player
             new_players.push(entity);
@@ -429,9 +714,13 @@ impl Sys {
         terrain: &ReadExpect<'_, TerrainGrid>,
         network_metrics: &ReadExpect<'_, NetworkRequestMetrics>,
         player_metrics: &ReadExpect<'_, PlayerMetrics>,
+        time: f64,
         uids: &ReadStorage<'_, Uid>,
         can_build: &ReadStorage<'_, CanBuild>,
-        force_updates: &ReadStorage<'_, ForceUpdate>,
+        character_states: &ReadStorage<'_, CharacterState>,
+        bodies: &ReadStorage<'_, Body>,
+        force_updates: &mut WriteStorage<'_, ForceUpdate>,
+        suspicions: &mut WriteStorage<'_, Suspicion>,
         stats: &mut WriteStorage<'_, Stats>,
         chat_modes: &ReadStorage<'_, ChatMode>,
         login_provider: &mut WriteExpect<'_, LoginProvider>,
@@ -442,9 +731,15 @@ impl Sys {
         orientations: &mut WriteStorage<'_, Ori>,
         players: &mut WriteStorage<'_, Player>,
         controllers: &mut WriteStorage<'_, Controller>,
+        ability_hotbars: &mut WriteStorage<'_, AbilityHotbar>,
         settings: &Read<'_, Settings>,
         editable_settings: &ReadExpect<'_, EditableSettings>,
         alias_validator: &ReadExpect<'_, AliasValidator>,
+        world_overview: &ReadExpect<'_, WorldOverview>,
+        entities: &Entities<'_>,
+        takeovers: &mut Vec<specs::Entity>,
+        rules_acknowledgements: &mut WriteExpect<'_, RulesAcknowledgements>,
+        named_locations: &ReadExpect<'_, NamedLocations>,
     ) -> Result<(), crate::error::Error> {
         let (mut b1, mut b2, mut b3, mut b4, mut b5) = (
             client.network_error,
@@ -473,6 +768,27 @@ impl Sys {
                 msg = q5.fuse() => (None, None, None, None,Some(msg)),
             );
             *cnt += 1;
+            let msg_name = match (&m1, &m2, &m3) {
+                (Some(Ok(msg)), _, _) | (_, Some(Ok(msg)), _) | (_, _, Some(Ok(msg))) => {
+                    Some(msg.name())
+                },
+                _ if matches!(m4, Some(Ok(_))) => Some("Ping"),
+                _ if matches!(m5, Some(Ok(_))) => Some("Register"),
+                _ => None,
+            };
+            if let Some(name) = msg_name {
+                network_metrics.client_msgs.with_label_values(&[name]).inc();
+            }
+            // Clients keep sending some messages on their own, so only count the ones
+            // caused by the player as activity
+            let is_player_input = match (&m1, &m2) {
+                (Some(Ok(ClientGeneral::ChatMsg(_))), _) => true,
+                (_, Some(Ok(msg))) => is_player_input(msg, controllers.get(entity)),
+                _ => matches!(m3, Some(Ok(_))) || matches!(m5, Some(Ok(_))),
+            };
+            if is_player_input {
+                client.last_input = time;
+            }
             if let Some(msg) = m1 {
                 client.network_error |= b1;
                 Self::handle_client_msg(
@@ -483,6 +799,10 @@ impl Sys {
                     player_metrics,
                     uids,
                     chat_modes,
+                    world_overview,
+                    time,
+                    login_provider,
+                    players,
                     msg?,
                 )?;
             }
@@ -494,8 +814,13 @@ impl Sys {
                     client,
                     terrain,
                     network_metrics,
+                    player_metrics,
+                    time,
                     can_build,
+                    character_states,
+                    bodies,
                     force_updates,
+                    suspicions,
                     stats,
                     block_changes,
                     positions,
@@ -503,6 +828,7 @@ impl Sys {
                     orientations,
                     players,
                     controllers,
+                    ability_hotbars,
                     settings,
                     msg?,
                 )?;
@@ -519,6 +845,7 @@ impl Sys {
                     players,
                     editable_settings,
                     alias_validator,
+                    rules_acknowledgements,
                     msg?,
                 )?;
             }
@@ -538,6 +865,13 @@ impl Sys {
                     admins,
                     players,
                     editable_settings,
+                    time,
+                    entities,
+                    stats,
+                    settings,
+                    takeovers,
+                    rules_acknowledgements,
+                    named_locations,
                     msg?,
                 )?;
             }
@@ -545,6 +879,66 @@ impl Sys {
     }
 }
 
+/// Whether the entity was hurt by someone within the last few seconds
+pub(crate) fn is_in_combat(stats: &Stats) -> bool {
+    let (since, change) = stats.health.last_change;
+    since < IN_COMBAT_TIME && change.amount < 0 && change.cause.attacker().is_some()
+}
+
+/// Whether an in game message was caused by the player, as opposed to the
+/// updates clients send every tick
+fn is_player_input(msg: &ClientGeneral, controller: Option<&Controller>) -> bool {
+    match msg {
+        ClientGeneral::ControllerInputs(inputs) => {
+            inputs.move_dir != Vec2::zero()
+                || inputs.holding_ability_key()
+                || inputs.jump.is_pressed()
+                || controller.map_or(true, |c| c.inputs.look_dir != inputs.look_dir)
+        },
+        ClientGeneral::ControlEvent(_)
+        | ClientGeneral::ControlAction(_)
+        | ClientGeneral::BreakBlock(_)
+        | ClientGeneral::PlaceBlock(..)
+        | ClientGeneral::EditSign { .. } => true,
+        _ => false,
+    }
+}
+
+/// Check whether a client may perform another discrete action, recording any
+/// violation in its suspicion.
+fn check_action(
+    entity: specs::Entity,
+    time: f64,
+    suspicions: &mut WriteStorage<'_, Suspicion>,
+    players: &WriteStorage<'_, Player>,
+    settings: &Read<'_, Settings>,
+) -> Result<(), anticheat::ActionViolation> {
+    let suspicion = match suspicions.entry(entity) {
+        Ok(entry) => entry.or_insert_with(Suspicion::default),
+        Err(_) => return Ok(()),
+    };
+    let verdict = suspicion.record_action(time);
+
+    if let Err(violation) = verdict {
+        suspicion.action_rejections += 1;
+        if let Some(score) = suspicion.report(
+            violation.severity(),
+            time,
+            settings.cheat_response_threshold,
+            settings.cheat_response,
+        ) {
+            let alias = players.get(entity).map(|p| p.alias.clone());
+            warn!(
+                ?alias,
+                ?violation,
+                ?score,
+                "Rejected action from suspicious player"
+            );
+        }
+    }
+    verdict
+}
+
 /// This system will handle new messages from clients
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -560,7 +954,9 @@ impl<'a> System<'a> for Sys {
         Write<'a, SysTimer<Self>>,
         ReadStorage<'a, Uid>,
         ReadStorage<'a, CanBuild>,
-        ReadStorage<'a, ForceUpdate>,
+        ReadStorage<'a, CharacterState>,
+        WriteStorage<'a, ForceUpdate>,
+        WriteStorage<'a, Suspicion>,
         WriteStorage<'a, Stats>,
         ReadStorage<'a, ChatMode>,
         WriteExpect<'a, LoginProvider>,
@@ -572,9 +968,17 @@ impl<'a> System<'a> for Sys {
         WriteStorage<'a, Player>,
         WriteStorage<'a, Client>,
         WriteStorage<'a, Controller>,
-        Read<'a, Settings>,
-        ReadExpect<'a, EditableSettings>,
-        ReadExpect<'a, AliasValidator>,
+        WriteStorage<'a, AbilityHotbar>,
+        // Grouped, since system data tuples can't have more than 26 elements
+        (
+            Read<'a, Settings>,
+            ReadExpect<'a, EditableSettings>,
+            ReadExpect<'a, AliasValidator>,
+            ReadExpect<'a, WorldOverview>,
+            WriteExpect<'a, RulesAcknowledgements>,
+            ReadExpect<'a, NamedLocations>,
+            ReadStorage<'a, Body>,
+        ),
     );
 
     #[allow(clippy::match_ref_pats)] // TODO: Pending review in #587
@@ -593,7 +997,9 @@ impl<'a> System<'a> for Sys {
             mut timer,
             uids,
             can_build,
-            force_updates,
+            character_states,
+            mut force_updates,
+            mut suspicions,
             mut stats,
             chat_modes,
             mut accounts,
@@ -605,9 +1011,16 @@ impl<'a> System<'a> for Sys {
             mut players,
             mut clients,
             mut controllers,
-            settings,
-            editable_settings,
-            alias_validator,
+            mut ability_hotbars,
+            (
+                settings,
+                editable_settings,
+                alias_validator,
+                world_overview,
+                mut rules_acknowledgements,
+                named_locations,
+                bodies,
+            ),
         ): Self::SystemData,
     ) {
         span!(_guard, "run", "message::Sys::run");
@@ -618,12 +1031,19 @@ impl<'a> System<'a> for Sys {
         let mut new_chat_msgs = Vec::new();
 
         // Player list to send new players.
-        let player_list = (&uids, &players, stats.maybe(), admins.maybe())
+        let player_list = (
+            &uids,
+            &players,
+            stats.maybe(),
+            admins.maybe(),
+            clients.maybe(),
+        )
             .join()
-            .map(|(uid, player, stats, admin)| {
+            .map(|(uid, player, stats, admin, client)| {
                 (*uid, PlayerInfo {
                     is_online: true,
                     is_admin: admin.is_some(),
+                    is_idle: client.map_or(false, |client| client.is_idle),
                     player_alias: player.alias.clone(),
                     character: stats.map(|stats| CharacterInfo {
                         name: stats.name.clone(),
@@ -634,10 +1054,37 @@ impl<'a> System<'a> for Sys {
             .collect::<HashMap<_, _>>();
         // List of new players to update player lists of all clients.
         let mut new_players = Vec::new();
+        // Sessions to disconnect because their account logged in again
+        let mut takeovers = Vec::new();
 
         for (entity, client) in (&entities, &mut clients).join() {
             let mut cnt = 0;
 
+            // Retry registering clients that were waiting for another session of
+            // their account to end
+            let pending_register = match client.pending_register.take() {
+                Some(msg) => Self::handle_register_msg(
+                    &player_list,
+                    &mut new_players,
+                    entity,
+                    client,
+                    &player_metrics,
+                    &mut accounts,
+                    &mut admins,
+                    &mut players,
+                    &editable_settings,
+                    time.0,
+                    &entities,
+                    &stats,
+                    &settings,
+                    &mut takeovers,
+                    &rules_acknowledgements,
+                    &named_locations,
+                    msg,
+                ),
+                None => Ok(()),
+            };
+
             let network_err: Result<(), crate::error::Error> = block_on(async {
                 //TIMEOUT 0.02 ms for msg handling
                 let work_future = Self::handle_messages(
@@ -652,9 +1099,13 @@ impl<'a> System<'a> for Sys {
                     &terrain,
                     &network_metrics,
                     &player_metrics,
+                    time.0,
                     &uids,
                     &can_build,
-                    &force_updates,
+                    &character_states,
+                    &bodies,
+                    &mut force_updates,
+                    &mut suspicions,
                     &mut stats,
                     &chat_modes,
                     &mut accounts,
@@ -665,9 +1116,15 @@ impl<'a> System<'a> for Sys {
                     &mut orientations,
                     &mut players,
                     &mut controllers,
+                    &mut ability_hotbars,
                     &settings,
                     &editable_settings,
                     &alias_validator,
+                    &world_overview,
+                    &entities,
+                    &mut takeovers,
+                    &mut rules_acknowledgements,
+                    &named_locations,
                 );
                 select!(
                     _ = Delay::new(std::time::Duration::from_micros(20)).fuse() => Ok(()),
@@ -675,8 +1132,19 @@ impl<'a> System<'a> for Sys {
                 )
             });
 
+            // Acknowledge the latest physics update with the state the server settled on
+            if let Some(seq) = client.physics_ack.take() {
+                if let (Some(&pos), Some(&vel), Some(&ori)) = (
+                    positions.get(entity),
+                    velocities.get(entity),
+                    orientations.get(entity),
+                ) {
+                    client.send_msg(ServerGeneral::PhysicsAck { seq, pos, vel, ori });
+                }
+            }
+
             // Network error
-            if network_err.is_err() {
+            if network_err.is_err() || pending_register.is_err() {
                 debug!(?entity, "postbox error with client, disconnecting");
                 player_metrics
                     .clients_disconnected
@@ -701,6 +1169,16 @@ impl<'a> System<'a> for Sys {
             }
         }
 
+        for old in takeovers {
+            info!(?old, "Disconnecting session, its account logged in again");
+            if let Some(client) = clients.get_mut(old) {
+                client.send_msg(ServerGeneral::Disconnect(DisconnectReason::Kicked(
+                    "Your account logged in from somewhere else".to_string(),
+                )));
+            }
+            server_emitter.emit(ServerEvent::ClientDisconnect(old));
+        }
+
         // Handle new players.
         // Tell all clients to add them to the player list.
         for entity in new_players {
@@ -710,6 +1188,7 @@ impl<'a> System<'a> for Sys {
                         player_alias: player.alias.clone(),
                         is_online: true,
                         is_admin: admins.get(entity).is_some(),
+                        is_idle: false,
                         character: None, // new players will be on character select.
                     }));
                 for client in (&mut clients).join().filter(|c| c.registered) {
@@ -1,12 +1,16 @@
+pub mod cooking;
 pub mod entity_sync;
+pub mod gc;
 pub mod invite_timeout;
 pub mod message;
 pub mod object;
 pub mod persistence;
 pub mod sentinel;
+pub mod spawning;
 pub mod subscription;
 pub mod terrain;
 pub mod terrain_sync;
+pub mod voice;
 pub mod waypoint;
 
 use specs::DispatcherBuilder;
@@ -22,9 +26,12 @@ pub type SubscriptionTimer = SysTimer<subscription::Sys>;
 pub type TerrainTimer = SysTimer<terrain::Sys>;
 pub type TerrainSyncTimer = SysTimer<terrain_sync::Sys>;
 pub type WaypointTimer = SysTimer<waypoint::Sys>;
+pub type VoiceTimer = SysTimer<voice::Sys>;
 pub type InviteTimeoutTimer = SysTimer<invite_timeout::Sys>;
 pub type PersistenceTimer = SysTimer<persistence::Sys>;
 pub type PersistenceScheduler = SysScheduler<persistence::Sys>;
+pub type SpawningScheduler = SysScheduler<spawning::Sys>;
+pub type GcScheduler = SysScheduler<gc::Sys>;
 
 // System names
 // Note: commented names may be useful in the future
@@ -37,13 +44,21 @@ const WAYPOINT_SYS: &str = "server_waypoint_sys";
 const INVITE_TIMEOUT_SYS: &str = "server_invite_timeout_sys";
 const PERSISTENCE_SYS: &str = "server_persistence_sys";
 const OBJECT_SYS: &str = "server_object_sys";
+const COOKING_SYS: &str = "server_cooking_sys";
+const GC_SYS: &str = "server_gc_sys";
+const SPAWNING_SYS: &str = "server_spawning_sys";
+const VOICE_SYS: &str = "server_voice_sys";
 
 pub fn add_server_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch_builder.add(terrain::Sys, TERRAIN_SYS, &[]);
-    dispatch_builder.add(waypoint::Sys, WAYPOINT_SYS, &[]);
+    dispatch_builder.add(waypoint::Sys::default(), WAYPOINT_SYS, &[]);
     dispatch_builder.add(invite_timeout::Sys, INVITE_TIMEOUT_SYS, &[]);
     dispatch_builder.add(persistence::Sys, PERSISTENCE_SYS, &[]);
     dispatch_builder.add(object::Sys, OBJECT_SYS, &[]);
+    dispatch_builder.add(cooking::Sys, COOKING_SYS, &[]);
+    dispatch_builder.add(gc::Sys, GC_SYS, &[]);
+    dispatch_builder.add(spawning::Sys, SPAWNING_SYS, &[]);
+    dispatch_builder.add(voice::Sys, VOICE_SYS, &[]);
 }
 
 pub fn run_sync_systems(ecs: &mut specs::World) {
@@ -84,6 +99,13 @@ impl<S> SysScheduler<S> {
             false
         }
     }
+
+    /// Lets the system run on the next tick
+    pub fn run_next(&mut self) {
+        if let Some(last_run) = Instant::now().checked_sub(self.interval) {
+            self.last_run = last_run;
+        }
+    }
 }
 
 impl<S> Default for SysScheduler<S> {
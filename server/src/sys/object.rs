@@ -49,12 +49,14 @@ impl<'a> System<'a> for Sys {
                             pos: pos.0,
                             explosion: Explosion {
                                 radius: 12.0,
+                                radius_per_power: 0.0,
                                 max_damage: 500,
                                 min_damage: 100,
                                 max_heal: 0,
                                 min_heal: 0,
                                 terrain_destruction_power: 4.0,
                                 energy_regen: 0,
+                                knockback: 30.0,
                             },
                             owner: *owner,
                             friendly_damage: true,
@@ -72,12 +74,14 @@ impl<'a> System<'a> for Sys {
                             pos: pos.0,
                             explosion: Explosion {
                                 radius: 12.0,
+                                radius_per_power: 0.0,
                                 max_damage: 50,
                                 min_damage: 10,
                                 max_heal: 0,
                                 min_heal: 0,
                                 terrain_destruction_power: 4.0,
                                 energy_regen: 0,
+                                knockback: 10.0,
                             },
                             owner: *owner,
                             friendly_damage: true,
@@ -1,9 +1,9 @@
 use super::SysTimer;
 use common::{
     comp::{
-        BeamSegment, Body, CanBuild, CharacterState, Collider, Energy, Gravity, Group, Item,
-        LightEmitter, Loadout, Mass, MountState, Mounting, Ori, Player, Pos, Scale, Shockwave,
-        Stats, Sticky, Vel,
+        BeamSegment, Body, Buffs, CanBuild, CharacterState, Collider, Energy, Gravity, Group, Item,
+        LightEmitter, Loadout, Mass, MountState, Mounting, Ori, Player, Poise, Pos, Scale,
+        Shockwave, Stats, Sticky, Vel,
     },
     msg::EcsCompPacket,
     span,
@@ -45,6 +45,7 @@ pub struct TrackedComps<'a> {
     pub player: ReadStorage<'a, Player>,
     pub stats: ReadStorage<'a, Stats>,
     pub energy: ReadStorage<'a, Energy>,
+    pub poise: ReadStorage<'a, Poise>,
     pub can_build: ReadStorage<'a, CanBuild>,
     pub light_emitter: ReadStorage<'a, LightEmitter>,
     pub item: ReadStorage<'a, Item>,
@@ -60,6 +61,7 @@ pub struct TrackedComps<'a> {
     pub character_state: ReadStorage<'a, CharacterState>,
     pub shockwave: ReadStorage<'a, Shockwave>,
     pub beam_segment: ReadStorage<'a, BeamSegment>,
+    pub buffs: ReadStorage<'a, Buffs>,
 }
 impl<'a> TrackedComps<'a> {
     pub fn create_entity_package(
@@ -89,6 +91,10 @@ impl<'a> TrackedComps<'a> {
             .get(entity)
             .cloned()
             .map(|c| comps.push(c.into()));
+        self.poise
+            .get(entity)
+            .cloned()
+            .map(|c| comps.push(c.into()));
         self.can_build
             .get(entity)
             .cloned()
@@ -143,6 +149,10 @@ impl<'a> TrackedComps<'a> {
             .get(entity)
             .cloned()
             .map(|c| comps.push(c.into()));
+        self.buffs
+            .get(entity)
+            .cloned()
+            .map(|c| comps.push(c.into()));
         // Add untracked comps
         pos.map(|c| comps.push(c.into()));
         vel.map(|c| comps.push(c.into()));
@@ -158,6 +168,7 @@ pub struct ReadTrackers<'a> {
     pub player: ReadExpect<'a, UpdateTracker<Player>>,
     pub stats: ReadExpect<'a, UpdateTracker<Stats>>,
     pub energy: ReadExpect<'a, UpdateTracker<Energy>>,
+    pub poise: ReadExpect<'a, UpdateTracker<Poise>>,
     pub can_build: ReadExpect<'a, UpdateTracker<CanBuild>>,
     pub light_emitter: ReadExpect<'a, UpdateTracker<LightEmitter>>,
     pub item: ReadExpect<'a, UpdateTracker<Item>>,
@@ -173,6 +184,7 @@ pub struct ReadTrackers<'a> {
     pub character_state: ReadExpect<'a, UpdateTracker<CharacterState>>,
     pub shockwave: ReadExpect<'a, UpdateTracker<Shockwave>>,
     pub beam_segment: ReadExpect<'a, UpdateTracker<BeamSegment>>,
+    pub buffs: ReadExpect<'a, UpdateTracker<Buffs>>,
 }
 impl<'a> ReadTrackers<'a> {
     pub fn create_sync_packages(
@@ -188,6 +200,7 @@ impl<'a> ReadTrackers<'a> {
             .with_component(&comps.uid, &*self.player, &comps.player, filter)
             .with_component(&comps.uid, &*self.stats, &comps.stats, filter)
             .with_component(&comps.uid, &*self.energy, &comps.energy, filter)
+            .with_component(&comps.uid, &*self.poise, &comps.poise, filter)
             .with_component(&comps.uid, &*self.can_build, &comps.can_build, filter)
             .with_component(
                 &comps.uid,
@@ -212,7 +225,8 @@ impl<'a> ReadTrackers<'a> {
                 filter,
             )
             .with_component(&comps.uid, &*self.shockwave, &comps.shockwave, filter)
-            .with_component(&comps.uid, &*self.beam_segment, &comps.beam_segment, filter);
+            .with_component(&comps.uid, &*self.beam_segment, &comps.beam_segment, filter)
+            .with_component(&comps.uid, &*self.buffs, &comps.buffs, filter);
 
         (entity_sync_package, comp_sync_package)
     }
@@ -225,6 +239,7 @@ pub struct WriteTrackers<'a> {
     player: WriteExpect<'a, UpdateTracker<Player>>,
     stats: WriteExpect<'a, UpdateTracker<Stats>>,
     energy: WriteExpect<'a, UpdateTracker<Energy>>,
+    poise: WriteExpect<'a, UpdateTracker<Poise>>,
     can_build: WriteExpect<'a, UpdateTracker<CanBuild>>,
     light_emitter: WriteExpect<'a, UpdateTracker<LightEmitter>>,
     item: WriteExpect<'a, UpdateTracker<Item>>,
@@ -240,6 +255,7 @@ pub struct WriteTrackers<'a> {
     character_state: WriteExpect<'a, UpdateTracker<CharacterState>>,
     shockwave: WriteExpect<'a, UpdateTracker<Shockwave>>,
     beam: WriteExpect<'a, UpdateTracker<BeamSegment>>,
+    buffs: WriteExpect<'a, UpdateTracker<Buffs>>,
 }
 
 fn record_changes(comps: &TrackedComps, trackers: &mut WriteTrackers) {
@@ -249,6 +265,7 @@ fn record_changes(comps: &TrackedComps, trackers: &mut WriteTrackers) {
     trackers.player.record_changes(&comps.player);
     trackers.stats.record_changes(&comps.stats);
     trackers.energy.record_changes(&comps.energy);
+    trackers.poise.record_changes(&comps.poise);
     trackers.can_build.record_changes(&comps.can_build);
     trackers.light_emitter.record_changes(&comps.light_emitter);
     trackers.item.record_changes(&comps.item);
@@ -266,6 +283,7 @@ fn record_changes(comps: &TrackedComps, trackers: &mut WriteTrackers) {
         .record_changes(&comps.character_state);
     trackers.shockwave.record_changes(&comps.shockwave);
     trackers.beam.record_changes(&comps.beam_segment);
+    trackers.buffs.record_changes(&comps.buffs);
     // Debug how many updates are being sent
     /*
     macro_rules! log_counts {
@@ -286,6 +304,7 @@ fn record_changes(comps: &TrackedComps, trackers: &mut WriteTrackers) {
     log_counts!(player, "Players");
     log_counts!(stats, "Stats");
     log_counts!(energy, "Energies");
+    log_counts!(poise, "Poises");
     log_counts!(light_emitter, "Light emitters");
     log_counts!(item, "Items");
     log_counts!(scale, "Scales");
@@ -299,6 +318,7 @@ fn record_changes(comps: &TrackedComps, trackers: &mut WriteTrackers) {
     log_counts!(character_state, "Character States");
     log_counts!(shockwave, "Shockwaves");
     log_counts!(beam, "Beams");
+    log_counts!(buffs, "Buffs");
     */
 }
 
@@ -308,6 +328,7 @@ pub fn register_trackers(world: &mut World) {
     world.register_tracker::<Player>();
     world.register_tracker::<Stats>();
     world.register_tracker::<Energy>();
+    world.register_tracker::<Poise>();
     world.register_tracker::<CanBuild>();
     world.register_tracker::<LightEmitter>();
     world.register_tracker::<Item>();
@@ -323,6 +344,7 @@ pub fn register_trackers(world: &mut World) {
     world.register_tracker::<CharacterState>();
     world.register_tracker::<Shockwave>();
     world.register_tracker::<BeamSegment>();
+    world.register_tracker::<Buffs>();
 }
 
 /// Deleted entities grouped by region
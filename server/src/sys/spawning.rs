@@ -0,0 +1,175 @@
+use super::{terrain::create_npc_event, SysScheduler};
+use crate::{
+    spawning::{self, SitePositions, Spawned},
+    Settings, SpawnPoint,
+};
+use common::{
+    comp::{Agent, Alignment, Player, Pos},
+    event::{EventBus, ServerEvent},
+    generation::EntityInfo,
+    region::REGION_SIZE,
+    span,
+    state::{Time, TimeOfDay},
+    terrain::TerrainGrid,
+    vol::ReadVol,
+};
+use hashbrown::HashMap;
+use rand::Rng;
+use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
+use vek::*;
+
+fn region_of(wpos: Vec2<f32>) -> Vec2<i32> {
+    wpos.map(|e| (e as i32).div_euclid(REGION_SIZE as i32))
+}
+
+/// Highest place in the column at `wpos` where an NPC can stand on dry ground
+fn find_ground(terrain: &TerrainGrid, wpos: Vec2<i32>) -> Option<Vec3<f32>> {
+    let chunk = terrain.get_key(terrain.pos_key(Vec3::new(wpos.x, wpos.y, 0)))?;
+    let is_solid = |z| {
+        terrain
+            .get(Vec3::new(wpos.x, wpos.y, z))
+            .map_or(false, |block| block.is_solid())
+    };
+    (chunk.get_min_z()..chunk.get_max_z())
+        .rev()
+        .find(|z| is_solid(*z) && !is_solid(*z + 1))
+        .filter(|z| {
+            terrain
+                .get(Vec3::new(wpos.x, wpos.y, z + 1))
+                .map_or(true, |block| !block.is_liquid())
+        })
+        .map(|z| Vec3::new(wpos.x, wpos.y, z + 1).map(|e| e as f32) + Vec3::new(0.5, 0.5, 0.0))
+}
+
+/// This system spawns wildlife and hostile NPCs out of sight around players,
+/// and deletes the ones it spawned once nobody has been near them for a while
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Time>,
+        Read<'a, TimeOfDay>,
+        Read<'a, Settings>,
+        Read<'a, EventBus<ServerEvent>>,
+        Write<'a, SysScheduler<Self>>,
+        ReadExpect<'a, TerrainGrid>,
+        ReadExpect<'a, SpawnPoint>,
+        ReadExpect<'a, SitePositions>,
+        ReadStorage<'a, Pos>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Agent>,
+        ReadStorage<'a, Alignment>,
+        WriteStorage<'a, Spawned>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            time,
+            time_of_day,
+            settings,
+            server_bus,
+            mut scheduler,
+            terrain,
+            spawn_point,
+            site_positions,
+            positions,
+            players,
+            agents,
+            alignments,
+            mut spawned,
+        ): Self::SystemData,
+    ) {
+        span!(_guard, "run", "spawning::Sys::run");
+        let spawn_settings = &settings.spawning;
+        if !spawn_settings.enabled || !scheduler.should_run() {
+            return;
+        }
+        let mut server_emitter = server_bus.emitter();
+
+        let player_positions = (&positions, &players)
+            .join()
+            .map(|(pos, _)| pos.0)
+            .collect::<Vec<_>>();
+
+        // Delete spawned NPCs nobody has been near for too long, and keep the
+        // ones that were tamed since
+        let mut tamed = Vec::new();
+        for (entity, pos, mark, alignment) in
+            (&entities, &positions, &mut spawned, alignments.maybe()).join()
+        {
+            if matches!(alignment, Some(Alignment::Owned(_))) {
+                tamed.push(entity);
+            } else if player_positions
+                .iter()
+                .any(|wpos| wpos.distance(pos.0) < spawn_settings.observe_distance)
+            {
+                mark.last_observed = time.0;
+            } else if time.0 - mark.last_observed > spawn_settings.despawn_after.as_secs_f64() {
+                server_emitter.emit(ServerEvent::Expire(entity));
+            }
+        }
+        for entity in tamed {
+            spawned.remove(entity);
+        }
+
+        let mut region_populations = HashMap::<_, usize>::new();
+        for (pos, _) in (&positions, &agents).join() {
+            *region_populations.entry(region_of(pos.0.xy())).or_default() += 1;
+        }
+
+        let night = spawning::is_night(time_of_day.0);
+        let mut rng = rand::thread_rng();
+        for player_pos in player_positions.iter() {
+            for _ in 0..spawn_settings.attempts_per_player {
+                let angle = rng.gen_range(0.0, std::f32::consts::PI * 2.0);
+                let dist = rng.gen_range(spawn_settings.min_distance, spawn_settings.max_distance);
+                let wpos2d = player_pos.xy() + Vec2::new(angle.cos(), angle.sin()) * dist;
+
+                // Don't spawn where another player would see it happen
+                if player_positions
+                    .iter()
+                    .any(|wpos| wpos.xy().distance(wpos2d) < spawn_settings.min_distance)
+                {
+                    continue;
+                }
+                let population = region_populations.entry(region_of(wpos2d)).or_default();
+                if *population >= spawn_settings.region_cap {
+                    continue;
+                }
+
+                let column = wpos2d.map(|e| e as i32);
+                let chunk = match terrain.get_key(terrain.pos_key(column.into())) {
+                    Some(chunk) => chunk,
+                    None => continue,
+                };
+                let wpos = match find_ground(&terrain, column) {
+                    Some(wpos) => wpos,
+                    None => continue,
+                };
+                let biome = chunk.meta().biome();
+                let near_site =
+                    site_positions.distance_to_nearest(wpos2d) < spawn_settings.site_safe_radius;
+
+                if let Some((body, alignment)) =
+                    spawning::choose_npc(biome, night, near_site, spawn_settings, &mut rng)
+                {
+                    let entity = EntityInfo::at(wpos)
+                        .with_body(body)
+                        .with_alignment(alignment)
+                        .with_automatic_name();
+                    server_emitter.emit(create_npc_event(
+                        entity,
+                        biome,
+                        &settings,
+                        &spawn_point,
+                        true,
+                    ));
+                    *population += 1;
+                }
+            }
+        }
+    }
+}
@@ -1,18 +1,23 @@
 use super::SysTimer;
-use crate::{chunk_generator::ChunkGenerator, client::Client, Tick};
+use crate::{
+    chunk_generator::ChunkGenerator, client::Client, persistence::block_diff::BlockDiffs, Settings,
+    SpawnPoint, Tick,
+};
 use common::{
     comp::{self, bird_medium, Alignment, Player, Pos},
+    difficulty,
     event::{EventBus, ServerEvent},
-    generation::get_npc_name,
+    generation::{get_npc_name, EntityInfo},
     msg::ServerGeneral,
     npc::NPC_NAMES,
     span,
     state::TerrainChanges,
-    terrain::TerrainGrid,
+    terrain::{BiomeKind, ChunkEncoding, PackedChunk, TerrainChunk, TerrainGrid},
     LoadoutBuilder,
 };
+use hashbrown::HashMap;
 use rand::Rng;
-use specs::{Join, Read, ReadStorage, System, Write, WriteExpect, WriteStorage};
+use specs::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteExpect, WriteStorage};
 use std::sync::Arc;
 use vek::*;
 
@@ -28,6 +33,9 @@ impl<'a> System<'a> for Sys {
     type SystemData = (
         Read<'a, EventBus<ServerEvent>>,
         Read<'a, Tick>,
+        Read<'a, Settings>,
+        ReadExpect<'a, SpawnPoint>,
+        ReadExpect<'a, BlockDiffs>,
         Write<'a, SysTimer<Self>>,
         WriteExpect<'a, ChunkGenerator>,
         WriteExpect<'a, TerrainGrid>,
@@ -42,6 +50,9 @@ impl<'a> System<'a> for Sys {
         (
             server_event_bus,
             tick,
+            settings,
+            spawn_point,
+            block_diffs,
             mut timer,
             mut chunk_generator,
             mut terrain,
@@ -59,22 +70,26 @@ impl<'a> System<'a> for Sys {
         // Fetch any generated `TerrainChunk`s and insert them into the terrain.
         // Also, send the chunk data to anybody that is close by.
         'insert_terrain_chunks: while let Some((key, res)) = chunk_generator.recv_new_chunk() {
-            let (chunk, supplement) = match res {
+            let (mut chunk, supplement) = match res {
                 Ok((chunk, supplement)) => (chunk, supplement),
-                Err(Some(entity)) => {
-                    if let Some(client) = clients.get_mut(entity) {
-                        client.send_msg(ServerGeneral::TerrainChunkUpdate {
-                            key,
-                            chunk: Err(()),
-                        });
+                Err(requesters) => {
+                    for entity in requesters {
+                        if let Some(client) = clients.get_mut(entity) {
+                            client.send_msg(ServerGeneral::TerrainChunkUpdate {
+                                key,
+                                chunk: Err(()),
+                            });
+                        }
                     }
                     continue 'insert_terrain_chunks;
                 },
-                Err(None) => {
-                    continue 'insert_terrain_chunks;
-                },
             };
+            // Reapply the changes players made to the chunk before it was unloaded
+            block_diffs.apply(key, &mut chunk);
+            let biome = chunk.meta().biome();
+
             // Send the chunk to all nearby players.
+            let mut packed = LazyPackedChunk::new(&chunk);
             for (view_distance, pos, client) in (&players, &positions, &mut clients)
                 .join()
                 .filter_map(|(player, pos, client)| {
@@ -92,7 +107,7 @@ impl<'a> System<'a> for Sys {
                 if adjusted_dist_sqr <= view_distance.pow(2) {
                     client.send_msg(ServerGeneral::TerrainChunkUpdate {
                         key,
-                        chunk: Ok(Box::new(chunk.clone())),
+                        chunk: Ok(packed.get(client.chunk_encoding)),
                     });
                 }
             }
@@ -112,84 +127,13 @@ impl<'a> System<'a> for Sys {
                     continue;
                 }
 
-                let mut body = entity.body;
-                let name = entity.name.unwrap_or_else(|| "Unnamed".to_string());
-                let alignment = entity.alignment;
-                let main_tool = entity.main_tool;
-                let mut stats = comp::Stats::new(name, body);
-                // let damage = stats.level.level() as i32; TODO: Make NPC base damage
-                // non-linearly depend on their level
-
-                let mut scale = entity.scale;
-
-                // TODO: Remove this and implement scaling or level depending on stuff like
-                // species instead
-                stats.level.set_level(
-                    entity.level.unwrap_or_else(|| {
-                        (rand::thread_rng().gen_range(1, 9) as f32 * scale) as u32
-                    }),
-                );
-
-                // Replace stuff if it's a boss
-                if entity.is_giant {
-                    if rand::random::<f32>() < 0.65 && entity.alignment != Alignment::Enemy {
-                        let body_new = comp::humanoid::Body::random();
-                        body = comp::Body::Humanoid(body_new);
-                        stats = comp::Stats::new(
-                            format!(
-                                "Gentle Giant {}",
-                                get_npc_name(&NPC_NAMES.humanoid, body_new.species)
-                            ),
-                            body,
-                        );
-                    }
-                    stats.level.set_level(rand::thread_rng().gen_range(30, 35));
-                    scale = 2.0 + rand::random::<f32>();
-                }
-
-                let loadout =
-                    LoadoutBuilder::build_loadout(body, alignment, main_tool, entity.is_giant)
-                        .build();
-
-                stats.update_max_hp(stats.body_type);
-
-                stats
-                    .health
-                    .set_to(stats.health.maximum(), comp::HealthSource::Revive);
-
-                let can_speak = match body {
-                    comp::Body::Humanoid(_) => alignment == comp::Alignment::Npc,
-                    comp::Body::BirdMedium(bird_medium) => match bird_medium.species {
-                        // Parrots like to have a word in this, too...
-                        bird_medium::Species::Parrot => alignment == comp::Alignment::Npc,
-                        _ => false,
-                    },
-                    _ => false,
-                };
-
-                // TODO: This code sets an appropriate base_damage for the enemy. This doesn't
-                // work because the damage is now saved in an ability
-                /*
-                if let Some(item::ItemKind::Tool(item::ToolData { base_damage, .. })) =
-                    &mut loadout.active_item.map(|i| i.item.kind)
-                {
-                    *base_damage = stats.level.level() as u32 * 3;
-                }
-                */
-                server_emitter.emit(ServerEvent::CreateNpc {
-                    pos: Pos(entity.pos),
-                    stats,
-                    loadout,
-                    agent: if entity.has_agency {
-                        Some(comp::Agent::new(entity.pos, can_speak, &body))
-                    } else {
-                        None
-                    },
-                    body,
-                    alignment,
-                    scale: comp::Scale(scale),
-                    drop_item: entity.loot_drop,
-                })
+                server_emitter.emit(create_npc_event(
+                    entity,
+                    biome,
+                    &settings,
+                    &spawn_point,
+                    false,
+                ));
             }
         }
 
@@ -200,8 +144,6 @@ impl<'a> System<'a> for Sys {
             .map(|(k, _)| k)
             // Don't check every chunk every tick (spread over 16 ticks)
             .filter(|k| k.x.abs() as u64 % 4 + (k.y.abs() as u64 % 4) * 4 == tick.0 % 16)
-            // There shouldn't be to many pending chunks so we will just check them all
-            .chain(chunk_generator.pending_chunks())
             .for_each(|chunk_key| {
                 let mut should_drop = true;
 
@@ -226,14 +168,129 @@ impl<'a> System<'a> for Sys {
             if terrain.remove(key).is_some() {
                 terrain_changes.removed_chunks.insert(key);
             }
-
-            chunk_generator.cancel_if_pending(key);
         }
 
+        // There shouldn't be to many pending chunks so we will just check them all.
+        // Chunks requested by players are only kept for as long as one of them is
+        // close enough, the ones requested by the server while any player is.
+        let wanted_by = |chunk_key: Vec2<i32>, player: &Player, pos: &Pos| {
+            player
+                .view_distance
+                .map_or(false, |vd| chunk_in_vd(pos.0, chunk_key, &terrain, vd))
+        };
+        chunk_generator.cancel_unwanted(|chunk_key, requester| match requester {
+            Some(entity) => match (players.get(entity), positions.get(entity)) {
+                (Some(player), Some(pos)) => wanted_by(chunk_key, player, pos),
+                _ => false,
+            },
+            None => (&players, &positions)
+                .join()
+                .any(|(player, pos)| wanted_by(chunk_key, player, pos)),
+        });
+
         timer.end()
     }
 }
 
+/// The event that creates an NPC generated with a chunk or by the spawner,
+/// with a level that depends on how dangerous the place it's created at is
+pub(crate) fn create_npc_event(
+    entity: EntityInfo,
+    biome: BiomeKind,
+    settings: &Settings,
+    spawn_point: &SpawnPoint,
+    spawned: bool,
+) -> ServerEvent {
+    let mut body = entity.body;
+    let name = entity.name.unwrap_or_else(|| "Unnamed".to_string());
+    let alignment = entity.alignment;
+    let main_tool = entity.main_tool;
+    let mut stats = comp::Stats::new(name, body);
+    // let damage = stats.level.level() as i32; TODO: Make NPC base damage
+    // non-linearly depend on their level
+
+    let mut scale = entity.scale;
+
+    // TODO: Remove this and implement scaling or level depending on stuff like
+    // species instead
+    stats.level.set_level(entity.level.unwrap_or_else(|| {
+        let level = (rand::thread_rng().gen_range(1, 9) as f32 * scale) as u32;
+        // Exploring further away from spawn and into harsher biomes gets more
+        // dangerous
+        if settings.scale_npc_levels {
+            let danger = (difficulty::region_danger(spawn_point.0.xy(), entity.pos.xy())
+                + difficulty::biome_danger(biome))
+            .min(difficulty::MAX_DANGER);
+            difficulty::scaled_level(level, danger)
+        } else {
+            level
+        }
+    }));
+
+    // Replace stuff if it's a boss
+    if entity.is_giant {
+        if rand::random::<f32>() < 0.65 && entity.alignment != Alignment::Enemy {
+            let body_new = comp::humanoid::Body::random();
+            body = comp::Body::Humanoid(body_new);
+            stats = comp::Stats::new(
+                format!(
+                    "Gentle Giant {}",
+                    get_npc_name(&NPC_NAMES.humanoid, body_new.species)
+                ),
+                body,
+            );
+        }
+        stats.level.set_level(rand::thread_rng().gen_range(30, 35));
+        scale = 2.0 + rand::random::<f32>();
+    }
+
+    let loadout =
+        LoadoutBuilder::build_loadout(body, alignment, main_tool, entity.is_giant).build();
+
+    stats.update_max_hp(stats.body_type);
+
+    stats
+        .health
+        .set_to(stats.health.maximum(), comp::HealthSource::Revive);
+
+    let can_speak = match body {
+        comp::Body::Humanoid(_) => {
+            matches!(alignment, comp::Alignment::Npc | comp::Alignment::Enemy)
+        },
+        comp::Body::BirdMedium(bird_medium) => match bird_medium.species {
+            // Parrots like to have a word in this, too...
+            bird_medium::Species::Parrot => alignment == comp::Alignment::Npc,
+            _ => false,
+        },
+        _ => false,
+    };
+
+    // TODO: This code sets an appropriate base_damage for the enemy. This doesn't
+    // work because the damage is now saved in an ability
+    /*
+    if let Some(item::ItemKind::Tool(item::ToolData { base_damage, .. })) =
+        &mut loadout.active_item.map(|i| i.item.kind)
+    {
+        *base_damage = stats.level.level() as u32 * 3;
+    }
+    */
+    ServerEvent::CreateNpc {
+        pos: Pos(entity.pos),
+        stats,
+        loadout,
+        agent: if entity.has_agency {
+            Some(comp::Agent::new(entity.pos, can_speak, &body).with_schedule(entity.schedule))
+        } else {
+            None
+        },
+        body,
+        alignment,
+        scale: comp::Scale(scale),
+        drop_item: entity.loot_drop,
+        spawned,
+    }
+}
+
 pub fn chunk_in_vd(
     player_pos: Vec3<f32>,
     chunk_pos: Vec2<i32>,
@@ -248,3 +305,27 @@ pub fn chunk_in_vd(
 
     adjusted_dist_sqr <= vd.pow(2)
 }
+
+/// Packs a chunk only once for every encoding it is sent in, and only if it is
+/// sent at all
+pub struct LazyPackedChunk<'a> {
+    chunk: &'a TerrainChunk,
+    packed: HashMap<ChunkEncoding, PackedChunk>,
+}
+
+impl<'a> LazyPackedChunk<'a> {
+    pub fn new(chunk: &'a TerrainChunk) -> Self {
+        Self {
+            chunk,
+            packed: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, encoding: ChunkEncoding) -> PackedChunk {
+        let chunk = self.chunk;
+        self.packed
+            .entry(encoding)
+            .or_insert_with(|| PackedChunk::pack_with(chunk, encoding))
+            .clone()
+    }
+}
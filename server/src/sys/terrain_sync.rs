@@ -2,11 +2,12 @@ use super::SysTimer;
 use crate::client::Client;
 use common::{
     comp::{Player, Pos},
-    msg::ServerGeneral,
+    msg::{Capability, ServerGeneral},
     span,
     state::TerrainChanges,
     terrain::TerrainGrid,
 };
+use hashbrown::HashMap;
 use specs::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
 
 /// This systems sends new chunks to clients as well as changes to existing
@@ -31,7 +32,12 @@ impl<'a> System<'a> for Sys {
         timer.start();
 
         // Sync changed chunks
-        'chunk: for chunk_key in &terrain_changes.modified_chunks {
+        for chunk_key in &terrain_changes.modified_chunks {
+            let chunk = match terrain.get_key(*chunk_key) {
+                Some(chunk) => chunk,
+                None => break,
+            };
+            let mut packed = super::terrain::LazyPackedChunk::new(chunk);
             for (player, pos, client) in (&players, &positions, &mut clients).join() {
                 if player
                     .view_distance
@@ -40,21 +46,56 @@ impl<'a> System<'a> for Sys {
                 {
                     client.send_msg(ServerGeneral::TerrainChunkUpdate {
                         key: *chunk_key,
-                        chunk: Ok(Box::new(match terrain.get_key(*chunk_key) {
-                            Some(chunk) => chunk.clone(),
-                            None => break 'chunk,
-                        })),
+                        chunk: Ok(packed.get(client.chunk_encoding)),
                     });
                 }
             }
         }
 
-        // TODO: Don't send all changed blocks to all clients
-        // Sync changed blocks
-        let msg = ServerGeneral::TerrainBlockUpdates(terrain_changes.modified_blocks.clone());
-        for (player, client) in (&players, &mut clients).join() {
-            if player.view_distance.is_some() {
-                client.send_msg(msg.clone());
+        // Sync changed blocks, only to clients that have the chunks they belong to
+        if !terrain_changes.modified_blocks.is_empty() {
+            let mut chunk_blocks = HashMap::<_, Vec<_>>::new();
+            for (pos, block) in terrain_changes.modified_blocks.iter() {
+                chunk_blocks
+                    .entry(terrain.pos_key(*pos))
+                    .or_default()
+                    .push((*pos, *block));
+            }
+
+            // Clients that can't apply block changes get the whole chunks again
+            let mut packed_chunks = HashMap::new();
+            for (player, pos, client) in (&players, &positions, &mut clients).join() {
+                let vd = match player.view_distance {
+                    Some(vd) => vd,
+                    None => continue,
+                };
+                let in_vd = chunk_blocks
+                    .iter()
+                    .filter(|(key, _)| super::terrain::chunk_in_vd(pos.0, **key, &terrain, vd));
+                if client.capabilities.contains(&Capability::TerrainDeltas) {
+                    let blocks = in_vd
+                        .flat_map(|(_, blocks)| blocks.iter().copied())
+                        .collect::<Vec<_>>();
+                    if !blocks.is_empty() {
+                        client.send_msg(ServerGeneral::TerrainBlockUpdates(blocks));
+                    }
+                } else {
+                    for (key, _) in in_vd {
+                        if terrain_changes.modified_chunks.contains(key) {
+                            // Already sent above
+                            continue;
+                        }
+                        if let Some(chunk) = terrain.get_key(*key) {
+                            let packed = packed_chunks
+                                .entry(*key)
+                                .or_insert_with(|| super::terrain::LazyPackedChunk::new(chunk));
+                            client.send_msg(ServerGeneral::TerrainChunkUpdate {
+                                key: *key,
+                                chunk: Ok(packed.get(client.chunk_encoding)),
+                            });
+                        }
+                    }
+                }
             }
         }
 
@@ -0,0 +1,89 @@
+use super::SysTimer;
+use crate::{client::Client, Settings};
+use common::{
+    comp::Pos,
+    msg::{Capability, VoiceFrame, VoiceRelay, MAX_BYTES_VOICE_FRAME},
+    region::{regions_in_vd, RegionMap},
+    span,
+    sync::Uid,
+};
+use specs::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage};
+use tracing::{debug, warn};
+
+/// Most voice frames taken from one client per tick, so that a client can't
+/// flood the others. Frames of 20ms cover real time at 30 ticks per second.
+const MAX_FRAMES_PER_TICK: usize = 4;
+
+/// This system relays the voice frames of players to the players near them
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)] // TODO: Pending review in #587
+    type SystemData = (
+        ReadStorage<'a, Uid>,
+        ReadStorage<'a, Pos>,
+        WriteStorage<'a, Client>,
+        ReadExpect<'a, RegionMap>,
+        Read<'a, Settings>,
+        Write<'a, SysTimer<Self>>,
+    );
+
+    fn run(
+        &mut self,
+        (uids, positions, mut clients, region_map, settings, mut timer): Self::SystemData,
+    ) {
+        span!(_guard, "run", "voice::Sys::run");
+        timer.start();
+
+        let mut relays = Vec::new();
+        for (uid, pos, client) in (&uids, &positions, &mut clients).join() {
+            if client.in_game.is_none() || !client.capabilities.contains(&Capability::Voice) {
+                continue;
+            }
+            for _ in 0..MAX_FRAMES_PER_TICK {
+                match client.voice_stream.try_recv::<VoiceFrame>() {
+                    Ok(Some(frame)) if frame.data.len() <= MAX_BYTES_VOICE_FRAME => {
+                        relays.push(VoiceRelay {
+                            speaker: *uid,
+                            pos: pos.0,
+                            frame,
+                        })
+                    },
+                    Ok(Some(frame)) => {
+                        warn!(
+                            ?uid,
+                            len = frame.data.len(),
+                            "Voice frame is too large, dropping"
+                        )
+                    },
+                    Ok(None) => break,
+                    Err(e) => {
+                        debug!(?e, "got a network error with client while recv voice");
+                        client.network_error = true;
+                        break;
+                    },
+                }
+            }
+        }
+
+        // Only look at the regions within hearing range of the speaker
+        let max_distance_sqr = settings.voice_distance.powi(2);
+        for relay in &relays {
+            for key in regions_in_vd(relay.pos, settings.voice_distance) {
+                if let Some(region) = region_map.get(key) {
+                    for (_, uid, pos, client) in
+                        (region.entities(), &uids, &positions, &mut clients).join()
+                    {
+                        if *uid != relay.speaker
+                            && client.in_game.is_some()
+                            && pos.0.distance_squared(relay.pos) <= max_distance_sqr
+                        {
+                            client.send_voice(relay);
+                        }
+                    }
+                }
+            }
+        }
+
+        timer.end();
+    }
+}
@@ -4,16 +4,19 @@ use common::{
     comp::{Player, Pos, Waypoint, WaypointArea},
     msg::{Notification, ServerGeneral},
     span,
-    state::Time,
 };
-use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
+use hashbrown::HashSet;
+use specs::{Entities, Entity, Join, ReadStorage, System, Write, WriteStorage};
 
-/// Cooldown time (in seconds) for "Waypoint Saved" notifications
-const NOTIFY_TIME: f64 = 10.0;
-
-/// This system updates player waypoints
+/// This system lets players know when they come across a waypoint they can
+/// rest at. Waypoints are only saved once the player interacts with them.
 /// TODO: Make this faster by only considering local waypoints
-pub struct Sys;
+#[derive(Default)]
+pub struct Sys {
+    /// Players that are within the area of a waypoint, so that they're only
+    /// notified when entering it
+    at_waypoint: HashSet<Entity>,
+}
 impl<'a> System<'a> for Sys {
     #[allow(clippy::type_complexity)] // TODO: Pending review in #587
     type SystemData = (
@@ -21,15 +24,14 @@ impl<'a> System<'a> for Sys {
         ReadStorage<'a, Pos>,
         ReadStorage<'a, Player>,
         ReadStorage<'a, WaypointArea>,
-        WriteStorage<'a, Waypoint>,
+        ReadStorage<'a, Waypoint>,
         WriteStorage<'a, Client>,
-        Read<'a, Time>,
         Write<'a, SysTimer<Self>>,
     );
 
     fn run(
         &mut self,
-        (entities, positions, players, waypoint_areas, mut waypoints, mut clients, time, mut timer): Self::SystemData,
+        (entities, positions, players, waypoint_areas, waypoints, mut clients, mut timer): Self::SystemData,
     ) {
         span!(_guard, "run", "waypoint::Sys::run");
         timer.start();
@@ -37,18 +39,31 @@ impl<'a> System<'a> for Sys {
         for (entity, player_pos, _, client) in
             (&entities, &positions, &players, &mut clients).join()
         {
-            for (waypoint_pos, waypoint_area) in (&positions, &waypoint_areas).join() {
-                if player_pos.0.distance_squared(waypoint_pos.0) < waypoint_area.radius().powi(2) {
-                    if let Ok(wp_old) = waypoints.insert(entity, Waypoint::new(player_pos.0, *time))
-                    {
-                        if wp_old.map_or(true, |w| w.elapsed(*time) > NOTIFY_TIME) {
-                            client
-                                .send_msg(ServerGeneral::Notification(Notification::WaypointSaved));
+            let area = (&positions, &waypoint_areas)
+                .join()
+                .find(|(pos, area)| player_pos.0.distance_squared(pos.0) < area.radius().powi(2));
+
+            match area {
+                Some((waypoint_pos, waypoint_area)) => {
+                    if self.at_waypoint.insert(entity) {
+                        // Don't bother players about the waypoint they already rest at
+                        let is_current = waypoints.get(entity).map_or(false, |wp| {
+                            wp.get_pos().distance_squared(waypoint_pos.0)
+                                < waypoint_area.radius().powi(2)
+                        });
+                        if !is_current {
+                            client.send_msg(ServerGeneral::Notification(
+                                Notification::WaypointDiscovered,
+                            ));
                         }
                     }
-                }
+                },
+                None => {
+                    self.at_waypoint.remove(&entity);
+                },
             }
         }
+        self.at_waypoint.retain(|entity| entities.is_alive(*entity));
 
         timer.end();
     }
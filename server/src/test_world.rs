@@ -1,7 +1,9 @@
 use common::{
     generation::{ChunkSupplement, EntityInfo},
+    msg::{RegionOverview, WorldOverview},
     terrain::{
-        Block, BlockKind, MapSizeLg, SpriteKind, TerrainChunk, TerrainChunkMeta, TerrainChunkSize,
+        BiomeKind, Block, BlockKind, MapSizeLg, SpriteKind, TerrainChunk, TerrainChunkMeta,
+        TerrainChunkSize,
     },
     vol::{ReadVol, RectVolSize, WriteVol},
 };
@@ -43,6 +45,19 @@ impl World {
     #[inline(always)]
     pub const fn map_size_lg(&self) -> MapSizeLg { DEFAULT_WORLD_CHUNKS_LG }
 
+    pub fn get_overview(&self) -> WorldOverview {
+        let size = WorldOverview::regions_in(self.map_size_lg().vec());
+        WorldOverview::new(size, vec![
+            RegionOverview {
+                biome: BiomeKind::Grassland,
+                alt: 256,
+            };
+            (size.x * size.y) as usize
+        ])
+    }
+
+    pub fn get_biome(&self, _wpos: Vec2<i32>) -> Option<BiomeKind> { Some(BiomeKind::Grassland) }
+
     pub fn generate_chunk(
         &self,
         _index: IndexRef,
@@ -43,6 +43,7 @@ fn main() {
         lantern: None,
         head: None,
         tabard: None,
+        appearance: Default::default(),
     };
 
     // Setup scene (using the character selection screen `Scene`)
@@ -1,11 +1,8 @@
 use super::{
     super::{vek::*, Animation},
-    CharacterSkeleton, SkeletonAttr,
-};
-use common::{
-    comp::item::{Hands, ToolKind},
-    states::utils::StageSection,
+    offhand_scale, CharacterSkeleton, SkeletonAttr,
 };
+use common::{comp::item::ToolKind, states::utils::StageSection};
 use std::f32::consts::PI;
 
 pub struct AlphaAnimation;
@@ -448,13 +445,7 @@ impl Animation for AlphaAnimation {
         next.control_l.scale = Vec3::one();
         next.control_r.scale = Vec3::one();
 
-        next.second.scale = match (
-            active_tool_kind.map(|tk| tk.hands()),
-            second_tool_kind.map(|tk| tk.hands()),
-        ) {
-            (Some(Hands::OneHand), Some(Hands::OneHand)) => Vec3::one(),
-            (_, _) => Vec3::zero(),
-        };
+        next.second.scale = offhand_scale(active_tool_kind.as_ref(), second_tool_kind.as_ref());
 
         next
     }
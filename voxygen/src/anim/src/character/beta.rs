@@ -1,11 +1,8 @@
 use super::{
     super::{vek::*, Animation},
-    CharacterSkeleton, SkeletonAttr,
-};
-use common::{
-    comp::item::{Hands, ToolKind},
-    states::utils::StageSection,
+    offhand_scale, CharacterSkeleton, SkeletonAttr,
 };
+use common::{comp::item::ToolKind, states::utils::StageSection};
 
 pub struct BetaAnimation;
 
@@ -256,13 +253,7 @@ impl Animation for BetaAnimation {
 
         next.control_r.scale = Vec3::one();
 
-        next.second.scale = match (
-            active_tool_kind.map(|tk| tk.hands()),
-            second_tool_kind.map(|tk| tk.hands()),
-        ) {
-            (Some(Hands::OneHand), Some(Hands::OneHand)) => Vec3::one(),
-            (_, _) => Vec3::zero(),
-        };
+        next.second.scale = offhand_scale(active_tool_kind.as_ref(), second_tool_kind.as_ref());
 
         next
     }
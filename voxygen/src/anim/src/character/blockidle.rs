@@ -1,8 +1,8 @@
 use super::{
     super::{vek::*, Animation},
-    CharacterSkeleton, SkeletonAttr,
+    offhand_scale, CharacterSkeleton, SkeletonAttr,
 };
-use common::comp::item::{Hands, ToolKind};
+use common::comp::item::ToolKind;
 use std::{f32::consts::PI, ops::Mul};
 
 pub struct Input {
@@ -307,13 +307,7 @@ impl Animation for BlockIdleAnimation {
         next.control_l.scale = Vec3::one();
         next.control_r.scale = Vec3::one();
 
-        next.second.scale = match (
-            active_tool_kind.map(|tk| tk.hands()),
-            second_tool_kind.map(|tk| tk.hands()),
-        ) {
-            (Some(Hands::OneHand), Some(Hands::OneHand)) => Vec3::one(),
-            (_, _) => Vec3::zero(),
-        };
+        next.second.scale = offhand_scale(active_tool_kind.as_ref(), second_tool_kind.as_ref());
 
         next
     }
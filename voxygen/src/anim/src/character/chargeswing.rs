@@ -1,11 +1,8 @@
 use super::{
     super::{vek::*, Animation},
-    CharacterSkeleton, SkeletonAttr,
-};
-use common::{
-    comp::item::{Hands, ToolKind},
-    states::utils::StageSection,
+    offhand_scale, CharacterSkeleton, SkeletonAttr,
 };
+use common::{comp::item::ToolKind, states::utils::StageSection};
 use std::f32::consts::PI;
 pub struct ChargeswingAnimation;
 
@@ -179,13 +176,7 @@ impl Animation for ChargeswingAnimation {
             }
         }
 
-        next.second.scale = match (
-            active_tool_kind.map(|tk| tk.hands()),
-            second_tool_kind.map(|tk| tk.hands()),
-        ) {
-            (Some(Hands::OneHand), Some(Hands::OneHand)) => Vec3::one(),
-            (_, _) => Vec3::zero(),
-        };
+        next.second.scale = offhand_scale(active_tool_kind.as_ref(), second_tool_kind.as_ref());
 
         //next.torso.position = Vec3::new(0.0, 0.0, 0.0) * skeleton_attr.scaler;
         //next.torso.orientation = Quaternion::rotation_z(0.0);
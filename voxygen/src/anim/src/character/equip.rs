@@ -1,5 +1,5 @@
-use super::{super::Animation, CharacterSkeleton, SkeletonAttr};
-use common::comp::item::{Hands, ToolKind};
+use super::{super::Animation, offhand_scale, CharacterSkeleton, SkeletonAttr};
+use common::comp::item::ToolKind;
 use std::{f32::consts::PI, ops::Mul};
 
 use super::super::vek::*;
@@ -224,13 +224,7 @@ impl Animation for EquipAnimation {
             next.torso.position = Vec3::new(0.0, 0.0, 0.0) * skeleton_attr.scaler;
         }
 
-        next.second.scale = match (
-            active_tool_kind.map(|tk| tk.hands()),
-            second_tool_kind.map(|tk| tk.hands()),
-        ) {
-            (Some(Hands::OneHand), Some(Hands::OneHand)) => Vec3::one(),
-            (_, _) => Vec3::zero(),
-        };
+        next.second.scale = offhand_scale(active_tool_kind.as_ref(), second_tool_kind.as_ref());
 
         next
     }
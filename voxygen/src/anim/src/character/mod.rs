@@ -23,6 +23,7 @@ pub mod sit;
 pub mod sneak;
 pub mod spin;
 pub mod spinmelee;
+pub mod stagger;
 pub mod stand;
 pub mod swim;
 pub mod swimwield;
@@ -37,12 +38,12 @@ pub use self::{
     jump::JumpAnimation, leapmelee::LeapAnimation, repeater::RepeaterAnimation,
     roll::RollAnimation, run::RunAnimation, shockwave::ShockwaveAnimation, shoot::ShootAnimation,
     sit::SitAnimation, sneak::SneakAnimation, spin::SpinAnimation, spinmelee::SpinMeleeAnimation,
-    stand::StandAnimation, swim::SwimAnimation, swimwield::SwimWieldAnimation,
-    wield::WieldAnimation,
+    stagger::StaggerAnimation, stand::StandAnimation, swim::SwimAnimation,
+    swimwield::SwimWieldAnimation, wield::WieldAnimation,
 };
 
 use super::{make_bone, vek::*, FigureBoneData, Skeleton};
-use common::comp;
+use common::comp::{self, item::ToolKind, Pairing};
 use core::convert::TryFrom;
 
 pub type Body = comp::humanoid::Body;
@@ -224,3 +225,15 @@ impl<'a> From<&'a Body> for SkeletonAttr {
         }
     }
 }
+
+/// Scale of the second item, which is only shown in the offhand when it's
+/// wielded together with the active item
+pub fn offhand_scale(
+    active_tool_kind: Option<&ToolKind>,
+    second_tool_kind: Option<&ToolKind>,
+) -> Vec3<f32> {
+    match (active_tool_kind, second_tool_kind) {
+        (Some(main), Some(offhand)) if Pairing::of(main, offhand).is_some() => Vec3::one(),
+        _ => Vec3::zero(),
+    }
+}
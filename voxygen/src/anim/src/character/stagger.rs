@@ -0,0 +1,50 @@
+use super::{
+    super::{vek::*, Animation},
+    CharacterSkeleton, SkeletonAttr,
+};
+use std::f32::consts::PI;
+
+/// Recoil from a hit, played on top of the movement pose. `intensity` goes from
+/// a light flinch to reeling from a heavy blow.
+pub struct StaggerAnimation;
+
+impl Animation for StaggerAnimation {
+    type Dependency = (f32, f64);
+    type Skeleton = CharacterSkeleton;
+
+    #[cfg(feature = "use-dyn-lib")]
+    const UPDATE_FN: &'static [u8] = b"character_stagger\0";
+
+    #[cfg_attr(feature = "be-dyn-lib", export_name = "character_stagger")]
+    fn update_skeleton_inner(
+        skeleton: &Self::Skeleton,
+        (intensity, _global_time): Self::Dependency,
+        anim_time: f64,
+        rate: &mut f32,
+        _skeleton_attr: &SkeletonAttr,
+    ) -> Self::Skeleton {
+        *rate = 1.0;
+        let mut next = (*skeleton).clone();
+
+        // Snaps back on impact, then slowly recovers
+        let impact = (anim_time as f32 * 12.0).min(PI / 2.0).sin();
+        let recover = 1.0 / (1.0 + anim_time as f32 * 4.0);
+        let recoil = impact * recover * intensity;
+        // Heavy hits leave the character swaying
+        let sway = (anim_time as f32 * 6.0).sin() * recover * (intensity - 0.5).max(0.0);
+
+        next.head.orientation = next.head.orientation
+            * Quaternion::rotation_x(recoil * 0.5)
+            * Quaternion::rotation_y(sway * 0.4);
+        next.chest.position += Vec3::new(0.0, recoil * -1.5, recoil * -0.5);
+        next.chest.orientation = next.chest.orientation
+            * Quaternion::rotation_x(recoil * 0.4)
+            * Quaternion::rotation_y(sway * 0.3);
+        next.belt.orientation = next.belt.orientation * Quaternion::rotation_x(recoil * -0.2);
+        next.shorts.orientation = next.shorts.orientation * Quaternion::rotation_x(recoil * -0.3);
+        next.hand_l.position += Vec3::new(-recoil * 1.5, recoil * -1.0, recoil * 2.0);
+        next.hand_r.position += Vec3::new(recoil * 1.5, recoil * -1.0, recoil * 2.0);
+
+        next
+    }
+}
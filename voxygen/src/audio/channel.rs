@@ -18,9 +18,13 @@
 
 use crate::audio::{
     fader::{FadeDirection, Fader},
-    Listener,
+    Listener, VOICE_SAMPLE_RATE,
+};
+use rodio::{buffer::SamplesBuffer, Device, Sample, Sink, Source, SpatialSink};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
 };
-use rodio::{Device, Sample, Sink, Source, SpatialSink};
 use vek::*;
 
 #[derive(PartialEq, Clone, Copy)]
@@ -134,6 +138,34 @@ impl MusicChannel {
     }
 }
 
+/// A StingerChannel uses a non-positional audio sink to play short musical cues
+/// over the soundtrack. Cues played while another one is playing are queued.
+pub struct StingerChannel {
+    sink: Sink,
+}
+
+impl StingerChannel {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            sink: Sink::new(device),
+        }
+    }
+
+    pub fn play<S>(&mut self, source: S)
+    where
+        S: Source + Send + 'static,
+        S::Item: Sample,
+        S::Item: Send,
+        <S as std::iter::Iterator>::Item: std::fmt::Debug,
+    {
+        self.sink.append(source);
+    }
+
+    pub fn set_volume(&mut self, volume: f32) { self.sink.set_volume(volume); }
+
+    pub fn is_done(&self) -> bool { self.sink.empty() }
+}
+
 /// An SfxChannel uses a positional audio sink, and is designed for short-lived
 /// audio which can be spatially controlled, but does not need control over
 /// playback or fading/transitions
@@ -179,3 +211,124 @@ impl SfxChannel {
             .set_right_ear_position(listener.ear_right_rpos.into_array());
     }
 }
+
+/// Longest stretch of voice audio waiting to be played for a single player,
+/// in samples. Anything beyond it (e.g. a burst of frames after a lag spike)
+/// would only add delay, so the oldest audio is dropped to catch up.
+const MAX_QUEUED_VOICE_SAMPLES: usize = VOICE_SAMPLE_RATE as usize / 5;
+
+/// How far ahead of playback voice audio is handed to the sink. Audio that
+/// was appended to a sink can't be dropped anymore, so this is kept just long
+/// enough to bridge the gaps between frames.
+const VOICE_SINK_AHEAD: Duration = Duration::from_millis(60);
+
+/// Decoded voice audio of a player waiting to be played
+#[derive(Default)]
+struct VoiceQueue {
+    frames: VecDeque<Vec<f32>>,
+    queued_samples: usize,
+    /// When the audio already handed to the sink is done playing
+    sink_until: Option<Instant>,
+}
+
+impl VoiceQueue {
+    fn push(&mut self, samples: Vec<f32>) {
+        self.queued_samples += samples.len();
+        self.frames.push_back(samples);
+        while self.queued_samples > MAX_QUEUED_VOICE_SAMPLES {
+            match self.frames.pop_front() {
+                Some(dropped) => self.queued_samples -= dropped.len(),
+                None => break,
+            }
+        }
+    }
+
+    /// The next frame to hand to the sink, if the sink is about to run out
+    fn pop_due(&mut self, now: Instant) -> Option<Vec<f32>> {
+        let sink_until = self.sink_until.filter(|t| *t > now).unwrap_or(now);
+        if sink_until > now + VOICE_SINK_AHEAD {
+            return None;
+        }
+        let samples = self.frames.pop_front()?;
+        self.queued_samples -= samples.len();
+        let duration =
+            Duration::from_micros(samples.len() as u64 * 1_000_000 / VOICE_SAMPLE_RATE as u64);
+        self.sink_until = Some(sink_until + duration);
+        Some(samples)
+    }
+}
+
+/// A VoiceChannel plays the voice chat audio of one player at their position.
+/// Audio is queued on the channel and fed to the sink as it plays, so that
+/// the queue stays bounded.
+pub struct VoiceChannel {
+    channel: SfxChannel,
+    queue: VoiceQueue,
+}
+
+impl VoiceChannel {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            channel: SfxChannel::new(device),
+            queue: VoiceQueue::default(),
+        }
+    }
+
+    pub fn push(&mut self, samples: Vec<f32>) {
+        self.queue.push(samples);
+        self.maintain();
+    }
+
+    /// Hand the queued audio that is about to be played to the sink
+    pub fn maintain(&mut self) {
+        let now = Instant::now();
+        while let Some(samples) = self.queue.pop_due(now) {
+            self.channel
+                .play(SamplesBuffer::new(1, VOICE_SAMPLE_RATE, samples));
+        }
+    }
+
+    pub fn set_volume(&mut self, volume: f32) { self.channel.set_volume(volume); }
+
+    pub fn set_pos(&mut self, pos: Vec3<f32>) { self.channel.set_pos(pos); }
+
+    pub fn update(&mut self, listener: &Listener) { self.channel.update(listener); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 20ms frame filled with `value`
+    fn frame(value: f32) -> Vec<f32> { vec![value; VOICE_SAMPLE_RATE as usize / 50] }
+
+    #[test]
+    fn voice_queue_drops_the_oldest_frames() {
+        let mut queue = VoiceQueue::default();
+        for i in 0..100 {
+            queue.push(frame(i as f32));
+        }
+
+        // 200ms worth of the newest frames are kept
+        assert_eq!(queue.frames.len(), 10);
+        assert_eq!(queue.queued_samples, MAX_QUEUED_VOICE_SAMPLES);
+        assert_eq!(queue.frames.front().map(|f| f[0]), Some(90.0));
+    }
+
+    #[test]
+    fn voice_queue_feeds_the_sink_ahead_of_playback() {
+        let mut queue = VoiceQueue::default();
+        for i in 0..10 {
+            queue.push(frame(i as f32));
+        }
+
+        // Frames are handed out until more than 60ms are buffered in the sink
+        let now = Instant::now();
+        let due = std::iter::from_fn(|| queue.pop_due(now)).count();
+        assert_eq!(due, 4);
+
+        // Once that has played, the next frames become due
+        let later = now + Duration::from_millis(40);
+        assert_eq!(queue.pop_due(later).map(|f| f[0]), Some(4.0));
+    }
+}
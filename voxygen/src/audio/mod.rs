@@ -5,17 +5,27 @@ pub mod fader;
 pub mod music;
 pub mod sfx;
 pub mod soundcache;
+pub mod stinger;
+#[cfg(feature = "voice")] pub mod voice;
 
-use channel::{MusicChannel, MusicChannelTag, SfxChannel};
+use channel::{MusicChannel, MusicChannelTag, SfxChannel, StingerChannel, VoiceChannel};
 use fader::Fader;
 use soundcache::SoundCache;
 use tracing::warn;
 
-use common::assets;
+use common::{assets, sync::Uid};
 use cpal::traits::DeviceTrait;
+use hashbrown::HashMap;
 use rodio::{source::Source, Decoder, Device};
 use vek::*;
 
+/// Sample rate of decoded voice chat audio
+pub const VOICE_SAMPLE_RATE: u32 = 48_000;
+
+/// How fast the soundtrack is ducked and restored around stingers, in volume
+/// per second
+const DUCK_SPEED: f32 = 2.0;
+
 #[derive(Default, Clone)]
 pub struct Listener {
     pos: Vec3<f32>,
@@ -37,9 +47,17 @@ pub struct AudioFrontend {
 
     music_channels: Vec<MusicChannel>,
     sfx_channels: Vec<SfxChannel>,
+    stinger_channel: Option<StingerChannel>,
+    /// A positional channel for each player currently heard in voice chat
+    voice_channels: HashMap<Uid, VoiceChannel>,
 
     sfx_volume: f32,
     music_volume: f32,
+    voice_volume: f32,
+    /// Factor currently applied to the volume of the soundtrack
+    music_duck: f32,
+    /// Factor the soundtrack is ducked to while a stinger plays
+    stinger_duck: f32,
 
     listener: Listener,
 }
@@ -53,6 +71,7 @@ impl AudioFrontend {
         if let Some(audio_device) = &audio_device {
             sfx_channels.resize_with(max_sfx_channels, || SfxChannel::new(&audio_device));
         }
+        let stinger_channel = audio_device.as_ref().map(StingerChannel::new);
 
         Self {
             device,
@@ -61,8 +80,13 @@ impl AudioFrontend {
             sound_cache: SoundCache::default(),
             music_channels: Vec::new(),
             sfx_channels,
+            stinger_channel,
+            voice_channels: HashMap::new(),
             sfx_volume: 1.0,
             music_volume: 1.0,
+            voice_volume: 1.0,
+            music_duck: 1.0,
+            stinger_duck: 1.0,
 
             listener: Listener::default(),
         }
@@ -77,21 +101,51 @@ impl AudioFrontend {
             sound_cache: SoundCache::default(),
             music_channels: Vec::new(),
             sfx_channels: Vec::new(),
+            stinger_channel: None,
+            voice_channels: HashMap::new(),
             sfx_volume: 1.0,
             music_volume: 1.0,
+            voice_volume: 1.0,
+            music_duck: 1.0,
+            stinger_duck: 1.0,
             listener: Listener::default(),
         }
     }
 
-    /// Drop any unused music channels, and update their faders
+    /// Drop any unused music channels, update their faders and the ducking of
+    /// the soundtrack, and feed the queued voice chat audio to its channels
     pub fn maintain(&mut self, dt: f32) {
         self.music_channels.retain(|c| !c.is_done());
 
         for channel in self.music_channels.iter_mut() {
             channel.maintain(dt);
         }
+
+        for channel in self.voice_channels.values_mut() {
+            channel.maintain();
+        }
+
+        let duck_target = match &self.stinger_channel {
+            Some(channel) if !channel.is_done() => self.stinger_duck,
+            _ => 1.0,
+        };
+        if (self.music_duck - duck_target).abs() > f32::EPSILON {
+            let step = DUCK_SPEED * dt;
+            self.music_duck = if self.music_duck < duck_target {
+                (self.music_duck + step).min(duck_target)
+            } else {
+                (self.music_duck - step).max(duck_target)
+            };
+
+            let volume = self.ducked_music_volume();
+            for channel in self.music_channels.iter_mut() {
+                channel.set_volume(volume);
+            }
+        }
     }
 
+    fn ducked_music_volume(&self) -> f32 { self.music_volume * self.music_duck }
+
     fn get_sfx_channel(&mut self) -> Option<&mut SfxChannel> {
         if self.audio_device.is_some() {
             if let Some(channel) = self.sfx_channels.iter_mut().find(|c| c.is_done()) {
@@ -113,10 +167,11 @@ impl AudioFrontend {
         &mut self,
         next_channel_tag: MusicChannelTag,
     ) -> Option<&mut MusicChannel> {
+        let volume = self.ducked_music_volume();
         if let Some(audio_device) = &self.audio_device {
             if self.music_channels.is_empty() {
                 let mut next_music_channel = MusicChannel::new(&audio_device);
-                next_music_channel.set_volume(self.music_volume);
+                next_music_channel.set_volume(self.ducked_music_volume());
 
                 self.music_channels.push(next_music_channel);
             } else {
@@ -124,11 +179,11 @@ impl AudioFrontend {
 
                 if existing_channel.get_tag() != next_channel_tag {
                     // Fade the existing channel out. It will be removed when the fade completes.
-                    existing_channel.set_fader(Fader::fade_out(2.0, self.music_volume));
+                    existing_channel.set_fader(Fader::fade_out(2.0, volume));
 
                     let mut next_music_channel = MusicChannel::new(&audio_device);
 
-                    next_music_channel.set_fader(Fader::fade_in(12.0, self.music_volume));
+                    next_music_channel.set_fader(Fader::fade_in(12.0, volume));
 
                     self.music_channels.push(next_music_channel);
                 }
@@ -164,6 +219,39 @@ impl AudioFrontend {
         }
     }
 
+    /// Play a short musical cue over the soundtrack, lowering the volume of
+    /// the soundtrack by the `duck` factor until the cue ends
+    pub fn play_stinger(&mut self, sound: &str, duck: f32) {
+        if self.music_enabled() {
+            let sound = self.sound_cache.load_sound(sound);
+            if let Some(channel) = &mut self.stinger_channel {
+                channel.set_volume(self.music_volume);
+                channel.play(sound);
+                self.stinger_duck = duck.max(0.0).min(1.0);
+            }
+        }
+    }
+
+    /// Queue decoded voice chat audio of a player, played at the position they
+    /// spoke from. Each player gets a channel of their own so that several can
+    /// talk at once.
+    pub fn play_voice(&mut self, speaker: Uid, samples: Vec<f32>, pos: Vec3<f32>) {
+        if let Some(audio_device) = &self.audio_device {
+            let volume = self.voice_volume;
+            let channel = self.voice_channels.entry(speaker).or_insert_with(|| {
+                let mut channel = VoiceChannel::new(audio_device);
+                channel.set_volume(volume);
+                channel
+            });
+            channel.set_pos(pos);
+            channel.update(&self.listener);
+            channel.push(samples);
+        }
+    }
+
+    /// Stop playing the voice of a player and drop their channel
+    pub fn stop_voice(&mut self, speaker: Uid) { self.voice_channels.remove(&speaker); }
+
     pub fn set_listener_pos(&mut self, pos: Vec3<f32>, ori: Vec3<f32>) {
         self.listener.pos = pos;
         self.listener.ori = ori.normalized();
@@ -177,6 +265,9 @@ impl AudioFrontend {
                 channel.update(&self.listener);
             }
         }
+        for channel in self.voice_channels.values_mut() {
+            channel.update(&self.listener);
+        }
     }
 
     /// Switches the playing music to the title music, which is pinned to a
@@ -200,6 +291,8 @@ impl AudioFrontend {
 
     pub fn get_music_volume(&self) -> f32 { self.music_volume }
 
+    pub fn get_voice_volume(&self) -> f32 { self.voice_volume }
+
     pub fn sfx_enabled(&self) -> bool { self.sfx_volume > 0.0 }
 
     pub fn music_enabled(&self) -> bool { self.music_volume > 0.0 }
@@ -212,10 +305,22 @@ impl AudioFrontend {
         }
     }
 
+    pub fn set_voice_volume(&mut self, voice_volume: f32) {
+        self.voice_volume = voice_volume;
+
+        for channel in self.voice_channels.values_mut() {
+            channel.set_volume(voice_volume);
+        }
+    }
+
     pub fn set_music_volume(&mut self, music_volume: f32) {
         self.music_volume = music_volume;
 
+        let volume = self.ducked_music_volume();
         for channel in self.music_channels.iter_mut() {
+            channel.set_volume(volume);
+        }
+        if let Some(channel) = &mut self.stinger_channel {
             channel.set_volume(music_volume);
         }
     }
@@ -71,7 +71,9 @@ impl EventMapper for CombatEventMapper {
                 let mapped_event = Self::map_event(character, state, loadout);
 
                 // Check for SFX config entry for this movement
-                if Self::should_emit(state, triggers.get_key_value(&mapped_event)) {
+                if !Self::sent_by_server(&mapped_event)
+                    && Self::should_emit(state, triggers.get_key_value(&mapped_event))
+                {
                     sfx_emitter.emit(SfxEventItem::new(mapped_event.clone(), Some(pos.0), None));
 
                     state.time = Instant::now();
@@ -129,6 +131,18 @@ impl CombatEventMapper {
         }
     }
 
+    /// Melee swings come in as sounds from the server, see
+    /// `SfxMgr::handle_sound`
+    fn sent_by_server(event: &SfxEvent) -> bool {
+        matches!(
+            event,
+            SfxEvent::Attack(
+                CharacterAbilityType::BasicMelee | CharacterAbilityType::ComboMelee(..),
+                _
+            )
+        )
+    }
+
     fn map_event(
         character_state: &CharacterState,
         previous_state: &PreviousEntityState,
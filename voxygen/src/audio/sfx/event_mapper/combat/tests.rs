@@ -14,9 +14,7 @@ fn maps_wield_while_equipping() {
 
     loadout.active_item = Some(ItemConfig {
         item: Item::new_from_asset_expect("common.items.weapons.axe.starter_axe"),
-        ability1: None,
-        ability2: None,
-        ability3: None,
+        abilities: Vec::new(),
         block_ability: None,
         dodge_ability: None,
     });
@@ -42,9 +40,7 @@ fn maps_unwield() {
 
     loadout.active_item = Some(ItemConfig {
         item: Item::new_from_asset_expect("common.items.weapons.bow.starter_bow"),
-        ability1: None,
-        ability2: None,
-        ability3: None,
+        abilities: Vec::new(),
         block_ability: None,
         dodge_ability: None,
     });
@@ -68,9 +64,7 @@ fn maps_basic_melee() {
 
     loadout.active_item = Some(ItemConfig {
         item: Item::new_from_asset_expect("common.items.weapons.axe.starter_axe"),
-        ability1: None,
-        ability2: None,
-        ability3: None,
+        abilities: Vec::new(),
         block_ability: None,
         dodge_ability: None,
     });
@@ -105,9 +99,7 @@ fn matches_ability_stage() {
 
     loadout.active_item = Some(ItemConfig {
         item: Item::new_from_asset_expect("common.items.weapons.sword.starter_sword"),
-        ability1: None,
-        ability2: None,
-        ability3: None,
+        abilities: Vec::new(),
         block_ability: None,
         dodge_ability: None,
     });
@@ -165,9 +157,7 @@ fn ignores_different_ability_stage() {
 
     loadout.active_item = Some(ItemConfig {
         item: Item::new_from_asset_expect("common.items.weapons.sword.starter_sword"),
-        ability1: None,
-        ability2: None,
-        ability3: None,
+        abilities: Vec::new(),
         block_ability: None,
         dodge_ability: None,
     });
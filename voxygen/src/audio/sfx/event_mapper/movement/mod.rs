@@ -78,8 +78,11 @@ impl EventMapper for MovementEventMapper {
                     _ => SfxEvent::Idle, // Ignore fish, etc...
                 };
 
-                // Check for SFX config entry for this movement
-                if Self::should_emit(state, triggers.get_key_value(&mapped_event)) {
+                // Check for SFX config entry for this movement. Footsteps come in as
+                // sounds from the server, see `SfxMgr::handle_sound`
+                if mapped_event != SfxEvent::Run
+                    && Self::should_emit(state, triggers.get_key_value(&mapped_event))
+                {
                     sfx_emitter.emit(SfxEventItem::new(
                         mapped_event.clone(),
                         Some(pos.0),
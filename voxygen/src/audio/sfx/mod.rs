@@ -88,7 +88,7 @@ use common::{
     assets,
     comp::{
         item::{ItemKind, ToolCategory},
-        object, Body, CharacterAbilityType, InventoryUpdateEvent,
+        object, Body, CharacterAbilityType, InventoryUpdateEvent, Sound, SoundKind,
     },
     event::EventBus,
     outcome::Outcome,
@@ -176,7 +176,13 @@ impl From<&InventoryUpdateEvent> for SfxEvent {
                     _ => SfxEvent::Inventory(SfxInventoryEvent::Collected),
                 }
             },
-            InventoryUpdateEvent::CollectFailed => {
+            InventoryUpdateEvent::Cooked { .. } | InventoryUpdateEvent::Brewed { .. } => {
+                SfxEvent::Inventory(SfxInventoryEvent::Collected)
+            },
+            InventoryUpdateEvent::CollectFailed
+            | InventoryUpdateEvent::CraftFailed(_)
+            | InventoryUpdateEvent::BrewFailed(_)
+            | InventoryUpdateEvent::EquipFailed(_) => {
                 SfxEvent::Inventory(SfxInventoryEvent::CollectFailed)
             },
             InventoryUpdateEvent::Consumed(consumable) => {
@@ -252,26 +258,56 @@ impl SfxMgr {
                 _ => cam_pos,
             };
 
-            if let Some(item) = self.triggers.get_trigger(&event.sfx) {
-                let sfx_file = match item.files.len() {
-                    0 => {
-                        debug!("Sfx event {:?} is missing audio file.", event.sfx);
-                        "voxygen.audio.sfx.placeholder"
-                    },
-                    1 => item
-                        .files
-                        .last()
-                        .expect("Failed to determine sound file for this trigger item."),
-                    _ => {
-                        let rand_step = rand::random::<usize>() % item.files.len();
-                        &item.files[rand_step]
-                    },
-                };
+            self.play_trigger(audio, &event.sfx, position, event.vol);
+        }
+    }
 
-                audio.play_sfx(sfx_file, position, event.vol);
-            } else {
-                debug!("Missing sfx trigger config for sfx event. {:?}", event.sfx);
-            }
+    /// Plays a sound the server sent, at the place it was made
+    pub fn handle_sound(&mut self, sound: &Sound, audio: &mut AudioFrontend) {
+        if !audio.sfx_enabled() {
+            return;
+        }
+
+        match &sound.kind {
+            SoundKind::Footstep => self.play_trigger(audio, &SfxEvent::Run, sound.pos, None),
+            SoundKind::Swing { ability, tool } => {
+                self.play_trigger(audio, &SfxEvent::Attack(*ability, *tool), sound.pos, None)
+            },
+            SoundKind::Explosion { power } => audio.play_sfx(
+                // TODO: from sfx config?
+                "voxygen.audio.sfx.explosion",
+                sound.pos,
+                Some((power.abs() / 2.5).min(1.5)),
+            ),
+        }
+    }
+
+    fn play_trigger(
+        &self,
+        audio: &mut AudioFrontend,
+        sfx: &SfxEvent,
+        pos: Vec3<f32>,
+        vol: Option<f32>,
+    ) {
+        if let Some(item) = self.triggers.get_trigger(sfx) {
+            let sfx_file = match item.files.len() {
+                0 => {
+                    debug!("Sfx event {:?} is missing audio file.", sfx);
+                    "voxygen.audio.sfx.placeholder"
+                },
+                1 => item
+                    .files
+                    .last()
+                    .expect("Failed to determine sound file for this trigger item."),
+                _ => {
+                    let rand_step = rand::random::<usize>() % item.files.len();
+                    &item.files[rand_step]
+                },
+            };
+
+            audio.play_sfx(sfx_file, pos, vol);
+        } else {
+            debug!("Missing sfx trigger config for sfx event. {:?}", sfx);
         }
     }
 
@@ -281,14 +317,8 @@ impl SfxMgr {
         }
 
         match outcome {
-            Outcome::Explosion { pos, power, .. } => {
-                audio.play_sfx(
-                    // TODO: from sfx config?
-                    "voxygen.audio.sfx.explosion",
-                    *pos,
-                    Some((power.abs() / 2.5).min(1.5)),
-                );
-            },
+            // Played from the sound the server sends along
+            Outcome::Explosion { .. } => {},
             Outcome::ProjectileShot { pos, body, .. } => {
                 // TODO: from sfx config?
                 match body {
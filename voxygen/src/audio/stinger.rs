@@ -0,0 +1,234 @@
+//! Handles musical stingers, short cues played over the soundtrack when
+//! something notable happens to the player
+//!
+//! Gameplay code emits an [`AudioEvent`](enum.AudioEvent.html) on the
+//! `EventBus<AudioEvent>` of the ECS, and the stinger configured for it is
+//! played at the end of the frame. While a stinger plays, the soundtrack is
+//! ducked so that the cue can be heard clearly.
+//!
+//! Stingers are configured in `/assets/voxygen/audio/stingers.ron`, which maps
+//! each event to the `.wav` files to choose from and the factor the volume of
+//! the soundtrack is multiplied by while it plays:
+//! ```text
+//! LevelUp: (
+//!     files: [
+//!         "voxygen.audio.stingers.level_up",
+//!     ],
+//!     duck: 0.3,
+//! ),
+//! ```
+//!
+//! Stingers are music, so they are muted with the soundtrack. Sounds that give
+//! feedback, such as the level up sound, stay on the SFX channels.
+//!
+//! If there are errors while reading or deserialising the configuration file, a
+//! warning is logged and stingers will be disabled.
+use crate::{audio::AudioFrontend, scene::SceneData};
+use common::{
+    assets,
+    combat::CombatEvent,
+    comp::{Body, Stats},
+    event::EventBus,
+    state::State,
+    sync::Uid,
+};
+use hashbrown::HashMap;
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use specs::{Join, WorldExt};
+use tracing::warn;
+
+/// Something that happened to the player which is accompanied by music
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum AudioEvent {
+    LevelUp,
+    /// The player dealt the killing blow to something
+    Kill,
+    Death,
+    /// A boss nearby lost enough health to enter its next phase
+    BossPhase,
+    /// Not emitted yet, there are no quests to complete
+    QuestComplete,
+}
+
+/// Fractions of their health below which bosses enter their next phase
+const BOSS_PHASES: [f32; 2] = [2.0 / 3.0, 1.0 / 3.0];
+
+/// The phase a boss with `stats` is in, starting at 0
+fn boss_phase(stats: &Stats) -> usize {
+    let fraction = stats.health.current() as f32 / stats.health.maximum().max(1) as f32;
+    BOSS_PHASES
+        .iter()
+        .filter(|threshold| fraction < **threshold)
+        .count()
+}
+
+#[derive(Debug, Deserialize)]
+struct StingerItem {
+    files: Vec<String>,
+    /// Factor applied to the volume of the soundtrack while the stinger plays
+    duck: f32,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StingerCollection(HashMap<AudioEvent, StingerItem>);
+
+pub struct StingerMgr {
+    stingers: StingerCollection,
+    /// The phase of each loaded boss
+    boss_phases: HashMap<Uid, usize>,
+}
+
+impl StingerMgr {
+    #[allow(clippy::new_without_default)] // TODO: Pending review in #587
+    pub fn new() -> Self {
+        Self {
+            stingers: Self::load_stingers(),
+            boss_phases: HashMap::new(),
+        }
+    }
+
+    /// Plays the stinger of the latest event emitted since the last frame.
+    /// Earlier ones are dropped, so that several events at once don't stack up
+    /// cues.
+    pub fn maintain(&mut self, audio: &mut AudioFrontend, state: &State) {
+        self.update_boss_phases(state);
+
+        let events = state
+            .ecs()
+            .read_resource::<EventBus<AudioEvent>>()
+            .recv_all();
+
+        if !audio.music_enabled() {
+            return;
+        }
+
+        let stinger = events
+            .filter_map(|event| self.stingers.0.get(&event))
+            .filter(|item| !item.files.is_empty())
+            .last();
+        if let Some(item) = stinger {
+            if let Some(file) = item.files.choose(&mut rand::thread_rng()) {
+                audio.play_stinger(file, item.duck);
+            }
+        }
+    }
+
+    /// Emits the audio events caused by a combat event
+    pub fn handle_combat_event(&mut self, event: &CombatEvent, scene_data: &SceneData) {
+        let ecs = scene_data.state.ecs();
+        let player = ecs
+            .read_storage::<Uid>()
+            .get(scene_data.player_entity)
+            .copied();
+
+        if let CombatEvent::Kill { killer, .. } = event {
+            if killer.is_some() && *killer == player {
+                ecs.read_resource::<EventBus<AudioEvent>>()
+                    .emit_now(AudioEvent::Kill);
+            }
+        }
+    }
+
+    /// Emits `BossPhase` when a loaded boss entered a later phase than the
+    /// one it was in the last frame. Bosses that are first seen in a later
+    /// phase don't count, so that joining a fight doesn't play the stinger.
+    fn update_boss_phases(&mut self, state: &State) {
+        let ecs = state.ecs();
+        let mut phases = HashMap::new();
+        let mut entered_phase = false;
+        for (uid, body, stats) in (
+            &ecs.read_storage::<Uid>(),
+            &ecs.read_storage::<Body>(),
+            &ecs.read_storage::<Stats>(),
+        )
+            .join()
+            .filter(|(_, body, stats)| body.is_boss() && !stats.is_dead)
+        {
+            let phase = boss_phase(stats);
+            entered_phase |= self
+                .boss_phases
+                .get(uid)
+                .map_or(false, |previous| phase > *previous);
+            phases.insert(*uid, phase);
+        }
+        self.boss_phases = phases;
+
+        if entered_phase {
+            ecs.read_resource::<EventBus<AudioEvent>>()
+                .emit_now(AudioEvent::BossPhase);
+        }
+    }
+
+    fn load_stingers() -> StingerCollection {
+        match assets::load_file("voxygen.audio.stingers", &["ron"]) {
+            Ok(file) => match ron::de::from_reader(file) {
+                Ok(config) => config,
+                Err(error) => {
+                    warn!(
+                        "Error parsing stinger config file, stingers will not be available: {}",
+                        format!("{:#?}", error)
+                    );
+
+                    StingerCollection::default()
+                },
+            },
+            Err(error) => {
+                warn!(
+                    "Error reading stinger config file, stingers will not be available: {}",
+                    format!("{:#?}", error)
+                );
+
+                StingerCollection::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::comp::HealthSource;
+
+    #[test]
+    fn every_event_has_a_stinger() {
+        let file = assets::load_file("voxygen.audio.stingers", &["ron"])
+            .expect("Failed to read stingers.ron");
+        let stingers: StingerCollection =
+            ron::de::from_reader(file).expect("Failed to parse stingers.ron");
+
+        for event in &[
+            AudioEvent::LevelUp,
+            AudioEvent::Kill,
+            AudioEvent::Death,
+            AudioEvent::BossPhase,
+            AudioEvent::QuestComplete,
+        ] {
+            let item = stingers
+                .0
+                .get(event)
+                .unwrap_or_else(|| panic!("No stinger for {:?}", event));
+            assert!(!item.files.is_empty(), "No files for {:?}", event);
+            for file in &item.files {
+                assert!(
+                    assets::load_file(file, &["wav"]).is_ok(),
+                    "Missing stinger file {}",
+                    file
+                );
+            }
+            assert!((0.0..=1.0).contains(&item.duck));
+        }
+    }
+
+    #[test]
+    fn bosses_enter_a_phase_per_third_of_their_health() {
+        let body = Body::Golem(common::comp::golem::Body::random());
+        let mut stats = Stats::new("Boss".to_owned(), body);
+        let maximum = stats.health.maximum();
+        assert_eq!(boss_phase(&stats), 0);
+        stats.health.set_to(maximum / 2, HealthSource::Unknown);
+        assert_eq!(boss_phase(&stats), 1);
+        stats.health.set_to(maximum / 4, HealthSource::Unknown);
+        assert_eq!(boss_phase(&stats), 2);
+    }
+}
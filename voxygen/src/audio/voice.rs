@@ -0,0 +1,279 @@
+//! Voice chat with nearby players
+//!
+//! The microphone is recorded on the cpal event loop, encoded with opus into
+//! frames of 20ms and sent to the server, which relays them to the players in
+//! hearing range. Frames received from other players are decoded and played
+//! at the position of the speaker, see
+//! [`AudioFrontend::play_voice`](../struct.AudioFrontend.html#method.
+//! play_voice).
+//!
+//! Only built with the `voice` feature, since it links libopus.
+use crate::{
+    audio::{AudioFrontend, VOICE_SAMPLE_RATE},
+    settings::AudioSettings,
+};
+use audiopus::{
+    coder::{Decoder, Encoder},
+    Application, Channels, SampleRate,
+};
+use client::Client;
+use common::{msg::VoiceRelay, sync::Uid};
+use cpal::{
+    traits::{EventLoopTrait, HostTrait},
+    Sample, StreamData, StreamId, UnknownTypeInputBuffer,
+};
+use crossbeam::channel;
+use hashbrown::HashMap;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// Samples in a frame, 20ms of audio
+const FRAME_SAMPLES: usize = (VOICE_SAMPLE_RATE / 50) as usize;
+/// Most recorded samples kept while waiting to be sent, older ones are dropped
+const MAX_PENDING_SAMPLES: usize = FRAME_SAMPLES * 10;
+/// Most lost frames of a speaker filled in by the decoder, longer gaps are
+/// left silent
+const MAX_CONCEALED_FRAMES: u32 = 2;
+/// Speakers not heard for this long have their decoder and channel dropped
+const SPEAKER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Records the microphone. The cpal event loop blocks the thread running it
+/// forever, so a capture is started once and paused while not transmitting.
+struct Capture {
+    event_loop: Arc<cpal::EventLoop>,
+    stream_id: StreamId,
+    samples_rx: channel::Receiver<Vec<f32>>,
+    playing: bool,
+}
+
+impl Capture {
+    fn start() -> Option<Self> {
+        let host = cpal::default_host();
+        let device = host.default_input_device()?;
+        let event_loop = Arc::new(host.event_loop());
+        let format = cpal::Format {
+            channels: 1,
+            sample_rate: cpal::SampleRate(VOICE_SAMPLE_RATE),
+            data_type: cpal::SampleFormat::F32,
+        };
+        let stream_id = match event_loop.build_input_stream(&device, &format) {
+            Ok(stream_id) => stream_id,
+            Err(e) => {
+                warn!(
+                    ?e,
+                    "The microphone can't record 48kHz mono, voice chat is disabled"
+                );
+                return None;
+            },
+        };
+
+        let (samples_tx, samples_rx) = channel::unbounded();
+        let run_loop = Arc::clone(&event_loop);
+        std::thread::spawn(move || {
+            run_loop.run(move |_, data| {
+                let samples = match data {
+                    Ok(StreamData::Input {
+                        buffer: UnknownTypeInputBuffer::F32(buffer),
+                    }) => buffer.to_vec(),
+                    Ok(StreamData::Input {
+                        buffer: UnknownTypeInputBuffer::I16(buffer),
+                    }) => buffer.iter().map(Sample::to_f32).collect(),
+                    Ok(StreamData::Input {
+                        buffer: UnknownTypeInputBuffer::U16(buffer),
+                    }) => buffer.iter().map(Sample::to_f32).collect(),
+                    Ok(_) => return,
+                    Err(e) => {
+                        debug!(?e, "Error while recording the microphone");
+                        return;
+                    },
+                };
+                let _ = samples_tx.send(samples);
+            })
+        });
+
+        Some(Self {
+            event_loop,
+            stream_id,
+            samples_rx,
+            playing: false,
+        })
+    }
+
+    fn set_playing(&mut self, playing: bool) {
+        if self.playing == playing {
+            return;
+        }
+        let result = if playing {
+            self.event_loop
+                .play_stream(self.stream_id.clone())
+                .map_err(|e| format!("{:?}", e))
+        } else {
+            self.event_loop
+                .pause_stream(self.stream_id.clone())
+                .map_err(|e| format!("{:?}", e))
+        };
+        match result {
+            Ok(()) => self.playing = playing,
+            Err(e) => warn!(?e, "Failed to start or stop recording the microphone"),
+        }
+    }
+}
+
+struct Speaker {
+    decoder: Decoder,
+    /// Sequence number of the next frame expected from the speaker
+    next_seq: u32,
+    last_heard: Instant,
+}
+
+pub struct VoiceMgr {
+    capture: Option<Capture>,
+    /// Whether starting the capture failed, so that it isn't retried every tick
+    capture_failed: bool,
+    encoder: Option<Encoder>,
+    /// Recorded samples not sent yet
+    pending: Vec<f32>,
+    speakers: HashMap<Uid, Speaker>,
+}
+
+impl VoiceMgr {
+    #[allow(clippy::new_without_default)] // TODO: Pending review in #587
+    pub fn new() -> Self {
+        let encoder = Encoder::new(SampleRate::Hz48000, Channels::Mono, Application::Voip)
+            .map_err(|e| warn!(?e, "Failed to create the voice encoder"))
+            .ok();
+
+        Self {
+            capture: None,
+            capture_failed: false,
+            encoder,
+            pending: Vec::new(),
+            speakers: HashMap::new(),
+        }
+    }
+
+    /// Sends the microphone to the server if voice chat is enabled, and plays
+    /// the voices of other players that aren't muted
+    pub fn maintain(
+        &mut self,
+        client: &mut Client,
+        audio: &mut AudioFrontend,
+        settings: &AudioSettings,
+    ) {
+        let transmit =
+            settings.voice_chat && client.voice_chat_available() && self.encoder.is_some();
+        self.transmit(client, transmit);
+
+        for relay in client.recv_voice_frames() {
+            let muted = client
+                .player_list
+                .get(&relay.speaker)
+                .map_or(false, |info| {
+                    settings.muted_voices.contains(&info.player_alias)
+                });
+            if muted {
+                self.speakers.remove(&relay.speaker);
+                audio.stop_voice(relay.speaker);
+            } else {
+                self.play(relay, audio);
+            }
+        }
+
+        let now = Instant::now();
+        self.speakers.retain(|uid, speaker| {
+            let heard = now.duration_since(speaker.last_heard) < SPEAKER_TIMEOUT;
+            if !heard {
+                audio.stop_voice(*uid);
+            }
+            heard
+        });
+    }
+
+    fn transmit(&mut self, client: &mut Client, transmit: bool) {
+        if transmit && self.capture.is_none() && !self.capture_failed {
+            self.capture = Capture::start();
+            self.capture_failed = self.capture.is_none();
+        }
+        let capture = match &mut self.capture {
+            Some(capture) => capture,
+            None => return,
+        };
+        capture.set_playing(transmit);
+
+        for samples in capture.samples_rx.try_iter() {
+            self.pending.extend(samples);
+        }
+        if !transmit {
+            self.pending.clear();
+            return;
+        }
+        if self.pending.len() > MAX_PENDING_SAMPLES {
+            let excess = self.pending.len() - MAX_PENDING_SAMPLES;
+            self.pending.drain(..excess);
+        }
+
+        if let Some(encoder) = &self.encoder {
+            let mut output = [0; common::msg::MAX_BYTES_VOICE_FRAME];
+            while self.pending.len() >= FRAME_SAMPLES {
+                let frame = self.pending.drain(..FRAME_SAMPLES).collect::<Vec<_>>();
+                match encoder.encode_float(&frame, &mut output) {
+                    Ok(len) => client.send_voice_frame(output[..len].to_vec()),
+                    Err(e) => debug!(?e, "Failed to encode voice frame"),
+                }
+            }
+        }
+    }
+
+    fn play(&mut self, relay: VoiceRelay, audio: &mut AudioFrontend) {
+        let seq = relay.frame.seq;
+        if !self.speakers.contains_key(&relay.speaker) {
+            match Decoder::new(SampleRate::Hz48000, Channels::Mono) {
+                Ok(decoder) => {
+                    self.speakers.insert(relay.speaker, Speaker {
+                        decoder,
+                        next_seq: seq,
+                        last_heard: Instant::now(),
+                    });
+                },
+                Err(e) => {
+                    warn!(?e, "Failed to create a voice decoder");
+                    return;
+                },
+            }
+        }
+        let speaker = match self.speakers.get_mut(&relay.speaker) {
+            Some(speaker) => speaker,
+            None => return,
+        };
+
+        // Frames arriving after a later one are dropped, the gap they left was
+        // filled in already
+        let lost = seq.wrapping_sub(speaker.next_seq);
+        if lost > u32::MAX / 2 {
+            return;
+        }
+
+        let mut samples =
+            Vec::with_capacity(FRAME_SAMPLES * (lost.min(MAX_CONCEALED_FRAMES) + 1) as usize);
+        for _ in 0..lost.min(MAX_CONCEALED_FRAMES) {
+            decode(&mut speaker.decoder, None, &mut samples);
+        }
+        decode(&mut speaker.decoder, Some(&relay.frame.data), &mut samples);
+        speaker.next_seq = seq.wrapping_add(1);
+        speaker.last_heard = Instant::now();
+
+        audio.play_voice(relay.speaker, samples, relay.pos);
+    }
+}
+
+/// Decode a frame, or fill in a lost one if there is no data
+fn decode(decoder: &mut Decoder, data: Option<&[u8]>, samples: &mut Vec<f32>) {
+    let mut output = [0.0; FRAME_SAMPLES];
+    match decoder.decode_float(data, &mut output[..], false) {
+        Ok(len) => samples.extend_from_slice(&output[..len]),
+        Err(e) => debug!(?e, "Failed to decode voice frame"),
+    }
+}
@@ -0,0 +1,562 @@
+//! Benchmark mode
+//!
+//! Setting `VOXYGEN_BENCHMARK` starts voxygen in benchmark mode instead of the
+//! main menu. It starts a local server generating the world from a fixed
+//! seed, enters it with a benchmark character, spawns the configured NPCs and
+//! moves the camera along a scripted path. The time of every frame rendered
+//! on the path is recorded and written to a JSON report, so that the reports
+//! of two builds can be compared to find performance regressions.
+//!
+//! The variable holds the path of a RON file with a [`BenchmarkConfig`], or
+//! is empty to run the default benchmark.
+//!
+//! With `headless` set in the configuration the window is hidden and nothing
+//! is drawn, so that the report only measures the simulation and the meshing
+//! of the terrain.
+
+use crate::{
+    menu::main::client_init::{ClientInit, Msg as InitMsg},
+    render::Renderer,
+    scene::camera::Camera,
+    session::SessionState,
+    settings::Settings,
+    singleplayer::Singleplayer,
+    window::Event,
+    Direction, GlobalState, PlayState, PlayStateResult,
+};
+use client::Client;
+use common::{
+    comp,
+    npc::{self, NpcKind, NPC_NAMES},
+    span, LoadoutBuilder,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use server::{state_ext::StateExt, Server};
+use specs::{Builder, WorldExt};
+use std::{cell::RefCell, fs, path::PathBuf, rc::Rc, time::Duration};
+use tracing::{error, info};
+use vek::*;
+
+pub const BENCHMARK_ENV: &str = "VOXYGEN_BENCHMARK";
+
+const CHARACTER_NAME: &str = "Benchmark";
+const CHARACTER_TOOL: &str = "common.items.weapons.sword.starter_sword";
+
+/// A point of the camera path, reached `time` seconds after the path started
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub time: f64,
+    pub pos: Vec3<f32>,
+    /// Yaw and pitch of the camera in radians
+    pub ori: Vec2<f32>,
+}
+
+/// NPCs spawned around the start of the path halfway through the warmup, once
+/// the character is there. They are spawned without AI so that they stay in
+/// the same place on every run.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NpcGroup {
+    /// `wild`, `enemy` or `npc`
+    pub alignment: String,
+    /// An NPC kind, as for `/spawn`
+    pub entity: String,
+    pub amount: u32,
+}
+
+/// Distance from the start of the path within which the NPCs are spawned
+const NPC_SPREAD: f32 = 24.0;
+
+/// An NPC of the benchmark, as it is spawned
+#[derive(Debug, PartialEq)]
+pub struct NpcSpawn {
+    pub name: String,
+    pub body: comp::Body,
+    pub alignment: comp::Alignment,
+    pub pos: Vec3<f32>,
+}
+
+/// The NPCs spawned for `npcs` around `center`. All of them are generated
+/// from `seed`, so that every run spawns the same ones.
+pub fn npc_spawns(npcs: &[NpcGroup], center: Vec3<f32>, seed: u32) -> Vec<NpcSpawn> {
+    let mut rng = StdRng::seed_from_u64(seed as u64);
+    let mut spawns = Vec::new();
+    for group in npcs {
+        let alignment = match group.alignment.as_str() {
+            "wild" => comp::Alignment::Wild,
+            "enemy" => comp::Alignment::Enemy,
+            "npc" => comp::Alignment::Npc,
+            _ => {
+                error!(?group.alignment, "Unknown alignment of benchmark NPCs");
+                continue;
+            },
+        };
+        let kind = match group.entity.parse::<NpcKind>() {
+            Ok(kind) => kind,
+            Err(()) => {
+                error!(?group.entity, "Unknown kind of benchmark NPCs");
+                continue;
+            },
+        };
+        for _ in 0..group.amount {
+            let body = npc::kind_to_body_with(kind, &mut rng);
+            let names = &NPC_NAMES[kind];
+            let name = names
+                .names
+                .choose(&mut rng)
+                .unwrap_or(&names.keyword)
+                .clone();
+            let offset = Vec2::new(
+                rng.gen_range(-NPC_SPREAD, NPC_SPREAD),
+                rng.gen_range(-NPC_SPREAD, NPC_SPREAD),
+            );
+            spawns.push(NpcSpawn {
+                name,
+                body,
+                alignment,
+                pos: center + offset,
+            });
+        }
+    }
+    spawns
+}
+
+/// Spawns the NPCs of the benchmark on the benchmark server. This bypasses
+/// `/spawn`, which spawns a limited amount of NPCs at random.
+pub fn spawn_npcs(server: &mut Server, npcs: &[NpcGroup], center: Vec3<f32>, seed: u32) {
+    for spawn in npc_spawns(npcs, center, seed) {
+        let loadout = LoadoutBuilder::build_loadout(spawn.body, spawn.alignment, None, false);
+        let entity = server
+            .state_mut()
+            .create_npc(
+                comp::Pos(spawn.pos),
+                comp::Stats::new(spawn.name, spawn.body),
+                loadout.build(),
+                spawn.body,
+            )
+            .with(comp::MountState::Unmounted)
+            .with(spawn.alignment)
+            .build();
+        let group = match spawn.alignment {
+            comp::Alignment::Enemy => Some(comp::group::ENEMY),
+            comp::Alignment::Npc => Some(comp::group::NPC),
+            _ => None,
+        };
+        if let Some(group) = group {
+            let _ = server.state().ecs().write_storage().insert(entity, group);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BenchmarkConfig {
+    pub seed: u32,
+    /// Seconds spent at the start of the path before recording, so that the
+    /// terrain around it is loaded
+    pub warmup: f64,
+    pub path: Vec<Waypoint>,
+    pub npcs: Vec<NpcGroup>,
+    /// Frame rate cap during the benchmark, replacing the one in the settings
+    pub max_fps: u32,
+    /// Where the report is written
+    pub output: PathBuf,
+    /// Hides the window and skips drawing the frames
+    pub headless: bool,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        let waypoint = |time, x, y, z, yaw: f32| Waypoint {
+            time,
+            pos: Vec3::new(x, y, z),
+            ori: Vec2::new(yaw.to_radians(), -0.2),
+        };
+        Self {
+            // The default world of the server
+            seed: 59686,
+            warmup: 20.0,
+            path: vec![
+                waypoint(0.0, 16384.0, 16384.0, 300.0, 0.0),
+                waypoint(20.0, 16384.0, 17184.0, 320.0, 45.0),
+                waypoint(40.0, 17184.0, 17184.0, 360.0, 135.0),
+                waypoint(60.0, 17184.0, 16384.0, 320.0, 270.0),
+            ],
+            npcs: vec![
+                NpcGroup {
+                    alignment: "wild".to_owned(),
+                    entity: "quadruped_medium".to_owned(),
+                    amount: 20,
+                },
+                NpcGroup {
+                    alignment: "npc".to_owned(),
+                    entity: "humanoid".to_owned(),
+                    amount: 20,
+                },
+            ],
+            max_fps: 1000,
+            output: PathBuf::from("benchmark.json"),
+            headless: false,
+        }
+    }
+}
+
+impl BenchmarkConfig {
+    /// The configuration named by `VOXYGEN_BENCHMARK`, if voxygen was started
+    /// in benchmark mode
+    pub fn from_env() -> Option<Result<Self, String>> {
+        let path = std::env::var_os(BENCHMARK_ENV)?;
+        if path.is_empty() {
+            return Some(Ok(Self::default()));
+        }
+        Some(
+            fs::File::open(&path)
+                .map_err(|e| format!("{:?}: {}", path, e))
+                .and_then(|file| {
+                    ron::de::from_reader(file).map_err(|e| format!("{:?}: {}", path, e))
+                }),
+        )
+    }
+}
+
+/// Where the camera is `time` seconds after the path started, or `None` once
+/// the path ended
+pub fn path_point(path: &[Waypoint], time: f64) -> Option<Waypoint> {
+    let end = path.iter().position(|w| w.time >= time)?;
+    if end == 0 {
+        return Some(path[0]);
+    }
+    let (a, b) = (path[end - 1], path[end]);
+    let t = ((time - a.time) / (b.time - a.time).max(f64::EPSILON)) as f32;
+    Some(Waypoint {
+        time,
+        pos: Lerp::lerp(a.pos, b.pos, t),
+        ori: Lerp::lerp(a.ori, b.ori, t),
+    })
+}
+
+/// Frame time statistics written to the report, in milliseconds
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FrameStats {
+    pub frames: usize,
+    pub mean: f32,
+    pub median: f32,
+    pub p95: f32,
+    pub p99: f32,
+    pub min: f32,
+    pub max: f32,
+}
+
+impl FrameStats {
+    pub fn new(frame_times: &[f32]) -> Self {
+        let mut sorted = frame_times.to_vec();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let percentile = |p: f32| {
+            sorted
+                .get(((sorted.len() as f32 * p).ceil() as usize).saturating_sub(1))
+                .copied()
+                .unwrap_or(0.0)
+        };
+        Self {
+            frames: sorted.len(),
+            mean: sorted.iter().sum::<f32>() / sorted.len().max(1) as f32,
+            median: percentile(0.5),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+            min: sorted.first().copied().unwrap_or(0.0),
+            max: sorted.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    version: &'a str,
+    seed: u32,
+    headless: bool,
+    view_distance: u32,
+    resolution: [u16; 2],
+    frame_time_ms: FrameStats,
+    frame_times_ms: &'a [f32],
+}
+
+/// A running benchmark, driven by the session
+pub struct Benchmark {
+    config: BenchmarkConfig,
+    /// Seconds since the character entered the world
+    elapsed: f64,
+    npcs_spawned: bool,
+    frame_times: Vec<f32>,
+}
+
+impl Benchmark {
+    pub fn new(config: BenchmarkConfig) -> Self {
+        Self {
+            config,
+            elapsed: 0.0,
+            npcs_spawned: false,
+            frame_times: Vec::new(),
+        }
+    }
+
+    pub fn max_fps(&self) -> u32 { self.config.max_fps }
+
+    pub fn headless(&self) -> bool { self.config.headless }
+
+    /// Moves the character and the camera along the path and records the
+    /// time of the last frame. Returns `false` once the path ended.
+    pub fn tick(
+        &mut self,
+        dt: Duration,
+        singleplayer: Option<&Singleplayer>,
+        client: &mut Client,
+        camera: &mut Camera,
+    ) -> bool {
+        if !self.npcs_spawned && self.elapsed >= self.config.warmup / 2.0 {
+            if let (Some(singleplayer), Some(start)) = (singleplayer, self.config.path.first()) {
+                singleplayer.spawn_benchmark_npcs(
+                    self.config.npcs.clone(),
+                    start.pos,
+                    self.config.seed,
+                );
+            }
+            self.npcs_spawned = true;
+        }
+
+        let time = self.elapsed - self.config.warmup;
+        let waypoint = match path_point(&self.config.path, time.max(0.0)) {
+            Some(waypoint) => waypoint,
+            None => return false,
+        };
+        if time >= 0.0 {
+            self.frame_times.push(dt.as_secs_f32() * 1000.0);
+        }
+        self.elapsed += dt.as_secs_f64();
+
+        let entity = client.entity();
+        let state = client.state_mut();
+        state.write_component(entity, comp::Pos(waypoint.pos));
+        state.write_component(entity, comp::Vel(Vec3::zero()));
+        camera.set_ori_instant(Vec3::new(waypoint.ori.x, waypoint.ori.y, 0.0));
+        true
+    }
+
+    /// Writes the report of the benchmark
+    pub fn finish(&self, global_state: &GlobalState) {
+        let resolution = global_state.window.renderer().get_resolution();
+        let report = Report {
+            version: &common::util::DISPLAY_VERSION_LONG,
+            seed: self.config.seed,
+            headless: self.config.headless,
+            view_distance: global_state.settings.graphics.view_distance,
+            resolution: resolution.into_array(),
+            frame_time_ms: FrameStats::new(&self.frame_times),
+            frame_times_ms: &self.frame_times,
+        };
+        let output = &self.config.output;
+        match serde_json::to_string_pretty(&report)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(output, json).map_err(|e| e.to_string()))
+        {
+            Ok(()) => info!(?output, stats = ?report.frame_time_ms, "Benchmark finished"),
+            Err(e) => error!(?e, ?output, "Could not write the benchmark report"),
+        }
+    }
+}
+
+/// Starts the benchmark server and enters it with the benchmark character
+/// before handing over to the session
+pub struct BenchmarkState {
+    client_init: Option<ClientInit>,
+    client: Option<Rc<RefCell<Client>>>,
+    character_created: bool,
+}
+
+impl BenchmarkState {
+    pub fn new(global_state: &mut GlobalState, config: BenchmarkConfig) -> Self {
+        let (singleplayer, server_settings) = Singleplayer::benchmark(config.seed);
+        if config.headless {
+            global_state.window.window().window().set_visible(false);
+        }
+        global_state.singleplayer = Some(singleplayer);
+        global_state.benchmark = Some(Benchmark::new(config));
+
+        let client_init = ClientInit::new(
+            (
+                server_settings.gameserver_address.ip().to_string(),
+                server_settings.gameserver_address.port(),
+                false,
+            ),
+            "singleplayer".to_owned(),
+            Some(global_state.settings.graphics.view_distance),
+            "".to_owned(),
+            false,
+        );
+        Self {
+            client_init: Some(client_init),
+            client: None,
+            character_created: false,
+        }
+    }
+
+    /// Picks the benchmark character once the character list arrived,
+    /// creating it on the first run
+    fn select_character(&mut self, client: &mut Client) -> bool {
+        if client.character_list.loading {
+            return false;
+        }
+        if client.server_rules().is_some() {
+            client.accept_rules();
+        }
+        match client
+            .character_list
+            .characters
+            .iter()
+            .find(|item| item.character.alias == CHARACTER_NAME)
+            .and_then(|item| item.character.id)
+        {
+            Some(character_id) => {
+                client.request_character(character_id);
+                true
+            },
+            None if !self.character_created => {
+                // Always the same character, so that it looks the same on every run
+                let mut rng = StdRng::seed_from_u64(0);
+                let body =
+                    comp::humanoid::Body::random_with(&mut rng, &comp::humanoid::Species::Human);
+                client.create_character(
+                    CHARACTER_NAME.to_owned(),
+                    Some(CHARACTER_TOOL.to_owned()),
+                    comp::Body::Humanoid(body),
+                );
+                self.character_created = true;
+                false
+            },
+            None => false,
+        }
+    }
+}
+
+impl PlayState for BenchmarkState {
+    fn enter(&mut self, _: &mut GlobalState, _: Direction) {}
+
+    fn tick(&mut self, global_state: &mut GlobalState, events: Vec<Event>) -> PlayStateResult {
+        span!(_guard, "tick", "<BenchmarkState as PlayState>::tick");
+        if events.iter().any(|event| matches!(event, Event::Close)) {
+            return PlayStateResult::Shutdown;
+        }
+
+        if let Some(Err(error)) = global_state
+            .singleplayer
+            .as_ref()
+            .and_then(|singleplayer| singleplayer.receiver.try_recv().ok())
+        {
+            error!(?error, "Could not start the benchmark server");
+            return PlayStateResult::Shutdown;
+        }
+
+        match self.client_init.as_ref().and_then(|init| init.poll()) {
+            Some(InitMsg::Done(Ok(mut client))) => {
+                self.client_init = None;
+                crate::ecs::init(client.state_mut().ecs_mut());
+                client.load_character_list();
+                self.client = Some(Rc::new(RefCell::new(client)));
+            },
+            Some(InitMsg::Done(Err(err))) => {
+                error!(?err, "Could not connect to the benchmark server");
+                return PlayStateResult::Shutdown;
+            },
+            // The benchmark server has no auth server
+            Some(InitMsg::IsAuthTrusted(_)) | None => {},
+        }
+
+        if let Some(client) = self.client.clone() {
+            if let Err(err) = client.borrow_mut().tick(
+                comp::ControllerInputs::default(),
+                global_state.clock.get_last_delta(),
+                |_| {},
+            ) {
+                error!(?err, "Lost the connection to the benchmark server");
+                return PlayStateResult::Shutdown;
+            }
+            client.borrow_mut().cleanup();
+
+            if self.select_character(&mut client.borrow_mut()) {
+                return PlayStateResult::Switch(Box::new(SessionState::new(global_state, client)));
+            }
+        }
+
+        PlayStateResult::Continue
+    }
+
+    fn name(&self) -> &'static str { "Benchmark" }
+
+    fn render(&mut self, _: &mut Renderer, _: &Settings) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_is_interpolated_between_waypoints() {
+        let waypoint = |time, x| Waypoint {
+            time,
+            pos: Vec3::new(x, 0.0, 0.0),
+            ori: Vec2::zero(),
+        };
+        let path = [waypoint(0.0, 0.0), waypoint(10.0, 100.0)];
+        assert_eq!(path_point(&path, 0.0), Some(path[0]));
+        assert_eq!(path_point(&path, 2.5).map(|w| w.pos.x), Some(25.0));
+        assert_eq!(path_point(&path, 10.5), None);
+    }
+
+    #[test]
+    fn frame_stats_use_nearest_rank_percentiles() {
+        let frame_times = (1..=100).rev().map(|t| t as f32).collect::<Vec<_>>();
+        let stats = FrameStats::new(&frame_times);
+        assert_eq!(stats.frames, 100);
+        assert_eq!(stats.median, 50.0);
+        assert_eq!(stats.p95, 95.0);
+        assert_eq!(stats.p99, 99.0);
+        assert_eq!((stats.min, stats.max), (1.0, 100.0));
+        assert_eq!(stats.mean, 50.5);
+    }
+
+    #[test]
+    fn npcs_are_spawned_in_full_from_the_seed() {
+        let npcs = BenchmarkConfig::default().npcs;
+        let center = Vec3::new(100.0, 100.0, 300.0);
+        let spawns = npc_spawns(&npcs, center, 7);
+        assert_eq!(spawns.len(), 40);
+        assert_eq!(
+            spawns
+                .iter()
+                .filter(|spawn| spawn.alignment == comp::Alignment::Wild)
+                .count(),
+            20
+        );
+        assert!(spawns
+            .iter()
+            .all(|spawn| spawn.pos.xy().distance(center.xy()) <= NPC_SPREAD * 2.0f32.sqrt()));
+        assert_eq!(spawns, npc_spawns(&npcs, center, 7));
+        assert_ne!(spawns, npc_spawns(&npcs, center, 8));
+    }
+
+    #[test]
+    fn unknown_npc_groups_are_skipped() {
+        let npcs = vec![
+            NpcGroup {
+                alignment: "pet".to_owned(),
+                entity: "humanoid".to_owned(),
+                amount: 3,
+            },
+            NpcGroup {
+                alignment: "wild".to_owned(),
+                entity: "not_an_npc".to_owned(),
+                amount: 3,
+            },
+        ];
+        assert!(npc_spawns(&npcs, Vec3::zero(), 0).is_empty());
+    }
+}
@@ -12,6 +12,8 @@ pub struct HpFloater {
     pub hp_change: i32,
     // Used for randomly offsetting
     pub rand: f32,
+    // Critical hits are shown larger
+    pub crit: bool,
 }
 #[derive(Clone, Debug, Default)]
 pub struct HpFloaterList {
@@ -24,6 +26,20 @@ pub struct HpFloaterList {
     // Used to display nametags outside normal range if this time is below a certain value
     pub time_since_last_dmg_by_me: Option<f32>,
 }
+impl HpFloaterList {
+    /// Marks the floater of a health change as a critical hit once the server
+    /// reports it, which happens right after the floater was spawned
+    pub fn mark_crit(&mut self, hp_change: i32) {
+        if let Some(floater) = self
+            .floaters
+            .iter_mut()
+            .rev()
+            .find(|f| f.hp_change == hp_change && !f.crit)
+        {
+            floater.crit = true;
+        }
+    }
+}
 impl Component for HpFloaterList {
     type Storage = IdvStorage<Self>;
 }
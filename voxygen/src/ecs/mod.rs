@@ -1,7 +1,7 @@
 pub mod comp;
 pub mod sys;
 
-use crate::audio::sfx::SfxEventItem;
+use crate::audio::{sfx::SfxEventItem, stinger::AudioEvent};
 use common::event::EventBus;
 use specs::{Entity, World, WorldExt};
 
@@ -15,12 +15,10 @@ pub struct ExpFloater {
     // Used to randomly offset position
     pub rand: (f32, f32),
 }
+/// Experience gained by the player, filled from the server's exp events
 #[derive(Clone, Debug, Default)]
 pub struct MyExpFloaterList {
     pub floaters: Vec<ExpFloater>,
-    pub last_exp: u32,
-    pub last_level: u32,
-    pub last_exp_max: u32,
 }
 
 pub fn init(world: &mut World) {
@@ -30,4 +28,5 @@ pub fn init(world: &mut World) {
 
     // Voxygen event buses
     world.insert(EventBus::<SfxEventItem>::default());
+    world.insert(EventBus::<AudioEvent>::default());
 }
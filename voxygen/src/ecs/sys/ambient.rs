@@ -0,0 +1,258 @@
+use crate::ecs::MyEntity;
+use client::terrain_query::{ground_height_at, raycast_terrain_until};
+use common::{
+    comp::{bird_small, fish_small, Body, Cosmetic, Ori, PhysicsState, Pos, Vel},
+    state::{DeltaTime, TimeOfDay},
+    terrain::TerrainGrid,
+    time::DayPeriod,
+    util::Dir,
+    vol::ReadVol,
+};
+use hashbrown::HashMap;
+use rand::prelude::*;
+use specs::{Entities, Entity, Join, Read, ReadExpect, System, WriteStorage};
+use vek::*;
+
+// At most this many critters are around the player at once
+const MAX_CRITTERS: usize = 48;
+// Chance per second that a new flock shows up
+const FLOCK_SPAWN_RATE: f32 = 0.2;
+// Flocks show up this far away from the player, so they don't pop in
+const MIN_SPAWN_DIST: f32 = 48.0;
+const MAX_SPAWN_DIST: f32 = 96.0;
+// Critters further away than this are removed
+const DESPAWN_DIST: f32 = 128.0;
+// Flock members keep at least this much room between each other
+const SEPARATION_DIST: f32 = 2.5;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Habitat {
+    Air,
+    Water,
+}
+
+impl Habitat {
+    fn of(body: &Body) -> Self {
+        match body {
+            Body::FishSmall(_) | Body::FishMedium(_) => Habitat::Water,
+            _ => Habitat::Air,
+        }
+    }
+
+    fn speed(self) -> f32 {
+        match self {
+            Habitat::Air => 9.0,
+            Habitat::Water => 3.0,
+        }
+    }
+}
+
+/// Spawns flocks of birds and schools of fish around the player and moves them
+/// with boids-style flocking. These critters are `Cosmetic`: they only exist
+/// in the local ECS and are never sent to or received from the server.
+#[derive(Default)]
+pub struct Sys {
+    next_flock: u32,
+}
+
+impl Sys {
+    /// Finds a spot for a new flock on a column near `origin`, above the ground
+    /// for birds or under the surface of deep enough water for fish.
+    fn find_spot(
+        terrain: &TerrainGrid,
+        origin: Vec3<f32>,
+        rng: &mut impl Rng,
+    ) -> Option<(Vec3<f32>, Habitat)> {
+        let dir = Vec2::new(rng.gen::<f32>() - 0.5, rng.gen::<f32>() - 0.5).try_normalized()?;
+        let wpos = origin + Vec3::from(dir * rng.gen_range(MIN_SPAWN_DIST, MAX_SPAWN_DIST));
+
+        let surface = raycast_terrain_until(
+            terrain,
+            wpos + Vec3::unit_z() * 48.0,
+            wpos - Vec3::unit_z() * 48.0,
+            |b| b.is_opaque() || b.kind().is_liquid(),
+        )?;
+
+        if surface.block.kind().is_liquid() {
+            let spot = surface.pos - Vec3::unit_z() * 2.0;
+            terrain
+                .get((spot - Vec3::unit_z() * 2.0).map(|e| e.floor() as i32))
+                .ok()
+                .filter(|b| b.kind().is_liquid())
+                .map(|_| (spot, Habitat::Water))
+        } else {
+            Some((
+                surface.pos + Vec3::unit_z() * rng.gen_range(12.0, 24.0),
+                Habitat::Air,
+            ))
+        }
+    }
+}
+
+impl<'a> System<'a> for Sys {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, MyEntity>,
+        ReadExpect<'a, TerrainGrid>,
+        Read<'a, DeltaTime>,
+        Read<'a, TimeOfDay>,
+        WriteStorage<'a, Cosmetic>,
+        WriteStorage<'a, Body>,
+        WriteStorage<'a, Pos>,
+        WriteStorage<'a, Vel>,
+        WriteStorage<'a, Ori>,
+        WriteStorage<'a, PhysicsState>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            my_entity,
+            terrain,
+            dt,
+            time_of_day,
+            mut cosmetics,
+            mut bodies,
+            mut positions,
+            mut velocities,
+            mut orientations,
+            mut physics_states,
+        ): Self::SystemData,
+    ) {
+        let player_pos = match positions.get(my_entity.0) {
+            Some(pos) => pos.0,
+            None => return,
+        };
+        let mut rng = thread_rng();
+
+        // Remove critters that were left behind
+        let far_away = (&entities, &cosmetics, &positions)
+            .join()
+            .filter(|(_, _, pos)| {
+                pos.0.xy().distance_squared(player_pos.xy()) > DESPAWN_DIST.powi(2)
+            })
+            .map(|(entity, _, _)| entity)
+            .collect::<Vec<Entity>>();
+        for entity in far_away {
+            let _ = entities.delete(entity);
+        }
+
+        // Occasionally spawn a new flock, birds only during the day
+        let critter_count = cosmetics.join().count();
+        if critter_count < MAX_CRITTERS && rng.gen::<f32>() < FLOCK_SPAWN_RATE * dt.0 {
+            if let Some((spot, habitat)) =
+                Self::find_spot(&terrain, player_pos, &mut rng).filter(|(_, habitat)| {
+                    *habitat == Habitat::Water || DayPeriod::from(time_of_day.0).is_light()
+                })
+            {
+                let flock = self.next_flock;
+                self.next_flock = self.next_flock.wrapping_add(1);
+                let size = rng.gen_range(3, 9).min(MAX_CRITTERS - critter_count);
+                let heading = Vec3::new(rng.gen::<f32>() - 0.5, rng.gen::<f32>() - 0.5, 0.0)
+                    .try_normalized()
+                    .unwrap_or_else(Vec3::unit_y);
+                for _ in 0..size {
+                    let body = match habitat {
+                        Habitat::Air => Body::BirdSmall(bird_small::Body::random()),
+                        Habitat::Water => Body::FishSmall(fish_small::Body::random()),
+                    };
+                    let offset = Vec3::new(
+                        rng.gen::<f32>() - 0.5,
+                        rng.gen::<f32>() - 0.5,
+                        (rng.gen::<f32>() - 0.5) * 0.5,
+                    ) * 6.0;
+                    entities
+                        .build_entity()
+                        .with(Cosmetic { flock }, &mut cosmetics)
+                        .with(body, &mut bodies)
+                        .with(Pos(spot + offset), &mut positions)
+                        .with(Vel(heading * habitat.speed()), &mut velocities)
+                        .with(Ori(Dir::new(heading)), &mut orientations)
+                        .with(PhysicsState::default(), &mut physics_states)
+                        .build();
+                }
+            }
+        }
+
+        // Boids: every critter steers towards the middle of its flock, matches
+        // the heading of its flockmates and keeps some distance from them
+        let critters = (&entities, &cosmetics, &bodies, &positions, &velocities)
+            .join()
+            .map(|(entity, cosmetic, body, pos, vel)| {
+                (entity, cosmetic.flock, Habitat::of(body), pos.0, vel.0)
+            })
+            .collect::<Vec<_>>();
+        let mut flocks = HashMap::<u32, (Vec3<f32>, Vec3<f32>, f32)>::new();
+        for (_, flock, _, pos, vel) in &critters {
+            let (pos_sum, vel_sum, count) =
+                flocks
+                    .entry(*flock)
+                    .or_insert((Vec3::zero(), Vec3::zero(), 0.0));
+            *pos_sum += *pos;
+            *vel_sum += *vel;
+            *count += 1.0;
+        }
+
+        for (entity, flock, habitat, pos, vel) in &critters {
+            let (pos_sum, vel_sum, count) = flocks[flock];
+            let cohesion = pos_sum / count - *pos;
+            let alignment = vel_sum / count - *vel;
+            let separation = critters
+                .iter()
+                .filter(|(other, other_flock, ..)| other != entity && other_flock == flock)
+                .map(|(_, _, _, other_pos, _)| *pos - *other_pos)
+                .filter(|offset| offset.magnitude_squared() < SEPARATION_DIST.powi(2))
+                .map(|offset| offset / offset.magnitude_squared().max(0.01))
+                .sum::<Vec3<f32>>();
+            let wander = Vec3::new(
+                rng.gen::<f32>() - 0.5,
+                rng.gen::<f32>() - 0.5,
+                rng.gen::<f32>() - 0.5,
+            ) * 4.0;
+
+            // Keep birds off the ground and fish under water
+            let avoid = match habitat {
+                Habitat::Air => {
+                    let below =
+                        ground_height_at(&terrain, *pos, 8.0).map_or(8.0, |ground| pos.z - ground);
+                    Vec3::unit_z() * (8.0 - below) * 2.0
+                },
+                Habitat::Water => {
+                    let in_water = |offset: f32| {
+                        terrain
+                            .get((*pos + Vec3::unit_z() * offset).map(|e| e.floor() as i32))
+                            .map_or(false, |b| b.kind().is_liquid())
+                    };
+                    if !in_water(1.0) {
+                        -Vec3::unit_z() * 4.0
+                    } else if !in_water(-1.0) {
+                        Vec3::unit_z() * 4.0
+                    } else {
+                        Vec3::zero()
+                    }
+                },
+            };
+
+            let steering = cohesion * 0.2 + alignment * 0.5 + separation * 2.0 + wander + avoid;
+            let new_vel = (*vel + steering * dt.0)
+                .try_normalized()
+                .unwrap_or_else(Vec3::unit_y)
+                * habitat.speed();
+
+            if let Some(vel) = velocities.get_mut(*entity) {
+                vel.0 = new_vel;
+            }
+            if let Some(pos) = positions.get_mut(*entity) {
+                pos.0 += new_vel * dt.0;
+            }
+            if let (Some(ori), Some(dir)) = (
+                orientations.get_mut(*entity),
+                Dir::from_unnormalized(new_vel),
+            ) {
+                ori.0 = dir;
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::ecs::{
     comp::{HpFloater, HpFloaterList},
-    ExpFloater, MyEntity, MyExpFloaterList,
+    MyEntity, MyExpFloaterList,
 };
 use common::{
     comp::{HealthSource, Pos, Stats},
@@ -72,9 +72,10 @@ impl<'a> System<'a> for Sys {
                 // (maybe health changes could be sent to the client as a list
                 // of events)
                 if match health.last_change.1.cause {
-                    HealthSource::Attack { by }
+                    HealthSource::Attack { by, .. }
                     | HealthSource::Projectile { owner: Some(by) }
                     | HealthSource::Energy { owner: Some(by) }
+                    | HealthSource::Buff { owner: Some(by) }
                     | HealthSource::Explosion { owner: Some(by) }
                     | HealthSource::Healing { by: Some(by) } => {
                         let by_me = my_uid.map_or(false, |&uid| by == uid);
@@ -85,7 +86,8 @@ impl<'a> System<'a> for Sys {
                         my_entity.0 == entity || by_me
                     },
                     HealthSource::Suicide => my_entity.0 == entity,
-                    HealthSource::World => my_entity.0 == entity,
+                    HealthSource::World | HealthSource::Hazard(_) => my_entity.0 == entity,
+                    HealthSource::Buff { owner: None } => my_entity.0 == entity,
                     HealthSource::LevelUp => my_entity.0 == entity,
                     HealthSource::Command => true,
                     HealthSource::Item => true,
@@ -95,6 +97,7 @@ impl<'a> System<'a> for Sys {
                         timer: 0.0,
                         hp_change: health.last_change.1.amount,
                         rand: rand::random(),
+                        crit: false,
                     });
                 }
             }
@@ -144,31 +147,9 @@ impl<'a> System<'a> for Sys {
             }
         }
 
-        // Update MyExpFloaterList
-        if let Some(stats) = stats.get(my_entity.0) {
+        // Update MyExpFloaterList, floaters are added when the server reports exp gains
+        if stats.get(my_entity.0).is_some() {
             let mut fl = my_exp_floater_list;
-            // Add a floater if exp changed
-            // TODO: can't handle if you level up more than once (maybe store total exp in
-            // stats)
-            let exp_change = if stats.level.level() != fl.last_level {
-                if stats.level.level() > fl.last_level {
-                    stats.exp.current() as i32 + fl.last_exp_max as i32 - fl.last_exp as i32
-                } else {
-                    // Level down
-                    stats.exp.current() as i32 - stats.exp.maximum() as i32 - fl.last_exp as i32
-                }
-            } else {
-                stats.exp.current() as i32 - fl.last_exp as i32
-            };
-
-            if exp_change != 0 {
-                fl.floaters.push(ExpFloater {
-                    timer: 0.0,
-                    exp_change,
-                    rand: (rand::random(), rand::random()),
-                });
-            }
-
             // Increment timers
             for mut floater in &mut fl.floaters {
                 floater.timer += dt.0;
@@ -182,18 +163,9 @@ impl<'a> System<'a> for Sys {
             {
                 fl.floaters.clear();
             }
-
-            // Update stored values
-            fl.last_exp = stats.exp.current();
-            fl.last_exp_max = stats.exp.maximum();
-            fl.last_level = stats.level.level();
         } else {
             // Clear if stats component doesn't exist
             my_exp_floater_list.floaters.clear();
-            // Clear stored values
-            my_exp_floater_list.last_exp = 0;
-            my_exp_floater_list.last_exp_max = 0;
-            my_exp_floater_list.last_level = 0;
         }
     }
 }
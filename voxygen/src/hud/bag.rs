@@ -16,7 +16,11 @@ use crate::{
     },
 };
 use client::Client;
-use common::comp::{item::Quality, Stats};
+use common::comp::{
+    item::{ItemKind, Quality},
+    slot::AppearanceChoice,
+    Appearance, Inventory, Item, Loadout, Stats,
+};
 use conrod_core::{
     color,
     widget::{self, Button, Image, Rectangle, Text},
@@ -50,6 +54,10 @@ widget_ids! {
         tab_2,
         tab_3,
         tab_4,
+        // Wardrobe
+        wardrobe_alignment,
+        wardrobe_names[],
+        wardrobe_buttons[],
         // Stats
         stats_alignment,
         level,
@@ -101,6 +109,7 @@ pub struct Bag<'a> {
 
     stats: &'a Stats,
     show: &'a Show,
+    anchor_area: widget::Id,
 }
 
 impl<'a> Bag<'a> {
@@ -117,6 +126,7 @@ impl<'a> Bag<'a> {
         localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
         stats: &'a Stats,
         show: &'a Show,
+        anchor_area: widget::Id,
     ) -> Self {
         Self {
             client,
@@ -131,19 +141,66 @@ impl<'a> Bag<'a> {
             localized_strings,
             stats,
             show,
+            anchor_area,
         }
     }
 }
 
 pub struct State {
     ids: Ids,
+    /// Whether the wardrobe is shown instead of the equipment
+    wardrobe: bool,
 }
 
 pub enum Event {
     Stats,
+    SetAppearance(ArmorSlot, AppearanceChoice),
     Close,
 }
 
+/// Armor slots that can be changed in the wardrobe, with their names
+const WARDROBE_SLOTS: [(ArmorSlot, &str); 8] = [
+    (ArmorSlot::Head, "hud.bag.head"),
+    (ArmorSlot::Shoulders, "hud.bag.shoulders"),
+    (ArmorSlot::Chest, "hud.bag.chest"),
+    (ArmorSlot::Hands, "hud.bag.hands"),
+    (ArmorSlot::Belt, "hud.bag.belt"),
+    (ArmorSlot::Legs, "hud.bag.legs"),
+    (ArmorSlot::Feet, "hud.bag.feet"),
+    (ArmorSlot::Back, "hud.bag.back"),
+];
+
+/// The appearance an armor slot changes to next in the wardrobe. This cycles
+/// through the equipped armor, nothing and the fitting armor in the inventory.
+fn next_appearance(slot: ArmorSlot, loadout: &Loadout, inventory: &Inventory) -> AppearanceChoice {
+    let choices = [AppearanceChoice::Equipped, AppearanceChoice::Hidden]
+        .iter()
+        .copied()
+        .chain(
+            inventory
+                .slots()
+                .iter()
+                .enumerate()
+                .filter_map(|(i, item)| match item.as_ref().map(Item::kind) {
+                    Some(ItemKind::Armor(armor)) if slot.can_hold(&armor.kind) => {
+                        Some(AppearanceChoice::Item(i))
+                    },
+                    _ => None,
+                }),
+        )
+        .collect::<Vec<_>>();
+    let current = match loadout.appearance.get(&slot) {
+        None => Some(0),
+        Some(Appearance::Hidden) => Some(1),
+        Some(Appearance::Item(shown)) => choices.iter().position(|choice| {
+            matches!(choice, AppearanceChoice::Item(i) if inventory.get(*i) == Some(shown))
+        }),
+    };
+    current.map_or(AppearanceChoice::Equipped, |i| {
+        choices[(i + 1) % choices.len()]
+    })
+}
+
 impl<'a> Widget for Bag<'a> {
     type Event = Option<Event>;
     type State = State;
@@ -152,6 +209,7 @@ impl<'a> Widget for Bag<'a> {
     fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
         State {
             ids: Ids::new(id_gen),
+            wardrobe: false,
         }
     }
 
@@ -207,13 +265,13 @@ impl<'a> Widget for Bag<'a> {
         .font_id(self.fonts.cyri.conrod_id)
         .desc_text_color(TEXT_COLOR);
         // BG
-        Image::new(if self.show.stats {
+        Image::new(if self.show.stats || state.wardrobe {
             self.imgs.inv_bg_stats
         } else {
             self.imgs.inv_bg_armor
         })
         .w_h(424.0, 708.0)
-        .bottom_right_with_margins_on(ui.window, 60.0, 5.0)
+        .bottom_right_with_margins_on(self.anchor_area, 60.0, 5.0)
         .color(Some(UI_MAIN))
         .set(state.ids.bg, ui);
         Image::new(self.imgs.inv_frame)
@@ -285,7 +343,77 @@ impl<'a> Widget for Bag<'a> {
             .scroll_kids_vertically()
             .set(state.ids.inv_alignment, ui);
 
-        if !self.show.stats {
+        if state.wardrobe {
+            // Title
+            Text::new(&self.localized_strings.get("hud.bag.wardrobe"))
+                .mid_top_with_margin_on(state.ids.bg_frame, 9.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(22))
+                .color(Color::Rgba(0.0, 0.0, 0.0, 1.0))
+                .set(state.ids.inventory_title_bg, ui);
+            Text::new(&self.localized_strings.get("hud.bag.wardrobe"))
+                .top_left_with_margins_on(state.ids.inventory_title_bg, 2.0, 2.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(22))
+                .color(TEXT_COLOR)
+                .set(state.ids.inventory_title, ui);
+            Rectangle::fill_with([362.0, 384.0], color::TRANSPARENT)
+                .mid_top_with_margin_on(state.ids.bg_frame, 48.0)
+                .set(state.ids.wardrobe_alignment, ui);
+            if state.ids.wardrobe_names.len() < WARDROBE_SLOTS.len() {
+                state.update(|s| {
+                    let gen = &mut ui.widget_id_generator();
+                    s.ids.wardrobe_names.resize(WARDROBE_SLOTS.len(), gen);
+                    s.ids.wardrobe_buttons.resize(WARDROBE_SLOTS.len(), gen);
+                });
+            }
+            // One row per slot, with what is shown in it and a button to change
+            // that
+            for (i, (slot, name)) in WARDROBE_SLOTS.iter().enumerate() {
+                let shown = match loadout.appearance.get(slot) {
+                    Some(Appearance::Hidden) => {
+                        self.localized_strings.get("hud.bag.wardrobe.hidden")
+                    },
+                    Some(Appearance::Item(item)) => item.name(),
+                    None => loadout.armor(*slot).map_or(
+                        self.localized_strings.get("hud.bag.wardrobe.empty"),
+                        |item| item.name(),
+                    ),
+                };
+                Text::new(&format!("{}: {}", self.localized_strings.get(name), shown))
+                    .top_left_with_margins_on(
+                        state.ids.wardrobe_alignment,
+                        10.0 + i as f64 * 40.0,
+                        10.0,
+                    )
+                    .font_id(self.fonts.cyri.conrod_id)
+                    .font_size(self.fonts.cyri.scale(14))
+                    .color(TEXT_COLOR)
+                    .set(state.ids.wardrobe_names[i], ui);
+                if Button::image(self.imgs.button)
+                    .w_h(70.0, 22.0)
+                    .top_right_with_margins_on(
+                        state.ids.wardrobe_alignment,
+                        6.0 + i as f64 * 40.0,
+                        10.0,
+                    )
+                    .hover_image(self.imgs.button_hover)
+                    .press_image(self.imgs.button_press)
+                    .label(&self.localized_strings.get("hud.bag.wardrobe.change"))
+                    .label_y(conrod_core::position::Relative::Scalar(1.0))
+                    .label_color(TEXT_COLOR)
+                    .label_font_size(self.fonts.cyri.scale(12))
+                    .label_font_id(self.fonts.cyri.conrod_id)
+                    .set(state.ids.wardrobe_buttons[i], ui)
+                    .was_clicked()
+                {
+                    event = Some(Event::SetAppearance(
+                        *slot,
+                        next_appearance(*slot, loadout, inventory),
+                    ));
+                }
+            }
+        } else if !self.show.stats {
             // Title
             Text::new(
                 &self
@@ -856,32 +984,52 @@ impl<'a> Widget for Bag<'a> {
             .set(state.ids.stats_button, ui)
             .was_clicked()
         {
+            state.update(|s| s.wardrobe = false);
             return Some(Event::Stats);
         };
         // Tabs
-        if Button::image(self.imgs.inv_tab_active)
+        let tab_image = |active| {
+            if active {
+                self.imgs.inv_tab_active
+            } else {
+                self.imgs.inv_tab_inactive
+            }
+        };
+        if Button::image(tab_image(!state.wardrobe))
             .w_h(28.0, 44.0)
             .bottom_left_with_margins_on(state.ids.bg, 172.0, 13.0)
-            .image_color(UI_MAIN)
+            .image_color(if state.wardrobe {
+                UI_HIGHLIGHT_0
+            } else {
+                UI_MAIN
+            })
             .set(state.ids.tab_1, ui)
             .was_clicked()
-        {}
-        if Button::image(self.imgs.inv_tab_inactive)
+        {
+            state.update(|s| s.wardrobe = false);
+        }
+        if Button::image(tab_image(state.wardrobe))
             .w_h(28.0, 44.0)
             .hover_image(self.imgs.inv_tab_inactive_hover)
             .press_image(self.imgs.inv_tab_inactive_press)
-            .image_color(UI_HIGHLIGHT_0)
+            .image_color(if state.wardrobe {
+                UI_MAIN
+            } else {
+                UI_HIGHLIGHT_0
+            })
             .down_from(state.ids.tab_1, 0.0)
             .with_tooltip(
                 self.tooltip_manager,
-                "Not yet Available",
+                &self.localized_strings.get("hud.bag.wardrobe"),
                 "",
                 &item_tooltip,
                 TEXT_COLOR,
             )
             .set(state.ids.tab_2, ui)
             .was_clicked()
-        {}
+        {
+            state.update(|s| s.wardrobe = true);
+        }
         if Button::image(self.imgs.inv_tab_inactive)
             .w_h(28.0, 44.0)
             .hover_image(self.imgs.inv_tab_inactive_hover)
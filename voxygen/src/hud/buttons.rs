@@ -57,6 +57,7 @@ pub struct Buttons<'a> {
     tooltip_manager: &'a mut TooltipManager,
     localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
     stats: &'a Stats,
+    anchor_area: widget::Id,
 }
 
 impl<'a> Buttons<'a> {
@@ -71,6 +72,7 @@ impl<'a> Buttons<'a> {
         tooltip_manager: &'a mut TooltipManager,
         localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
         stats: &'a Stats,
+        anchor_area: widget::Id,
     ) -> Self {
         Self {
             client,
@@ -83,6 +85,7 @@ impl<'a> Buttons<'a> {
             tooltip_manager,
             localized_strings,
             stats,
+            anchor_area,
         }
     }
 }
@@ -145,7 +148,7 @@ impl<'a> Widget for Buttons<'a> {
         } else {
             self.imgs.bag_open
         })
-        .bottom_right_with_margins_on(ui.window, 5.0, 5.0)
+        .bottom_right_with_margins_on(self.anchor_area, 5.0, 5.0)
         .hover_image(if !self.show_bag {
             self.imgs.bag_hover
         } else {
@@ -217,7 +220,7 @@ impl<'a> Widget for Buttons<'a> {
         // Settings
         if Button::image(self.imgs.settings)
             .w_h(29.0, 25.0)
-            .bottom_right_with_margins_on(ui.window, 5.0, 57.0)
+            .bottom_right_with_margins_on(self.anchor_area, 5.0, 57.0)
             .hover_image(self.imgs.settings_hover)
             .press_image(self.imgs.settings_press)
             .with_tooltip(
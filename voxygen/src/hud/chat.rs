@@ -9,7 +9,8 @@ use common::{
         chat::{KillSource, KillType},
         ChatMsg, ChatType,
     },
-    msg::validate_chat_msg,
+    msg::{validate_chat_msg, PlayerInfo},
+    sync::Uid,
 };
 use conrod_core::{
     input::Key,
@@ -21,6 +22,7 @@ use conrod_core::{
     widget::{self, Button, Id, Image, List, Rectangle, Text, TextEdit},
     widget_ids, Color, Colorable, Positionable, Sizeable, Ui, UiCell, Widget, WidgetCommon,
 };
+use hashbrown::HashMap;
 use std::collections::VecDeque;
 
 widget_ids! {
@@ -46,6 +48,8 @@ const CHAT_BOX_HEIGHT: f64 = 174.0;
 pub struct Chat<'a> {
     new_messages: &'a mut VecDeque<ChatMsg>,
     client: &'a Client,
+    /// The player list as the HUD shows it, names are taken from here
+    player_list: &'a HashMap<Uid, PlayerInfo>,
     force_input: Option<String>,
     force_cursor: Option<Index>,
     force_completions: Option<Vec<String>>,
@@ -61,20 +65,24 @@ pub struct Chat<'a> {
     history_max: usize,
 
     localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
+    anchor_area: widget::Id,
 }
 
 impl<'a> Chat<'a> {
     pub fn new(
         new_messages: &'a mut VecDeque<ChatMsg>,
         client: &'a Client,
+        player_list: &'a HashMap<Uid, PlayerInfo>,
         global_state: &'a GlobalState,
         imgs: &'a Imgs,
         fonts: &'a ConrodVoxygenFonts,
         localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
+        anchor_area: widget::Id,
     ) -> Self {
         Self {
             new_messages,
             client,
+            player_list,
             force_input: None,
             force_cursor: None,
             force_completions: None,
@@ -84,6 +92,7 @@ impl<'a> Chat<'a> {
             common: widget::CommonBuilder::default(),
             history_max: 32,
             localized_strings,
+            anchor_area,
         }
     }
 
@@ -295,7 +304,7 @@ impl<'a> Widget for Chat<'a> {
             };
             Rectangle::fill([CHAT_BOX_WIDTH, y])
                 .rgba(0.0, 0.0, 0.0, transp + 0.1)
-                .bottom_left_with_margins_on(ui.window, 10.0, 10.0)
+                .bottom_left_with_margins_on(self.anchor_area, 10.0, 10.0)
                 .w(CHAT_BOX_WIDTH)
                 .set(state.ids.chat_input_bg, ui);
 
@@ -318,7 +327,7 @@ impl<'a> Widget for Chat<'a> {
                 if input_focused {
                     r.up_from(state.ids.chat_input_bg, 0.0)
                 } else {
-                    r.bottom_left_with_margins_on(ui.window, 10.0, 10.0)
+                    r.bottom_left_with_margins_on(self.anchor_area, 10.0, 10.0)
                 }
             })
             .crop_kids()
@@ -373,6 +382,10 @@ impl<'a> Widget for Chat<'a> {
                             .localized_strings
                             .get("hud.chat.pvp_energy_kill_msg")
                             .to_string(),
+                        KillSource::Player(_, KillType::Buff) => self
+                            .localized_strings
+                            .get("hud.chat.pvp_buff_kill_msg")
+                            .to_string(),
                         KillSource::NonPlayer(_, KillType::Melee) => self
                             .localized_strings
                             .get("hud.chat.npc_melee_kill_msg")
@@ -389,6 +402,10 @@ impl<'a> Widget for Chat<'a> {
                             .localized_strings
                             .get("hud.chat.npc_energy_kill_msg")
                             .to_string(),
+                        KillSource::NonPlayer(_, KillType::Buff) => self
+                            .localized_strings
+                            .get("hud.chat.npc_buff_kill_msg")
+                            .to_string(),
                         KillSource::Environment(_) => self
                             .localized_strings
                             .get("hud.chat.environmental_kill_msg")
@@ -408,7 +425,9 @@ impl<'a> Widget for Chat<'a> {
                     },
                     _ => message.message,
                 };
-                let msg = self.client.format_message(&message, show_char_name);
+                let msg =
+                    self.client
+                        .format_message_with(&message, show_char_name, self.player_list);
                 let text = Text::new(&msg)
                     .font_size(self.fonts.opensans.scale(15))
                     .font_id(self.fonts.opensans.conrod_id)
@@ -557,6 +576,7 @@ fn render_chat_line(chat_type: &ChatType<String>, imgs: &Imgs) -> (Color, conrod
         ChatType::Kill(_, _) => (KILL_COLOR, imgs.chat_kill_small),
         ChatType::Tell(_from, _to) => (TELL_COLOR, imgs.chat_tell_small),
         ChatType::Say(_uid) => (SAY_COLOR, imgs.chat_say_small),
+        ChatType::Shout(_uid) => (SAY_COLOR, imgs.chat_say_small),
         ChatType::Group(_uid, _s) => (GROUP_COLOR, imgs.chat_group_small),
         ChatType::Faction(_uid, _s) => (FACTION_COLOR, imgs.chat_faction_small),
         ChatType::Region(_uid) => (REGION_COLOR, imgs.chat_region_small),
@@ -0,0 +1,504 @@
+use super::{
+    img_ids::{Imgs, ImgsRot},
+    Show, HP_COLOR, KILL_COLOR, TEXT_COLOR, TEXT_COLOR_GREY, UI_HIGHLIGHT_0, UI_MAIN,
+};
+
+use crate::{
+    i18n::VoxygenLocalization,
+    ui::{fonts::ConrodVoxygenFonts, ImageFrame, Tooltip, TooltipManager, Tooltipable},
+};
+use client::Client;
+use common::{
+    combat::{self, Involvement},
+    comp::{DamageSource, HitModifiers, Stats},
+    sync::{Uid, WorldSyncExt},
+};
+use conrod_core::{
+    color,
+    widget::{self, Button, Image, List, Rectangle, Text},
+    widget_ids, Color, Colorable, Labelable, Positionable, Sizeable, Widget, WidgetCommon,
+};
+use specs::WorldExt;
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Number of events kept for display, older ones are dropped
+pub const MAX_COMBAT_EVENTS: usize = 200;
+/// Seconds of dealt damage taken into account by the DPS meter
+const DPS_WINDOW: f64 = 5.0;
+
+widget_ids! {
+    pub struct Ids {
+        frame,
+        bg,
+        close,
+        title_align,
+        title,
+        all_tab,
+        dealt_tab,
+        taken_tab,
+        export_button,
+        export_label,
+        event_list,
+        empty_txt,
+    }
+}
+
+pub struct State {
+    ids: Ids,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CombatLogTab {
+    All,
+    Dealt,
+    Taken,
+}
+
+/// A health change as it is shown in the combat log, with names resolved at
+/// the time it was received since the entities involved may be gone later on
+#[derive(Clone, Debug)]
+pub struct CombatEvent {
+    /// Game time at which the event happened
+    pub time: f64,
+    pub involvement: Involvement,
+    pub attacker: Option<String>,
+    pub target: String,
+    /// Change in health of the target, negative for damage
+    pub amount: i32,
+    pub source: DamageSource,
+    pub hit: HitModifiers,
+}
+
+impl CombatEvent {
+    /// Builds the log entry of a health change sent by the server
+    pub fn from_combat_event(event: &combat::CombatEvent, client: &Client) -> Option<Self> {
+        if let combat::CombatEvent::Damage {
+            target,
+            change,
+            source,
+            hit,
+            ..
+        } = event
+        {
+            Some(Self {
+                time: client.state().get_time(),
+                involvement: event.involvement(client.uid()),
+                attacker: event.attacker().map(|uid| uid_to_name(uid, client)),
+                target: uid_to_name(*target, client),
+                amount: change.amount,
+                source: *source,
+                hit: *hit,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn is_shown_in(&self, tab: CombatLogTab) -> bool {
+        match tab {
+            CombatLogTab::All => true,
+            CombatLogTab::Dealt => self.involvement == Involvement::Dealt,
+            CombatLogTab::Taken => self.involvement == Involvement::Taken,
+        }
+    }
+
+    /// A single line of comma separated values, matching `CSV_HEADER`
+    fn to_csv(&self) -> String {
+        format!(
+            "{:.3},{},{},{},{:?},{},{},{},{}",
+            self.time,
+            self.involvement.as_str(),
+            csv_field(self.attacker.as_deref().unwrap_or("")),
+            csv_field(&self.target),
+            self.source,
+            self.amount,
+            self.hit.mitigated,
+            self.hit.crit,
+            self.hit.blocked,
+        )
+    }
+}
+
+/// Damage per second dealt by the player over the last few seconds, or `None`
+/// if they didn't deal any. It is measured from the first hit in the window so
+/// that it doesn't start out low when a fight begins.
+pub fn damage_per_second(events: &VecDeque<CombatEvent>, now: f64) -> Option<f32> {
+    let mut total = 0;
+    let mut first_hit = None;
+    for event in events
+        .iter()
+        .rev()
+        .take_while(|event| now - event.time <= DPS_WINDOW)
+        .filter(|event| event.involvement == Involvement::Dealt && event.amount < 0)
+    {
+        total += -event.amount;
+        first_hit = Some(event.time);
+    }
+    first_hit.map(|first_hit| total as f32 / (now - first_hit).max(1.0) as f32)
+}
+
+pub fn uid_to_name(uid: Uid, client: &Client) -> String {
+    match client.player_list.get(&uid) {
+        Some(player_info) => player_info
+            .character
+            .as_ref()
+            .map_or_else(|| player_info.player_alias.clone(), |c| c.name.clone()),
+        None => client
+            .state()
+            .ecs()
+            .entity_from_uid(uid.0)
+            .and_then(|entity| {
+                client
+                    .state()
+                    .ecs()
+                    .read_storage::<Stats>()
+                    .get(entity)
+                    .map(|stats| stats.name.clone())
+            })
+            .unwrap_or_else(|| format!("Npc<{}>", uid)),
+    }
+}
+
+/// Quotes a value if it would otherwise break the csv layout
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+const CSV_HEADER: &str =
+    "time,involvement,attacker,target,source,amount,mitigated,critical,blocked";
+
+/// Appends combat events to a csv file for analysis outside of the game
+pub struct CombatLogExport {
+    writer: BufWriter<File>,
+}
+
+impl CombatLogExport {
+    /// Opens a new file named after the current date in `dir`
+    pub fn new(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        let file_name = chrono::Local::now()
+            .format("combat_log_%Y-%m-%d_%H-%M-%S.csv")
+            .to_string();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(file_name))?;
+        let mut writer = BufWriter::new(file);
+        writeln!(writer, "{}", CSV_HEADER)?;
+        Ok(Self { writer })
+    }
+
+    pub fn write(&mut self, event: &CombatEvent) -> io::Result<()> {
+        writeln!(self.writer, "{}", event.to_csv())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> { self.writer.flush() }
+}
+
+#[derive(WidgetCommon)]
+pub struct CombatLog<'a> {
+    show: &'a Show,
+    events: &'a VecDeque<CombatEvent>,
+    export: bool,
+    imgs: &'a Imgs,
+    rot_imgs: &'a ImgsRot,
+    fonts: &'a ConrodVoxygenFonts,
+    localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
+    tooltip_manager: &'a mut TooltipManager,
+
+    #[conrod(common_builder)]
+    common: widget::CommonBuilder,
+    anchor_area: widget::Id,
+}
+
+impl<'a> CombatLog<'a> {
+    #[allow(clippy::too_many_arguments)] // TODO: Pending review in #587
+    pub fn new(
+        show: &'a Show,
+        events: &'a VecDeque<CombatEvent>,
+        export: bool,
+        imgs: &'a Imgs,
+        rot_imgs: &'a ImgsRot,
+        fonts: &'a ConrodVoxygenFonts,
+        localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
+        tooltip_manager: &'a mut TooltipManager,
+        anchor_area: widget::Id,
+    ) -> Self {
+        Self {
+            show,
+            events,
+            export,
+            imgs,
+            rot_imgs,
+            fonts,
+            localized_strings,
+            tooltip_manager,
+            common: widget::CommonBuilder::default(),
+            anchor_area,
+        }
+    }
+
+    fn format_event(&self, event: &CombatEvent) -> String {
+        let source = self.localized_strings.get(match event.source {
+            DamageSource::Melee => "hud.combat_log.source.melee",
+            DamageSource::Healing => "hud.combat_log.source.healing",
+            DamageSource::Projectile => "hud.combat_log.source.projectile",
+            DamageSource::Explosion => "hud.combat_log.source.explosion",
+            DamageSource::Falling => "hud.combat_log.source.falling",
+            DamageSource::Shockwave => "hud.combat_log.source.shockwave",
+            DamageSource::Energy => "hud.combat_log.source.energy",
+            DamageSource::Buff => "hud.combat_log.source.buff",
+        });
+        let key = match (event.attacker.is_some(), event.amount >= 0) {
+            (true, false) => "hud.combat_log.damage",
+            (true, true) => "hud.combat_log.heal",
+            (false, false) => "hud.combat_log.damage_unknown",
+            (false, true) => "hud.combat_log.heal_unknown",
+        };
+        let mut line = self
+            .localized_strings
+            .get(key)
+            .replace("{attacker}", event.attacker.as_deref().unwrap_or(""))
+            .replace("{target}", &event.target)
+            .replace("{amount}", &event.amount.abs().to_string())
+            .replace("{source}", source);
+        if event.hit.crit {
+            line.push_str(self.localized_strings.get("hud.combat_log.critical"));
+        }
+        if event.hit.blocked {
+            line.push_str(self.localized_strings.get("hud.combat_log.blocked"));
+        }
+        if event.hit.mitigated > 0 {
+            line.push_str(
+                &self
+                    .localized_strings
+                    .get("hud.combat_log.mitigated")
+                    .replace("{amount}", &event.hit.mitigated.to_string()),
+            );
+        }
+        line
+    }
+}
+
+pub enum Event {
+    Close,
+    ChangeTab(CombatLogTab),
+    ToggleExport(bool),
+}
+
+impl<'a> Widget for CombatLog<'a> {
+    type Event = Vec<Event>;
+    type State = State;
+    type Style = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        Self::State {
+            ids: Ids::new(id_gen),
+        }
+    }
+
+    #[allow(clippy::unused_unit)] // TODO: Pending review in #587
+    fn style(&self) -> Self::Style { () }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs { state, ui, .. } = args;
+        let mut events = Vec::new();
+        let button_tooltip = Tooltip::new({
+            // Edge images [t, b, r, l]
+            // Corner images [tr, tl, br, bl]
+            let edge = &self.rot_imgs.tt_side;
+            let corner = &self.rot_imgs.tt_corner;
+            ImageFrame::new(
+                [edge.cw180, edge.none, edge.cw270, edge.cw90],
+                [corner.none, corner.cw270, corner.cw90, corner.cw180],
+                Color::Rgba(0.08, 0.07, 0.04, 1.0),
+                5.0,
+            )
+        })
+        .title_font_size(self.fonts.cyri.scale(15))
+        .parent(ui.window)
+        .desc_font_size(self.fonts.cyri.scale(12))
+        .font_id(self.fonts.cyri.conrod_id)
+        .desc_text_color(TEXT_COLOR);
+
+        // Window frame and BG
+        Image::new(self.imgs.social_bg_on)
+            .bottom_right_with_margins_on(self.anchor_area, 308.0, 25.0)
+            .color(Some(UI_MAIN))
+            .w_h(280.0, 460.0)
+            .set(state.ids.bg, ui);
+        Image::new(self.imgs.social_frame_on)
+            .middle_of(state.ids.bg)
+            .color(Some(UI_HIGHLIGHT_0))
+            .w_h(280.0, 460.0)
+            .set(state.ids.frame, ui);
+        // X-Button
+        if Button::image(self.imgs.close_button)
+            .w_h(24.0, 25.0)
+            .hover_image(self.imgs.close_button_hover)
+            .press_image(self.imgs.close_button_press)
+            .top_right_with_margins_on(state.ids.frame, 0.0, 0.0)
+            .set(state.ids.close, ui)
+            .was_clicked()
+        {
+            events.push(Event::Close);
+        }
+
+        // Title
+        Rectangle::fill_with([212.0, 42.0], color::TRANSPARENT)
+            .top_left_with_margins_on(state.ids.frame, 2.0, 44.0)
+            .set(state.ids.title_align, ui);
+        Text::new(&self.localized_strings.get("hud.combat_log"))
+            .middle_of(state.ids.title_align)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(20))
+            .color(TEXT_COLOR)
+            .set(state.ids.title, ui);
+
+        // Filter tabs
+        let tabs = [
+            (CombatLogTab::All, state.ids.all_tab, "hud.combat_log.all"),
+            (
+                CombatLogTab::Dealt,
+                state.ids.dealt_tab,
+                "hud.combat_log.dealt",
+            ),
+            (
+                CombatLogTab::Taken,
+                state.ids.taken_tab,
+                "hud.combat_log.taken",
+            ),
+        ];
+        for (i, (tab, id, key)) in tabs.iter().enumerate() {
+            let button = Button::image(self.imgs.button)
+                .w_h(80.0, 24.0)
+                .hover_image(self.imgs.button_hover)
+                .press_image(self.imgs.button_press)
+                .label(&self.localized_strings.get(key))
+                .label_font_size(self.fonts.cyri.scale(14))
+                .label_font_id(self.fonts.cyri.conrod_id)
+                .label_color(if self.show.combat_log_tab == *tab {
+                    TEXT_COLOR
+                } else {
+                    TEXT_COLOR_GREY
+                });
+            let button = if i == 0 {
+                button.top_left_with_margins_on(state.ids.frame, 48.0, 12.0)
+            } else {
+                button.right_from(tabs[i - 1].1, 4.0)
+            };
+            if button.set(*id, ui).was_clicked() {
+                events.push(Event::ChangeTab(*tab));
+            }
+        }
+
+        // Events, newest at the top
+        let shown = self
+            .events
+            .iter()
+            .rev()
+            .filter(|event| event.is_shown_in(self.show.combat_log_tab))
+            .collect::<Vec<_>>();
+        if shown.is_empty() {
+            Text::new(&self.localized_strings.get("hud.combat_log.empty"))
+                .mid_top_with_margin_on(state.ids.frame, 90.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(14))
+                .color(TEXT_COLOR_GREY)
+                .set(state.ids.empty_txt, ui);
+        }
+        let (mut items, _) = List::flow_down(shown.len())
+            .top_left_with_margins_on(state.ids.frame, 80.0, 12.0)
+            .w_h(256.0, 340.0)
+            .scroll_kids_vertically()
+            .set(state.ids.event_list, ui);
+        while let Some(item) = items.next(ui) {
+            let event = shown[item.i];
+            let color = match event.involvement {
+                _ if event.amount > 0 => HP_COLOR,
+                Involvement::Taken => KILL_COLOR,
+                Involvement::Dealt => TEXT_COLOR,
+                Involvement::Other => TEXT_COLOR_GREY,
+            };
+            let text = Text::new(&self.format_event(event))
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(12))
+                .color(color)
+                .w(256.0);
+            item.set(text, ui);
+        }
+
+        // Export to file
+        let export = Button::image(if self.export {
+            self.imgs.checkbox_checked
+        } else {
+            self.imgs.checkbox
+        })
+        .w_h(18.0, 18.0)
+        .bottom_left_with_margins_on(state.ids.frame, 12.0, 12.0)
+        .with_tooltip(
+            self.tooltip_manager,
+            &self.localized_strings.get("hud.combat_log.export"),
+            &self.localized_strings.get("hud.combat_log.export_desc"),
+            &button_tooltip,
+            TEXT_COLOR,
+        )
+        .set(state.ids.export_button, ui);
+        if export.was_clicked() {
+            events.push(Event::ToggleExport(!self.export));
+        }
+        Text::new(&self.localized_strings.get("hud.combat_log.export"))
+            .right_from(state.ids.export_button, 8.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(14))
+            .graphics_for(state.ids.export_button)
+            .color(TEXT_COLOR)
+            .set(state.ids.export_label, ui);
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("Wolf"), "Wolf");
+        assert_eq!(
+            csv_field("Bob, the \"Brave\""),
+            "\"Bob, the \"\"Brave\"\"\""
+        );
+    }
+
+    #[test]
+    fn dps_only_counts_recent_damage_dealt() {
+        let event = |time, involvement, amount| CombatEvent {
+            time,
+            involvement,
+            attacker: None,
+            target: "Training Dummy".to_owned(),
+            amount,
+            source: DamageSource::Melee,
+            hit: HitModifiers::default(),
+        };
+        let mut events = VecDeque::new();
+        assert_eq!(damage_per_second(&events, 10.0), None);
+        events.push_back(event(1.0, Involvement::Dealt, -1000));
+        events.push_back(event(6.0, Involvement::Dealt, -100));
+        events.push_back(event(7.0, Involvement::Taken, -500));
+        events.push_back(event(7.5, Involvement::Dealt, 50));
+        events.push_back(event(8.0, Involvement::Dealt, -200));
+        assert_eq!(damage_per_second(&events, 10.0), Some(75.0));
+    }
+}
@@ -9,9 +9,12 @@ use crate::{
     ui::{fonts::ConrodVoxygenFonts, ImageFrame, Tooltip, TooltipManager, Tooltipable},
 };
 use client::{self, Client};
-use common::comp::{
-    item::{ItemDesc, Quality},
-    Inventory,
+use common::{
+    alchemy::{default_alchemy, Property},
+    comp::{
+        item::{ItemDesc, Quality},
+        AlchemyJournal, Inventory,
+    },
 };
 use conrod_core::{
     color,
@@ -43,11 +46,18 @@ widget_ids! {
         output_img_frame,
         output_img,
         output_amount,
+        btn_brewing,
+        btn_brew,
+        brew_frame[],
+        brew_img[],
+        brew_text[],
     }
 }
 
 pub enum Event {
     CraftRecipe(String),
+    /// Brew the ingredients in the given inventory slots
+    Brew(Vec<usize>),
     Close,
 }
 
@@ -63,6 +73,7 @@ pub struct Crafting<'a> {
     inventory: &'a Inventory,
     #[conrod(common_builder)]
     common: widget::CommonBuilder,
+    anchor_area: widget::Id,
 }
 #[allow(clippy::too_many_arguments)]
 impl<'a> Crafting<'a> {
@@ -75,6 +86,7 @@ impl<'a> Crafting<'a> {
         tooltip_manager: &'a mut TooltipManager,
         item_imgs: &'a ItemImgs,
         inventory: &'a Inventory,
+        anchor_area: widget::Id,
     ) -> Self {
         Self {
             client,
@@ -86,6 +98,7 @@ impl<'a> Crafting<'a> {
             item_imgs,
             inventory,
             common: widget::CommonBuilder::default(),
+            anchor_area,
         }
     }
 }
@@ -93,6 +106,10 @@ impl<'a> Crafting<'a> {
 pub struct State {
     ids: Ids,
     selected_recipe: Option<String>,
+    /// Whether the brewing page is shown instead of a recipe
+    brewing: bool,
+    /// Inventory slots of the ingredients picked for brewing
+    brew_slots: Vec<usize>,
 }
 
 impl<'a> Widget for Crafting<'a> {
@@ -104,6 +121,8 @@ impl<'a> Widget for Crafting<'a> {
         State {
             ids: Ids::new(id_gen),
             selected_recipe: None,
+            brewing: false,
+            brew_slots: Vec::new(),
         }
     }
 
@@ -143,7 +162,7 @@ impl<'a> Widget for Crafting<'a> {
         .desc_text_color(TEXT_COLOR);
 
         Image::new(self.imgs.crafting_window)
-            .bottom_right_with_margins_on(ui.window, 308.0, 450.0)
+            .bottom_right_with_margins_on(self.anchor_area, 308.0, 450.0)
             .color(Some(UI_MAIN))
             .w_h(422.0, 460.0)
             .set(ids.window, ui);
@@ -294,6 +313,30 @@ impl<'a> Widget for Crafting<'a> {
             },
         }
 
+        // Brewing, above the recipes
+        if Button::image(if state.brewing {
+            self.imgs.selection
+        } else {
+            self.imgs.nothing
+        })
+        .mid_top_with_margin_on(state.ids.align_rec, 2.0)
+        .label(&self.localized_strings.get("hud.crafting.brewing"))
+        .w_h(130.0, 20.0)
+        .hover_image(self.imgs.selection_hover)
+        .press_image(self.imgs.selection_press)
+        .label_color(TEXT_COLOR)
+        .label_font_size(self.fonts.cyri.scale(12))
+        .label_font_id(self.fonts.cyri.conrod_id)
+        .label_y(conrod_core::position::Relative::Scalar(2.0))
+        .set(state.ids.btn_brewing, ui)
+        .was_clicked()
+        {
+            state.update(|s| {
+                s.brewing = !s.brewing;
+                s.selected_recipe = None;
+            });
+        }
+
         // Recipe list
         for (i, (name, recipe, can_perform)) in recipe_iter.enumerate() {
             let button = Button::image(
@@ -313,7 +356,7 @@ impl<'a> Widget for Crafting<'a> {
             );
             // Recipe Button
             let button = if i == 0 {
-                button.mid_top_with_margin_on(state.ids.align_rec, 2.0)
+                button.mid_bottom_with_margin_on(state.ids.btn_brewing, -25.0)
             } else {
                 button.mid_bottom_with_margin_on(state.ids.recipe_names[i - 1], -25.0)
             };
@@ -337,7 +380,10 @@ impl<'a> Widget for Crafting<'a> {
                 {
                     state.update(|s| s.selected_recipe = None);
                 } else {
-                    state.update(|s| s.selected_recipe = Some(name.clone()));
+                    state.update(|s| {
+                        s.selected_recipe = Some(name.clone());
+                        s.brewing = false;
+                    });
                 }
             }
         }
@@ -490,6 +536,154 @@ impl<'a> Widget for Crafting<'a> {
             }
         }
 
+        // Brewing
+        if state.brewing {
+            let alchemy = default_alchemy();
+            let journal = self
+                .client
+                .state()
+                .ecs()
+                .read_storage::<AlchemyJournal>()
+                .get(self.client.entity())
+                .cloned()
+                .unwrap_or_default();
+            let ingredients = self
+                .inventory
+                .slots()
+                .iter()
+                .enumerate()
+                .filter_map(|(slot, item)| item.as_ref().map(|item| (slot, item)))
+                .filter(|(_, item)| alchemy.is_ingredient(item.item_definition_id()))
+                .collect::<Vec<_>>();
+            // Forget picked slots that don't hold an ingredient anymore
+            if state
+                .brew_slots
+                .iter()
+                .any(|slot| ingredients.iter().all(|(i, _)| i != slot))
+            {
+                state.update(|s| {
+                    s.brew_slots
+                        .retain(|slot| ingredients.iter().any(|(i, _)| i == slot))
+                });
+            }
+            if state.ids.brew_frame.len() < ingredients.len() {
+                state.update(|s| {
+                    let gen = &mut ui.widget_id_generator();
+                    s.ids.brew_frame.resize(ingredients.len(), gen);
+                    s.ids.brew_img.resize(ingredients.len(), gen);
+                    s.ids.brew_text.resize(ingredients.len(), gen);
+                });
+            }
+
+            Text::new(&self.localized_strings.get("hud.crafting.brewing"))
+                .mid_top_with_margin_on(state.ids.align_ing, -22.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(14))
+                .color(TEXT_COLOR)
+                .parent(state.ids.window)
+                .set(state.ids.title_ing, ui);
+            Text::new(&self.localized_strings.get("hud.crafting.brew_pick"))
+                .top_left_with_margins_on(state.ids.align_ing, 10.0, 5.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(14))
+                .color(TEXT_COLOR)
+                .set(state.ids.ingredients_txt, ui);
+
+            for (i, (slot, item)) in ingredients.iter().enumerate() {
+                let picked = state.brew_slots.contains(slot);
+                let frame = Image::new(if picked {
+                    self.imgs.inv_slot_green
+                } else {
+                    self.imgs.inv_slot
+                })
+                .w_h(25.0, 25.0);
+                let frame = if i == 0 {
+                    frame.down_from(state.ids.ingredients_txt, 20.0)
+                } else {
+                    frame.down_from(state.ids.brew_frame[i - 1], 10.0)
+                };
+                frame.set(state.ids.brew_frame[i], ui);
+                let (title, desc) = super::util::item_text(*item);
+                if Button::image(self.item_imgs.img_id_or_not_found_img(item.kind().into()))
+                    .w_h(22.0, 22.0)
+                    .middle_of(state.ids.brew_frame[i])
+                    .with_tooltip(
+                        self.tooltip_manager,
+                        title,
+                        &*desc,
+                        &item_tooltip,
+                        get_quality_col(*item),
+                    )
+                    .set(state.ids.brew_img[i], ui)
+                    .was_clicked()
+                {
+                    state.update(|s| {
+                        if picked {
+                            s.brew_slots.retain(|picked| picked != slot);
+                        } else {
+                            s.brew_slots.push(*slot);
+                        }
+                    });
+                }
+                // Only what brewing revealed about the ingredient is shown
+                let known = journal
+                    .known(item.item_definition_id())
+                    .map(|property| self.localized_strings.get(property_key(property)))
+                    .collect::<Vec<_>>();
+                let text = format!(
+                    "{} ({})",
+                    item.name(),
+                    if known.is_empty() {
+                        self.localized_strings
+                            .get("hud.crafting.property_unknown")
+                            .to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                );
+                Text::new(&text)
+                    .right_from(state.ids.brew_frame[i], 10.0)
+                    .font_id(self.fonts.cyri.conrod_id)
+                    .font_size(self.fonts.cyri.scale(12))
+                    .color(if picked { TEXT_COLOR } else { TEXT_GRAY_COLOR })
+                    .set(state.ids.brew_text[i], ui);
+            }
+
+            let has_vial = self
+                .inventory
+                .slots()
+                .iter()
+                .flatten()
+                .any(|item| item.is_same_item_def(&alchemy.vial));
+            let can_brew = state.brew_slots.len() >= 2 && has_vial;
+            if Button::image(self.imgs.button)
+                .w_h(105.0, 25.0)
+                .hover_image(
+                    can_brew
+                        .then_some(self.imgs.button_hover)
+                        .unwrap_or(self.imgs.button),
+                )
+                .press_image(
+                    can_brew
+                        .then_some(self.imgs.button_press)
+                        .unwrap_or(self.imgs.button),
+                )
+                .label(&self.localized_strings.get("hud.crafting.brew"))
+                .label_y(conrod_core::position::Relative::Scalar(1.0))
+                .label_color(can_brew.then_some(TEXT_COLOR).unwrap_or(TEXT_GRAY_COLOR))
+                .label_font_size(self.fonts.cyri.scale(12))
+                .label_font_id(self.fonts.cyri.conrod_id)
+                .image_color(can_brew.then_some(TEXT_COLOR).unwrap_or(TEXT_GRAY_COLOR))
+                .mid_bottom_with_margin_on(state.ids.align_ing, -31.0)
+                .set(state.ids.btn_brew, ui)
+                .was_clicked()
+                && can_brew
+            {
+                events.push(Event::Brew(state.brew_slots.clone()));
+                state.update(|s| s.brew_slots.clear());
+            }
+        }
+
         let ids = &state.ids;
         // Scrollbars
         Scrollbar::y_axis(ids.align_rec)
@@ -513,3 +707,13 @@ impl<'a> Widget for Crafting<'a> {
         events
     }
 }
+
+fn property_key(property: Property) -> &'static str {
+    match property {
+        Property::Restoring => "hud.crafting.property.restoring",
+        Property::Invigorating => "hud.crafting.property.invigorating",
+        Property::Regenerating => "hud.crafting.property.regenerating",
+        Property::Fortifying => "hud.crafting.property.fortifying",
+        Property::Toxic => "hud.crafting.property.toxic",
+    }
+}
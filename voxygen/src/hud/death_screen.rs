@@ -0,0 +1,198 @@
+use super::{combat_log::uid_to_name, img_ids::Imgs, BLACK, CRITICAL_HP_COLOR, TEXT_COLOR};
+use crate::{
+    i18n::VoxygenLocalization, ui::fonts::ConrodVoxygenFonts, window::GameInput, GlobalState,
+};
+use client::Client;
+use common::comp::chat::{KillSource, KillType};
+use conrod_core::{
+    widget::{self, Button, Text},
+    widget_ids, Colorable, Labelable, Positionable, Sizeable, Widget, WidgetCommon,
+};
+
+widget_ids! {
+    struct Ids {
+        title_bg,
+        title,
+        cause_bg,
+        cause,
+        respawn_button,
+        respawn_key_bg,
+        respawn_key,
+    }
+}
+
+/// Why the player died, with the attacker's name resolved when it happened
+/// since the attacker may be gone by the time the screen is drawn
+#[derive(Clone)]
+pub struct Death {
+    pub cause: KillSource,
+    pub attacker: Option<String>,
+}
+
+impl Death {
+    pub fn new(cause: KillSource, client: &Client) -> Self {
+        let attacker = match &cause {
+            KillSource::Player(uid, _) => Some(uid_to_name(*uid, client)),
+            KillSource::NonPlayer(name, _) => Some(name.clone()),
+            _ => None,
+        };
+        Self { cause, attacker }
+    }
+}
+
+pub struct State {
+    ids: Ids,
+}
+
+pub enum Event {
+    Respawn,
+}
+
+/// Shown while the character is dead, tells the player what killed them and
+/// lets them respawn
+#[derive(WidgetCommon)]
+pub struct DeathScreen<'a> {
+    death: &'a Death,
+    global_state: &'a GlobalState,
+    imgs: &'a Imgs,
+    fonts: &'a ConrodVoxygenFonts,
+    localized_strings: &'a VoxygenLocalization,
+
+    #[conrod(common_builder)]
+    common: widget::CommonBuilder,
+}
+
+impl<'a> DeathScreen<'a> {
+    pub fn new(
+        death: &'a Death,
+        global_state: &'a GlobalState,
+        imgs: &'a Imgs,
+        fonts: &'a ConrodVoxygenFonts,
+        localized_strings: &'a VoxygenLocalization,
+    ) -> Self {
+        Self {
+            death,
+            global_state,
+            imgs,
+            fonts,
+            localized_strings,
+            common: widget::CommonBuilder::default(),
+        }
+    }
+
+    fn cause_text(&self) -> String {
+        let key = match &self.death.cause {
+            KillSource::Player(_, kill_type) | KillSource::NonPlayer(_, kill_type) => {
+                match kill_type {
+                    KillType::Melee => "hud.death.melee",
+                    KillType::Projectile => "hud.death.ranged",
+                    KillType::Explosion => "hud.death.explosion",
+                    KillType::Energy => "hud.death.energy",
+                    KillType::Buff => "hud.death.buff",
+                }
+            },
+            KillSource::Environment(_) => "hud.death.environment",
+            KillSource::FallDamage => "hud.death.fall",
+            KillSource::Suicide => "hud.death.suicide",
+            KillSource::Other => "hud.death.other",
+        };
+        let attacker = self.death.attacker.as_deref().unwrap_or("");
+        let environment = match &self.death.cause {
+            KillSource::Environment(environment) => environment.as_str(),
+            _ => "",
+        };
+        self.localized_strings
+            .get(key)
+            .replace("{attacker}", attacker)
+            .replace("{environment}", environment)
+    }
+}
+
+impl<'a> Widget for DeathScreen<'a> {
+    type Event = Option<Event>;
+    type State = State;
+    type Style = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+        }
+    }
+
+    #[allow(clippy::unused_unit)] // TODO: Pending review in #587
+    fn style(&self) -> Self::Style { () }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs { state, ui, .. } = args;
+        let localized_strings = self.localized_strings;
+
+        Text::new(&localized_strings.get("hud.you_died"))
+            .mid_top_with_margin_on(ui.window, 250.0)
+            .font_size(self.fonts.cyri.scale(50))
+            .font_id(self.fonts.cyri.conrod_id)
+            .color(BLACK)
+            .set(state.ids.title_bg, ui);
+        Text::new(&localized_strings.get("hud.you_died"))
+            .bottom_left_with_margins_on(state.ids.title_bg, 2.0, 2.0)
+            .font_size(self.fonts.cyri.scale(50))
+            .font_id(self.fonts.cyri.conrod_id)
+            .color(CRITICAL_HP_COLOR)
+            .set(state.ids.title, ui);
+
+        let cause = self.cause_text();
+        Text::new(&cause)
+            .mid_bottom_with_margin_on(state.ids.title_bg, -50.0)
+            .font_size(self.fonts.cyri.scale(25))
+            .font_id(self.fonts.cyri.conrod_id)
+            .color(BLACK)
+            .set(state.ids.cause_bg, ui);
+        Text::new(&cause)
+            .bottom_left_with_margins_on(state.ids.cause_bg, 2.0, 2.0)
+            .font_size(self.fonts.cyri.scale(25))
+            .font_id(self.fonts.cyri.conrod_id)
+            .color(TEXT_COLOR)
+            .set(state.ids.cause, ui);
+
+        let respawn = Button::image(self.imgs.button)
+            .mid_bottom_with_margin_on(state.ids.cause_bg, -80.0)
+            .w_h(210.0, 50.0)
+            .hover_image(self.imgs.button_hover)
+            .press_image(self.imgs.button_press)
+            .label(&localized_strings.get("hud.respawn"))
+            .label_y(conrod_core::position::Relative::Scalar(3.0))
+            .label_color(TEXT_COLOR)
+            .label_font_size(self.fonts.cyri.scale(20))
+            .label_font_id(self.fonts.cyri.conrod_id)
+            .set(state.ids.respawn_button, ui)
+            .was_clicked();
+
+        if let Some(key) = self
+            .global_state
+            .settings
+            .controls
+            .get_binding(GameInput::Respawn)
+        {
+            let hint = localized_strings
+                .get("hud.press_key_to_respawn")
+                .replace("{key}", key.to_string().as_str());
+            Text::new(&hint)
+                .mid_bottom_with_margin_on(state.ids.respawn_button, -40.0)
+                .font_size(self.fonts.cyri.scale(20))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(BLACK)
+                .set(state.ids.respawn_key_bg, ui);
+            Text::new(&hint)
+                .bottom_left_with_margins_on(state.ids.respawn_key_bg, 2.0, 2.0)
+                .font_size(self.fonts.cyri.scale(20))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(TEXT_COLOR)
+                .set(state.ids.respawn_key, ui);
+        }
+
+        if respawn {
+            Some(Event::Respawn)
+        } else {
+            None
+        }
+    }
+}
@@ -10,6 +10,7 @@ use crate::{
 use client::{self, Client};
 use common::{
     comp::{group::Role, Stats},
+    msg::PlayerInfo,
     sync::{Uid, WorldSyncExt},
 };
 use conrod_core::{
@@ -18,6 +19,7 @@ use conrod_core::{
     widget::{self, Button, Image, Rectangle, Scrollbar, Text},
     widget_ids, Color, Colorable, Labelable, Positionable, Sizeable, Widget, WidgetCommon,
 };
+use hashbrown::HashMap;
 use specs::{saveload::MarkerAllocator, WorldExt};
 
 widget_ids! {
@@ -61,6 +63,8 @@ pub struct State {
 pub struct Group<'a> {
     show: &'a mut Show,
     client: &'a Client,
+    /// The player list as the HUD shows it, names are taken from here
+    player_list: &'a HashMap<Uid, PlayerInfo>,
     settings: &'a Settings,
     imgs: &'a Imgs,
     fonts: &'a ConrodVoxygenFonts,
@@ -70,6 +74,7 @@ pub struct Group<'a> {
 
     #[conrod(common_builder)]
     common: widget::CommonBuilder,
+    anchor_area: widget::Id,
 }
 
 impl<'a> Group<'a> {
@@ -77,16 +82,19 @@ impl<'a> Group<'a> {
     pub fn new(
         show: &'a mut Show,
         client: &'a Client,
+        player_list: &'a HashMap<Uid, PlayerInfo>,
         settings: &'a Settings,
         imgs: &'a Imgs,
         fonts: &'a ConrodVoxygenFonts,
         localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
         pulse: f32,
         global_state: &'a GlobalState,
+        anchor_area: widget::Id,
     ) -> Self {
         Self {
             show,
             client,
+            player_list,
             settings,
             imgs,
             fonts,
@@ -94,6 +102,7 @@ impl<'a> Group<'a> {
             pulse,
             global_state,
             common: widget::CommonBuilder::default(),
+            anchor_area,
         }
     }
 }
@@ -106,6 +115,18 @@ pub enum Event {
     AssignLeader(Uid),
 }
 
+/// Players in the group are named as in the player list, pets by their stats
+fn member_name<'b>(
+    player_list: &'b HashMap<Uid, PlayerInfo>,
+    uid: Uid,
+    stats: &'b Stats,
+) -> &'b str {
+    player_list
+        .get(&uid)
+        .and_then(|player| player.character.as_ref())
+        .map_or(&stats.name, |character| &character.name)
+}
+
 impl<'a> Widget for Group<'a> {
     type Event = Vec<Event>;
     type State = State;
@@ -148,7 +169,8 @@ impl<'a> Widget for Group<'a> {
         }
 
         // Helper
-        let uid_to_name_text = |uid, client: &Client| match client.player_list.get(&uid) {
+        let player_list = self.player_list;
+        let uid_to_name_text = |uid, client: &Client| match player_list.get(&uid) {
             Some(player_info) => player_info
                 .character
                 .as_ref()
@@ -178,7 +200,7 @@ impl<'a> Widget for Group<'a> {
         if self.show.group_menu || open_invite.is_some() {
             // Frame
             Rectangle::fill_with([220.0, 140.0], color::Color::Rgba(0.0, 0.0, 0.0, 0.8))
-                .bottom_left_with_margins_on(ui.window, 108.0, 490.0)
+                .bottom_left_with_margins_on(self.anchor_area, 108.0, 490.0)
                 .crop_kids()
                 .set(state.ids.bg, ui);
         }
@@ -186,7 +208,7 @@ impl<'a> Widget for Group<'a> {
             // Group Menu button
             Button::image(self.imgs.group_icon)
                 .w_h(49.0, 26.0)
-                .bottom_left_with_margins_on(ui.window, 10.0, 490.0)
+                .bottom_left_with_margins_on(self.anchor_area, 10.0, 490.0)
                 .set(state.ids.group_button, ui);
             // Show timeout bar
             let timeout_progress =
@@ -211,7 +233,7 @@ impl<'a> Widget for Group<'a> {
                 self.imgs.group_icon
             })
             .w_h(49.0, 26.0)
-            .bottom_left_with_margins_on(ui.window, 10.0, 490.0)
+            .bottom_left_with_margins_on(self.anchor_area, 10.0, 490.0)
             .hover_image(self.imgs.group_icon_hover)
             .press_image(self.imgs.group_icon_press)
             .set(state.ids.group_button, ui)
@@ -303,7 +325,7 @@ impl<'a> Widget for Group<'a> {
                 let stats = entity.and_then(|entity| stats.get(entity));
                 let energy = entity.and_then(|entity| energy.get(entity));
                 if let Some(stats) = stats {
-                    let char_name = stats.name.to_string();
+                    let char_name = member_name(player_list, uid, stats);
                     let health_perc = stats.health.current() as f64 / stats.health.maximum() as f64;
 
                     // change panel positions when debug info is shown
@@ -313,8 +335,11 @@ impl<'a> Widget for Group<'a> {
                         110.0
                     };
                     let back = if i == 0 {
-                        Image::new(self.imgs.member_bg)
-                            .top_left_with_margins_on(ui.window, offset, 20.0)
+                        Image::new(self.imgs.member_bg).top_left_with_margins_on(
+                            self.anchor_area,
+                            offset,
+                            20.0,
+                        )
                     } else {
                         Image::new(self.imgs.member_bg)
                             .down_from(state.ids.member_panels_bg[i - 1], 40.0)
@@ -385,14 +410,14 @@ impl<'a> Widget for Group<'a> {
                         .color(Some(UI_HIGHLIGHT_0))
                         .set(state.ids.member_panels_frame[i], ui);
                     // Panel Text
-                    Text::new(&char_name)
+                    Text::new(char_name)
                         .top_left_with_margins_on(state.ids.member_panels_frame[i], -22.0, 0.0)
                         .font_size(20)
                         .font_id(self.fonts.cyri.conrod_id)
                         .color(BLACK)
                         .w(300.0) // limit name length display
                         .set(state.ids.member_panels_txt_bg[i], ui);
-                    Text::new(&char_name)
+                    Text::new(char_name)
                         .bottom_left_with_margins_on(state.ids.member_panels_txt_bg[i], 2.0, 2.0)
                         .font_size(20)
                         .font_id(self.fonts.cyri.conrod_id)
@@ -411,7 +436,7 @@ impl<'a> Widget for Group<'a> {
                 } else {
                     // Values N.A.
                     if let Some(stats) = stats {
-                        Text::new(&stats.name.to_string())
+                        Text::new(member_name(player_list, uid, stats))
                             .top_left_with_margins_on(state.ids.member_panels_frame[i], -22.0, 0.0)
                             .font_size(20)
                             .font_id(self.fonts.cyri.conrod_id)
@@ -424,8 +449,11 @@ impl<'a> Widget for Group<'a> {
                         110.0
                     };
                     let back = if i == 0 {
-                        Image::new(self.imgs.member_bg)
-                            .top_left_with_margins_on(ui.window, offset, 20.0)
+                        Image::new(self.imgs.member_bg).top_left_with_margins_on(
+                            self.anchor_area,
+                            offset,
+                            20.0,
+                        )
                     } else {
                         Image::new(self.imgs.member_bg)
                             .down_from(state.ids.member_panels_bg[i - 1], 40.0)
@@ -18,6 +18,9 @@ pub enum Slot {
 pub enum SlotContents {
     Inventory(usize),
     Ability3,
+    /// A slot of the ability hotbar of the character, see
+    /// [`common::comp::AbilityHotbar`]
+    Ability(usize),
 }
 
 #[derive(Clone, Debug)]
@@ -61,6 +64,22 @@ impl State {
         self.slots[slot as usize] = Some(SlotContents::Inventory(inventory_index));
     }
 
+    /// Links a slot of the ability hotbar to the first empty slot, unless it
+    /// is already linked. Returns `false` if there's no room for it.
+    pub fn add_ability_link(&mut self, ability_slot: usize) -> bool {
+        let contents = Some(SlotContents::Ability(ability_slot));
+        if self.slots.contains(&contents) {
+            return true;
+        }
+        match self.slots.iter_mut().find(|s| s.is_none()) {
+            Some(slot) => {
+                *slot = contents;
+                true
+            },
+            None => false,
+        }
+    }
+
     // TODO: remove
     // Adds ability3 slot if it is missing and should be present
     // Removes if it is there and shouldn't be present
@@ -26,7 +26,9 @@ pub enum ItemKey {
     Utility(Utility),
     Consumable(String),
     Throwable(Throwable),
+    Thrown(String),
     Ingredient(String),
+    Dye(String),
     Empty,
 }
 
@@ -40,7 +42,9 @@ impl From<&ItemKind> for ItemKey {
             ItemKind::Utility { kind, .. } => ItemKey::Utility(*kind),
             ItemKind::Consumable { kind, .. } => ItemKey::Consumable(kind.clone()),
             ItemKind::Throwable { kind, .. } => ItemKey::Throwable(*kind),
+            ItemKind::Thrown { kind, .. } => ItemKey::Thrown(kind.clone()),
             ItemKind::Ingredient { kind, .. } => ItemKey::Ingredient(kind.clone()),
+            ItemKind::Dye { kind, .. } => ItemKey::Dye(kind.clone()),
         }
     }
 }
@@ -0,0 +1,154 @@
+use super::{
+    combat_log::uid_to_name,
+    KILL_COLOR, TEXT_BG, TEXT_COLOR,
+};
+use crate::{i18n::VoxygenLocalization, ui::fonts::ConrodVoxygenFonts};
+use client::Client;
+use common::combat::{CombatEvent, Involvement};
+use conrod_core::{
+    widget::{self, Text},
+    widget_ids, Colorable, Positionable, Widget, WidgetCommon,
+};
+use std::collections::VecDeque;
+
+/// Number of kills shown at once, older ones are dropped
+pub const MAX_KILLS: usize = 5;
+/// Seconds a kill stays in the feed
+const KILL_SHOWTIME: f64 = 8.0;
+/// Seconds over which a kill fades out at the end of its showtime
+const KILL_FADE: f64 = 1.0;
+
+widget_ids! {
+    pub struct Ids {
+        kill_bgs[],
+        kill_txts[],
+    }
+}
+
+pub struct State {
+    ids: Ids,
+}
+
+/// A death nearby, with names resolved when it happened since the entities
+/// involved are usually gone afterwards
+#[derive(Clone, Debug)]
+pub struct KillEvent {
+    /// Game time at which the kill happened
+    pub time: f64,
+    pub involvement: Involvement,
+    pub killer: Option<String>,
+    pub target: String,
+}
+
+impl KillEvent {
+    /// Builds the kill feed entry of a kill sent by the server
+    pub fn from_combat_event(event: &CombatEvent, client: &Client) -> Option<Self> {
+        if let CombatEvent::Kill { target, killer, .. } = event {
+            Some(Self {
+                time: client.state().get_time(),
+                involvement: event.involvement(client.uid()),
+                killer: killer.map(|uid| uid_to_name(uid, client)),
+                target: uid_to_name(*target, client),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Recent kills in the top right corner, below the minimap
+#[derive(WidgetCommon)]
+pub struct KillFeed<'a> {
+    kills: &'a VecDeque<KillEvent>,
+    time: f64,
+    localized_strings: &'a VoxygenLocalization,
+    fonts: &'a ConrodVoxygenFonts,
+    #[conrod(common_builder)]
+    common: widget::CommonBuilder,
+}
+
+impl<'a> KillFeed<'a> {
+    pub fn new(
+        kills: &'a VecDeque<KillEvent>,
+        time: f64,
+        localized_strings: &'a VoxygenLocalization,
+        fonts: &'a ConrodVoxygenFonts,
+    ) -> Self {
+        Self {
+            kills,
+            time,
+            localized_strings,
+            fonts,
+            common: widget::CommonBuilder::default(),
+        }
+    }
+
+    fn format_kill(&self, kill: &KillEvent) -> String {
+        match &kill.killer {
+            Some(killer) => self
+                .localized_strings
+                .get("hud.kill_feed.kill")
+                .replace("{killer}", killer)
+                .replace("{target}", &kill.target),
+            None => self
+                .localized_strings
+                .get("hud.kill_feed.death")
+                .replace("{target}", &kill.target),
+        }
+    }
+}
+
+impl<'a> Widget for KillFeed<'a> {
+    type Event = ();
+    type State = State;
+    type Style = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+        }
+    }
+
+    #[allow(clippy::unused_unit)] // TODO: Pending review in #587
+    fn style(&self) -> Self::Style { () }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs { state, ui, .. } = args;
+
+        let kills = self
+            .kills
+            .iter()
+            .rev()
+            .take_while(|kill| self.time - kill.time < KILL_SHOWTIME)
+            .collect::<Vec<_>>();
+        if state.ids.kill_txts.len() < kills.len() {
+            state.update(|s| {
+                let gen = &mut ui.widget_id_generator();
+                s.ids.kill_bgs.resize(kills.len(), gen);
+                s.ids.kill_txts.resize(kills.len(), gen);
+            });
+        }
+
+        // Newest kill on top
+        for (i, kill) in kills.into_iter().enumerate() {
+            let fade = ((KILL_SHOWTIME - (self.time - kill.time)) / KILL_FADE).min(1.0) as f32;
+            let color = match kill.involvement {
+                Involvement::Dealt | Involvement::Taken => KILL_COLOR,
+                Involvement::Other => TEXT_COLOR,
+            };
+            let text = self.format_kill(kill);
+            Text::new(&text)
+                .color(TEXT_BG.alpha(fade))
+                .top_right_with_margins_on(ui.window, 240.0 + i as f64 * 22.0, 10.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(15))
+                .set(state.ids.kill_bgs[i], ui);
+            Text::new(&text)
+                .color(color.alpha(fade))
+                .top_left_with_margins_on(state.ids.kill_bgs[i], -1.0, -1.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(15))
+                .set(state.ids.kill_txts[i], ui);
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
     img_ids::{Imgs, ImgsRot},
-    Show, TEXT_COLOR, UI_HIGHLIGHT_0, UI_MAIN,
+    Show, GROUP_COLOR, TEXT_COLOR, UI_HIGHLIGHT_0, UI_MAIN,
 };
 use crate::{
     i18n::VoxygenLocalization,
@@ -8,13 +8,13 @@ use crate::{
     GlobalState,
 };
 use client::{self, Client};
-use common::{comp, terrain::TerrainChunkSize, vol::RectVolSize};
+use common::{comp, difficulty, sync::UidAllocator, terrain::TerrainChunkSize, vol::RectVolSize};
 use conrod_core::{
     color, position,
     widget::{self, Button, Image, Rectangle, Text},
     widget_ids, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
 };
-use specs::WorldExt;
+use specs::{saveload::MarkerAllocator, WorldExt};
 use vek::*;
 widget_ids! {
     struct Ids {
@@ -31,6 +31,10 @@ widget_ids! {
         map_title,
         qlog_title,
         zoom_slider,
+        danger_txt,
+        overview_txt,
+        member_indicators[],
+        location_names[],
     }
 }
 
@@ -246,6 +250,115 @@ impl<'a> Widget for Map<'a> {
             .parent(ui.window)
             .set(state.ids.indicator, ui);
 
+        // Group members
+        let ecs = self.client.state().ecs();
+        let positions = ecs.read_storage::<comp::Pos>();
+        let uid_allocator = ecs.read_resource::<UidAllocator>();
+        let member_positions = self
+            .client
+            .group_members()
+            .keys()
+            .filter_map(|uid| uid_allocator.retrieve_entity_internal(uid.0))
+            .filter(|entity| *entity != self.client.entity())
+            .filter_map(|entity| positions.get(entity))
+            .map(|pos| pos.0)
+            .collect::<Vec<_>>();
+        if state.ids.member_indicators.len() < member_positions.len() {
+            state.update(|s| {
+                s.ids
+                    .member_indicators
+                    .resize(member_positions.len(), &mut ui.widget_id_generator())
+            });
+        }
+        // Size of a block on the map in UI units
+        let block_scale = 760.0 / (w_src * TerrainChunkSize::RECT_SIZE.x as f64);
+
+        // Danger of the region under the cursor, or of the current one
+        let hovered = ui.widget_input(state.ids.grid).mouse();
+        let danger_pos = hovered.map_or(Vec2::from(player_pos), |mouse| {
+            let rel = Vec2::from(mouse.rel_xy()) / block_scale;
+            Vec2::from(player_pos) + rel.map(|e| e as f32)
+        });
+        if let Some(danger) = self.client.region_danger(danger_pos) {
+            Text::new(
+                &self
+                    .localized_strings
+                    .get("hud.map.region_danger")
+                    .replace("{danger}", &danger.to_string())
+                    .replace("{max}", &difficulty::MAX_DANGER.to_string()),
+            )
+            .mid_top_with_margin_on(state.ids.qlog_align, 40.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(16))
+            .color(TEXT_COLOR)
+            .set(state.ids.danger_txt, ui);
+        }
+        if let Some(overview) = self.client.region_overview(danger_pos) {
+            Text::new(
+                &self
+                    .localized_strings
+                    .get("hud.map.region_overview")
+                    .replace("{biome}", &format!("{:?}", overview.biome))
+                    .replace("{alt}", &overview.alt.to_string()),
+            )
+            .mid_top_with_margin_on(state.ids.qlog_align, 60.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(16))
+            .color(TEXT_COLOR)
+            .set(state.ids.overview_txt, ui);
+        }
+
+        let member_sz = Vec2::new(32.0, 37.0) * 0.4;
+        for (member_pos, id) in member_positions
+            .iter()
+            .zip(state.ids.member_indicators.iter())
+        {
+            let rel = Vec2::<f32>::from(*member_pos - player_pos).map(|e| e as f64) * block_scale;
+            // Hide members that are off the visible part of the map
+            if rel.map(|e| e.abs()).reduce_partial_max() > 380.0 {
+                continue;
+            }
+            Image::new(self.rot_imgs.indicator_mmap_small.none)
+                .x_y_position_relative_to(
+                    state.ids.grid,
+                    position::Relative::Scalar(rel.x),
+                    position::Relative::Scalar(rel.y),
+                )
+                .w_h(member_sz.x, member_sz.y)
+                .color(Some(GROUP_COLOR))
+                .floating(true)
+                .parent(ui.window)
+                .set(*id, ui);
+        }
+
+        // Named locations
+        let locations = self.client.named_locations();
+        if state.ids.location_names.len() < locations.len() {
+            state.update(|s| {
+                s.ids
+                    .location_names
+                    .resize(locations.len(), &mut ui.widget_id_generator())
+            });
+        }
+        for (location, id) in locations.iter().zip(state.ids.location_names.iter()) {
+            let rel = Vec2::<f32>::from(location.pos - player_pos).map(|e| e as f64) * block_scale;
+            if rel.map(|e| e.abs()).reduce_partial_max() > 380.0 {
+                continue;
+            }
+            Text::new(&location.name)
+                .x_y_position_relative_to(
+                    state.ids.grid,
+                    position::Relative::Scalar(rel.x),
+                    position::Relative::Scalar(rel.y),
+                )
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(14))
+                .color(TEXT_COLOR)
+                .floating(true)
+                .parent(ui.window)
+                .set(*id, ui);
+        }
+
         events
     }
 }
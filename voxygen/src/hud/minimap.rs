@@ -1,16 +1,16 @@
 use super::{
     img_ids::{Imgs, ImgsRot},
-    Show, TEXT_COLOR, UI_HIGHLIGHT_0, UI_MAIN,
+    Show, GROUP_COLOR, TEXT_COLOR, UI_HIGHLIGHT_0, UI_MAIN,
 };
 use crate::ui::{fonts::ConrodVoxygenFonts, img_ids};
 use client::{self, Client};
-use common::{comp, terrain::TerrainChunkSize, vol::RectVolSize};
+use common::{comp, sync::UidAllocator, terrain::TerrainChunkSize, vol::RectVolSize};
 use conrod_core::{
     color, position,
     widget::{self, Button, Image, Rectangle, Text},
     widget_ids, Color, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
 };
-use specs::WorldExt;
+use specs::{saveload::MarkerAllocator, WorldExt};
 use vek::*;
 
 widget_ids! {
@@ -28,6 +28,7 @@ widget_ids! {
         mmap_east,
         mmap_south,
         mmap_west,
+        member_indicators[],
     }
 }
 
@@ -44,9 +45,11 @@ pub struct MiniMap<'a> {
     #[conrod(common_builder)]
     common: widget::CommonBuilder,
     ori: Vec3<f32>,
+    anchor_area: widget::Id,
 }
 
 impl<'a> MiniMap<'a> {
+    #[allow(clippy::too_many_arguments)] // TODO: Pending review in #587
     pub fn new(
         show: &'a Show,
         client: &'a Client,
@@ -55,6 +58,7 @@ impl<'a> MiniMap<'a> {
         world_map: &'a (img_ids::Rotations, Vec2<u32>),
         fonts: &'a ConrodVoxygenFonts,
         ori: Vec3<f32>,
+        anchor_area: widget::Id,
     ) -> Self {
         Self {
             show,
@@ -65,6 +69,7 @@ impl<'a> MiniMap<'a> {
             fonts,
             common: widget::CommonBuilder::default(),
             ori,
+            anchor_area,
         }
     }
 }
@@ -109,7 +114,7 @@ impl<'a> Widget for MiniMap<'a> {
         if self.show.mini_map {
             Image::new(self.imgs.mmap_frame)
                 .w_h(174.0 * SCALE, 190.0 * SCALE)
-                .top_right_with_margins_on(ui.window, 5.0, 5.0)
+                .top_right_with_margins_on(self.anchor_area, 5.0, 5.0)
                 .color(Some(UI_MAIN))
                 .set(state.ids.mmap_frame, ui);
             Image::new(self.imgs.mmap_frame_2)
@@ -222,6 +227,54 @@ impl<'a> Widget for MiniMap<'a> {
                 .parent(ui.window)
                 .set(state.ids.indicator, ui);
 
+            // Group members
+            let ecs = self.client.state().ecs();
+            let positions = ecs.read_storage::<comp::Pos>();
+            let uid_allocator = ecs.read_resource::<UidAllocator>();
+            let member_positions = self
+                .client
+                .group_members()
+                .keys()
+                .filter_map(|uid| uid_allocator.retrieve_entity_internal(uid.0))
+                .filter(|entity| *entity != self.client.entity())
+                .filter_map(|entity| positions.get(entity))
+                .map(|pos| pos.0)
+                .collect::<Vec<_>>();
+            if state.ids.member_indicators.len() < member_positions.len() {
+                state.update(|s| {
+                    s.ids
+                        .member_indicators
+                        .resize(member_positions.len(), &mut ui.widget_id_generator())
+                });
+            }
+            // Size of a block on the minimap in UI units
+            let block_scale = map_size.x * SCALE / (w_src * TerrainChunkSize::RECT_SIZE.x as f64);
+            for (member_pos, id) in member_positions
+                .iter()
+                .zip(state.ids.member_indicators.iter())
+            {
+                let rel = Vec2::<f32>::from(*member_pos - player_pos).map(|e| e as f64);
+                // The minimap rotates with the camera, like the compass directions
+                let rel = (Vec2::unit_x().rotated_z(self.ori.x as f64) * rel.x
+                    + Vec2::unit_y().rotated_z(self.ori.x as f64) * rel.y)
+                    * block_scale;
+                // Hide members that are off the edge of the minimap
+                if rel.map(|e| e.abs()).reduce_partial_max() > map_size.x * SCALE * 0.5 {
+                    continue;
+                }
+                Image::new(self.rot_imgs.indicator_mmap_small.none)
+                    .x_y_position_relative_to(
+                        state.ids.grid,
+                        position::Relative::Scalar(rel.x),
+                        position::Relative::Scalar(rel.y),
+                    )
+                    .w_h(32.0 * ind_scale, 37.0 * ind_scale)
+                    .color(Some(GROUP_COLOR))
+                    .floating(true)
+                    .parent(ui.window)
+                    .set(*id, ui);
+            }
+
             // Compass directions
             let dirs = [
                 (Vec2::new(0.0, 1.0), state.ids.mmap_north, "N", true),
@@ -256,7 +309,7 @@ impl<'a> Widget for MiniMap<'a> {
             Image::new(self.imgs.mmap_frame_closed)
                 .w_h(174.0 * SCALE, 18.0 * SCALE)
                 .color(Some(UI_MAIN))
-                .top_right_with_margins_on(ui.window, 0.0, 5.0)
+                .top_right_with_margins_on(self.anchor_area, 0.0, 5.0)
                 .set(state.ids.mmap_frame, ui);
         }
 
@@ -1,25 +1,34 @@
 mod bag;
 mod buttons;
 mod chat;
+mod combat_log;
 mod crafting;
+mod death_screen;
 mod esc_menu;
 mod group;
 mod hotbar;
 mod img_ids;
 mod item_imgs;
+mod kill_feed;
 mod map;
 mod minimap;
+mod name_mask;
+mod net_graph;
 mod overhead;
 mod overitem;
 mod popup;
 mod settings_window;
+mod sign;
 mod skillbar;
 mod slots;
 mod social;
 mod spell;
 mod util;
 
+pub use combat_log::{CombatEvent, CombatLogExport};
+pub use death_screen::Death;
 pub use hotbar::{SlotContents as HotbarSlotContents, State as HotbarState};
+pub use kill_feed::KillEvent;
 
 pub use settings_window::ScaleChange;
 use std::time::Duration;
@@ -28,16 +37,22 @@ use bag::Bag;
 use buttons::Buttons;
 use chat::Chat;
 use chrono::NaiveTime;
+use combat_log::{CombatLog, CombatLogTab};
 use crafting::Crafting;
+use death_screen::DeathScreen;
 use esc_menu::EscMenu;
 use group::Group;
 use img_ids::Imgs;
 use item_imgs::ItemImgs;
+use kill_feed::KillFeed;
 use map::Map;
 use minimap::MiniMap;
+use name_mask::{Maskable, NameMask};
+use net_graph::NetGraph;
 use popup::Popup;
 use serde::{Deserialize, Serialize};
 use settings_window::{SettingsTab, SettingsWindow};
+use sign::Sign;
 use skillbar::Skillbar;
 use social::{Social, SocialTab};
 use spell::Spell;
@@ -60,14 +75,16 @@ use common::{
     assets::Asset,
     comp,
     comp::item::{ItemDesc, Quality},
+    memory::{format_bytes, MemoryReport},
     span,
     sync::Uid,
     terrain::TerrainChunk,
     vol::RectRasterableVol,
 };
 use conrod_core::{
+    color,
     text::cursor::Index,
-    widget::{self, Button, Image, Text},
+    widget::{self, Button, Image, Rectangle, Text},
     widget_ids, Color, Colorable, Labelable, Positionable, Sizeable, Widget,
 };
 use specs::{Join, WorldExt};
@@ -93,6 +110,7 @@ const HP_COLOR: Color = Color::Rgba(0.33, 0.63, 0.0, 1.0);
 const LOW_HP_COLOR: Color = Color::Rgba(0.93, 0.59, 0.03, 1.0);
 const CRITICAL_HP_COLOR: Color = Color::Rgba(0.79, 0.19, 0.17, 1.0);
 const MANA_COLOR: Color = Color::Rgba(0.29, 0.62, 0.75, 0.9);
+const POISE_COLOR: Color = Color::Rgba(0.85, 0.68, 0.21, 0.9);
 //const TRANSPARENT: Color = Color::Rgba(0.0, 0.0, 0.0, 0.0);
 //const FOCUS_COLOR: Color = Color::Rgba(1.0, 0.56, 0.04, 1.0);
 //const RAGE_COLOR: Color = Color::Rgba(0.5, 0.04, 0.13, 1.0);
@@ -159,6 +177,9 @@ const SPEECH_BUBBLE_RANGE: f32 = NAMETAG_RANGE;
 
 widget_ids! {
     struct Ids {
+        // Area that HUD elements are anchored to
+        anchor_area,
+
         // Crosshair
         crosshair_inner,
         crosshair_outer,
@@ -201,11 +222,18 @@ widget_ids! {
         orientation,
         loaded_distance,
         time,
+        weather,
         entity_count,
         num_chunks,
         num_lights,
         num_figures,
         num_particles,
+        memory_usage,
+        net_graph,
+
+        // Shader reload errors
+        shader_error_bg,
+        shader_error,
 
         // Game Version
         version,
@@ -243,6 +271,10 @@ widget_ids! {
         esc_menu,
         small_window,
         social_window,
+        combat_log_window,
+        kill_feed,
+        death_screen,
+        sign,
         crafting_window,
         settings_window,
         group_window,
@@ -254,6 +286,8 @@ widget_ids! {
         // Auto walk indicator
         auto_walk_txt,
         auto_walk_bg,
+        dps_meter_txt,
+        dps_meter_bg,
 
         // Example Quest
         quest_bg,
@@ -279,6 +313,7 @@ pub struct DebugInfo {
     pub num_figures_visible: u32,
     pub num_particles: u32,
     pub num_particles_visible: u32,
+    pub memory: MemoryReport,
 }
 
 pub struct HudInfo {
@@ -286,10 +321,13 @@ pub struct HudInfo {
     pub is_first_person: bool,
     pub target_entity: Option<specs::Entity>,
     pub selected_entity: Option<(specs::Entity, std::time::Instant)>,
+    /// Block the player is looking at
+    pub select_pos: Option<Vec3<i32>>,
 }
 
 pub enum Event {
     ToggleTips(bool),
+    ToggleCombatLogExport(bool),
     SendMessage(String),
     AdjustMousePan(u32),
     AdjustMouseZoom(u32),
@@ -302,11 +340,20 @@ pub enum Event {
     AdjustFigureLoDRenderDistance(u32),
     AdjustMusicVolume(f32),
     AdjustSfxVolume(f32),
+    #[cfg(feature = "voice")]
+    AdjustVoiceVolume(f32),
+    #[cfg(feature = "voice")]
+    ToggleVoiceChat(bool),
+    /// Mute or unmute the voice chat of the player with this alias
+    #[cfg(feature = "voice")]
+    ToggleVoiceMute(String),
     ChangeAudioDevice(String),
     ChangeMaxFPS(u32),
     ChangeFOV(u16),
     ChangeGamma(f32),
     ChangeAmbiance(f32),
+    ChangeHeadBob(f32),
+    ChangeScreenShake(f32),
     MapZoom(f64),
     AdjustWindowSize([u16; 2]),
     ChangeFullscreenMode(FullScreenSettings),
@@ -324,14 +371,28 @@ pub enum Event {
     SctDamageBatch(bool),
     SpeechBubbleDarkMode(bool),
     SpeechBubbleIcon(bool),
+    SpeechBubbleDuration(f32),
+    MaxSpeechBubbles(u32),
+    ToggleDpsMeter(bool),
+    ToggleStreamerMode(bool),
     ToggleDebug(bool),
     UiScale(ScaleChange),
+    ChangeUiMaxAspectRatio(Option<f64>),
     CharacterSelection,
+    Respawn,
+    EditSign {
+        pos: Vec3<i32>,
+        text: String,
+    },
     UseSlot(comp::slot::Slot),
     SwapSlots(comp::slot::Slot, comp::slot::Slot),
+    DyeItem(usize, comp::slot::Slot),
+    SetAppearance(comp::slot::ArmorSlot, comp::slot::AppearanceChoice),
     DropSlot(comp::slot::Slot),
     ChangeHotbarState(Box<HotbarState>),
     Ability3(bool),
+    UseAbilitySlot(usize),
+    SetAbilitySlot(usize, Option<comp::AbilityId>),
     Logout,
     Quit,
     ChangeLanguage(Box<LanguageMetadata>),
@@ -342,6 +403,7 @@ pub enum Event {
     ChangeAutoWalkBehavior(PressBehavior),
     ChangeStopAutoWalkOnInput(bool),
     CraftRecipe(String),
+    Brew(Vec<usize>),
     InviteMember(common::sync::Uid),
     AcceptInvite,
     DeclineInvite,
@@ -402,6 +464,7 @@ pub struct Show {
     debug: bool,
     bag: bool,
     social: bool,
+    combat_log: bool,
     spell: bool,
     group: bool,
     group_menu: bool,
@@ -412,6 +475,7 @@ pub struct Show {
     ingame: bool,
     settings_tab: SettingsTab,
     social_tab: SocialTab,
+    combat_log_tab: CombatLogTab,
     want_grab: bool,
     stats: bool,
     free_look: bool,
@@ -510,6 +574,7 @@ impl Show {
             || self.esc_menu
             || self.map
             || self.social
+            || self.combat_log
             || self.crafting
             || self.spell
             || self.help
@@ -522,6 +587,7 @@ impl Show {
             self.intro = false;
             self.map = false;
             self.social = false;
+            self.combat_log = false;
             self.spell = false;
             self.crafting = false;
             self.open_windows = Windows::None;
@@ -564,6 +630,12 @@ impl Show {
         self.spell = !self.spell;
         self.social = false;
     }
+
+    fn toggle_combat_log(&mut self) {
+        if !self.esc_menu {
+            self.combat_log = !self.combat_log;
+        }
+    }
 }
 
 pub struct Hud {
@@ -576,6 +648,12 @@ pub struct Hud {
     rot_imgs: ImgsRot,
     new_messages: VecDeque<comp::ChatMsg>,
     new_notifications: VecDeque<common::msg::Notification>,
+    combat_events: VecDeque<CombatEvent>,
+    kill_events: VecDeque<KillEvent>,
+    /// Set while the character is dead
+    death: Option<Death>,
+    /// Position and text of the sign being edited
+    sign_editor: Option<(Vec3<i32>, String)>,
     speech_bubbles: HashMap<Uid, comp::SpeechBubble>,
     show: Show,
     //never_show: bool,
@@ -593,6 +671,8 @@ pub struct Hud {
     hotbar: hotbar::State,
     events: Vec<Event>,
     crosshair_opacity: f32,
+    /// Picks the pseudonyms of other players in streamer mode for this session
+    mask_salt: u64,
 }
 
 impl Hud {
@@ -650,6 +730,10 @@ impl Hud {
             ids,
             new_messages: VecDeque::new(),
             new_notifications: VecDeque::new(),
+            combat_events: VecDeque::new(),
+            kill_events: VecDeque::new(),
+            death: None,
+            sign_editor: None,
             speech_bubbles: HashMap::new(),
             //intro: false,
             //intro_2: false,
@@ -664,12 +748,14 @@ impl Hud {
                 crafting: false,
                 ui: true,
                 social: false,
+                combat_log: false,
                 spell: false,
                 group: false,
                 group_menu: false,
                 mini_map: true,
                 settings_tab: SettingsTab::Interface,
                 social_tab: SocialTab::Online,
+                combat_log_tab: CombatLogTab::All,
                 want_grab: true,
                 ingame: true,
                 stats: false,
@@ -689,6 +775,7 @@ impl Hud {
             hotbar: hotbar_state,
             events: Vec::new(),
             crosshair_opacity: 0.0,
+            mask_salt: rand::random(),
         }
     }
 
@@ -711,6 +798,20 @@ impl Hud {
     ) -> Vec<Event> {
         span!(_guard, "update_layout", "Hud::update_layout");
         let mut events = std::mem::replace(&mut self.events, Vec::new());
+        // Names are masked here, before any widget sees them
+        let name_mask = if global_state.settings.gameplay.streamer_mode {
+            NameMask::new(client, self.mask_salt)
+        } else {
+            NameMask::default()
+        };
+        let player_list = name_mask.apply(&client.player_list);
+        for msg in self.new_messages.iter_mut() {
+            msg.mask_names(&name_mask);
+        }
+        let anchor_area_size = self
+            .ui
+            .scale()
+            .anchor_area_size(global_state.settings.gameplay.ui_max_aspect_ratio);
         let (ref mut ui_widgets, ref mut tooltip_manager) = self.ui.set_widgets();
         // pulse time for pulsating elements
         self.pulse = self.pulse + dt.as_secs_f32();
@@ -718,11 +819,18 @@ impl Hud {
         let fps = global_state.clock.get_tps();
         let version = common::util::DISPLAY_VERSION_LONG.clone();
 
+        // Area that HUD elements pinned to the screen edges are placed in
+        Rectangle::fill_with(anchor_area_size.into_array(), color::TRANSPARENT)
+            .middle_of(ui_widgets.window)
+            .graphics_for(ui_widgets.window)
+            .set(self.ids.anchor_area, ui_widgets);
+
         if self.show.ingame {
             let ecs = client.state().ecs();
             let pos = ecs.read_storage::<comp::Pos>();
             let stats = ecs.read_storage::<comp::Stats>();
             let energy = ecs.read_storage::<comp::Energy>();
+            let poises = ecs.read_storage::<comp::Poise>();
             let hp_floater_lists = ecs.read_storage::<vcomp::HpFloaterList>();
             let uids = ecs.read_storage::<common::sync::Uid>();
             let interpolated = ecs.read_storage::<vcomp::Interpolated>();
@@ -804,6 +912,8 @@ impl Hud {
 
             // Max amount the sct font size increases when "flashing"
             const FLASH_MAX: u32 = 2;
+            // Amount the sct font size increases for critical hits
+            const CRIT_SIZE: u32 = 10;
 
             // Get player position.
             let player_pos = client
@@ -1082,11 +1192,28 @@ impl Hud {
                 .retain(|_uid, bubble| bubble.timeout > now);
 
             // Push speech bubbles
+            let bubble_duration =
+                Duration::from_secs_f32(global_state.settings.gameplay.speech_bubble_duration);
+            let max_bubbles = global_state.settings.gameplay.max_speech_bubbles as usize;
             for msg in self.new_messages.iter() {
-                if let Some((bubble, uid)) = msg.to_bubble() {
+                if let Some((mut bubble, uid)) = msg.to_bubble() {
+                    bubble.timeout = now + bubble_duration;
                     self.speech_bubbles.insert(uid, bubble);
                 }
             }
+            // Drop the oldest bubbles when there are too many
+            while self.speech_bubbles.len() > max_bubbles {
+                let oldest = self
+                    .speech_bubbles
+                    .iter()
+                    .min_by_key(|(_, bubble)| bubble.timeout)
+                    .map(|(uid, _)| *uid);
+                if let Some(uid) = oldest {
+                    self.speech_bubbles.remove(&uid);
+                } else {
+                    break;
+                }
+            }
 
             let mut overhead_walker = self.ids.overheads.walk();
             let mut overitem_walker = self.ids.overitems.walk();
@@ -1126,6 +1253,7 @@ impl Hud {
                 interpolated.maybe(),
                 &stats,
                 energy.maybe(),
+                poises.maybe(),
                 scales.maybe(),
                 &bodies,
                 &hp_floater_lists,
@@ -1138,7 +1266,7 @@ impl Hud {
                     entity != me && !stats.is_dead
                 })
                 .filter_map(
-                    |(entity, pos, interpolated, stats, energy, scale, body, hpfl, uid)| {
+                    |(entity, pos, interpolated, stats, energy, poise, scale, body, hpfl, uid)| {
                         // Use interpolated position if available
                         let pos = interpolated.map_or(pos.0, |i| i.pos);
                         let in_group = client.group_members().contains_key(uid);
@@ -1165,10 +1293,16 @@ impl Hud {
                                     })
                                     .powi(2);
 
+                        // Only bosses show their poise, so regular fights stay uncluttered
+                        let poise = poise.filter(|_| body.is_boss());
                         let info = display_overhead_info.then(|| overhead::Info {
-                            name: &stats.name,
+                            name: player_list
+                                .get(uid)
+                                .and_then(|player| player.character.as_ref())
+                                .map_or(&stats.name, |character| &character.name),
                             stats,
                             energy,
+                            poise,
                         });
                         let bubble = if dist_sqr < SPEECH_BUBBLE_RANGE.powi(2) {
                             speech_bubbles.get(uid)
@@ -1332,13 +1466,15 @@ impl Hud {
                                 floater.hp_change.abs() as f32 / stats.health.maximum() as f32;
                             // Increase font size based on fraction of maximum health
                             // "flashes" by having a larger size in the first 100ms
+                            // Critical hits are larger, which also shifts their color
                             let font_size = 30
                                 + ((max_hp_frac * 10.0) as u32) * 3
                                 + if floater.timer < 0.1 {
                                     FLASH_MAX * (((1.0 - floater.timer / 0.1) * 10.0) as u32)
                                 } else {
                                     0
-                                };
+                                }
+                                + if floater.crit { CRIT_SIZE } else { 0 };
                             let font_col = font_col(font_size);
                             // Timer sets the widget offset
                             let y = (floater.timer as f64
@@ -1468,6 +1604,29 @@ impl Hud {
             }
         }
 
+        // Display the error of the last failed shader reload so that graphics iteration
+        // doesn't require watching the log
+        if let Some(shader_error) = global_state.window.renderer().shader_error() {
+            Text::new(&format!("Shader compilation failed:\n{}", shader_error))
+                .mid_top_with_margin_on(ui_widgets.window, 60.0)
+                .w(1000.0)
+                .wrap_by_word()
+                .font_size(self.fonts.cyri.scale(14))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(ERROR_COLOR)
+                .set(self.ids.shader_error, ui_widgets);
+            Rectangle::fill_with(
+                [
+                    1010.0,
+                    ui_widgets.h_of(self.ids.shader_error).unwrap_or(0.0) + 10.0,
+                ],
+                Color::Rgba(0.0, 0.0, 0.0, 0.7),
+            )
+            .middle_of(self.ids.shader_error)
+            .depth(1.0)
+            .set(self.ids.shader_error_bg, ui_widgets);
+        }
+
         // Display debug window.
         if let Some(debug_info) = debug_info {
             self.velocity = match debug_info.velocity {
@@ -1476,7 +1635,7 @@ impl Hud {
             };
             // Alpha Version
             Text::new(&version)
-                .top_left_with_margins_on(ui_widgets.window, 5.0, 5.0)
+                .top_left_with_margins_on(self.ids.anchor_area, 5.0, 5.0)
                 .font_size(self.fonts.cyri.scale(14))
                 .font_id(self.fonts.cyri.conrod_id)
                 .color(TEXT_COLOR)
@@ -1568,11 +1727,26 @@ impl Hud {
             .font_size(self.fonts.cyri.scale(14))
             .set(self.ids.time, ui_widgets);
 
+            // Weather
+            let weather = client.current_weather();
+            Text::new(&format!(
+                "Weather: {:.0}% clouds, {:.0}% rain, wind {:.1} m/s{}",
+                weather.cloud * 100.0,
+                weather.rain * 100.0,
+                weather.wind.magnitude(),
+                if weather.is_storm() { " (storm)" } else { "" },
+            ))
+            .color(TEXT_COLOR)
+            .down_from(self.ids.time, 5.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(14))
+            .set(self.ids.weather, ui_widgets);
+
             // Number of entities
             let entity_count = client.state().ecs().entities().join().count();
             Text::new(&format!("Entity count: {}", entity_count))
                 .color(TEXT_COLOR)
-                .down_from(self.ids.time, 5.0)
+                .down_from(self.ids.weather, 5.0)
                 .font_id(self.fonts.cyri.conrod_id)
                 .font_size(self.fonts.cyri.scale(14))
                 .set(self.ids.entity_count, ui_widgets);
@@ -1618,6 +1792,33 @@ impl Hud {
             .font_size(self.fonts.cyri.scale(14))
             .set(self.ids.num_particles, ui_widgets);
 
+            // Approximate memory used by each subsystem
+            let memory = &debug_info.memory;
+            Text::new(&format!(
+                "Memory: {} ({})",
+                format_bytes(memory.total()),
+                memory
+                    .iter()
+                    .map(|(subsystem, bytes)| format!("{} {}", subsystem, format_bytes(bytes)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ))
+            .color(TEXT_COLOR)
+            .down_from(self.ids.num_particles, 5.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(14))
+            .set(self.ids.memory_usage, ui_widgets);
+
+            // Bandwidth used by each stream, below the shortcut hints
+            NetGraph::new(
+                client.net_stats(),
+                client.network_stats(),
+                client.net_conditions(),
+                &self.fonts,
+            )
+            .down_from(self.ids.memory_usage, 50.0)
+            .set(self.ids.net_graph, ui_widgets);
+
             // Help Window
             if let Some(help_key) = global_state.settings.controls.get_binding(GameInput::Help) {
                 Text::new(
@@ -1627,7 +1828,7 @@ impl Hud {
                         .replace("{key}", help_key.to_string().as_str()),
                 )
                 .color(TEXT_COLOR)
-                .down_from(self.ids.num_particles, 5.0)
+                .down_from(self.ids.memory_usage, 5.0)
                 .font_id(self.fonts.cyri.conrod_id)
                 .font_size(self.fonts.cyri.scale(14))
                 .set(self.ids.help_info, ui_widgets);
@@ -1660,7 +1861,7 @@ impl Hud {
                         .replace("{key}", help_key.to_string().as_str()),
                 )
                 .color(TEXT_COLOR)
-                .bottom_left_with_margins_on(ui_widgets.window, 210.0, 10.0)
+                .bottom_left_with_margins_on(self.ids.anchor_area, 210.0, 10.0)
                 .font_id(self.fonts.cyri.conrod_id)
                 .font_size(self.fonts.cyri.scale(12))
                 .set(self.ids.help_info, ui_widgets);
@@ -1678,7 +1879,7 @@ impl Hud {
                         .replace("{key}", toggle_debug_key.to_string().as_str()),
                 )
                 .color(TEXT_COLOR)
-                .top_left_with_margins_on(ui_widgets.window, 5.0, 5.0)
+                .top_left_with_margins_on(self.ids.anchor_area, 5.0, 5.0)
                 .font_id(self.fonts.cyri.conrod_id)
                 .font_size(self.fonts.cyri.scale(12))
                 .set(self.ids.debug_info, ui_widgets);
@@ -1737,6 +1938,7 @@ impl Hud {
                 tooltip_manager,
                 &self.voxygen_i18n,
                 &player_stats,
+                self.ids.anchor_area,
             )
             .set(self.ids.buttons, ui_widgets)
             {
@@ -1769,6 +1971,7 @@ impl Hud {
             &self.world_map,
             &self.fonts,
             camera.get_orientation(),
+            self.ids.anchor_area,
         )
         .set(self.ids.minimap, ui_widgets)
         {
@@ -1791,10 +1994,14 @@ impl Hud {
                     &self.voxygen_i18n,
                     &player_stats,
                     &self.show,
+                    self.ids.anchor_area,
                 )
                 .set(self.ids.bag, ui_widgets)
                 {
                     Some(bag::Event::Stats) => self.show.stats = !self.show.stats,
+                    Some(bag::Event::SetAppearance(slot, choice)) => {
+                        events.push(Event::SetAppearance(slot, choice))
+                    },
                     Some(bag::Event::Close) => {
                         self.show.stats = false;
                         self.show.bag(false);
@@ -1820,6 +2027,8 @@ impl Hud {
         let character_states = ecs.read_storage::<comp::CharacterState>();
         let controllers = ecs.read_storage::<comp::Controller>();
         let inventories = ecs.read_storage::<comp::Inventory>();
+        let ability_hotbars = ecs.read_storage::<comp::AbilityHotbar>();
+        let default_ability_hotbar = comp::AbilityHotbar::default();
         if let (
             Some(stats),
             Some(loadout),
@@ -1849,6 +2058,9 @@ impl Hud {
                 &controller,
                 &inventory,
                 &self.hotbar,
+                ability_hotbars
+                    .get(entity)
+                    .unwrap_or(&default_ability_hotbar),
                 tooltip_manager,
                 &mut self.slot_manager,
                 &self.voxygen_i18n,
@@ -1857,6 +2069,49 @@ impl Hud {
             .set(self.ids.skillbar, ui_widgets);
         }
 
+        // Death screen
+        if let Some(death) = &self.death {
+            if let Some(death_screen::Event::Respawn) = DeathScreen::new(
+                &name_mask.apply(death),
+                global_state,
+                &self.imgs,
+                &self.fonts,
+                &self.voxygen_i18n,
+            )
+            .set(self.ids.death_screen, ui_widgets)
+            {
+                events.push(Event::Respawn);
+            }
+        }
+
+        // Sign being edited, or the one looked at
+        if let Some((_, text)) = &self.sign_editor {
+            let sign_events = Sign::new(text, true, &self.imgs, &self.fonts, &self.voxygen_i18n)
+                .set(self.ids.sign, ui_widgets);
+            for event in sign_events {
+                match event {
+                    sign::Event::Change(text) => {
+                        if let Some(editor) = &mut self.sign_editor {
+                            editor.1 = text;
+                        }
+                    },
+                    sign::Event::Save => {
+                        if let Some((pos, text)) = self.sign_editor.take() {
+                            events.push(Event::EditSign { pos, text });
+                        }
+                        self.show.want_grab = true;
+                    },
+                    sign::Event::Close => {
+                        self.sign_editor = None;
+                        self.show.want_grab = true;
+                    },
+                }
+            }
+        } else if let Some(text) = info.select_pos.and_then(|pos| client.sign_text(pos)) {
+            Sign::new(&text, false, &self.imgs, &self.fonts, &self.voxygen_i18n)
+                .set(self.ids.sign, ui_widgets);
+        }
+
         // Crafting
         if self.show.crafting {
             if let Some(inventory) = inventories.get(entity) {
@@ -1870,6 +2125,7 @@ impl Hud {
                     tooltip_manager,
                     &self.item_imgs,
                     &inventory,
+                    self.ids.anchor_area,
                 )
                 .set(self.ids.crafting_window, ui_widgets)
                 {
@@ -1877,6 +2133,9 @@ impl Hud {
                         crafting::Event::CraftRecipe(r) => {
                             events.push(Event::CraftRecipe(r));
                         },
+                        crafting::Event::Brew(slots) => {
+                            events.push(Event::Brew(slots));
+                        },
                         crafting::Event::Close => {
                             self.show.stats = false;
                             self.show.crafting(false);
@@ -1901,10 +2160,12 @@ impl Hud {
         match Chat::new(
             &mut self.new_messages,
             &client,
+            &player_list,
             global_state,
             &self.imgs,
             &self.fonts,
             &self.voxygen_i18n,
+            self.ids.anchor_area,
         )
         .and_then(self.force_chat_input.take(), |c, input| c.input(input))
         .and_then(self.tab_complete.take(), |c, input| {
@@ -1953,6 +2214,18 @@ impl Hud {
                     settings_window::Event::SpeechBubbleIcon(sbi) => {
                         events.push(Event::SpeechBubbleIcon(sbi));
                     },
+                    settings_window::Event::SpeechBubbleDuration(duration) => {
+                        events.push(Event::SpeechBubbleDuration(duration));
+                    },
+                    settings_window::Event::MaxSpeechBubbles(max) => {
+                        events.push(Event::MaxSpeechBubbles(max));
+                    },
+                    settings_window::Event::ToggleDpsMeter(dps_meter) => {
+                        events.push(Event::ToggleDpsMeter(dps_meter));
+                    },
+                    settings_window::Event::ToggleStreamerMode(streamer_mode) => {
+                        events.push(Event::ToggleStreamerMode(streamer_mode));
+                    },
                     settings_window::Event::Sct(sct) => {
                         events.push(Event::Sct(sct));
                     },
@@ -2022,6 +2295,14 @@ impl Hud {
                     settings_window::Event::MaximumFPS(max_fps) => {
                         events.push(Event::ChangeMaxFPS(max_fps));
                     },
+                    #[cfg(feature = "voice")]
+                    settings_window::Event::AdjustVoiceVolume(voice_volume) => {
+                        events.push(Event::AdjustVoiceVolume(voice_volume));
+                    },
+                    #[cfg(feature = "voice")]
+                    settings_window::Event::ToggleVoiceChat(voice_chat) => {
+                        events.push(Event::ToggleVoiceChat(voice_chat));
+                    },
                     settings_window::Event::ChangeAudioDevice(name) => {
                         events.push(Event::ChangeAudioDevice(name));
                     },
@@ -2040,6 +2321,9 @@ impl Hud {
                     settings_window::Event::UiScale(scale_change) => {
                         events.push(Event::UiScale(scale_change));
                     },
+                    settings_window::Event::UiMaxAspectRatio(max_aspect_ratio) => {
+                        events.push(Event::ChangeUiMaxAspectRatio(max_aspect_ratio));
+                    },
                     settings_window::Event::AdjustFOV(new_fov) => {
                         events.push(Event::ChangeFOV(new_fov));
                     },
@@ -2049,6 +2333,12 @@ impl Hud {
                     settings_window::Event::AdjustAmbiance(new_ambiance) => {
                         events.push(Event::ChangeAmbiance(new_ambiance));
                     },
+                    settings_window::Event::AdjustHeadBob(head_bob_intensity) => {
+                        events.push(Event::ChangeHeadBob(head_bob_intensity));
+                    },
+                    settings_window::Event::AdjustScreenShake(screen_shake_intensity) => {
+                        events.push(Event::ChangeScreenShake(screen_shake_intensity));
+                    },
                     settings_window::Event::ChangeRenderMode(new_render_mode) => {
                         events.push(Event::ChangeRenderMode(new_render_mode));
                     },
@@ -2092,12 +2382,16 @@ impl Hud {
                 for event in Social::new(
                     &self.show,
                     client,
+                    &player_list,
+                    #[cfg(feature = "voice")]
+                    &global_state.settings.audio.muted_voices,
                     &self.imgs,
                     &self.fonts,
                     &self.voxygen_i18n,
                     info.selected_entity,
                     &self.rot_imgs,
                     tooltip_manager,
+                    self.ids.anchor_area,
                 )
                 .set(self.ids.social_window, ui_widgets)
                 {
@@ -2115,20 +2409,59 @@ impl Hud {
                             self.show.open_social_tab(social_tab)
                         },
                         social::Event::Invite(uid) => events.push(Event::InviteMember(uid)),
+                        #[cfg(feature = "voice")]
+                        social::Event::ToggleVoiceMute(alias) => {
+                            events.push(Event::ToggleVoiceMute(alias))
+                        },
                     }
                 }
             }
         }
+        // Kill feed
+        KillFeed::new(
+            &name_mask.apply(&self.kill_events),
+            client.state().get_time(),
+            &self.voxygen_i18n,
+            &self.fonts,
+        )
+        .set(self.ids.kill_feed, ui_widgets);
+
+        // Combat Log Window
+        if self.show.combat_log {
+            for event in CombatLog::new(
+                &self.show,
+                &name_mask.apply(&self.combat_events),
+                global_state.settings.gameplay.export_combat_log,
+                &self.imgs,
+                &self.rot_imgs,
+                &self.fonts,
+                &self.voxygen_i18n,
+                tooltip_manager,
+                self.ids.anchor_area,
+            )
+            .set(self.ids.combat_log_window, ui_widgets)
+            {
+                match event {
+                    combat_log::Event::Close => self.show.combat_log = false,
+                    combat_log::Event::ChangeTab(tab) => self.show.combat_log_tab = tab,
+                    combat_log::Event::ToggleExport(export) => {
+                        events.push(Event::ToggleCombatLogExport(export))
+                    },
+                }
+            }
+        }
         // Group Window
         for event in Group::new(
             &mut self.show,
             client,
+            &player_list,
             &global_state.settings,
             &self.imgs,
             &self.fonts,
             &self.voxygen_i18n,
             self.pulse,
             &global_state,
+            self.ids.anchor_area,
         )
         .set(self.ids.group_window, ui_widgets)
         {
@@ -2149,6 +2482,7 @@ impl Hud {
                 &self.imgs,
                 &self.fonts,
                 &self.voxygen_i18n,
+                self.ids.anchor_area,
             )
             .set(self.ids.spell, ui_widgets)
             {
@@ -2157,6 +2491,14 @@ impl Hud {
                     self.show.want_grab = true;
                     self.force_ungrab = false;
                 },
+                Some(spell::Event::SetAbilitySlot(slot, ability)) => {
+                    events.push(Event::SetAbilitySlot(slot, ability));
+                },
+                Some(spell::Event::AddToHotbar(slot)) => {
+                    if self.hotbar.add_ability_link(slot) {
+                        events.push(Event::ChangeHotbarState(Box::new(self.hotbar.to_owned())));
+                    }
+                },
                 None => {},
             }
         }
@@ -2271,6 +2613,30 @@ impl Hud {
                 .set(self.ids.auto_walk_txt, ui_widgets);
         }
 
+        // DPS meter
+        if global_state.settings.gameplay.dps_meter {
+            if let Some(dps) =
+                combat_log::damage_per_second(&self.combat_events, client.state().get_time())
+            {
+                let dps_text = self
+                    .voxygen_i18n
+                    .get("hud.dps_meter")
+                    .replace("{dps}", &format!("{:.1}", dps / 10.0));
+                Text::new(&dps_text)
+                    .color(TEXT_BG)
+                    .mid_top_with_margin_on(ui_widgets.window, 100.0)
+                    .font_id(self.fonts.cyri.conrod_id)
+                    .font_size(self.fonts.cyri.scale(20))
+                    .set(self.ids.dps_meter_bg, ui_widgets);
+                Text::new(&dps_text)
+                    .color(TEXT_COLOR)
+                    .top_left_with_margins_on(self.ids.dps_meter_bg, -1.0, -1.0)
+                    .font_id(self.fonts.cyri.conrod_id)
+                    .font_size(self.fonts.cyri.scale(20))
+                    .set(self.ids.dps_meter_txt, ui_widgets);
+            }
+        }
+
         // Maintain slot manager
         for event in self.slot_manager.maintain(ui_widgets) {
             use comp::slot::Slot;
@@ -2282,8 +2648,26 @@ impl Hud {
             };
             match event {
                 slot::Event::Dragged(a, b) => {
-                    // Swap between slots
-                    if let (Some(a), Some(b)) = (to_slot(a), to_slot(b)) {
+                    let inventory = inventories.get(entity);
+                    let dye = match a {
+                        Inventory(i) => inventory
+                            .and_then(|inv| inv.get(i.0))
+                            .filter(|item| matches!(item.kind(), comp::item::ItemKind::Dye { .. }))
+                            .map(|_| i.0),
+                        _ => None,
+                    };
+                    let dyeable = match b {
+                        Inventory(i) => inventory
+                            .and_then(|inv| inv.get(i.0))
+                            .map_or(false, comp::Item::is_dyeable),
+                        Equip(_) => true,
+                        Hotbar(_) => false,
+                    };
+                    if let (Some(dye), Some(target), true) = (dye, to_slot(b), dyeable) {
+                        // Dragging a dye onto armor dyes it
+                        events.push(Event::DyeItem(dye, target));
+                    } else if let (Some(a), Some(b)) = (to_slot(a), to_slot(b)) {
+                        // Swap between slots
                         events.push(Event::SwapSlots(a, b));
                     } else if let (Inventory(i), Hotbar(h)) = (a, b) {
                         self.hotbar.add_inventory_link(h, i.0);
@@ -2314,6 +2698,9 @@ impl Hud {
                                 },
                                 hotbar::SlotContents::Ability3 => {}, /* Event::Ability3(true),
                                                                        * sticks */
+                                hotbar::SlotContents::Ability(i) => {
+                                    events.push(Event::UseAbilitySlot(i));
+                                },
                             }
                         });
                     }
@@ -2327,10 +2714,48 @@ impl Hud {
 
     pub fn new_message(&mut self, msg: comp::ChatMsg) { self.new_messages.push_back(msg); }
 
+    /// Replace the contents of the hotbar, e.g. with the ones the server
+    /// stored for the character
+    pub fn set_hotbar_slots(&mut self, slots: [Option<HotbarSlotContents>; 10]) {
+        self.hotbar.slots = slots;
+    }
+
     pub fn new_notification(&mut self, msg: common::msg::Notification) {
         self.new_notifications.push_back(msg);
     }
 
+    pub fn new_combat_event(&mut self, event: CombatEvent) {
+        if self.combat_events.len() >= combat_log::MAX_COMBAT_EVENTS {
+            self.combat_events.pop_front();
+        }
+        self.combat_events.push_back(event);
+    }
+
+    pub fn new_kill_event(&mut self, event: KillEvent) {
+        if self.kill_events.len() >= kill_feed::MAX_KILLS {
+            self.kill_events.pop_front();
+        }
+        self.kill_events.push_back(event);
+    }
+
+    /// Show the death screen, releasing the cursor so the respawn button can
+    /// be clicked
+    pub fn death(&mut self, death: Death) {
+        self.death = Some(death);
+        self.show.want_grab = false;
+    }
+
+    pub fn respawned(&mut self) {
+        self.death = None;
+        self.show.want_grab = true;
+    }
+
+    /// Open the editor for the text of a sign
+    pub fn edit_sign(&mut self, pos: Vec3<i32>, text: String) {
+        self.sign_editor = Some((pos, text));
+        self.show.want_grab = false;
+    }
+
     pub fn scale_change(&mut self, scale_change: ScaleChange) -> ScaleMode {
         let scale_mode = match scale_change {
             ScaleChange::Adjust(scale) => ScaleMode::Absolute(scale),
@@ -2378,6 +2803,11 @@ impl Hud {
                         }
                     },
                     hotbar::SlotContents::Ability3 => events.push(Event::Ability3(state)),
+                    hotbar::SlotContents::Ability(i) => {
+                        if just_pressed {
+                            events.push(Event::UseAbilitySlot(i));
+                        }
+                    },
                 });
             }
         }
@@ -2440,6 +2870,10 @@ impl Hud {
                     self.show.toggle_social();
                     true
                 },
+                GameInput::CombatLog if state => {
+                    self.show.toggle_combat_log();
+                    true
+                },
                 GameInput::Crafting if state => {
                     self.show.toggle_crafting();
                     true
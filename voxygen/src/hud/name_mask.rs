@@ -0,0 +1,245 @@
+//! Streamer mode
+//!
+//! While streaming or recording, the names of other players are replaced by
+//! pseudonyms. The HUD builds a [`NameMask`] from the player list once per
+//! frame and applies it to the data it hands to widgets: the player list,
+//! chat messages as they arrive, kill and combat events and the cause of
+//! death. Widgets only ever see masked names and don't have to know about
+//! streamer mode. Pseudonyms stay the same for a player during a session but
+//! change between sessions, so they can't be used to recognize players
+//! across streams.
+
+use super::{combat_log::CombatEvent, death_screen::Death, kill_feed::KillEvent};
+use client::Client;
+use common::{
+    comp::{chat::KillSource, ChatMsg},
+    msg::PlayerInfo,
+    sync::Uid,
+};
+use hashbrown::HashMap;
+use std::{borrow::Cow, collections::VecDeque};
+
+const ADJECTIVES: [&str; 16] = [
+    "Amber", "Brave", "Calm", "Swift", "Gentle", "Golden", "Hidden", "Jolly", "Lucky", "Mossy",
+    "Quiet", "Rusty", "Silver", "Sleepy", "Sunny", "Wild",
+];
+const ANIMALS: [&str; 16] = [
+    "Badger", "Bat", "Crab", "Deer", "Duck", "Fox", "Frog", "Hare", "Heron", "Lynx", "Otter",
+    "Owl", "Pig", "Seal", "Toad", "Wolf",
+];
+
+/// Replaces the names of other players by pseudonyms
+#[derive(Default)]
+pub struct NameMask {
+    /// Names of other players with their pseudonyms, longest name first so
+    /// that names containing others are replaced as a whole
+    names: Vec<(String, String)>,
+}
+
+impl NameMask {
+    /// Masks the account and character names of everyone in the player list
+    /// except ourselves. `salt` picks the pseudonyms of this session.
+    pub fn new(client: &Client, salt: u64) -> Self {
+        let own_uid = client.uid();
+        let mut names = Vec::new();
+        for (uid, info) in client
+            .player_list
+            .iter()
+            .filter(|(uid, _)| Some(**uid) != own_uid)
+        {
+            let pseudonym = pseudonym(*uid, salt);
+            names.push((info.player_alias.clone(), pseudonym.clone()));
+            if let Some(character) = &info.character {
+                names.push((character.name.clone(), pseudonym));
+            }
+        }
+        names.retain(|(name, _)| !name.is_empty());
+        names.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()));
+        Self { names }
+    }
+
+    /// Replaces every name of another player in a text that stands on its
+    /// own, not as part of a longer word
+    pub fn mask<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if self.names.is_empty() {
+            return Cow::Borrowed(text);
+        }
+        let mut masked = String::with_capacity(text.len());
+        let mut changed = false;
+        let mut rest = text;
+        let mut prev = None;
+        while let Some(c) = rest.chars().next() {
+            let replacement = self
+                .names
+                .iter()
+                .filter(|_| !prev.map_or(false, is_word_char))
+                .find(|(name, _)| {
+                    rest.starts_with(name.as_str())
+                        && !rest[name.len()..]
+                            .chars()
+                            .next()
+                            .map_or(false, is_word_char)
+                });
+            match replacement {
+                Some((name, pseudonym)) => {
+                    masked.push_str(pseudonym);
+                    prev = name.chars().last();
+                    rest = &rest[name.len()..];
+                    changed = true;
+                },
+                None => {
+                    masked.push(c);
+                    prev = Some(c);
+                    rest = &rest[c.len_utf8()..];
+                },
+            }
+        }
+        if changed {
+            Cow::Owned(masked)
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// Masked copy of some HUD data, or the data itself if there is nothing
+    /// to mask
+    pub fn apply<'a, T: Maskable>(&self, data: &'a T) -> Cow<'a, T> {
+        if self.names.is_empty() {
+            Cow::Borrowed(data)
+        } else {
+            let mut data = data.clone();
+            data.mask_names(self);
+            Cow::Owned(data)
+        }
+    }
+
+    fn mask_string(&self, text: &mut String) { *text = self.mask(text).into_owned(); }
+}
+
+/// HUD data that can contain the names of other players
+pub trait Maskable: Clone {
+    fn mask_names(&mut self, mask: &NameMask);
+}
+
+impl<T: Maskable> Maskable for VecDeque<T> {
+    fn mask_names(&mut self, mask: &NameMask) {
+        self.iter_mut().for_each(|item| item.mask_names(mask));
+    }
+}
+
+impl Maskable for HashMap<Uid, PlayerInfo> {
+    fn mask_names(&mut self, mask: &NameMask) {
+        for info in self.values_mut() {
+            mask.mask_string(&mut info.player_alias);
+            if let Some(character) = &mut info.character {
+                mask.mask_string(&mut character.name);
+            }
+        }
+    }
+}
+
+impl Maskable for ChatMsg {
+    fn mask_names(&mut self, mask: &NameMask) { mask.mask_string(&mut self.message); }
+}
+
+impl Maskable for KillEvent {
+    fn mask_names(&mut self, mask: &NameMask) {
+        if let Some(killer) = &mut self.killer {
+            mask.mask_string(killer);
+        }
+        mask.mask_string(&mut self.target);
+    }
+}
+
+impl Maskable for CombatEvent {
+    fn mask_names(&mut self, mask: &NameMask) {
+        if let Some(attacker) = &mut self.attacker {
+            mask.mask_string(attacker);
+        }
+        mask.mask_string(&mut self.target);
+    }
+}
+
+impl Maskable for Death {
+    fn mask_names(&mut self, mask: &NameMask) {
+        // Creatures can't be named like players, only the names of players
+        // that killed us are masked
+        if let (KillSource::Player(..), Some(attacker)) = (&self.cause, &mut self.attacker) {
+            mask.mask_string(attacker);
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool { c.is_alphanumeric() || c == '_' }
+
+fn pseudonym(uid: Uid, salt: u64) -> String {
+    // Mixes the bits so that neighbouring uids don't get similar pseudonyms
+    let mut hash = (uid.0 ^ salt).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    hash ^= hash >> 29;
+    format!(
+        "{} {} {}",
+        ADJECTIVES[hash as usize % ADJECTIVES.len()],
+        ANIMALS[(hash >> 8) as usize % ANIMALS.len()],
+        (hash >> 16) % 100
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::combat::Involvement;
+
+    #[test]
+    fn only_whole_names_are_masked() {
+        let mask = NameMask {
+            names: vec![
+                ("Anna Maria".to_owned(), "Calm Fox 1".to_owned()),
+                ("Anna".to_owned(), "Wild Owl 2".to_owned()),
+            ],
+        };
+        assert_eq!(
+            mask.mask("[Anna] hi Anna Maria"),
+            "[Wild Owl 2] hi Calm Fox 1"
+        );
+        assert_eq!(mask.mask("Annabelle and Hanna"), "Annabelle and Hanna");
+        assert!(matches!(mask.mask("no names"), Cow::Borrowed(_)));
+        assert_eq!(NameMask::default().mask("Anna"), "Anna");
+    }
+
+    #[test]
+    fn pseudonyms_depend_on_the_session() {
+        assert_eq!(pseudonym(Uid(7), 1), pseudonym(Uid(7), 1));
+        assert_ne!(pseudonym(Uid(7), 1), pseudonym(Uid(8), 1));
+
+        // With another salt, players get other pseudonyms. A single pseudonym
+        // could match by chance, all of them can't.
+        let pseudonyms = |salt| {
+            (0..8)
+                .map(|uid| pseudonym(Uid(uid), salt))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pseudonyms(42), pseudonyms(42));
+        assert_ne!(pseudonyms(42), pseudonyms(43));
+        assert_ne!(pseudonyms(42), pseudonyms(42 << 32));
+    }
+
+    #[test]
+    fn hud_data_is_masked() {
+        let mask = NameMask {
+            names: vec![("Anna".to_owned(), "Wild Owl 2".to_owned())],
+        };
+        let event = KillEvent {
+            time: 0.0,
+            involvement: Involvement::Other,
+            killer: Some("Anna".to_owned()),
+            target: "Bob".to_owned(),
+        };
+        let masked = mask.apply(&event);
+        assert_eq!(masked.killer.as_deref(), Some("Wild Owl 2"));
+        assert_eq!(masked.target, "Bob");
+        assert!(matches!(
+            NameMask::default().apply(&event),
+            Cow::Borrowed(_)
+        ));
+    }
+}
@@ -0,0 +1,224 @@
+use super::{ERROR_COLOR, TEXT_COLOR};
+use crate::ui::fonts::ConrodVoxygenFonts;
+use client::{
+    net_sim::NetConditions,
+    net_stats::{MsgCategory, NetStats, NetworkStats, StreamKind, HISTORY_SECS},
+};
+use conrod_core::{
+    color,
+    widget::{self, Rectangle, Text},
+    widget_ids, Color, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
+};
+
+const GRAPH_WIDTH: f64 = 240.0;
+const GRAPH_HEIGHT: f64 = 80.0;
+/// Bytes per second at the top of the graph, unless more were used
+const MIN_SCALE: usize = 1024;
+
+widget_ids! {
+    struct Ids {
+        bg,
+        bars[],
+        legend[],
+        categories[],
+        summary,
+        conditions,
+    }
+}
+
+#[derive(WidgetCommon)]
+pub struct NetGraph<'a> {
+    stats: &'a NetStats,
+    summary: NetworkStats,
+    conditions: NetConditions,
+    fonts: &'a ConrodVoxygenFonts,
+    #[conrod(common_builder)]
+    common: widget::CommonBuilder,
+}
+
+/// Bandwidth used by each stream of the connection to the server during the
+/// last seconds, the ping and packet loss, and the network conditions that are
+/// simulated
+impl<'a> NetGraph<'a> {
+    pub fn new(
+        stats: &'a NetStats,
+        summary: NetworkStats,
+        conditions: NetConditions,
+        fonts: &'a ConrodVoxygenFonts,
+    ) -> Self {
+        Self {
+            stats,
+            summary,
+            conditions,
+            fonts,
+            common: widget::CommonBuilder::default(),
+        }
+    }
+}
+
+pub struct State {
+    ids: Ids,
+}
+
+fn stream_color(kind: StreamKind) -> Color {
+    match kind {
+        StreamKind::Register => color::GREY,
+        StreamKind::General => color::LIGHT_BLUE,
+        StreamKind::Ping => color::YELLOW,
+        StreamKind::CharacterScreen => color::LIGHT_PURPLE,
+        StreamKind::InGame => color::LIGHT_GREEN,
+        StreamKind::Terrain => color::LIGHT_ORANGE,
+        StreamKind::Voice => color::LIGHT_RED,
+    }
+}
+
+fn format_rate(bytes: usize) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB/s", bytes as f64 / (1024.0 * 1024.0))
+    } else if bytes >= 1024 {
+        format!("{:.1} KB/s", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B/s", bytes)
+    }
+}
+
+impl<'a> Widget for NetGraph<'a> {
+    type Event = ();
+    type State = State;
+    type Style = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+        }
+    }
+
+    fn style(&self) -> Self::Style {}
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs { id, state, ui, .. } = args;
+
+        let num_bars = HISTORY_SECS * StreamKind::COUNT;
+        if state.ids.bars.len() < num_bars {
+            state.update(|s| s.ids.bars.resize(num_bars, &mut ui.widget_id_generator()));
+        }
+        if state.ids.legend.len() < StreamKind::COUNT {
+            state.update(|s| {
+                s.ids
+                    .legend
+                    .resize(StreamKind::COUNT, &mut ui.widget_id_generator())
+            });
+        }
+        if state.ids.categories.len() < MsgCategory::COUNT {
+            state.update(|s| {
+                s.ids
+                    .categories
+                    .resize(MsgCategory::COUNT, &mut ui.widget_id_generator())
+            });
+        }
+
+        Rectangle::fill_with([GRAPH_WIDTH, GRAPH_HEIGHT], Color::Rgba(0.0, 0.0, 0.0, 0.5))
+            .top_left_of(id)
+            .set(state.ids.bg, ui);
+
+        // Traffic in both directions is stacked by stream, the newest second on
+        // the right
+        let scale = self
+            .stats
+            .history()
+            .map(|sample| sample.total_sent() + sample.total_received())
+            .max()
+            .unwrap_or(0)
+            .max(MIN_SCALE) as f64;
+        let bar_width = GRAPH_WIDTH / HISTORY_SECS as f64;
+        let first_bar = HISTORY_SECS - self.stats.history().len();
+        for (i, sample) in self.stats.history().enumerate() {
+            let mut bottom = 0.0;
+            for kind in StreamKind::ALL.iter() {
+                let bytes = sample.sent(*kind) + sample.received(*kind);
+                if bytes == 0 {
+                    continue;
+                }
+                let height = bytes as f64 / scale * GRAPH_HEIGHT;
+                Rectangle::fill_with([bar_width, height], stream_color(*kind))
+                    .bottom_left_with_margins_on(
+                        state.ids.bg,
+                        bottom,
+                        (first_bar + i) as f64 * bar_width,
+                    )
+                    .set(state.ids.bars[i * StreamKind::COUNT + kind.index()], ui);
+                bottom += height;
+            }
+        }
+
+        // Rates during the last second
+        let last = self.stats.last();
+        for kind in StreamKind::ALL.iter() {
+            let text = Text::new(&format!(
+                "{}: {} down, {} up",
+                kind.name(),
+                format_rate(last.received(*kind)),
+                format_rate(last.sent(*kind)),
+            ))
+            .color(stream_color(*kind))
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(12));
+            match kind.index() {
+                0 => text.down_from(state.ids.bg, 5.0),
+                i => text.down_from(state.ids.legend[i - 1], 3.0),
+            }
+            .set(state.ids.legend[kind.index()], ui);
+        }
+
+        // The same traffic by what the messages are about
+        let last = self.summary.last;
+        for category in MsgCategory::ALL.iter() {
+            let text = Text::new(&format!(
+                "{}: {} down, {} up",
+                category.name(),
+                format_rate(last.category_received(*category)),
+                format_rate(last.category_sent(*category)),
+            ))
+            .color(TEXT_COLOR)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(12));
+            match category.index() {
+                0 => text.down_from(state.ids.legend[StreamKind::COUNT - 1], 8.0),
+                i => text.down_from(state.ids.categories[i - 1], 3.0),
+            }
+            .set(state.ids.categories[category.index()], ui);
+        }
+
+        Text::new(&format!(
+            "Ping: {:.0} ms, {:.0}% packet loss",
+            self.summary.ping_ms,
+            self.summary.packet_loss * 100.0,
+        ))
+        .color(TEXT_COLOR)
+        .down_from(state.ids.categories[MsgCategory::COUNT - 1], 8.0)
+        .font_id(self.fonts.cyri.conrod_id)
+        .font_size(self.fonts.cyri.scale(12))
+        .set(state.ids.summary, ui);
+
+        let conditions = if self.conditions.is_perfect() {
+            "No simulated network conditions".to_string()
+        } else {
+            format!(
+                "Simulated: {} ms latency, {} ms jitter, {:.0}% loss",
+                self.conditions.latency.as_millis(),
+                self.conditions.jitter.as_millis(),
+                self.conditions.loss * 100.0,
+            )
+        };
+        Text::new(&conditions)
+            .color(if self.conditions.is_perfect() {
+                TEXT_COLOR
+            } else {
+                ERROR_COLOR
+            })
+            .down_from(state.ids.summary, 3.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(12))
+            .set(state.ids.conditions, ui);
+    }
+}
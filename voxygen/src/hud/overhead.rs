@@ -1,19 +1,22 @@
 use super::{
     img_ids::Imgs, DEFAULT_NPC, FACTION_COLOR, GROUP_COLOR, GROUP_MEMBER, HP_COLOR, LOW_HP_COLOR,
-    MANA_COLOR, REGION_COLOR, SAY_COLOR, TELL_COLOR, TEXT_BG, TEXT_COLOR,
+    MANA_COLOR, POISE_COLOR, REGION_COLOR, SAY_COLOR, TELL_COLOR, TEXT_BG, TEXT_COLOR,
 };
 use crate::{
     i18n::VoxygenLocalization,
     settings::GameplaySettings,
     ui::{fonts::ConrodVoxygenFonts, Ingameable},
 };
-use common::comp::{Energy, SpeechBubble, SpeechBubbleType, Stats};
+use common::comp::{Energy, Poise, SpeechBubble, SpeechBubbleType, Stats};
 use conrod_core::{
     position::Align,
     widget::{self, Image, Rectangle, Text},
     widget_ids, Color, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
 };
+use std::time::Instant;
 const MAX_BUBBLE_WIDTH: f64 = 250.0;
+/// Seconds over which speech bubbles fade out before they disappear
+const BUBBLE_FADE_SECS: f32 = 1.0;
 
 widget_ids! {
     struct Ids {
@@ -43,6 +46,7 @@ widget_ids! {
         health_bar_bg,
         health_txt,
         mana_bar,
+        poise_bar,
         health_bar_fg,
     }
 }
@@ -52,6 +56,7 @@ pub struct Info<'a> {
     pub name: &'a str,
     pub stats: &'a Stats,
     pub energy: Option<&'a Energy>,
+    pub poise: Option<&'a Poise>,
 }
 
 /// Determines whether to show the healthbar
@@ -119,6 +124,8 @@ impl<'a> Ingameable for Overhead<'a> {
         // - 1 for HP text
         // - If there's mana
         //   - 1 Rect::new for mana
+        // - If there's poise
+        //   - 1 Rect::new for poise
         //
         // If there's a speech bubble
         // - 2 Text::new for speech bubble
@@ -127,6 +134,7 @@ impl<'a> Ingameable for Overhead<'a> {
         self.info.map_or(0, |info| {
             2 + if show_healthbar(info.stats) {
                 5 + if info.energy.is_some() { 1 } else { 0 }
+                    + if info.poise.is_some() { 1 } else { 0 }
             } else {
                 0
             }
@@ -156,6 +164,7 @@ impl<'a> Widget for Overhead<'a> {
             name,
             stats,
             energy,
+            poise,
         }) = self.info
         {
             // Used to set healthbar colours based on hp_percentage
@@ -264,6 +273,26 @@ impl<'a> Widget for Overhead<'a> {
                     .set(state.ids.mana_bar, ui);
                 }
 
+                // % Poise Filling
+                if let Some(poise) = poise {
+                    let poise_factor = if poise.maximum() > 0 {
+                        poise.current() as f64 / poise.maximum() as f64
+                    } else {
+                        0.0
+                    };
+
+                    Rectangle::fill_with(
+                        [72.0 * poise_factor * BARSIZE, MANA_BAR_HEIGHT],
+                        POISE_COLOR,
+                    )
+                    .x_y(
+                        ((3.5 + (poise_factor * 36.5)) - 36.45) * BARSIZE,
+                        MANA_BAR_Y - MANA_BAR_HEIGHT,
+                    )
+                    .parent(id)
+                    .set(state.ids.poise_bar, ui);
+                }
+
                 // Foreground
                 Image::new(self.imgs.enemy_health)
                 .w_h(84.0 * BARSIZE, 10.0 * BARSIZE)
@@ -326,8 +355,16 @@ impl<'a> Widget for Overhead<'a> {
                 |s: &str, i| -> String { self.voxygen_i18n.get_variation(&s, i).to_string() };
             let bubble_contents: String = bubble.message(localizer);
             let (text_color, shadow_color) = bubble_color(&bubble, dark_mode);
+            // Fade out towards the end of the bubble's duration
+            let fade = bubble
+                .timeout
+                .saturating_duration_since(Instant::now())
+                .as_secs_f32()
+                .min(BUBBLE_FADE_SECS)
+                / BUBBLE_FADE_SECS;
+            let tint = Some(Color::Rgba(1.0, 1.0, 1.0, fade));
             let mut text = Text::new(&bubble_contents)
-                .color(text_color)
+                .color(text_color.alpha(fade))
                 .font_id(self.fonts.cyri.conrod_id)
                 .font_size(18)
                 .up_from(state.ids.name, 26.0)
@@ -346,6 +383,7 @@ impl<'a> Widget for Overhead<'a> {
             })
             .w_h(16.0, 16.0)
             .top_left_with_margin_on(state.ids.speech_bubble_text, -20.0)
+            .color(tint)
             .parent(id)
             .set(state.ids.speech_bubble_top_left, ui);
             Image::new(if dark_mode {
@@ -356,6 +394,7 @@ impl<'a> Widget for Overhead<'a> {
             .h(16.0)
             .padded_w_of(state.ids.speech_bubble_text, -4.0)
             .mid_top_with_margin_on(state.ids.speech_bubble_text, -20.0)
+            .color(tint)
             .parent(id)
             .set(state.ids.speech_bubble_top, ui);
             Image::new(if dark_mode {
@@ -365,6 +404,7 @@ impl<'a> Widget for Overhead<'a> {
             })
             .w_h(16.0, 16.0)
             .top_right_with_margin_on(state.ids.speech_bubble_text, -20.0)
+            .color(tint)
             .parent(id)
             .set(state.ids.speech_bubble_top_right, ui);
             Image::new(if dark_mode {
@@ -375,6 +415,7 @@ impl<'a> Widget for Overhead<'a> {
             .w(16.0)
             .padded_h_of(state.ids.speech_bubble_text, -4.0)
             .mid_left_with_margin_on(state.ids.speech_bubble_text, -20.0)
+            .color(tint)
             .parent(id)
             .set(state.ids.speech_bubble_left, ui);
             Image::new(if dark_mode {
@@ -384,6 +425,7 @@ impl<'a> Widget for Overhead<'a> {
             })
             .padded_wh_of(state.ids.speech_bubble_text, -4.0)
             .top_left_with_margin_on(state.ids.speech_bubble_text, -4.0)
+            .color(tint)
             .parent(id)
             .set(state.ids.speech_bubble_mid, ui);
             Image::new(if dark_mode {
@@ -394,6 +436,7 @@ impl<'a> Widget for Overhead<'a> {
             .w(16.0)
             .padded_h_of(state.ids.speech_bubble_text, -4.0)
             .mid_right_with_margin_on(state.ids.speech_bubble_text, -20.0)
+            .color(tint)
             .parent(id)
             .set(state.ids.speech_bubble_right, ui);
             Image::new(if dark_mode {
@@ -403,6 +446,7 @@ impl<'a> Widget for Overhead<'a> {
             })
             .w_h(16.0, 16.0)
             .bottom_left_with_margin_on(state.ids.speech_bubble_text, -20.0)
+            .color(tint)
             .parent(id)
             .set(state.ids.speech_bubble_bottom_left, ui);
             Image::new(if dark_mode {
@@ -413,6 +457,7 @@ impl<'a> Widget for Overhead<'a> {
             .h(16.0)
             .padded_w_of(state.ids.speech_bubble_text, -4.0)
             .mid_bottom_with_margin_on(state.ids.speech_bubble_text, -20.0)
+            .color(tint)
             .parent(id)
             .set(state.ids.speech_bubble_bottom, ui);
             Image::new(if dark_mode {
@@ -422,6 +467,7 @@ impl<'a> Widget for Overhead<'a> {
             })
             .w_h(16.0, 16.0)
             .bottom_right_with_margin_on(state.ids.speech_bubble_text, -20.0)
+            .color(tint)
             .parent(id)
             .set(state.ids.speech_bubble_bottom_right, ui);
             let tail = Image::new(if dark_mode {
@@ -429,6 +475,7 @@ impl<'a> Widget for Overhead<'a> {
             } else {
                 self.imgs.speech_bubble_tail
             })
+            .color(tint)
             .parent(id)
             .mid_bottom_with_margin_on(state.ids.speech_bubble_text, -32.0);
 
@@ -440,7 +487,7 @@ impl<'a> Widget for Overhead<'a> {
             .set(state.ids.speech_bubble_tail, ui);
 
             let mut text_shadow = Text::new(&bubble_contents)
-                .color(shadow_color)
+                .color(shadow_color.alpha(fade))
                 .font_id(self.fonts.cyri.conrod_id)
                 .font_size(18)
                 .x_relative_to(state.ids.speech_bubble_text, 1.0)
@@ -462,6 +509,7 @@ impl<'a> Widget for Overhead<'a> {
             };
             Image::new(icon)
                 .w_h(16.0, 16.0)
+                .color(tint)
                 .top_left_with_margin_on(state.ids.speech_bubble_text, -16.0)
                 // TODO: Figure out whether this should be parented.
                 // .parent(id)
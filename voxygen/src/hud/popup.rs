@@ -120,17 +120,17 @@ impl<'a> Widget for Popup<'a> {
 
         // Push waypoint to message queue
         for notification in self.new_notifications {
-            match notification {
-                Notification::WaypointSaved => {
-                    state.update(|s| {
-                        if s.infos.is_empty() {
-                            s.last_info_update = Instant::now();
-                        }
-                        let text = self.voxygen_i18n.get("hud.waypoint_saved");
-                        s.infos.push_back(text.to_string());
-                    });
-                },
-            }
+            let key = match notification {
+                Notification::WaypointSaved => "hud.waypoint_saved",
+                Notification::WaypointDiscovered => "hud.waypoint_discovered",
+            };
+            state.update(|s| {
+                if s.infos.is_empty() {
+                    s.last_info_update = Instant::now();
+                }
+                let text = self.voxygen_i18n.get(key);
+                s.infos.push_back(text.to_string());
+            });
         }
 
         // Get next error from queue
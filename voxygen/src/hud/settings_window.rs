@@ -55,6 +55,8 @@ widget_ids! {
         relative_to_win_text,
         absolute_scale_button,
         absolute_scale_text,
+        limit_aspect_button,
+        limit_aspect_text,
         gameplay,
         controls,
         languages,
@@ -124,6 +126,12 @@ widget_ids! {
         ambiance_slider,
         ambiance_text,
         ambiance_value,
+        head_bob_slider,
+        head_bob_text,
+        head_bob_value,
+        screen_shake_slider,
+        screen_shake_text,
+        screen_shake_value,
         aa_mode_text,
         aa_mode_list,
         cloud_mode_text,
@@ -157,6 +165,10 @@ widget_ids! {
         audio_volume_text,
         sfx_volume_slider,
         sfx_volume_text,
+        voice_volume_slider,
+        voice_volume_text,
+        voice_chat_button,
+        voice_chat_label,
         audio_device_list,
         audio_device_text,
         hotbar_title,
@@ -200,6 +212,16 @@ widget_ids! {
         speech_bubble_dark_mode_button,
         speech_bubble_icon_text,
         speech_bubble_icon_button,
+        speech_bubble_duration_text,
+        speech_bubble_duration_slider,
+        speech_bubble_duration_value,
+        max_speech_bubbles_text,
+        max_speech_bubbles_slider,
+        max_speech_bubbles_value,
+        dps_meter_text,
+        dps_meter_button,
+        streamer_mode_text,
+        streamer_mode_button,
         free_look_behavior_text,
         free_look_behavior_list,
         auto_walk_behavior_text,
@@ -276,17 +298,24 @@ pub enum Event {
     AdjustLodDetail(u32),
     AdjustGamma(f32),
     AdjustAmbiance(f32),
+    AdjustHeadBob(f32),
+    AdjustScreenShake(f32),
     AdjustWindowSize([u16; 2]),
     ChangeFullscreenMode(FullScreenSettings),
     ToggleParticlesEnabled(bool),
     ChangeRenderMode(Box<RenderMode>),
     AdjustMusicVolume(f32),
     AdjustSfxVolume(f32),
+    #[cfg(feature = "voice")]
+    AdjustVoiceVolume(f32),
+    #[cfg(feature = "voice")]
+    ToggleVoiceChat(bool),
     ChangeAudioDevice(String),
     MaximumFPS(u32),
     CrosshairTransp(f32),
     CrosshairType(CrosshairType),
     UiScale(ScaleChange),
+    UiMaxAspectRatio(Option<f64>),
     ChatTransp(f32),
     ChatCharName(bool),
     Sct(bool),
@@ -294,6 +323,10 @@ pub enum Event {
     SctDamageBatch(bool),
     SpeechBubbleDarkMode(bool),
     SpeechBubbleIcon(bool),
+    SpeechBubbleDuration(f32),
+    MaxSpeechBubbles(u32),
+    ToggleDpsMeter(bool),
+    ToggleStreamerMode(bool),
     ChangeLanguage(Box<LanguageMetadata>),
     ChangeBinding(GameInput),
     ResetBindings,
@@ -620,6 +653,48 @@ impl<'a> Widget for SettingsWindow<'a> {
                     .set(state.ids.ui_scale_slider, ui);
             }
 
+            // Limit HUD Aspect Ratio Button
+            let limit_aspect = self
+                .global_state
+                .settings
+                .gameplay
+                .ui_max_aspect_ratio
+                .is_some();
+            if Button::image(if limit_aspect {
+                self.imgs.check_checked
+            } else {
+                self.imgs.check
+            })
+            .w_h(288.0 / 24.0, 288.0 / 24.0)
+            .down_from(state.ids.absolute_scale_button, 8.0)
+            .hover_image(if limit_aspect {
+                self.imgs.check_checked_mo
+            } else {
+                self.imgs.check_mo
+            })
+            .press_image(if limit_aspect {
+                self.imgs.check_checked
+            } else {
+                self.imgs.check_press
+            })
+            .set(state.ids.limit_aspect_button, ui)
+            .was_clicked()
+            {
+                events.push(Event::UiMaxAspectRatio(if limit_aspect {
+                    None
+                } else {
+                    Some(16.0 / 9.0)
+                }));
+            }
+
+            Text::new(self.localized_strings.get("hud.settings.limit_hud_aspect"))
+                .right_from(state.ids.limit_aspect_button, 10.0)
+                .font_size(self.fonts.cyri.scale(14))
+                .font_id(self.fonts.cyri.conrod_id)
+                .graphics_for(state.ids.limit_aspect_button)
+                .color(TEXT_COLOR)
+                .set(state.ids.limit_aspect_text, ui);
+
             // Crosshair Options
             // Crosshair Types
             // Round
@@ -752,7 +827,7 @@ impl<'a> Widget for SettingsWindow<'a> {
                 .set(state.ids.crosshair_inner_3, ui);
             // Crosshair Transparency Text and Slider
             Text::new(&self.localized_strings.get("hud.settings.crosshair"))
-                .down_from(state.ids.absolute_scale_button, 20.0)
+                .down_from(state.ids.limit_aspect_button, 20.0)
                 .font_size(self.fonts.cyri.scale(18))
                 .font_id(self.fonts.cyri.conrod_id)
                 .color(TEXT_COLOR)
@@ -1081,10 +1156,124 @@ impl<'a> Widget for SettingsWindow<'a> {
             .color(TEXT_COLOR)
             .set(state.ids.speech_bubble_icon_text, ui);
 
+            // Speech bubble duration
+            let speech_bubble_duration = self.global_state.settings.gameplay.speech_bubble_duration;
+            Text::new(
+                &self
+                    .localized_strings
+                    .get("hud.settings.speech_bubble_duration"),
+            )
+            .down_from(state.ids.speech_bubble_icon_button, 10.0)
+            .font_size(self.fonts.cyri.scale(14))
+            .font_id(self.fonts.cyri.conrod_id)
+            .color(TEXT_COLOR)
+            .set(state.ids.speech_bubble_duration_text, ui);
+            if let Some(new_val) = ImageSlider::continuous(
+                speech_bubble_duration,
+                1.0,
+                15.0,
+                self.imgs.slider_indicator,
+                self.imgs.slider,
+            )
+            .w_h(104.0, 22.0)
+            .down_from(state.ids.speech_bubble_duration_text, 8.0)
+            .track_breadth(12.0)
+            .slider_length(10.0)
+            .pad_track((5.0, 5.0))
+            .set(state.ids.speech_bubble_duration_slider, ui)
+            {
+                events.push(Event::SpeechBubbleDuration(new_val));
+            }
+            Text::new(&format!("{:.1}s", speech_bubble_duration))
+                .right_from(state.ids.speech_bubble_duration_slider, 8.0)
+                .font_size(self.fonts.cyri.scale(14))
+                .graphics_for(state.ids.speech_bubble_duration_slider)
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(TEXT_COLOR)
+                .set(state.ids.speech_bubble_duration_value, ui);
+
+            // Maximum number of speech bubbles
+            let max_speech_bubbles = self.global_state.settings.gameplay.max_speech_bubbles;
+            Text::new(
+                &self
+                    .localized_strings
+                    .get("hud.settings.max_speech_bubbles"),
+            )
+            .down_from(state.ids.speech_bubble_duration_slider, 10.0)
+            .font_size(self.fonts.cyri.scale(14))
+            .font_id(self.fonts.cyri.conrod_id)
+            .color(TEXT_COLOR)
+            .set(state.ids.max_speech_bubbles_text, ui);
+            if let Some(new_val) = ImageSlider::discrete(
+                max_speech_bubbles,
+                1,
+                30,
+                self.imgs.slider_indicator,
+                self.imgs.slider,
+            )
+            .w_h(104.0, 22.0)
+            .down_from(state.ids.max_speech_bubbles_text, 8.0)
+            .track_breadth(12.0)
+            .slider_length(10.0)
+            .pad_track((5.0, 5.0))
+            .set(state.ids.max_speech_bubbles_slider, ui)
+            {
+                events.push(Event::MaxSpeechBubbles(new_val));
+            }
+            Text::new(&format!("{}", max_speech_bubbles))
+                .right_from(state.ids.max_speech_bubbles_slider, 8.0)
+                .font_size(self.fonts.cyri.scale(14))
+                .graphics_for(state.ids.max_speech_bubbles_slider)
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(TEXT_COLOR)
+                .set(state.ids.max_speech_bubbles_value, ui);
+
+            // DPS meter
+            let dps_meter = ToggleButton::new(
+                self.global_state.settings.gameplay.dps_meter,
+                self.imgs.checkbox,
+                self.imgs.checkbox_checked,
+            )
+            .down_from(state.ids.max_speech_bubbles_slider, 10.0)
+            .w_h(18.0, 18.0)
+            .hover_images(self.imgs.checkbox_mo, self.imgs.checkbox_checked_mo)
+            .press_images(self.imgs.checkbox_press, self.imgs.checkbox_checked)
+            .set(state.ids.dps_meter_button, ui);
+            if self.global_state.settings.gameplay.dps_meter != dps_meter {
+                events.push(Event::ToggleDpsMeter(dps_meter));
+            }
+            Text::new(&self.localized_strings.get("hud.settings.dps_meter"))
+                .right_from(state.ids.dps_meter_button, 10.0)
+                .font_size(self.fonts.cyri.scale(15))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(TEXT_COLOR)
+                .set(state.ids.dps_meter_text, ui);
+
+            // Streamer mode
+            let streamer_mode = ToggleButton::new(
+                self.global_state.settings.gameplay.streamer_mode,
+                self.imgs.checkbox,
+                self.imgs.checkbox_checked,
+            )
+            .down_from(state.ids.dps_meter_button, 10.0)
+            .w_h(18.0, 18.0)
+            .hover_images(self.imgs.checkbox_mo, self.imgs.checkbox_checked_mo)
+            .press_images(self.imgs.checkbox_press, self.imgs.checkbox_checked)
+            .set(state.ids.streamer_mode_button, ui);
+            if self.global_state.settings.gameplay.streamer_mode != streamer_mode {
+                events.push(Event::ToggleStreamerMode(streamer_mode));
+            }
+            Text::new(&self.localized_strings.get("hud.settings.streamer_mode"))
+                .right_from(state.ids.streamer_mode_button, 10.0)
+                .font_size(self.fonts.cyri.scale(15))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(TEXT_COLOR)
+                .set(state.ids.streamer_mode_text, ui);
+
             // Energybars Numbers
             // Hotbar text
             Text::new(&self.localized_strings.get("hud.settings.energybar_numbers"))
-                .down_from(state.ids.speech_bubble_icon_button, 20.0)
+                .down_from(state.ids.streamer_mode_button, 20.0)
                 .font_size(self.fonts.cyri.scale(18))
                 .font_id(self.fonts.cyri.conrod_id)
                 .color(TEXT_COLOR)
@@ -1914,6 +2103,72 @@ impl<'a> Widget for SettingsWindow<'a> {
             .color(TEXT_COLOR)
             .set(state.ids.ambiance_value, ui);
 
+            // Head bob
+            if let Some(new_val) = ImageSlider::discrete(
+                (self.global_state.settings.graphics.head_bob_intensity * 10.0).round() as i32,
+                0,
+                20,
+                self.imgs.slider_indicator,
+                self.imgs.slider,
+            )
+            .w_h(104.0, 22.0)
+            .right_from(state.ids.fov_slider, 50.0)
+            .track_breadth(12.0)
+            .slider_length(10.0)
+            .pad_track((5.0, 5.0))
+            .set(state.ids.head_bob_slider, ui)
+            {
+                events.push(Event::AdjustHeadBob(new_val as f32 / 10.0));
+            }
+            Text::new(&self.localized_strings.get("hud.settings.head_bob"))
+                .up_from(state.ids.head_bob_slider, 8.0)
+                .font_size(self.fonts.cyri.scale(14))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(TEXT_COLOR)
+                .set(state.ids.head_bob_text, ui);
+            Text::new(&format!(
+                "{:.0}%",
+                self.global_state.settings.graphics.head_bob_intensity * 100.0
+            ))
+            .right_from(state.ids.head_bob_slider, 8.0)
+            .font_size(self.fonts.cyri.scale(14))
+            .font_id(self.fonts.cyri.conrod_id)
+            .color(TEXT_COLOR)
+            .set(state.ids.head_bob_value, ui);
+
+            // Screen shake
+            if let Some(new_val) = ImageSlider::discrete(
+                (self.global_state.settings.graphics.screen_shake_intensity * 10.0).round() as i32,
+                0,
+                20,
+                self.imgs.slider_indicator,
+                self.imgs.slider,
+            )
+            .w_h(104.0, 22.0)
+            .right_from(state.ids.lod_detail_slider, 50.0)
+            .track_breadth(12.0)
+            .slider_length(10.0)
+            .pad_track((5.0, 5.0))
+            .set(state.ids.screen_shake_slider, ui)
+            {
+                events.push(Event::AdjustScreenShake(new_val as f32 / 10.0));
+            }
+            Text::new(&self.localized_strings.get("hud.settings.screen_shake"))
+                .up_from(state.ids.screen_shake_slider, 8.0)
+                .font_size(self.fonts.cyri.scale(14))
+                .font_id(self.fonts.cyri.conrod_id)
+                .color(TEXT_COLOR)
+                .set(state.ids.screen_shake_text, ui);
+            Text::new(&format!(
+                "{:.0}%",
+                self.global_state.settings.graphics.screen_shake_intensity * 100.0
+            ))
+            .right_from(state.ids.screen_shake_slider, 8.0)
+            .font_size(self.fonts.cyri.scale(14))
+            .font_id(self.fonts.cyri.conrod_id)
+            .color(TEXT_COLOR)
+            .set(state.ids.screen_shake_value, ui);
+
             // Sprites VD
             if let Some(new_val) = ImageSlider::discrete(
                 self.global_state.settings.graphics.sprite_render_distance,
@@ -2611,11 +2866,67 @@ impl<'a> Widget for SettingsWindow<'a> {
                 events.push(Event::AdjustSfxVolume(new_val));
             }
 
+            // Voice Chat -------------------------------------------------------
+            #[cfg(feature = "voice")]
+            {
+                Text::new(&self.localized_strings.get("hud.settings.voice_volume"))
+                    .down_from(state.ids.sfx_volume_slider, 10.0)
+                    .font_size(self.fonts.cyri.scale(14))
+                    .font_id(self.fonts.cyri.conrod_id)
+                    .color(TEXT_COLOR)
+                    .set(state.ids.voice_volume_text, ui);
+
+                if let Some(new_val) = ImageSlider::continuous(
+                    self.global_state.settings.audio.voice_volume,
+                    0.0,
+                    1.0,
+                    self.imgs.slider_indicator,
+                    self.imgs.slider,
+                )
+                .w_h(104.0, 22.0)
+                .down_from(state.ids.voice_volume_text, 10.0)
+                .track_breadth(12.0)
+                .slider_length(10.0)
+                .pad_track((5.0, 5.0))
+                .set(state.ids.voice_volume_slider, ui)
+                {
+                    events.push(Event::AdjustVoiceVolume(new_val));
+                }
+
+                let voice_chat = ToggleButton::new(
+                    self.global_state.settings.audio.voice_chat,
+                    self.imgs.checkbox,
+                    self.imgs.checkbox_checked,
+                )
+                .w_h(18.0, 18.0)
+                .down_from(state.ids.voice_volume_slider, 10.0)
+                .hover_images(self.imgs.checkbox_mo, self.imgs.checkbox_checked_mo)
+                .press_images(self.imgs.checkbox_press, self.imgs.checkbox_checked)
+                .set(state.ids.voice_chat_button, ui);
+
+                if self.global_state.settings.audio.voice_chat != voice_chat {
+                    events.push(Event::ToggleVoiceChat(voice_chat));
+                }
+
+                Text::new(&self.localized_strings.get("hud.settings.voice_chat"))
+                    .right_from(state.ids.voice_chat_button, 10.0)
+                    .font_size(self.fonts.cyri.scale(14))
+                    .font_id(self.fonts.cyri.conrod_id)
+                    .graphics_for(state.ids.voice_chat_button)
+                    .color(TEXT_COLOR)
+                    .set(state.ids.voice_chat_label, ui);
+            }
+
             // Audio Device Selector --------------------------------------------
             let device = &self.global_state.audio.device;
             let device_list = &self.global_state.audio.device_list;
+            let above_device = if cfg!(feature = "voice") {
+                state.ids.voice_chat_button
+            } else {
+                state.ids.sfx_volume_slider
+            };
             Text::new(&self.localized_strings.get("hud.settings.audio_device"))
-                .down_from(state.ids.sfx_volume_slider, 10.0)
+                .down_from(above_device, 10.0)
                 .font_size(self.fonts.cyri.scale(14))
                 .font_id(self.fonts.cyri.conrod_id)
                 .color(TEXT_COLOR)
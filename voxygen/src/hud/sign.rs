@@ -0,0 +1,144 @@
+use super::{img_ids::Imgs, TEXT_COLOR};
+use crate::{i18n::VoxygenLocalization, ui::fonts::ConrodVoxygenFonts};
+use common::terrain::sign::validate_sign_text;
+use conrod_core::{
+    widget::{self, Button, Rectangle, Text, TextEdit},
+    widget_ids, Colorable, Labelable, Positionable, Sizeable, Widget, WidgetCommon,
+};
+
+const SIGN_WIDTH: f64 = 300.0;
+const SIGN_HEIGHT: f64 = 120.0;
+
+widget_ids! {
+    struct Ids {
+        bg,
+        text,
+        input,
+        save_button,
+        cancel_button,
+    }
+}
+
+pub struct State {
+    ids: Ids,
+}
+
+pub enum Event {
+    Change(String),
+    Save,
+    Close,
+}
+
+/// The text written on a sign, shown while looking at it. When editing, the
+/// text can be changed and saved.
+#[derive(WidgetCommon)]
+pub struct Sign<'a> {
+    text: &'a str,
+    editing: bool,
+    imgs: &'a Imgs,
+    fonts: &'a ConrodVoxygenFonts,
+    localized_strings: &'a VoxygenLocalization,
+
+    #[conrod(common_builder)]
+    common: widget::CommonBuilder,
+}
+
+impl<'a> Sign<'a> {
+    pub fn new(
+        text: &'a str,
+        editing: bool,
+        imgs: &'a Imgs,
+        fonts: &'a ConrodVoxygenFonts,
+        localized_strings: &'a VoxygenLocalization,
+    ) -> Self {
+        Self {
+            text,
+            editing,
+            imgs,
+            fonts,
+            localized_strings,
+            common: widget::CommonBuilder::default(),
+        }
+    }
+}
+
+impl<'a> Widget for Sign<'a> {
+    type Event = Vec<Event>;
+    type State = State;
+    type Style = ();
+
+    fn init_state(&self, id_gen: widget::id::Generator) -> Self::State {
+        State {
+            ids: Ids::new(id_gen),
+        }
+    }
+
+    #[allow(clippy::unused_unit)] // TODO: Pending review in #587
+    fn style(&self) -> Self::Style { () }
+
+    fn update(self, args: widget::UpdateArgs<Self>) -> Self::Event {
+        let widget::UpdateArgs { state, ui, .. } = args;
+        let mut events = Vec::new();
+
+        Rectangle::fill([SIGN_WIDTH, SIGN_HEIGHT])
+            .rgba(0.0, 0.0, 0.0, 0.7)
+            .mid_bottom_with_margin_on(ui.window, 300.0)
+            .set(state.ids.bg, ui);
+
+        if !self.editing {
+            Text::new(self.text)
+                .top_left_with_margins_on(state.ids.bg, 8.0, 8.0)
+                .w(SIGN_WIDTH - 16.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(16))
+                .color(TEXT_COLOR)
+                .set(state.ids.text, ui);
+            return events;
+        }
+
+        if let Some(text) = TextEdit::new(self.text)
+            .top_left_with_margins_on(state.ids.bg, 8.0, 8.0)
+            .w_h(SIGN_WIDTH - 16.0, SIGN_HEIGHT - 16.0)
+            .font_id(self.fonts.cyri.conrod_id)
+            .font_size(self.fonts.cyri.scale(16))
+            .color(TEXT_COLOR)
+            .set(state.ids.input, ui)
+        {
+            // Ignore edits the server would reject anyway
+            if validate_sign_text(&text).is_ok() {
+                events.push(Event::Change(text));
+            }
+        }
+
+        if Button::image(self.imgs.button)
+            .w_h(100.0, 30.0)
+            .bottom_right_with_margins_on(state.ids.bg, -35.0, 0.0)
+            .hover_image(self.imgs.button_hover)
+            .press_image(self.imgs.button_press)
+            .label(&self.localized_strings.get("hud.sign.save"))
+            .label_color(TEXT_COLOR)
+            .label_font_id(self.fonts.cyri.conrod_id)
+            .label_font_size(self.fonts.cyri.scale(14))
+            .set(state.ids.save_button, ui)
+            .was_clicked()
+        {
+            events.push(Event::Save);
+        }
+        if Button::image(self.imgs.button)
+            .w_h(100.0, 30.0)
+            .left_from(state.ids.save_button, 10.0)
+            .hover_image(self.imgs.button_hover)
+            .press_image(self.imgs.button_press)
+            .label(&self.localized_strings.get("common.cancel"))
+            .label_color(TEXT_COLOR)
+            .label_font_id(self.fonts.cyri.conrod_id)
+            .label_font_size(self.fonts.cyri.scale(14))
+            .set(state.ids.cancel_button, ui)
+            .was_clicked()
+        {
+            events.push(Event::Close);
+        }
+
+        events
+    }
+}
@@ -2,8 +2,8 @@ use super::{
     hotbar,
     img_ids::{Imgs, ImgsRot},
     item_imgs::ItemImgs,
-    slots, BarNumbers, ShortcutNumbers, Show, XpBar, BLACK, CRITICAL_HP_COLOR, HP_COLOR,
-    LOW_HP_COLOR, MANA_COLOR, TEXT_COLOR, XP_COLOR,
+    slots, BarNumbers, ShortcutNumbers, Show, XpBar, BLACK, HP_COLOR, LOW_HP_COLOR, MANA_COLOR,
+    TEXT_COLOR, XP_COLOR,
 };
 use crate::{
     i18n::VoxygenLocalization,
@@ -18,9 +18,9 @@ use crate::{
 use common::comp::{
     item::{
         tool::{Tool, ToolKind},
-        Hands, ItemKind,
+        ItemKind,
     },
-    CharacterState, ControllerInputs, Energy, Inventory, Loadout, Stats,
+    AbilityHotbar, CharacterState, ControllerInputs, Energy, Inventory, Loadout, Stats,
 };
 use conrod_core::{
     color,
@@ -32,10 +32,6 @@ use vek::*;
 
 widget_ids! {
     struct Ids {
-        death_message_1,
-        death_message_2,
-        death_message_1_bg,
-        death_message_2_bg,
         level_text,
         next_level_text,
         xp_bar_mid,
@@ -128,6 +124,7 @@ pub struct Skillbar<'a> {
     controller: &'a ControllerInputs,
     inventory: &'a Inventory,
     hotbar: &'a hotbar::State,
+    ability_hotbar: &'a AbilityHotbar,
     tooltip_manager: &'a mut TooltipManager,
     slot_manager: &'a mut slots::SlotManager,
     localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
@@ -154,6 +151,7 @@ impl<'a> Skillbar<'a> {
         controller: &'a ControllerInputs,
         inventory: &'a Inventory,
         hotbar: &'a hotbar::State,
+        ability_hotbar: &'a AbilityHotbar,
         tooltip_manager: &'a mut TooltipManager,
         slot_manager: &'a mut slots::SlotManager,
         localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
@@ -175,6 +173,7 @@ impl<'a> Skillbar<'a> {
             controller,
             inventory,
             hotbar,
+            ability_hotbar,
             tooltip_manager,
             slot_manager,
             localized_strings,
@@ -297,48 +296,6 @@ impl<'a> Widget for Skillbar<'a> {
                 .graphics_for(state.ids.level_align)
                 .set(state.ids.level_down, ui);
         }
-        // Death message
-        if self.stats.is_dead {
-            if let Some(key) = self
-                .global_state
-                .settings
-                .controls
-                .get_binding(GameInput::Respawn)
-            {
-                Text::new(&localized_strings.get("hud.you_died"))
-                    .middle_of(ui.window)
-                    .font_size(self.fonts.cyri.scale(50))
-                    .font_id(self.fonts.cyri.conrod_id)
-                    .color(Color::Rgba(0.0, 0.0, 0.0, 1.0))
-                    .set(state.ids.death_message_1_bg, ui);
-                Text::new(
-                    &localized_strings
-                        .get("hud.press_key_to_respawn")
-                        .replace("{key}", key.to_string().as_str()),
-                )
-                .mid_bottom_with_margin_on(state.ids.death_message_1_bg, -120.0)
-                .font_size(self.fonts.cyri.scale(30))
-                .font_id(self.fonts.cyri.conrod_id)
-                .color(Color::Rgba(0.0, 0.0, 0.0, 1.0))
-                .set(state.ids.death_message_2_bg, ui);
-                Text::new(&localized_strings.get("hud.you_died"))
-                    .bottom_left_with_margins_on(state.ids.death_message_1_bg, 2.0, 2.0)
-                    .font_size(self.fonts.cyri.scale(50))
-                    .font_id(self.fonts.cyri.conrod_id)
-                    .color(CRITICAL_HP_COLOR)
-                    .set(state.ids.death_message_1, ui);
-                Text::new(
-                    &localized_strings
-                        .get("hud.press_key_to_respawn")
-                        .replace("{key}", key.to_string().as_str()),
-                )
-                .bottom_left_with_margins_on(state.ids.death_message_2_bg, 2.0, 2.0)
-                .font_size(self.fonts.cyri.scale(30))
-                .font_id(self.fonts.cyri.conrod_id)
-                .color(CRITICAL_HP_COLOR)
-                .set(state.ids.death_message_2, ui);
-            }
-        }
         // Experience-Bar
         match self.global_state.settings.gameplay.xp_bar {
             XpBar::Always => {
@@ -664,25 +621,17 @@ impl<'a> Widget for Skillbar<'a> {
             },
         }
 
-        let active_tool_kind = match self.loadout.active_item.as_ref().map(|i| i.item.kind()) {
-            Some(ItemKind::Tool(Tool { kind, .. })) => Some(kind),
-            _ => None,
-        };
-
-        let second_tool_kind = match self.loadout.second_item.as_ref().map(|i| i.item.kind()) {
+        // The secondary ability belongs to the offhand item, if there is one
+        let tool_kind = match self
+            .loadout
+            .offhand()
+            .or_else(|| self.loadout.active_item.as_ref())
+            .map(|i| i.item.kind())
+        {
             Some(ItemKind::Tool(Tool { kind, .. })) => Some(kind),
             _ => None,
         };
 
-        let tool_kind = match (
-            active_tool_kind.map(|tk| tk.hands()),
-            second_tool_kind.map(|tk| tk.hands()),
-        ) {
-            (Some(Hands::TwoHand), _) => active_tool_kind,
-            (_, Some(Hands::OneHand)) => second_tool_kind,
-            (_, _) => None,
-        };
-
         Image::new(self.imgs.skillbar_slot_big_bg)
             .w_h(38.0 * scale, 38.0 * scale)
             .color(match tool_kind {
@@ -735,7 +684,13 @@ impl<'a> Widget for Skillbar<'a> {
         })
         .set(state.ids.m2_content, ui);
         // Slots
-        let content_source = (self.hotbar, self.inventory, self.loadout, self.energy); // TODO: avoid this
+        let content_source = (
+            self.hotbar,
+            self.inventory,
+            self.loadout,
+            self.energy,
+            self.ability_hotbar,
+        ); // TODO: avoid this
         let image_source = (self.item_imgs, self.imgs);
 
         let mut slot_maker = SlotMaker {
@@ -825,6 +780,13 @@ impl<'a> Widget for Skillbar<'a> {
                             },
                             _ => None,
                         }),
+                    hotbar::SlotContents::Ability(slot) => {
+                        let item = content_source.2.active_item.as_ref()?;
+                        content_source
+                            .4
+                            .ability(slot, item)
+                            .map(|ability| (ability.kind_name(), item.item.name()))
+                    },
                 })
         };
 
@@ -9,7 +9,7 @@ use common::comp::{
         tool::{Tool, ToolKind},
         ItemKind,
     },
-    Energy, Inventory, Loadout,
+    AbilityHotbar, CharacterAbility, Energy, Inventory, ItemConfig, Loadout,
 };
 use conrod_core::{image, Color};
 
@@ -90,7 +90,13 @@ pub enum HotbarImage {
     BowJumpBurst,
 }
 
-type HotbarSource<'a> = (&'a hotbar::State, &'a Inventory, &'a Loadout, &'a Energy);
+type HotbarSource<'a> = (
+    &'a hotbar::State,
+    &'a Inventory,
+    &'a Loadout,
+    &'a Energy,
+    &'a AbilityHotbar,
+);
 type HotbarImageSource<'a> = (&'a ItemImgs, &'a img_ids::Imgs);
 
 impl<'a> SlotKey<HotbarSource<'a>, HotbarImageSource<'a>> for HotbarSlot {
@@ -98,7 +104,7 @@ impl<'a> SlotKey<HotbarSource<'a>, HotbarImageSource<'a>> for HotbarSlot {
 
     fn image_key(
         &self,
-        (hotbar, inventory, loadout, energy): &HotbarSource<'a>,
+        (hotbar, inventory, loadout, energy, ability_hotbar): &HotbarSource<'a>,
     ) -> Option<(Self::ImageKey, Option<Color>)> {
         hotbar.get(*self).and_then(|contents| match contents {
             hotbar::SlotContents::Inventory(idx) => inventory
@@ -148,15 +154,20 @@ impl<'a> SlotKey<HotbarSource<'a>, HotbarImageSource<'a>> for HotbarSlot {
                         ),
                     })
                 }),
+            hotbar::SlotContents::Ability(slot) => {
+                let item = loadout.active_item.as_ref()?;
+                let ability = ability_hotbar.ability(slot, item)?;
+                Some((ability_image(ability, item), None))
+            },
         })
     }
 
-    fn amount(&self, (hotbar, inventory, _, _): &HotbarSource<'a>) -> Option<u32> {
+    fn amount(&self, (hotbar, inventory, _, _, _): &HotbarSource<'a>) -> Option<u32> {
         hotbar
             .get(*self)
             .and_then(|content| match content {
                 hotbar::SlotContents::Inventory(idx) => inventory.get(idx),
-                hotbar::SlotContents::Ability3 => None,
+                hotbar::SlotContents::Ability3 | hotbar::SlotContents::Ability(_) => None,
             })
             .map(|item| item.amount())
             .filter(|amount| *amount > 1)
@@ -175,6 +186,24 @@ impl<'a> SlotKey<HotbarSource<'a>, HotbarImageSource<'a>> for HotbarSlot {
     }
 }
 
+/// The skill image of an ability, or the image of its item for abilities
+/// without one
+fn ability_image(ability: &CharacterAbility, item: &ItemConfig) -> HotbarImage {
+    let tool = match item.item.kind() {
+        ItemKind::Tool(Tool { kind, .. }) => Some(kind),
+        _ => None,
+    };
+    match (ability, tool) {
+        (CharacterAbility::SpinMelee { .. }, _) => HotbarImage::SwordWhirlwind,
+        (CharacterAbility::LeapMelee { .. }, Some(ToolKind::Hammer(_))) => HotbarImage::HammerLeap,
+        (CharacterAbility::LeapMelee { .. }, _) => HotbarImage::AxeLeapSlash,
+        (CharacterAbility::Shockwave { .. }, _) => HotbarImage::FireAoe,
+        (CharacterAbility::RepeaterRanged { .. }, _) => HotbarImage::BowJumpBurst,
+        (CharacterAbility::Boost { .. }, _) => HotbarImage::SnakeArrow,
+        _ => HotbarImage::Item(item.item.kind().into()),
+    }
+}
+
 impl From<InventorySlot> for SlotKind {
     fn from(inventory: InventorySlot) -> Self { Self::Inventory(inventory) }
 }
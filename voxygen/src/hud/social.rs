@@ -8,12 +8,14 @@ use crate::{
     ui::{fonts::ConrodVoxygenFonts, ImageFrame, Tooltip, TooltipManager, Tooltipable},
 };
 use client::{self, Client};
-use common::{comp::group, sync::Uid};
+use common::{comp::group, msg::PlayerInfo, sync::Uid};
 use conrod_core::{
     color,
     widget::{self, Button, Image, Rectangle, Scrollbar, Text},
     widget_ids, Color, Colorable, Labelable, Positionable, Sizeable, Widget, WidgetCommon,
 };
+use hashbrown::HashMap;
+#[cfg(feature = "voice")] use hashbrown::HashSet;
 use std::time::Instant;
 
 widget_ids! {
@@ -45,6 +47,7 @@ widget_ids! {
         friends_test,
         faction_test,
         invite_button,
+        mute_button,
     }
 }
 
@@ -65,6 +68,10 @@ pub enum SocialTab {
 pub struct Social<'a> {
     show: &'a Show,
     client: &'a Client,
+    /// The player list as the HUD shows it, names are taken from here
+    player_list: &'a HashMap<Uid, PlayerInfo>,
+    #[cfg(feature = "voice")]
+    muted_voices: &'a HashSet<String>,
     imgs: &'a Imgs,
     fonts: &'a ConrodVoxygenFonts,
     localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
@@ -74,6 +81,7 @@ pub struct Social<'a> {
 
     #[conrod(common_builder)]
     common: widget::CommonBuilder,
+    anchor_area: widget::Id,
 }
 
 impl<'a> Social<'a> {
@@ -81,16 +89,22 @@ impl<'a> Social<'a> {
     pub fn new(
         show: &'a Show,
         client: &'a Client,
+        player_list: &'a HashMap<Uid, PlayerInfo>,
+        #[cfg(feature = "voice")] muted_voices: &'a HashSet<String>,
         imgs: &'a Imgs,
         fonts: &'a ConrodVoxygenFonts,
         localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
         selected_entity: Option<(specs::Entity, Instant)>,
         rot_imgs: &'a ImgsRot,
         tooltip_manager: &'a mut TooltipManager,
+        anchor_area: widget::Id,
     ) -> Self {
         Self {
             show,
             client,
+            player_list,
+            #[cfg(feature = "voice")]
+            muted_voices,
             imgs,
             rot_imgs,
             fonts,
@@ -98,6 +112,7 @@ impl<'a> Social<'a> {
             tooltip_manager,
             selected_entity,
             common: widget::CommonBuilder::default(),
+            anchor_area,
         }
     }
 }
@@ -105,6 +120,9 @@ impl<'a> Social<'a> {
 pub enum Event {
     Close,
     Invite(Uid),
+    /// Mute or unmute the voice chat of the player with this alias
+    #[cfg(feature = "voice")]
+    ToggleVoiceMute(String),
     ChangeSocialTab(SocialTab),
 }
 
@@ -162,7 +180,7 @@ impl<'a> Widget for Social<'a> {
             SocialTab::Faction => self.imgs.social_frame_fact,
         };
         Image::new(window_bg)
-            .bottom_left_with_margins_on(ui.window, 308.0, pos)
+            .bottom_left_with_margins_on(self.anchor_area, 308.0, pos)
             .color(Some(UI_MAIN))
             .w_h(280.0, 460.0)
             .set(state.ids.bg, ui);
@@ -266,7 +284,7 @@ impl<'a> Widget for Social<'a> {
         }
         // Online Tab
         if let SocialTab::Online = self.show.social_tab {
-            let players = self.client.player_list.iter().filter(|(_, p)| p.is_online);
+            let players = self.player_list.iter().filter(|(_, p)| p.is_online);
             let count = players.clone().count();
             let height = if count > 1 {
                 count as f64 - 1.0 + 20.0 * count as f64 - 1.0
@@ -392,10 +410,10 @@ impl<'a> Widget for Social<'a> {
                         } else if hide_username {
                             character.name.clone()
                         } else {
-                            format!("[{}] {}", alias, &character.name)
+                            format!("[{}] {}", alias, character.name)
                         }
                     },
-                    None => alias.clone(), // character select or spectating
+                    None => alias.to_string(), // character select or spectating
                 };
                 let level = match &player_info.character {
                     Some(character) => {
@@ -409,6 +427,9 @@ impl<'a> Widget for Social<'a> {
                 };
                 let zone_name = match &player_info.character {
                     None => self.localized_strings.get("hud.group.in_menu").to_string(), /* character select or spectating */
+                    _ if player_info.is_idle => {
+                        self.localized_strings.get("hud.social.idle").to_string()
+                    },
                     _ => format!("{} ", &zone),
                 };
                 // Player name widgets
@@ -591,6 +612,40 @@ impl<'a> Widget for Social<'a> {
                     });
                 }
             }
+
+            // Voice Mute Button, for other players selected in the list. Voices
+            // are muted by the real alias, not the one shown.
+            #[cfg(feature = "voice")]
+            {
+                let selected_alias = state
+                    .selected_uid
+                    .as_ref()
+                    .filter(|(uid, _)| self.client.uid() != Some(*uid))
+                    .and_then(|(uid, _)| self.client.player_list.get(uid))
+                    .map(|player| player.player_alias.clone());
+                if let Some(alias) = selected_alias.filter(|_| self.client.voice_chat_available()) {
+                    let label = if self.muted_voices.contains(&alias) {
+                        self.localized_strings.get("hud.social.unmute_voice")
+                    } else {
+                        self.localized_strings.get("hud.social.mute_voice")
+                    };
+                    if Button::image(self.imgs.button)
+                        .w_h(106.0, 26.0)
+                        .left_from(state.ids.invite_button, 5.0)
+                        .hover_image(self.imgs.button_hover)
+                        .press_image(self.imgs.button_press)
+                        .label(label)
+                        .label_y(conrod_core::position::Relative::Scalar(3.0))
+                        .label_color(TEXT_COLOR)
+                        .label_font_size(self.fonts.cyri.scale(15))
+                        .label_font_id(self.fonts.cyri.conrod_id)
+                        .set(state.ids.mute_button, ui)
+                        .was_clicked()
+                    {
+                        events.push(Event::ToggleVoiceMute(alias));
+                    }
+                }
+            }
         } // End of Online Tab
 
         events
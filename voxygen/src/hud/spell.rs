@@ -3,10 +3,12 @@ use crate::{i18n::VoxygenLocalization, ui::fonts::ConrodVoxygenFonts};
 use conrod_core::{
     color,
     widget::{self, Button, Image, Rectangle, Text},
-    widget_ids, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
+    widget_ids, Colorable, Labelable, Positionable, Sizeable, Widget, WidgetCommon,
 };
 
 use client::{self, Client};
+use common::comp::{AbilityHotbar, AbilityId, ItemConfig, Loadout, ABILITY_SLOTS};
+use specs::WorldExt;
 
 widget_ids! {
     pub struct Ids {
@@ -15,13 +17,16 @@ widget_ids! {
         spell_title,
         frame,
         content_align,
+        slot_names[],
+        slot_change_buttons[],
+        slot_hotbar_buttons[],
     }
 }
 
 #[derive(WidgetCommon)]
 pub struct Spell<'a> {
     _show: &'a Show,
-    _client: &'a Client,
+    client: &'a Client,
 
     imgs: &'a Imgs,
     fonts: &'a ConrodVoxygenFonts,
@@ -29,23 +34,26 @@ pub struct Spell<'a> {
 
     #[conrod(common_builder)]
     common: widget::CommonBuilder,
+    anchor_area: widget::Id,
 }
 
 impl<'a> Spell<'a> {
     pub fn new(
         show: &'a Show,
-        _client: &'a Client,
+        client: &'a Client,
         imgs: &'a Imgs,
         fonts: &'a ConrodVoxygenFonts,
         localized_strings: &'a std::sync::Arc<VoxygenLocalization>,
+        anchor_area: widget::Id,
     ) -> Self {
         Self {
             _show: show,
-            _client,
+            client,
             imgs,
             fonts,
             localized_strings,
             common: widget::CommonBuilder::default(),
+            anchor_area,
         }
     }
 }
@@ -56,6 +64,19 @@ impl<'a> Spell<'a> {
 
 pub enum Event {
     Close,
+    SetAbilitySlot(usize, Option<AbilityId>),
+    /// Link a slot of the ability hotbar to the item hotbar
+    AddToHotbar(usize),
+}
+
+/// The ability that comes after the current one of a slot: the skill at the
+/// position of the slot first, then every skill of the item in order
+fn next_ability(current: Option<&AbilityId>, item: &ItemConfig) -> Option<AbilityId> {
+    let mut skills = item.skill_ids();
+    match current {
+        None => skills.next(),
+        Some(current) => skills.skip_while(|id| id != current).nth(1),
+    }
 }
 
 impl<'a> Widget for Spell<'a> {
@@ -74,7 +95,7 @@ impl<'a> Widget for Spell<'a> {
         } = args;
 
         Image::new(self.imgs.window_3)
-            .top_left_with_margins_on(ui.window, 200.0, 25.0)
+            .top_left_with_margins_on(self.anchor_area, 200.0, 25.0)
             .w_h(103.0 * 4.0, 122.0 * 4.0)
             .color(Some(UI_MAIN))
             .set(state.spell_frame, ui);
@@ -106,9 +127,76 @@ impl<'a> Widget for Spell<'a> {
             .set(state.content_align, ui);
 
         // Contents
+        let ecs = self.client.state().ecs();
+        let loadouts = ecs.read_storage::<Loadout>();
+        let hotbars = ecs.read_storage::<AbilityHotbar>();
+        let item = match loadouts
+            .get(self.client.entity())
+            .and_then(|loadout| loadout.active_item.as_ref())
+        {
+            Some(item) => item,
+            None => return None,
+        };
+        let default_hotbar = AbilityHotbar::default();
+        let hotbar = hotbars.get(self.client.entity()).unwrap_or(&default_hotbar);
+
+        if state.slot_names.len() < ABILITY_SLOTS {
+            state.update(|s| {
+                let gen = &mut ui.widget_id_generator();
+                s.slot_names.resize(ABILITY_SLOTS, gen);
+                s.slot_change_buttons.resize(ABILITY_SLOTS, gen);
+                s.slot_hotbar_buttons.resize(ABILITY_SLOTS, gen);
+            });
+        }
+        let mut event = None;
+        // One row per slot, with the ability in it and buttons to change it and
+        // to put the slot on the hotbar
+        for i in 0..ABILITY_SLOTS {
+            let name = hotbar.ability(i, item).map_or_else(
+                || self.localized_strings.get("hud.spell.empty"),
+                |ability| ability.kind_name(),
+            );
+            Text::new(&format!("{}: {}", i + 1, name))
+                .top_left_with_margins_on(state.content_align, 10.0 + i as f64 * 40.0, 10.0)
+                .font_id(self.fonts.cyri.conrod_id)
+                .font_size(self.fonts.cyri.scale(14))
+                .color(TEXT_COLOR)
+                .set(state.slot_names[i], ui);
+            if Button::image(self.imgs.button)
+                .w_h(70.0, 22.0)
+                .top_right_with_margins_on(state.content_align, 6.0 + i as f64 * 40.0, 90.0)
+                .hover_image(self.imgs.button_hover)
+                .press_image(self.imgs.button_press)
+                .label(&self.localized_strings.get("hud.spell.change"))
+                .label_y(conrod_core::position::Relative::Scalar(1.0))
+                .label_color(TEXT_COLOR)
+                .label_font_size(self.fonts.cyri.scale(12))
+                .label_font_id(self.fonts.cyri.conrod_id)
+                .set(state.slot_change_buttons[i], ui)
+                .was_clicked()
+            {
+                event = Some(Event::SetAbilitySlot(
+                    i,
+                    next_ability(hotbar.slots[i].as_ref(), item),
+                ));
+            }
+            if Button::image(self.imgs.button)
+                .w_h(70.0, 22.0)
+                .top_right_with_margins_on(state.content_align, 6.0 + i as f64 * 40.0, 10.0)
+                .hover_image(self.imgs.button_hover)
+                .press_image(self.imgs.button_press)
+                .label(&self.localized_strings.get("hud.spell.add_to_hotbar"))
+                .label_y(conrod_core::position::Relative::Scalar(1.0))
+                .label_color(TEXT_COLOR)
+                .label_font_size(self.fonts.cyri.scale(12))
+                .label_font_id(self.fonts.cyri.conrod_id)
+                .set(state.slot_hotbar_buttons[i], ui)
+                .was_clicked()
+            {
+                event = Some(Event::AddToHotbar(i));
+            }
+        }
 
-        // Frame
-
-        None
+        event
     }
 }
@@ -24,9 +24,12 @@ pub fn item_text<'a>(item: &'a impl ItemDesc) -> (&'_ str, Cow<'a, str>) {
         ItemKind::Tool(tool) => Cow::Owned(tool_desc(&tool, item.description())),
         ItemKind::Glider(_glider) => Cow::Owned(glider_desc(item.description())),
         ItemKind::Consumable { .. } => Cow::Owned(consumable_desc(item.description())),
-        ItemKind::Throwable { .. } => Cow::Owned(throwable_desc(item.description())),
+        ItemKind::Throwable { .. } | ItemKind::Thrown { .. } => {
+            Cow::Owned(throwable_desc(item.description()))
+        },
         ItemKind::Utility { .. } => Cow::Owned(utility_desc(item.description())),
         ItemKind::Ingredient { .. } => Cow::Owned(ingredient_desc(item.description())),
+        ItemKind::Dye { .. } => Cow::Owned(dye_desc(item.description())),
         ItemKind::Lantern { .. } => Cow::Owned(lantern_desc(item.description())),
         //_ => Cow::Borrowed(item.description()),
     };
@@ -48,6 +51,8 @@ fn utility_desc(desc: &str) -> String { format!("{}\n\n<Right-Click to use>", de
 
 fn ingredient_desc(desc: &str) -> String { format!("Crafting Ingredient\n\n{}", desc) }
 
+fn dye_desc(desc: &str) -> String { format!("Dye\n\n{}\n\n<Drag onto armor to dye it>", desc) }
+
 fn lantern_desc(desc: &str) -> String { format!("Lantern\n\n{}\n\n<Right-Click to use>", desc) }
 
 // Armor Description
@@ -97,7 +102,7 @@ fn tool_desc(tool: &Tool, desc: &str) -> String {
         ToolKind::Farming(_) => "Farming Tool",
         ToolKind::Empty => "Empty",
     };
-    let power = tool.base_power();
+    let power = tool.power();
 
     if !desc.is_empty() {
         format!(
@@ -8,6 +8,8 @@
 #[macro_use]
 pub mod ui;
 pub mod audio;
+#[cfg(feature = "singleplayer")]
+pub mod benchmark;
 pub mod controller;
 mod ecs;
 pub mod error;
@@ -30,6 +32,8 @@ pub mod window;
 // Reexports
 pub use crate::error::Error;
 
+#[cfg(feature = "singleplayer")]
+use crate::benchmark::Benchmark;
 #[cfg(feature = "singleplayer")]
 use crate::singleplayer::Singleplayer;
 use crate::{
@@ -51,6 +55,8 @@ pub struct GlobalState {
     pub clock: Clock,
     #[cfg(feature = "singleplayer")]
     pub singleplayer: Option<Singleplayer>,
+    #[cfg(feature = "singleplayer")]
+    pub benchmark: Option<Benchmark>,
     // TODO: redo this so that the watcher doesn't have to exist for reloading to occur
     pub localization_watcher: watch::ReloadIndicator,
 }
@@ -65,6 +71,17 @@ impl GlobalState {
 
     pub fn maintain(&mut self, dt: f32) { self.audio.maintain(dt); }
 
+    /// The frame rate cap of the settings, or of the benchmark while one runs
+    pub fn max_fps(&self) -> u32 {
+        #[cfg(feature = "singleplayer")]
+        {
+            if let Some(benchmark) = &self.benchmark {
+                return benchmark.max_fps();
+            }
+        }
+        self.settings.graphics.max_fps
+    }
+
     #[cfg(feature = "singleplayer")]
     pub fn paused(&self) -> bool {
         self.singleplayer
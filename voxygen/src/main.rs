@@ -146,6 +146,7 @@ fn main() {
 
     audio.set_music_volume(settings.audio.music_volume);
     audio.set_sfx_volume(settings.audio.sfx_volume);
+    audio.set_voice_volume(settings.audio.voice_volume);
 
     // Load the profile.
     let profile = Profile::load();
@@ -183,6 +184,8 @@ fn main() {
         info_message: None,
         #[cfg(feature = "singleplayer")]
         singleplayer: None,
+        #[cfg(feature = "singleplayer")]
+        benchmark: None,
         localization_watcher,
     };
 
@@ -12,7 +12,7 @@ use crate::{
 use client::Client;
 use common::{
     assets::Asset,
-    character::{Character, CharacterId, CharacterItem, MAX_CHARACTERS_PER_PLAYER},
+    character::{Character, CharacterId, CharacterItem},
     comp::{self, humanoid},
     npc, LoadoutBuilder,
 };
@@ -72,6 +72,14 @@ widget_ids! {
         creating_character_text,
         deleting_character_text,
         character_error_message,
+        rules_bg,
+        rules_frame,
+        rules_title,
+        rules_align,
+        rules_text,
+        rules_scrollbar,
+        rules_accept,
+        rules_decline,
 
         //Alpha Disclaimer
         alpha_text,
@@ -82,6 +90,7 @@ widget_ids! {
         character_deletes[],
         character_names[],
         character_locations[],
+        character_weapons[],
         character_levels[],
 
         character_box_2,
@@ -384,9 +393,7 @@ impl CharSelectionUi {
                 loadout.active_item = tool.map(|tool| comp::ItemConfig {
                     // FIXME: Error gracefully.
                     item: comp::Item::new_from_asset_expect(tool),
-                    ability1: None,
-                    ability2: None,
-                    ability3: None,
+                    abilities: Vec::new(),
                     block_ability: None,
                     dodge_ability: None,
                 });
@@ -418,7 +425,7 @@ impl CharSelectionUi {
         let mut events = Vec::new();
 
         let can_enter_world = match &self.mode {
-            Mode::Select(opt) => opt.is_some(),
+            Mode::Select(opt) => opt.is_some() && client.server_rules().is_none(),
             Mode::Create { .. } => false,
         };
 
@@ -720,6 +727,71 @@ impl CharSelectionUi {
                 .mid_top_with_margin_on(ui_widgets.window, 2.0)
                 .set(self.ids.alpha_text, ui_widgets);
 
+                // Server rules, which have to be accepted before entering the world
+                let mut accept_rules = false;
+                if let Some(rules) = client.server_rules() {
+                    Rectangle::fill_with([600.0, 500.0], color::rgba(0.0, 0.0, 0.0, 0.9))
+                        .mid_top_with_margin_on(ui_widgets.window, 150.0)
+                        .set(self.ids.rules_bg, ui_widgets);
+                    Image::new(self.imgs.info_frame)
+                        .w_h(630.0, 500.0)
+                        .middle_of(self.ids.rules_bg)
+                        .color(Some(UI_MAIN))
+                        .set(self.ids.rules_frame, ui_widgets);
+                    Text::new(&self.voxygen_i18n.get("char_selection.server_rules"))
+                        .mid_top_with_margin_on(self.ids.rules_bg, 20.0)
+                        .font_size(self.fonts.cyri.scale(24))
+                        .font_id(self.fonts.cyri.conrod_id)
+                        .color(TEXT_COLOR)
+                        .set(self.ids.rules_title, ui_widgets);
+                    Rectangle::fill_with([540.0, 340.0], color::TRANSPARENT)
+                        .mid_top_with_margin_on(self.ids.rules_bg, 65.0)
+                        .scroll_kids_vertically()
+                        .set(self.ids.rules_align, ui_widgets);
+                    Text::new(rules)
+                        .top_left_of(self.ids.rules_align)
+                        .w(530.0)
+                        .font_size(self.fonts.cyri.scale(16))
+                        .font_id(self.fonts.cyri.conrod_id)
+                        .color(TEXT_COLOR)
+                        .set(self.ids.rules_text, ui_widgets);
+                    Scrollbar::y_axis(self.ids.rules_align)
+                        .thickness(5.0)
+                        .auto_hide(true)
+                        .color(UI_MAIN)
+                        .set(self.ids.rules_scrollbar, ui_widgets);
+                    if Button::image(self.imgs.button)
+                        .w_h(150.0, 40.0)
+                        .bottom_left_with_margins_on(self.ids.rules_bg, 25.0, 100.0)
+                        .hover_image(self.imgs.button_hover)
+                        .press_image(self.imgs.button_press)
+                        .label_y(Relative::Scalar(2.0))
+                        .label(&self.voxygen_i18n.get("common.decline"))
+                        .label_font_id(self.fonts.cyri.conrod_id)
+                        .label_font_size(self.fonts.cyri.scale(18))
+                        .label_color(TEXT_COLOR)
+                        .set(self.ids.rules_decline, ui_widgets)
+                        .was_clicked()
+                    {
+                        events.push(Event::Logout);
+                    }
+                    accept_rules = Button::image(self.imgs.button)
+                        .w_h(150.0, 40.0)
+                        .bottom_right_with_margins_on(self.ids.rules_bg, 25.0, 100.0)
+                        .hover_image(self.imgs.button_hover)
+                        .press_image(self.imgs.button_press)
+                        .label_y(Relative::Scalar(2.0))
+                        .label(&self.voxygen_i18n.get("common.accept"))
+                        .label_font_id(self.fonts.cyri.conrod_id)
+                        .label_font_size(self.fonts.cyri.scale(18))
+                        .label_color(TEXT_COLOR)
+                        .set(self.ids.rules_accept, ui_widgets)
+                        .was_clicked();
+                }
+                if accept_rules {
+                    client.accept_rules();
+                }
+
                 // Resize character selection widgets
                 self.ids
                     .character_boxes
@@ -736,6 +808,9 @@ impl CharSelectionUi {
                 self.ids
                     .character_locations
                     .resize(character_count, &mut ui_widgets.widget_id_generator());
+                self.ids
+                    .character_weapons
+                    .resize(character_count, &mut ui_widgets.widget_id_generator());
 
                 // Character selection
                 for (i, character_item) in client.character_list.characters.iter().enumerate() {
@@ -807,6 +882,16 @@ impl CharSelectionUi {
                         .font_id(self.fonts.cyri.conrod_id)
                         .color(TEXT_COLOR)
                         .set(self.ids.character_locations[i], ui_widgets);
+
+                    // Equipped weapon
+                    if let Some(item_config) = &character_item.loadout.active_item {
+                        Text::new(item_config.item.name())
+                            .bottom_right_with_margins_on(self.ids.character_boxes[i], 6.0, 9.0)
+                            .font_size(self.fonts.cyri.scale(17))
+                            .font_id(self.fonts.cyri.conrod_id)
+                            .color(TEXT_COLOR)
+                            .set(self.ids.character_weapons[i], ui_widgets);
+                    }
                 }
 
                 // Create Character Button
@@ -822,7 +907,8 @@ impl CharSelectionUi {
                     )
                 };
 
-                let character_limit_reached = character_count >= MAX_CHARACTERS_PER_PLAYER;
+                let character_slots = client.character_list.unlocks.character_slots();
+                let character_limit_reached = character_count >= character_slots;
 
                 let color = if character_limit_reached {
                     Color::Rgba(0.38, 0.38, 0.10, 1.0)
@@ -834,7 +920,12 @@ impl CharSelectionUi {
                     .w_h(386.0, 80.0)
                     .hover_image(self.imgs.selection_hover)
                     .press_image(self.imgs.selection_press)
-                    .label(&self.voxygen_i18n.get("char_selection.create_new_charater"))
+                    .label(&format!(
+                        "{} ({}/{})",
+                        self.voxygen_i18n.get("char_selection.create_new_charater"),
+                        character_count,
+                        character_slots
+                    ))
                     .label_color(color)
                     .label_font_id(self.fonts.cyri.conrod_id)
                     .image_color(color)
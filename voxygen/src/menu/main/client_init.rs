@@ -49,6 +49,7 @@ impl ClientInit {
         username: String,
         view_distance: Option<u32>,
         password: String,
+        keep_signed_in: bool,
     ) -> Self {
         let (server_address, default_port, prefer_ipv6) = connection_args;
 
@@ -79,7 +80,9 @@ impl ClientInit {
                         for socket_addr in
                             first_addrs.clone().into_iter().chain(second_addrs.clone())
                         {
-                            match Client::new(socket_addr, view_distance) {
+                            match Client::new(socket_addr, view_distance)
+                                .map(|client| client.with_session_refresh(keep_signed_in))
+                            {
                                 Ok(mut client) => {
                                     if let Err(e) =
                                         client.register(username, password, |auth_server| {
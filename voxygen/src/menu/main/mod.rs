@@ -1,4 +1,4 @@
-mod client_init;
+pub mod client_init;
 mod ui;
 
 use super::char_selection::CharSelectionState;
@@ -122,6 +122,9 @@ impl PlayState for MainMenuState {
                             client::Error::AlreadyLoggedIn => {
                                 localized_strings.get("main.login.already_logged_in").into()
                             },
+                            client::Error::AlreadyLoggedInAndInCombat => localized_strings
+                                .get("main.login.already_logged_in_combat")
+                                .into(),
                             client::Error::NotOnWhitelist => {
                                 localized_strings.get("main.login.not_on_whitelist").into()
                             },
@@ -303,6 +306,7 @@ fn attempt_login(
                 username,
                 Some(global_state.settings.graphics.view_distance),
                 password,
+                global_state.settings.networking.keep_signed_in,
             ));
         }
     } else {
@@ -669,6 +669,7 @@ impl<'a> MainMenuUi {
                     .w_h(400.0, 300.0)
                     .set(self.ids.servers_frame, ui_widgets);
 
+                let streamer_mode = global_state.settings.gameplay.streamer_mode;
                 let ref mut net_settings = global_state.settings.networking;
 
                 // TODO: Draw scroll bar or remove it.
@@ -687,7 +688,16 @@ impl<'a> MainMenuUi {
                     } else {
                         text.push_str("  ")
                     }
-                    text.push_str(&net_settings.servers[item.i]);
+                    if streamer_mode {
+                        text.push_str(
+                            &self
+                                .voxygen_i18n
+                                .get("main.hidden_server")
+                                .replace("{n}", &(item.i + 1).to_string()),
+                        );
+                    } else {
+                        text.push_str(&net_settings.servers[item.i]);
+                    }
 
                     if item
                         .set(
@@ -737,17 +747,22 @@ impl<'a> MainMenuUi {
                 .w_h(338.0 * scale, 50.0 * scale)
                 .middle_of(self.ids.srvr_bg)
                 .set(self.ids.address_bg, ui_widgets);
-            for event in TextBox::new(&self.server_address)
-                    .w_h(290.0*scale, 30.0*scale)
-                    .mid_top_with_margin_on(self.ids.address_bg, 8.0*scale)
-                    .font_size(self.fonts.cyri.scale(18))
-                    .font_id(self.fonts.cyri.conrod_id)
-                    .text_color(TEXT_COLOR)
-                    // transparent background
-                    .color(TRANSPARENT)
-                    .border_color(TRANSPARENT)
-                    .set(self.ids.address_field, ui_widgets)
-            {
+            let address_field = TextBox::new(&self.server_address)
+                .w_h(290.0 * scale, 30.0 * scale)
+                .mid_top_with_margin_on(self.ids.address_bg, 8.0 * scale)
+                .font_size(self.fonts.cyri.scale(18))
+                .font_id(self.fonts.cyri.conrod_id)
+                .text_color(TEXT_COLOR)
+                // transparent background
+                .color(TRANSPARENT)
+                .border_color(TRANSPARENT);
+            // Streamer mode keeps the address off screen like a password
+            let address_field = if global_state.settings.gameplay.streamer_mode {
+                address_field.hide_text("*")
+            } else {
+                address_field
+            };
+            for event in address_field.set(self.ids.address_field, ui_widgets) {
                 match event {
                     TextBoxEvent::Update(server_address) => {
                         self.server_address = server_address.to_string();
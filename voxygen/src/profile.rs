@@ -5,7 +5,11 @@ use serde::{Deserialize, Serialize};
 use std::{fs, io::Write, path::PathBuf};
 use tracing::warn;
 
-/// Represents a character in the profile.
+/// Represents a character in the profile. This is also what is stored as the
+/// character's UI preferences on servers that support it, so that it follows
+/// the player to other machines. Only the hotbar is kept for now: the HUD has
+/// no chat filters or map markers yet, which would go here once it does.
+/// Since the fields default, servers holding older preferences keep working.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
 pub struct CharacterProfile {
@@ -29,7 +29,7 @@ use std::{
     fs::File,
     io::{BufReader, Read},
 };
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 use vek::*;
 
 /// Represents the format of the pre-processed color target.
@@ -161,6 +161,10 @@ pub struct Renderer {
     player_shadow_pipeline: GfxPipeline<figure::pipe::Init<'static>>,
 
     shader_reload_indicator: ReloadIndicator,
+    /// The error produced by the most recent failed attempt to recreate the
+    /// pipelines after a shader file changed. While this is set, the last
+    /// successfully compiled pipelines remain in use.
+    shader_error: Option<String>,
 
     noise_tex: Texture<(gfx::format::R8, gfx::format::Unorm)>,
 
@@ -298,6 +302,7 @@ impl Renderer {
             player_shadow_pipeline,
 
             shader_reload_indicator,
+            shader_error: None,
 
             noise_tex,
 
@@ -773,11 +778,21 @@ impl Renderer {
                     shadow_map.terrain_directed_pipeline = terrain_directed_pipeline;
                     shadow_map.figure_directed_pipeline = figure_directed_pipeline;
                 }
+                if self.shader_error.take().is_some() {
+                    info!("Shaders recompiled successfully");
+                }
+            },
+            Err(e) => {
+                error!(?e, "Could not recreate shaders from assets due to an error",);
+                self.shader_error = Some(format!("{:?}", e));
             },
-            Err(e) => error!(?e, "Could not recreate shaders from assets due to an error",),
         }
     }
 
+    /// Get the error from the last failed shader reload, if the currently
+    /// bound pipelines are stale because the changed shaders did not compile.
+    pub fn shader_error(&self) -> Option<&str> { self.shader_error.as_deref() }
+
     /// Create a new set of constants with the provided values.
     pub fn create_consts<T: Copy + gfx::traits::Pod>(
         &mut self,
@@ -1,3 +1,5 @@
+#[cfg(feature = "singleplayer")]
+use crate::benchmark::{BenchmarkConfig, BenchmarkState};
 use crate::{
     menu::main::MainMenuState,
     ui,
@@ -10,7 +12,7 @@ use tracing::debug;
 
 pub fn run(mut global_state: GlobalState, event_loop: EventLoop) {
     // Set up the initial play state.
-    let mut states: Vec<Box<dyn PlayState>> = vec![Box::new(MainMenuState::new(&mut global_state))];
+    let mut states: Vec<Box<dyn PlayState>> = vec![initial_state(&mut global_state)];
     states.last_mut().map(|current_state| {
         current_state.enter(&mut global_state, Direction::Forwards);
         let current_state = current_state.name();
@@ -68,6 +70,19 @@ pub fn run(mut global_state: GlobalState, event_loop: EventLoop) {
     });
 }
 
+/// The main menu, or the benchmark if voxygen was started in benchmark mode
+fn initial_state(global_state: &mut GlobalState) -> Box<dyn PlayState> {
+    #[cfg(feature = "singleplayer")]
+    {
+        match BenchmarkConfig::from_env() {
+            Some(Ok(config)) => return Box::new(BenchmarkState::new(global_state, config)),
+            Some(Err(e)) => tracing::error!(?e, "Could not load the benchmark configuration"),
+            None => {},
+        }
+    }
+    Box::new(MainMenuState::new(global_state))
+}
+
 fn handle_main_events_cleared(
     states: &mut Vec<Box<dyn PlayState>>,
     control_flow: &mut winit::event_loop::ControlFlow,
@@ -144,7 +159,14 @@ fn handle_main_events_cleared(
     }
 
     drop(guard);
-    if let Some(last) = states.last_mut() {
+    #[cfg(feature = "singleplayer")]
+    let headless = global_state
+        .benchmark
+        .as_ref()
+        .map_or(false, |benchmark| benchmark.headless());
+    #[cfg(not(feature = "singleplayer"))]
+    let headless = false;
+    if let Some(last) = states.last_mut().filter(|_| !headless) {
         span!(guard, "Render");
         let renderer = global_state.window.renderer_mut();
         // Clear the shadow maps.
@@ -168,9 +190,9 @@ fn handle_main_events_cleared(
     if !exit {
         // Wait for the next tick.
         span!(_guard, "Main thread sleep");
-        global_state.clock.tick(Duration::from_millis(
-            1000 / global_state.settings.graphics.max_fps as u64,
-        ));
+        global_state
+            .clock
+            .tick(Duration::from_millis(1000 / global_state.max_fps() as u64));
 
         span!(_guard, "Maintain global state");
         // Maintain global state.
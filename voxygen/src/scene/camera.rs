@@ -1,4 +1,5 @@
-use common::{span, terrain::TerrainGrid, vol::ReadVol};
+use client::Client;
+use common::{span, vol::ReadVol};
 use std::f32::consts::PI;
 use treeculler::Frustum;
 use vek::*;
@@ -11,6 +12,14 @@ const THIRD_PERSON_INTERP_TIME: f32 = 0.1;
 const FREEFLY_INTERP_TIME: f32 = 0.0;
 const LERP_ORI_RATE: f32 = 15.0;
 pub const MIN_ZOOM: f32 = 0.1;
+/// Bounds of the vertical field of view, in degrees. Values outside of this
+/// produce degenerate or extremely distorted projections.
+pub const MIN_FOV_DEG: u16 = 30;
+pub const MAX_FOV_DEG: u16 = 120;
+/// How much screen shake decays per second
+const SHAKE_DECAY_RATE: f32 = 1.5;
+/// Largest angular offset applied by screen shake, in radians
+const MAX_SHAKE_ANGLE: f32 = 0.05;
 
 // Possible TODO: Add more modes
 #[derive(PartialEq, Clone, Copy, Eq, Hash)]
@@ -42,6 +51,8 @@ pub struct Camera {
     fov: f32,
     aspect: f32,
     mode: CameraMode,
+    /// Current screen shake in `0.0..=1.0`, decaying over time
+    shake: f32,
 
     last_time: Option<f64>,
 
@@ -62,6 +73,7 @@ impl Camera {
             fov: 1.1,
             aspect,
             mode,
+            shake: 0.0,
 
             last_time: None,
 
@@ -77,8 +89,14 @@ impl Camera {
 
     /// Compute the transformation matrices (view matrix and projection matrix)
     /// and position of the camera.
-    pub fn compute_dependents(&mut self, terrain: &TerrainGrid) {
-        self.compute_dependents_full(terrain, |block| !block.is_opaque())
+    pub fn compute_dependents(&mut self, client: &Client) {
+        span!(_guard, "compute_dependents", "Camera::compute_dependents");
+        let (start, end) = (self.focus - self.forward() * self.dist, self.focus);
+        let dist = match client.raycast_terrain_until(start, end, |block| !block.is_opaque()) {
+            Some(hit) => f32::min(self.dist - hit.distance - 0.03, self.dist),
+            None => self.dist,
+        };
+        self.compute_dependents_at(dist.max(0.0));
     }
 
     /// The is_fluid argument should return true for transparent voxels.
@@ -104,12 +122,17 @@ impl Camera {
             }
             .max(0.0)
         };
+        self.compute_dependents_at(dist);
+    }
 
+    /// Compute the matrices for the camera being `dist` away from its focus
+    fn compute_dependents_at(&mut self, dist: f32) {
+        let ori = self.ori + self.shake_offset();
         self.dependents.view_mat = Mat4::<f32>::identity()
             * Mat4::translation_3d(-Vec3::unit_z() * dist)
-            * Mat4::rotation_z(self.ori.z)
-            * Mat4::rotation_x(self.ori.y)
-            * Mat4::rotation_y(self.ori.x)
+            * Mat4::rotation_z(ori.z)
+            * Mat4::rotation_x(ori.y)
+            * Mat4::rotation_y(ori.x)
             * Mat4::rotation_3d(PI / 2.0, -Vec4::unit_x())
             * Mat4::translation_3d(-self.focus.map(|e| e.fract()));
 
@@ -204,6 +227,7 @@ impl Camera {
     pub fn update(&mut self, time: f64, dt: f32, smoothing_enabled: bool) {
         // This is horribly frame time dependent, but so is most of the game
         let delta = self.last_time.replace(time).map_or(0.0, |t| time - t);
+        self.shake = (self.shake - SHAKE_DECAY_RATE * delta as f32).max(0.0);
         if (self.dist - self.tgt_dist).abs() > 0.01 {
             self.dist = f32::lerp(
                 self.dist,
@@ -281,10 +305,31 @@ impl Camera {
     /// Set the field of view of the camera in radians.
     pub fn set_fov(&mut self, fov: f32) { self.fov = fov; }
 
-    /// Set the FOV in degrees
+    /// Set the vertical FOV in degrees, clamped to a usable range.
+    ///
+    /// The horizontal FOV follows from the aspect ratio, so wider screens see
+    /// more to the sides rather than less above and below.
     pub fn set_fov_deg(&mut self, fov: u16) {
-        //Magic value comes from pi/180; no use recalculating.
-        self.set_fov((fov as f32) * 0.01745329)
+        self.set_fov((fov.max(MIN_FOV_DEG).min(MAX_FOV_DEG) as f32).to_radians())
+    }
+
+    /// Shake the camera, e.g. in response to a nearby explosion. Shake
+    /// accumulates up to a maximum and decays over time.
+    pub fn add_shake(&mut self, amount: f32) { self.shake = (self.shake + amount).min(1.0); }
+
+    /// The orientation offset currently caused by screen shake.
+    fn shake_offset(&self) -> Vec3<f32> {
+        if self.shake <= 0.0 {
+            return Vec3::zero();
+        }
+        let t = self.last_time.unwrap_or(0.0) as f32;
+        // Squaring makes small amounts of shake subtle while large amounts stay violent
+        let angle = self.shake.powi(2) * MAX_SHAKE_ANGLE;
+        Vec3::new(
+            (t * 37.0).sin() + (t * 23.0).cos() * 0.5,
+            (t * 31.0).cos() + (t * 19.0).sin() * 0.5,
+            (t * 29.0).sin() * 0.5,
+        ) * angle
     }
 
     /// Set the mode of the camera.
@@ -13,8 +13,9 @@ use common::{
         item::{
             armor::{Armor, ArmorKind},
             tool::ToolKind,
-            ItemKind,
+            Item, ItemKind,
         },
+        slot::ArmorSlot,
         CharacterState, Loadout,
     },
     figure::Segment,
@@ -73,14 +74,30 @@ pub(super) struct CharacterToolKey {
     pub second: Option<ToolKind>,
 }
 
+/// A piece of armor, and the color it was dyed in.
+#[derive(Eq, Hash, PartialEq)]
+pub(super) struct ArmorKey {
+    pub spec: String,
+    pub dye: Option<Rgb<u8>>,
+}
+
+impl ArmorKey {
+    fn new(spec: &str, item: Option<&Item>) -> Self {
+        Self {
+            spec: spec.to_owned(),
+            dye: item.and_then(Item::dye),
+        }
+    }
+}
+
 /// Character data that exists in third person only.
 #[derive(Eq, Hash, PartialEq)]
 pub(super) struct CharacterThirdPersonKey {
-    pub shoulder: Option<String>,
-    pub chest: Option<String>,
-    pub belt: Option<String>,
-    pub back: Option<String>,
-    pub pants: Option<String>,
+    pub shoulder: Option<ArmorKey>,
+    pub chest: Option<ArmorKey>,
+    pub belt: Option<ArmorKey>,
+    pub back: Option<ArmorKey>,
+    pub pants: Option<ArmorKey>,
 }
 
 #[derive(Eq, Hash, PartialEq)]
@@ -102,8 +119,8 @@ pub(super) struct CharacterCacheKey {
     pub tool: Option<CharacterToolKey>,
     pub lantern: Option<String>,
     pub glider: Option<String>,
-    pub hand: Option<String>,
-    pub foot: Option<String>,
+    pub hand: Option<ArmorKey>,
+    pub foot: Option<ArmorKey>,
 }
 
 impl CharacterCacheKey {
@@ -134,45 +151,48 @@ impl CharacterCacheKey {
                     shoulder: if let Some(ItemKind::Armor(Armor {
                         kind: ArmorKind::Shoulder(armor),
                         ..
-                    })) = loadout.shoulder.as_ref().map(|i| i.kind())
+                    })) = loadout.shown_armor(ArmorSlot::Shoulders).map(|i| i.kind())
                     {
-                        Some(armor.clone())
+                        Some(ArmorKey::new(
+                            armor,
+                            loadout.shown_armor(ArmorSlot::Shoulders),
+                        ))
                     } else {
                         None
                     },
                     chest: if let Some(ItemKind::Armor(Armor {
                         kind: ArmorKind::Chest(armor),
                         ..
-                    })) = loadout.chest.as_ref().map(|i| i.kind())
+                    })) = loadout.shown_armor(ArmorSlot::Chest).map(|i| i.kind())
                     {
-                        Some(armor.clone())
+                        Some(ArmorKey::new(armor, loadout.shown_armor(ArmorSlot::Chest)))
                     } else {
                         None
                     },
                     belt: if let Some(ItemKind::Armor(Armor {
                         kind: ArmorKind::Belt(armor),
                         ..
-                    })) = loadout.belt.as_ref().map(|i| i.kind())
+                    })) = loadout.shown_armor(ArmorSlot::Belt).map(|i| i.kind())
                     {
-                        Some(armor.clone())
+                        Some(ArmorKey::new(armor, loadout.shown_armor(ArmorSlot::Belt)))
                     } else {
                         None
                     },
                     back: if let Some(ItemKind::Armor(Armor {
                         kind: ArmorKind::Back(armor),
                         ..
-                    })) = loadout.back.as_ref().map(|i| i.kind())
+                    })) = loadout.shown_armor(ArmorSlot::Back).map(|i| i.kind())
                     {
-                        Some(armor.clone())
+                        Some(ArmorKey::new(armor, loadout.shown_armor(ArmorSlot::Back)))
                     } else {
                         None
                     },
                     pants: if let Some(ItemKind::Armor(Armor {
                         kind: ArmorKind::Pants(armor),
                         ..
-                    })) = loadout.pants.as_ref().map(|i| i.kind())
+                    })) = loadout.shown_armor(ArmorSlot::Legs).map(|i| i.kind())
                     {
-                        Some(armor.clone())
+                        Some(ArmorKey::new(armor, loadout.shown_armor(ArmorSlot::Legs)))
                     } else {
                         None
                     },
@@ -215,18 +235,18 @@ impl CharacterCacheKey {
             hand: if let Some(ItemKind::Armor(Armor {
                 kind: ArmorKind::Hand(armor),
                 ..
-            })) = loadout.hand.as_ref().map(|i| i.kind())
+            })) = loadout.shown_armor(ArmorSlot::Hands).map(|i| i.kind())
             {
-                Some(armor.clone())
+                Some(ArmorKey::new(armor, loadout.shown_armor(ArmorSlot::Hands)))
             } else {
                 None
             },
             foot: if let Some(ItemKind::Armor(Armor {
                 kind: ArmorKind::Foot(armor),
                 ..
-            })) = loadout.foot.as_ref().map(|i| i.kind())
+            })) = loadout.shown_armor(ArmorSlot::Feet).map(|i| i.kind())
             {
-                Some(armor.clone())
+                Some(ArmorKey::new(armor, loadout.shown_armor(ArmorSlot::Feet)))
             } else {
                 None
             },
@@ -72,6 +72,13 @@ fn recolor_grey(rgb: Rgb<u8>, color: Rgb<u8>) -> Rgb<u8> {
     }
 }
 
+/// Recolor a voxel of dyed armor. Only the brightness of the voxel is kept, so
+/// that the whole piece of armor takes on the color of the dye.
+fn dye_voxel(rgb: Rgb<u8>, dye: Rgb<u8>) -> Rgb<u8> {
+    let luma = (rgb.r as f32 * 0.299 + rgb.g as f32 * 0.587 + rgb.b as f32 * 0.114) as u8;
+    recolor_grey(Rgb::broadcast(luma), dye)
+}
+
 /// A set of reloadable specifications for a Body.
 pub trait BodySpec: Sized {
     type Spec;
@@ -372,8 +379,8 @@ make_vox_spec!(
         let tool = loadout.tool.as_ref();
         let lantern = loadout.lantern.as_deref();
         let glider = loadout.glider.as_deref();
-        let hand = loadout.hand.as_deref();
-        let foot = loadout.foot.as_deref();
+        let hand = loadout.hand.as_ref();
+        let foot = loadout.foot.as_ref();
 
         [
             third_person.map(|_| {
@@ -386,62 +393,72 @@ make_vox_spec!(
                 spec.armor_chest.asset.mesh_chest(
                     body,
                     &spec.color.asset,
-                    loadout.chest.as_deref(),
+                    loadout.chest.as_ref().map(|armor| armor.spec.as_str()),
+                    loadout.chest.as_ref().and_then(|armor| armor.dye),
                 )
             }),
             third_person.map(|loadout| {
                 spec.armor_belt.asset.mesh_belt(
                     body,
                     &spec.color.asset,
-                    loadout.belt.as_deref(),
+                    loadout.belt.as_ref().map(|armor| armor.spec.as_str()),
+                    loadout.belt.as_ref().and_then(|armor| armor.dye),
                 )
             }),
             third_person.map(|loadout| {
                 spec.armor_back.asset.mesh_back(
                     body,
                     &spec.color.asset,
-                    loadout.back.as_deref(),
+                    loadout.back.as_ref().map(|armor| armor.spec.as_str()),
+                    loadout.back.as_ref().and_then(|armor| armor.dye),
                 )
             }),
             third_person.map(|loadout| {
                 spec.armor_pants.asset.mesh_pants(
                     body,
                     &spec.color.asset,
-                    loadout.pants.as_deref(),
+                    loadout.pants.as_ref().map(|armor| armor.spec.as_str()),
+                    loadout.pants.as_ref().and_then(|armor| armor.dye),
                 )
             }),
             Some(spec.armor_hand.asset.mesh_left_hand(
                 body,
                 &spec.color.asset,
-                hand,
+                hand.map(|armor| armor.spec.as_str()),
+                hand.and_then(|armor| armor.dye),
             )),
             Some(spec.armor_hand.asset.mesh_right_hand(
                 body,
                 &spec.color.asset,
-                hand,
+                hand.map(|armor| armor.spec.as_str()),
+                hand.and_then(|armor| armor.dye),
             )),
             Some(spec.armor_foot.asset.mesh_left_foot(
                 body,
                 &spec.color.asset,
-                foot,
+                foot.map(|armor| armor.spec.as_str()),
+                foot.and_then(|armor| armor.dye),
             )),
             Some(spec.armor_foot.asset.mesh_right_foot(
                 body,
                 &spec.color.asset,
-                foot,
+                foot.map(|armor| armor.spec.as_str()),
+                foot.and_then(|armor| armor.dye),
             )),
             third_person.map(|loadout| {
                 spec.armor_shoulder.asset.mesh_left_shoulder(
                     body,
                     &spec.color.asset,
-                    loadout.shoulder.as_deref(),
+                    loadout.shoulder.as_ref().map(|armor| armor.spec.as_str()),
+                    loadout.shoulder.as_ref().and_then(|armor| armor.dye),
                 )
             }),
             third_person.map(|loadout| {
                 spec.armor_shoulder.asset.mesh_right_shoulder(
                     body,
                     &spec.color.asset,
-                    loadout.shoulder.as_deref(),
+                    loadout.shoulder.as_ref().map(|armor| armor.spec.as_str()),
+                    loadout.shoulder.as_ref().and_then(|armor| armor.dye),
                 )
             }),
             Some(spec.armor_glider.asset.mesh_glider(
@@ -478,6 +495,7 @@ impl HumArmorShoulderSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         shoulder: Option<&str>,
+        dye: Option<Rgb<u8>>,
         flipped: bool,
     ) -> BoneMeshes {
         let spec = if let Some(shoulder) = shoulder {
@@ -514,7 +532,9 @@ impl HumArmorShoulderSpec {
             spec.right.vox_spec.1
         };
 
-        if let Some(color) = if flipped {
+        if let Some(dye) = dye {
+            shoulder_segment = shoulder_segment.map_rgb(|rgb| dye_voxel(rgb, dye));
+        } else if let Some(color) = if flipped {
             spec.left.color
         } else {
             spec.right.color
@@ -532,8 +552,9 @@ impl HumArmorShoulderSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         shoulder: Option<&str>,
+        dye: Option<Rgb<u8>>,
     ) -> BoneMeshes {
-        self.mesh_shoulder(body, color_spec, shoulder, true)
+        self.mesh_shoulder(body, color_spec, shoulder, dye, true)
     }
 
     fn mesh_right_shoulder(
@@ -541,8 +562,9 @@ impl HumArmorShoulderSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         shoulder: Option<&str>,
+        dye: Option<Rgb<u8>>,
     ) -> BoneMeshes {
-        self.mesh_shoulder(body, color_spec, shoulder, false)
+        self.mesh_shoulder(body, color_spec, shoulder, dye, false)
     }
 }
 // Chest
@@ -552,6 +574,7 @@ impl HumArmorChestSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         chest: Option<&str>,
+        dye: Option<Rgb<u8>>,
     ) -> BoneMeshes {
         let spec = if let Some(chest) = chest {
             match self.0.map.get(chest) {
@@ -578,7 +601,9 @@ impl HumArmorChestSpec {
 
         let mut chest_armor = graceful_load_mat_segment(&spec.vox_spec.0);
 
-        if let Some(color) = spec.color {
+        if let Some(dye) = dye {
+            chest_armor = chest_armor.map_rgb(|rgb| dye_voxel(rgb, dye));
+        } else if let Some(color) = spec.color {
             let chest_color = Vec3::from(color);
             chest_armor = chest_armor.map_rgb(|rgb| recolor_grey(rgb, Rgb::from(chest_color)));
         }
@@ -599,6 +624,7 @@ impl HumArmorHandSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         hand: Option<&str>,
+        dye: Option<Rgb<u8>>,
         flipped: bool,
     ) -> BoneMeshes {
         let spec = if let Some(hand) = hand {
@@ -630,7 +656,9 @@ impl HumArmorHandSpec {
             spec.right.vox_spec.1
         };
 
-        if let Some(color) = if flipped {
+        if let Some(dye) = dye {
+            hand_segment = hand_segment.map_rgb(|rgb| dye_voxel(rgb, dye));
+        } else if let Some(color) = if flipped {
             spec.left.color
         } else {
             spec.right.color
@@ -647,8 +675,9 @@ impl HumArmorHandSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         hand: Option<&str>,
+        dye: Option<Rgb<u8>>,
     ) -> BoneMeshes {
-        self.mesh_hand(body, color_spec, hand, true)
+        self.mesh_hand(body, color_spec, hand, dye, true)
     }
 
     fn mesh_right_hand(
@@ -656,13 +685,20 @@ impl HumArmorHandSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         hand: Option<&str>,
+        dye: Option<Rgb<u8>>,
     ) -> BoneMeshes {
-        self.mesh_hand(body, color_spec, hand, false)
+        self.mesh_hand(body, color_spec, hand, dye, false)
     }
 }
 // Belt
 impl HumArmorBeltSpec {
-    fn mesh_belt(&self, body: &Body, color_spec: &HumColorSpec, belt: Option<&str>) -> BoneMeshes {
+    fn mesh_belt(
+        &self,
+        body: &Body,
+        color_spec: &HumColorSpec,
+        belt: Option<&str>,
+        dye: Option<Rgb<u8>>,
+    ) -> BoneMeshes {
         let spec = if let Some(belt) = belt {
             match self.0.map.get(belt) {
                 Some(spec) => spec,
@@ -682,7 +718,9 @@ impl HumArmorBeltSpec {
             body.species.eye_color(body.eye_color),
         );
 
-        if let Some(color) = spec.color {
+        if let Some(dye) = dye {
+            belt_segment = belt_segment.map_rgb(|rgb| dye_voxel(rgb, dye));
+        } else if let Some(color) = spec.color {
             let belt_color = Vec3::from(color);
             belt_segment = belt_segment.map_rgb(|rgb| recolor_grey(rgb, Rgb::from(belt_color)));
         }
@@ -692,7 +730,13 @@ impl HumArmorBeltSpec {
 }
 // Cape
 impl HumArmorBackSpec {
-    fn mesh_back(&self, body: &Body, color_spec: &HumColorSpec, back: Option<&str>) -> BoneMeshes {
+    fn mesh_back(
+        &self,
+        body: &Body,
+        color_spec: &HumColorSpec,
+        back: Option<&str>,
+        dye: Option<Rgb<u8>>,
+    ) -> BoneMeshes {
         let spec = if let Some(back) = back {
             match self.0.map.get(back) {
                 Some(spec) => spec,
@@ -711,7 +755,9 @@ impl HumArmorBackSpec {
             color_spec.hair_color(body.species, body.hair_color),
             body.species.eye_color(body.eye_color),
         );
-        if let Some(color) = spec.color {
+        if let Some(dye) = dye {
+            back_segment = back_segment.map_rgb(|rgb| dye_voxel(rgb, dye));
+        } else if let Some(color) = spec.color {
             let back_color = Vec3::from(color);
             back_segment = back_segment.map_rgb(|rgb| recolor_grey(rgb, Rgb::from(back_color)));
         }
@@ -726,6 +772,7 @@ impl HumArmorPantsSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         pants: Option<&str>,
+        dye: Option<Rgb<u8>>,
     ) -> BoneMeshes {
         let spec = if let Some(pants) = pants {
             match self.0.map.get(pants) {
@@ -752,7 +799,9 @@ impl HumArmorPantsSpec {
 
         let mut pants_armor = graceful_load_mat_segment(&spec.vox_spec.0);
 
-        if let Some(color) = spec.color {
+        if let Some(dye) = dye {
+            pants_armor = pants_armor.map_rgb(|rgb| dye_voxel(rgb, dye));
+        } else if let Some(color) = spec.color {
             let pants_color = Vec3::from(color);
             pants_armor = pants_armor.map_rgb(|rgb| recolor_grey(rgb, Rgb::from(pants_color)));
         }
@@ -773,6 +822,7 @@ impl HumArmorFootSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         foot: Option<&str>,
+        dye: Option<Rgb<u8>>,
         flipped: bool,
     ) -> BoneMeshes {
         let spec = if let Some(foot) = foot {
@@ -798,7 +848,9 @@ impl HumArmorFootSpec {
             body.species.eye_color(body.eye_color),
         );
 
-        if let Some(color) = spec.color {
+        if let Some(dye) = dye {
+            foot_segment = foot_segment.map_rgb(|rgb| dye_voxel(rgb, dye));
+        } else if let Some(color) = spec.color {
             let foot_color = Vec3::from(color);
             foot_segment = foot_segment.map_rgb(|rgb| recolor_grey(rgb, Rgb::from(foot_color)));
         }
@@ -811,8 +863,9 @@ impl HumArmorFootSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         foot: Option<&str>,
+        dye: Option<Rgb<u8>>,
     ) -> BoneMeshes {
-        self.mesh_foot(body, color_spec, foot, true)
+        self.mesh_foot(body, color_spec, foot, dye, true)
     }
 
     fn mesh_right_foot(
@@ -820,8 +873,9 @@ impl HumArmorFootSpec {
         body: &Body,
         color_spec: &HumColorSpec,
         foot: Option<&str>,
+        dye: Option<Rgb<u8>>,
     ) -> BoneMeshes {
-        self.mesh_foot(body, color_spec, foot, false)
+        self.mesh_foot(body, color_spec, foot, dye, false)
     }
 }
 
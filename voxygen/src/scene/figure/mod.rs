@@ -50,6 +50,8 @@ use vek::*;
 const DAMAGE_FADE_COEFFICIENT: f64 = 5.0;
 const MOVING_THRESHOLD: f32 = 0.7;
 const MOVING_THRESHOLD_SQR: f32 = MOVING_THRESHOLD * MOVING_THRESHOLD;
+// Fraction of the collapse animation of a dead figure played per second
+const COLLAPSE_RATE: f32 = 1.5;
 
 /// camera data, figure LOD render distance.
 pub type CameraData<'a> = (&'a Camera, f32);
@@ -643,6 +645,7 @@ impl FigureMgr {
                     .coherent_test_against_frustum(frustum, meta.lpindex);
                 meta.visible = in_frustum;
                 meta.lpindex = lpindex;
+                meta.is_dead = stats.map_or(false, |s| s.is_dead);
                 if in_frustum {
                     /* // Update visible bounds.
                     visible_aabb.expand_to_contain(Aabb {
@@ -1186,6 +1189,24 @@ impl FigureMgr {
                                 ),
                             }
                         },
+                        CharacterState::Flinch(_) => {
+                            anim::character::StaggerAnimation::update_skeleton(
+                                &target_base,
+                                (0.4, time),
+                                state.state_time,
+                                &mut state_animation_rate,
+                                skeleton_attr,
+                            )
+                        },
+                        CharacterState::Stagger(_) => {
+                            anim::character::StaggerAnimation::update_skeleton(
+                                &target_base,
+                                (1.0, time),
+                                state.state_time,
+                                &mut state_animation_rate,
+                                skeleton_attr,
+                            )
+                        },
                         CharacterState::BasicBlock { .. } => {
                             anim::character::BlockIdleAnimation::update_skeleton(
                                 &CharacterSkeleton::default(),
@@ -1214,7 +1235,7 @@ impl FigureMgr {
                                 skeleton_attr,
                             )
                         },
-                        CharacterState::Wielding { .. } => {
+                        CharacterState::Wielding { .. } | CharacterState::Fishing(_) => {
                             if physics.in_fluid.is_some() {
                                 anim::character::SwimWieldAnimation::update_skeleton(
                                     &target_base,
@@ -2741,31 +2762,29 @@ impl FigureMgr {
                 ecs.read_storage::<Loadout>().maybe(),
                 ecs.read_storage::<Scale>().maybe(),
             )
-            .join()
-            // Don't render dead entities
-            .filter(|(_, _, _, _, stats, _, _)| stats.map_or(true, |s| !s.is_dead))
-            .for_each(|(entity, pos, _, body, _, loadout, _)| {
-                if let Some((locals, bone_consts, model, _)) = self.get_model_for_render(
-                    tick,
-                    camera,
-                    None,
-                    entity,
-                    body,
-                    loadout,
-                    false,
-                    pos.0,
-                    figure_lod_render_distance,
-                    |state| state.can_shadow_sun(),
-                ) {
-                    renderer.render_figure_shadow_directed(
-                        model,
-                        global,
-                        locals,
-                        bone_consts,
-                        &global.shadow_mats,
-                    );
-                }
-            });
+                .join()
+                .for_each(|(entity, pos, _, body, _, loadout, _)| {
+                    if let Some((locals, bone_consts, model, _)) = self.get_model_for_render(
+                        tick,
+                        camera,
+                        None,
+                        entity,
+                        body,
+                        loadout,
+                        false,
+                        pos.0,
+                        figure_lod_render_distance,
+                        |state| state.can_shadow_sun(),
+                    ) {
+                        renderer.render_figure_shadow_directed(
+                            model,
+                            global,
+                            locals,
+                            bone_consts,
+                            &global.shadow_mats,
+                        );
+                    }
+                });
         }
     }
 
@@ -2796,8 +2815,6 @@ impl FigureMgr {
             ecs.read_storage::<Scale>().maybe(),
         )
             .join()
-        // Don't render dead entities
-        .filter(|(_, _, _, _, stats, _, _)| stats.map_or(true, |s| !s.is_dead))
         {
             let is_player = entity == player_entity;
 
@@ -2841,13 +2858,6 @@ impl FigureMgr {
             ecs.read_storage::<Pos>().get(player_entity),
             ecs.read_storage::<Body>().get(player_entity),
         ) {
-            let stats_storage = state.read_storage::<Stats>();
-            let stats = stats_storage.get(player_entity);
-
-            if stats.map_or(false, |s| s.is_dead) {
-                return;
-            }
-
             let loadout_storage = ecs.read_storage::<Loadout>();
             let loadout = loadout_storage.get(player_entity);
 
@@ -3293,6 +3303,10 @@ pub struct FigureStateMeta {
     visible: bool,
     last_pos: Option<anim::vek::Vec3<f32>>,
     avg_vel: anim::vek::Vec3<f32>,
+    is_dead: bool,
+    /// How far the figure has collapsed after dying, from 0 (standing) to 1
+    /// (lying on the ground)
+    collapse: f32,
 }
 
 impl FigureStateMeta {
@@ -3337,6 +3351,8 @@ impl<S: Skeleton> FigureState<S> {
                 can_shadow_sun: false,
                 last_pos: None,
                 avg_vel: anim::vek::Vec3::zero(),
+                is_dead: false,
+                collapse: 0.0,
             },
             skeleton,
         }
@@ -3389,8 +3405,19 @@ impl<S: Skeleton> FigureState<S> {
 
         self.state_time += (dt * state_animation_rate) as f64;
 
+        // Dead figures topple over onto their side, standing back up at once when
+        // they respawn
+        self.collapse = if self.is_dead {
+            (self.collapse + dt * COLLAPSE_RATE).min(1.0)
+        } else {
+            0.0
+        };
+        // Ease in, so the body picks up speed as it falls
+        let collapse_angle = self.collapse.powi(2) * std::f32::consts::FRAC_PI_2;
+
         let mat = anim::vek::Mat4::rotation_z(-ori.x.atan2(ori.y))
             * anim::vek::Mat4::rotation_x(ori.z.atan2(anim::vek::Vec2::from(ori).magnitude()))
+            * anim::vek::Mat4::rotation_y(collapse_angle)
             * anim::vek::Mat4::scaling_3d(anim::vek::Vec3::from(0.8 * scale));
 
         let atlas_offs = model.allocation.rectangle.min;
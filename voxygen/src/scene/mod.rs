@@ -14,7 +14,7 @@ pub use self::{
     terrain::Terrain,
 };
 use crate::{
-    audio::{music::MusicMgr, sfx::SfxMgr, AudioFrontend},
+    audio::{music::MusicMgr, sfx::SfxMgr, stinger::StingerMgr, AudioFrontend},
     render::{
         create_pp_mesh, create_skybox_mesh, Consts, GlobalModel, Globals, Light, LodData, Model,
         PostProcessLocals, PostProcessPipeline, Renderer, Shadow, ShadowLocals, SkyboxLocals,
@@ -25,6 +25,7 @@ use crate::{
 };
 use client::Client;
 use common::{
+    combat::CombatEvent,
     comp,
     comp::humanoid::DEFAULT_HUMANOID_EYE_HEIGHT,
     outcome::Outcome,
@@ -56,6 +57,8 @@ const SHADOW_FAR: f32 = 128.0; // Far plane for shadow map point light rendering
 /// Above this speed is considered running
 /// Used for first person camera effects
 const RUNNING_THRESHOLD: f32 = 0.7;
+/// Explosions shake the camera up to this many times their radius away
+const EXPLOSION_SHAKE_RANGE_FACTOR: f32 = 3.0;
 
 /// is_daylight, array of active lights.
 pub type LightData<'a> = (bool, &'a [Light]);
@@ -98,9 +101,11 @@ pub struct Scene {
     figure_mgr: FigureMgr,
     sfx_mgr: SfxMgr,
     music_mgr: MusicMgr,
+    stinger_mgr: StingerMgr,
 }
 
 pub struct SceneData<'a> {
+    pub client: &'a Client,
     pub state: &'a State,
     pub player_entity: specs::Entity,
     pub target_entity: Option<specs::Entity>,
@@ -115,6 +120,8 @@ pub struct SceneData<'a> {
     pub particles_enabled: bool,
     pub figure_lod_render_distance: f32,
     pub is_aiming: bool,
+    pub head_bob_intensity: f32,
+    pub screen_shake_intensity: f32,
 }
 
 impl<'a> SceneData<'a> {
@@ -299,6 +306,7 @@ impl Scene {
             figure_mgr: FigureMgr::new(renderer),
             sfx_mgr: SfxMgr::new(),
             music_mgr: MusicMgr::new(),
+            stinger_mgr: StingerMgr::new(),
         }
     }
 
@@ -375,6 +383,14 @@ impl Scene {
         }
     }
 
+    pub fn handle_sound(&mut self, sound: &comp::Sound, audio: &mut AudioFrontend) {
+        self.sfx_mgr.handle_sound(sound, audio);
+    }
+
+    pub fn handle_combat_event(&mut self, event: &CombatEvent, scene_data: &SceneData) {
+        self.stinger_mgr.handle_combat_event(event, scene_data);
+    }
+
     pub fn handle_outcome(
         &mut self,
         outcome: &Outcome,
@@ -389,38 +405,52 @@ impl Scene {
             Outcome::Explosion {
                 pos,
                 power,
-                radius: _,
+                radius,
                 is_attack: _,
                 reagent,
-            } => self.event_lights.push(EventLight {
-                light: Light::new(
-                    *pos,
-                    match reagent {
-                        Some(Reagent::Blue) => Rgb::new(0.15, 0.4, 1.0),
-                        Some(Reagent::Green) => Rgb::new(0.0, 1.0, 0.0),
-                        Some(Reagent::Purple) => Rgb::new(0.7, 0.0, 1.0),
-                        Some(Reagent::Red) => Rgb::new(1.0, 0.0, 0.0),
-                        Some(Reagent::Yellow) => Rgb::new(1.0, 1.0, 0.0),
-                        None => {
-                            if *power < 0.0 {
-                                Rgb::new(0.0, 1.0, 0.0)
-                            } else {
-                                Rgb::new(1.0, 0.5, 0.0)
-                            }
+            } => {
+                // Shake the camera if the player is close enough to feel the blast
+                if *power > 0.0 && scene_data.screen_shake_intensity > 0.0 {
+                    if let Some(player_pos) = scene_data
+                        .state
+                        .read_component_copied::<comp::Pos>(scene_data.player_entity)
+                    {
+                        let shake_range = radius * EXPLOSION_SHAKE_RANGE_FACTOR;
+                        let falloff = (1.0 - player_pos.0.distance(*pos) / shake_range).max(0.0);
+                        self.camera
+                            .add_shake(falloff * scene_data.screen_shake_intensity);
+                    }
+                }
+                self.event_lights.push(EventLight {
+                    light: Light::new(
+                        *pos,
+                        match reagent {
+                            Some(Reagent::Blue) => Rgb::new(0.15, 0.4, 1.0),
+                            Some(Reagent::Green) => Rgb::new(0.0, 1.0, 0.0),
+                            Some(Reagent::Purple) => Rgb::new(0.7, 0.0, 1.0),
+                            Some(Reagent::Red) => Rgb::new(1.0, 0.0, 0.0),
+                            Some(Reagent::Yellow) => Rgb::new(1.0, 1.0, 0.0),
+                            None => {
+                                if *power < 0.0 {
+                                    Rgb::new(0.0, 1.0, 0.0)
+                                } else {
+                                    Rgb::new(1.0, 0.5, 0.0)
+                                }
+                            },
                         },
+                        power.abs()
+                            * match reagent {
+                                Some(_) => 5.0,
+                                None => 2.5,
+                            },
+                    ),
+                    timeout: match reagent {
+                        Some(_) => 1.0,
+                        None => 0.5,
                     },
-                    power.abs()
-                        * match reagent {
-                            Some(_) => 5.0,
-                            None => 2.5,
-                        },
-                ),
-                timeout: match reagent {
-                    Some(_) => 1.0,
-                    None => 0.5,
-                },
-                fadeout: |timeout| timeout * 2.0,
-            }),
+                    fadeout: |timeout| timeout * 2.0,
+                })
+            },
             Outcome::ProjectileShot { .. } => {},
         }
     }
@@ -493,7 +523,10 @@ impl Scene {
                 if player_rolling {
                     player_scale * 0.8
                 } else if is_running && on_ground.unwrap_or(false) {
-                    eye_height + (scene_data.state.get_time() as f32 * 17.0).sin() * 0.05
+                    eye_height
+                        + (scene_data.state.get_time() as f32 * 17.0).sin()
+                            * 0.05
+                            * scene_data.head_bob_intensity
                 } else {
                     eye_height
                 }
@@ -520,7 +553,7 @@ impl Scene {
         );
 
         // Compute camera matrices.
-        self.camera.compute_dependents(&*scene_data.state.terrain());
+        self.camera.compute_dependents(scene_data.client);
         let camera::Dependents {
             view_mat,
             proj_mat,
@@ -985,6 +1018,7 @@ impl Scene {
             &self.camera,
         );
         self.music_mgr.maintain(audio, scene_data.state);
+        self.stinger_mgr.maintain(audio, scene_data.state);
     }
 
     /// Render the scene using the provided `Renderer`.
@@ -961,6 +961,27 @@ impl<V: RectRasterableVol> Terrain<V> {
 
     pub fn shadow_chunk_count(&self) -> usize { self.shadow_chunks.len() }
 
+    /// Bytes of GPU buffers holding the meshes and sprite instances of the
+    /// loaded chunks
+    pub fn mesh_memory_usage(&self) -> usize {
+        self.chunks
+            .values()
+            .map(|chunk| {
+                chunk.opaque_model.vbuf.get_info().size
+                    + chunk
+                        .fluid_model
+                        .as_ref()
+                        .map(|model| model.vbuf.get_info().size)
+                        .unwrap_or_default()
+                    + chunk
+                        .sprite_instances
+                        .values()
+                        .map(|instances| instances.ibuf.get_info().size)
+                        .sum::<usize>()
+            })
+            .sum()
+    }
+
     pub fn render_shadows(
         &self,
         renderer: &mut Renderer,
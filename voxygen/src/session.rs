@@ -1,10 +1,19 @@
+#[cfg(feature = "voice")]
+use crate::audio::voice::VoiceMgr;
 use crate::{
-    audio::sfx::{SfxEvent, SfxEventItem},
-    ecs::MyEntity,
-    hud::{DebugInfo, Event as HudEvent, Hud, HudInfo, PressBehavior},
+    audio::{
+        sfx::{SfxEvent, SfxEventItem},
+        stinger::AudioEvent,
+    },
+    ecs::{comp::HpFloaterList, ExpFloater, MyEntity, MyExpFloaterList},
+    hud::{
+        CombatEvent, CombatLogExport, Death, DebugInfo, Event as HudEvent, Hud, HudInfo, KillEvent,
+        PressBehavior,
+    },
     i18n::{i18n_asset_key, VoxygenLocalization},
     key_state::KeyState,
     menu::char_selection::CharSelectionState,
+    profile::CharacterProfile,
     render::Renderer,
     scene::{camera, CameraMode, Scene, SceneData},
     settings::{AudioOutput, ControlSettings, Settings},
@@ -13,24 +22,36 @@ use crate::{
 };
 use client::{self, Client};
 use common::{
+    alchemy::BrewError,
     assets::Asset,
-    comp,
+    combat, comp,
     comp::{
-        ChatMsg, ChatType, InventoryUpdateEvent, Pos, Vel, MAX_MOUNT_RANGE_SQR,
+        slot::EquipError, ChatMsg, ChatType, InventoryUpdateEvent, Pos, Vel, MAX_MOUNT_RANGE_SQR,
         MAX_PICKUP_RANGE_SQR,
     },
     event::EventBus,
+    memory::MemoryReport,
     outcome::Outcome,
+    recipe::CraftError,
     span,
-    terrain::{Block, BlockKind},
+    sync::WorldSyncExt,
+    terrain::{sign::is_sign, Block, BlockKind},
     util::Dir,
     vol::ReadVol,
 };
 use specs::{Join, WorldExt};
-use std::{cell::RefCell, rc::Rc, sync::Arc, time::Duration};
-use tracing::{error, info};
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{error, info, warn};
 use vek::*;
 
+/// How often the memory usage in the debug overlay is measured again
+const MEMORY_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
 /// The action to perform after a tick
 enum TickAction {
     // Continue executing
@@ -55,6 +76,11 @@ pub struct SessionState {
     is_aiming: bool,
     target_entity: Option<specs::Entity>,
     selected_entity: Option<(specs::Entity, std::time::Instant)>,
+    combat_log_export: Option<CombatLogExport>,
+    /// Memory usage shown in the debug overlay and when it was measured
+    memory_report: Option<(Instant, MemoryReport)>,
+    #[cfg(feature = "voice")]
+    voice_mgr: VoiceMgr,
 }
 
 /// Represents an active game session (i.e., the one being played).
@@ -72,6 +98,9 @@ impl SessionState {
             .camera_mut()
             .set_fov_deg(global_state.settings.graphics.fov);
         let hud = Hud::new(global_state, &client.borrow());
+        client
+            .borrow_mut()
+            .set_net_conditions(global_state.settings.networking.simulated_conditions());
         let voxygen_i18n = VoxygenLocalization::load_expect(&i18n_asset_key(
             &global_state.settings.language.selected_language,
         ));
@@ -95,6 +124,10 @@ impl SessionState {
             is_aiming: false,
             target_entity: None,
             selected_entity: None,
+            combat_log_export: None,
+            memory_report: None,
+            #[cfg(feature = "voice")]
+            voice_mgr: VoiceMgr::new(),
         }
     }
 
@@ -110,6 +143,8 @@ impl SessionState {
         dt: Duration,
         global_state: &mut GlobalState,
         outcomes: &mut Vec<Outcome>,
+        combat_events: &mut Vec<combat::CombatEvent>,
+        sounds: &mut Vec<comp::Sound>,
     ) -> Result<TickAction, Error> {
         span!(_guard, "tick", "Session::tick");
         self.inputs.tick(dt);
@@ -144,6 +179,89 @@ impl SessionState {
                                 chat_type: ChatType::Loot,
                             });
                         },
+                        InventoryUpdateEvent::CraftFailed(error) => {
+                            let message = match error {
+                                CraftError::UnknownRecipe => {
+                                    self.voxygen_i18n.get("hud.chat.craft_fail").to_string()
+                                },
+                                CraftError::MissingIngredients(missing) => self
+                                    .voxygen_i18n
+                                    .get("hud.chat.craft_fail_missing")
+                                    .replace(
+                                        "{items}",
+                                        &missing
+                                            .iter()
+                                            .map(|(name, amount)| format!("{} x{}", name, amount))
+                                            .collect::<Vec<_>>()
+                                            .join(", "),
+                                    ),
+                                CraftError::NotAtFire => self
+                                    .voxygen_i18n
+                                    .get("hud.chat.craft_fail_fire")
+                                    .to_string(),
+                                CraftError::AlreadyCooking => self
+                                    .voxygen_i18n
+                                    .get("hud.chat.craft_fail_cooking")
+                                    .to_string(),
+                            };
+                            self.hud.new_message(ChatMsg {
+                                message,
+                                chat_type: ChatType::CommandError,
+                            });
+                        },
+                        InventoryUpdateEvent::Cooked { item, burnt } => {
+                            let key = if burnt {
+                                "hud.chat.food_burnt"
+                            } else {
+                                "hud.chat.food_cooked"
+                            };
+                            self.hud.new_message(ChatMsg {
+                                message: self.voxygen_i18n.get(key).replace("{item}", item.name()),
+                                chat_type: ChatType::Loot,
+                            });
+                        },
+                        InventoryUpdateEvent::Brewed { potions, learned } => {
+                            let mut message = if potions.is_empty() {
+                                self.voxygen_i18n.get("hud.chat.brew_fizzled").to_string()
+                            } else {
+                                self.voxygen_i18n
+                                    .get("hud.chat.brewed")
+                                    .replace("{potions}", &potions.join(", "))
+                            };
+                            if learned > 0 {
+                                message.push(' ');
+                                message.push_str(
+                                    &self
+                                        .voxygen_i18n
+                                        .get("hud.chat.brew_learned")
+                                        .replace("{amount}", &learned.to_string()),
+                                );
+                            }
+                            self.hud.new_message(ChatMsg {
+                                message,
+                                chat_type: ChatType::Loot,
+                            });
+                        },
+                        InventoryUpdateEvent::BrewFailed(error) => {
+                            let key = match error {
+                                BrewError::TooFewIngredients => "hud.chat.brew_fail_few",
+                                BrewError::NotAnIngredient(_) => "hud.chat.brew_fail_ingredient",
+                                BrewError::MissingVial => "hud.chat.brew_fail_vial",
+                            };
+                            self.hud.new_message(ChatMsg {
+                                message: self.voxygen_i18n.get(key).to_string(),
+                                chat_type: ChatType::CommandError,
+                            });
+                        },
+                        InventoryUpdateEvent::EquipFailed(EquipError::HandsFull) => {
+                            self.hud.new_message(ChatMsg {
+                                message: self
+                                    .voxygen_i18n
+                                    .get("hud.chat.equip_fail_hands")
+                                    .to_string(),
+                                chat_type: ChatType::CommandError,
+                            });
+                        },
                         _ => {},
                     };
                 },
@@ -162,6 +280,34 @@ impl SessionState {
                         message,
                     });
                 },
+                client::Event::SessionRefreshFailed(error) => {
+                    self.hud.new_message(ChatMsg {
+                        chat_type: ChatType::CommandError,
+                        message: self
+                            .voxygen_i18n
+                            .get("hud.chat.session_refresh_failed")
+                            .replace("{error}", &error),
+                    });
+                },
+                client::Event::UiPreferences(Some(data)) => {
+                    match ron::de::from_bytes::<CharacterProfile>(&data) {
+                        Ok(preferences) => {
+                            let client = self.client.borrow();
+                            if let Some(character_id) = client.active_character_id {
+                                global_state.profile.set_hotbar_slots(
+                                    &client.server_info.name,
+                                    character_id,
+                                    preferences.hotbar_slots,
+                                );
+                                global_state.profile.save_to_file_warn();
+                            }
+                            self.hud.set_hotbar_slots(preferences.hotbar_slots);
+                        },
+                        Err(e) => warn!(?e, "Failed to parse UI preferences from the server"),
+                    }
+                },
+                // Nothing stored yet, the local hotbar is kept
+                client::Event::UiPreferences(None) => {},
                 client::Event::Kicked(reason) => {
                     global_state.info_message = Some(format!(
                         "{}: {}",
@@ -178,6 +324,72 @@ impl SessionState {
                     global_state.settings.save_to_file_warn();
                 },
                 client::Event::Outcome(outcome) => outcomes.push(outcome),
+                client::Event::Sound(sound) => sounds.push(sound),
+                client::Event::Death { cause, .. } => {
+                    client
+                        .state()
+                        .ecs()
+                        .read_resource::<EventBus<AudioEvent>>()
+                        .emit_now(AudioEvent::Death);
+                    self.hud.death(Death::new(cause, &client));
+                },
+                client::Event::Respawned => self.hud.respawned(),
+                client::Event::ExpGain { exp, .. } => {
+                    client
+                        .state()
+                        .ecs()
+                        .write_resource::<MyExpFloaterList>()
+                        .floaters
+                        .push(ExpFloater {
+                            timer: 0.0,
+                            exp_change: exp as i32,
+                            rand: (rand::random(), rand::random()),
+                        });
+                },
+                client::Event::LevelUp(_) => {
+                    client
+                        .state()
+                        .ecs()
+                        .read_resource::<EventBus<AudioEvent>>()
+                        .emit_now(AudioEvent::LevelUp);
+                },
+            }
+        }
+
+        for event in client.recv_combat_events() {
+            if let Some(entry) = CombatEvent::from_combat_event(&event, &client) {
+                if global_state.settings.gameplay.export_combat_log {
+                    export_combat_event(
+                        &mut self.combat_log_export,
+                        &entry,
+                        &mut global_state.settings,
+                    );
+                }
+                self.hud.new_combat_event(entry);
+            }
+            if let combat::CombatEvent::Damage {
+                target,
+                change,
+                hit: comp::HitModifiers { crit: true, .. },
+                ..
+            } = &event
+            {
+                let ecs = client.state().ecs();
+                if let Some(entity) = ecs.entity_from_uid(target.0) {
+                    if let Some(floaters) = ecs.write_storage::<HpFloaterList>().get_mut(entity) {
+                        floaters.mark_crit(change.amount);
+                    }
+                }
+            }
+            if let Some(entry) = KillEvent::from_combat_event(&event, &client) {
+                self.hud.new_kill_event(entry);
+            }
+            combat_events.push(event);
+        }
+
+        if let Some(export) = &mut self.combat_log_export {
+            if let Err(e) = export.flush() {
+                warn!(?e, "Failed to write the combat log");
             }
         }
 
@@ -194,6 +406,7 @@ impl PlayState for SessionState {
         global_state.window.grab_cursor(true);
 
         self.client.borrow_mut().clear_terrain();
+        self.client.borrow_mut().request_ui_preferences();
 
         // Send startup commands to the server
         if global_state.settings.send_logon_commands {
@@ -230,7 +443,7 @@ impl PlayState for SessionState {
             // Compute camera data
             self.scene
                 .camera_mut()
-                .compute_dependents(&*self.client.borrow().state().terrain());
+                .compute_dependents(&self.client.borrow());
             let camera::Dependents {
                 cam_pos, cam_dir, ..
             } = self.scene.camera().dependents();
@@ -272,14 +485,14 @@ impl PlayState for SessionState {
                 .get(self.client.borrow().entity())
                 .is_some();
 
-            // Only highlight collectables
+            // Only highlight collectables and signs
             self.scene.set_select_pos(select_pos.filter(|sp| {
                 self.client
                     .borrow()
                     .state()
                     .terrain()
                     .get(*sp)
-                    .map(|b| b.is_collectible() || can_build)
+                    .map(|b| b.is_collectible() || is_sign(b) || can_build)
                     .unwrap_or(false)
             }));
 
@@ -500,9 +713,19 @@ impl PlayState for SessionState {
                         if state {
                             let mut client = self.client.borrow_mut();
 
-                            // Collect terrain sprites
+                            // Collect terrain sprites, or edit signs
                             if let Some(select_pos) = self.scene.select_pos() {
-                                client.collect_block(select_pos);
+                                let at_sign = client
+                                    .state()
+                                    .terrain()
+                                    .get(select_pos)
+                                    .map_or(false, is_sign);
+                                if at_sign && can_build {
+                                    let text = client.sign_text(select_pos).unwrap_or_default();
+                                    self.hud.edit_sign(select_pos, text);
+                                } else {
+                                    client.collect_block(select_pos);
+                                }
                             }
 
                             // Collect lootable entities
@@ -533,6 +756,21 @@ impl PlayState for SessionState {
                                 if let Some(entity) = entity {
                                     client.pick_up(entity);
                                 }
+
+                                // Rest at a nearby campfire to respawn there
+                                let at_campfire = (
+                                    &client.state().ecs().read_storage::<comp::Pos>(),
+                                    &client.state().ecs().read_storage::<comp::Body>(),
+                                )
+                                    .join()
+                                    .any(|(pos, body)| {
+                                        *body == comp::Body::Object(comp::object::Body::CampfireLit)
+                                            && pos.0.distance_squared(player_pos.0)
+                                                < MAX_PICKUP_RANGE_SQR
+                                    });
+                                if at_campfire {
+                                    client.set_waypoint();
+                                }
                             }
                         }
                     }
@@ -610,6 +848,9 @@ impl PlayState for SessionState {
                             message: screenshot_message,
                         })
                     },
+                    Event::SettingsChanged => {
+                        global_state.settings.save_to_file_warn();
+                    },
 
                     // Pass all other events to the scene
                     event => {
@@ -678,6 +919,24 @@ impl PlayState for SessionState {
             self.inputs.climb = self.key_state.climb();
 
             let mut outcomes = Vec::new();
+            let mut combat_events = Vec::new();
+            let mut sounds = Vec::new();
+
+            // Let the benchmark move the character and the camera along its path
+            #[cfg(feature = "singleplayer")]
+            {
+                let dt = global_state.clock.get_last_delta();
+                let mut client = self.client.borrow_mut();
+                let camera = self.scene.camera_mut();
+                let singleplayer = global_state.singleplayer.as_ref();
+                let finished = global_state.benchmark.as_mut().map_or(false, |benchmark| {
+                    !benchmark.tick(dt, singleplayer, &mut client, camera)
+                });
+                if let (true, Some(benchmark)) = (finished, &global_state.benchmark) {
+                    benchmark.finish(global_state);
+                    return PlayStateResult::Shutdown;
+                }
+            }
 
             // Runs if either in a multiplayer server or the singleplayer server is unpaused
             if !global_state.paused() {
@@ -686,6 +945,8 @@ impl PlayState for SessionState {
                     global_state.clock.get_avg_delta(),
                     global_state,
                     &mut outcomes,
+                    &mut combat_events,
+                    &mut sounds,
                 ) {
                     Ok(TickAction::Continue) => {}, // Do nothing
                     Ok(TickAction::Disconnect) => return PlayStateResult::Pop, // Go to main menu
@@ -702,7 +963,21 @@ impl PlayState for SessionState {
             // Recompute dependents just in case some input modified the camera
             self.scene
                 .camera_mut()
-                .compute_dependents(&*self.client.borrow().state().terrain());
+                .compute_dependents(&self.client.borrow());
+
+            // Measuring memory walks all chunks and storages, so only do it once per
+            // second while the debug overlay is open
+            if global_state.settings.gameplay.toggle_debug
+                && self.memory_report.map_or(true, |(measured, _)| {
+                    measured.elapsed() >= MEMORY_REPORT_INTERVAL
+                })
+            {
+                let report = MemoryReport {
+                    meshes: self.scene.terrain().mesh_memory_usage(),
+                    ..self.client.borrow().memory_report()
+                };
+                self.memory_report = Some((Instant::now(), report));
+            }
 
             // Generate debug info, if needed (it iterates through enough data that we might
             // as well avoid it unless we need it).
@@ -746,6 +1021,10 @@ impl PlayState for SessionState {
                     num_particles: self.scene.particle_mgr().particle_count() as u32,
                     num_particles_visible: self.scene.particle_mgr().particle_count_visible()
                         as u32,
+                    memory: self
+                        .memory_report
+                        .map(|(_, report)| report)
+                        .unwrap_or_default(),
                 });
 
             // Extract HUD events ensuring the client borrow gets dropped.
@@ -763,6 +1042,7 @@ impl PlayState for SessionState {
                     ),
                     target_entity: self.target_entity,
                     selected_entity: self.selected_entity,
+                    select_pos: self.scene.select_pos(),
                 },
             );
 
@@ -783,6 +1063,10 @@ impl PlayState for SessionState {
                     HudEvent::CharacterSelection => {
                         self.client.borrow_mut().request_remove_character()
                     },
+                    HudEvent::Respawn => self.client.borrow_mut().respawn(),
+                    HudEvent::EditSign { pos, text } => {
+                        self.client.borrow_mut().edit_sign(pos, text);
+                    },
                     HudEvent::Logout => self.client.borrow_mut().request_logout(),
                     HudEvent::Quit => {
                         return PlayStateResult::Shutdown;
@@ -814,6 +1098,13 @@ impl PlayState for SessionState {
                         global_state.settings.gameplay.loading_tips = loading_tips;
                         global_state.settings.save_to_file_warn();
                     },
+                    HudEvent::ToggleCombatLogExport(export) => {
+                        global_state.settings.gameplay.export_combat_log = export;
+                        global_state.settings.save_to_file_warn();
+                        if !export {
+                            self.combat_log_export = None;
+                        }
+                    },
                     HudEvent::SctDamageBatch(sct_damage_batch) => {
                         global_state.settings.gameplay.sct_damage_batch = sct_damage_batch;
                         global_state.settings.save_to_file_warn();
@@ -826,6 +1117,22 @@ impl PlayState for SessionState {
                         global_state.settings.gameplay.speech_bubble_icon = sbi;
                         global_state.settings.save_to_file_warn();
                     },
+                    HudEvent::SpeechBubbleDuration(duration) => {
+                        global_state.settings.gameplay.speech_bubble_duration = duration;
+                        global_state.settings.save_to_file_warn();
+                    },
+                    HudEvent::MaxSpeechBubbles(max) => {
+                        global_state.settings.gameplay.max_speech_bubbles = max;
+                        global_state.settings.save_to_file_warn();
+                    },
+                    HudEvent::ToggleDpsMeter(dps_meter) => {
+                        global_state.settings.gameplay.dps_meter = dps_meter;
+                        global_state.settings.save_to_file_warn();
+                    },
+                    HudEvent::ToggleStreamerMode(streamer_mode) => {
+                        global_state.settings.gameplay.streamer_mode = streamer_mode;
+                        global_state.settings.save_to_file_warn();
+                    },
                     HudEvent::ToggleDebug(toggle_debug) => {
                         global_state.settings.gameplay.toggle_debug = toggle_debug;
                         global_state.settings.save_to_file_warn();
@@ -898,6 +1205,10 @@ impl PlayState for SessionState {
                             self.hud.scale_change(scale_change);
                         global_state.settings.save_to_file_warn();
                     },
+                    HudEvent::ChangeUiMaxAspectRatio(max_aspect_ratio) => {
+                        global_state.settings.gameplay.ui_max_aspect_ratio = max_aspect_ratio;
+                        global_state.settings.save_to_file_warn();
+                    },
                     HudEvent::AdjustMusicVolume(music_volume) => {
                         global_state.audio.set_music_volume(music_volume);
 
@@ -910,6 +1221,26 @@ impl PlayState for SessionState {
                         global_state.settings.audio.sfx_volume = sfx_volume;
                         global_state.settings.save_to_file_warn();
                     },
+                    #[cfg(feature = "voice")]
+                    HudEvent::AdjustVoiceVolume(voice_volume) => {
+                        global_state.audio.set_voice_volume(voice_volume);
+
+                        global_state.settings.audio.voice_volume = voice_volume;
+                        global_state.settings.save_to_file_warn();
+                    },
+                    #[cfg(feature = "voice")]
+                    HudEvent::ToggleVoiceChat(voice_chat) => {
+                        global_state.settings.audio.voice_chat = voice_chat;
+                        global_state.settings.save_to_file_warn();
+                    },
+                    #[cfg(feature = "voice")]
+                    HudEvent::ToggleVoiceMute(alias) => {
+                        let muted_voices = &mut global_state.settings.audio.muted_voices;
+                        if !muted_voices.remove(&alias) {
+                            muted_voices.insert(alias);
+                        }
+                        global_state.settings.save_to_file_warn();
+                    },
                     HudEvent::ChangeAudioDevice(name) => {
                         global_state.audio.set_device(name.clone());
 
@@ -922,6 +1253,12 @@ impl PlayState for SessionState {
                     },
                     HudEvent::UseSlot(x) => self.client.borrow_mut().use_slot(x),
                     HudEvent::SwapSlots(a, b) => self.client.borrow_mut().swap_slots(a, b),
+                    HudEvent::DyeItem(dye, target) => {
+                        self.client.borrow_mut().dye_item(dye, target)
+                    },
+                    HudEvent::SetAppearance(slot, choice) => {
+                        self.client.borrow_mut().set_appearance(slot, choice)
+                    },
                     HudEvent::DropSlot(x) => {
                         let mut client = self.client.borrow_mut();
                         client.drop_slot(x);
@@ -945,16 +1282,34 @@ impl PlayState for SessionState {
 
                         global_state.profile.save_to_file_warn();
 
+                        // Keep the hotbar on the server too, to have it on other machines
+                        let preferences = CharacterProfile {
+                            hotbar_slots: state.slots,
+                        };
+                        match ron::ser::to_string(&preferences) {
+                            Ok(data) => self
+                                .client
+                                .borrow_mut()
+                                .set_ui_preferences(data.into_bytes()),
+                            Err(e) => warn!(?e, "Failed to serialize UI preferences"),
+                        }
+
                         info!("Event! -> ChangedHotbarState")
                     },
                     HudEvent::Ability3(state) => self.inputs.ability3.set_state(state),
+                    HudEvent::UseAbilitySlot(slot) => {
+                        self.client.borrow_mut().use_ability_slot(slot);
+                    },
+                    HudEvent::SetAbilitySlot(slot, ability) => {
+                        self.client.borrow_mut().set_ability_slot(slot, ability);
+                    },
                     HudEvent::ChangeFOV(new_fov) => {
                         global_state.settings.graphics.fov = new_fov;
                         global_state.settings.save_to_file_warn();
                         self.scene.camera_mut().set_fov_deg(new_fov);
                         self.scene
                             .camera_mut()
-                            .compute_dependents(&*self.client.borrow().state().terrain());
+                            .compute_dependents(&self.client.borrow());
                     },
                     HudEvent::MapZoom(map_zoom) => {
                         global_state.settings.gameplay.map_zoom = map_zoom;
@@ -968,6 +1323,15 @@ impl PlayState for SessionState {
                         global_state.settings.graphics.ambiance = new_ambiance;
                         global_state.settings.save_to_file_warn();
                     },
+                    HudEvent::ChangeHeadBob(head_bob_intensity) => {
+                        global_state.settings.graphics.head_bob_intensity = head_bob_intensity;
+                        global_state.settings.save_to_file_warn();
+                    },
+                    HudEvent::ChangeScreenShake(screen_shake_intensity) => {
+                        global_state.settings.graphics.screen_shake_intensity =
+                            screen_shake_intensity;
+                        global_state.settings.save_to_file_warn();
+                    },
                     HudEvent::ChangeRenderMode(new_render_mode) => {
                         // Do this first so if it crashes the setting isn't saved :)
                         global_state
@@ -1024,6 +1388,9 @@ impl PlayState for SessionState {
                     HudEvent::CraftRecipe(r) => {
                         self.client.borrow_mut().craft_recipe(&r);
                     },
+                    HudEvent::Brew(slots) => {
+                        self.client.borrow_mut().brew(slots);
+                    },
                     HudEvent::InviteMember(uid) => {
                         self.client.borrow_mut().send_group_invite(uid);
                     },
@@ -1048,6 +1415,7 @@ impl PlayState for SessionState {
             {
                 let client = self.client.borrow();
                 let scene_data = SceneData {
+                    client: &client,
                     state: client.state(),
                     player_entity: client.entity(),
                     target_entity: self.target_entity,
@@ -1067,6 +1435,8 @@ impl PlayState for SessionState {
                         .figure_lod_render_distance
                         as f32,
                     is_aiming,
+                    head_bob_intensity: global_state.settings.graphics.head_bob_intensity,
+                    screen_shake_intensity: global_state.settings.graphics.screen_shake_intensity,
                 };
 
                 // Runs if either in a multiplayer server or the singleplayer server is unpaused
@@ -1082,9 +1452,22 @@ impl PlayState for SessionState {
                         self.scene
                             .handle_outcome(&outcome, &scene_data, &mut global_state.audio);
                     }
+                    for event in combat_events {
+                        self.scene.handle_combat_event(&event, &scene_data);
+                    }
+                    for sound in sounds {
+                        self.scene.handle_sound(&sound, &mut global_state.audio);
+                    }
                 }
             }
 
+            #[cfg(feature = "voice")]
+            self.voice_mgr.maintain(
+                &mut self.client.borrow_mut(),
+                &mut global_state.audio,
+                &global_state.settings.audio,
+            );
+
             // Clean things up after the tick.
             self.cleanup();
 
@@ -1112,6 +1495,7 @@ impl PlayState for SessionState {
             let client = self.client.borrow();
 
             let scene_data = SceneData {
+                client: &client,
                 state: client.state(),
                 player_entity: client.entity(),
                 target_entity: self.target_entity,
@@ -1126,6 +1510,8 @@ impl PlayState for SessionState {
                 figure_lod_render_distance: settings.graphics.figure_lod_render_distance as f32,
                 particles_enabled: settings.graphics.particles_enabled,
                 is_aiming: self.is_aiming,
+                head_bob_intensity: settings.graphics.head_bob_intensity,
+                screen_shake_intensity: settings.graphics.screen_shake_intensity,
             };
             self.scene.render(
                 renderer,
@@ -1140,6 +1526,30 @@ impl PlayState for SessionState {
     }
 }
 
+/// Appends a combat event to the exported combat log, creating the file on
+/// first use. Exporting is turned off if the file can't be created.
+fn export_combat_event(
+    export: &mut Option<CombatLogExport>,
+    event: &CombatEvent,
+    settings: &mut Settings,
+) {
+    if export.is_none() {
+        match CombatLogExport::new(&settings.log.logs_path) {
+            Ok(new_export) => *export = Some(new_export),
+            Err(e) => {
+                warn!(?e, "Failed to create the combat log file, disabling export");
+                settings.gameplay.export_combat_log = false;
+                return;
+            },
+        }
+    }
+    if let Some(export) = export {
+        if let Err(e) = export.write(event) {
+            warn!(?e, "Failed to write the combat log");
+        }
+    }
+}
+
 /// Max distance an entity can be "targeted"
 const MAX_TARGET_RANGE: f32 = 300.0;
 /// Calculate what the cursor is pointing at within the 3d scene
@@ -1163,26 +1573,19 @@ fn under_cursor(
         Some(pos) => pos.0 + (Vec3::unit_z() * 2.0),
         _ => cam_pos, // Should never happen, but a safe fallback
     };
-    let terrain = client.state().terrain();
-
-    let cam_ray = terrain
-        .ray(cam_pos, cam_pos + cam_dir * 100.0)
-        .until(|block| block.is_filled() || block.is_collectible())
-        .cast();
+    let cam_hit = client.raycast_terrain_until(cam_pos, cam_pos + cam_dir * 100.0, |block| {
+        block.is_filled() || block.is_collectible()
+    });
 
-    let cam_dist = cam_ray.0;
+    let cam_dist = cam_hit.map_or(100.0, |hit| hit.distance);
 
     // The ray hit something, is it within range?
-    let (build_pos, select_pos) = if matches!(cam_ray.1, Ok(Some(_)) if
-        player_pos.distance_squared(cam_pos + cam_dir * cam_dist)
-        <= MAX_PICKUP_RANGE_SQR)
-    {
-        (
-            Some((cam_pos + cam_dir * (cam_dist - 0.01)).map(|e| e.floor() as i32)),
-            Some((cam_pos + cam_dir * (cam_dist + 0.01)).map(|e| e.floor() as i32)),
-        )
-    } else {
-        (None, None)
+    let (build_pos, select_pos) = match cam_hit {
+        Some(hit) if player_pos.distance_squared(hit.pos) <= MAX_PICKUP_RANGE_SQR => (
+            Some((cam_pos + cam_dir * (hit.distance - 0.01)).map(|e| e.floor() as i32)),
+            Some(hit.block_pos),
+        ),
+        _ => (None, None),
     };
 
     // See if ray hits entities
@@ -1190,7 +1593,7 @@ fn under_cursor(
     let ecs = client.state().ecs();
     // Don't cast through blocks
     // Could check for intersection with entity from last frame to narrow this down
-    let cast_dist = if let Ok(Some(_)) = cam_ray.1 {
+    let cast_dist = if cam_hit.is_some() {
         cam_dist.min(MAX_TARGET_RANGE)
     } else {
         MAX_TARGET_RANGE
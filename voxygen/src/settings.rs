@@ -5,10 +5,11 @@ use crate::{
     ui::ScaleMode,
     window::{FullScreenSettings, GameInput, KeyMouse},
 };
+use client::net_sim::NetConditions;
 use directories_next::UserDirs;
 use hashbrown::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf};
+use std::{fs, path::PathBuf, time::Duration};
 use tracing::warn;
 use winit::event::{MouseButton, VirtualKeyCode};
 
@@ -143,6 +144,7 @@ impl ControlSettings {
             GameInput::Map => KeyMouse::Key(VirtualKeyCode::M),
             GameInput::Bag => KeyMouse::Key(VirtualKeyCode::B),
             GameInput::Social => KeyMouse::Key(VirtualKeyCode::O),
+            GameInput::CombatLog => KeyMouse::Key(VirtualKeyCode::H),
             GameInput::Crafting => KeyMouse::Key(VirtualKeyCode::C),
             GameInput::Spellbook => KeyMouse::Key(VirtualKeyCode::P),
             GameInput::Settings => KeyMouse::Key(VirtualKeyCode::N),
@@ -213,6 +215,7 @@ impl Default for ControlSettings {
             GameInput::Map,
             GameInput::Bag,
             GameInput::Social,
+            GameInput::CombatLog,
             GameInput::Crafting,
             GameInput::Spellbook,
             GameInput::Settings,
@@ -498,6 +501,10 @@ pub struct GameplaySettings {
     pub sct_damage_batch: bool,
     pub speech_bubble_dark_mode: bool,
     pub speech_bubble_icon: bool,
+    /// Seconds speech bubbles stay above the speaker
+    pub speech_bubble_duration: f32,
+    /// Most speech bubbles shown at once, older ones are removed first
+    pub max_speech_bubbles: u32,
     pub mouse_y_inversion: bool,
     pub smooth_pan_enable: bool,
     pub crosshair_transp: f32,
@@ -509,11 +516,17 @@ pub struct GameplaySettings {
     pub shortcut_numbers: ShortcutNumbers,
     pub bar_numbers: BarNumbers,
     pub ui_scale: ScaleMode,
+    pub ui_max_aspect_ratio: Option<f64>,
     pub free_look_behavior: PressBehavior,
     pub auto_walk_behavior: PressBehavior,
     pub stop_auto_walk_on_input: bool,
     pub map_zoom: f64,
     pub loading_tips: bool,
+    pub export_combat_log: bool,
+    pub dps_meter: bool,
+    /// Hide the names of other players and the server address, for streaming
+    /// or recording the game
+    pub streamer_mode: bool,
 }
 
 impl Default for GameplaySettings {
@@ -530,6 +543,8 @@ impl Default for GameplaySettings {
             sct_damage_batch: false,
             speech_bubble_dark_mode: false,
             speech_bubble_icon: true,
+            speech_bubble_duration: 5.0,
+            max_speech_bubbles: 10,
             crosshair_transp: 0.6,
             chat_transp: 0.4,
             chat_character_name: true,
@@ -539,11 +554,15 @@ impl Default for GameplaySettings {
             shortcut_numbers: ShortcutNumbers::On,
             bar_numbers: BarNumbers::Values,
             ui_scale: ScaleMode::RelativeToWindow([1920.0, 1080.0].into()),
+            ui_max_aspect_ratio: None,
             free_look_behavior: PressBehavior::Toggle,
             auto_walk_behavior: PressBehavior::Toggle,
             stop_auto_walk_on_input: true,
             map_zoom: 4.0,
             loading_tips: true,
+            export_combat_log: false,
+            dps_meter: false,
+            streamer_mode: false,
         }
     }
 }
@@ -556,6 +575,27 @@ pub struct NetworkingSettings {
     pub servers: Vec<String>,
     pub default_server: usize,
     pub trusted_auth_servers: HashSet<String>,
+    /// Latency in milliseconds added to messages in either direction, to test
+    /// how the game copes with a bad connection
+    pub simulated_latency_ms: u64,
+    /// Largest random delay in milliseconds added on top of the simulated
+    /// latency
+    pub simulated_jitter_ms: u64,
+    /// Chance of each message being lost, between 0 and 1
+    pub simulated_loss: f32,
+    /// Keep the auth server password in memory while connected, to sign in
+    /// again when the session expires instead of being disconnected
+    pub keep_signed_in: bool,
+}
+
+impl NetworkingSettings {
+    pub fn simulated_conditions(&self) -> NetConditions {
+        NetConditions {
+            latency: Duration::from_millis(self.simulated_latency_ms),
+            jitter: Duration::from_millis(self.simulated_jitter_ms),
+            loss: self.simulated_loss.max(0.0).min(1.0),
+        }
+    }
 }
 
 impl Default for NetworkingSettings {
@@ -568,6 +608,10 @@ impl Default for NetworkingSettings {
                 .iter()
                 .map(|s| s.to_string())
                 .collect(),
+            simulated_latency_ms: 0,
+            simulated_jitter_ms: 0,
+            simulated_loss: 0.0,
+            keep_signed_in: false,
         }
     }
 }
@@ -621,6 +665,10 @@ pub struct GraphicsSettings {
     pub window_size: [u16; 2],
     pub fullscreen: FullScreenSettings,
     pub lod_detail: u32,
+    /// Multiplier for the first person head bob while running, 0 disables it
+    pub head_bob_intensity: f32,
+    /// Multiplier for camera shake caused by e.g. explosions, 0 disables it
+    pub screen_shake_intensity: f32,
 }
 
 impl Default for GraphicsSettings {
@@ -638,6 +686,8 @@ impl Default for GraphicsSettings {
             window_size: [1920, 1080],
             fullscreen: FullScreenSettings::default(),
             lod_detail: 300,
+            head_bob_intensity: 1.0,
+            screen_shake_intensity: 1.0,
         }
     }
 }
@@ -665,10 +715,16 @@ pub struct AudioSettings {
     pub master_volume: f32,
     pub music_volume: f32,
     pub sfx_volume: f32,
+    pub voice_volume: f32,
     pub max_sfx_channels: usize,
 
     /// Audio Device that Voxygen will use to play audio.
     pub output: AudioOutput,
+    /// Whether the microphone is sent to nearby players, if voice chat is
+    /// supported by this build and the server
+    pub voice_chat: bool,
+    /// Aliases of the players whose voice isn't played
+    pub muted_voices: HashSet<String>,
 }
 
 impl Default for AudioSettings {
@@ -677,8 +733,11 @@ impl Default for AudioSettings {
             master_volume: 1.0,
             music_volume: 0.4,
             sfx_volume: 0.6,
+            voice_volume: 1.0,
             max_sfx_channels: 10,
             output: AudioOutput::Automatic,
+            voice_chat: false,
+            muted_voices: HashSet::new(),
         }
     }
 }
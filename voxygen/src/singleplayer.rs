@@ -1,3 +1,4 @@
+use crate::benchmark::{self, NpcGroup};
 use client::Client;
 use common::clock::Clock;
 use crossbeam::channel::{bounded, unbounded, Receiver, Sender, TryRecvError};
@@ -11,11 +12,17 @@ use std::{
     time::Duration,
 };
 use tracing::{error, info, warn};
+use vek::*;
 
 const TPS: u64 = 30;
 
 enum Msg {
     Stop,
+    SpawnBenchmarkNpcs {
+        npcs: Vec<NpcGroup>,
+        center: Vec3<f32>,
+        seed: u32,
+    },
 }
 
 /// Used to start and stop the background thread running the server
@@ -30,8 +37,6 @@ pub struct Singleplayer {
 
 impl Singleplayer {
     pub fn new(client: Option<&Client>) -> (Self, server::Settings) {
-        let (sender, receiver) = unbounded();
-
         // Determine folder to save server data in
         let server_data_dir = {
             let mut path = common::userdata_dir_workspace!();
@@ -79,6 +84,43 @@ impl Singleplayer {
         let settings = server::Settings::singleplayer(&server_data_dir);
         let editable_settings = server::EditableSettings::singleplayer(&server_data_dir);
 
+        Self::start(client, server_data_dir, settings, editable_settings)
+    }
+
+    /// Starts a server for a benchmark run. It keeps its data apart from the
+    /// singleplayer saves and always generates the world from `seed`.
+    pub fn benchmark(seed: u32) -> (Self, server::Settings) {
+        let server_data_dir = {
+            let mut path = common::userdata_dir_workspace!();
+            path.push("benchmark");
+            path
+        };
+
+        let settings = server::Settings {
+            world_seed: seed,
+            map_file: None,
+            // The benchmark moves the character along its path by setting its position
+            validate_player_movement: false,
+            // Only the NPCs of the benchmark, so that every run sees the same ones
+            spawning: server::spawning::SpawnSettings {
+                enabled: false,
+                ..Default::default()
+            },
+            ..server::Settings::singleplayer(&server_data_dir)
+        };
+        let editable_settings = server::EditableSettings::singleplayer(&server_data_dir);
+
+        Self::start(None, server_data_dir, settings, editable_settings)
+    }
+
+    fn start(
+        client: Option<&Client>,
+        server_data_dir: std::path::PathBuf,
+        settings: server::Settings,
+        editable_settings: server::EditableSettings,
+    ) -> (Self, server::Settings) {
+        let (sender, receiver) = unbounded();
+
         let thread_pool = client.map(|c| c.thread_pool().clone());
         let settings2 = settings.clone();
 
@@ -136,6 +178,13 @@ impl Singleplayer {
     /// Pauses if true is passed and unpauses if false (Does nothing if in that
     /// state already)
     pub fn pause(&self, state: bool) { self.paused.store(state, Ordering::SeqCst); }
+
+    /// Spawns the NPCs of a benchmark around `center`
+    pub fn spawn_benchmark_npcs(&self, npcs: Vec<NpcGroup>, center: Vec3<f32>, seed: u32) {
+        let _ = self
+            .sender
+            .send(Msg::SpawnBenchmarkNpcs { npcs, center, seed });
+    }
 }
 
 impl Drop for Singleplayer {
@@ -156,6 +205,9 @@ fn run_server(mut server: Server, rec: Receiver<Msg>, paused: Arc<AtomicBool>) {
         match rec.try_recv() {
             Ok(msg) => match msg {
                 Msg::Stop => break,
+                Msg::SpawnBenchmarkNpcs { npcs, center, seed } => {
+                    benchmark::spawn_npcs(&mut server, &npcs, center, seed)
+                },
             },
             Err(err) => match err {
                 TryRecvError::Empty => (),
@@ -182,6 +234,8 @@ fn run_server(mut server: Server, rec: Receiver<Msg>, paused: Arc<AtomicBool>) {
                 Event::ClientConnected { .. } => info!("Client connected!"),
                 Event::ClientDisconnected { .. } => info!("Client disconnected!"),
                 Event::Chat { entity: _, msg } => info!("[Client] {}", msg),
+                // Singleplayer servers don't have a schedule
+                Event::Shutdown { .. } => {},
             }
         }
 
@@ -75,6 +75,18 @@ impl Scale {
     // Get scaled window size.
     pub fn scaled_window_size(&self) -> Vec2<f64> { self.window_dims / self.scale_factor_logical() }
 
+    // Get the scaled size of the area that HUD elements are anchored to. This is
+    // the whole window, unless the window is wider than `max_aspect_ratio`, in
+    // which case it is narrowed to that aspect ratio so that elements pinned to
+    // the edges don't end up at the far sides of ultrawide screens.
+    pub fn anchor_area_size(&self, max_aspect_ratio: Option<f64>) -> Vec2<f64> {
+        let dims = self.scaled_window_size();
+        match max_aspect_ratio {
+            Some(ratio) => Vec2::new(dims.x.min(dims.y * ratio), dims.y),
+            None => dims,
+        }
+    }
+
     // Transform point from logical to scaled coordinates.
     pub fn scale_point(&self, point: Vec2<f64>) -> Vec2<f64> { point / self.scale_factor_logical() }
 }
@@ -54,6 +54,7 @@ pub enum GameInput {
     Map,
     Bag,
     Social,
+    CombatLog,
     Crafting,
     Spellbook,
     Settings,
@@ -106,6 +107,7 @@ impl GameInput {
             GameInput::Map => "gameinput.map",
             GameInput::Bag => "gameinput.bag",
             GameInput::Social => "gameinput.social",
+            GameInput::CombatLog => "gameinput.combatlog",
             GameInput::Crafting => "gameinput.crafting",
             GameInput::Spellbook => "gameinput.spellbook",
             GameInput::Settings => "gameinput.settings",
@@ -165,6 +167,7 @@ impl GameInput {
             GameInput::Map,
             GameInput::Bag,
             GameInput::Social,
+            GameInput::CombatLog,
             GameInput::Crafting,
             GameInput::Spellbook,
             GameInput::Settings,
@@ -934,6 +937,7 @@ impl Window {
                         KeyMouse::Mouse(button),
                         controls,
                         &mut self.remapping_keybindings,
+                        &mut self.events,
                     ),
                 ) {
                     for game_input in game_inputs {
@@ -976,6 +980,7 @@ impl Window {
                         KeyMouse::Key(key),
                         controls,
                         &mut self.remapping_keybindings,
+                        &mut self.events,
                     ) {
                         for game_input in game_inputs {
                             match game_input {
@@ -1355,12 +1360,14 @@ impl Window {
         key_mouse: KeyMouse,
         controls: &'a mut ControlSettings,
         remapping: &mut Option<GameInput>,
+        events: &mut Vec<Event>,
     ) -> Option<impl Iterator<Item = &'a GameInput>> {
         match *remapping {
-            // TODO: save settings
             Some(game_input) => {
                 controls.modify_binding(game_input, key_mouse);
                 *remapping = None;
+                // Let the current play state know so the new binding gets saved
+                events.push(Event::SettingsChanged);
                 None
             },
             None => {
@@ -5,8 +5,8 @@ mod econ;
 use self::{Occupation::*, Stock::*};
 use crate::{
     config::CONFIG,
-    sim::WorldSim,
-    site::{Castle, Dungeon, Settlement, Site as WorldSite},
+    sim::{location::generate_name, WorldSim},
+    site::{Castle, Dungeon, Ruins, Settlement, Site as WorldSite},
     util::{attempt, seed_expan, MapVec, CARDINALS, NEIGHBORS},
     Index,
 };
@@ -101,10 +101,13 @@ impl Civs {
             attempt(5, || {
                 let (kind, size) = match ctx.rng.gen_range(0, 8) {
                     0 => (SiteKind::Castle, 3),
+                    1 | 2 => (SiteKind::Ruins, 1),
                     _ => (SiteKind::Dungeon, 0),
                 };
                 let loc = find_site_loc(&mut ctx, None, size)?;
+                let name = generate_name(&mut ctx.rng);
                 this.establish_site(&mut ctx.reseed(), loc, |place| Site {
+                    name,
                     kind,
                     center: loc,
                     place,
@@ -143,6 +146,7 @@ impl Civs {
                 SiteKind::Settlement => 10.0,
                 SiteKind::Dungeon => 2.0,
                 SiteKind::Castle => 5.0,
+                SiteKind::Ruins => 4.0,
             };
 
             let (raise, raise_dist): (f32, i32) = match &site.kind {
@@ -208,6 +212,7 @@ impl Civs {
                 SiteKind::Castle => {
                     WorldSite::castle(Castle::generate(wpos, Some(ctx.sim), &mut rng))
                 },
+                SiteKind::Ruins => WorldSite::ruins(Ruins::generate(wpos, Some(ctx.sim), &mut rng)),
             });
             let site_ref = &index.sites[site];
 
@@ -369,7 +374,9 @@ impl Civs {
     fn birth_civ(&mut self, ctx: &mut GenCtx<impl Rng>) -> Option<Id<Civ>> {
         let site = attempt(5, || {
             let loc = find_site_loc(ctx, None, 1)?;
+            let name = generate_name(&mut ctx.rng);
             self.establish_site(ctx, loc, |place| Site {
+                name,
                 kind: SiteKind::Settlement,
                 center: loc,
                 place,
@@ -795,6 +802,7 @@ pub struct Track {
 
 #[derive(Debug)]
 pub struct Site {
+    pub name: String,
     pub kind: SiteKind,
     pub center: Vec2<i32>,
     pub place: Id<Place>,
@@ -858,11 +866,12 @@ impl fmt::Display for Site {
     }
 }
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SiteKind {
     Settlement,
     Dungeon,
     Castle,
+    Ruins,
 }
 
 impl Site {
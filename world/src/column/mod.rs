@@ -8,7 +8,7 @@ use crate::{
 use common::{
     terrain::{
         quadratic_nearest_point, river_spline_coeffs, uniform_idx_as_vec2, vec2_as_uniform_idx,
-        TerrainChunkSize,
+        BiomeKind, TerrainChunkSize,
     },
     vol::RectVolSize,
 };
@@ -39,6 +39,8 @@ pub struct Colors {
     pub desert_sand: (f32, f32, f32),
     pub snow: (f32, f32, f32),
     pub snow_moss: (f32, f32, f32),
+    pub swamp_grass: (f32, f32, f32),
+    pub swamp_mud: (f32, f32, f32),
 
     pub stone_col: (u8, u8, u8),
 
@@ -805,6 +807,8 @@ impl<'a> Sampler<'a> for ColumnGen<'a> {
             desert_sand,
             snow,
             snow_moss,
+            swamp_grass,
+            swamp_mud,
             stone_col,
             dirt_low,
             dirt_high,
@@ -980,6 +984,20 @@ impl<'a> Sampler<'a> for ColumnGen<'a> {
             humidity.sub(CONFIG.jungle_hum).mul(1.0),
         );
 
+        // Swamps are muddy, with patches of dull grass
+        let swampiness = sim.get_interpolated_monotone(wpos, |chunk| {
+            if chunk.get_biome() == BiomeKind::Swamp {
+                1.0
+            } else {
+                0.0
+            }
+        })?;
+        let ground = Rgb::lerp(
+            ground,
+            Rgb::lerp(swamp_mud.into(), swamp_grass.into(), marble.powf(1.5)),
+            swampiness,
+        );
+
         // Snow covering
         let snow_cover = temp
             .sub(CONFIG.snow_temp)
@@ -106,6 +106,9 @@ impl IndexOwned {
 pub struct Noise {
     pub cave_nz: SuperSimplex,
     pub scatter_nz: SuperSimplex,
+    /// Tunnels are carved where both of these are close to zero
+    pub tunnel_nz: (SuperSimplex, SuperSimplex),
+    pub ore_nz: SuperSimplex,
 }
 
 impl Noise {
@@ -114,6 +117,11 @@ impl Noise {
         Self {
             cave_nz: SuperSimplex::new().set_seed(seed + 0),
             scatter_nz: SuperSimplex::new().set_seed(seed + 1),
+            tunnel_nz: (
+                SuperSimplex::new().set_seed(seed + 2),
+                SuperSimplex::new().set_seed(seed + 3),
+            ),
+            ore_nz: SuperSimplex::new().set_seed(seed + 4),
         }
     }
 }
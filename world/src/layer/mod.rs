@@ -1,6 +1,10 @@
 pub mod scatter;
+pub mod template;
+pub mod underground;
 
-pub use self::scatter::apply_scatter_to;
+pub use self::{
+    scatter::apply_scatter_to, template::apply_templates_to, underground::apply_underground_to,
+};
 
 use crate::{
     column::ColumnSample,
@@ -28,6 +32,9 @@ use vek::*;
 pub struct Colors {
     pub bridge: (u8, u8, u8),
     pub stalagtite: (u8, u8, u8),
+    pub ore_shallow: (u8, u8, u8),
+    pub ore_mid: (u8, u8, u8),
+    pub ore_deep: (u8, u8, u8),
 }
 
 const EMPTY_AIR: Block = Block::air(SpriteKind::Empty);
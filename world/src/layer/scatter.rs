@@ -1,4 +1,9 @@
-use crate::{column::ColumnSample, sim::SimChunk, util::RandomField, IndexRef, CONFIG};
+use crate::{
+    column::ColumnSample,
+    sim::{SimChunk, Vegetation},
+    util::RandomField,
+    IndexRef, CONFIG,
+};
 use common::{
     terrain::{Block, SpriteKind},
     vol::{BaseVol, ReadVol, RectSizedVol, WriteVol},
@@ -285,6 +290,7 @@ pub fn apply_scatter_to<'a>(
         // Underwater chests
         (Chest, true, |_, _| (MUSH_FACT * 0.1, None)),
     ];
+    let plants = Vegetation::of(chunk.get_biome()).plants;
 
     for y in 0..vol.size_xy().y as i32 {
         for x in 0..vol.size_xy().x as i32 {
@@ -306,6 +312,11 @@ pub fn apply_scatter_to<'a>(
                 .enumerate()
                 .find_map(|(i, (kind, is_underwater, f))| {
                     let (density, patch) = f(chunk, col_sample);
+                    let density = if *kind == Chest {
+                        density
+                    } else {
+                        density * plants
+                    };
                     let is_patch = patch
                         .map(|(wavelen, threshold)| {
                             index
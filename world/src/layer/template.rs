@@ -0,0 +1,322 @@
+//! Voxel structures placed in the world as described by a manifest
+//!
+//! Each entry of the `world.manifests.templates` manifest names a `.vox`
+//! model and how it is scattered over the world: how far copies are apart, in
+//! which biomes they appear, whether they are rotated, how they sit on the
+//! terrain and which blocks of the terrain they may replace. This lets new
+//! houses, ruins and the like be added without touching any code.
+//!
+//! Like the rest of worldgen, placement only depends on the world seed, so a
+//! template that spans several chunks is placed the same way in each of them.
+
+use crate::{
+    block::block_from_structure,
+    column::ColumnSample,
+    sim::WorldSim,
+    util::{RandomField, Sampler},
+    IndexRef,
+};
+use common::{
+    assets::{Asset, Ron},
+    terrain::{structure::StructureBlock, BiomeKind, Block, BlockKind, Structure},
+    vol::{BaseVol, ReadVol, RectSizedVol, WriteVol},
+};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::sync::Arc;
+use vek::*;
+
+const TEMPLATES_MANIFEST: &str = "world.manifests.templates";
+/// Keeps the placement of templates apart from other things seeded by the
+/// world seed
+const TEMPLATE_SEED: u32 = 0x7e3a_91c5;
+
+/// How a template is lifted or lowered onto the terrain below it
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub enum Snap {
+    /// The lowest ground below the template, so it is partly buried on slopes
+    Lowest,
+    /// The average of the ground below the template
+    Average,
+    /// The highest ground below the template, so nothing of it is buried
+    Highest,
+}
+
+impl Default for Snap {
+    fn default() -> Self { Snap::Average }
+}
+
+/// Which blocks of the terrain a template may replace
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize)]
+pub enum Replace {
+    /// Any block, the template is placed as it is
+    All,
+    /// Only air and water, the template merges with the terrain it's in
+    Empty,
+    /// Only solid blocks, for things embedded in rock or earth
+    Solid,
+}
+
+impl Default for Replace {
+    fn default() -> Self { Replace::All }
+}
+
+impl Replace {
+    fn allows(self, block: Block) -> bool {
+        match self {
+            Replace::All => true,
+            Replace::Empty => !block.is_solid(),
+            Replace::Solid => block.is_solid(),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+pub struct TemplateSpec {
+    /// Asset specifier of the `.vox` model
+    pub specifier: String,
+    /// Position in the model that is placed on the ground
+    pub center: [i32; 3],
+    /// The world is divided into cells of this size in blocks, each of which
+    /// holds at most one copy of the template
+    pub spacing: u32,
+    /// Chance of a cell holding a copy of the template
+    pub chance: f32,
+    /// Biomes the template appears in, or any biome but the ocean if empty
+    #[serde(default)]
+    pub biomes: Vec<BiomeKind>,
+    /// Rotate each copy by a random multiple of 90 degrees
+    #[serde(default)]
+    pub rotate: bool,
+    #[serde(default)]
+    pub snap: Snap,
+    /// Moves the template up or down after it was snapped to the terrain
+    #[serde(default)]
+    pub z_offset: i32,
+    /// Extend the bottom layer of the template down to the ground, so it
+    /// doesn't float where the ground is lower
+    #[serde(default)]
+    pub foundation: bool,
+    #[serde(default)]
+    pub replace: Replace,
+    /// Kind of the blocks made of colored voxels of the model
+    #[serde(default = "default_block_kind")]
+    pub block_kind: BlockKind,
+}
+
+fn default_block_kind() -> BlockKind { BlockKind::Misc }
+
+struct Template {
+    spec: TemplateSpec,
+    structure: Arc<Structure>,
+    /// Horizontal distance from the center the template can reach in any
+    /// rotation
+    radius: i32,
+}
+
+impl Template {
+    fn load_all() -> Vec<Self> {
+        Ron::<Vec<TemplateSpec>>::load_expect_cloned(TEMPLATES_MANIFEST)
+            .into_iter()
+            .map(|spec| {
+                let center = Vec3::from(spec.center);
+                let structure = Structure::load_map(&spec.specifier, |s| s.with_center(center))
+                    .unwrap_or_else(|_| panic!("Could not load template {}", spec.specifier));
+                let bounds = structure.get_bounds();
+                let radius = bounds
+                    .min
+                    .xy()
+                    .map(i32::abs)
+                    .reduce_max()
+                    .max(bounds.max.xy().map(i32::abs).reduce_max());
+                Self {
+                    spec,
+                    structure,
+                    radius,
+                }
+            })
+            .collect()
+    }
+}
+
+lazy_static! {
+    static ref TEMPLATES: Vec<Template> = Template::load_all();
+}
+
+/// A copy of a template in the world
+struct Placement {
+    origin: Vec3<i32>,
+    /// Number of quarter turns counterclockwise
+    rotation: u32,
+    seed: u32,
+}
+
+impl Placement {
+    /// Position in the template of the block at an offset from the origin
+    fn to_template(&self, offs: Vec3<i32>) -> Vec3<i32> {
+        let xy = match self.rotation {
+            0 => offs.xy(),
+            1 => Vec2::new(offs.y, -offs.x),
+            2 => -offs.xy(),
+            _ => Vec2::new(-offs.y, offs.x),
+        };
+        Vec3::new(xy.x, xy.y, offs.z)
+    }
+}
+
+/// Copies of the template with the given index that might reach into the
+/// area between `min` and `max`
+fn placements(
+    template: &Template,
+    i: usize,
+    sim: &WorldSim,
+    seed: u32,
+    min: Vec2<i32>,
+    max: Vec2<i32>,
+) -> Vec<Placement> {
+    let spec = &template.spec;
+    let spacing = spec.spacing.max(1) as i32;
+    // Copies are kept away from the edges of their cell so that they don't
+    // overlap copies in neighbouring cells
+    let jitter = (spacing / 2 - template.radius).max(0);
+    let field = RandomField::new(seed.wrapping_add(TEMPLATE_SEED).wrapping_add(i as u32));
+
+    let min_cell = (min - template.radius).map(|e| e.div_euclid(spacing));
+    let max_cell = (max + template.radius).map(|e| e.div_euclid(spacing));
+    let mut placements = Vec::new();
+    for y in min_cell.y..=max_cell.y {
+        for x in min_cell.x..=max_cell.x {
+            let cell = Vec3::new(x, y, 0);
+            let seed = field.get(cell);
+            if (seed % 1024) as f32 / 1024.0 >= spec.chance {
+                continue;
+            }
+            let jitter = Vec2::new(field.get(cell + 1), field.get(cell - 1))
+                .map(|e| (e % (jitter as u32 * 2 + 1)) as i32 - jitter);
+            let wpos = Vec2::new(x, y) * spacing + spacing / 2 + jitter;
+
+            // Keep out of sites and water, and away from the wrong biomes
+            let chunk = match sim.get_wpos(wpos) {
+                Some(chunk) => chunk,
+                None => continue,
+            };
+            let biome = chunk.get_biome();
+            if !chunk.sites.is_empty()
+                || chunk.is_underwater()
+                || chunk.river.is_river()
+                || if spec.biomes.is_empty() {
+                    biome == BiomeKind::Ocean
+                } else {
+                    !spec.biomes.contains(&biome)
+                }
+            {
+                continue;
+            }
+
+            // Sample the ground at the corners of the footprint
+            let r = template.radius;
+            let alts = [
+                Vec2::new(-r, -r),
+                Vec2::new(r, -r),
+                Vec2::new(-r, r),
+                Vec2::new(r, r),
+                Vec2::zero(),
+            ]
+            .iter()
+            .filter_map(|offs| sim.get_alt_approx(wpos + offs))
+            .collect::<Vec<_>>();
+            if alts.is_empty() {
+                continue;
+            }
+            let alt = match spec.snap {
+                Snap::Lowest => alts.iter().copied().fold(f32::MAX, f32::min),
+                Snap::Average => alts.iter().sum::<f32>() / alts.len() as f32,
+                Snap::Highest => alts.iter().copied().fold(f32::MIN, f32::max),
+            };
+
+            placements.push(Placement {
+                origin: Vec3::new(wpos.x, wpos.y, alt as i32 + spec.z_offset),
+                rotation: if spec.rotate { (seed >> 10) % 4 } else { 0 },
+                seed,
+            });
+        }
+    }
+    placements
+}
+
+pub fn apply_templates_to<'a>(
+    wpos2d: Vec2<i32>,
+    mut get_column: impl FnMut(Vec2<i32>) -> Option<&'a ColumnSample<'a>>,
+    vol: &mut (impl BaseVol<Vox = Block> + RectSizedVol + ReadVol + WriteVol),
+    index: IndexRef,
+    sim: &WorldSim,
+) {
+    let size = vol.size_xy().map(|e| e as i32);
+
+    for (i, template) in TEMPLATES.iter().enumerate() {
+        let spec = &template.spec;
+        let bounds = template.structure.get_bounds();
+
+        for placement in placements(template, i, sim, index.seed, wpos2d, wpos2d + size) {
+            let with_kind = |block: Block| match block.kind() {
+                BlockKind::Misc => {
+                    Block::new(spec.block_kind, block.get_color().unwrap_or_default())
+                },
+                _ => block,
+            };
+
+            for y in 0..size.y {
+                for x in 0..size.x {
+                    let offs = Vec2::new(x, y);
+                    let rpos = wpos2d + offs - placement.origin.xy();
+                    if rpos.map(i32::abs).reduce_max() > template.radius {
+                        continue;
+                    }
+                    let col_sample = if let Some(col_sample) = get_column(offs) {
+                        col_sample
+                    } else {
+                        continue;
+                    };
+
+                    let get_block = |z| {
+                        let tpos = placement.to_template(Vec3::new(rpos.x, rpos.y, z));
+                        let sblock = *template.structure.get(tpos).ok()?;
+                        let block = block_from_structure(
+                            index,
+                            sblock,
+                            tpos,
+                            placement.origin.xy(),
+                            placement.seed,
+                            col_sample,
+                            Block::air,
+                        )?;
+                        Some((sblock, with_kind(block)))
+                    };
+
+                    for z in bounds.min.z..bounds.max.z {
+                        let pos = Vec3::new(offs.x, offs.y, placement.origin.z + z);
+                        if let Some((sblock, block)) = get_block(z) {
+                            // Hollow voxels always carve out the terrain
+                            if sblock == StructureBlock::Hollow
+                                || vol.get(pos).map_or(false, |b| spec.replace.allows(*b))
+                            {
+                                let _ = vol.set(pos, block);
+                            }
+                        }
+                    }
+
+                    if spec.foundation {
+                        if let Some((_, block)) =
+                            get_block(bounds.min.z).filter(|(_, block)| block.is_solid())
+                        {
+                            let ground = col_sample.alt as i32 - 1;
+                            for z in ground..placement.origin.z + bounds.min.z {
+                                let _ = vol.set(Vec3::new(offs.x, offs.y, z), block);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Tunnels, ore veins and rooms beneath the surface
+//!
+//! Tunnels wind through the rock where two 3D noise fields are both close to
+//! zero. Ore veins run through the walls of tunnels, with richer ore deeper
+//! down, and some chunks hide a room further below. Everything is derived from
+//! the world seed and block positions alone, so a world always has the same
+//! underground no matter in which order, or by whom, its chunks are generated.
+
+use crate::{
+    column::ColumnSample,
+    sim::SimChunk,
+    util::{RandomField, Sampler},
+    IndexRef,
+};
+use common::{
+    terrain::{Block, BlockKind, SpriteKind},
+    vol::{BaseVol, ReadVol, RectSizedVol, WriteVol},
+};
+use noise::NoiseFn;
+use std::ops::Range;
+use vek::*;
+
+/// Depths below the surface between which tunnels are carved
+const TUNNEL_DEPTH: Range<f32> = 20.0..100.0;
+/// Horizontal frequency of the tunnel noise, lower values make longer and
+/// straighter tunnels
+const TUNNEL_FREQ: f64 = 1.0 / 48.0;
+/// Tunnels are squashed vertically so that they are easier to walk through
+const TUNNEL_SQUASH: f64 = 2.0;
+/// Largest value of both noise fields inside of a tunnel
+const TUNNEL_WIDTH: f64 = 0.06;
+/// Tunnel walls reach this far out in noise units, ore veins only run through
+/// them so that they can be seen from inside the tunnels
+const WALL_WIDTH: f64 = 0.1;
+
+const ORE_FREQ: f64 = 1.0 / 12.0;
+/// Ore runs through the tunnel walls where the ore noise is above this
+const ORE_THRESHOLD: f64 = 0.55;
+/// Ore gets richer below these depths
+const ORE_TIER_DEPTHS: [f32; 2] = [45.0, 75.0];
+
+/// Offset from the world seed of the random fields rooms are placed with
+const ROOM_SEED: u32 = 100;
+/// Chance of a chunk having a room
+const ROOM_CHANCE: f32 = 0.05;
+/// Depths below the altitude of the chunk at which room floors are placed
+const ROOM_DEPTH: Range<u32> = 30..60;
+/// Rooms are never dug closer than this to the surface
+const ROOM_MIN_COVER: f32 = 8.0;
+
+/// A room dug into the rock of a chunk
+struct Room {
+    /// Position of the room's floor relative to the chunk, at its lowest
+    /// corner
+    min: Vec3<i32>,
+    size: Vec3<i32>,
+}
+
+impl Room {
+    /// The room in the chunk at `chunk_wpos`, if it has one
+    fn of_chunk(
+        index: IndexRef,
+        chunk_wpos: Vec2<i32>,
+        chunk: &SimChunk,
+        size: Vec2<i32>,
+    ) -> Option<Self> {
+        let seed = |i: u32| RandomField::new(index.seed + ROOM_SEED + i).get(chunk_wpos.into());
+        if !RandomField::new(index.seed + ROOM_SEED).chance(chunk_wpos.into(), ROOM_CHANCE)
+            || chunk.is_underwater()
+        {
+            return None;
+        }
+
+        let room_size =
+            Vec3::new(8 + seed(1) % 8, 8 + seed(2) % 8, 5 + seed(3) % 3).map(|e| e as i32);
+        let depth = ROOM_DEPTH.start + seed(4) % (ROOM_DEPTH.end - ROOM_DEPTH.start);
+        let margin = (size - room_size.xy()).map(|e| e.max(1) as u32);
+        Some(Self {
+            min: Vec3::new(
+                (seed(5) % margin.x) as i32,
+                (seed(6) % margin.y) as i32,
+                chunk.alt as i32 - depth as i32,
+            ),
+            size: room_size,
+        })
+    }
+
+    fn contains(&self, offs: Vec2<i32>) -> bool {
+        offs.x >= self.min.x
+            && offs.y >= self.min.y
+            && offs.x < self.min.x + self.size.x
+            && offs.y < self.min.y + self.size.y
+    }
+}
+
+pub fn apply_underground_to<'a>(
+    wpos2d: Vec2<i32>,
+    mut get_column: impl FnMut(Vec2<i32>) -> Option<&'a ColumnSample<'a>>,
+    vol: &mut (impl BaseVol<Vox = Block> + RectSizedVol + ReadVol + WriteVol),
+    index: IndexRef,
+    chunk: &SimChunk,
+) {
+    let size = vol.size_xy().map(|e| e as i32);
+    let room = Room::of_chunk(index, wpos2d, chunk, size);
+    let ore_colors = [
+        index.colors.layer.ore_shallow,
+        index.colors.layer.ore_mid,
+        index.colors.layer.ore_deep,
+    ];
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let offs = Vec2::new(x, y);
+
+            let wpos2d = wpos2d + offs;
+
+            // Sample terrain
+            let col_sample = if let Some(col_sample) = get_column(offs) {
+                col_sample
+            } else {
+                continue;
+            };
+
+            // Tunnels and ore veins
+            let min_z = (col_sample.alt - TUNNEL_DEPTH.end) as i32;
+            let max_z = (col_sample.alt - TUNNEL_DEPTH.start) as i32;
+            for z in min_z..max_z {
+                let wpos = Vec3::new(wpos2d.x, wpos2d.y, z);
+                let pos = wpos.map(|e| e as f64) * Vec3::new(1.0, 1.0, TUNNEL_SQUASH) * TUNNEL_FREQ;
+                let a = index.noise.tunnel_nz.0.get(pos.into_array()).abs();
+                if a > WALL_WIDTH {
+                    continue;
+                }
+                let b = index.noise.tunnel_nz.1.get(pos.into_array()).abs();
+                let lpos = Vec3::new(offs.x, offs.y, z);
+
+                if a < TUNNEL_WIDTH && b < TUNNEL_WIDTH {
+                    let _ = vol.set(lpos, Block::air(SpriteKind::Empty));
+                } else if b < WALL_WIDTH
+                    && index
+                        .noise
+                        .ore_nz
+                        .get((wpos.map(|e| e as f64) * ORE_FREQ).into_array())
+                        > ORE_THRESHOLD
+                {
+                    let depth = col_sample.alt - z as f32;
+                    let tier = ORE_TIER_DEPTHS.iter().filter(|d| depth > **d).count();
+                    let _ = vol.map(lpos, |block| {
+                        if block.kind() == BlockKind::Rock || block.kind() == BlockKind::WeakRock {
+                            Block::new(BlockKind::WeakRock, ore_colors[tier].into())
+                        } else {
+                            block
+                        }
+                    });
+                }
+            }
+
+            // Rooms
+            if let Some(room) = room.as_ref().filter(|room| room.contains(offs)) {
+                let roof = room.min.z + room.size.z;
+                if col_sample.alt - (roof as f32) < ROOM_MIN_COVER {
+                    continue;
+                }
+                for z in room.min.z..roof {
+                    let _ = vol.set(Vec3::new(offs.x, offs.y, z), Block::air(SpriteKind::Empty));
+                }
+                // Whoever dug the room left something behind in the middle of it
+                if offs == room.min.xy() + room.size.xy() / 2 {
+                    let _ = vol.set(
+                        Vec3::new(offs.x, offs.y, room.min.z),
+                        Block::air(SpriteKind::Chest),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -38,8 +38,10 @@ use crate::{
 use common::{
     comp::{self, bird_medium, quadruped_low, quadruped_medium, quadruped_small},
     generation::{ChunkSupplement, EntityInfo},
-    msg::WorldMapMsg,
-    terrain::{Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta, TerrainChunkSize},
+    msg::{WorldMapMsg, WorldOverview},
+    terrain::{
+        BiomeKind, Block, BlockKind, SpriteKind, TerrainChunk, TerrainChunkMeta, TerrainChunkSize,
+    },
     vol::{ReadVol, RectVolSize, WriteVol},
 };
 use rand::Rng;
@@ -66,6 +68,15 @@ pub struct Colors {
     pub site: site::Colors,
 }
 
+/// What a site of the world is called, what it is and where it is
+#[derive(Clone, Debug)]
+pub struct SiteInfo {
+    pub name: String,
+    pub kind: civ::SiteKind,
+    /// Center of the site
+    pub wpos: Vec2<i32>,
+}
+
 impl World {
     pub fn generate(seed: u32, opts: sim::WorldOpts) -> (Self, IndexOwned) {
         // NOTE: Generating index first in order to quickly fail if the color manifest
@@ -83,12 +94,30 @@ impl World {
 
     pub fn civs(&self) -> &civ::Civs { &self.civs }
 
+    /// The towns, dungeons, castles and ruins of the world
+    pub fn sites(&self) -> impl Iterator<Item = SiteInfo> + '_ {
+        self.civs.sites().map(|site| SiteInfo {
+            name: site.name.clone(),
+            kind: site.kind,
+            wpos: site.center.map2(TerrainChunkSize::RECT_SIZE, |e, sz: u32| {
+                e * sz as i32 + sz as i32 / 2
+            }),
+        })
+    }
+
     pub fn tick(&self, _dt: Duration) {
         // TODO
     }
 
     pub fn get_map_data(&self, index: IndexRef) -> WorldMapMsg { self.sim.get_map(index) }
 
+    pub fn get_overview(&self) -> WorldOverview { self.sim.get_overview() }
+
+    /// The biome at a world position, or `None` outside of the world
+    pub fn get_biome(&self, wpos: Vec2<i32>) -> Option<BiomeKind> {
+        self.sim.get_wpos(wpos).map(|chunk| chunk.get_biome())
+    }
+
     pub fn sample_columns(
         &self,
     ) -> impl Sampler<Index = (Vec2<i32>, IndexRef), Sample = Option<ColumnSample>> + '_ {
@@ -202,8 +231,10 @@ impl World {
 
         // Apply layers (paths, caves, etc.)
         layer::apply_caves_to(chunk_wpos2d, sample_get, &mut chunk, index);
+        layer::apply_underground_to(chunk_wpos2d, sample_get, &mut chunk, index, sim_chunk);
         layer::apply_scatter_to(chunk_wpos2d, sample_get, &mut chunk, index, sim_chunk);
         layer::apply_paths_to(chunk_wpos2d, sample_get, &mut chunk, index);
+        layer::apply_templates_to(chunk_wpos2d, sample_get, &mut chunk, index, &self.sim);
 
         // Apply site generation
         sim_chunk.sites.iter().for_each(|site| {
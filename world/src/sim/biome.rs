@@ -0,0 +1,56 @@
+use crate::CONFIG;
+use common::terrain::BiomeKind;
+
+/// Mountains are chaotic enough to be mountains whatever their climate
+const MOUNTAIN_CHAOS: f32 = 0.6;
+/// Swamps only form on flat land...
+const SWAMP_MAX_CHAOS: f32 = 0.2;
+/// ...not far above the sea
+const SWAMP_MAX_HEIGHT: f32 = 40.0;
+
+/// The biome of land with the given altitude and chaos, in a climate with the
+/// given temperature and humidity
+pub fn classify_biome(alt: f32, chaos: f32, temp: f32, humidity: f32) -> BiomeKind {
+    if alt < CONFIG.sea_level {
+        BiomeKind::Ocean
+    } else if chaos > MOUNTAIN_CHAOS {
+        BiomeKind::Mountain
+    } else if temp < CONFIG.snow_temp {
+        BiomeKind::Snowlands
+    } else if temp > CONFIG.desert_temp || humidity < CONFIG.desert_hum {
+        BiomeKind::Desert
+    } else if humidity > CONFIG.jungle_hum
+        && chaos < SWAMP_MAX_CHAOS
+        && alt < CONFIG.sea_level + SWAMP_MAX_HEIGHT
+    {
+        BiomeKind::Swamp
+    } else if humidity > CONFIG.forest_hum {
+        BiomeKind::Forest
+    } else {
+        BiomeKind::Grassland
+    }
+}
+
+/// How densely a biome is covered in plants, relative to what its climate
+/// alone allows
+#[derive(Copy, Clone, Debug)]
+pub struct Vegetation {
+    pub trees: f32,
+    /// Flowers, grasses, bushes and other scattered plants
+    pub plants: f32,
+}
+
+impl Vegetation {
+    pub fn of(biome: BiomeKind) -> Self {
+        let (trees, plants) = match biome {
+            BiomeKind::Forest => (1.0, 1.0),
+            BiomeKind::Grassland => (0.5, 1.2),
+            BiomeKind::Swamp => (0.7, 1.5),
+            BiomeKind::Mountain => (0.6, 0.6),
+            BiomeKind::Snowlands => (0.8, 0.5),
+            BiomeKind::Desert => (0.5, 0.4),
+            BiomeKind::Ocean | BiomeKind::Void => (1.0, 1.0),
+        };
+        Self { trees, plants }
+    }
+}
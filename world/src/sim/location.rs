@@ -36,7 +36,7 @@ pub struct Kingdom {
     region_name: String,
 }
 
-fn generate_name(rng: &mut impl Rng) -> String {
+pub(crate) fn generate_name(rng: &mut impl Rng) -> String {
     let firstsyl = [
         "Eri", "Val", "Gla", "Wilde", "Cold", "Deep", "Dura", "Ester", "Fay", "Dark", "West",
         "East", "North", "South", "Ray", "Eri", "Dal", "Som", "Sommer", "Black", "Iron", "Grey",
@@ -1,6 +1,7 @@
+mod biome;
 mod diffusion;
 mod erosion;
-mod location;
+pub(crate) mod location;
 mod map;
 mod util;
 mod way;
@@ -8,6 +9,7 @@ mod way;
 // Reexports
 use self::erosion::Compute;
 pub use self::{
+    biome::{classify_biome, Vegetation},
     diffusion::diffusion,
     erosion::{
         do_erosion, fill_sinks, get_drainage, get_lakes, get_multi_drainage, get_multi_rec,
@@ -33,7 +35,10 @@ use crate::{
 };
 use common::{
     assets,
-    msg::WorldMapMsg,
+    msg::{
+        world_msg::{RegionOverview, OVERVIEW_REGION_LG},
+        WorldMapMsg, WorldOverview,
+    },
     store::Id,
     terrain::{
         map::MapConfig, uniform_idx_as_vec2, vec2_as_uniform_idx, BiomeKind, MapSizeLg,
@@ -41,6 +46,7 @@ use common::{
     },
     vol::RectVolSize,
 };
+use hashbrown::HashMap;
 use noise::{
     BasicMulti, Billow, Fbm, HybridMulti, MultiFractal, NoiseFn, RangeFunction, RidgedMulti,
     Seedable, SuperSimplex, Worley,
@@ -1406,6 +1412,38 @@ impl WorldSim {
 
     pub fn get_size(&self) -> Vec2<u32> { self.map_size_lg().chunks().map(u32::from) }
 
+    /// Summarize the biome and altitude of each region of the world, so that
+    /// clients can draw maps without its terrain
+    pub fn get_overview(&self) -> WorldOverview {
+        let size = WorldOverview::regions_in(self.map_size_lg().vec());
+        let regions = (0..size.x * size.y)
+            .into_par_iter()
+            .map(|i| {
+                let region =
+                    Vec2::new(i % size.x, i / size.x).map(|e| (e as i32) << OVERVIEW_REGION_LG);
+                let mut biomes = HashMap::new();
+                let (mut alt_sum, mut count) = (0.0, 0);
+                for y in 0..1 << OVERVIEW_REGION_LG {
+                    for x in 0..1 << OVERVIEW_REGION_LG {
+                        if let Some(chunk) = self.get(region + Vec2::new(x, y)) {
+                            *biomes.entry(chunk.get_biome()).or_insert(0) += 1;
+                            alt_sum += chunk.alt;
+                            count += 1;
+                        }
+                    }
+                }
+                match biomes.into_iter().max_by_key(|(_, n)| *n) {
+                    Some((biome, _)) => RegionOverview {
+                        biome,
+                        alt: (alt_sum / count as f32) as i16,
+                    },
+                    None => RegionOverview::VOID,
+                }
+            })
+            .collect();
+        WorldOverview::new(size, regions)
+    }
+
     /// Draw a map of the world based on chunk information.  Returns a buffer of
     /// u32s.
     pub fn get_map(&self, index: IndexRef) -> WorldMapMsg {
@@ -2172,6 +2210,9 @@ impl SimChunk {
             .mul(0.95)
             .add(0.5)
         } as f32;
+        // Plains and other open biomes have fewer trees than their climate alone allows
+        let tree_density =
+            tree_density * Vegetation::of(classify_biome(alt, chaos, temp, humidity)).trees;
 
         Self {
             chaos,
@@ -2286,18 +2327,6 @@ impl SimChunk {
     }
 
     pub fn get_biome(&self) -> BiomeKind {
-        if self.alt < CONFIG.sea_level {
-            BiomeKind::Ocean
-        } else if self.chaos > 0.6 {
-            BiomeKind::Mountain
-        } else if self.temp > CONFIG.desert_temp {
-            BiomeKind::Desert
-        } else if self.temp < CONFIG.snow_temp {
-            BiomeKind::Snowlands
-        } else if self.tree_density > 0.65 {
-            BiomeKind::Forest
-        } else {
-            BiomeKind::Grassland
-        }
+        classify_biome(self.alt, self.chaos, self.temp, self.humidity)
     }
 }
@@ -2,11 +2,12 @@ mod block_mask;
 mod castle;
 mod dungeon;
 pub mod economy;
+mod ruins;
 mod settlement;
 
 // Reexports
 pub use self::{
-    block_mask::BlockMask, castle::Castle, dungeon::Dungeon, economy::Economy,
+    block_mask::BlockMask, castle::Castle, dungeon::Dungeon, economy::Economy, ruins::Ruins,
     settlement::Settlement,
 };
 
@@ -24,6 +25,7 @@ use vek::*;
 pub struct Colors {
     pub castle: castle::Colors,
     pub dungeon: dungeon::Colors,
+    pub ruins: ruins::Colors,
     pub settlement: settlement::Colors,
 }
 
@@ -44,6 +46,7 @@ pub enum SiteKind {
     Settlement(Settlement),
     Dungeon(Dungeon),
     Castle(Castle),
+    Ruins(Ruins),
 }
 
 impl Site {
@@ -68,11 +71,19 @@ impl Site {
         }
     }
 
+    pub fn ruins(r: Ruins) -> Self {
+        Self {
+            kind: SiteKind::Ruins(r),
+            economy: Economy::default(),
+        }
+    }
+
     pub fn radius(&self) -> f32 {
         match &self.kind {
             SiteKind::Settlement(s) => s.radius(),
             SiteKind::Dungeon(d) => d.radius(),
             SiteKind::Castle(c) => c.radius(),
+            SiteKind::Ruins(r) => r.radius(),
         }
     }
 
@@ -81,6 +92,7 @@ impl Site {
             SiteKind::Settlement(s) => s.get_origin(),
             SiteKind::Dungeon(d) => d.get_origin(),
             SiteKind::Castle(c) => c.get_origin(),
+            SiteKind::Ruins(r) => r.get_origin(),
         }
     }
 
@@ -89,6 +101,7 @@ impl Site {
             SiteKind::Settlement(s) => s.spawn_rules(wpos),
             SiteKind::Dungeon(d) => d.spawn_rules(wpos),
             SiteKind::Castle(c) => c.spawn_rules(wpos),
+            SiteKind::Ruins(r) => r.spawn_rules(wpos),
         }
     }
 
@@ -103,6 +116,7 @@ impl Site {
             SiteKind::Settlement(s) => s.apply_to(index, wpos2d, get_column, vol),
             SiteKind::Dungeon(d) => d.apply_to(index, wpos2d, get_column, vol),
             SiteKind::Castle(c) => c.apply_to(index, wpos2d, get_column, vol),
+            SiteKind::Ruins(r) => r.apply_to(index, wpos2d, get_column, vol),
         }
     }
 
@@ -120,6 +134,7 @@ impl Site {
             },
             SiteKind::Dungeon(d) => d.apply_supplement(dynamic_rng, wpos2d, get_column, supplement),
             SiteKind::Castle(c) => c.apply_supplement(dynamic_rng, wpos2d, get_column, supplement),
+            SiteKind::Ruins(r) => r.apply_supplement(dynamic_rng, wpos2d, get_column, supplement),
         }
     }
 }
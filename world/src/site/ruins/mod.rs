@@ -0,0 +1,173 @@
+//! The remains of buildings abandoned long ago
+//!
+//! Ruins are a handful of rectangular buildings of which only crumbling,
+//! overgrown stone walls are left. Some of them still hide a chest that nobody
+//! came back for.
+
+use super::SpawnRules;
+use crate::{
+    column::ColumnSample,
+    sim::WorldSim,
+    util::{RandomField, Sampler},
+    IndexRef,
+};
+use common::{
+    generation::ChunkSupplement,
+    terrain::{Block, BlockKind, SpriteKind},
+    vol::{BaseVol, ReadVol, RectSizedVol, WriteVol},
+};
+use rand::prelude::*;
+use serde::Deserialize;
+use vek::*;
+
+/// Buildings are placed within this distance of the origin of the ruins
+const SPREAD: i32 = 24;
+/// How far below the ground wall foundations go
+const FOUNDATION_DEPTH: i32 = 2;
+
+#[derive(Deserialize)]
+pub struct Colors {
+    pub stone: (u8, u8, u8),
+    /// Top of walls that are covered in moss
+    pub moss: (u8, u8, u8),
+}
+
+struct Ruin {
+    /// Lowest corner of the building, relative to the origin of the ruins
+    offset: Vec2<i32>,
+    size: Vec2<i32>,
+    /// Height of the walls before they crumbled
+    height: i32,
+    alt: i32,
+    has_chest: bool,
+}
+
+impl Ruin {
+    fn contains(&self, rpos: Vec2<i32>) -> bool {
+        rpos.x >= self.offset.x
+            && rpos.y >= self.offset.y
+            && rpos.x < self.offset.x + self.size.x
+            && rpos.y < self.offset.y + self.size.y
+    }
+
+    fn is_wall(&self, rpos: Vec2<i32>) -> bool {
+        rpos.x == self.offset.x
+            || rpos.y == self.offset.y
+            || rpos.x == self.offset.x + self.size.x - 1
+            || rpos.y == self.offset.y + self.size.y - 1
+    }
+}
+
+pub struct Ruins {
+    origin: Vec2<i32>,
+    seed: u32,
+    ruins: Vec<Ruin>,
+}
+
+impl Ruins {
+    pub fn generate(wpos: Vec2<i32>, sim: Option<&WorldSim>, rng: &mut impl Rng) -> Self {
+        let ruins = (0..rng.gen_range(2, 6))
+            .map(|_| {
+                let size = Vec2::new(rng.gen_range(5, 13), rng.gen_range(5, 13));
+                let offset = Vec2::new(
+                    rng.gen_range(-SPREAD, SPREAD - size.x),
+                    rng.gen_range(-SPREAD, SPREAD - size.y),
+                );
+                let center = wpos + offset + size / 2;
+                Ruin {
+                    offset,
+                    size,
+                    height: rng.gen_range(3, 8),
+                    alt: sim
+                        .and_then(|sim| sim.get_alt_approx(center))
+                        .unwrap_or(0.0) as i32,
+                    has_chest: rng.gen_bool(0.3),
+                }
+            })
+            .collect();
+
+        Self {
+            origin: wpos,
+            seed: rng.gen(),
+            ruins,
+        }
+    }
+
+    pub fn get_origin(&self) -> Vec2<i32> { self.origin }
+
+    pub fn radius(&self) -> f32 { (SPREAD * 2) as f32 }
+
+    pub fn spawn_rules(&self, wpos: Vec2<i32>) -> SpawnRules {
+        let rpos = wpos - self.origin;
+        SpawnRules {
+            trees: !self.ruins.iter().any(|ruin| ruin.contains(rpos)),
+        }
+    }
+
+    pub fn apply_to<'a>(
+        &'a self,
+        index: IndexRef,
+        wpos2d: Vec2<i32>,
+        mut get_column: impl FnMut(Vec2<i32>) -> Option<&'a ColumnSample<'a>>,
+        vol: &mut (impl BaseVol<Vox = Block> + RectSizedVol + ReadVol + WriteVol),
+    ) {
+        let colors = &index.colors.site.ruins;
+        let stone = Block::new(BlockKind::Rock, colors.stone.into());
+        let moss = Block::new(BlockKind::WeakRock, colors.moss.into());
+
+        for y in 0..vol.size_xy().y as i32 {
+            for x in 0..vol.size_xy().x as i32 {
+                let offs = Vec2::new(x, y);
+                let wpos2d = wpos2d + offs;
+                let rpos = wpos2d - self.origin;
+
+                let ruin = if let Some(ruin) = self.ruins.iter().find(|ruin| ruin.contains(rpos)) {
+                    ruin
+                } else {
+                    continue;
+                };
+                if get_column(offs).is_none() {
+                    continue;
+                }
+
+                // Clear the inside of the building, even where the terrain has
+                // grown over it since
+                for z in ruin.alt..ruin.alt + ruin.height {
+                    let _ = vol.set(Vec3::new(offs.x, offs.y, z), Block::air(SpriteKind::Empty));
+                }
+                let _ = vol.set(Vec3::new(offs.x, offs.y, ruin.alt - 1), stone);
+
+                if ruin.is_wall(rpos) {
+                    // Walls crumbled down to a different height at every column,
+                    // and some fell down entirely
+                    let height = (RandomField::new(self.seed).get(wpos2d.into())
+                        % (ruin.height as u32 + 2)) as i32
+                        - 1;
+                    for z in ruin.alt - FOUNDATION_DEPTH..ruin.alt + height {
+                        let block = if z == ruin.alt + height - 1 && height > 2 {
+                            moss
+                        } else {
+                            stone
+                        };
+                        let _ = vol.set(Vec3::new(offs.x, offs.y, z), block);
+                    }
+                } else if ruin.has_chest && rpos == ruin.offset + ruin.size / 2 {
+                    let _ = vol.set(
+                        Vec3::new(offs.x, offs.y, ruin.alt),
+                        Block::air(SpriteKind::Chest),
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn apply_supplement<'a>(
+        &'a self,
+        // NOTE: Used only for dynamic elements like chests and entities!
+        _dynamic_rng: &mut impl Rng,
+        _wpos2d: Vec2<i32>,
+        _get_column: impl FnMut(Vec2<i32>) -> Option<&'a ColumnSample<'a>>,
+        _supplement: &mut ChunkSupplement,
+    ) {
+    }
+}
@@ -14,7 +14,7 @@ use crate::{
 };
 use common::{
     astar::Astar,
-    comp::{self, bird_medium, humanoid, object, quadruped_small, Item},
+    comp::{self, agent::Schedule, bird_medium, humanoid, object, quadruped_small, Item},
     generation::{ChunkSupplement, EntityInfo},
     path::Path,
     spiral::Spiral2d,
@@ -130,6 +130,8 @@ impl Structure {
         }
     }
 
+    pub fn is_house(&self) -> bool { matches!(self.kind, StructureKind::House(_)) }
+
     pub fn sample(&self, index: IndexRef, rpos: Vec3<i32>) -> Option<Block> {
         match &self.kind {
             StructureKind::House(house) => house.sample(index, rpos),
@@ -926,6 +928,23 @@ impl Settlement {
                         })
                         .do_if(is_dummy, |e| e.with_name("Training Dummy"))
                         .do_if(!is_dummy, |e| e.with_automatic_name());
+                    // Villagers work where they spawned and spend the night in one of the houses
+                    let home = self
+                        .structures
+                        .iter()
+                        .filter(|structure| structure.is_house())
+                        .choose(dynamic_rng)
+                        .map(|house| {
+                            let pos = self.origin + house.bounds_2d().center();
+                            let alt = house.bounds().min.z + 8;
+                            Vec3::new(pos.x, pos.y, alt).map(|e| e as f32)
+                        });
+                    let entity = match home {
+                        Some(home) if is_human => {
+                            entity.with_schedule(Schedule::new(home, entity_wpos))
+                        },
+                        _ => entity,
+                    };
 
                     supplement.add_entity(entity);
                 }